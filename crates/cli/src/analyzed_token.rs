@@ -1,32 +1,84 @@
-use std::collections::HashSet;
+use super::blame::LineBlame;
+use super::coverage::TokenCoverage;
+use super::messages;
+use super::snippets::Snippet;
+use std::collections::BTreeSet;
 use std::path::PathBuf;
-use token_analysis::{TokenUsage, UsageLikelihoodStatus};
+use token_analysis::{RenameHint, Rule, TokenUsage, UsageLikelihoodStatus};
 
 pub struct AnalyzedToken {
     pub token: String,
     pub first_path: PathBuf,
     pub likelihood_status: UsageLikelihoodStatus,
     pub likelihood_reason: String,
+    pub rule: Rule,
     pub files: Vec<PathBuf>,
-    pub defined_paths: HashSet<PathBuf>,
-    pub occurred_paths: HashSet<PathBuf>,
+    pub defined_paths: BTreeSet<PathBuf>,
+    pub occurred_paths: BTreeSet<PathBuf>,
+    pub last_change: Option<LineBlame>,
+    /// Runtime coverage of the definitions, when a report was passed with `--coverage`
+    pub coverage: Option<TokenCoverage>,
+    /// Source of each definition, by its path, when requested with `--snippets`
+    pub snippets: Vec<(PathBuf, Snippet)>,
+    /// A heavily used token named almost alike, which the token may have been renamed to
+    pub rename_hint: Option<RenameHint>,
+    /// Owners of the files defining the token, from CODEOWNERS
+    pub owners: BTreeSet<String>,
+    pub generic_languages: Vec<String>,
+    /// Scopes enclosing the token's definitions, such as classes or modules
+    pub scopes: BTreeSet<String>,
+    pub kinds: BTreeSet<String>,
 }
 
 impl From<&TokenUsage> for AnalyzedToken {
     fn from(usage: &TokenUsage) -> Self {
+        let definitions = &usage.result.token.definitions;
+
         AnalyzedToken {
             token: usage.result.token.token.to_string(),
             first_path: usage.result.token.first_path().to_path_buf(),
             likelihood_status: usage.usage_likelihood.status.clone(),
-            likelihood_reason: usage.usage_likelihood.reason.clone(),
+            likelihood_reason: messages::reason(
+                usage.usage_likelihood.rule,
+                &usage.usage_likelihood.reason,
+            ),
+            rule: usage.usage_likelihood.rule,
             files: usage
                 .result
                 .occurrences
                 .keys()
                 .map(|v| v.to_path_buf())
                 .collect(),
-            defined_paths: usage.result.defined_paths(),
-            occurred_paths: usage.result.occurred_paths(),
+            defined_paths: usage.result.defined_paths().into_iter().collect(),
+            occurred_paths: usage.result.occurred_paths().into_iter().collect(),
+            last_change: None,
+            coverage: None,
+            snippets: vec![],
+            rename_hint: None,
+            owners: BTreeSet::new(),
+            generic_languages: generic_languages(usage),
+            scopes: definitions
+                .iter()
+                .filter_map(|d| d.scope().map(String::from))
+                .collect(),
+            kinds: definitions
+                .iter()
+                .map(|d| format!("{:?}", d.kind))
+                .collect(),
         }
     }
 }
+
+/// Unrecognized languages of a token's definitions, which were analyzed with generic rules
+pub fn generic_languages(usage: &TokenUsage) -> Vec<String> {
+    let mut languages: Vec<String> = usage
+        .result
+        .token
+        .definitions
+        .iter()
+        .filter_map(|d| d.unrecognized_language())
+        .collect();
+    languages.sort();
+    languages.dedup();
+    languages
+}
@@ -0,0 +1,85 @@
+use super::context::Context;
+use codebase_files::writes::Writes;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use token_analysis::{Baseline, TokenUsage};
+
+// Written at the project's root (or a sub-project's) by --generate-baseline
+const FILE_NAME: &str = ".unused-baseline.json";
+
+pub struct LoadedBaseline {
+    pub baseline: Baseline,
+    pub warnings: Vec<String>,
+}
+
+/// Where the baseline for the project (or sub-project) being analyzed is kept
+pub fn path(context: &Context) -> PathBuf {
+    match &context.sub_project {
+        Some(project) => project.root.join(FILE_NAME),
+        None => PathBuf::from(FILE_NAME),
+    }
+}
+
+/// Load the findings recorded at `path`, if a baseline has been generated
+pub fn load(path: &Path) -> LoadedBaseline {
+    let loaded = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string()));
+
+    match loaded {
+        Ok(baseline) => LoadedBaseline {
+            baseline,
+            warnings: vec![],
+        },
+        Err(_) if !path.exists() => LoadedBaseline {
+            baseline: Baseline::default(),
+            warnings: vec![],
+        },
+        Err(e) => LoadedBaseline {
+            baseline: Baseline::default(),
+            warnings: vec![format!(
+                "Unable to load baseline {}, so all findings were reported: {}",
+                path.display(),
+                e
+            )],
+        },
+    }
+}
+
+/// Record `findings` at `path`, replacing any previous baseline
+pub fn write(path: &Path, findings: &[&TokenUsage], writes: Writes) -> io::Result<Baseline> {
+    let baseline = Baseline::new(findings.iter().copied());
+    let mut contents = serde_json::to_string_pretty(&baseline)?;
+    contents.push('\n');
+    writes.write(path, contents)?;
+
+    Ok(baseline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_missing_and_invalid_baselines_as_empty() {
+        let root = std::env::temp_dir().join("baseline_loads_missing_and_invalid");
+        fs::create_dir_all(&root).unwrap();
+        let valid = root.join("valid.json");
+        let invalid = root.join("invalid.json");
+        fs::write(&valid, r#"{"findings": {"legacy": ["app/person.rb"]}}"#).unwrap();
+        fs::write(&invalid, "[]").unwrap();
+
+        let loaded_valid = load(&valid);
+        let loaded_invalid = load(&invalid);
+        let loaded_missing = load(&root.join("missing.json"));
+        fs::remove_dir_all(&root).ok();
+
+        assert_eq!(loaded_valid.baseline.len(), 1);
+        assert!(loaded_valid.warnings.is_empty());
+        assert!(loaded_invalid.baseline.is_empty());
+        assert_eq!(loaded_invalid.warnings.len(), 1);
+        assert!(loaded_missing.baseline.is_empty());
+        assert!(loaded_missing.warnings.is_empty());
+    }
+}
@@ -0,0 +1,317 @@
+use codebase_files::DiskFileSystem;
+use read_ctags::CtagItem;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use token_analysis::TokenUsage;
+
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+/// The commit that last changed a definition's line
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LineBlame {
+    pub commit: String,
+    pub author: String,
+    pub date: String,
+    pub age_days: u64,
+}
+
+/// Blame for the definitions of reported findings, keyed by path and tag address
+#[derive(Default)]
+pub struct Blame {
+    lines: HashMap<(Arc<Path>, String), LineBlame>,
+}
+
+pub struct LoadedBlame {
+    pub blame: Blame,
+    pub warnings: Vec<String>,
+}
+
+impl Blame {
+    /// Run `git blame` once per defining file of `usages`
+    ///
+    /// Files git can't blame, such as untracked files, are skipped; a warning is only reported
+    /// when nothing could be blamed.
+    pub fn load(usages: &[&TokenUsage]) -> LoadedBlame {
+        let mut by_file: BTreeMap<&Path, Vec<(&CtagItem, usize)>> = BTreeMap::new();
+
+        for definition in usages
+            .iter()
+            .flat_map(|u| u.result.token.definitions.iter())
+        {
+            if let Some(line) = definition.line(&DiskFileSystem) {
+                by_file
+                    .entry(&*definition.file_path)
+                    .or_default()
+                    .push((definition, line));
+            }
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let mut lines = HashMap::new();
+        let mut errors = vec![];
+
+        for (path, definitions) in by_file {
+            match blame_lines(path, definitions.iter().map(|(_, line)| *line)) {
+                Ok(output) => {
+                    let blamed = parse(&output, now);
+                    for (definition, line) in definitions {
+                        if let Some(blame) = blamed.get(&line) {
+                            lines.insert(
+                                (definition.file_path.clone(), definition.address.clone()),
+                                blame.clone(),
+                            );
+                        }
+                    }
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+
+        let warnings = match errors.first() {
+            Some(e) if lines.is_empty() => vec![format!("Unable to read git blame: {}", e)],
+            _ => vec![],
+        };
+
+        LoadedBlame {
+            blame: Blame { lines },
+            warnings,
+        }
+    }
+
+    /// Blame for a single definition
+    pub fn get(&self, definition: &CtagItem) -> Option<&LineBlame> {
+        self.lines
+            .get(&(definition.file_path.clone(), definition.address.clone()))
+    }
+
+    /// The most recent change across a token's definitions
+    pub fn latest(&self, usage: &TokenUsage) -> Option<&LineBlame> {
+        usage
+            .result
+            .token
+            .ordered_definitions()
+            .into_iter()
+            .filter_map(|d| self.get(d))
+            .min_by_key(|b| b.age_days)
+    }
+}
+
+fn blame_lines<I>(path: &Path, lines: I) -> Result<String, String>
+where
+    I: Iterator<Item = usize>,
+{
+    let mut command = Command::new("git");
+    command.args(["blame", "--porcelain"]);
+    for line in lines {
+        command.arg("-L").arg(format!("{},{}", line, line));
+    }
+    let output = command
+        .arg("--")
+        .arg(path)
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).map_err(|e| e.to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+// Porcelain output lists "<commit> <original line> <final line> [<count>]" before each line,
+// followed by the commit's details the first time the commit appears; uncommitted lines are
+// skipped
+fn parse(output: &str, now: u64) -> HashMap<usize, LineBlame> {
+    let mut authors: HashMap<&str, (&str, u64)> = HashMap::new();
+    let mut blamed_lines: Vec<(usize, &str)> = vec![];
+    let mut current: Option<(&str, usize)> = None;
+    let mut author = "";
+
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            if let Some(entry) = current.take() {
+                blamed_lines.push((entry.1, entry.0));
+            }
+        } else if let Some(name) = line.strip_prefix("author ") {
+            author = name;
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            if let (Some((commit, _)), Ok(time)) = (current, time.parse()) {
+                authors.insert(commit, (author, time));
+            }
+        } else {
+            let mut parts = line.split(' ');
+            if let (Some(commit), Some(_), Some(Ok(final_line))) =
+                (parts.next(), parts.next(), parts.next().map(str::parse))
+            {
+                if commit.len() == 40 && commit.chars().all(|c| c.is_ascii_hexdigit()) {
+                    current = Some((commit, final_line));
+                }
+            }
+        }
+    }
+
+    blamed_lines
+        .into_iter()
+        .filter(|(_, commit)| commit.chars().any(|c| c != '0'))
+        .filter_map(|(line, commit)| {
+            authors.get(commit).map(|(author, time)| {
+                (
+                    line,
+                    LineBlame {
+                        commit: commit[..7].to_string(),
+                        author: author.to_string(),
+                        date: date(*time),
+                        age_days: now.saturating_sub(*time) / SECONDS_PER_DAY,
+                    },
+                )
+            })
+        })
+        .collect()
+}
+
+/// Civil date (UTC) for a Unix timestamp, such as `2024-03-09`
+pub fn date(timestamp: u64) -> String {
+    let (year, month, day) = civil_date(timestamp);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Year, month, and day (UTC) for a Unix timestamp
+pub fn civil_date(timestamp: u64) -> (i64, i64, i64) {
+    let days = (timestamp / SECONDS_PER_DAY) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use project_configuration::{ProjectConfiguration, TokenPolicy};
+    use read_ctags::{Language, TokenKind};
+    use std::collections::HashSet;
+    use token_search::{Token, TokenSearchResult};
+
+    fn definition(path: &str, address: &str) -> CtagItem {
+        CtagItem {
+            name: "full_name".to_string(),
+            file_path: Path::new(path).into(),
+            raw_path: path.into(),
+            address: address.to_string(),
+            language: Some(Language::Ruby),
+            tags: BTreeMap::new(),
+            kind: TokenKind::Method,
+        }
+    }
+
+    fn change(commit: &str, age_days: u64) -> LineBlame {
+        LineBlame {
+            commit: commit.to_string(),
+            author: String::from("Jane Doe"),
+            date: String::from("2020-01-01"),
+            age_days,
+        }
+    }
+
+    #[test]
+    fn parses_porcelain_blame() {
+        let output = "\
+3f1c2a9b8e7d6c5b4a39281706f5e4d3c2b1a098 2 2 1
+author Jane Doe
+author-mail <jane@example.com>
+author-time 1577836800
+author-tz +0000
+summary Add Person
+filename app/models/person.rb
+\t  def full_name
+3f1c2a9b8e7d6c5b4a39281706f5e4d3c2b1a098 9 12 1
+\t  def age
+0000000000000000000000000000000000000000 14 14 1
+author Not Committed Yet
+author-time 1600000000
+\t  def nickname
+";
+
+        let blamed = parse(output, 1577836800 + 10 * SECONDS_PER_DAY);
+
+        assert_eq!(
+            blamed.get(&2),
+            Some(&LineBlame {
+                commit: String::from("3f1c2a9"),
+                author: String::from("Jane Doe"),
+                date: String::from("2020-01-01"),
+                age_days: 10,
+            })
+        );
+        assert_eq!(blamed.get(&12).map(|b| b.age_days), Some(10));
+        assert_eq!(blamed.get(&14), None);
+    }
+
+    #[test]
+    fn reports_the_latest_change_across_definitions() {
+        let person = definition("app/models/person.rb", "2");
+        let admin = definition("app/models/admin.rb", "5");
+        let untracked = definition("app/models/guest.rb", "3");
+        let blame = Blame {
+            lines: HashMap::from([
+                (
+                    (person.file_path.clone(), person.address.clone()),
+                    change("3f1c2a9", 40),
+                ),
+                (
+                    (admin.file_path.clone(), admin.address.clone()),
+                    change("8e7d6c5", 3),
+                ),
+            ]),
+        };
+        let result = TokenSearchResult {
+            token: Token::new(
+                "full_name".to_string(),
+                [person.clone(), admin, untracked.clone()]
+                    .iter()
+                    .cloned()
+                    .collect(),
+            ),
+            occurrences: HashMap::new(),
+            mentions: HashMap::new(),
+            scopes: None,
+        };
+        let usage = TokenUsage::new(
+            &ProjectConfiguration::default(),
+            result,
+            &HashSet::new(),
+            &TokenPolicy::default(),
+            &[],
+        );
+
+        assert_eq!(blame.get(&person), Some(&change("3f1c2a9", 40)));
+        assert_eq!(blame.get(&untracked), None);
+        assert_eq!(blame.latest(&usage), Some(&change("8e7d6c5", 3)));
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(951_782_400), "2000-02-29");
+        assert_eq!(date(1_709_251_199), "2024-02-29");
+    }
+}
@@ -0,0 +1,199 @@
+use super::severity::Severity;
+use codebase_files::ExcludeGlobs;
+use project_configuration::FindingsBudget;
+use serde::Serialize;
+use token_analysis::TokenUsage;
+
+/// Findings tolerated in known-bad areas of a project, under `budgets` in the project settings
+///
+/// Each finding counts against the first budget (in the order written) whose glob matches the
+/// file defining it. Once budgets are configured, a run fails when any budget is exceeded or
+/// when findings fall outside every budget, so new directories are held to zero findings.
+/// Findings reported as `info` never count.
+#[derive(Clone, Debug, Default)]
+pub struct Budgets(Vec<Budget>);
+
+#[derive(Clone, Debug)]
+struct Budget {
+    glob: String,
+    matcher: ExcludeGlobs,
+    findings: Option<usize>,
+    errors: Option<usize>,
+    warnings: Option<usize>,
+}
+
+/// Findings counted against each budget in a single run
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BudgetReport<'a> {
+    pub budgets: Vec<BudgetSpend<'a>>,
+    /// Findings outside every budget, each exceeding the budget of zero new directories have
+    pub unbudgeted: usize,
+}
+
+/// Findings counted against a single budget
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BudgetSpend<'a> {
+    pub glob: &'a str,
+    /// Findings reported as errors
+    pub errors: usize,
+    /// Findings reported as warnings, or without a configured severity
+    pub warnings: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_findings: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_errors: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_warnings: Option<usize>,
+    pub exceeded: bool,
+}
+
+impl Budgets {
+    pub fn parse(settings: &[FindingsBudget]) -> Result<Self, String> {
+        settings
+            .iter()
+            .map(|budget| {
+                let key = format!("budgets.{}", budget.glob);
+                let matcher =
+                    ExcludeGlobs::new(&[&budget.glob]).map_err(|e| format!("{}: {}", key, e))?;
+                let mut parsed = Budget {
+                    glob: budget.glob.clone(),
+                    matcher,
+                    findings: budget.findings,
+                    errors: None,
+                    warnings: None,
+                };
+                for (severity, limit) in budget.severities.iter() {
+                    let key = format!("{}.{}", key, severity);
+                    match severity.parse().map_err(|e| format!("{}: {}", key, e))? {
+                        Severity::Error => parsed.errors = Some(*limit),
+                        Severity::Warning => parsed.warnings = Some(*limit),
+                        Severity::Info => {
+                            return Err(format!("{}: info findings never count", key))
+                        }
+                    }
+                }
+                Ok(parsed)
+            })
+            .collect::<Result<_, String>>()
+            .map(Budgets)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Does a budget cover the file defining `usage`?
+    pub fn covers(&self, usage: &TokenUsage) -> bool {
+        self.budget(usage).is_some()
+    }
+
+    /// Count `findings`, each with its configured severity, against the budgets
+    pub fn spend(&self, findings: &[(&TokenUsage, Option<Severity>)]) -> BudgetReport<'_> {
+        let mut counts = vec![(0, 0); self.0.len()];
+        let mut unbudgeted = 0;
+
+        for (usage, severity) in findings {
+            if *severity == Some(Severity::Info) {
+                continue;
+            }
+            match self.budget(usage) {
+                Some(index) if *severity == Some(Severity::Error) => counts[index].0 += 1,
+                Some(index) => counts[index].1 += 1,
+                None => unbudgeted += 1,
+            }
+        }
+
+        BudgetReport {
+            budgets: self
+                .0
+                .iter()
+                .zip(counts)
+                .map(|(budget, (errors, warnings))| BudgetSpend {
+                    glob: &budget.glob,
+                    errors,
+                    warnings,
+                    max_findings: budget.findings,
+                    max_errors: budget.errors,
+                    max_warnings: budget.warnings,
+                    exceeded: budget.findings.is_some_and(|max| errors + warnings > max)
+                        || budget.errors.is_some_and(|max| errors > max)
+                        || budget.warnings.is_some_and(|max| warnings > max),
+                })
+                .collect(),
+            unbudgeted,
+        }
+    }
+
+    fn budget(&self, usage: &TokenUsage) -> Option<usize> {
+        let path = usage.result.token.first_path();
+
+        self.0
+            .iter()
+            .position(|budget| budget.matcher.is_excluded(path))
+    }
+}
+
+impl BudgetReport<'_> {
+    /// Was a budget exceeded, or were there findings outside every budget?
+    pub fn exceeded(&self) -> bool {
+        self.unbudgeted > 0 || self.budgets.iter().any(|b| b.exceeded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::usage;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn counts_findings_against_budgets() {
+        let budgets = Budgets::parse(&[
+            FindingsBudget {
+                glob: String::from("app/legacy/**"),
+                findings: Some(2),
+                severities: BTreeMap::new(),
+            },
+            FindingsBudget {
+                glob: String::from("lib/**"),
+                findings: None,
+                severities: BTreeMap::from([
+                    (String::from("error"), 0),
+                    (String::from("warning"), 5),
+                ]),
+            },
+        ])
+        .unwrap();
+        let legacy = [
+            usage("total", "app/legacy/order.rb"),
+            usage("tax", "app/legacy/order.rb"),
+        ];
+        let library = usage("render", "lib/report.rb");
+        let new = usage("checkout", "app/checkout.rb");
+
+        let within = budgets.spend(&[
+            (&legacy[0], None),
+            (&legacy[1], Some(Severity::Warning)),
+            (&library, Some(Severity::Warning)),
+            (&new, Some(Severity::Info)),
+        ]);
+        assert!(!within.exceeded());
+        assert_eq!(within.budgets[0].warnings, 2);
+        assert_eq!(within.budgets[1].warnings, 1);
+        assert!(budgets.covers(&library));
+        assert!(!budgets.covers(&new));
+
+        let over = budgets.spend(&[(&library, Some(Severity::Error)), (&new, None)]);
+        assert!(over.exceeded());
+        assert!(!over.budgets[0].exceeded);
+        assert!(over.budgets[1].exceeded);
+        assert_eq!(over.unbudgeted, 1);
+
+        assert!(Budgets::parse(&[FindingsBudget {
+            glob: String::from("lib/**"),
+            findings: None,
+            severities: BTreeMap::from([(String::from("info"), 0)]),
+        }])
+        .is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use super::cli_configuration::CliConfiguration;
+use super::flags::CacheCommand;
+use super::formatters::internal::configuration_warnings;
+use super::ignore_sources;
+use super::kinds;
+use super::remote_tags;
+use super::report_files;
+use codebase_files::writes::Writes;
+use colored::*;
+use std::path::PathBuf;
+use token_search::{OccurrenceCache, ScanCache};
+
+pub fn render(command: CacheCommand, writes: Writes) {
+    let locations = vec![
+        ("Occurrence counts", OccurrenceCache::default_path()),
+        ("Per-file scans", ScanCache::default_path()),
+        ("Remote ignore lists", ignore_sources::cache_root()),
+        ("Remote tags", remote_tags::cache_root()),
+        ("Imported kinds", kinds::default_path()),
+        ("Report file hashes", report_files::default_path()),
+    ];
+
+    match command {
+        CacheCommand::Path => {
+            for (name, path) in locations {
+                println!("   {}: {}", name, display(&path).cyan());
+            }
+        }
+        CacheCommand::Clear => {
+            for (name, path) in locations {
+                if let Some(path) = path.filter(|p| p.exists()) {
+                    match writes.remove(&path) {
+                        Ok(_) => println!("   Removed {}: {}", name, path.display()),
+                        Err(e) => eprintln!("Unable to remove {}: {}", path.display(), e),
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn index(cli_config: &CliConfiguration) {
+    match cli_config.occurrence_cache() {
+        Some(path) => println!(
+            "Indexed {} tokens across {} files into {}",
+            cli_config.analyzed_tokens_count().to_string().yellow(),
+            cli_config.searched_files_count().to_string().yellow(),
+            path.to_string_lossy().cyan()
+        ),
+        None => eprintln!("Unable to index occurrences outside of a git repository"),
+    }
+
+    configuration_warnings(cli_config);
+}
+
+fn display(path: &Option<PathBuf>) -> String {
+    path.as_ref()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| String::from("unavailable"))
+}
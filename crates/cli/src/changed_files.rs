@@ -0,0 +1,208 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::process::Command;
+use token_search::Token;
+
+pub struct LoadedChangedFiles {
+    /// Files changed since the base revision, or `None` when every file should be analyzed
+    pub paths: Option<HashSet<PathBuf>>,
+    /// Staged contents of staged files that also have unstaged changes
+    pub staged_contents: HashMap<PathBuf, String>,
+    pub warnings: Vec<String>,
+}
+
+/// Load the files changed since the merge base of `base` and `HEAD`, including uncommitted and
+/// untracked files, or else the files staged in git when `staged` is set
+///
+/// When git can't determine the changes, every file is analyzed and a warning is reported.
+pub fn load(base: Option<&str>, staged: bool) -> LoadedChangedFiles {
+    let (loaded, description) = match base {
+        Some(base) => (
+            changed_since(base).map(|paths| (paths, HashMap::new())),
+            format!("files changed since {}", base),
+        ),
+        None if staged => (staged_files(), String::from("staged files")),
+        None => {
+            return LoadedChangedFiles {
+                paths: None,
+                staged_contents: HashMap::new(),
+                warnings: vec![],
+            }
+        }
+    };
+
+    match loaded {
+        Ok((paths, staged_contents)) => LoadedChangedFiles {
+            paths: Some(paths),
+            staged_contents,
+            warnings: vec![],
+        },
+        Err(e) => LoadedChangedFiles {
+            paths: None,
+            staged_contents: HashMap::new(),
+            warnings: vec![format!(
+                "Unable to find {}, so all files were analyzed: {}",
+                description, e
+            )],
+        },
+    }
+}
+
+/// Tokens with at least one definition in `paths`
+///
+/// Definitions in files with staged contents only count when the token appears in those
+/// contents, so definitions that are only in the working tree aren't reported.
+pub fn defined_in(
+    tokens: Vec<Token>,
+    paths: &HashSet<PathBuf>,
+    staged_contents: &HashMap<PathBuf, String>,
+) -> Vec<Token> {
+    tokens
+        .into_iter()
+        .filter(|token| {
+            token.defined_paths.iter().any(|p| {
+                paths.contains(&**p)
+                    && staged_contents
+                        .get(&**p)
+                        .is_none_or(|contents| contents.contains(&token.token))
+            })
+        })
+        .collect()
+}
+
+fn changed_since(base: &str) -> Result<HashSet<PathBuf>, String> {
+    let merge_base = git(&["merge-base", base, "HEAD"])?;
+    let mut paths = null_separated(&git(&[
+        "-c",
+        "core.quotePath=false",
+        "diff",
+        "--name-only",
+        "--relative",
+        "--no-renames",
+        "-z",
+        merge_base.trim(),
+        "--",
+    ])?);
+    paths.extend(null_separated(&git(&[
+        "ls-files",
+        "--others",
+        "--exclude-standard",
+        "-z",
+    ])?));
+
+    Ok(paths)
+}
+
+// Staged files (other than deletions), along with the staged contents of any that also have
+// unstaged changes
+type StagedFiles = (HashSet<PathBuf>, HashMap<PathBuf, String>);
+
+fn staged_files() -> Result<StagedFiles, String> {
+    let diff = |extra: &[&str]| {
+        let mut args = vec![
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "--name-only",
+            "--relative",
+            "--no-renames",
+            "-z",
+        ];
+        args.extend(extra);
+        git(&args).map(|output| null_separated(&output))
+    };
+    let staged = diff(&["--cached", "--diff-filter=d"])?;
+    let unstaged = diff(&[])?;
+
+    let contents = staged
+        .intersection(&unstaged)
+        .map(|path| {
+            git(&["show", &format!(":./{}", path.display())])
+                .map(|contents| (path.to_path_buf(), contents))
+        })
+        .collect::<Result<_, String>>()?;
+
+    Ok((staged, contents))
+}
+
+fn null_separated(output: &str) -> HashSet<PathBuf> {
+    output
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+fn git(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).map_err(|e| e.to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use read_ctags::{CtagItem, TokenKind};
+    use std::collections::BTreeMap;
+
+    fn token(name: &str, paths: &[&str]) -> Token {
+        let definitions = paths
+            .iter()
+            .map(|path| CtagItem {
+                name: name.to_string(),
+                file_path: PathBuf::from(path).into(),
+                raw_path: (*path).into(),
+                address: String::from("1"),
+                language: None,
+                tags: BTreeMap::new(),
+                kind: TokenKind::Undefined,
+            })
+            .collect();
+
+        Token::new(name.to_string(), definitions)
+    }
+
+    #[test]
+    fn keeps_tokens_defined_in_changed_files() {
+        let changed = null_separated("app/models/person.rb\0app/my models/order.rb\0");
+        let tokens = vec![
+            token("full_name", &["app/models/person.rb"]),
+            token("total", &["app/my models/order.rb", "lib/total.rb"]),
+            token("Report", &["app/reports/report.rb"]),
+        ];
+
+        let kept: Vec<String> = defined_in(tokens, &changed, &HashMap::new())
+            .into_iter()
+            .map(|t| t.token)
+            .collect();
+
+        assert_eq!(kept, vec!["full_name", "total"]);
+    }
+
+    #[test]
+    fn ignores_definitions_missing_from_staged_contents() {
+        let staged = null_separated("app/models/person.rb\0");
+        let contents = HashMap::from([(
+            PathBuf::from("app/models/person.rb"),
+            String::from("class Person\n  def full_name\n  end\nend\n"),
+        )]);
+        let tokens = vec![
+            token("full_name", &["app/models/person.rb"]),
+            token("age", &["app/models/person.rb"]),
+        ];
+
+        let kept: Vec<String> = defined_in(tokens, &staged, &contents)
+            .into_iter()
+            .map(|t| t.token)
+            .collect();
+
+        assert_eq!(kept, vec!["full_name"]);
+    }
+}
@@ -1,48 +1,474 @@
 use super::analyzed_token::AnalyzedToken;
+use super::baseline;
+use super::blame::Blame;
+use super::budgets::{BudgetReport, Budgets};
+use super::changed_files;
+use super::context::Context;
+use super::coverage::Coverage;
+use super::crash_report;
+use super::dependency_tags;
+use super::deprecations;
+use super::exit_status::ExitStatus;
+use super::flags::FormatTarget;
+use super::flags::{AnalysisFlags, FileSize, GroupBy};
 use super::formatters;
+use super::ignore_sources;
+use super::metrics;
+use super::pagination::Page;
 use super::project_configurations_loader::load_and_parse_config;
-use super::{Flags, Format};
-use project_configuration::{AssertionConflict, ProjectConfiguration};
-use std::collections::{HashMap, HashSet};
+use super::report_files;
+use super::sarif_suppressions;
+use super::severity::{Category, FindingSeverities, Severities, Severity};
+use super::snippets::Snippets;
+use super::sources::Sources;
+use super::terminal;
+use super::timings::Timings;
+use super::Format;
+use codebase_files::writes::Writes;
+use codebase_files::CodeOwners;
+use colored::*;
+use project_configuration::{
+    AssertionConflict, FrameworkProfiles, IgnoreList, PackageExports, ProjectConfiguration,
+    RailsRoutes,
+};
+use read_ctags::{CtagItem, GlobalTags, KindFilter, STDIN_PATH};
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use std::io::{self, Write};
 use std::iter::FromIterator;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use token_analysis::{
-    AnalysisFilter, SortOrder, TokenUsage, TokenUsageResults, UsageLikelihoodStatus,
+    AnalysisFilter, Baseline, DeprecatedUsage, Estimate, OrderField, RenameHint, RenameHints,
+    Sample, SortOrder, TokenUsage, TokenUsageResults, UsageLikelihoodStatus,
+};
+use token_search::{
+    InlineSuppression, InlineSuppressions, LanguageRestriction, OccurrenceCache, Occurrences,
+    ScanCache, Token, TokenSearchConfig, TokenSearchResults,
 };
-use token_search::{LanguageRestriction, Token, TokenSearchConfig, TokenSearchResults};
+
+pub enum TagsSource {
+    TagsFile(PathBuf),
+    Gtags(PathBuf),
+    /// Generated by running ctags, with --regenerate-tags
+    Generated,
+    RegexFallback,
+}
 
 pub struct CliConfiguration {
-    flags: Flags,
+    flags: AnalysisFlags,
+    format: Format,
+    outputs: Vec<FormatTarget>,
+    force: bool,
+    output_failed: Cell<bool>,
+    tags_source: TagsSource,
     token_search_config: TokenSearchConfig,
     analysis_filter: AnalysisFilter,
+    baseline_path: PathBuf,
     project_configuration: ProjectConfiguration,
+    framework_profiles: Vec<String>,
+    loading_warnings: Vec<String>,
+    sampling: Option<Sampling>,
+    deprecations: Option<Vec<DeprecatedUsage>>,
     outcome: TokenUsageResults,
+    blame: Blame,
+    coverage: Coverage,
+    snippets: Snippets,
+    sources: Sources,
+    rename_hints: RenameHints,
+    code_owners: CodeOwners,
+    severities: Severities,
+    budgets: Budgets,
+    scan_duration: Duration,
+    timings: Timings,
+}
+
+struct Sampling {
+    sample: Sample,
+    population: usize,
+    sampled: usize,
 }
 
 impl CliConfiguration {
-    pub fn new(flags: Flags, tokens: Vec<Token>) -> Self {
-        let token_search_config = build_token_search_config(&flags, tokens);
-        let analysis_filter = build_analysis_filter(&flags);
-        let results = TokenSearchResults::generate_with_config(&token_search_config);
-        let project_configuration = load_and_parse_config()
+    pub fn new(
+        context: &Context,
+        flags: AnalysisFlags,
+        tokens: Vec<Token>,
+        tags_source: TagsSource,
+        timings: Timings,
+    ) -> Self {
+        let started = Instant::now();
+        let mut flags = context.project.apply(flags);
+        flags.no_progress |= !context.displays_progress();
+        let deprecated = &context.project.settings.deprecated;
+        let mut deprecation_files: Vec<PathBuf> =
+            deprecated.from.iter().map(PathBuf::from).collect();
+        deprecation_files.extend(flags.deprecated.iter().cloned());
+        let loaded_deprecations = deprecations::load(&deprecated.tokens, &deprecation_files);
+        let deprecated_tokens = deprecated_tokens(&loaded_deprecations.tokens, &tokens);
+        let roots = &context.project.settings.roots;
+        let excludes = context.project.exclude_globs(&flags.exclude);
+        let definition_globs = context
+            .project
+            .definition_globs(&flags.include_definitions, &flags.exclude_definitions);
+        let usage_globs = context
+            .project
+            .usage_globs(&flags.include_usages, &flags.exclude_usages);
+        let kinds = KindFilter::new(flags.only_kinds.clone(), flags.ignore_kinds.clone());
+        let eligible = |definition: &CtagItem| {
+            let path = &definition.file_path;
+            roots.is_definition_root(path)
+                && definition_globs.includes(path)
+                && !excludes.is_excluded(path)
+                && kinds.includes(definition)
+        };
+        let tokens = eligible_definitions(tokens, eligible);
+        let changed = changed_files::load(flags.diff_base.as_deref(), flags.staged);
+        let tokens = match &changed.paths {
+            Some(paths) => changed_files::defined_in(tokens, paths, &changed.staged_contents),
+            None => tokens,
+        };
+        let (tokens, sampling) = sample_tokens(&flags, tokens);
+        let mut token_search_config = build_token_search_config(&flags, tokens, context);
+        token_search_config.content_overrides = changed.staged_contents;
+        token_search_config.writes = context.writes;
+        let (references, reference_warnings) = gtags_references(&flags, &tags_source);
+        token_search_config.references = references;
+        if flags.timings {
+            token_search_config.scan_timings = Some(timings.scan());
+        }
+        token_search_config.files.retain(|path| {
+            let usage_root = roots.is_usage_root(path) && usage_globs.includes(path);
+            match (usage_root, excludes.is_excluded(path)) {
+                (false, _) => {
+                    tracing::debug!("Skipping file {}: not a usage root", path.display());
+                    false
+                }
+                (_, true) => {
+                    tracing::debug!(
+                        "Skipping file {}: matches ignore_paths or --exclude",
+                        path.display()
+                    );
+                    false
+                }
+                _ => true,
+            }
+        });
+        let translation_keys = if flags.translation_keys {
+            let keys = translation_keys(&token_search_config, eligible);
+            match &changed.paths {
+                Some(paths) => {
+                    changed_files::defined_in(keys, paths, &token_search_config.content_overrides)
+                }
+                None => keys,
+            }
+        } else {
+            vec![]
+        };
+        token_search_config
+            .tokens
+            .extend(translation_keys.iter().cloned());
+        let mut analysis_filter = build_analysis_filter(&flags);
+        analysis_filter.add_ignored(context.project.settings.ignore.assertions());
+        crash_report::set_phase("searching for tokens");
+        let mut results = timings.time("scan", || {
+            TokenSearchResults::generate_with_config(&token_search_config)
+        });
+        results.credit_definitions(&translation_keys);
+        analysis_filter
+            .set_inline_suppressions(InlineSuppressions::scan(&token_search_config, &results));
+        crash_report::set_phase("loading configuration");
+        let mut project_configuration = load_and_parse_config(context.config_path.as_deref())
             .best_match(&results)
-            .unwrap_or(ProjectConfiguration::default());
-        let outcome =
-            TokenUsageResults::calculate(&token_search_config, results, &project_configuration);
+            .unwrap_or_default();
+        let framework_profiles = if flags.no_framework_profiles {
+            vec![]
+        } else {
+            apply_framework_profiles(&mut project_configuration)
+        };
+        project_configuration.doc_references = context.doc_references;
+        project_configuration.kind_weights = context.project.kind_weights.clone();
+        let mut routes_warnings = vec![];
+        if !flags.no_framework_profiles {
+            match RailsRoutes::load(Path::new("."), flags.rails_routes.as_deref()) {
+                Ok(Some(routes)) => project_configuration.routes = routes,
+                Ok(None) => {}
+                Err(e) => routes_warnings.push(format!("Unable to read Rails routes: {}", e)),
+            }
+        }
+        if !flags.no_package_exports {
+            match PackageExports::load(Path::new(".")) {
+                Ok(Some(exports)) => project_configuration.exports = exports,
+                Ok(None) => {}
+                Err(e) => routes_warnings.push(format!("Unable to read package exports: {}", e)),
+            }
+        }
+        let loaded_ignores = ignore_sources::load(
+            &project_configuration.ignore_from,
+            context.project.cache_ttl,
+            context.writes,
+        );
+        analysis_filter.add_ignored(loaded_ignores.ignore_list.assertions());
+        let suppressions = sarif_suppressions::load(&flags.sarif_suppressions);
+        analysis_filter.add_ignored(suppressions.ignore_list.assertions());
+        let baseline_path = baseline::path(context);
+        let loaded_baseline = if flags.generate_baseline || flags.no_baseline {
+            baseline::LoadedBaseline {
+                baseline: Baseline::default(),
+                warnings: vec![],
+            }
+        } else {
+            baseline::load(&baseline_path)
+        };
+        if !loaded_baseline.baseline.is_empty() {
+            tracing::info!(
+                "Loaded {} baseline findings from {}",
+                loaded_baseline.baseline.len(),
+                baseline_path.display()
+            );
+        }
+        analysis_filter.set_baseline(loaded_baseline.baseline);
+        let dependencies = dependency_tags::load(&flags.dependency_tags);
+        let deprecations = if deprecated_tokens.is_empty() {
+            None
+        } else {
+            crash_report::set_phase("searching for deprecated tokens");
+            Some(DeprecatedUsage::calculate(
+                &loaded_deprecations.tokens,
+                &TokenSearchResults::generate_with_config(&TokenSearchConfig {
+                    filter_tokens: |_| true,
+                    tokens: deprecated_tokens,
+                    files: token_search_config.files.clone(),
+                    display_progress: false,
+                    language_restriction: LanguageRestriction::NoRestriction,
+                    language_mappings: token_search_config.language_mappings.clone(),
+                    occurrence_cache: None,
+                    scan_cache: None,
+                    scope_by_imports: false,
+                    link_aliases: false,
+                    attribute_scopes: false,
+                    content_overrides: token_search_config.content_overrides.clone(),
+                    references: None,
+                    max_file_size: token_search_config.max_file_size,
+                    memory_limit: token_search_config.memory_limit,
+                    syntax_aware: token_search_config.syntax_aware,
+                    scan_timings: None,
+                    cancellation: token_search_config.cancellation.clone(),
+                    file_system: token_search_config.file_system.clone(),
+                    writes: token_search_config.writes,
+                }),
+            ))
+        };
+        crash_report::set_phase("analyzing usage");
+        let outcome = timings.time("match", || {
+            TokenUsageResults::calculate(
+                &token_search_config,
+                results,
+                &project_configuration,
+                &dependencies.tokens,
+                &context.project.settings.token_policy,
+                &context.analyzers,
+            )
+        });
+        let scan_duration = started.elapsed();
+        let loaded_blame = if flags.blame {
+            crash_report::set_phase("reading git blame");
+            Blame::load(&outcome.filter(&analysis_filter))
+        } else {
+            Blame::load(&[])
+        };
+        let loaded_coverage = Coverage::load(&flags.coverage);
+        let snippets = if flags.snippets || flags.context.is_some() {
+            Snippets::load(
+                &outcome.filter(&analysis_filter),
+                flags.context.unwrap_or(0),
+            )
+        } else {
+            Snippets::default()
+        };
+        let sources = Sources::new(token_search_config.file_system.clone());
+        let rename_hints = RenameHints::find(outcome.value(), &outcome.filter(&analysis_filter));
+        let mut loading_warnings = changed.warnings;
+        loading_warnings.extend(loaded_ignores.warnings);
+        loading_warnings.extend(dependencies.warnings);
+        loading_warnings.extend(loaded_deprecations.warnings);
+        loading_warnings.extend(suppressions.warnings);
+        loading_warnings.extend(loaded_baseline.warnings);
+        loading_warnings.extend(loaded_blame.warnings);
+        loading_warnings.extend(loaded_coverage.warnings);
+        loading_warnings.extend(reference_warnings);
+        loading_warnings.extend(routes_warnings);
+        let code_owners = match CodeOwners::find() {
+            Ok(Some((path, code_owners))) => {
+                tracing::info!("Read code owners from {}", path.display());
+                code_owners
+            }
+            Ok(None) => CodeOwners::default(),
+            Err(e) => {
+                loading_warnings.push(format!("Unable to read code owners: {}", e));
+                CodeOwners::default()
+            }
+        };
 
         Self {
             flags,
+            format: context.format,
+            outputs: context.outputs.clone(),
+            force: context.force,
+            output_failed: Cell::new(false),
+            tags_source,
             token_search_config,
             analysis_filter,
+            baseline_path,
             project_configuration,
+            framework_profiles,
+            loading_warnings,
+            sampling,
+            deprecations,
             outcome,
+            blame: loaded_blame.blame,
+            coverage: loaded_coverage.coverage,
+            snippets,
+            sources,
+            rename_hints,
+            code_owners,
+            severities: context.project.severities.clone(),
+            budgets: context.project.budgets.clone(),
+            scan_duration,
+            timings,
         }
     }
 
+    /// Render each requested format from the same results, to STDOUT or its file
     pub fn render(&self) {
-        match self.flags.format {
-            Format::Json => formatters::json::format(self),
-            Format::Standard => formatters::standard::format(self),
-            Format::Compact => formatters::compact::format(self),
+        crash_report::set_phase("rendering output");
+        let started = Instant::now();
+
+        for target in self.outputs.iter() {
+            let rendered = match &target.path {
+                None => match self.render_format(target.format, &mut io::stdout().lock()) {
+                    Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+                        Err(format!("Unable to write output: {}", e))
+                    }
+                    _ => Ok(()),
+                },
+                Some(path) => {
+                    // Files never include color codes, regardless of the terminal
+                    let colorize = control::SHOULD_COLORIZE.should_colorize();
+                    control::set_override(false);
+                    let mut contents = vec![];
+                    let rendered = self.write_format(target.format, &mut contents, None);
+                    control::set_override(colorize);
+                    rendered
+                        .map_err(|e| format!("Unable to write {}: {}", path.display(), e))
+                        .and_then(|_| {
+                            report_files::write(path, &contents, self.writes(), self.force)
+                        })
+                }
+            };
+
+            if let Err(e) = rendered {
+                eprintln!("{}", e.red());
+                self.output_failed.set(true);
+            }
+        }
+
+        if let Some(path) = &self.flags.metrics_file {
+            if let Err(e) = metrics::write(self, path) {
+                eprintln!("{}", e.red());
+                self.output_failed.set(true);
+            }
+        }
+
+        formatters::internal::configuration_warnings(self);
+
+        self.timings.record("report", started.elapsed());
+        if self.flags.timings {
+            match serde_json::to_string_pretty(&self.timings.report()) {
+                Ok(report) => eprintln!("{}", report),
+                Err(e) => eprintln!("{}", format!("Unable to report timings: {}", e).red()),
+            }
+        }
+    }
+
+    /// Record every current finding in the baseline, so later runs only report new findings
+    pub fn write_baseline(&self) {
+        match baseline::write(&self.baseline_path, &self.for_json(), self.writes()) {
+            Ok(baseline) => println!(
+                "Recorded {} findings in {}",
+                baseline.len().to_string().yellow(),
+                self.baseline_path.to_string_lossy().cyan()
+            ),
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "Unable to write baseline {}: {}",
+                        self.baseline_path.display(),
+                        e
+                    )
+                    .red()
+                );
+                self.output_failed.set(true);
+            }
+        }
+
+        formatters::internal::configuration_warnings(self);
+    }
+
+    /// Render a format to STDOUT, fitting it to the terminal's width
+    pub fn render_format(&self, format: Format, out: &mut dyn Write) -> io::Result<()> {
+        self.write_format(format, out, terminal::width())
+    }
+
+    fn write_format(
+        &self,
+        format: Format,
+        out: &mut dyn Write,
+        width: Option<usize>,
+    ) -> io::Result<()> {
+        match format {
+            Format::Json => formatters::json::format(self, out),
+            Format::Standard => formatters::standard::format(self, out, width),
+            Format::Compact => formatters::compact::format(self, out),
+            Format::Sarif => formatters::sarif::format(self, out),
+            Format::Csv => formatters::delimited::csv(self, out),
+            Format::Tsv => formatters::delimited::tsv(self, out),
+            Format::Html => formatters::html::format(self, out),
+            Format::Junit => formatters::junit::format(self, out),
+            Format::Checkstyle => formatters::checkstyle::format(self, out),
+            Format::Quickfix => formatters::quickfix::format(self, out),
+            Format::Github => formatters::github::format(self, out),
+            Format::Codeclimate => formatters::codeclimate::format(self, out),
+            Format::Porcelain => formatters::porcelain::format(self, out),
+        }
+    }
+
+    /// Findings configured as errors always fail the run (unless a budget tolerates them), and
+    /// those configured as info never do
+    pub fn exit_status(&self) -> ExitStatus {
+        let severities = self.severities();
+        let severity: Vec<(&TokenUsage, Option<Severity>)> = self
+            .for_json()
+            .into_iter()
+            .map(|u| (u, severities.of(u)))
+            .collect();
+        let errors = severity
+            .iter()
+            .any(|(u, s)| *s == Some(Severity::Error) && !self.budgets.covers(u));
+        let findings = severity
+            .iter()
+            .filter(|(_, s)| *s != Some(Severity::Info))
+            .count();
+        let over_budget = !self.budgets.is_empty() && self.budgets.spend(&severity).exceeded();
+
+        if self.output_failed.get() {
+            ExitStatus::Error
+        } else if errors || over_budget {
+            ExitStatus::Findings
+        } else {
+            ExitStatus::for_findings(findings, self.flags.fail_on_unused, self.flags.max_unused)
         }
     }
 
@@ -50,6 +476,10 @@ impl CliConfiguration {
         !self.flags.no_summary
     }
 
+    pub fn group_by(&self) -> GroupBy {
+        self.flags.group_by.unwrap_or_default()
+    }
+
     pub fn sort_order(&self) -> &SortOrder {
         &self.analysis_filter.sort_order
     }
@@ -67,7 +497,6 @@ impl CliConfiguration {
             .filter(&self.analysis_filter)
             .iter()
             .map(|t| t.result.token.token.len())
-            .into_iter()
             .max()
             .unwrap_or(0)
     }
@@ -77,11 +506,37 @@ impl CliConfiguration {
             .filter(&self.analysis_filter)
             .iter()
             .map(|t| t.result.token.first_path().to_string_lossy().len())
-            .into_iter()
             .max()
             .unwrap_or(0)
     }
 
+    pub fn tags_source(&self) -> String {
+        match &self.tags_source {
+            TagsSource::TagsFile(path) if path == Path::new(STDIN_PATH) => "STDIN".to_string(),
+            TagsSource::TagsFile(path) => path.to_string_lossy().to_string(),
+            TagsSource::Gtags(path) => format!("{} (GNU GLOBAL)", path.to_string_lossy()),
+            TagsSource::Generated => "generated with ctags".to_string(),
+            TagsSource::RegexFallback if cfg!(feature = "tree-sitter") => {
+                "tree-sitter fallback (approximate; regex for other languages)".to_string()
+            }
+            TagsSource::RegexFallback => "regex fallback (approximate)".to_string(),
+        }
+    }
+
+    pub fn tags_path(&self) -> Option<&PathBuf> {
+        match &self.tags_source {
+            TagsSource::TagsFile(path) | TagsSource::Gtags(path) => Some(path),
+            TagsSource::Generated | TagsSource::RegexFallback => None,
+        }
+    }
+
+    pub fn using_fallback_tags(&self) -> bool {
+        match self.tags_source {
+            TagsSource::RegexFallback => true,
+            TagsSource::TagsFile(_) | TagsSource::Gtags(_) | TagsSource::Generated => false,
+        }
+    }
+
     pub fn language_restriction(&self) -> String {
         self.token_search_config.language_restriction.to_string()
     }
@@ -90,25 +545,209 @@ impl CliConfiguration {
         self.outcome.filter(&self.analysis_filter)
     }
 
+    /// Findings to report, keeping only the page selected by --top-files, --offset, and --limit
+    pub fn findings(&self) -> Vec<&TokenUsage> {
+        self.page().apply(self.for_json())
+    }
+
+    pub fn page(&self) -> Page {
+        Page::new(&self.flags)
+    }
+
     pub fn analyses(&self) -> Vec<AnalyzedToken> {
-        self.outcome
-            .filter(&self.analysis_filter)
+        self.findings()
+            .into_iter()
+            .map(|t| AnalyzedToken {
+                last_change: self.blame.latest(t).cloned(),
+                coverage: self.coverage.for_usage(t),
+                snippets: self.snippets.for_usage(t),
+                rename_hint: self.rename_hint(t).cloned(),
+                owners: self.owners(t).into_iter().map(String::from).collect(),
+                ..t.into()
+            })
+            .collect()
+    }
+
+    /// A heavily used token named almost like `usage`, which it may have been renamed to
+    pub fn rename_hint(&self, usage: &TokenUsage) -> Option<&RenameHint> {
+        self.rename_hints.get(&usage.result.token.token)
+    }
+
+    /// Severities of the findings, by the category each falls into
+    pub fn severities(&self) -> FindingSeverities<'_> {
+        self.severities.resolve(&self.included(), &self.for_json())
+    }
+
+    /// Findings counted against each budget under `budgets`, when any are configured
+    pub fn budgets(&self) -> Option<BudgetReport<'_>> {
+        if self.budgets.is_empty() {
+            return None;
+        }
+        let severities = self.severities();
+        let findings: Vec<(&TokenUsage, Option<Severity>)> = self
+            .for_json()
             .into_iter()
-            .map(|t| t.into())
+            .map(|u| (u, severities.of(u)))
+            .collect();
+
+        Some(self.budgets.spend(&findings))
+    }
+
+    /// Number of findings in each category, before paging
+    pub fn categories(&self) -> BTreeMap<Category, usize> {
+        let (analyzed, findings) = (self.included(), self.for_json());
+        let categorized = self.severities.categorize(&analyzed, &findings);
+        let mut counts = BTreeMap::new();
+        for usage in findings.iter() {
+            *counts.entry(categorized.category(usage)).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Owners of the files defining the token, from the project's CODEOWNERS file
+    pub fn owners(&self, usage: &TokenUsage) -> BTreeSet<&str> {
+        usage
+            .result
+            .token
+            .defined_paths
+            .iter()
+            .flat_map(|path| self.code_owners.owners(path))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Was a CODEOWNERS file read?
+    pub fn has_code_owners(&self) -> bool {
+        !self.code_owners.is_empty()
+    }
+
+    pub fn blame(&self) -> &Blame {
+        &self.blame
+    }
+
+    pub fn coverage(&self) -> &Coverage {
+        &self.coverage
+    }
+
+    pub fn snippets(&self) -> &Snippets {
+        &self.snippets
+    }
+
+    /// Contents of defining files, read once each for locating definitions
+    pub fn sources(&self) -> &Sources {
+        &self.sources
+    }
+
+    pub fn sample_estimate(&self) -> Option<(Sample, Estimate)> {
+        self.sampling.as_ref().map(|s| {
+            let findings = self.outcome.filter(&self.analysis_filter).len();
+            (
+                s.sample,
+                Estimate::extrapolate(s.population, s.sampled, findings),
+            )
+        })
+    }
+
+    /// Tokens that would be reported but for an `unused:ignore` comment, by path and line
+    pub fn inline_suppressions(&self) -> Vec<&InlineSuppression> {
+        let mut suppressions: Vec<_> = self
+            .outcome
+            .value()
+            .iter()
+            .filter_map(|u| self.analysis_filter.suppression(u))
+            .collect();
+        suppressions.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+        suppressions
+    }
+
+    pub fn deprecations(&self) -> Option<&[DeprecatedUsage]> {
+        self.deprecations.as_deref()
+    }
+
+    /// Every analyzed token not ignored, regardless of likelihood
+    pub fn included(&self) -> Vec<&TokenUsage> {
+        self.outcome
+            .value()
+            .iter()
+            .filter(|u| self.analysis_filter.includes(&u.result))
             .collect()
     }
 
+    pub fn find(&self, token: &str) -> Option<&TokenUsage> {
+        self.outcome.find(token)
+    }
+
+    pub fn reported(&self, usage: &TokenUsage) -> bool {
+        self.analysis_filter.includes_usage(usage)
+            && self.analysis_filter.includes(&usage.result)
+            && !usage.allowed()
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Time spent searching for and analyzing tokens, after the tags were loaded
+    pub fn scan_duration(&self) -> Duration {
+        self.scan_duration
+    }
+
+    pub fn analyzed_tokens_count(&self) -> usize {
+        self.outcome.len()
+    }
+
+    pub fn searched_files(&self) -> &[PathBuf] {
+        &self.token_search_config.files
+    }
+
+    pub fn searched_files_count(&self) -> usize {
+        self.token_search_config.files.len()
+    }
+
+    pub fn occurrence_cache(&self) -> Option<&PathBuf> {
+        self.token_search_config.occurrence_cache.as_ref()
+    }
+
+    /// Whether files may be written, which they're not with `--no-write`
+    pub fn writes(&self) -> Writes {
+        self.token_search_config.writes
+    }
+
+    /// Whether report files may replace files unused didn't write
+    pub fn force(&self) -> bool {
+        self.force
+    }
+
     pub fn configuration_name(&self) -> String {
         self.project_configuration.name.to_string()
     }
 
+    /// Languages in the tags file without language-specific heuristics, whose tokens were
+    /// analyzed with generic rules
+    pub fn generic_languages(&self) -> BTreeSet<String> {
+        self.outcome
+            .value()
+            .iter()
+            .flat_map(|u| u.result.token.definitions.iter())
+            .filter_map(|d| d.unrecognized_language())
+            .collect()
+    }
+
+    pub fn framework_profiles(&self) -> &[String] {
+        &self.framework_profiles
+    }
+
+    pub fn loading_warnings(&self) -> &[String] {
+        &self.loading_warnings
+    }
+
     pub fn low_likelihood_conflicts(&self) -> HashMap<String, Vec<AssertionConflict>> {
         let mut conflict_results = HashMap::new();
 
         for ll in self.project_configuration.low_likelihood.iter() {
             let conflicts = ll.conflicts();
 
-            if conflicts.len() > 0 {
+            if !conflicts.is_empty() {
                 conflict_results.insert(ll.name.to_string(), conflicts);
             }
         }
@@ -117,12 +756,181 @@ impl CliConfiguration {
     }
 }
 
-fn build_token_search_config(cmd: &Flags, token_results: Vec<Token>) -> TokenSearchConfig {
-    let mut search_config = TokenSearchConfig::default();
-    search_config.tokens = token_results;
+// Rules from detected profiles are checked after those in the YAML configuration
+fn apply_framework_profiles(project_configuration: &mut ProjectConfiguration) -> Vec<String> {
+    FrameworkProfiles::default()
+        .detect(Path::new("."))
+        .into_iter()
+        .map(|profile| {
+            project_configuration
+                .low_likelihood
+                .extend(profile.low_likelihood());
+            profile.name().to_string()
+        })
+        .collect()
+}
+
+// Deprecated tokens keep their definitions when tagged so definition sites aren't counted as usages
+fn deprecated_tokens(names: &BTreeSet<String>, tokens: &[Token]) -> Vec<Token> {
+    let mut tagged: HashMap<&str, &Token> = tokens.iter().map(|t| (t.token.as_str(), t)).collect();
+
+    names
+        .iter()
+        .map(|name| match tagged.remove(name.as_str()) {
+            Some(token) => token.clone(),
+            None => Token::new(name.to_string(), HashSet::new()),
+        })
+        .collect()
+}
+
+// Definitions outside the definition roots (such as vendored dependencies) or their globs,
+// matching an exclude glob, or of a kind filtered out with `--only-kinds` or `--ignore-kinds`
+// are dropped;
+// occurrences in files outside the definition roots still count as usages of the project's own
+// definitions
+fn eligible_definitions<F>(tokens: Vec<Token>, eligible: F) -> Vec<Token>
+where
+    F: Fn(&CtagItem) -> bool,
+{
+    tokens
+        .into_iter()
+        .filter_map(|token| {
+            if token.definitions.iter().all(&eligible) {
+                return Some(token);
+            }
 
-    if cmd.no_progress {
-        search_config.display_progress = false;
+            let definitions: HashSet<_> = token
+                .definitions
+                .into_iter()
+                .filter(|d| eligible(d))
+                .collect();
+
+            if definitions.is_empty() {
+                None
+            } else {
+                Some(Token::new(token.token, definitions))
+            }
+        })
+        .collect()
+}
+
+// Keys sharing a name with a token defined in code are left to that token, since occurrences
+// can't be told apart
+fn translation_keys<F>(config: &TokenSearchConfig, eligible: F) -> Vec<Token>
+where
+    F: Fn(&CtagItem) -> bool,
+{
+    let names: HashSet<&str> = config.tokens.iter().map(|t| t.token.as_str()).collect();
+    let keys: Vec<Token> = Token::translation_keys(&config.files)
+        .into_iter()
+        .filter(|key| !names.contains(key.token.as_str()))
+        .collect();
+    tracing::info!("Loaded {} translation keys from locale files", keys.len());
+
+    eligible_definitions(keys, eligible)
+}
+
+fn sample_tokens(cmd: &AnalysisFlags, tokens: Vec<Token>) -> (Vec<Token>, Option<Sampling>) {
+    match cmd.sample {
+        Some(rate) => {
+            let sample = Sample {
+                rate,
+                seed: cmd.seed,
+            };
+            let population = tokens.len();
+            let tokens = sample.select(tokens, |t| &t.token);
+            let sampled = tokens.len();
+
+            (
+                tokens,
+                Some(Sampling {
+                    sample,
+                    population,
+                    sampled,
+                }),
+            )
+        }
+        None => (tokens, None),
+    }
+}
+
+// References from GRTAGS, when requested with `--gtags-references` and definitions came from GTAGS
+fn gtags_references(
+    flags: &AnalysisFlags,
+    tags_source: &TagsSource,
+) -> (Option<Occurrences>, Vec<String>) {
+    if !flags.gtags_references {
+        return (None, vec![]);
+    }
+
+    match tags_source {
+        TagsSource::Gtags(_) => match GlobalTags::default().references() {
+            Ok(references) => {
+                tracing::info!("Loaded GRTAGS references for {} tokens", references.len());
+                (Some(references), vec![])
+            }
+            Err(e) => (
+                None,
+                vec![format!(
+                    "Unable to read GRTAGS references, searching files instead: {}",
+                    e
+                )],
+            ),
+        },
+        _ => (
+            None,
+            vec![String::from(
+                "--gtags-references only applies to definitions read from GTAGS; searching files instead",
+            )],
+        ),
+    }
+}
+
+fn build_token_search_config(
+    cmd: &AnalysisFlags,
+    token_results: Vec<Token>,
+    context: &Context,
+) -> TokenSearchConfig {
+    let mut search_config = TokenSearchConfig {
+        tokens: token_results,
+        display_progress: !cmd.no_progress,
+        language_mappings: context.project.language_mappings.clone(),
+        ..TokenSearchConfig::for_files(TokenSearchConfig::walk_files(
+            !cmd.no_progress,
+            context.symlinks,
+        ))
+    };
+
+    if cmd.incremental {
+        search_config.occurrence_cache = OccurrenceCache::default_path();
+    }
+
+    if cmd.scan_cache {
+        search_config.scan_cache = ScanCache::default_path();
+    }
+
+    if cmd.scope_by_imports {
+        search_config.scope_by_imports = true;
+    }
+
+    if cmd.link_aliases {
+        search_config.link_aliases = true;
+    }
+
+    if cmd.attribute_scopes {
+        search_config.attribute_scopes = true;
+    }
+
+    if cmd.syntax_aware {
+        search_config.syntax_aware = true;
+    }
+
+    if let Some(FileSize(limit)) = cmd.max_file_size {
+        search_config.max_file_size = Some(limit).filter(|limit| *limit > 0);
+    }
+
+    if let Some(FileSize(limit)) = cmd.memory_limit {
+        search_config.memory_limit = Some(limit).filter(|limit| *limit > 0);
     }
 
     if !cmd.only_filetypes.is_empty() {
@@ -135,10 +943,40 @@ fn build_token_search_config(cmd: &Flags, token_results: Vec<Token>) -> TokenSea
             LanguageRestriction::Except(to_hash_set(&cmd.except_filetypes));
     }
 
+    if let Some(restriction) = language_restriction(cmd) {
+        search_config.files.retain(|path| {
+            let included = restriction.includes_file(path, &context.project.language_mappings);
+            if !included {
+                tracing::debug!(
+                    "Skipping file {}: its language is {}",
+                    path.display(),
+                    restriction
+                );
+            }
+            included
+        });
+        search_config.language_restriction = restriction;
+    }
+
     search_config
 }
 
-fn build_analysis_filter(cmd: &Flags) -> AnalysisFilter {
+// Unlike `--only-filetypes` and `--except-filetypes`, `--only-languages` and `--ignore-languages`
+// restrict the files searched as well as the tokens
+fn language_restriction(cmd: &AnalysisFlags) -> Option<LanguageRestriction> {
+    match (&cmd.only_languages[..], &cmd.ignore_languages[..]) {
+        ([], []) => None,
+        ([], ignored) => Some(LanguageRestriction::Except(to_hash_set(ignored))),
+        (only, ignored) => Some(LanguageRestriction::Only(
+            only.iter()
+                .filter(|language| !ignored.contains(language))
+                .cloned()
+                .collect(),
+        )),
+    }
+}
+
+fn build_analysis_filter(cmd: &AnalysisFlags) -> AnalysisFilter {
     let mut analysis_filter = AnalysisFilter::default();
 
     if !cmd.likelihoods.is_empty() {
@@ -149,13 +987,30 @@ fn build_analysis_filter(cmd: &Flags) -> AnalysisFilter {
         analysis_filter.usage_likelihood_filter = UsageLikelihoodStatus::all();
     }
 
-    analysis_filter.set_order_field(cmd.sort_order.clone());
+    if let Some(minimum) = &cmd.min_likelihood {
+        analysis_filter.set_min_likelihood(minimum);
+    }
+
+    if let Some(max) = cmd.max_occurrences {
+        analysis_filter.set_max_occurrences(max);
+    }
 
-    if cmd.reverse {
+    let order_field = cmd.sort_order.unwrap_or_default();
+    analysis_filter.set_order_field(order_field);
+
+    // The most valuable and safest cleanups are listed first, unless reversed
+    if cmd.reverse != matches!(order_field, OrderField::Value | OrderField::Confidence) {
         analysis_filter.set_order_descending();
     }
 
     analysis_filter.set_ignored(cmd.ignore.clone());
+    analysis_filter.add_ignored(
+        IgnoreList {
+            tokens_matching: cmd.ignore_tokens_matching.clone(),
+            ..IgnoreList::default()
+        }
+        .assertions(),
+    );
 
     analysis_filter
 }
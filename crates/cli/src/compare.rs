@@ -0,0 +1,205 @@
+use super::context::Context;
+use super::exit_status::ExitStatus;
+use super::flags::Format;
+use super::formatters::internal::print_json;
+use colored::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// A finding read from a JSON report
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct ReportedFinding {
+    /// Sub-project reporting the finding, in reports for monorepos
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    token: String,
+    likelihood: String,
+    paths: Vec<String>,
+}
+
+/// Findings only reported in the new report, and those only reported in the old one
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct FindingsDiff {
+    added: Vec<ReportedFinding>,
+    resolved: Vec<ReportedFinding>,
+}
+
+type Findings = BTreeMap<(Option<String>, String), ReportedFinding>;
+
+/// Compare two JSON reports, exiting with status 1 when the new report has findings the old
+/// one didn't
+pub fn render(context: &Context, old: &Path, new: &Path) -> ExitStatus {
+    let diff = match load(old).and_then(|old| Ok(FindingsDiff::between(old, load(new)?))) {
+        Ok(diff) => diff,
+        Err(e) => {
+            eprintln!("{}", e.red());
+            return ExitStatus::Error;
+        }
+    };
+
+    match context.format {
+        Format::Json => print_json(&diff),
+        _ => {
+            println!("   Newly unused: {}", diff.added.len().to_string().yellow());
+            for finding in &diff.added {
+                println!("   {} {}", "+".red(), describe(finding));
+            }
+            println!("   Resolved: {}", diff.resolved.len().to_string().yellow());
+            for finding in &diff.resolved {
+                println!("   {} {}", "-".green(), describe(finding));
+            }
+        }
+    }
+
+    if diff.added.is_empty() {
+        ExitStatus::Clean
+    } else {
+        ExitStatus::Findings
+    }
+}
+
+impl FindingsDiff {
+    fn between(mut old: Findings, new: Findings) -> Self {
+        let mut diff = FindingsDiff::default();
+
+        for (key, finding) in new {
+            if old.remove(&key).is_none() {
+                diff.added.push(finding);
+            }
+        }
+        diff.resolved.extend(old.into_values());
+
+        diff
+    }
+}
+
+fn describe(finding: &ReportedFinding) -> String {
+    let project = finding
+        .project
+        .as_ref()
+        .map(|name| format!("{}: ", name))
+        .unwrap_or_default();
+
+    format!(
+        "{}{} ({}) {}",
+        project,
+        finding.token,
+        finding.likelihood,
+        finding.paths.join(", ").cyan()
+    )
+}
+
+fn load(path: &Path) -> Result<Findings, String> {
+    fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string()))
+        .and_then(|report| parse(&report))
+        .map_err(|e| format!("Unable to read report {}: {}", path.display(), e))
+}
+
+// Reports for monorepos nest each sub-project's report under `projects`
+fn parse(report: &Value) -> Result<Findings, String> {
+    match (
+        report["findings"].as_array(),
+        report["projects"].as_object(),
+    ) {
+        (Some(findings), _) => Ok(parse_findings(None, findings)),
+        (None, Some(projects)) => Ok(projects
+            .iter()
+            .flat_map(|(name, report)| {
+                parse_findings(Some(name), report["findings"].as_array().unwrap_or(&vec![]))
+            })
+            .collect()),
+        (None, None) => Err(String::from(
+            "expected a JSON report written with --format json",
+        )),
+    }
+}
+
+fn parse_findings(project: Option<&String>, findings: &[Value]) -> Findings {
+    findings
+        .iter()
+        .filter_map(|finding| {
+            let token = finding["token"].as_str()?.to_string();
+            let paths = finding["definitions"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|definition| definition["path"].as_str())
+                .map(String::from)
+                .collect();
+
+            Some((
+                (project.cloned(), token.clone()),
+                ReportedFinding {
+                    project: project.cloned(),
+                    token,
+                    likelihood: finding["likelihood"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    paths,
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn report(tokens: &[&str]) -> Value {
+        json!({
+            "version": 1,
+            "findings": tokens
+                .iter()
+                .map(|token| json!({
+                    "token": token,
+                    "likelihood": "high",
+                    "definitions": [{ "path": "app/person.rb" }]
+                }))
+                .collect::<Vec<_>>()
+        })
+    }
+
+    #[test]
+    fn reports_newly_unused_and_resolved_findings() {
+        let old = parse(&report(&["full_name", "legacy"])).unwrap();
+        let new = parse(&report(&["full_name", "total"])).unwrap();
+
+        let diff = FindingsDiff::between(old, new);
+
+        assert_eq!(
+            diff.added
+                .iter()
+                .map(|f| f.token.as_str())
+                .collect::<Vec<_>>(),
+            vec!["total"]
+        );
+        assert_eq!(
+            diff.resolved
+                .iter()
+                .map(|f| f.token.as_str())
+                .collect::<Vec<_>>(),
+            vec!["legacy"]
+        );
+        assert_eq!(diff.added[0].paths, vec!["app/person.rb"]);
+    }
+
+    #[test]
+    fn compares_sub_projects_separately() {
+        let old = parse(&json!({ "projects": { "api": report(&["legacy"]) } })).unwrap();
+        let new = parse(&json!({ "projects": { "web": report(&["legacy"]) } })).unwrap();
+
+        let diff = FindingsDiff::between(old, new);
+
+        assert_eq!(diff.added[0].project.as_deref(), Some("web"));
+        assert_eq!(diff.resolved[0].project.as_deref(), Some("api"));
+        assert!(parse(&json!({ "runs": [] })).is_err());
+    }
+}
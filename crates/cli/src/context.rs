@@ -0,0 +1,160 @@
+use super::flags::{Format, FormatTarget, GlobalFlags};
+use super::logging;
+use super::project_defaults::ProjectDefaults;
+use codebase_files::writes::Writes;
+use codebase_files::{CodebaseFiles, Symlinks};
+use project_configuration::SubProject;
+use read_ctags::{LoadMode, ParseMode, TagsFormat, TagsReader};
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use token_analysis::UsageAnalyzer;
+
+/// Settings shared by every subcommand, resolved from the global flags and project settings
+pub struct Context {
+    pub config_path: Option<PathBuf>,
+    /// Tags file passed with `--tags`, read instead of searching for one
+    pub tags_path: Option<PathBuf>,
+    /// Merge every tags file found rather than reading the first, with `--all-tags`
+    pub all_tags: bool,
+    /// Project root tag paths are made relative to, passed with `--tags-root`
+    pub tags_root: Option<PathBuf>,
+    /// Format tags files are parsed as, passed with `--tags-format`
+    pub tags_format: TagsFormat,
+    /// Whether malformed tags are skipped rather than failing, with `--lenient-tags`
+    pub parse_mode: ParseMode,
+    /// Whether files may be written, which they're not with `--no-write`
+    pub writes: Writes,
+    /// Whether report files may replace files unused didn't write, with `--force`
+    pub force: bool,
+    /// Whether walks follow symlinks, which they do with `--follow-symlinks`
+    pub symlinks: Symlinks,
+    /// Whether references in documentation are counted apart, with `--doc-references`
+    pub doc_references: bool,
+    pub format: Format,
+    /// Formats rendered by the analysis, with at most one written to STDOUT
+    pub outputs: Vec<FormatTarget>,
+    pub project: ProjectDefaults,
+    /// The sub-project being analyzed, when the project settings declare several
+    pub sub_project: Option<SubProject>,
+    /// Analyzers adjusting each token's likelihood, given by the program embedding unused
+    pub analyzers: Arc<Vec<Box<dyn UsageAnalyzer>>>,
+}
+
+impl Context {
+    pub fn new(
+        flags: GlobalFlags,
+        project: ProjectDefaults,
+        analyzers: Vec<Box<dyn UsageAnalyzer>>,
+    ) -> Self {
+        let (mut outputs, files): (Vec<_>, Vec<_>) =
+            flags.format.into_iter().partition(|t| t.path.is_none());
+
+        // The last format without a path is written to STDOUT, or the default when only files
+        // were requested
+        outputs = if flags.json {
+            vec![FormatTarget {
+                format: Format::Json,
+                path: None,
+            }]
+        } else if flags.porcelain {
+            vec![FormatTarget {
+                format: Format::Porcelain,
+                path: None,
+            }]
+        } else if outputs.is_empty() && files.is_empty() {
+            vec![FormatTarget {
+                format: project.format.unwrap_or_default(),
+                path: None,
+            }]
+        } else {
+            outputs.pop().into_iter().collect()
+        };
+        let format = outputs
+            .first()
+            .map(|t| t.format)
+            .or(project.format)
+            .unwrap_or_default();
+        outputs.extend(files);
+
+        Context {
+            config_path: flags.config,
+            tags_path: flags.tags,
+            all_tags: flags.all_tags,
+            tags_root: flags.tags_root,
+            tags_format: flags.tags_format.unwrap_or_default(),
+            parse_mode: if flags.lenient_tags {
+                ParseMode::Lenient
+            } else {
+                ParseMode::Strict
+            },
+            writes: if flags.no_write {
+                Writes::Disabled
+            } else {
+                Writes::Enabled
+            },
+            force: flags.force,
+            symlinks: if flags.follow_symlinks {
+                Symlinks::Follow
+            } else {
+                Symlinks::Skip
+            },
+            doc_references: flags.doc_references,
+            format,
+            outputs,
+            project,
+            sub_project: None,
+            analyzers: Arc::new(analyzers),
+        }
+    }
+
+    /// Should progress be displayed while analyzing?
+    ///
+    /// Progress is hidden with `--quiet`, or when STDOUT isn't a terminal or is rendering a
+    /// machine-readable format, since the output is likely being captured by another tool.
+    pub fn displays_progress(&self) -> bool {
+        io::stdout().is_terminal() && !self.format.is_machine_readable() && !logging::quiet()
+    }
+
+    /// Reader for the tags file passed with `--tags`, or else the first found in the project (or
+    /// every one found, with `--all-tags`)
+    pub fn tags_reader(&self) -> TagsReader {
+        let reader = match &self.tags_path {
+            Some(path) => TagsReader::for_path(path),
+            None if self.all_tags => TagsReader::default().with_mode(LoadMode::All),
+            None => TagsReader::default(),
+        }
+        .with_format(self.tags_format)
+        .with_parse_mode(self.parse_mode);
+        match &self.tags_root {
+            Some(root) => reader.with_root(root),
+            None => reader,
+        }
+    }
+
+    /// Files in the codebase, walked from the current directory
+    pub fn codebase_files(&self) -> CodebaseFiles {
+        CodebaseFiles::walk(Path::new("."), self.symlinks)
+    }
+
+    /// Context for analyzing one of the sub-projects declared under `projects`
+    pub fn for_sub_project(&self, name: &str) -> Context {
+        Context {
+            config_path: self.config_path.clone(),
+            tags_path: self.tags_path.clone(),
+            all_tags: self.all_tags,
+            tags_root: self.tags_root.clone(),
+            tags_format: self.tags_format,
+            parse_mode: self.parse_mode,
+            writes: self.writes,
+            force: self.force,
+            symlinks: self.symlinks,
+            doc_references: self.doc_references,
+            format: self.format,
+            outputs: self.outputs.clone(),
+            project: self.project.for_sub_project(name),
+            sub_project: self.project.settings.projects.get(name).cloned(),
+            analyzers: self.analyzers.clone(),
+        }
+    }
+}
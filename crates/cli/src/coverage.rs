@@ -0,0 +1,282 @@
+use codebase_files::{text, DiskFileSystem};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::path::{Path, PathBuf};
+use token_analysis::TokenUsage;
+
+/// Runtime coverage of a token's definitions
+///
+/// Definition lines usually run when their file is loaded (such as Ruby's `def` or Python's
+/// `def`), so only the lines after each definition's first are counted when ctags recorded where
+/// it ends (`--fields=+e`) and any of those lines are executable.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TokenCoverage {
+    pub executable_lines: usize,
+    pub covered_lines: usize,
+    /// Nothing defining the token ran: combined with a finding, it's very likely dead
+    pub never_run: bool,
+}
+
+/// Hit counts per executable line, keyed by path relative to the current directory
+#[derive(Default)]
+pub struct Coverage {
+    files: HashMap<PathBuf, BTreeMap<usize, u64>>,
+}
+
+// Hit counts per line of a file named in a report
+type FileHits = (PathBuf, BTreeMap<usize, u64>);
+
+pub struct LoadedCoverage {
+    pub coverage: Coverage,
+    pub warnings: Vec<String>,
+}
+
+impl Coverage {
+    /// Read each lcov or SimpleCov JSON report, merging hits for files in several reports
+    pub fn load(paths: &[PathBuf]) -> LoadedCoverage {
+        let cwd = env::current_dir().unwrap_or_default();
+        let mut files: HashMap<PathBuf, BTreeMap<usize, u64>> = HashMap::new();
+        let mut warnings = vec![];
+
+        for path in paths {
+            match text::read(path)
+                .map_err(|e| e.to_string())
+                .and_then(|contents| parse(&contents))
+            {
+                Ok(reported) => {
+                    for (file, lines) in reported {
+                        let hits = files.entry(relative_path(&file, &cwd)).or_default();
+                        for (line, count) in lines {
+                            *hits.entry(line).or_default() += count;
+                        }
+                    }
+                }
+                Err(e) => warnings.push(format!(
+                    "Unable to read coverage from {}: {}",
+                    path.display(),
+                    e
+                )),
+            }
+        }
+
+        LoadedCoverage {
+            coverage: Coverage { files },
+            warnings,
+        }
+    }
+
+    /// Coverage of a token's definitions, when the report covers any of their files
+    pub fn for_usage(&self, usage: &TokenUsage) -> Option<TokenCoverage> {
+        let mut executable_lines = 0;
+        let mut covered_lines = 0;
+        let mut reported = false;
+
+        for definition in usage.result.token.definitions.iter() {
+            let (hits, line) = match (
+                self.files.get(&*definition.file_path),
+                definition.line(&DiskFileSystem),
+            ) {
+                (Some(hits), Some(line)) => (hits, line),
+                _ => continue,
+            };
+            reported = true;
+
+            let body: Vec<u64> = match definition.end_line() {
+                Some(end) if end > line => hits.range(line + 1..=end).map(|(_, c)| *c).collect(),
+                _ => vec![],
+            };
+            let counted = if body.is_empty() {
+                hits.get(&line).into_iter().copied().collect()
+            } else {
+                body
+            };
+
+            executable_lines += counted.len();
+            covered_lines += counted.iter().filter(|c| **c > 0).count();
+        }
+
+        if reported {
+            Some(TokenCoverage {
+                executable_lines,
+                covered_lines,
+                never_run: executable_lines > 0 && covered_lines == 0,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+// Reports list absolute paths (SimpleCov) or paths relative to where tests ran (lcov)
+fn relative_path(path: &Path, cwd: &Path) -> PathBuf {
+    let path = path.strip_prefix(cwd).unwrap_or(path);
+    path.strip_prefix("./").unwrap_or(path).to_path_buf()
+}
+
+fn parse(contents: &str) -> Result<Vec<FileHits>, String> {
+    if contents.trim_start().starts_with('{') {
+        parse_simplecov(contents)
+    } else {
+        parse_lcov(contents)
+    }
+}
+
+// lcov tracefiles list each file's `DA:<line>,<hits>[,<checksum>]` records between `SF:<path>`
+// and `end_of_record`
+fn parse_lcov(contents: &str) -> Result<Vec<FileHits>, String> {
+    let mut files = vec![];
+    let mut current: Option<FileHits> = None;
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if let Some(path) = line.strip_prefix("SF:") {
+            current = Some((PathBuf::from(path), BTreeMap::new()));
+        } else if let Some(record) = line.strip_prefix("DA:") {
+            let mut fields = record.split(',');
+            match (
+                current.as_mut(),
+                fields.next().and_then(|l| l.parse().ok()),
+                fields.next().and_then(|c| c.parse().ok()),
+            ) {
+                (Some((_, hits)), Some(line), Some(count)) => {
+                    hits.insert(line, count);
+                }
+                _ => return Err(format!("invalid line record on line {}", index + 1)),
+            }
+        } else if line == "end_of_record" {
+            files.extend(current.take());
+        }
+    }
+    files.extend(current);
+
+    if files.is_empty() {
+        Err(String::from("expected an lcov tracefile or SimpleCov JSON"))
+    } else {
+        Ok(files)
+    }
+}
+
+// SimpleCov's `.resultset.json` nests a `coverage` mapping under each test command, while its
+// JSON formatter writes one at the top level; each file maps to an array of hits per line
+// (`null` for lines that aren't executable), either directly or under `lines`
+fn parse_simplecov(contents: &str) -> Result<Vec<FileHits>, String> {
+    let report: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    let coverages: Vec<&Value> = match report.get("coverage") {
+        Some(coverage) => vec![coverage],
+        None => report
+            .as_object()
+            .into_iter()
+            .flat_map(|commands| commands.values())
+            .filter_map(|command| command.get("coverage"))
+            .collect(),
+    };
+
+    let files: Vec<FileHits> = coverages
+        .into_iter()
+        .filter_map(Value::as_object)
+        .flat_map(|files| files.iter())
+        .filter_map(|(path, file)| {
+            let lines = file.get("lines").unwrap_or(file).as_array()?;
+            let hits = lines
+                .iter()
+                .enumerate()
+                .filter_map(|(index, count)| count.as_u64().map(|c| (index + 1, c)))
+                .collect();
+            Some((PathBuf::from(path), hits))
+        })
+        .collect();
+
+    if files.is_empty() {
+        Err(String::from("no file coverage found in SimpleCov JSON"))
+    } else {
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TokenUsageBuilder;
+
+    fn coverage(contents: &str) -> Coverage {
+        let cwd = env::current_dir().unwrap();
+
+        Coverage {
+            files: parse(contents)
+                .unwrap()
+                .into_iter()
+                .map(|(path, hits)| (relative_path(&path, &cwd), hits))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn parses_lcov() {
+        let lcov = "TN:\nSF:./app/person.rb\nDA:2,1\nDA:3,0,abc\nDA:4,0\nend_of_record\n";
+
+        assert_eq!(
+            parse(lcov),
+            Ok(vec![(
+                PathBuf::from("./app/person.rb"),
+                [(2, 1), (3, 0), (4, 0)].iter().cloned().collect()
+            )])
+        );
+        assert!(parse("SF:app/person.rb\nDA:x,1\n").is_err());
+        assert!(parse("not coverage").is_err());
+    }
+
+    #[test]
+    fn parses_simplecov() {
+        let resultset = r#"{"RSpec": {"coverage": {"/app/person.rb": {"lines": [null, 1, 0]}}}}"#;
+        let legacy = r#"{"RSpec": {"coverage": {"/app/person.rb": [null, 1, 0]}}}"#;
+        let formatter = r#"{"meta": {}, "coverage": {"/app/person.rb": {"lines": [null, 1, 0]}}}"#;
+        let expected = Ok(vec![(
+            PathBuf::from("/app/person.rb"),
+            [(2, 1), (3, 0)].iter().cloned().collect(),
+        )]);
+
+        assert_eq!(parse(resultset), expected);
+        assert_eq!(parse(legacy), expected);
+        assert_eq!(parse(formatter), expected);
+        assert!(parse(r#"{"RSpec": {}}"#).is_err());
+    }
+
+    #[test]
+    fn counts_definition_bodies_when_ends_are_known() {
+        let coverage =
+            coverage("SF:app/person.rb\nDA:2,1\nDA:3,0\nDA:4,0\nDA:7,2\nend_of_record\n");
+        let legacy = TokenUsageBuilder::new("legacy", "app/person.rb").with_address("2");
+
+        assert_eq!(
+            coverage.for_usage(&legacy.clone().with_tag("end", "5").build()),
+            Some(TokenCoverage {
+                executable_lines: 2,
+                covered_lines: 0,
+                never_run: true,
+            })
+        );
+        assert_eq!(
+            coverage.for_usage(&legacy.build()),
+            Some(TokenCoverage {
+                executable_lines: 1,
+                covered_lines: 1,
+                never_run: false,
+            })
+        );
+        assert_eq!(
+            coverage
+                .for_usage(
+                    &legacy
+                        .clone()
+                        .with_address("6")
+                        .with_tag("end", "8")
+                        .build()
+                )
+                .map(|c| c.never_run),
+            Some(false)
+        );
+        assert_eq!(Coverage::default().for_usage(&legacy.build()), None);
+    }
+}
@@ -0,0 +1,298 @@
+use super::cli_configuration::CliConfiguration;
+use super::context::Context;
+use super::error_message;
+use super::exit_status::ExitStatus;
+use super::flags::AnalysisFlags;
+use super::formatters;
+use super::serve;
+use codebase_files::paths;
+use codebase_files::writes::Writes;
+use colored::*;
+use serde_json::{json, Value};
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// Error codes defined by JSON-RPC 2.0, and one of its server error codes for failed rescans
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const RESCAN_FAILED: i64 = -32000;
+
+type Client = Arc<Mutex<dyn Write + Send>>;
+
+// A line received from a client, answered on the same client
+struct Message {
+    line: String,
+    client: Client,
+}
+
+#[derive(Debug, PartialEq)]
+struct Request {
+    // Absent for notifications, which aren't answered
+    id: Option<Value>,
+    method: String,
+    params: Value,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// Keep tags and per-file scans in memory, answering JSON-RPC 2.0 requests (one per line) on
+/// STDIN, or from each client connected to a unix socket
+///
+/// Methods are `findings`, `lookupToken` (`{"token": ...}`), `analyzeFile` (`{"path": ...}`,
+/// returning findings defined in that file), `rescan` (`{"full": true}` to search every file
+/// again), and `shutdown`. Requests are answered in the order they're received, so editor
+/// integrations don't need to start a new analysis for each query.
+pub fn run(context: &Context, socket: Option<PathBuf>, mut flags: AnalysisFlags) -> ExitStatus {
+    flags.no_progress = true;
+    flags.scan_cache = true;
+
+    let mut cli_config = match super::build_configuration(context, flags.clone()) {
+        Ok(cli_config) => cli_config,
+        Err(e) => {
+            error_message::failed_token_parse(e);
+            return ExitStatus::Error;
+        }
+    };
+
+    let (sender, messages) = mpsc::channel();
+    match &socket {
+        Some(path) => {
+            if let Err(e) = listen(path, sender, context.writes) {
+                eprintln!(
+                    "{}",
+                    format!("Unable to listen on {}: {}", path.display(), e).red()
+                );
+                return ExitStatus::Error;
+            }
+            tracing::info!("Listening on {}", path.display());
+        }
+        None => {
+            let stdout: Client = Arc::new(Mutex::new(io::stdout()));
+            thread::spawn(move || read_messages(io::stdin().lock(), stdout, sender));
+        }
+    }
+
+    for message in messages {
+        let request = match parse_request(&message.line) {
+            Ok(request) => request,
+            Err((id, error)) => {
+                reply(&message.client, id.unwrap_or(Value::Null), Err(error));
+                continue;
+            }
+        };
+
+        let result = call(context, &flags, &mut cli_config, &request);
+        if let Some(id) = request.id {
+            reply(&message.client, id, result);
+        }
+        if request.method == "shutdown" {
+            break;
+        }
+    }
+
+    if let Some(path) = socket {
+        context.writes.remove(&path).ok();
+    }
+    ExitStatus::Clean
+}
+
+fn call(
+    context: &Context,
+    flags: &AnalysisFlags,
+    cli_config: &mut CliConfiguration,
+    request: &Request,
+) -> Result<Value, RpcError> {
+    match request.method.as_str() {
+        "findings" => Ok(formatters::json::report(cli_config)),
+        "lookupToken" => {
+            let token = string_param(&request.params, "token")?;
+            Ok(cli_config
+                .find(token)
+                .map(formatters::json::finding)
+                .unwrap_or(Value::Null))
+        }
+        "analyzeFile" => {
+            let path = project_path(string_param(&request.params, "path")?);
+            Ok(cli_config
+                .for_json()
+                .into_iter()
+                .filter(|usage| {
+                    usage
+                        .result
+                        .token
+                        .definitions
+                        .iter()
+                        .any(|d| *d.file_path == *path)
+                })
+                .map(formatters::json::finding)
+                .collect())
+        }
+        "rescan" => {
+            let full = request.params["full"].as_bool().unwrap_or(false);
+            match serve::rescan(context, flags, full) {
+                Ok(rescanned) => {
+                    *cli_config = rescanned;
+                    Ok(json!({ "rescan": if full { "full" } else { "incremental" } }))
+                }
+                Err(message) => Err(RpcError {
+                    code: RESCAN_FAILED,
+                    message,
+                }),
+            }
+        }
+        "shutdown" => Ok(Value::Null),
+        method => Err(RpcError {
+            code: METHOD_NOT_FOUND,
+            message: format!("Unknown method: {}", method),
+        }),
+    }
+}
+
+fn parse_request(line: &str) -> Result<Request, (Option<Value>, RpcError)> {
+    let value: Value = serde_json::from_str(line).map_err(|e| {
+        let error = RpcError {
+            code: PARSE_ERROR,
+            message: e.to_string(),
+        };
+        (None, error)
+    })?;
+    let id = value.get("id").cloned();
+
+    match value["method"].as_str() {
+        Some(method) if value["jsonrpc"] == "2.0" => Ok(Request {
+            id,
+            method: method.to_string(),
+            params: value.get("params").cloned().unwrap_or(Value::Null),
+        }),
+        _ => Err((
+            id,
+            RpcError {
+                code: INVALID_REQUEST,
+                message: String::from("Expected a JSON-RPC 2.0 request with a method"),
+            },
+        )),
+    }
+}
+
+fn string_param<'a>(params: &'a Value, name: &str) -> Result<&'a str, RpcError> {
+    params[name].as_str().ok_or_else(|| RpcError {
+        code: INVALID_PARAMS,
+        message: format!("Missing string parameter {}", name),
+    })
+}
+
+// Editors send absolute paths, while findings are relative to the project
+fn project_path(path: &str) -> PathBuf {
+    let path = paths::normalize(path);
+    env::current_dir()
+        .ok()
+        .and_then(|root| paths::relative_to(&path, &root))
+        .unwrap_or(path)
+}
+
+fn reply(client: &Client, id: Value, result: Result<Value, RpcError>) {
+    let response = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message }
+        }),
+    };
+
+    if let Ok(mut out) = client.lock() {
+        writeln!(out, "{}", response).and_then(|_| out.flush()).ok();
+    }
+}
+
+fn read_messages<R: BufRead>(reader: R, client: Client, sender: Sender<Message>) {
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let message = Message {
+            line,
+            client: client.clone(),
+        };
+        if sender.send(message).is_err() {
+            break;
+        }
+    }
+}
+
+// A socket left behind by a daemon that didn't shut down cleanly is replaced. Binding creates
+// the socket's file, which read-only mode refuses before the daemon starts
+#[cfg(unix)]
+fn listen(path: &Path, sender: Sender<Message>, writes: Writes) -> io::Result<()> {
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    if path.exists() && UnixStream::connect(path).is_err() {
+        writes.remove(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let sender = sender.clone();
+            if let Ok(reader) = stream.try_clone() {
+                let client: Client = Arc::new(Mutex::new(stream));
+                thread::spawn(move || read_messages(io::BufReader::new(reader), client, sender));
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn listen(_path: &Path, _sender: Sender<Message>, _writes: Writes) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "unix sockets aren't supported on this platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_requests_and_notifications() {
+        assert_eq!(
+            parse_request(
+                r#"{"jsonrpc": "2.0", "id": 1, "method": "lookupToken", "params": {"token": "name"}}"#
+            )
+            .ok(),
+            Some(Request {
+                id: Some(json!(1)),
+                method: String::from("lookupToken"),
+                params: json!({ "token": "name" }),
+            })
+        );
+        assert_eq!(
+            parse_request(r#"{"jsonrpc": "2.0", "method": "rescan"}"#).ok(),
+            Some(Request {
+                id: None,
+                method: String::from("rescan"),
+                params: Value::Null,
+            })
+        );
+
+        let (id, error) = parse_request(r#"{"id": 2, "method": "findings"}"#)
+            .err()
+            .unwrap();
+        assert_eq!((id, error.code), (Some(json!(2)), INVALID_REQUEST));
+        let (id, error) = parse_request("findings").err().unwrap();
+        assert_eq!((id, error.code), (None, PARSE_ERROR));
+    }
+}
@@ -0,0 +1,29 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use token_search::Token;
+
+pub struct LoadedDependencies {
+    pub tokens: HashSet<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Load token names defined in the provided dependency tags files
+///
+/// Application tokens sharing one of these names shadow a dependency definition.
+pub fn load(paths: &[PathBuf]) -> LoadedDependencies {
+    let mut tokens = HashSet::new();
+    let mut warnings = vec![];
+
+    for path in paths {
+        match Token::from_tags_path(path.to_path_buf()) {
+            Ok(loaded) => tokens.extend(loaded.into_iter().map(|t| t.token)),
+            Err(e) => warnings.push(format!(
+                "Unable to load dependency tags {}: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    LoadedDependencies { tokens, warnings }
+}
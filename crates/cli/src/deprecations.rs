@@ -0,0 +1,53 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+pub struct LoadedDeprecations {
+    pub tokens: BTreeSet<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Combine deprecated tokens listed inline with those read from files
+///
+/// Files list one token per line; blank lines and lines starting with `#` are skipped.
+pub fn load(tokens: &[String], paths: &[PathBuf]) -> LoadedDeprecations {
+    let mut loaded: BTreeSet<String> = tokens.iter().cloned().collect();
+    let mut warnings = vec![];
+
+    for path in paths {
+        match fs::read_to_string(path) {
+            Ok(contents) => loaded.extend(parse(&contents)),
+            Err(e) => warnings.push(format!(
+                "Unable to load deprecated tokens {}: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    LoadedDeprecations {
+        tokens: loaded,
+        warnings,
+    }
+}
+
+fn parse(contents: &str) -> impl Iterator<Item = String> + '_ {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_comments_and_blank_lines() {
+        assert_eq!(
+            parse("# Remove by Q3\nlegacy_total\n\n  OldPresenter  \n").collect::<Vec<_>>(),
+            vec![String::from("legacy_total"), String::from("OldPresenter")]
+        );
+    }
+}
@@ -1,13 +1,17 @@
 mod check_up;
 mod files_count;
 mod loaded_project_configurations;
+mod tags_cover_languages;
 mod tags_included_in_files_searched;
+mod tags_line_numbers;
 mod tokens_count;
 mod using_universal_ctags;
 
+use super::context::Context;
 use super::doctor::{
-    check_up::*, files_count::*, loaded_project_configurations::*,
-    tags_included_in_files_searched::*, tokens_count::*, using_universal_ctags::*,
+    check_up::*, files_count::*, loaded_project_configurations::*, tags_cover_languages::*,
+    tags_included_in_files_searched::*, tags_line_numbers::*, tokens_count::*,
+    using_universal_ctags::*,
 };
 use colored::*;
 
@@ -16,21 +20,34 @@ pub struct Doctor {
 }
 
 impl Doctor {
-    pub fn new() -> Self {
+    pub fn new(context: &Context) -> Self {
+        let tags_reader = context.tags_reader();
+        let tags_file = tags_reader.load().map_err(|e| e.to_string());
+        let tags_file = tags_file.as_ref().map_err(String::as_str);
+        let files = context.codebase_files().paths;
+
         Self {
             checks: vec![
-                Box::new(IncludingTagsInFilesSearched::new()),
-                Box::new(TokensCount::new()),
-                Box::new(FilesCount::new()),
-                Box::new(UsingUniversalCtags::new()),
-                Box::new(LoadedProjectConfigurations::new()),
+                Box::new(IncludingTagsInFilesSearched::new(&tags_reader, &files)),
+                Box::new(TokensCount::new(&tags_reader)),
+                Box::new(FilesCount::new(&files)),
+                Box::new(UsingUniversalCtags::new(&tags_reader)),
+                Box::new(TagsLineNumbers::new(tags_file)),
+                Box::new(TagsCoverLanguages::new(
+                    tags_file,
+                    &files,
+                    &context.project.language_mappings,
+                )),
+                Box::new(LoadedProjectConfigurations::new(
+                    context.config_path.as_deref(),
+                )),
             ],
         }
     }
 
     pub fn render(&self) {
         println!("Unused Doctor");
-        println!("");
+        println!();
 
         let mut oks = 0;
         let mut warnings = 0;
@@ -43,10 +60,10 @@ impl Doctor {
                 Status::Error(_) => errors += 1,
             }
 
-            Self::render_check_up(check)
+            Self::render_check_up(check.as_ref())
         }
 
-        println!("");
+        println!();
         println!(
             "{}: {}, {}, {}",
             Self::colorized_outcome(warnings, errors),
@@ -68,14 +85,24 @@ impl Doctor {
         }
     }
 
-    fn render_check_up(check_up: &Box<dyn CheckUp>) {
-        match check_up.status() {
+    fn render_check_up(check_up: &dyn CheckUp) {
+        let status = check_up.status();
+        let fix = match status {
+            Status::OK(_) => None,
+            _ => check_up.fix(),
+        };
+
+        match status {
             Status::OK(message) => Self::render_status("OK".green(), check_up.name(), message),
             Status::Warn(message) => {
                 Self::render_status("Warning".yellow(), check_up.name(), message)
             }
             Status::Error(message) => Self::render_status("Error".red(), check_up.name(), message),
         }
+
+        if let Some(fix) = fix {
+            println!("     Fix: {}", fix.cyan());
+        }
     }
 
     fn render_status(status: colored::ColoredString, name: &str, message: String) {
@@ -7,4 +7,9 @@ pub enum Status {
 pub trait CheckUp {
     fn name(&self) -> &str;
     fn status(&self) -> Status;
+
+    /// How to resolve a warning or error, shown alongside it
+    fn fix(&self) -> Option<String> {
+        None
+    }
 }
@@ -1,11 +1,10 @@
 use super::check_up::{CheckUp, Status};
-use codebase_files::CodebaseFiles;
+use std::path::PathBuf;
 
 pub struct FilesCount(usize);
 
 impl FilesCount {
-    pub fn new() -> Self {
-        let file_paths = CodebaseFiles::all().paths;
+    pub fn new(file_paths: &[PathBuf]) -> Self {
         Self(file_paths.len())
     }
 }
@@ -3,12 +3,13 @@ use super::{
     check_up::{CheckUp, Status},
 };
 use project_configuration::ProjectConfigurations;
+use std::path::Path;
 
 pub struct LoadedProjectConfigurations(ProjectConfigurations);
 
 impl LoadedProjectConfigurations {
-    pub fn new() -> Self {
-        LoadedProjectConfigurations(load_and_parse_config())
+    pub fn new(config_path: Option<&Path>) -> Self {
+        LoadedProjectConfigurations(load_and_parse_config(config_path))
     }
 
     fn config_keys(&self) -> Vec<String> {
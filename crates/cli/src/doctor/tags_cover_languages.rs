@@ -0,0 +1,115 @@
+use super::check_up::{CheckUp, Status};
+use read_ctags::{Language, LanguageMappings, TagsFile};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+// Data and markup languages rarely define tokens worth tagging
+const UNTAGGED_LANGUAGES: [Language; 5] = [
+    Language::HTML,
+    Language::JSON,
+    Language::Markdown,
+    Language::SVG,
+    Language::XML,
+];
+
+pub enum TagsCoverLanguages {
+    // Languages of the project's files without tags, with the number of files in each
+    Success(BTreeMap<String, usize>),
+    Failure(String),
+}
+
+impl TagsCoverLanguages {
+    pub fn new(
+        tags_file: Result<&TagsFile, &str>,
+        files: &[PathBuf],
+        mappings: &LanguageMappings,
+    ) -> Self {
+        let tags_file = match tags_file {
+            Ok(tags_file) => tags_file,
+            Err(e) => return Self::Failure(e.to_string()),
+        };
+        let tagged: HashSet<Language> = tags_file
+            .tags
+            .iter()
+            .filter_map(|t| mappings.get(&t.file_path).or(t.language))
+            .collect();
+        let mut untagged = BTreeMap::new();
+
+        for language in files.iter().filter_map(|path| mappings.from_path(path)) {
+            if !tagged.contains(&language) && !UNTAGGED_LANGUAGES.contains(&language) {
+                *untagged.entry(language.to_string()).or_default() += 1;
+            }
+        }
+
+        Self::Success(untagged)
+    }
+}
+
+impl CheckUp for TagsCoverLanguages {
+    fn name(&self) -> &str {
+        "Does the tags file cover the project's languages?"
+    }
+
+    fn status(&self) -> Status {
+        match self {
+            Self::Success(untagged) if untagged.is_empty() => {
+                Status::OK(String::from("Every language in the project has tags"))
+            }
+            Self::Success(untagged) => Status::Warn(format!(
+                "No tags for {}",
+                untagged
+                    .iter()
+                    .map(|(language, files)| format!("{} ({} file(s))", language, files))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            Self::Failure(e) => Status::Error(e.to_string()),
+        }
+    }
+
+    fn fix(&self) -> Option<String> {
+        match self {
+            Self::Success(_) => Some(String::from(
+                "Check `ctags --list-languages` includes them and that ctags isn't excluding their files, then regenerate tags",
+            )),
+            Self::Failure(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use read_ctags::CtagItem;
+
+    #[test]
+    fn warns_about_languages_without_tags() {
+        let tags_file = CtagItem::parse(
+            PathBuf::from("tags"),
+            "full_name\tapp/person.rb\t2;\"\tf\tlanguage:Ruby\n",
+        )
+        .unwrap();
+        let files: Vec<PathBuf> = [
+            "app/person.rb",
+            "lib/parser.ex",
+            "lib/lexer.ex",
+            "config/settings.json",
+            "README.md",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+        let mappings = LanguageMappings::default();
+        let check = TagsCoverLanguages::new(Ok(&tags_file), &files, &mappings);
+
+        assert!(matches!(
+            check.status(),
+            Status::Warn(message) if message == "No tags for Elixir (2 file(s))"
+        ));
+        assert!(matches!(
+            TagsCoverLanguages::new(Ok(&tags_file), &files[..1], &mappings).status(),
+            Status::OK(_)
+        ));
+    }
+}
@@ -1,5 +1,5 @@
 use super::check_up::{CheckUp, Status};
-use codebase_files::CodebaseFiles;
+use read_ctags::TagsReader;
 use std::path::PathBuf;
 use token_search::Token;
 
@@ -12,10 +12,10 @@ pub enum IncludingTagsInFilesSearched {
 }
 
 impl IncludingTagsInFilesSearched {
-    pub fn new() -> Self {
-        match Token::all() {
+    pub fn new(tags_reader: &TagsReader, files_searched: &[PathBuf]) -> Self {
+        match Token::from_reader(tags_reader) {
             Ok((ctags_path, _)) => IncludingTagsInFilesSearched::Success {
-                files_searched: CodebaseFiles::all().paths,
+                files_searched: files_searched.to_vec(),
                 ctags_path,
             },
             Err(e) => IncludingTagsInFilesSearched::Failure(format!("{}", e)),
@@ -51,4 +51,14 @@ impl CheckUp for IncludingTagsInFilesSearched {
             Err(e) => Status::Error(e),
         }
     }
+
+    fn fix(&self) -> Option<String> {
+        match self.tags_searched() {
+            Ok((ctags_path, _)) => Some(format!(
+                "Add {} to .gitignore, or move it to .git/tags",
+                ctags_path.file_name().unwrap_or_default().to_string_lossy()
+            )),
+            Err(_) => None,
+        }
+    }
 }
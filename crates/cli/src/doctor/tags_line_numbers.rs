@@ -0,0 +1,84 @@
+use super::check_up::{CheckUp, Status};
+use read_ctags::TagsFile;
+
+pub enum TagsLineNumbers {
+    Success { entries: usize, with_line: usize },
+    Failure(String),
+}
+
+impl TagsLineNumbers {
+    pub fn new(tags_file: Result<&TagsFile, &str>) -> Self {
+        match tags_file {
+            Ok(tags_file) => Self::Success {
+                entries: tags_file.tags.len(),
+                with_line: tags_file
+                    .tags
+                    .iter()
+                    .filter(|tag| tag.tags.contains_key("line"))
+                    .count(),
+            },
+            Err(e) => Self::Failure(e.to_string()),
+        }
+    }
+}
+
+impl CheckUp for TagsLineNumbers {
+    fn name(&self) -> &str {
+        "Does the tags file include line numbers (--fields=+n)?"
+    }
+
+    fn status(&self) -> Status {
+        match self {
+            Self::Success { entries, with_line } if with_line == entries => Status::OK(format!(
+                "{} of {} tag(s) include a line",
+                with_line, entries
+            )),
+            Self::Success { entries, with_line } => Status::Warn(format!(
+                "{} of {} tag(s) include a line; findings fall back to search patterns",
+                with_line, entries
+            )),
+            Self::Failure(e) => Status::Error(e.to_string()),
+        }
+    }
+
+    fn fix(&self) -> Option<String> {
+        match self {
+            Self::Success { .. } => Some(String::from(
+                "Regenerate tags with `ctags -R --fields=+n .`",
+            )),
+            Self::Failure(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use read_ctags::CtagItem;
+    use std::path::PathBuf;
+
+    fn status(tags: &str) -> Status {
+        let tags_file = CtagItem::parse(PathBuf::from("tags"), tags).unwrap();
+        TagsLineNumbers::new(Ok(&tags_file)).status()
+    }
+
+    #[test]
+    fn warns_when_tags_are_missing_line_numbers() {
+        let numbered = "full_name\tapp/person.rb\t/^  def full_name$/;\"\tf\tline:2\n";
+        let searched = "age\tapp/person.rb\t/^  def age$/;\"\tf\n";
+
+        assert!(matches!(
+            status(numbered),
+            Status::OK(message) if message == "1 of 1 tag(s) include a line"
+        ));
+        assert!(matches!(
+            status(&format!("{}{}", numbered, searched)),
+            Status::Warn(message)
+                if message == "1 of 2 tag(s) include a line; findings fall back to search patterns"
+        ));
+        assert!(matches!(
+            TagsLineNumbers::new(Err("No tags file found")).status(),
+            Status::Error(message) if message == "No tags file found"
+        ));
+    }
+}
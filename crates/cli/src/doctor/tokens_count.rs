@@ -1,4 +1,5 @@
 use super::check_up::{CheckUp, Status};
+use read_ctags::TagsReader;
 use token_search::Token;
 
 pub enum TokensCount {
@@ -7,8 +8,8 @@ pub enum TokensCount {
 }
 
 impl TokensCount {
-    pub fn new() -> Self {
-        match Token::all() {
+    pub fn new(tags_reader: &TagsReader) -> Self {
+        match Token::from_reader(tags_reader) {
             Ok((_, results)) => Self::Success(results.len()),
             Err(e) => Self::Failure(format!("{}", e)),
         }
@@ -33,4 +34,15 @@ impl CheckUp for TokensCount {
             Self::Failure(e) => Status::Error(e.to_string()),
         }
     }
+
+    fn fix(&self) -> Option<String> {
+        match self {
+            Self::Success(_) => Some(String::from(
+                "Check ctags ran from the project root and recognizes its languages",
+            )),
+            Self::Failure(_) => Some(String::from(
+                "Generate tags with `ctags -R --fields=+n .` from the project root, or pass --tags PATH",
+            )),
+        }
+    }
 }
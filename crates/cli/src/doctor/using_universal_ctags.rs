@@ -4,8 +4,8 @@ use read_ctags::TagsReader;
 pub struct UsingUniversalCtags(Option<String>);
 
 impl UsingUniversalCtags {
-    pub fn new() -> Self {
-        match TagsReader::default().load() {
+    pub fn new(tags_reader: &TagsReader) -> Self {
+        match tags_reader.load() {
             Ok(outcome) => Self(outcome.program.name),
             Err(_) => Self(None),
         }
@@ -30,4 +30,15 @@ impl CheckUp for UsingUniversalCtags {
             }
         }
     }
+
+    fn fix(&self) -> Option<String> {
+        match &self.0 {
+            None => Some(String::from(
+                "Regenerate tags with Universal Ctags (https://ctags.io/), which records its name in the tags file",
+            )),
+            Some(_) => Some(String::from(
+                "Install Universal Ctags (https://ctags.io/) and regenerate tags; Exuberant Ctags is unmaintained and omits fields unused relies on",
+            )),
+        }
+    }
 }
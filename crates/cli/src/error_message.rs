@@ -2,14 +2,38 @@ use colored::*;
 use read_ctags::ReadCtagsError;
 
 pub fn failed_token_parse(err: ReadCtagsError) {
+    if let ReadCtagsError::StaleTags(_) = err {
+        return stale_tags(&err);
+    }
+
     eprintln!("{}", "Failed to parse tags".red());
-    eprintln!("");
+    eprintln!();
     eprintln!("Uh oh!");
-    eprintln!("");
+    eprintln!();
     eprintln!("It looks there's an issue with your ctags file; either it doesn't exist, or the formatting is off.");
-    eprintln!("");
+    eprintln!();
     eprintln!("Ensure you've installed Universal Ctags (https://ctags.io/) and re-run it within your application.");
-    eprintln!("");
+    eprintln!();
     eprintln!("Error:");
     eprintln!("{}", format!("{}", err).cyan());
 }
+
+fn stale_tags(err: &ReadCtagsError) {
+    eprintln!("{}", "Tags file is out of date".red());
+    eprintln!();
+    eprintln!("Files changed after the tags file was generated, so results would be unreliable.");
+    eprintln!();
+    eprintln!("Re-run ctags within your application, or drop --strict-freshness to only warn.");
+    eprintln!();
+    eprintln!("Error:");
+    eprintln!("{}", err.to_string().cyan());
+}
+
+pub fn invalid_project_settings(err: &str) {
+    eprintln!("{}", "Failed to load project settings".red());
+    eprintln!();
+    eprintln!("Fix or remove the project's .unused.yml or unused.toml and re-run unused.");
+    eprintln!();
+    eprintln!("Error:");
+    eprintln!("{}", err.cyan());
+}
@@ -0,0 +1,240 @@
+use codebase_files::writes::Writes;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+// Extension marking a cached failure, stored alongside the last successful result
+const NEGATIVE_EXTENSION: &str = "miss";
+
+/// How long cached results from external evidence sources stay fresh
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CacheTtl {
+    /// Lifetime of successful results
+    pub positive: Duration,
+    /// Lifetime of failures, during which the source isn't consulted again
+    pub negative: Duration,
+}
+
+impl Default for CacheTtl {
+    fn default() -> Self {
+        CacheTtl {
+            positive: Duration::from_secs(60 * 60 * 24),
+            negative: Duration::from_secs(60 * 15),
+        }
+    }
+}
+
+/// Results from an external evidence source (such as a remote ignore list), cached on disk so
+/// repeated runs don't pay for the same network requests or git commands
+///
+/// Failures are cached too: until they expire, the last successful result is reused if there is
+/// one, and otherwise the cached failure is returned without consulting the source.
+pub struct EvidenceCache {
+    root: Option<PathBuf>,
+    ttl: CacheTtl,
+    writes: Writes,
+}
+
+impl EvidenceCache {
+    /// A cache for one kind of source, stored in its own directory beneath the user cache
+    ///
+    /// Results are only cached when `writes` allows it; otherwise the source is consulted every
+    /// time a fresh result isn't already cached.
+    pub fn new(namespace: &str, ttl: CacheTtl, writes: Writes) -> Self {
+        EvidenceCache {
+            root: Self::path(namespace),
+            ttl,
+            writes,
+        }
+    }
+
+    /// Directory where results for `namespace` are cached
+    pub fn path(namespace: &str) -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("unused").join(namespace))
+    }
+
+    /// Return a fresh cached result for `key`, or consult the source with `fetch` and cache it
+    pub fn get_or_fetch<F>(&self, key: &str, fetch: F) -> Result<String, String>
+    where
+        F: FnOnce() -> Result<String, String>,
+    {
+        let root = match &self.root {
+            Some(root) => root,
+            None => return fetch(),
+        };
+        let positive = root.join(file_name(key));
+        let negative = positive.with_extension(NEGATIVE_EXTENSION);
+
+        if let Some(contents) = read_fresh(&positive, self.ttl.positive) {
+            return Ok(contents);
+        }
+
+        let outcome = match read_fresh(&negative, self.ttl.negative) {
+            Some(error) => Err(error),
+            None => {
+                let outcome = fetch();
+                match &outcome {
+                    Ok(contents) => {
+                        self.writes.write(&positive, contents).ok();
+                        self.writes.remove(&negative).ok();
+                    }
+                    Err(e) => {
+                        self.writes.write(&negative, e).ok();
+                    }
+                }
+                outcome
+            }
+        };
+
+        outcome.or_else(|e| fs::read_to_string(&positive).map_err(|_| e))
+    }
+}
+
+/// Parse a duration such as `90s`, `15m`, `24h`, or `7d`; plain numbers are seconds
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let (amount, unit) = match input.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => input.split_at(index),
+        None => (input, "s"),
+    };
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        _ => return Err(format!("Unable to parse duration: {}", input)),
+    };
+
+    amount
+        .parse::<u64>()
+        .map(|amount| Duration::from_secs(amount * multiplier))
+        .map_err(|_| format!("Unable to parse duration: {}", input))
+}
+
+/// Name of the file caching results for `key`, such as a URL
+///
+/// Names are the SHA-256 of the whole key, so keys differing only in punctuation (like
+/// `https://a.b/c-d` and `https://a.b/c_d`) don't share a file.
+pub fn file_name(key: &str) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn read_fresh(path: &Path, ttl: Duration) -> Option<String> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    let age = SystemTime::now().duration_since(modified).ok()?;
+
+    if age < ttl {
+        fs::read_to_string(path).ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn cache(name: &str, ttl: CacheTtl) -> EvidenceCache {
+        let root = std::env::temp_dir().join(name);
+        fs::remove_dir_all(&root).ok();
+
+        EvidenceCache {
+            root: Some(root),
+            ttl,
+            writes: Writes::Enabled,
+        }
+    }
+
+    #[test]
+    fn caches_failures_until_they_expire() {
+        let calls = Cell::new(0);
+        let failing = || {
+            calls.set(calls.get() + 1);
+            Err(String::from("connection refused"))
+        };
+        let cache = cache("unused_caches_failures", CacheTtl::default());
+
+        assert_eq!(
+            cache.get_or_fetch("https://example.com/list.yml", failing),
+            Err(String::from("connection refused"))
+        );
+        assert_eq!(
+            cache.get_or_fetch("https://example.com/list.yml", failing),
+            Err(String::from("connection refused"))
+        );
+        assert_eq!(calls.get(), 1);
+
+        let expired = EvidenceCache {
+            ttl: CacheTtl {
+                negative: Duration::from_secs(0),
+                ..CacheTtl::default()
+            },
+            ..cache
+        };
+
+        assert_eq!(
+            expired.get_or_fetch("https://example.com/list.yml", || Ok(String::from("ok"))),
+            Ok(String::from("ok"))
+        );
+        assert_eq!(
+            expired.get_or_fetch("https://example.com/list.yml", failing),
+            Ok(String::from("ok"))
+        );
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn reuses_stale_results_when_the_source_fails() {
+        let cache = cache(
+            "unused_reuses_stale_results",
+            CacheTtl {
+                positive: Duration::from_secs(0),
+                ..CacheTtl::default()
+            },
+        );
+
+        assert_eq!(
+            cache.get_or_fetch("git-log", || Ok(String::from("abc123"))),
+            Ok(String::from("abc123"))
+        );
+        assert_eq!(
+            cache.get_or_fetch("git-log", || Err(String::from("not a git repository"))),
+            Ok(String::from("abc123"))
+        );
+    }
+
+    #[test]
+    fn caches_urls_differing_only_in_punctuation_separately() {
+        let cache = cache("unused_caches_urls_separately", CacheTtl::default());
+
+        assert_ne!(file_name("https://a.b/c-d"), file_name("https://a.b/c_d"));
+        assert_eq!(
+            cache.get_or_fetch("https://a.b/c-d", || Ok(String::from("dashed"))),
+            Ok(String::from("dashed"))
+        );
+        assert_eq!(
+            cache.get_or_fetch("https://a.b/c_d", || Ok(String::from("underscored"))),
+            Ok(String::from("underscored"))
+        );
+        assert_eq!(
+            cache.get_or_fetch("https://a.b/c-d", || Err(String::from("not cached"))),
+            Ok(String::from("dashed"))
+        );
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("15m"), Ok(Duration::from_secs(900)));
+        assert_eq!(parse_duration("2d"), Ok(Duration::from_secs(172_800)));
+        assert_eq!(
+            parse_duration("soon"),
+            Err(String::from("Unable to parse duration: soon"))
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use structopt::clap::ErrorKind;
+
+/// Exit status reported by the `unused` binary
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExitStatus {
+    /// Completed without findings exceeding the configured thresholds
+    Clean,
+    /// Findings exceeded `--max-unused`, any were found with `--fail-on-unused`, or `tags validate`
+    /// found malformed lines
+    Findings,
+    /// unused was unable to complete, e.g. because tags couldn't be read or a flag was misused
+    Error,
+}
+
+impl ExitStatus {
+    pub fn code(&self) -> i32 {
+        match self {
+            ExitStatus::Clean => 0,
+            ExitStatus::Findings => 1,
+            ExitStatus::Error => 2,
+        }
+    }
+
+    /// Status for `findings` reported, given `--fail-on-unused` and `--max-unused`
+    pub fn for_findings(findings: usize, fail_on_unused: bool, max_unused: Option<usize>) -> Self {
+        let exceeds_maximum = max_unused.is_some_and(|max| findings > max);
+
+        if exceeds_maximum || (fail_on_unused && findings > 0) {
+            ExitStatus::Findings
+        } else {
+            ExitStatus::Clean
+        }
+    }
+
+    /// Status for flags that couldn't be parsed, which is an error unless help or the version was
+    /// asked for, so a mistyped flag is never mistaken for findings
+    pub fn for_usage(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::HelpDisplayed | ErrorKind::VersionDisplayed => ExitStatus::Clean,
+            _ => ExitStatus::Error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fails_on_any_finding_with_fail_on_unused() {
+        assert_eq!(ExitStatus::for_findings(0, true, None), ExitStatus::Clean);
+        assert_eq!(
+            ExitStatus::for_findings(1, true, None),
+            ExitStatus::Findings
+        );
+        assert_eq!(ExitStatus::for_findings(1, false, None), ExitStatus::Clean);
+    }
+
+    #[test]
+    fn fails_only_beyond_max_unused() {
+        assert_eq!(
+            ExitStatus::for_findings(5, false, Some(5)),
+            ExitStatus::Clean
+        );
+        assert_eq!(
+            ExitStatus::for_findings(6, false, Some(5)),
+            ExitStatus::Findings
+        );
+        assert_eq!(
+            ExitStatus::for_findings(0, false, Some(0)),
+            ExitStatus::Clean
+        );
+        assert_eq!(
+            ExitStatus::for_findings(1, false, Some(0)),
+            ExitStatus::Findings
+        );
+    }
+
+    #[test]
+    fn reports_usage_errors_apart_from_findings() {
+        assert_eq!(
+            ExitStatus::for_usage(ErrorKind::InvalidValue).code(),
+            ExitStatus::Error.code()
+        );
+        assert_eq!(
+            ExitStatus::for_usage(ErrorKind::UnknownArgument),
+            ExitStatus::Error
+        );
+        assert_eq!(
+            ExitStatus::for_usage(ErrorKind::HelpDisplayed),
+            ExitStatus::Clean
+        );
+        assert_eq!(
+            ExitStatus::for_usage(ErrorKind::VersionDisplayed),
+            ExitStatus::Clean
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use super::cli_configuration::CliConfiguration;
+use super::formatters::{
+    self,
+    internal::{configuration_warnings, print_json},
+};
+use super::sources::Sources;
+use super::Format;
+use colored::*;
+use itertools::Itertools;
+use read_ctags::CtagItem;
+use token_analysis::{CleanupValue, TokenUsage};
+
+pub fn render(cli_config: &CliConfiguration, token: &str) {
+    match cli_config.find(token) {
+        Some(usage) => match cli_config.format() {
+            Format::Json => print_json(&formatters::json::finding(usage)),
+            _ => explain(cli_config, usage),
+        },
+        None => eprintln!(
+            "{} wasn't analyzed; it may not be defined in the tags file, or it was skipped for being too short or containing spaces",
+            token.yellow()
+        ),
+    }
+
+    configuration_warnings(cli_config);
+}
+
+fn explain(cli_config: &CliConfiguration, usage: &TokenUsage) {
+    let likelihood = &usage.usage_likelihood;
+    let counts = &usage.file_type_counts;
+
+    println!("{}", usage.result.token.token.white());
+    println!(
+        "   Likelihood: {} (score {})",
+        likelihood.status.to_string().cyan(),
+        likelihood.score
+    );
+    println!(
+        "   Reason: {} [{}]",
+        likelihood.reason.cyan(),
+        likelihood.rule
+    );
+    println!(
+        "   Rule: {} ({})",
+        likelihood.rule.name(),
+        likelihood.rule.description()
+    );
+    let cleanup = CleanupValue::estimate(usage);
+    println!(
+        "   Cleanup value: {} ({} lines × {:.0}% confidence)",
+        cleanup.value.to_string().yellow(),
+        cleanup.lines,
+        cleanup.confidence * 100.0
+    );
+    println!(
+        "   Reported with current filters: {}",
+        if cli_config.reported(usage) {
+            "yes"
+        } else {
+            "no"
+        }
+    );
+
+    println!("   Defined in:");
+    for definition in usage
+        .result
+        .token
+        .definitions
+        .iter()
+        .sorted_by_key(|d| (&d.file_path, &d.address))
+    {
+        println!(
+            "   * {} ({:?})",
+            location(definition, cli_config.sources()).yellow(),
+            definition.kind
+        );
+    }
+
+    println!(
+        "   Occurrences: {} across {} files (application: {}, test: {}, config: {}, unknown: {})",
+        counts.total().occurrence_count,
+        counts.total().file_count,
+        counts.app.occurrence_count,
+        counts.test.occurrence_count,
+        counts.config.occurrence_count,
+        counts.unknown.occurrence_count
+    );
+    for (path, count) in usage.result.occurrences.iter().sorted() {
+        println!("   * {}: {}", path.to_string_lossy().yellow(), count);
+    }
+
+    if let Some(scopes) = &usage.result.scopes {
+        println!("   Occurrences by scope ({} ambiguous):", scopes.ambiguous);
+        for (scope, count) in &scopes.scopes {
+            println!("   * {}: {}", scope.yellow(), count);
+        }
+    }
+}
+
+// Line-number addresses are already part of the location
+fn location(definition: &CtagItem, sources: &Sources) -> String {
+    match sources.position(definition).0 {
+        Some(line) if definition.address == line.to_string() => {
+            format!("{}:{}", definition.file_path.to_string_lossy(), line)
+        }
+        Some(line) => format!(
+            "{}:{} {}",
+            definition.file_path.to_string_lossy(),
+            line,
+            definition.address
+        ),
+        None => format!(
+            "{} {}",
+            definition.file_path.to_string_lossy(),
+            definition.address
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codebase_files::MemoryFileSystem;
+    use read_ctags::{Language, TokenKind};
+    use std::collections::BTreeMap;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    fn definition(path: &Path, address: &str) -> CtagItem {
+        CtagItem {
+            name: "full_name".to_string(),
+            file_path: path.into(),
+            raw_path: path.to_string_lossy().into(),
+            address: address.to_string(),
+            language: Some(Language::Ruby),
+            tags: BTreeMap::new(),
+            kind: TokenKind::Method,
+        }
+    }
+
+    #[test]
+    fn locates_definitions_by_their_resolved_line() {
+        let file_system = MemoryFileSystem::default();
+        file_system.insert(
+            "app/models/person.rb",
+            "class Person\n  def full_name\n  end\nend\n",
+        );
+        let sources = Sources::new(Arc::new(file_system));
+        let path = Path::new("app/models/person.rb");
+        let missing = Path::new("app/models/missing.rb");
+
+        let numbered = location(&definition(path, "2"), &sources);
+        let searched = location(&definition(path, "/^  def full_name$/"), &sources);
+        let unresolved = location(&definition(missing, "/^  def full_name$/"), &sources);
+
+        assert_eq!(numbered, "app/models/person.rb:2");
+        assert_eq!(searched, "app/models/person.rb:2 /^  def full_name$/");
+        assert_eq!(unresolved, "app/models/missing.rb /^  def full_name$/");
+    }
+}
@@ -0,0 +1,217 @@
+use super::cli_configuration::CliConfiguration;
+use super::sources::Sources;
+use colored::*;
+use read_ctags::CtagItem;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use token_analysis::{Confidence, TokenUsage, UsageLikelihoodStatus};
+
+// Unchanged lines shown around each removal, as `diff -u` does
+const CONTEXT: usize = 3;
+
+/// Remove the definitions of high-likelihood findings with at least `min_confidence`, printing
+/// the removals as a unified diff or (with `write`) applying them
+///
+/// Only tokens whose every definition has a range ctags recorded (with `--fields=+e`) are
+/// removed; others are counted as skipped, since guessing where a definition ends could delete
+/// live code.
+pub fn render(cli_config: &CliConfiguration, write: bool, min_confidence: u8) {
+    let findings = cli_config.findings();
+    let (fixable, skipped): (Vec<&TokenUsage>, Vec<&TokenUsage>) = findings
+        .into_iter()
+        .filter(|usage| is_high_confidence(usage, min_confidence))
+        .partition(|usage| removals(usage, cli_config.sources()).is_some());
+
+    let mut by_file: BTreeMap<&Path, Vec<(usize, usize)>> = BTreeMap::new();
+    for usage in &fixable {
+        for (path, range) in removals(usage, cli_config.sources()).unwrap_or_default() {
+            by_file.entry(path).or_default().push(range);
+        }
+    }
+
+    let mut files = 0;
+    for (path, ranges) in by_file {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                eprintln!("Unable to read {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+        let ranges = merged(ranges, &lines);
+        if ranges.is_empty() {
+            eprintln!(
+                "Skipping {}: its definitions extend past the end of the file",
+                path.display()
+            );
+            continue;
+        }
+
+        if write {
+            match cli_config.writes().write(path, without(&lines, &ranges)) {
+                Ok(()) => files += 1,
+                Err(e) => eprintln!("Unable to write {}: {}", path.display(), e),
+            }
+        } else {
+            print!("{}", unified_diff(path, &lines, &ranges));
+            files += 1;
+        }
+    }
+
+    let summary = format!(
+        "{} {} tokens from {} files",
+        if write { "Removed" } else { "Would remove" },
+        fixable.len(),
+        files
+    );
+    eprintln!("{}", summary.green());
+    if !skipped.is_empty() {
+        eprintln!(
+            "{}",
+            format!(
+                "Skipped {} findings without an end line; generate tags with --fields=+e to fix them",
+                skipped.len()
+            )
+            .yellow()
+        );
+    }
+}
+
+fn is_high_confidence(usage: &TokenUsage, min_confidence: u8) -> bool {
+    usage.usage_likelihood.status == UsageLikelihoodStatus::High
+        && Confidence::estimate(usage).score >= min_confidence
+}
+
+// Lines spanned by each of the token's definitions (1-based and inclusive), or `None` when any
+// of them can't be located or has no recorded end
+fn removals<'a>(
+    usage: &'a TokenUsage,
+    sources: &Sources,
+) -> Option<Vec<(&'a Path, (usize, usize))>> {
+    usage
+        .result
+        .token
+        .definitions
+        .iter()
+        .map(|definition: &CtagItem| {
+            let start = sources.position(definition).0?;
+            let end = definition.end_line().filter(|end| *end >= start)?;
+            Some((&*definition.file_path, (start, end)))
+        })
+        .collect()
+}
+
+// Ranges sorted and merged, so definitions nested within others (such as methods of a class
+// that's removed) are removed once; a blank line following a definition is removed with it
+// when the definition is preceded by one too, so removals don't leave a double gap
+//
+// Ranges beyond the end of the file come from stale tags, so none are removed from it then.
+fn merged(mut ranges: Vec<(usize, usize)>, lines: &[&str]) -> Vec<(usize, usize)> {
+    if ranges.iter().any(|(_, end)| *end > lines.len()) {
+        return vec![];
+    }
+    ranges.sort_unstable();
+
+    let blank = |line: usize| lines[line - 1].trim().is_empty();
+    let mut merged: Vec<(usize, usize)> = vec![];
+    for (start, mut end) in ranges {
+        if end < lines.len() && blank(end + 1) && (start == 1 || blank(start - 1)) {
+            end += 1;
+        }
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+fn removed(ranges: &[(usize, usize)], line: usize) -> bool {
+    ranges
+        .iter()
+        .any(|(start, end)| (*start..=*end).contains(&line))
+}
+
+fn without(lines: &[&str], ranges: &[(usize, usize)]) -> String {
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !removed(ranges, index + 1))
+        .map(|(_, line)| *line)
+        .collect()
+}
+
+// A unified diff removing `ranges` from the file at `path`, with hunks whose context would
+// overlap combined, as `diff -u` writes them
+fn unified_diff(path: &Path, lines: &[&str], ranges: &[(usize, usize)]) -> String {
+    let mut hunks: Vec<(usize, usize)> = vec![];
+    for (start, end) in ranges {
+        let hunk = (
+            start.saturating_sub(CONTEXT).max(1),
+            (end + CONTEXT).min(lines.len()),
+        );
+        match hunks.last_mut() {
+            Some(last) if hunk.0 <= last.1 + 1 => last.1 = hunk.1,
+            _ => hunks.push(hunk),
+        }
+    }
+
+    let path = path.to_string_lossy();
+    let mut diff = format!("--- a/{}\n+++ b/{}\n", path, path);
+    let mut offset = 0;
+    for (start, end) in hunks {
+        let length = end - start + 1;
+        let deleted = (start..=end).filter(|line| removed(ranges, *line)).count();
+        let new_length = length - deleted;
+        let new_start = if new_length == 0 {
+            start - offset - 1
+        } else {
+            start - offset
+        };
+        writeln!(
+            diff,
+            "@@ -{},{} +{},{} @@",
+            start, length, new_start, new_length
+        )
+        .ok();
+
+        for line in start..=end {
+            let text = lines[line - 1];
+            diff.push(if removed(ranges, line) { '-' } else { ' ' });
+            diff.push_str(text);
+            if !text.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        offset += deleted;
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERSON: &str = "class Person\n  def name\n    @name\n  end\n\n  def legacy\n    nil\n  end\n\n  def age\n    @age\n  end\nend\n";
+
+    #[test]
+    fn removes_definitions_as_a_unified_diff() {
+        let lines: Vec<&str> = PERSON.split_inclusive('\n').collect();
+        let ranges = merged(vec![(6, 8), (7, 7)], &lines);
+
+        assert_eq!(ranges, vec![(6, 9)]);
+        assert_eq!(
+            without(&lines, &ranges),
+            "class Person\n  def name\n    @name\n  end\n\n  def age\n    @age\n  end\nend\n"
+        );
+        assert_eq!(
+            unified_diff(Path::new("app/person.rb"), &lines, &ranges),
+            "--- a/app/person.rb\n+++ b/app/person.rb\n@@ -3,10 +3,6 @@\n     @name\n   end\n \n-  def legacy\n-    nil\n-  end\n-\n   def age\n     @age\n   end\n"
+        );
+        assert!(merged(vec![(12, 20)], &lines).is_empty());
+    }
+}
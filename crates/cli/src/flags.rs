@@ -1,15 +1,265 @@
-use read_ctags::Language;
+use super::messages::Lang;
+use super::remote_tags::Checksum;
+use super::schedule::CronSchedule;
+use codebase_files::ExcludeGlobs;
+use project_configuration::TokenPattern;
+use read_ctags::{KindPattern, Language, TagsFormat};
+use std::path::PathBuf;
 use std::str::FromStr;
 use structopt::StructOpt;
-use token_analysis::{OrderField, UsageLikelihoodStatus};
+use token_analysis::{OrderField, SampleRate, UsageLikelihoodStatus};
 
 #[derive(Debug, StructOpt)]
 pub enum Command {
+    /// Analyze the codebase for potentially unused tokens (the default)
+    Analyze(AnalysisFlags),
+
+    /// Summarize findings by likelihood and rule
+    Report(AnalysisFlags),
+
     /// Run diagnostics to identify any potential issues running unused
     Doctor,
 
+    /// Inspect the tags file used for analysis
+    Tags(TagsCommand),
+
+    /// Build or refresh the occurrence index used by --incremental
+    Index(AnalysisFlags),
+
+    /// Inspect or clear cached data
+    Cache(CacheCommand),
+
+    /// Manage the kinds of definitions recognized in tags files
+    Kinds(KindsCommand),
+
+    /// Keep results in memory and answer line-delimited queries on STDIN
+    Serve {
+        /// Rescan from scratch on this cron schedule (in UTC), e.g. "0 3 * * *"
+        ///
+        /// Other rescans reuse per-file scans for files that haven't changed. Overrides
+        /// `serve.full_rescan` in the project settings.
+        #[structopt(long)]
+        full_rescan: Option<CronSchedule>,
+
+        #[structopt(flatten)]
+        analysis: AnalysisFlags,
+    },
+
+    /// Keep tags and scans in memory and answer JSON-RPC 2.0 requests, for editor integrations
+    ///
+    /// Requests are read one per line from STDIN, or from clients of a unix socket with
+    /// `--socket`. Methods are `findings`, `lookupToken`, `analyzeFile`, `rescan`, and `shutdown`.
+    Daemon {
+        /// Listen on this unix socket rather than STDIN
+        #[structopt(long, parse(from_os_str))]
+        socket: Option<PathBuf>,
+
+        #[structopt(flatten)]
+        analysis: AnalysisFlags,
+    },
+
+    /// Explain how a single token was classified
+    Explain {
+        /// Token to explain
+        token: String,
+
+        #[structopt(flatten)]
+        analysis: AnalysisFlags,
+    },
+
+    /// Remove the definitions of high-confidence findings, for review as a patch
+    ///
+    /// Only findings whose every definition has an end line in the tags file (written by
+    /// Universal Ctags with `--fields=+e`) are removed. Prints a unified diff to STDOUT unless
+    /// --write is passed.
+    Fix {
+        /// Print the removals as a unified diff without changing any files (the default)
+        #[structopt(long, conflicts_with = "write")]
+        dry_run: bool,
+
+        /// Remove the definitions from their files
+        #[structopt(long)]
+        write: bool,
+
+        /// Only remove findings with at least this confidence score (from 0 to 100), as listed
+        /// by --sort confidence
+        #[structopt(long, default_value = "50")]
+        min_confidence: u8,
+
+        #[structopt(flatten)]
+        analysis: AnalysisFlags,
+    },
+
+    /// List the most-referenced tokens and files
+    Hot {
+        /// Number of tokens and files to list
+        #[structopt(long, default_value = "50")]
+        top: usize,
+
+        #[structopt(flatten)]
+        analysis: AnalysisFlags,
+    },
+
+    /// Summarize the codebase's tokens before acting on findings
+    Stats(StatsCommand),
+
+    /// Record findings over time, to show whether dead code is shrinking
+    History(HistoryCommand),
+
+    /// Run unused from git hooks, blocking commits that introduce unused definitions
+    Hook(HookCommand),
+
+    /// Write a GraphViz DOT graph of references between files (or scopes) to STDOUT
+    ///
+    /// Edges point from where tokens are referenced to where they're defined, weighted by the
+    /// number of references; nodes defining tokens likely to be unused are filled.
+    Graph {
+        /// Draw a node per file or per scope (class, module, ...) of the definitions
+        #[structopt(long, default_value = "file", possible_values = &["file", "scope"], case_insensitive = true)]
+        nodes: GraphNodes,
+
+        #[structopt(flatten)]
+        analysis: AnalysisFlags,
+    },
+
+    /// List pub Rust items only used within their own crate, as candidates for pub(crate)
+    Visibility(AnalysisFlags),
+
+    /// Compare two JSON reports, listing newly unused and resolved tokens
+    ///
+    /// Exits with status 1 when the new report has findings the old one didn't, to gate pull
+    /// requests on reports from their base and head.
+    Compare {
+        /// JSON report from before the changes (written with --format json)
+        #[structopt(parse(from_os_str))]
+        old: PathBuf,
+
+        /// JSON report from after the changes
+        #[structopt(parse(from_os_str))]
+        new: PathBuf,
+    },
+
     /// Write the default YAML configuration to STDOUT
     DefaultYaml,
+
+    /// List the rules used to classify tokens
+    Rules,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum TagsCommand {
+    /// Display the location, generator, and size of the tags file
+    Info,
+
+    /// List the definitions parsed from the tags file
+    Parse {
+        /// Tags file to parse instead of the one used for analysis (`-` reads STDIN)
+        #[structopt(parse(from_os_str))]
+        path: Option<PathBuf>,
+    },
+
+    /// Check every line of the tags file parses, listing any malformed lines
+    ///
+    /// Each tag is also checked against the working tree, listing tags in files that no longer
+    /// exist, tags whose address no longer resolves to a line, and duplicate entries. Exits with
+    /// status 1 when any line is malformed or any tag is dangling.
+    Validate {
+        /// Tags file to check instead of the one used for analysis (`-` reads STDIN)
+        #[structopt(parse(from_os_str))]
+        path: Option<PathBuf>,
+    },
+
+    /// Find tags by name, as `readtags` does
+    ///
+    /// Lists matching tags as lines of a tags file, or as JSON with --format json.
+    Find {
+        /// Name of the tags to find
+        name: String,
+
+        /// Find tags whose names start with NAME
+        #[structopt(long)]
+        prefix: bool,
+
+        /// Ignore case when comparing names
+        #[structopt(long)]
+        icase: bool,
+
+        /// Only find tags of these kinds, by ctags' kind letter or full name, e.g. 'f' or
+        /// 'method,function'
+        #[structopt(long, use_delimiter = true)]
+        kind: Vec<KindPattern>,
+    },
+
+    /// Compare definitions between two tags files or git revisions
+    ///
+    /// Each side is either a path to a tags file or a git revision, whose files are exported to
+    /// a temporary directory and tagged with ctags.
+    Diff { old: String, new: String },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum StatsCommand {
+    /// Show how many tokens occur once, 2-5 times, and so on, the most-referenced tokens, and
+    /// occurrences by kind
+    Tokens {
+        /// Number of most-referenced tokens to list
+        #[structopt(long, default_value = "10")]
+        top: usize,
+
+        #[structopt(flatten)]
+        analysis: Box<AnalysisFlags>,
+    },
+
+    /// Show the tags file's entries by language and kind, the files with the most definitions,
+    /// and any malformed lines
+    Tags {
+        /// Number of files with the most definitions to list
+        #[structopt(long, default_value = "10")]
+        top: usize,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum KindsCommand {
+    /// Import kinds from `ctags --list-kinds-full`, so definitions from languages and kinds
+    /// added to ctags since this release are recognized
+    Import,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum HistoryCommand {
+    /// Analyze the codebase and append a summary of the run (its time, commit, and findings by
+    /// category) to .unused-history.jsonl
+    Record(Box<AnalysisFlags>),
+
+    /// Show recorded runs as a table of findings over time, or as JSON with --format json
+    Show {
+        /// Only show this many of the most recent runs
+        #[structopt(long)]
+        last: Option<usize>,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum HookCommand {
+    /// Analyze files staged in git, exiting with status 1 when they introduce definitions likely
+    /// to be unused
+    ///
+    /// Tokens the committed versions of the staged files already mention aren't reported, so
+    /// existing findings don't block commits. Occurrence counts are cached by file contents.
+    PreCommit(Box<AnalysisFlags>),
+
+    /// Install a git pre-commit hook running `unused hook pre-commit`
+    Install,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum CacheCommand {
+    /// List cache locations
+    Path,
+
+    /// Remove cached occurrence counts and ignore lists
+    Clear,
 }
 
 #[derive(Debug, StructOpt)]
@@ -19,18 +269,135 @@ pub enum Command {
     setting = structopt::clap::AppSettings::ColoredHelp
 )]
 pub struct Flags {
-    /// Disable color output
-    #[structopt(long)]
+    #[structopt(flatten)]
+    pub global: GlobalFlags,
+
+    #[structopt(flatten)]
+    pub analysis: AnalysisFlags,
+
+    #[structopt(subcommand)]
+    pub cmd: Option<Command>,
+}
+
+// Flags shared by every subcommand
+#[derive(Debug, StructOpt)]
+pub struct GlobalFlags {
+    /// Read project configurations from this YAML file instead of ~/.config/unused/unused.yml
+    #[structopt(long, global = true, parse(from_os_str))]
+    pub config: Option<PathBuf>,
+
+    /// Disable color output; the same as `--color never`
+    #[structopt(long, global = true)]
     pub no_color: bool,
 
-    /// Disable summary
-    #[structopt(long)]
-    pub no_summary: bool,
+    /// When to color output [default: auto]
+    ///
+    /// `auto` colors output written to a terminal unless the NO_COLOR environment variable is set.
+    #[structopt(long, global = true, value_name = "WHEN", possible_values = &["auto", "always", "never"], case_insensitive = true)]
+    pub color: Option<ColorChoice>,
+
+    /// Never write to the filesystem
+    ///
+    /// Caches aren't updated, output can only go to STDOUT, and commands that change files fail.
+    #[structopt(long, global = true)]
+    pub no_write: bool,
 
     /// Render output as JSON
-    #[structopt(long)]
+    #[structopt(long, global = true)]
     pub json: bool,
 
+    /// Render output in the porcelain format, whose layout is stable across releases
+    ///
+    /// One tab-separated line per definition, following a `# unused porcelain v1` line; see the
+    /// README for its fields. Meant for scripts and editor plugins.
+    #[structopt(long, global = true, conflicts_with = "json")]
+    pub porcelain: bool,
+
+    /// Overwrite report files (from FORMAT=PATH) that weren't written by unused
+    #[structopt(long, global = true)]
+    pub force: bool,
+
+    /// Language of report strings, such as en or de [default: from LC_ALL, LC_MESSAGES, or LANG]
+    ///
+    /// Applies to standard and compact output; machine-readable formats are always in English.
+    #[structopt(long, global = true)]
+    pub lang: Option<Lang>,
+
+    /// Format output [default: standard]
+    ///
+    /// One of standard (or terminal), compact, json, sarif, csv, tsv, html, junit, checkstyle,
+    /// quickfix, github, codeclimate (or gitlab), or porcelain. Repeat with FORMAT=PATH to also write formats
+    /// to files from the same run, e.g. `--format json=unused.json --format terminal`.
+    #[structopt(long, global = true, number_of_values = 1)]
+    pub format: Vec<FormatTarget>,
+
+    /// Read tags from this file instead of searching for .git/tags, tags, or tmp/tags (`-` reads
+    /// STDIN)
+    ///
+    /// An https:// URL (or s3:// URL, when built with the `s3` feature) fetches a centrally
+    /// generated tags file, gzipped or not, and caches it for the `cache.ttl` project setting.
+    #[structopt(long, global = true, parse(from_os_str))]
+    pub tags: Option<PathBuf>,
+
+    /// SHA-256 checksum a remote tags file must match, as sha256:HEX
+    ///
+    /// The cached copy is reused while the checksum is unchanged, without fetching it again.
+    #[structopt(long, global = true, requires = "tags")]
+    pub tags_checksum: Option<Checksum>,
+
+    /// Read every tags file found (.git/tags, tags, and tmp/tags), merging their tags, rather than
+    /// only the first
+    #[structopt(long, global = true, conflicts_with = "tags")]
+    pub all_tags: bool,
+
+    /// Project root as seen by ctags, such as /app for tags generated in a container [default:
+    /// current directory]
+    ///
+    /// Absolute tag paths beneath it, and paths relative to where ctags ran (recorded by
+    /// Universal Ctags), are made relative to it to match the files searched.
+    #[structopt(long, global = true, parse(from_os_str))]
+    pub tags_root: Option<PathBuf>,
+
+    /// Format of tags files [default: auto, detecting `ctags --output-format=json` tags]
+    #[structopt(long, global = true, possible_values = &TagsFormat::variants(), case_insensitive = true)]
+    pub tags_format: Option<TagsFormat>,
+
+    /// Follow symlinked files and directories when walking the codebase
+    ///
+    /// Symlinks are skipped otherwise. Loops are skipped, and files reachable through several
+    /// paths are only searched once, so their occurrences aren't counted twice.
+    #[structopt(long, global = true)]
+    pub follow_symlinks: bool,
+
+    /// Count references in documentation separately, reporting tokens only referenced in docs
+    ///
+    /// References in Markdown and RDoc files, and in comments (such as YARD tags), don't count
+    /// as usage; tokens with no other references are reported at medium likelihood.
+    #[structopt(long, global = true)]
+    pub doc_references: bool,
+
+    /// Skip malformed lines in tags files, warning about each, rather than failing
+    #[structopt(long, global = true)]
+    pub lenient_tags: bool,
+
+    /// Log additional detail to STDERR; repeat (-vv, -vvv) for more
+    ///
+    /// -v logs what was loaded and searched, and -vv also logs what was skipped and why.
+    #[structopt(long = "verbose", short = "v", global = true, parse(from_occurrences))]
+    pub verbosity: u8,
+
+    /// Only log errors, hiding warnings and progress
+    #[structopt(long, short = "q", global = true, conflicts_with = "verbosity")]
+    pub quiet: bool,
+}
+
+// Flags controlling how tokens are analyzed and reported
+#[derive(Clone, Debug, StructOpt)]
+pub struct AnalysisFlags {
+    /// Disable summary
+    #[structopt(long)]
+    pub no_summary: bool,
+
     /// Hide progress bar
     #[structopt(long, short = "P")]
     pub no_progress: bool,
@@ -39,15 +406,30 @@ pub struct Flags {
     #[structopt(long, short = "a")]
     pub all_likelihoods: bool,
 
-    /// Limit token output to those that match the provided likelihood(s)
+    /// Limit token output to those that match the provided likelihood(s) [default: high]
     ///
     /// This allows for a comma-delimited list of likelihoods.
-    #[structopt(long = "likelihood", short = "l", use_delimiter = true, default_value = "high", possible_values = &["high", "medium", "low"])]
+    #[structopt(long = "likelihood", short = "l", use_delimiter = true, possible_values = &["high", "medium", "low"])]
     pub likelihoods: Vec<UsageLikelihoodStatus>,
 
-    /// Sort output
-    #[structopt(long, possible_values = &OrderField::variants(), default_value, case_insensitive = true)]
-    pub sort_order: OrderField,
+    /// Limit token output to those at least as likely to be unused as the provided likelihood
+    #[structopt(long, possible_values = &["high", "medium", "low"], conflicts_with_all = &["likelihoods", "all-likelihoods"])]
+    pub min_likelihood: Option<UsageLikelihoodStatus>,
+
+    /// Also report tokens referenced at most this many times outside their definitions,
+    /// whatever their likelihood
+    ///
+    /// This finds nearly-dead code, such as helpers with a single caller worth inlining.
+    #[structopt(long, value_name = "N")]
+    pub max_occurrences: Option<usize>,
+
+    /// Sort output [default: token]
+    ///
+    /// `value` lists the largest likely-safe deletions first: estimated lines deleted, weighted
+    /// by the likelihood score. `confidence` lists the safest deletions first, weighing the kinds
+    /// of definitions, references, and the risk of dynamic calls.
+    #[structopt(long, alias = "sort", possible_values = &OrderField::variants(), case_insensitive = true)]
+    pub sort_order: Option<OrderField>,
 
     /// Reverse sort order
     #[structopt(long)]
@@ -61,9 +443,33 @@ pub struct Flags {
     #[structopt(long, possible_values = &Language::extensions(), use_delimiter = true)]
     pub except_filetypes: Vec<Language>,
 
-    /// Format output
-    #[structopt(long, possible_values = &["standard", "compact", "json"], default_value = "standard", case_insensitive = true)]
-    pub format: Format,
+    /// Limit tokens to definitions of these kinds, by ctags' kind letter or full name, e.g.
+    /// 'f,c,m' or 'function,class'
+    ///
+    /// Letters are read in each definition's language, as `ctags --list-kinds-full` lists them.
+    #[structopt(long, use_delimiter = true)]
+    pub only_kinds: Vec<KindPattern>,
+
+    /// Ignore definitions of these kinds, by ctags' kind letter or full name, e.g. 'v' or
+    /// 'variable,constant'
+    #[structopt(long, use_delimiter = true)]
+    pub ignore_kinds: Vec<KindPattern>,
+
+    /// Limit tokens to those defined in these languages, and only search files in them (and files
+    /// in languages that aren't recognized, such as templates), e.g. 'ruby,javascript'
+    #[structopt(long, use_delimiter = true, parse(try_from_str = language_name))]
+    pub only_languages: Vec<Language>,
+
+    /// Ignore tokens defined in these languages, and don't search files in them, e.g. 'json'
+    #[structopt(long, use_delimiter = true, parse(try_from_str = language_name))]
+    pub ignore_languages: Vec<Language>,
+
+    /// Group findings in the standard format [default: token]
+    ///
+    /// `owner` groups findings by the owners of their files, from CODEOWNERS, and also groups
+    /// the HTML report by owner.
+    #[structopt(long, possible_values = &["token", "file", "scope", "kind", "owner"], case_insensitive = true)]
+    pub group_by: Option<GroupBy>,
 
     /// Ignore files/directories matching the provided value
     ///
@@ -71,15 +477,276 @@ pub struct Flags {
     #[structopt(long, use_delimiter = true)]
     pub ignore: Vec<String>,
 
-    #[structopt(subcommand)]
-    pub cmd: Option<Command>,
+    /// Ignore tokens matching this regular expression, e.g. '^Test|Helper$'
+    ///
+    /// Repeat for multiple patterns; these are added to `ignore.tokens_matching` in the project
+    /// settings.
+    #[structopt(long, number_of_values = 1, validator = TokenPattern::validate)]
+    pub ignore_tokens_matching: Vec<String>,
+
+    /// Neither search nor report paths matching this gitignore-style glob, e.g. 'spec/**'
+    ///
+    /// Repeat for multiple globs; these are added to `ignore_paths` in the project settings.
+    #[structopt(long, number_of_values = 1, validator = ExcludeGlobs::validate)]
+    pub exclude: Vec<String>,
+
+    /// Only report definitions from paths matching this gitignore-style glob, e.g. 'app/**'
+    ///
+    /// Repeat for multiple globs; these are added to `roots.definitions.include` in the project
+    /// settings.
+    #[structopt(long, number_of_values = 1, validator = ExcludeGlobs::validate)]
+    pub include_definitions: Vec<String>,
+
+    /// Don't report definitions from paths matching this gitignore-style glob, though
+    /// occurrences there still count as usages
+    ///
+    /// Repeat for multiple globs; these are added to `roots.definitions.exclude` in the project
+    /// settings.
+    #[structopt(long, number_of_values = 1, validator = ExcludeGlobs::validate)]
+    pub exclude_definitions: Vec<String>,
+
+    /// Only count occurrences in paths matching this gitignore-style glob as usages
+    ///
+    /// Repeat for multiple globs; these are added to `roots.usages.include` in the project
+    /// settings.
+    #[structopt(long, number_of_values = 1, validator = ExcludeGlobs::validate)]
+    pub include_usages: Vec<String>,
+
+    /// Don't count occurrences in paths matching this gitignore-style glob as usages, e.g.
+    /// 'db/schema.rb', though definitions there are still reported
+    ///
+    /// Repeat for multiple globs; these are added to `roots.usages.exclude` in the project
+    /// settings.
+    #[structopt(long, number_of_values = 1, validator = ExcludeGlobs::validate)]
+    pub exclude_usages: Vec<String>,
+
+    /// Skip files larger than this, e.g. '500KB' or '10MB' (in multiples of 1024 bytes); 0
+    /// searches files of any size [default: 5MB]
+    ///
+    /// Skipped files, along with binary files, are logged with `-v`.
+    #[structopt(long)]
+    pub max_file_size: Option<FileSize>,
+
+    /// Roughly how much memory searching may hold at once, e.g. '2GB' (in multiples of 1024
+    /// bytes); 0 sets no limit [default: no limit]
+    ///
+    /// Tokens are searched in shards, reading every file once per shard, and counts are spilled
+    /// to a temporary file while later shards are searched. Ignored with --incremental or
+    /// --scan-cache.
+    #[structopt(long)]
+    pub memory_limit: Option<FileSize>,
+
+    /// Tags file(s) generated for dependencies, used to detect tokens shadowing them
+    ///
+    /// This supports providing multiple values with a comma-delimited list
+    #[structopt(long, use_delimiter = true)]
+    pub dependency_tags: Vec<PathBuf>,
+
+    /// File(s) listing deprecated tokens, one per line, whose remaining usages are reported
+    ///
+    /// This supports providing multiple values with a comma-delimited list
+    #[structopt(long, use_delimiter = true)]
+    pub deprecated: Vec<PathBuf>,
+
+    /// SARIF log(s) whose suppressed results, such as dismissed code scanning alerts, are ignored
+    ///
+    /// This supports providing multiple values with a comma-delimited list
+    #[structopt(long, use_delimiter = true)]
+    pub sarif_suppressions: Vec<PathBuf>,
+
+    /// Write current findings to .unused-baseline.json; later runs only report findings not in it
+    #[structopt(long, conflicts_with_all = &["interactive", "watch", "no-baseline"])]
+    pub generate_baseline: bool,
+
+    /// Report every finding, including those recorded in .unused-baseline.json
+    #[structopt(long)]
+    pub no_baseline: bool,
+
+    /// Skip framework profiles detected from the project's manifests (e.g. Gemfile, mix.exs)
+    #[structopt(long)]
+    pub no_framework_profiles: bool,
+
+    /// Read Rails routes from `rails routes` output saved to FILE, rather than config/routes.rb
+    ///
+    /// Controller actions reached by a route are treated as entry points, and public actions of
+    /// routed controllers that no route reaches are reported. Skipped with
+    /// --no-framework-profiles.
+    #[structopt(long, value_name = "FILE", parse(from_os_str))]
+    pub rails_routes: Option<PathBuf>,
+
+    /// Report tokens exported from package.json entry points or lib.rs as plainly unused,
+    /// rather than as public API unused internally
+    #[structopt(long)]
+    pub no_package_exports: bool,
+
+    /// Fail, rather than warn, when files changed after the tags file was generated
+    #[structopt(long)]
+    pub strict_freshness: bool,
+
+    /// Generate tags with ctags when the tags file is missing, stale, or malformed
+    ///
+    /// Tags are read from ctags' output in memory; the project's tags file is left untouched.
+    #[structopt(long, conflicts_with = "strict-freshness")]
+    pub regenerate_tags: bool,
+
+    /// Only count occurrences in files that plausibly import a token's module
+    ///
+    /// This applies to tokens defined in Rust, Go, Python, JavaScript, and TypeScript files,
+    /// improving precision for common names defined in several places.
+    #[structopt(long)]
+    pub scope_by_imports: bool,
+
+    /// Credit occurrences of aliases to the names they alias
+    ///
+    /// Aliases are declared with Ruby's `alias` and `alias_method`, `as` in JavaScript and
+    /// TypeScript imports and exports, and `use ... as` in Rust.
+    #[structopt(long)]
+    pub link_aliases: bool,
+
+    /// Attribute occurrences of names defined in several classes to the class each refers to
+    ///
+    /// Occurrences are attributed by their receiver (`Order.build`, `order.build`, or a bare call
+    /// within the class), using the `class:` and `scope:` fields of tags. Classes no occurrence
+    /// refers to are reported as unused in scope, unless some occurrences are ambiguous.
+    #[structopt(long)]
+    pub attribute_scopes: bool,
+
+    /// Don't count occurrences in comments, telling them apart with a lexer for each language
+    ///
+    /// Occurrences in comments and string literals are listed separately in JSON output.
+    /// Occurrences in strings still count, since they may be dynamic references. Every file is
+    /// searched, so caches and --memory-limit aren't used.
+    #[structopt(long)]
+    pub syntax_aware: bool,
+
+    /// Also report unused translation keys, defined in locale files such as config/locales/en.yml
+    ///
+    /// Keys are named by their dotted path below the locale (e.g. `users.show.title`) and counted
+    /// where that path is written, such as `t('users.show.title')`. Lazy lookups (`t('.title')`)
+    /// and keys built at runtime aren't resolved.
+    #[structopt(long)]
+    pub translation_keys: bool,
+
+    /// Reuse occurrence counts from the previous run, updating them from git diff hunks
+    #[structopt(long)]
+    pub incremental: bool,
+
+    /// Reuse occurrence counts for files whose contents haven't changed since the previous run
+    #[structopt(long)]
+    pub scan_cache: bool,
+
+    /// Count references recorded in GNU GLOBAL's GRTAGS rather than searching files
+    ///
+    /// This only applies when definitions are read from a GTAGS database.
+    #[structopt(long)]
+    pub gtags_references: bool,
+
+    /// Only report tokens defined in files changed since the merge base with this revision
+    ///
+    /// Uncommitted and untracked files count as changed, e.g. `--diff-base origin/main`.
+    #[structopt(long)]
+    pub diff_base: Option<String>,
+
+    /// Only report tokens defined in files staged in git, as staged
+    ///
+    /// Files with unstaged changes are searched as they are in the index, so a pre-commit hook
+    /// evaluates exactly what's about to be committed.
+    #[structopt(long, conflicts_with = "diff-base")]
+    pub staged: bool,
+
+    /// Analyze a reproducible sample of tokens (e.g. 5%) and estimate the total
+    #[structopt(long)]
+    pub sample: Option<SampleRate>,
+
+    /// Seed used to select tokens when sampling
+    #[structopt(long, default_value = "0")]
+    pub seed: u64,
+
+    /// Annotate findings with the commit, author, and age of each definition's line from git blame
+    #[structopt(long)]
+    pub blame: bool,
+
+    /// Coverage report(s), as lcov tracefiles or SimpleCov JSON, marking findings whose
+    /// definitions never ran
+    ///
+    /// This supports providing multiple values with a comma-delimited list
+    #[structopt(long, use_delimiter = true)]
+    pub coverage: Vec<PathBuf>,
+
+    /// Show the source line of each definition with findings
+    #[structopt(long)]
+    pub snippets: bool,
+
+    /// Lines of source to show either side of each definition (implies --snippets)
+    #[structopt(long, value_name = "lines")]
+    pub context: Option<usize>,
+
+    /// Triage findings in a terminal UI, saving accepted ignores to the project settings
+    #[structopt(long)]
+    pub interactive: bool,
+
+    /// Re-analyze whenever files change, printing new and resolved findings
+    #[structopt(long, conflicts_with = "interactive")]
+    pub watch: bool,
+
+    /// Write OpenMetrics gauges (tokens, findings by language, dead files, and scan duration) to
+    /// this file, e.g. for Prometheus' textfile collector
+    #[structopt(long, value_name = "path")]
+    pub metrics_file: Option<PathBuf>,
+
+    /// Report how long each phase of the run took, and the directories searching spent the
+    /// longest in, as JSON on STDERR
+    #[structopt(long)]
+    pub timings: bool,
+
+    /// Report at most this many findings, after skipping any given by --offset
+    ///
+    /// Summaries and exit statuses still count every finding, and the JSON and SARIF formats
+    /// include the total so reports can be read a page at a time.
+    #[structopt(long)]
+    pub limit: Option<usize>,
+
+    /// Skip this many findings before reporting any, e.g. `--offset 100 --limit 100` for the
+    /// second page of a hundred
+    #[structopt(long)]
+    pub offset: Option<usize>,
+
+    /// Only report findings defined in the files defining the most findings
+    #[structopt(long, value_name = "files")]
+    pub top_files: Option<usize>,
+
+    /// Exit with status 1 when any findings are reported
+    #[structopt(long)]
+    pub fail_on_unused: bool,
+
+    /// Exit with status 1 when more than this many findings are reported
+    #[structopt(long)]
+    pub max_unused: Option<usize>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, Default)]
 pub enum Format {
+    #[default]
     Standard,
     Compact,
     Json,
+    Sarif,
+    Csv,
+    Tsv,
+    Html,
+    Junit,
+    Quickfix,
+    Github,
+    Codeclimate,
+    Checkstyle,
+    Porcelain,
+}
+
+impl Format {
+    /// Is this format meant to be read by other tools, rather than in a terminal?
+    pub fn is_machine_readable(self) -> bool {
+        !matches!(self, Format::Standard | Format::Compact)
+    }
 }
 
 impl FromStr for Format {
@@ -87,10 +754,162 @@ impl FromStr for Format {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_ref() {
-            "standard" => Ok(Format::Standard),
+            "standard" | "terminal" => Ok(Format::Standard),
             "compact" => Ok(Format::Compact),
             "json" => Ok(Format::Json),
+            "sarif" => Ok(Format::Sarif),
+            "csv" => Ok(Format::Csv),
+            "tsv" => Ok(Format::Tsv),
+            "html" => Ok(Format::Html),
+            "junit" => Ok(Format::Junit),
+            "quickfix" => Ok(Format::Quickfix),
+            "github" => Ok(Format::Github),
+            "codeclimate" | "gitlab" => Ok(Format::Codeclimate),
+            "checkstyle" => Ok(Format::Checkstyle),
+            "porcelain" => Ok(Format::Porcelain),
             v => Err(format!("Unknown format: {}", v)),
         }
     }
 }
+
+/// A format to render, written to STDOUT or to a file
+#[derive(Clone, Debug)]
+pub struct FormatTarget {
+    pub format: Format,
+    pub path: Option<PathBuf>,
+}
+
+impl FromStr for FormatTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((_, "")) => Err(format!("Missing output path: {}", s)),
+            Some((format, path)) => Ok(FormatTarget {
+                format: format.parse()?,
+                path: Some(PathBuf::from(path)),
+            }),
+            None => Ok(FormatTarget {
+                format: s.parse()?,
+                path: None,
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum GraphNodes {
+    File,
+    Scope,
+}
+
+impl FromStr for GraphNodes {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "file" => Ok(GraphNodes::File),
+            "scope" => Ok(GraphNodes::Scope),
+            v => Err(format!("Unknown graph nodes: {}", v)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            v => Err(format!("Unknown color choice: {}", v)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub enum GroupBy {
+    #[default]
+    Token,
+    File,
+    Scope,
+    Kind,
+    Owner,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "token" => Ok(GroupBy::Token),
+            "file" => Ok(GroupBy::File),
+            "scope" => Ok(GroupBy::Scope),
+            "kind" => Ok(GroupBy::Kind),
+            "owner" => Ok(GroupBy::Owner),
+            v => Err(format!("Unknown grouping: {}", v)),
+        }
+    }
+}
+
+// Languages are named in full (e.g. `javascript`), or by a file extension as with
+// `--only-filetypes`
+fn language_name(s: &str) -> Result<Language, String> {
+    s.parse()
+        .or_else(|e| Language::from_template_extension(s).ok_or(e))
+}
+
+/// A size in bytes, written with an optional `KB`, `MB`, or `GB` suffix
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FileSize(pub u64);
+
+impl FromStr for FileSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lowercase = s.trim().to_lowercase();
+        let digits = lowercase.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+        let multiplier: u64 = match lowercase[digits.len()..].as_ref() {
+            "" | "b" => 1,
+            "k" | "kb" => 1024,
+            "m" | "mb" => 1024 * 1024,
+            "g" | "gb" => 1024 * 1024 * 1024,
+            _ => return Err(format!("Unknown file size: {}", s)),
+        };
+
+        digits
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .and_then(|count| count.checked_mul(multiplier))
+            .map(FileSize)
+            .ok_or_else(|| format!("Unknown file size: {}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hides_progress_only_for_formats_read_by_other_tools() {
+        assert!(!Format::Standard.is_machine_readable());
+        assert!(!Format::Compact.is_machine_readable());
+        for format in ["json", "sarif", "csv", "html", "junit", "quickfix"] {
+            assert!(
+                format.parse::<Format>().unwrap().is_machine_readable(),
+                "{}",
+                format
+            );
+        }
+    }
+}
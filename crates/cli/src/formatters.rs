@@ -1,4 +1,13 @@
+pub mod checkstyle;
+pub mod codeclimate;
 pub mod compact;
-mod internal;
+pub mod delimited;
+pub mod github;
+pub mod html;
+pub mod internal;
 pub mod json;
+pub mod junit;
+pub mod porcelain;
+pub mod quickfix;
+pub mod sarif;
 pub mod standard;
@@ -0,0 +1,90 @@
+use super::internal::{escape_markup as escape, CliConfiguration};
+use crate::severity::Severity;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+use token_analysis::{TokenUsage, UsageLikelihoodStatus};
+
+// Version of the Checkstyle report layout written, which CI plugins check before parsing
+const CHECKSTYLE_VERSION: &str = "4.3";
+
+// Each definition is an error within its file's element, as written by Checkstyle itself
+pub fn format(cli_config: &CliConfiguration, out: &mut dyn Write) -> io::Result<()> {
+    let usages = cli_config.findings();
+    let severities = cli_config.severities();
+    let mut by_file: BTreeMap<&Path, Vec<(usize, usize, &TokenUsage)>> = BTreeMap::new();
+
+    for usage in usages.iter() {
+        for definition in usage.result.token.definitions.iter() {
+            let (line, column) = cli_config.sources().position(definition);
+            by_file.entry(&definition.file_path).or_default().push((
+                line.unwrap_or(1),
+                column.unwrap_or(1),
+                usage,
+            ));
+        }
+    }
+
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(out, "<checkstyle version=\"{}\">", CHECKSTYLE_VERSION)?;
+
+    for (path, mut errors) in by_file {
+        errors.sort_by(|a, b| {
+            (a.0, a.1, &a.2.result.token.token).cmp(&(b.0, b.1, &b.2.result.token.token))
+        });
+        writeln!(out, "  <file name=\"{}\">", escape(&path.to_string_lossy()))?;
+
+        for (line, column, usage) in errors {
+            writeln!(out, "{}", error(line, column, usage, severities.of(usage)))?;
+        }
+
+        writeln!(out, "  </file>")?;
+    }
+
+    writeln!(out, "</checkstyle>")?;
+    Ok(())
+}
+
+fn error(line: usize, column: usize, usage: &TokenUsage, configured: Option<Severity>) -> String {
+    let likelihood = &usage.usage_likelihood;
+
+    format!(
+        "    <error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\" source=\"unused.{}\"/>",
+        line,
+        column,
+        configured.map_or_else(|| severity(&likelihood.status), Severity::checkstyle),
+        escape(&format!(
+            "{} is likely unused: {} ({} likelihood)",
+            usage.result.token.token, likelihood.reason, likelihood.status
+        )),
+        likelihood.rule
+    )
+}
+
+fn severity(status: &UsageLikelihoodStatus) -> &'static str {
+    match status {
+        UsageLikelihoodStatus::High => "warning",
+        UsageLikelihoodStatus::Medium | UsageLikelihoodStatus::Low => "info",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TokenUsageBuilder;
+    use read_ctags::Language;
+
+    #[test]
+    fn reports_escaped_errors_at_configured_severities() {
+        let usage = TokenUsageBuilder::new("<=>", "app/models/person.rb")
+            .with_address("2")
+            .with_language(Language::Ruby)
+            .build();
+
+        assert_eq!(
+            error(2, 7, &usage, None),
+            "    <error line=\"2\" column=\"7\" severity=\"warning\" message=\"&lt;=&gt; is likely unused: Only one occurrence exists (high likelihood)\" source=\"unused.UN001\"/>"
+        );
+        assert!(error(2, 7, &usage, Some(Severity::Error)).contains("severity=\"error\""));
+    }
+}
@@ -0,0 +1,128 @@
+use super::internal::CliConfiguration;
+use crate::crash_report::fnv1a;
+use crate::severity::Severity;
+use crate::sources::Sources;
+use serde::Serialize;
+use std::io::{self, Write};
+use token_analysis::{TokenUsage, UsageLikelihoodStatus};
+
+// Code Climate issues, as read by GitLab's Code Quality merge request widget
+#[derive(Debug, PartialEq, Serialize)]
+struct Issue {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    check_name: &'static str,
+    description: String,
+    categories: [&'static str; 1],
+    // Identifies the issue across runs, so GitLab can tell new issues from resolved ones
+    fingerprint: String,
+    severity: &'static str,
+    location: Location,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Location {
+    path: String,
+    lines: Lines,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Lines {
+    begin: usize,
+}
+
+// One issue per definition, in a single JSON array
+pub fn format(cli_config: &CliConfiguration, out: &mut dyn Write) -> io::Result<()> {
+    let severities = cli_config.severities();
+    let issues: Vec<Issue> = cli_config
+        .findings()
+        .into_iter()
+        .flat_map(|usage| issues(usage, severities.of(usage), cli_config.sources()))
+        .collect();
+
+    writeln!(out, "{}", serde_json::to_string(&issues)?)
+}
+
+fn issues(usage: &TokenUsage, configured: Option<Severity>, sources: &Sources) -> Vec<Issue> {
+    let token = &usage.result.token.token;
+    let likelihood = &usage.usage_likelihood;
+    let definitions = usage.result.token.ordered_definitions();
+
+    definitions
+        .into_iter()
+        .map(|definition| {
+            let path = definition
+                .file_path
+                .to_string_lossy()
+                .replace('\\', "/")
+                .trim_start_matches("./")
+                .to_string();
+            // Line numbers are left out so moving a definition doesn't make it a new issue
+            let fingerprint = fnv1a(format!("{}\0{}\0{}", likelihood.rule, token, path).as_bytes());
+
+            Issue {
+                kind: "issue",
+                check_name: likelihood.rule.id(),
+                description: format!("{} is likely unused: {}", token, likelihood.reason),
+                categories: ["Clutter"],
+                fingerprint: format!("{:016x}", fingerprint),
+                severity: configured
+                    .map_or_else(|| severity(&likelihood.status), Severity::code_quality),
+                location: Location {
+                    path,
+                    lines: Lines {
+                        begin: sources.position(definition).0.unwrap_or(1),
+                    },
+                },
+            }
+        })
+        .collect()
+}
+
+fn severity(status: &UsageLikelihoodStatus) -> &'static str {
+    match status {
+        UsageLikelihoodStatus::High => "minor",
+        UsageLikelihoodStatus::Medium | UsageLikelihoodStatus::Low => "info",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TokenUsageBuilder;
+    use read_ctags::Language;
+
+    #[test]
+    fn reports_an_issue_per_definition_with_stable_fingerprints() {
+        let usage = |path, line| {
+            TokenUsageBuilder::new("full_name", path)
+                .with_address(line)
+                .with_language(Language::Ruby)
+                .build()
+        };
+        let issues = issues(&usage("./app/person.rb", "2"), None, &Sources::default());
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].check_name, "UN001");
+        assert_eq!(issues[0].severity, "minor");
+        assert_eq!(
+            issues[0].description,
+            "full_name is likely unused: Only one occurrence exists"
+        );
+        assert_eq!(
+            issues[0].location,
+            Location {
+                path: String::from("app/person.rb"),
+                lines: Lines { begin: 2 },
+            }
+        );
+        assert_eq!(
+            issues[0].fingerprint,
+            super::issues(&usage("app/person.rb", "7"), None, &Sources::default())[0].fingerprint
+        );
+        assert_ne!(
+            issues[0].fingerprint,
+            super::issues(&usage("app/order.rb", "2"), None, &Sources::default())[0].fingerprint
+        );
+    }
+}
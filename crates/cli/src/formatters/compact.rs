@@ -1,7 +1,9 @@
-use super::internal::{colored::*, configuration_warnings, CliConfiguration};
+use super::internal::{colored::*, CliConfiguration};
+use crate::messages::{fill, Message};
+use std::io::{self, Write};
 use token_analysis::UsageLikelihoodStatus;
 
-pub fn format(cli_config: &CliConfiguration) {
+pub fn format(cli_config: &CliConfiguration, out: &mut dyn Write) -> io::Result<()> {
     let token_width = cli_config.max_token_length() + 3;
     let file_width = cli_config.max_file_length() + 3;
     for analysis in cli_config.analyses() {
@@ -10,15 +12,23 @@ pub fn format(cli_config: &CliConfiguration) {
             UsageLikelihoodStatus::Medium => analysis.token.yellow(),
             UsageLikelihoodStatus::Low => analysis.token.green(),
         };
-        println!(
-            "{:token_width$} {:file_width$} {}",
+        // Only the first definition's path fits on the line, so note any others
+        let defined_in = match analysis.defined_paths.len() {
+            0 | 1 => String::new(),
+            files => format!(" ({})", fill(Message::DefinedInFiles, &[&files])),
+        };
+        writeln!(
+            out,
+            "{:token_width$} {:file_width$} {} {}{}",
             display_token,
             analysis.first_path.to_string_lossy().cyan(),
+            analysis.rule,
             analysis.likelihood_reason,
+            defined_in,
             token_width = token_width,
             file_width = file_width
-        );
+        )?;
     }
 
-    configuration_warnings(cli_config);
+    Ok(())
 }
@@ -0,0 +1,134 @@
+use super::internal::CliConfiguration;
+use crate::sources::Sources;
+use std::io::{self, Write};
+use token_analysis::{Confidence, TokenUsage};
+
+const HEADERS: [&str; 11] = [
+    "token",
+    "kind",
+    "language",
+    "path",
+    "address",
+    "line",
+    "occurrences",
+    "likelihood",
+    "score",
+    "rule",
+    "confidence",
+];
+
+pub fn csv(cli_config: &CliConfiguration, out: &mut dyn Write) -> io::Result<()> {
+    format(cli_config, out, ',')
+}
+
+pub fn tsv(cli_config: &CliConfiguration, out: &mut dyn Write) -> io::Result<()> {
+    format(cli_config, out, '\t')
+}
+
+fn format(cli_config: &CliConfiguration, out: &mut dyn Write, delimiter: char) -> io::Result<()> {
+    writeln!(out, "{}", row(&HEADERS, delimiter))?;
+
+    for usage in cli_config.findings() {
+        for fields in records(usage, cli_config.sources()) {
+            writeln!(out, "{}", row(&fields, delimiter))?;
+        }
+    }
+
+    Ok(())
+}
+
+// One record per definition, so each row points at a single location
+fn records(usage: &TokenUsage, sources: &Sources) -> Vec<Vec<String>> {
+    let definitions = usage.result.token.ordered_definitions();
+    let confidence = Confidence::estimate(usage).score;
+
+    definitions
+        .into_iter()
+        .map(|definition| {
+            vec![
+                usage.result.token.token.to_string(),
+                format!("{:?}", definition.kind),
+                definition
+                    .language
+                    .map(|l| l.to_string())
+                    .unwrap_or_default(),
+                definition.file_path.to_string_lossy().to_string(),
+                definition.address.to_string(),
+                sources
+                    .position(definition)
+                    .0
+                    .map(|line| line.to_string())
+                    .unwrap_or_default(),
+                usage.file_type_counts.total().occurrence_count.to_string(),
+                usage.usage_likelihood.status.to_string(),
+                usage.usage_likelihood.score.to_string(),
+                usage.usage_likelihood.rule.to_string(),
+                confidence.to_string(),
+            ]
+        })
+        .collect()
+}
+
+fn row<T: AsRef<str>>(fields: &[T], delimiter: char) -> String {
+    fields
+        .iter()
+        .map(|f| escape(f.as_ref(), delimiter))
+        .collect::<Vec<_>>()
+        .join(&delimiter.to_string())
+}
+
+// CSV quotes fields per RFC 4180; TSV has no quoting, so separators are replaced with spaces
+fn escape(field: &str, delimiter: char) -> String {
+    if delimiter == '\t' {
+        field.replace(['\t', '\n', '\r'], " ")
+    } else if field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TokenUsageBuilder;
+    use read_ctags::Language;
+
+    #[test]
+    fn escapes_fields_for_each_delimiter() {
+        assert_eq!(
+            row(&["name", "/^  def name(a, b)$/", "say \"hi\""], ','),
+            "name,\"/^  def name(a, b)$/\",\"say \"\"hi\"\"\""
+        );
+        assert_eq!(
+            row(&["name", "/^\tdef name$/"], '\t'),
+            "name\t/^ def name$/"
+        );
+    }
+
+    #[test]
+    fn records_each_definition_with_its_line() {
+        let usage = TokenUsageBuilder::new("full_name", "app/models/person.rb")
+            .with_address("2")
+            .with_language(Language::Ruby)
+            .build();
+
+        let records = records(&usage, &Sources::default());
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].len(), HEADERS.len());
+        assert_eq!(
+            records[0][..8],
+            [
+                "full_name",
+                "Method",
+                "Ruby",
+                "app/models/person.rb",
+                "2",
+                "2",
+                "1",
+                "high"
+            ]
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use super::internal::CliConfiguration;
+use crate::sources::Sources;
+use std::io::{self, Write};
+use token_analysis::{TokenUsage, UsageLikelihoodStatus};
+
+// One GitHub Actions `::warning` (or `::notice`) workflow command per definition, which GitHub
+// shows as an annotation on the definition's line in pull request diffs
+pub fn format(cli_config: &CliConfiguration, out: &mut dyn Write) -> io::Result<()> {
+    for usage in cli_config.findings() {
+        for line in annotations(usage, cli_config.sources()) {
+            writeln!(out, "{}", line)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn annotations(usage: &TokenUsage, sources: &Sources) -> Vec<String> {
+    let token = &usage.result.token.token;
+    let likelihood = &usage.usage_likelihood;
+    let definitions = usage.result.token.ordered_definitions();
+
+    definitions
+        .into_iter()
+        .map(|definition| {
+            let (line, column) = sources.position(definition);
+            format!(
+                "::{} file={},line={},col={},title={}::{}",
+                command(&likelihood.status),
+                escape_property(&definition.file_path.to_string_lossy()),
+                line.unwrap_or(1),
+                column.unwrap_or(1),
+                escape_property(&format!("Unused token ({})", likelihood.rule)),
+                escape_data(&format!(
+                    "token '{}' appears unused: {} ({} likelihood)",
+                    token, likelihood.reason, likelihood.status
+                ))
+            )
+        })
+        .collect()
+}
+
+fn command(status: &UsageLikelihoodStatus) -> &'static str {
+    match status {
+        UsageLikelihoodStatus::High => "warning",
+        UsageLikelihoodStatus::Medium | UsageLikelihoodStatus::Low => "notice",
+    }
+}
+
+// Workflow commands end at a newline, so messages encode them (and `%`, their escape character)
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+// Properties are also delimited by `,` and separated from the message by `::`
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TokenUsageBuilder;
+    use read_ctags::Language;
+
+    #[test]
+    fn annotates_definitions_with_escaped_properties() {
+        let usage = TokenUsageBuilder::new("full_name", "app/models/person,old.rb")
+            .with_address("2")
+            .with_language(Language::Ruby)
+            .build();
+
+        assert_eq!(
+            annotations(&usage, &Sources::default()),
+            vec!["::warning file=app/models/person%2Cold.rb,line=2,col=1,title=Unused token (UN001)::token 'full_name' appears unused: Only one occurrence exists (high likelihood)"]
+        );
+        assert_eq!(escape_data("100%\nfull"), "100%25%0Afull");
+    }
+}
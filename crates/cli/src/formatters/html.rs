@@ -0,0 +1,199 @@
+use super::internal::{escape_markup as escape, CliConfiguration};
+use super::standard::UNOWNED;
+use crate::flags::GroupBy;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+use token_analysis::{TokenUsage, UsageLikelihoodStatus};
+
+const STYLE: &str = "
+body { font-family: -apple-system, BlinkMacSystemFont, sans-serif; margin: 2em; color: #24292e; }
+h1 { font-size: 1.5em; }
+dl.summary { display: grid; grid-template-columns: max-content auto; gap: 0.25em 1em; }
+dl.summary dt { font-weight: bold; }
+details { margin: 0.5em 0; border: 1px solid #e1e4e8; border-radius: 4px; padding: 0.5em; }
+summary { cursor: pointer; font-family: monospace; }
+table { border-collapse: collapse; width: 100%; margin-top: 0.5em; }
+th, td { text-align: left; padding: 0.25em 0.75em; border-bottom: 1px solid #e1e4e8; }
+th { cursor: pointer; user-select: none; }
+th::after { content: ' \\2195'; color: #959da5; }
+td.token { font-family: monospace; }
+.high { color: #cb2431; }
+.medium { color: #b08800; }
+.low { color: #22863a; }
+";
+
+const SCRIPT: &str = "
+document.querySelectorAll('th').forEach(function (header) {
+  header.addEventListener('click', function () {
+    var table = header.closest('table');
+    var index = Array.prototype.indexOf.call(header.parentNode.children, header);
+    var ascending = header.dataset.order !== 'asc';
+    var rows = Array.prototype.slice.call(table.querySelectorAll('tbody tr'));
+    rows.sort(function (a, b) {
+      var x = a.children[index].dataset.value || a.children[index].textContent;
+      var y = b.children[index].dataset.value || b.children[index].textContent;
+      var result = isNaN(x) || isNaN(y) ? x.localeCompare(y) : x - y;
+      return ascending ? result : -result;
+    });
+    header.dataset.order = ascending ? 'asc' : 'desc';
+    rows.forEach(function (row) { table.querySelector('tbody').appendChild(row); });
+  });
+});
+";
+
+pub fn format(cli_config: &CliConfiguration, out: &mut dyn Write) -> io::Result<()> {
+    let usages = cli_config.findings();
+    let mut by_file: BTreeMap<&Path, Vec<&TokenUsage>> = BTreeMap::new();
+
+    for usage in usages.iter() {
+        for path in usage.result.token.defined_paths.iter() {
+            by_file.entry(&**path).or_default().push(usage);
+        }
+    }
+
+    // Sections are per owner with --group-by owner, and per defining file otherwise
+    let sections: BTreeMap<String, Vec<&TokenUsage>> = match cli_config.group_by() {
+        GroupBy::Owner => {
+            let mut by_owner: BTreeMap<String, Vec<&TokenUsage>> = BTreeMap::new();
+            for usage in usages.iter() {
+                let owners = cli_config.owners(usage);
+                if owners.is_empty() {
+                    by_owner.entry(UNOWNED.to_string()).or_default().push(usage);
+                }
+                for owner in owners {
+                    by_owner.entry(owner.to_string()).or_default().push(usage);
+                }
+            }
+            by_owner
+        }
+        _ => by_file
+            .iter()
+            .map(|(path, usages)| (path.to_string_lossy().to_string(), usages.clone()))
+            .collect(),
+    };
+
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html lang=\"en\">")?;
+    writeln!(out, "<head>")?;
+    writeln!(out, "<meta charset=\"utf-8\">")?;
+    writeln!(out, "<title>unused report</title>")?;
+    writeln!(out, "<style>{}</style>", STYLE)?;
+    writeln!(out, "</head>")?;
+    writeln!(out, "<body>")?;
+    writeln!(out, "<h1>unused report</h1>")?;
+    writeln!(out, "<dl class=\"summary\">")?;
+    summary_item(out, "Tokens", &usages.len().to_string())?;
+    summary_item(out, "Files", &by_file.len().to_string())?;
+    summary_item(out, "Tags source", &cli_config.tags_source())?;
+    summary_item(
+        out,
+        "Usage likelihood",
+        &cli_config.usage_likelihood_filter().join(", "),
+    )?;
+    summary_item(
+        out,
+        "Configuration setting",
+        &cli_config.configuration_name(),
+    )?;
+    let generic_languages = cli_config.generic_languages();
+    if !generic_languages.is_empty() {
+        summary_item(
+            out,
+            "Analyzed with generic rules",
+            &generic_languages.into_iter().collect::<Vec<_>>().join(", "),
+        )?;
+    }
+    writeln!(out, "</dl>")?;
+
+    for (section, section_usages) in sections {
+        writeln!(out, "<details open>")?;
+        writeln!(
+            out,
+            "<summary>{} ({})</summary>",
+            escape(&section),
+            section_usages.len()
+        )?;
+        writeln!(out, "<table>")?;
+        writeln!(out, "<thead><tr><th>Token</th><th>Likelihood</th><th>Rule</th><th>Reason</th><th>Occurrences</th></tr></thead>")?;
+        writeln!(out, "<tbody>")?;
+        for usage in section_usages {
+            table_row(out, usage)?;
+        }
+        writeln!(out, "</tbody>")?;
+        writeln!(out, "</table>")?;
+        writeln!(out, "</details>")?;
+    }
+
+    writeln!(out, "<script>{}</script>", SCRIPT)?;
+    writeln!(out, "</body>")?;
+    writeln!(out, "</html>")?;
+
+    Ok(())
+}
+
+fn summary_item(out: &mut dyn Write, label: &str, value: &str) -> io::Result<()> {
+    writeln!(out, "<dt>{}</dt><dd>{}</dd>", label, escape(value))
+}
+
+fn table_row(out: &mut dyn Write, usage: &TokenUsage) -> io::Result<()> {
+    let likelihood = usage.usage_likelihood.status.to_string();
+    let likelihood_rank = match usage.usage_likelihood.status {
+        UsageLikelihoodStatus::High => 0,
+        UsageLikelihoodStatus::Medium => 1,
+        UsageLikelihoodStatus::Low => 2,
+    };
+
+    writeln!(
+        out,
+        "<tr><td class=\"token {}\">{}</td><td class=\"{}\" data-value=\"{}\">{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+        likelihood,
+        escape(&usage.result.token.token),
+        likelihood,
+        likelihood_rank,
+        likelihood,
+        usage.usage_likelihood.rule,
+        escape(&usage.usage_likelihood.reason),
+        usage.file_type_counts.total().occurrence_count
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TokenUsageBuilder;
+    use read_ctags::Language;
+
+    fn rendered<F>(write: F) -> String
+    where
+        F: FnOnce(&mut dyn Write) -> io::Result<()>,
+    {
+        let mut out = vec![];
+        write(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn renders_sortable_escaped_rows() {
+        let usage = TokenUsageBuilder::new("<=>", "app/models/person.rb")
+            .with_address("2")
+            .with_language(Language::Ruby)
+            .build();
+        let row = rendered(|out| table_row(out, &usage));
+
+        assert_eq!(
+            row,
+            "<tr><td class=\"token high\">&lt;=&gt;</td><td class=\"high\" data-value=\"0\">high</td><td>UN001</td><td>Only one occurrence exists</td><td>1</td></tr>\n"
+        );
+    }
+
+    #[test]
+    fn escapes_summary_values() {
+        let item = rendered(|out| summary_item(out, "Tags source", "tags & \"more\""));
+
+        assert_eq!(
+            item,
+            "<dt>Tags source</dt><dd>tags &amp; &quot;more&quot;</dd>\n"
+        );
+    }
+}
@@ -1,9 +1,28 @@
-pub use super::super::{cli_configuration::CliConfiguration, flags::Flags};
+pub use super::super::{analyzed_token::AnalyzedToken, cli_configuration::CliConfiguration};
+use crate::logging;
 pub use colored;
 use colored::*;
 use project_configuration::ProjectConfigurations;
+use serde::Serialize;
+use std::io::{self, Write};
 
 pub fn configuration_warnings(config: &CliConfiguration) {
+    if logging::quiet() {
+        return;
+    }
+
+    if config.using_fallback_tags() {
+        eprintln!(
+            "{}",
+            "No tags file was found and ctags isn't installed; results are fallback-quality and based on approximate definitions.".yellow()
+        );
+        eprintln!("Install Universal Ctags (https://ctags.io/) and generate a tags file for accurate results.");
+    }
+
+    for warning in config.loading_warnings() {
+        eprintln!("{}", warning.yellow());
+    }
+
     for (likelihood_name, conflicts) in config.low_likelihood_conflicts() {
         eprintln!(
             "Issues detected in YAML low-likelihood configuration: {}",
@@ -13,7 +32,7 @@ pub fn configuration_warnings(config: &CliConfiguration) {
         for conflict in conflicts {
             let keys: Vec<_> = conflict
                 .assertions()
-                .into_iter()
+                .iter()
                 .filter_map(ProjectConfigurations::assertion_to_key)
                 .collect();
 
@@ -28,3 +47,42 @@ pub fn configuration_warnings(config: &CliConfiguration) {
         }
     }
 }
+
+/// Print `value` as a line of JSON on STDOUT
+///
+/// Output is often piped into `jq` or `head`, which may close STDOUT early; that isn't an error.
+pub fn print_json<T: Serialize + ?Sized>(value: &T) {
+    let written = serde_json::to_string(value)
+        .map_err(io::Error::from)
+        .and_then(|json| writeln!(io::stdout().lock(), "{}", json));
+
+    match written {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+            eprintln!("{}", format!("Unable to write output: {}", e).red())
+        }
+        _ => {}
+    }
+}
+
+/// Escape text for inclusion in HTML or XML documents
+pub fn escape_markup(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_markup() {
+        assert_eq!(
+            escape_markup("<=> & \"spaceship\""),
+            "&lt;=&gt; &amp; &quot;spaceship&quot;"
+        );
+    }
+}
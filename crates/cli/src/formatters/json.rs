@@ -1,7 +1,277 @@
-use super::internal::{configuration_warnings, CliConfiguration};
-use serde_json;
+use super::internal::CliConfiguration;
+use crate::analyzed_token::generic_languages;
+use crate::blame::{Blame, LineBlame};
+use crate::budgets::BudgetReport;
+use crate::coverage::{Coverage, TokenCoverage};
+use crate::snippets::{Snippet, Snippets};
+use crate::sources::Sources;
+use read_ctags::{Language, TokenKind};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use token_analysis::{
+    CleanupValue, Confidence, Counts, DeprecatedUsage, Estimate, RenameHint, Rule, TokenUsage,
+};
+use token_search::{InlineSuppression, Mentions, ScopeAttribution};
 
-pub fn format(cli_config: &CliConfiguration) {
-    println!("{}", serde_json::to_string(&cli_config.for_json()).unwrap());
-    configuration_warnings(cli_config);
+/// Version of the JSON document layout; bumped whenever a field is renamed or removed
+const FORMAT_VERSION: u8 = 1;
+
+#[derive(Serialize)]
+struct Report<'a> {
+    version: u8,
+    /// Findings before --top-files, --offset, and --limit, for reading the report a page at a time
+    total: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<usize>,
+    findings: Vec<Finding<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sample: Option<SampleReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deprecations: Option<&'a [DeprecatedUsage]>,
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    generic_languages: BTreeSet<String>,
+    /// Tokens not reported because their definitions are annotated with `unused:ignore`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suppressed: Vec<&'a InlineSuppression>,
+    /// Findings counted against each budget under `budgets`, when any are configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    budgets: Option<BudgetReport<'a>>,
+    /// Findings by the owners of their files, when a CODEOWNERS file was read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ownership: Option<Ownership<'a>>,
+}
+
+#[derive(Serialize, Default)]
+struct Ownership<'a> {
+    /// Tokens owned by each owner, in the order they're reported
+    owners: BTreeMap<&'a str, Vec<&'a str>>,
+    /// Tokens defined only in files without owners
+    unowned: Vec<&'a str>,
+}
+
+#[derive(Serialize)]
+struct SampleReport {
+    rate: f64,
+    seed: u64,
+    #[serde(flatten)]
+    estimate: Estimate,
+}
+
+#[derive(Serialize)]
+struct Finding<'a> {
+    token: &'a str,
+    likelihood: String,
+    score: u8,
+    reason: &'a str,
+    rule: Rule,
+    cleanup: CleanupValue,
+    confidence: Confidence,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    coverage: Option<TokenCoverage>,
+    /// A heavily used token named almost alike, which the token may have been renamed to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rename_hint: Option<&'a RenameHint>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    generic_languages: Vec<String>,
+    /// Owners of the files defining the token, from CODEOWNERS
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    owners: BTreeSet<&'a str>,
+    /// Number of files defining the token, such as a Ruby class reopened across files
+    defined_in: usize,
+    definitions: Vec<Definition<'a>>,
+    occurrences: Occurrences<'a>,
+}
+
+#[derive(Serialize)]
+struct Definition<'a> {
+    path: &'a Path,
+    address: &'a str,
+    line: Option<usize>,
+    column: Option<usize>,
+    kind: &'a TokenKind,
+    language: Option<Language>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_change: Option<&'a LineBlame>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snippet: Option<&'a Snippet>,
+}
+
+#[derive(Serialize)]
+struct Occurrences<'a> {
+    total: Counts,
+    application: Counts,
+    test: Counts,
+    config: Counts,
+    unknown: Counts,
+    files: BTreeMap<&'a PathBuf, usize>,
+    /// Occurrences in comments and string literals, with --syntax-aware
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    mentions: BTreeMap<&'a PathBuf, Mentions>,
+    /// Occurrences attributed to each scope defining the token, with --attribute-scopes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scopes: Option<&'a ScopeAttribution>,
+}
+
+impl<'a> Finding<'a> {
+    fn new(
+        usage: &'a TokenUsage,
+        sources: &Sources,
+        blame: Option<&'a Blame>,
+        snippets: Option<&'a Snippets>,
+        coverage: Option<&Coverage>,
+    ) -> Self {
+        let definitions: Vec<Definition> = usage
+            .result
+            .token
+            .ordered_definitions()
+            .into_iter()
+            .map(|d| {
+                let (line, column) = sources.position(d);
+                Definition {
+                    path: &d.file_path,
+                    address: &d.address,
+                    line,
+                    column,
+                    kind: &d.kind,
+                    language: d.language,
+                    last_change: blame.and_then(|b| b.get(d)),
+                    snippet: snippets.and_then(|s| s.get(d)),
+                }
+            })
+            .collect();
+
+        let counts = &usage.file_type_counts;
+
+        Finding {
+            token: &usage.result.token.token,
+            likelihood: usage.usage_likelihood.status.to_string(),
+            score: usage.usage_likelihood.score,
+            reason: &usage.usage_likelihood.reason,
+            rule: usage.usage_likelihood.rule,
+            cleanup: CleanupValue::estimate(usage),
+            confidence: Confidence::estimate(usage),
+            coverage: coverage.and_then(|c| c.for_usage(usage)),
+            rename_hint: None,
+            generic_languages: generic_languages(usage),
+            owners: BTreeSet::new(),
+            defined_in: usage.result.token.defined_paths.len(),
+            definitions,
+            occurrences: Occurrences {
+                total: counts.total(),
+                application: counts.app,
+                test: counts.test,
+                config: counts.config,
+                unknown: counts.unknown,
+                files: usage
+                    .result
+                    .occurrences
+                    .iter()
+                    .map(|(k, v)| (k, *v))
+                    .collect(),
+                mentions: usage.result.mentions.iter().map(|(k, v)| (k, *v)).collect(),
+                scopes: usage.result.scopes.as_ref(),
+            },
+        }
+    }
+}
+
+pub fn format(cli_config: &CliConfiguration, out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "{}", report(cli_config))
+}
+
+/// The full JSON report for the current findings
+pub fn report(cli_config: &CliConfiguration) -> serde_json::Value {
+    let page = cli_config.page();
+    let findings = cli_config.findings();
+    let report = Report {
+        version: FORMAT_VERSION,
+        total: cli_config.for_json().len(),
+        offset: page.offset,
+        limit: page.limit,
+        findings: findings
+            .iter()
+            .map(|usage| Finding {
+                owners: cli_config.owners(usage),
+                rename_hint: cli_config.rename_hint(usage),
+                ..Finding::new(
+                    usage,
+                    cli_config.sources(),
+                    Some(cli_config.blame()),
+                    Some(cli_config.snippets()),
+                    Some(cli_config.coverage()),
+                )
+            })
+            .collect(),
+        sample: cli_config
+            .sample_estimate()
+            .map(|(sample, estimate)| SampleReport {
+                rate: sample.rate.fraction(),
+                seed: sample.seed,
+                estimate,
+            }),
+        deprecations: cli_config.deprecations(),
+        generic_languages: cli_config.generic_languages(),
+        suppressed: cli_config.inline_suppressions(),
+        budgets: cli_config.budgets(),
+        ownership: Some(&findings)
+            .filter(|_| cli_config.has_code_owners())
+            .map(|findings| ownership(cli_config, findings)),
+    };
+
+    serde_json::to_value(&report).unwrap()
+}
+
+fn ownership<'a>(cli_config: &'a CliConfiguration, findings: &[&'a TokenUsage]) -> Ownership<'a> {
+    let mut ownership = Ownership::default();
+    for usage in findings {
+        let token = usage.result.token.token.as_str();
+        let owners = cli_config.owners(usage);
+        if owners.is_empty() {
+            ownership.unowned.push(token);
+        }
+        for owner in owners {
+            ownership.owners.entry(owner).or_default().push(token);
+        }
+    }
+    ownership
+}
+
+/// A single finding, in the same shape as entries of the report's `findings`
+pub fn finding(usage: &TokenUsage) -> serde_json::Value {
+    serde_json::to_value(Finding::new(usage, &Sources::default(), None, None, None)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TokenUsageBuilder;
+    use serde_json::json;
+
+    #[test]
+    fn reports_findings_with_their_definitions_and_occurrences() {
+        let usage = TokenUsageBuilder::new("full_name", "app/models/person.rb")
+            .with_address("2")
+            .with_language(Language::Ruby)
+            .build();
+        let finding =
+            serde_json::to_value(Finding::new(&usage, &Sources::default(), None, None, None))
+                .unwrap();
+
+        assert_eq!(finding["token"], "full_name");
+        assert_eq!(finding["likelihood"], "high");
+        assert_eq!(finding["reason"], "Only one occurrence exists");
+        assert_eq!(finding["definitions"][0]["path"], "app/models/person.rb");
+        assert_eq!(finding["definitions"][0]["address"], "2");
+        assert_eq!(finding["definitions"][0]["kind"], "Method");
+        assert_eq!(finding["definitions"][0]["language"], "Ruby");
+        assert_eq!(
+            finding["occurrences"]["files"],
+            json!({"app/models/person.rb": 1})
+        );
+        assert_eq!(finding["occurrences"]["total"]["occurrence_count"], 1);
+    }
 }
@@ -0,0 +1,66 @@
+use super::internal::{escape_markup as escape, CliConfiguration};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+use token_analysis::TokenUsage;
+
+// Each finding is a failed test case, grouped into one suite per defining file
+pub fn format(cli_config: &CliConfiguration, out: &mut dyn Write) -> io::Result<()> {
+    let usages = cli_config.findings();
+    let mut by_file: BTreeMap<&Path, Vec<&TokenUsage>> = BTreeMap::new();
+
+    for usage in usages.iter() {
+        for path in usage.result.token.defined_paths.iter() {
+            by_file.entry(&**path).or_default().push(usage);
+        }
+    }
+
+    let total: usize = by_file.values().map(|u| u.len()).sum();
+
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        out,
+        "<testsuites name=\"unused\" tests=\"{}\" failures=\"{}\">",
+        total, total
+    )?;
+
+    for (path, file_usages) in by_file {
+        let path = escape(&path.to_string_lossy());
+
+        writeln!(
+            out,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">",
+            path,
+            file_usages.len(),
+            file_usages.len()
+        )?;
+
+        for usage in file_usages {
+            let likelihood = &usage.usage_likelihood;
+
+            writeln!(
+                out,
+                "    <testcase name=\"{}\" classname=\"{}\">",
+                escape(&usage.result.token.token),
+                path
+            )?;
+            writeln!(
+                out,
+                "      <failure message=\"{}\" type=\"{}\">{}: {} ({} likelihood, {} occurrences). {}</failure>",
+                escape(&likelihood.reason),
+                likelihood.rule,
+                escape(&usage.result.token.token),
+                escape(&likelihood.reason),
+                likelihood.status,
+                usage.file_type_counts.total().occurrence_count,
+                escape(likelihood.rule.description())
+            )?;
+            writeln!(out, "    </testcase>")?;
+        }
+
+        writeln!(out, "  </testsuite>")?;
+    }
+
+    writeln!(out, "</testsuites>")?;
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use super::internal::CliConfiguration;
+use crate::sources::Sources;
+use std::io::{self, Write};
+use token_analysis::TokenUsage;
+
+// Written first, so parsers can check the layout before reading any findings. Fields are never
+// added, removed, or reordered within a version; a changed layout is a new version.
+const VERSION_LINE: &str = "# unused porcelain v1";
+
+/// One tab-separated line per definition, for scripts and editor plugins
+///
+/// Fields are, in order: rule ID, likelihood, score, occurrences, line, column, kind, token, and
+/// path. Line and column are 0 when unknown. Backslashes, tabs, and newlines in tokens and paths
+/// are escaped as `\\`, `\t`, and `\n`, and the output is never colored or translated.
+pub fn format(cli_config: &CliConfiguration, out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "{}", VERSION_LINE)?;
+
+    for usage in cli_config.findings() {
+        for line in lines(usage, cli_config.sources()) {
+            writeln!(out, "{}", line)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn lines(usage: &TokenUsage, sources: &Sources) -> Vec<String> {
+    let likelihood = &usage.usage_likelihood;
+    let definitions = usage.result.token.ordered_definitions();
+
+    definitions
+        .into_iter()
+        .map(|definition| {
+            let (line, column) = sources.position(definition);
+            [
+                likelihood.rule.id().to_string(),
+                likelihood.status.to_string(),
+                likelihood.score.to_string(),
+                usage.file_type_counts.total().occurrence_count.to_string(),
+                line.unwrap_or(0).to_string(),
+                column.unwrap_or(0).to_string(),
+                format!("{:?}", definition.kind),
+                escape(&usage.result.token.token),
+                escape(&definition.file_path.to_string_lossy()),
+            ]
+            .join("\t")
+        })
+        .collect()
+}
+
+fn escape(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TokenUsageBuilder;
+    use read_ctags::Language;
+
+    #[test]
+    fn writes_a_tab_separated_line_per_definition() {
+        let usage = TokenUsageBuilder::new("full_name", "app/odd\tname.rb")
+            .with_address("3")
+            .with_language(Language::Ruby)
+            .build();
+
+        assert_eq!(
+            lines(&usage, &Sources::default()),
+            vec![format!(
+                "UN001\thigh\t{}\t1\t3\t0\tMethod\tfull_name\tapp/odd\\tname.rb",
+                usage.usage_likelihood.score
+            )]
+        );
+    }
+}
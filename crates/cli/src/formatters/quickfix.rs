@@ -0,0 +1,67 @@
+use super::internal::CliConfiguration;
+use crate::sources::Sources;
+use std::io::{self, Write};
+use token_analysis::TokenUsage;
+
+// One `path:line:col: message` line per definition, matching vim's default errorformat
+pub fn format(cli_config: &CliConfiguration, out: &mut dyn Write) -> io::Result<()> {
+    for usage in cli_config.findings() {
+        for line in entries(usage, cli_config.sources()) {
+            writeln!(out, "{}", line)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn entries(usage: &TokenUsage, sources: &Sources) -> Vec<String> {
+    let token = &usage.result.token.token;
+    let likelihood = &usage.usage_likelihood;
+    let definitions = usage.result.token.ordered_definitions();
+
+    definitions
+        .into_iter()
+        .map(|definition| {
+            let (line, column) = sources.position(definition);
+            format!(
+                "{}:{}:{}: {} is likely unused: {} ({} likelihood, {})",
+                definition.file_path.to_string_lossy(),
+                line.unwrap_or(1),
+                column.unwrap_or(1),
+                token,
+                likelihood.reason,
+                likelihood.status,
+                likelihood.rule
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TokenUsageBuilder;
+    use codebase_files::MemoryFileSystem;
+    use read_ctags::Language;
+    use std::sync::Arc;
+
+    #[test]
+    fn locates_definitions_by_line_and_column() {
+        let file_system = Arc::new(MemoryFileSystem::default());
+        file_system.insert(
+            "app/person.rb",
+            "class Person\n  def full_name\n  end\nend\n",
+        );
+        let usage = TokenUsageBuilder::new("full_name", "app/person.rb")
+            .with_address("/^  def full_name$/")
+            .with_language(Language::Ruby)
+            .build();
+
+        assert_eq!(
+            entries(&usage, &Sources::new(file_system)),
+            vec![String::from(
+                "app/person.rb:2:7: full_name is likely unused: Only one occurrence exists (high likelihood, UN001)"
+            )]
+        );
+    }
+}
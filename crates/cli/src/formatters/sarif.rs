@@ -0,0 +1,271 @@
+use super::internal::CliConfiguration;
+use crate::severity::Severity;
+use crate::sources::Sources;
+use read_ctags::CtagItem;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::Path;
+use token_analysis::{Confidence, Rule, TokenUsage, UsageLikelihoodStatus};
+
+const SARIF_VERSION: &str = "2.1.0";
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const INFORMATION_URI: &str = "https://github.com/unused-code/unused_rs";
+
+#[derive(Serialize)]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+#[derive(Serialize)]
+struct Run {
+    tool: Tool,
+    results: Vec<SarifResult>,
+    properties: RunProperties,
+}
+
+// Findings before --top-files, --offset, and --limit, for reading the log a page at a time
+#[derive(Serialize)]
+struct RunProperties {
+    total: usize,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<ReportingDescriptor>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportingDescriptor {
+    id: &'static str,
+    name: &'static str,
+    short_description: Message,
+    help_uri: String,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: &'static str,
+    rule_index: usize,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    // Confidence the token can be deleted safely, from 0 to 100
+    rank: u8,
+    properties: ResultProperties,
+}
+
+// The token is recorded so suppressions can be read back exactly with --sarif-suppressions
+#[derive(Serialize)]
+struct ResultProperties {
+    token: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Region>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+    uri_base_id: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_column: Option<usize>,
+}
+
+impl SarifResult {
+    // Findings in a category with a configured severity are reported at its level
+    fn new(usage: &TokenUsage, severity: Option<Severity>, sources: &Sources) -> Self {
+        let rule = usage.usage_likelihood.rule;
+        let definitions = usage.result.token.ordered_definitions();
+
+        SarifResult {
+            rule_id: rule.id(),
+            rule_index: Rule::all().iter().position(|r| *r == rule).unwrap_or(0),
+            level: severity.map_or_else(
+                || level(&usage.usage_likelihood.status),
+                Severity::sarif_level,
+            ),
+            message: Message {
+                text: format!(
+                    "{} is likely unused: {}",
+                    usage.result.token.token, usage.usage_likelihood.reason
+                ),
+            },
+            locations: definitions
+                .into_iter()
+                .map(|d| location(d, sources))
+                .collect(),
+            rank: Confidence::estimate(usage).score,
+            properties: ResultProperties {
+                token: usage.result.token.token.to_string(),
+            },
+        }
+    }
+}
+
+fn level(status: &UsageLikelihoodStatus) -> &'static str {
+    match status {
+        UsageLikelihoodStatus::High => "warning",
+        UsageLikelihoodStatus::Medium | UsageLikelihoodStatus::Low => "note",
+    }
+}
+
+fn location(definition: &CtagItem, sources: &Sources) -> Location {
+    Location {
+        physical_location: PhysicalLocation {
+            artifact_location: ArtifactLocation {
+                uri: uri(&definition.file_path),
+                uri_base_id: "%SRCROOT%",
+            },
+            region: region(definition, sources),
+        },
+    }
+}
+
+// SARIF columns count UTF-16 code units by default, rather than the bytes tags resolve to
+fn region(definition: &CtagItem, sources: &Sources) -> Option<Region> {
+    let (line, column) = sources.position(definition);
+    let start_line = line?;
+    let columns = column
+        .zip(sources.contents(&definition.file_path))
+        .and_then(|(column, source)| {
+            let text = source.lines().nth(start_line - 1)?;
+            let start = text[..column - 1].encode_utf16().count() + 1;
+            Some((start, start + definition.name.encode_utf16().count()))
+        });
+
+    Some(Region {
+        start_line,
+        start_column: columns.map(|(start, _)| start),
+        end_column: columns.map(|(_, end)| end),
+    })
+}
+
+fn uri(path: &Path) -> String {
+    path.to_string_lossy()
+        .replace('\\', "/")
+        .trim_start_matches("./")
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+pub fn format(cli_config: &CliConfiguration, out: &mut dyn Write) -> io::Result<()> {
+    let severities = cli_config.severities();
+    let log = Log {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: vec![Run {
+            tool: Tool {
+                driver: Driver {
+                    name: "unused",
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: INFORMATION_URI,
+                    rules: Rule::all()
+                        .into_iter()
+                        .map(|r| ReportingDescriptor {
+                            id: r.id(),
+                            name: r.name(),
+                            short_description: Message {
+                                text: r.description().to_string(),
+                            },
+                            help_uri: r.documentation_url(),
+                        })
+                        .collect(),
+                },
+            },
+            results: cli_config
+                .findings()
+                .into_iter()
+                .map(|usage| SarifResult::new(usage, severities.of(usage), cli_config.sources()))
+                .collect(),
+            properties: RunProperties {
+                total: cli_config.for_json().len(),
+            },
+        }],
+    };
+
+    writeln!(out, "{}", serde_json::to_string(&log).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codebase_files::MemoryFileSystem;
+    use read_ctags::TokenKind;
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    #[test]
+    fn encodes_uris() {
+        assert_eq!(
+            uri(&PathBuf::from("./app/my models/person.rb")),
+            "app/my%20models/person.rb"
+        );
+    }
+
+    #[test]
+    fn counts_region_columns_in_utf16() {
+        let file_system = Arc::new(MemoryFileSystem::default());
+        file_system.insert("app/size.rb", "# café\n  größe = total\n");
+        let definition = CtagItem {
+            name: String::from("total"),
+            file_path: Path::new("app/size.rb").into(),
+            raw_path: "app/size.rb".into(),
+            address: String::from("2"),
+            language: None,
+            tags: BTreeMap::new(),
+            kind: TokenKind::Method,
+        };
+
+        let region = region(&definition, &Sources::new(file_system)).unwrap();
+
+        assert_eq!(region.start_line, 2);
+        assert_eq!(region.start_column, Some(11));
+        assert_eq!(region.end_column, Some(16));
+    }
+}
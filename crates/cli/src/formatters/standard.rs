@@ -1,74 +1,582 @@
-use super::internal::{colored::*, configuration_warnings, CliConfiguration};
-use std::collections::HashSet;
-use token_analysis::UsageLikelihoodStatus;
-
-pub fn format(cli_config: &CliConfiguration) {
-    let mut files_list = HashSet::new();
-    let mut tokens_list = HashSet::new();
-
-    for analysis in cli_config.analyses() {
-        tokens_list.insert(analysis.token.clone());
-        for v in analysis.files {
-            files_list.insert(v);
-        }
-
-        let display_token = match analysis.likelihood_status {
-            UsageLikelihoodStatus::High => analysis.token.red(),
-            UsageLikelihoodStatus::Medium => analysis.token.yellow(),
-            UsageLikelihoodStatus::Low => analysis.token.green(),
-        };
-        println!("{}", display_token);
-        println!("   Reason: {}", analysis.likelihood_reason.cyan());
-
-        println!(
-            "   Defined in: ({})",
+use super::internal::{colored::*, AnalyzedToken, CliConfiguration};
+use crate::budgets::BudgetReport;
+use crate::flags::GroupBy;
+use crate::messages::{fill, text, Message};
+use crate::snippets::Snippet;
+use crate::terminal::{fit, fit_path};
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, Write};
+use std::path::Path;
+use token_analysis::{DeprecatedUsage, Rule, UsageLikelihoodStatus};
+
+// Lines are fit to the terminal's width when there is one; otherwise they're never truncated
+#[derive(Clone, Copy)]
+struct Layout {
+    width: Option<usize>,
+}
+
+impl Layout {
+    fn path(&self, path: &Path, indent: usize) -> String {
+        let path = path.to_string_lossy();
+        match self.width {
+            Some(width) => fit_path(&path, width.saturating_sub(indent)),
+            None => path.to_string(),
+        }
+    }
+
+    fn text(&self, text: &str, indent: usize) -> String {
+        match self.width {
+            Some(width) => fit(text, width.saturating_sub(indent)),
+            None => text.to_string(),
+        }
+    }
+}
+
+pub fn format(
+    cli_config: &CliConfiguration,
+    out: &mut dyn Write,
+    width: Option<usize>,
+) -> io::Result<()> {
+    let layout = Layout { width };
+    let analyses = cli_config.analyses();
+    let tokens_list: HashSet<_> = analyses.iter().map(|a| a.token.clone()).collect();
+    let files_list: HashSet<_> = analyses.iter().flat_map(|a| a.files.clone()).collect();
+
+    // Tokens only referenced from tests are listed in their own section
+    let (test_only, analyses): (Vec<_>, Vec<_>) = analyses
+        .into_iter()
+        .partition(|a| a.rule == Rule::OnlyUsedInTests);
+    let test_only_count = test_only.len();
+
+    render_analyses(out, layout, cli_config.group_by(), analyses)?;
+
+    if !test_only.is_empty() {
+        writeln!(out, "{}", text(Message::OnlyUsedInTestsHeading).white())?;
+        render_analyses(out, layout, cli_config.group_by(), test_only)?;
+    }
+
+    if let Some(deprecations) = cli_config.deprecations() {
+        render_deprecations(out, layout, deprecations)?;
+    }
+
+    if cli_config.display_summary() {
+        usage_summary(
+            out,
+            tokens_list.len(),
+            files_list.len(),
+            test_only_count,
+            cli_config,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn render_analyses(
+    out: &mut dyn Write,
+    layout: Layout,
+    group_by: GroupBy,
+    analyses: Vec<AnalyzedToken>,
+) -> io::Result<()> {
+    match group_by {
+        GroupBy::Token => render_by_token(out, layout, analyses),
+        GroupBy::File => render_by_file(out, layout, analyses),
+        GroupBy::Scope => render_by_scope(out, layout, analyses),
+        GroupBy::Kind => render_by_kind(out, layout, analyses),
+        GroupBy::Owner => render_by_owner(out, layout, analyses),
+    }
+}
+
+fn render_by_token(
+    out: &mut dyn Write,
+    layout: Layout,
+    analyses: Vec<AnalyzedToken>,
+) -> io::Result<()> {
+    for analysis in analyses {
+        writeln!(out, "{}", display_token(&analysis))?;
+        writeln!(
+            out,
+            "   {}: {} [{}]",
+            text(Message::Reason),
+            analysis.likelihood_reason.cyan(),
+            analysis.rule
+        )?;
+
+        writeln!(
+            out,
+            "   {}: ({})",
+            text(Message::DefinedIn),
             analysis.defined_paths.len().to_string().yellow()
-        );
-        for d in analysis.defined_paths {
-            println!("   * {}", d.to_string_lossy().yellow());
+        )?;
+        for d in &analysis.defined_paths {
+            writeln!(out, "   * {}", layout.path(d, 5).yellow())?;
+            for (_, snippet) in analysis.snippets.iter().filter(|(path, _)| path == d) {
+                render_snippet(out, layout, snippet)?;
+            }
+        }
+
+        if !analysis.generic_languages.is_empty() {
+            writeln!(
+                out,
+                "   {}",
+                fill(
+                    Message::GenericRules,
+                    &[&analysis.generic_languages.join(", ").yellow()]
+                )
+            )?;
+        }
+
+        if let Some(change) = &analysis.last_change {
+            writeln!(
+                out,
+                "   {}",
+                fill(
+                    Message::LastChanged,
+                    &[
+                        &change.date,
+                        &change.age_days.to_string().yellow(),
+                        &change.author.cyan(),
+                        &change.commit
+                    ]
+                )
+            )?;
+        }
+
+        match &analysis.coverage {
+            Some(coverage) if coverage.never_run => writeln!(
+                out,
+                "   {}",
+                fill(
+                    Message::NeverRun,
+                    &[&coverage.executable_lines.to_string().red()]
+                )
+            )?,
+            Some(coverage) => writeln!(
+                out,
+                "   {}",
+                fill(
+                    Message::Covered,
+                    &[
+                        &coverage.covered_lines.to_string().yellow(),
+                        &coverage.executable_lines
+                    ]
+                )
+            )?,
+            None => {}
+        }
+
+        if let Some(hint) = &analysis.rename_hint {
+            writeln!(
+                out,
+                "   {}",
+                fill(
+                    Message::RenameHint,
+                    &[
+                        &analysis.token.yellow(),
+                        &hint.token.cyan(),
+                        &hint.references
+                    ]
+                )
+            )?;
         }
 
         let occurred_count = analysis.occurred_paths.len();
 
         if occurred_count > 0 {
-            println!("   Found in: ({})", occurred_count.to_string().yellow());
+            writeln!(
+                out,
+                "   {}: ({})",
+                text(Message::FoundIn),
+                occurred_count.to_string().yellow()
+            )?;
             for d in &analysis.occurred_paths {
-                println!("   * {}", d.to_string_lossy().yellow());
+                writeln!(out, "   * {}", layout.path(d, 5).yellow())?;
             }
         }
 
-        println!("");
+        writeln!(out)?;
     }
 
-    if cli_config.display_summary() {
-        usage_summary(tokens_list.len(), files_list.len(), &cli_config);
+    Ok(())
+}
+
+fn render_by_file(
+    out: &mut dyn Write,
+    layout: Layout,
+    analyses: Vec<AnalyzedToken>,
+) -> io::Result<()> {
+    let mut by_file: BTreeMap<String, Vec<&AnalyzedToken>> = BTreeMap::new();
+
+    for analysis in analyses.iter() {
+        for path in analysis.defined_paths.iter() {
+            by_file
+                .entry(path.to_string_lossy().to_string())
+                .or_default()
+                .push(analysis);
+        }
+    }
+
+    render_groups(out, layout, by_file, |_| 0)
+}
+
+fn render_by_kind(
+    out: &mut dyn Write,
+    layout: Layout,
+    analyses: Vec<AnalyzedToken>,
+) -> io::Result<()> {
+    let mut by_kind: BTreeMap<String, Vec<&AnalyzedToken>> = BTreeMap::new();
+
+    for analysis in analyses.iter() {
+        for kind in analysis.kinds.iter() {
+            by_kind.entry(kind.to_string()).or_default().push(analysis);
+        }
+    }
+
+    render_groups(out, layout, by_kind, |_| 0)
+}
+
+fn render_by_owner(
+    out: &mut dyn Write,
+    layout: Layout,
+    analyses: Vec<AnalyzedToken>,
+) -> io::Result<()> {
+    let mut by_owner: BTreeMap<String, Vec<&AnalyzedToken>> = BTreeMap::new();
+
+    for analysis in analyses.iter() {
+        if analysis.owners.is_empty() {
+            by_owner
+                .entry(String::from(UNOWNED))
+                .or_default()
+                .push(analysis);
+        }
+        for owner in analysis.owners.iter() {
+            by_owner
+                .entry(owner.to_string())
+                .or_default()
+                .push(analysis);
+        }
+    }
+
+    render_groups(out, layout, by_owner, |_| 0)
+}
+
+pub(crate) const UNOWNED: &str = "(unowned)";
+
+// Scopes sort so nested scopes follow the scope enclosing them, and are indented by depth
+fn render_by_scope(
+    out: &mut dyn Write,
+    layout: Layout,
+    analyses: Vec<AnalyzedToken>,
+) -> io::Result<()> {
+    let mut by_scope: BTreeMap<String, Vec<&AnalyzedToken>> = BTreeMap::new();
+
+    for analysis in analyses.iter() {
+        if analysis.scopes.is_empty() {
+            by_scope
+                .entry(String::from(TOP_LEVEL_SCOPE))
+                .or_default()
+                .push(analysis);
+        }
+        for scope in analysis.scopes.iter() {
+            by_scope
+                .entry(scope.to_string())
+                .or_default()
+                .push(analysis);
+        }
+    }
+
+    render_groups(out, layout, by_scope, scope_depth)
+}
+
+const TOP_LEVEL_SCOPE: &str = "(top level)";
+
+fn scope_depth(scope: &str) -> usize {
+    scope.matches("::").count() + scope.matches('.').count()
+}
+
+// Tokens within a group are padded so their reasons line up in a column
+fn render_groups(
+    out: &mut dyn Write,
+    layout: Layout,
+    groups: BTreeMap<String, Vec<&AnalyzedToken>>,
+    depth: impl Fn(&str) -> usize,
+) -> io::Result<()> {
+    for (group, group_analyses) in groups {
+        let indent = "  ".repeat(depth(&group));
+        let count = group_analyses.len().to_string();
+
+        writeln!(
+            out,
+            "{}{} ({})",
+            indent,
+            layout
+                .path(Path::new(&group), indent.len() + count.len() + 3)
+                .cyan(),
+            count.yellow()
+        )?;
+
+        let token_width = group_analyses
+            .iter()
+            .map(|a| a.token.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        for analysis in group_analyses {
+            let rule = format!("[{}]", analysis.rule);
+            let used = indent.len() + 5 + token_width + 2 + rule.len() + 1;
+            writeln!(
+                out,
+                "{}   * {:token_width$}  {} {}",
+                indent,
+                display_token(analysis),
+                layout.text(&analysis.likelihood_reason, used),
+                rule.dimmed(),
+                token_width = token_width
+            )?;
+        }
+
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+fn render_deprecations(
+    out: &mut dyn Write,
+    layout: Layout,
+    deprecations: &[DeprecatedUsage],
+) -> io::Result<()> {
+    writeln!(out, "{}", text(Message::DeprecatedUsagesHeading).white())?;
+
+    for deprecation in deprecations {
+        writeln!(
+            out,
+            "   {}: {}",
+            deprecation.token.yellow(),
+            colorize_total(deprecation.usages)
+        )?;
+        for (path, count) in deprecation.files.iter() {
+            let count = format!("({})", count);
+            writeln!(
+                out,
+                "   * {} {}",
+                layout.path(path, 6 + count.len()).yellow(),
+                count
+            )?;
+        }
+    }
+
+    writeln!(out)
+}
+
+// Source lines numbered like an editor gutter, with the definition's line marked
+fn render_snippet(out: &mut dyn Write, layout: Layout, snippet: &Snippet) -> io::Result<()> {
+    let width = (snippet.start_line + snippet.lines.len() - 1)
+        .to_string()
+        .len();
+
+    for (number, line) in snippet.numbered() {
+        let marker = if number == snippet.line { ">" } else { " " };
+        let gutter = format!("{} {:>width$} |", marker, number, width = width);
+        let line = layout.text(line, 6 + gutter.len());
+        if number == snippet.line {
+            writeln!(out, "     {} {}", gutter.cyan(), line)?;
+        } else {
+            writeln!(out, "     {} {}", gutter.cyan(), line.dimmed())?;
+        }
     }
 
-    configuration_warnings(cli_config);
+    Ok(())
 }
 
-fn usage_summary(tokens_count: usize, files_count: usize, cli_config: &CliConfiguration) {
-    println!("");
-    println!("{}", "== UNUSED SUMMARY ==".white());
-    println!("   Tokens found: {}", colorize_total(tokens_count));
-    println!("   Files found: {}", colorize_total(files_count));
-    println!(
-        "   Applied language filters: {}",
-        format!("{}", cli_config.language_restriction()).cyan()
-    );
-    println!(
-        "   Sort order: {}",
+fn display_token(analysis: &AnalyzedToken) -> ColoredString {
+    match analysis.likelihood_status {
+        UsageLikelihoodStatus::High => analysis.token.red(),
+        UsageLikelihoodStatus::Medium => analysis.token.yellow(),
+        UsageLikelihoodStatus::Low => analysis.token.green(),
+    }
+}
+
+fn usage_summary(
+    out: &mut dyn Write,
+    tokens_count: usize,
+    files_count: usize,
+    test_only_count: usize,
+    cli_config: &CliConfiguration,
+) -> io::Result<()> {
+    writeln!(out)?;
+    writeln!(out, "{}", text(Message::SummaryHeading).white())?;
+    writeln!(
+        out,
+        "   {}: {}",
+        text(Message::TokensFound),
+        colorize_total(tokens_count)
+    )?;
+    writeln!(
+        out,
+        "   {}: {}",
+        text(Message::FilesFound),
+        colorize_total(files_count)
+    )?;
+    if test_only_count > 0 {
+        writeln!(
+            out,
+            "   {}: {}",
+            text(Message::OnlyUsedInTests),
+            colorize_total(test_only_count)
+        )?;
+    }
+    writeln!(
+        out,
+        "   {}: {}",
+        text(Message::TagsSource),
+        cli_config.tags_source().cyan()
+    )?;
+    writeln!(
+        out,
+        "   {}: {}",
+        text(Message::LanguageFilters),
+        cli_config.language_restriction().to_string().cyan()
+    )?;
+    writeln!(
+        out,
+        "   {}: {}",
+        text(Message::SortOrder),
         format!("{}", cli_config.sort_order()).cyan()
-    );
-    println!(
-        "   Usage likelihood: {}",
+    )?;
+    writeln!(
+        out,
+        "   {}: {}",
+        text(Message::UsageLikelihood),
         cli_config.usage_likelihood_filter().join(", ").cyan()
-    );
-    println!(
-        "   Configuration setting: {}",
+    )?;
+    writeln!(
+        out,
+        "   {}: {}",
+        text(Message::ConfigurationSetting),
         cli_config.configuration_name().cyan()
-    );
-    println!("");
+    )?;
+    if !cli_config.framework_profiles().is_empty() {
+        writeln!(
+            out,
+            "   {}: {}",
+            text(Message::FrameworkProfiles),
+            cli_config.framework_profiles().join(", ").cyan()
+        )?;
+    }
+    let generic_languages = cli_config.generic_languages();
+    if !generic_languages.is_empty() {
+        writeln!(
+            out,
+            "   {}",
+            fill(
+                Message::AnalyzedWithGenericRules,
+                &[&generic_languages
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+                    .yellow()]
+            )
+        )?;
+    }
+    let suppressed = cli_config.inline_suppressions().len();
+    if suppressed > 0 {
+        writeln!(
+            out,
+            "   {}: {}",
+            text(Message::SuppressedInline),
+            suppressed.to_string().yellow()
+        )?;
+    }
+    if let Some(budgets) = cli_config.budgets() {
+        budget_summary(out, &budgets)?;
+    }
+    if let Some(deprecations) = cli_config.deprecations() {
+        writeln!(
+            out,
+            "   {}: {}",
+            text(Message::DeprecatedUsages),
+            colorize_total(deprecations.iter().map(|d| d.usages).sum())
+        )?;
+    }
+    if let Some((sample, estimate)) = cli_config.sample_estimate() {
+        writeln!(
+            out,
+            "   {}",
+            fill(
+                Message::Sampled,
+                &[
+                    &estimate.sampled.to_string().cyan(),
+                    &estimate.population.to_string().cyan(),
+                    &sample.rate,
+                    &sample.seed
+                ]
+            )
+        )?;
+        writeln!(
+            out,
+            "   {}",
+            fill(
+                Message::EstimatedFindings,
+                &[
+                    &colorize_total(estimate.estimated_findings),
+                    &estimate.lower_bound,
+                    &estimate.upper_bound
+                ]
+            )
+        )?;
+    }
+    if cli_config.page().is_paged() {
+        writeln!(
+            out,
+            "   {}",
+            fill(
+                Message::Showing,
+                &[
+                    &cli_config.findings().len().to_string().cyan(),
+                    &cli_config.for_json().len().to_string().cyan()
+                ]
+            )
+        )?;
+    }
+    writeln!(out)
+}
+
+// Each budget's findings against its limits, and the findings outside every budget
+fn budget_summary(out: &mut dyn Write, budgets: &BudgetReport) -> io::Result<()> {
+    for budget in budgets.budgets.iter() {
+        let mut spent = vec![];
+        if let Some(max) = budget.max_findings {
+            spent.push(fill(
+                Message::BudgetFindings,
+                &[&(budget.errors + budget.warnings), &max],
+            ));
+        }
+        if let Some(max) = budget.max_errors {
+            spent.push(fill(Message::BudgetErrors, &[&budget.errors, &max]));
+        }
+        if let Some(max) = budget.max_warnings {
+            spent.push(fill(Message::BudgetWarnings, &[&budget.warnings, &max]));
+        }
+        let spent = spent.join(", ");
+
+        writeln!(
+            out,
+            "   {}: {}",
+            fill(Message::Budget, &[&budget.glob]),
+            if budget.exceeded {
+                format!("{} ({})", spent, text(Message::OverBudget)).red()
+            } else {
+                spent.green()
+            }
+        )?;
+    }
+    writeln!(
+        out,
+        "   {}: {}",
+        text(Message::OutsideBudgets),
+        colorize_total(budgets.unbudgeted)
+    )
 }
 
 fn colorize_total(amount: usize) -> colored::ColoredString {
@@ -0,0 +1,178 @@
+use super::cli_configuration::CliConfiguration;
+use super::flags::GraphNodes;
+use super::formatters::internal::configuration_warnings;
+use read_ctags::CtagItem;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use token_analysis::TokenUsage;
+
+// Fill color for nodes defining tokens likely to be unused
+const UNUSED_FILL: &str = "#f4cccc";
+
+#[derive(Default)]
+struct Graph {
+    // Node names, with the number of tokens likely unused each defines
+    nodes: BTreeMap<String, usize>,
+    // Edges from a referencing node to a defining node, with the number of references
+    edges: BTreeMap<(String, String), usize>,
+}
+
+pub fn render(cli_config: &CliConfiguration, nodes: GraphNodes) {
+    let usages = cli_config.included();
+    let reported: Vec<bool> = usages.iter().map(|u| cli_config.reported(u)).collect();
+
+    print!("{}", to_dot(&graph(&usages, &reported, nodes)));
+    configuration_warnings(cli_config);
+}
+
+// Edges point from the files referencing a token to where it's defined, so nodes without
+// incoming edges define only tokens nothing else references
+fn graph(usages: &[&TokenUsage], reported: &[bool], nodes: GraphNodes) -> Graph {
+    let scopes_by_path = scopes_by_path(usages);
+    let mut graph = Graph::default();
+
+    for (usage, reported) in usages.iter().zip(reported) {
+        let defining: BTreeSet<String> = usage
+            .result
+            .token
+            .definitions
+            .iter()
+            .map(|d| definition_node(d, nodes))
+            .collect();
+
+        for node in defining.iter() {
+            *graph.nodes.entry(node.clone()).or_default() += usize::from(*reported);
+        }
+
+        for (path, count) in usage.result.occurrences.iter() {
+            let referencing = match nodes {
+                GraphNodes::File => vec![path_node(path)],
+                GraphNodes::Scope => match scopes_by_path.get(path.as_path()) {
+                    Some(scopes) => scopes.iter().cloned().collect(),
+                    None => vec![path_node(path)],
+                },
+            };
+
+            for from in referencing {
+                for to in defining.iter().filter(|to| **to != from) {
+                    graph.nodes.entry(from.clone()).or_default();
+                    *graph.edges.entry((from.clone(), to.clone())).or_default() += count;
+                }
+            }
+        }
+    }
+
+    graph
+}
+
+// A file referencing a token in scope mode is represented by the scopes it defines
+fn scopes_by_path<'a>(usages: &[&'a TokenUsage]) -> HashMap<&'a Path, BTreeSet<String>> {
+    let mut scopes: HashMap<&Path, BTreeSet<String>> = HashMap::new();
+
+    for usage in usages {
+        for definition in usage.result.token.definitions.iter() {
+            if let Some(scope) = definition.scope() {
+                scopes
+                    .entry(&definition.file_path)
+                    .or_default()
+                    .insert(scope.to_string());
+            }
+        }
+    }
+
+    scopes
+}
+
+fn definition_node(definition: &CtagItem, nodes: GraphNodes) -> String {
+    match (nodes, definition.scope()) {
+        (GraphNodes::Scope, Some(scope)) => scope.to_string(),
+        _ => path_node(&definition.file_path),
+    }
+}
+
+fn path_node(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+fn to_dot(graph: &Graph) -> String {
+    let mut dot = String::from("digraph unused {\n    rankdir=LR;\n    node [shape=box];\n");
+
+    for (node, unused) in graph.nodes.iter() {
+        if *unused == 0 {
+            dot.push_str(&format!("    {};\n", quote(node)));
+        } else {
+            dot.push_str(&format!(
+                "    {} [label={}, style=filled, fillcolor=\"{}\"];\n",
+                quote(node),
+                quote(&format!("{}\n{} likely unused", node, unused)),
+                UNUSED_FILL
+            ));
+        }
+    }
+
+    for ((from, to), count) in graph.edges.iter() {
+        dot.push_str(&format!(
+            "    {} -> {} [label=\"{}\", weight={}];\n",
+            quote(from),
+            quote(to),
+            count,
+            count
+        ));
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+fn quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TokenUsageBuilder;
+
+    #[test]
+    fn links_referencing_files_to_definitions() {
+        let usages = [
+            TokenUsageBuilder::new("name", "lib/person.rb")
+                .with_tag("class", "Person")
+                .with_occurrences(&[("lib/person.rb", 2), ("lib/order.rb", 3)])
+                .build(),
+            TokenUsageBuilder::new("total", "lib/order.rb")
+                .with_tag("class", "Order")
+                .build(),
+        ];
+        let usages: Vec<&TokenUsage> = usages.iter().collect();
+
+        assert_eq!(
+            to_dot(&graph(&usages, &[false, true], GraphNodes::File)),
+            "digraph unused {
+    rankdir=LR;
+    node [shape=box];
+    \"lib/order.rb\" [label=\"lib/order.rb\\n1 likely unused\", style=filled, fillcolor=\"#f4cccc\"];
+    \"lib/person.rb\";
+    \"lib/order.rb\" -> \"lib/person.rb\" [label=\"3\", weight=3];
+}
+"
+        );
+
+        let by_scope = graph(&usages, &[false, true], GraphNodes::Scope);
+        assert_eq!(
+            by_scope.edges.into_iter().collect::<Vec<_>>(),
+            vec![((String::from("Order"), String::from("Person")), 3)]
+        );
+    }
+
+    #[test]
+    fn quotes_node_names() {
+        assert_eq!(quote("a\"b\\c\nd"), "\"a\\\"b\\\\c\\nd\"");
+    }
+}
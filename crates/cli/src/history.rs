@@ -0,0 +1,253 @@
+use super::blame;
+use super::cli_configuration::CliConfiguration;
+use super::context::Context;
+use super::formatters::internal::{configuration_warnings, print_json};
+use super::severity::Category;
+use super::Format;
+use codebase_files::writes::Writes;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Written at the project's root (or a sub-project's) by `history record`, one run per line
+const FILE_NAME: &str = ".unused-history.jsonl";
+
+// Categories in the order their columns are shown
+const CATEGORIES: [Category; 4] = [
+    Category::Unused,
+    Category::TestOnly,
+    Category::DeadFile,
+    Category::LowUsage,
+];
+
+/// A summary of one run, appended to the history by `history record`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Commit checked out when the run was recorded, outside a git repository `None`
+    pub commit: Option<String>,
+    /// Tokens analyzed, excluding ignored tokens
+    pub tokens: usize,
+    /// Findings reported, before paging
+    pub findings: usize,
+    /// Findings in each category
+    pub categories: BTreeMap<Category, usize>,
+}
+
+/// Where the history for the project (or sub-project) being analyzed is kept
+pub fn path(context: &Context) -> PathBuf {
+    match &context.sub_project {
+        Some(project) => project.root.join(FILE_NAME),
+        None => PathBuf::from(FILE_NAME),
+    }
+}
+
+/// Summarize the run and append it to the history at `path`
+pub fn record(cli_config: &CliConfiguration, path: &Path) {
+    let run = Run {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        commit: head_commit(),
+        tokens: cli_config.included().len(),
+        findings: cli_config.for_json().len(),
+        categories: cli_config.categories(),
+    };
+
+    match append(path, &run, cli_config.writes()) {
+        Ok(()) => println!(
+            "Recorded {} findings{} to {}",
+            run.findings.to_string().yellow(),
+            run.commit
+                .as_deref()
+                .map(|commit| format!(" at {}", short(commit)))
+                .unwrap_or_default(),
+            path.to_string_lossy().cyan()
+        ),
+        Err(e) => eprintln!(
+            "{}",
+            format!("Unable to record history to {}: {}", path.display(), e).red()
+        ),
+    }
+
+    configuration_warnings(cli_config);
+}
+
+/// Show the runs recorded at `path`, oldest first, keeping only the `last` runs when given
+pub fn show(context: &Context, path: &Path, last: Option<usize>) {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format!("Unable to read {}: {}", path.display(), e).red()
+            );
+            return;
+        }
+    };
+    let (runs, malformed) = parse(&contents);
+    let runs = &runs[runs.len().saturating_sub(last.unwrap_or(runs.len()))..];
+
+    match context.format {
+        Format::Json => print_json(runs),
+        _ if runs.is_empty() => println!(
+            "No runs recorded in {}; record one with `unused history record`",
+            path.display()
+        ),
+        _ => print!("{}", table(runs)),
+    }
+
+    if malformed > 0 {
+        eprintln!(
+            "{}",
+            format!(
+                "Skipped {} malformed lines in {}",
+                malformed,
+                path.display()
+            )
+            .yellow()
+        );
+    }
+}
+
+fn append(path: &Path, run: &Run, writes: Writes) -> io::Result<()> {
+    let mut contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents.push_str(&serde_json::to_string(run)?);
+    contents.push('\n');
+
+    writes.write(path, contents)
+}
+
+// Runs from each line that parses, and the number of lines that don't
+fn parse(contents: &str) -> (Vec<Run>, usize) {
+    let mut runs = vec![];
+    let mut malformed = 0;
+
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(run) => runs.push(run),
+            Err(_) => malformed += 1,
+        }
+    }
+
+    (runs, malformed)
+}
+
+// A row per run, with the change in findings from the run before it
+fn table(runs: &[Run]) -> String {
+    let mut rows = vec![vec![
+        String::from("Date"),
+        String::from("Commit"),
+        String::from("Tokens"),
+        String::from("Findings"),
+        String::from("Change"),
+    ]];
+    rows[0].extend(CATEGORIES.iter().map(|c| label(*c)));
+
+    let mut previous: Option<usize> = None;
+    for run in runs {
+        let mut row = vec![
+            blame::date(run.timestamp),
+            run.commit.as_deref().map(short).unwrap_or("-").to_string(),
+            run.tokens.to_string(),
+            run.findings.to_string(),
+            previous
+                .map(|previous| change(previous, run.findings))
+                .unwrap_or_else(|| String::from("-")),
+        ];
+        row.extend(
+            CATEGORIES
+                .iter()
+                .map(|c| run.categories.get(c).copied().unwrap_or(0).to_string()),
+        );
+        rows.push(row);
+        previous = Some(run.findings);
+    }
+
+    let widths: Vec<usize> = (0..rows[0].len())
+        .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or(0))
+        .collect();
+    rows.iter()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .collect();
+            format!("   {}\n", cells.join("  ").trim_end())
+        })
+        .collect()
+}
+
+fn label(category: Category) -> String {
+    match category {
+        Category::Unused => "Unused",
+        Category::TestOnly => "Test-only",
+        Category::DeadFile => "Dead-file",
+        Category::LowUsage => "Low-usage",
+    }
+    .to_string()
+}
+
+fn change(previous: usize, current: usize) -> String {
+    match current.cmp(&previous) {
+        std::cmp::Ordering::Greater => format!("+{}", current - previous),
+        std::cmp::Ordering::Less => format!("-{}", previous - current),
+        std::cmp::Ordering::Equal => String::from("0"),
+    }
+}
+
+fn short(commit: &str) -> &str {
+    &commit[..commit.len().min(8)]
+}
+
+fn head_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_a_trend_from_recorded_runs() {
+        let (runs, malformed) = parse(concat!(
+            r#"{"timestamp":1709942400,"commit":"0123456789abcdef","tokens":120,"findings":14,"categories":{"unused":9,"test-only":3,"low-usage":2}}"#,
+            "\nnot json\n",
+            r#"{"timestamp":1710547200,"commit":null,"tokens":118,"findings":11,"categories":{"unused":6,"test-only":3,"dead-file":2}}"#,
+            "\n"
+        ));
+
+        assert_eq!(malformed, 1);
+        assert_eq!(runs[1].categories.get(&Category::DeadFile), Some(&2));
+        assert_eq!(
+            table(&runs),
+            concat!(
+                "   Date        Commit    Tokens  Findings  Change  Unused  Test-only  Dead-file  Low-usage\n",
+                "   2024-03-09  01234567  120     14        -       9       3          0          2\n",
+                "   2024-03-16  -         118     11        -3      6       3          2          0\n",
+            )
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use super::context::Context;
+use super::error_message;
+use super::exit_status::ExitStatus;
+use super::flags::AnalysisFlags;
+use codebase_files::text::is_identifier_char;
+use codebase_files::writes::Writes;
+use codebase_files::DiskFileSystem;
+use colored::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use token_analysis::TokenUsage;
+
+// Written to the repository's hooks directory by `hook install`
+const PRE_COMMIT_SCRIPT: &str =
+    "#!/bin/sh\n# Installed by `unused hook install`\nexec unused hook pre-commit\n";
+
+/// Analyze the files staged in git, exiting with status `1` when they introduce definitions
+/// likely to be unused
+///
+/// Only tokens defined in staged files are reported, and of those only tokens the committed
+/// versions of those files don't already mention, so existing findings never block a commit.
+/// Occurrence counts are cached by file contents, so unchanged files aren't searched again.
+pub fn pre_commit(context: &Context, mut flags: AnalysisFlags) -> ExitStatus {
+    flags.staged = true;
+    flags.scan_cache = true;
+
+    let cli_config = match super::build_configuration(context, flags) {
+        Ok(cli_config) => cli_config,
+        Err(e) => {
+            error_message::failed_token_parse(e);
+            return ExitStatus::Error;
+        }
+    };
+    for warning in cli_config.loading_warnings() {
+        eprintln!("{}", warning.yellow());
+    }
+
+    let introduced: Vec<&TokenUsage> = cli_config
+        .for_json()
+        .into_iter()
+        .filter(|usage| {
+            usage
+                .result
+                .token
+                .defined_paths
+                .iter()
+                .any(|path| introduced(&usage.result.token.token, committed(path).as_deref()))
+        })
+        .collect();
+    if introduced.is_empty() {
+        return ExitStatus::Clean;
+    }
+
+    eprintln!(
+        "{}",
+        format!(
+            "unused: staged changes introduce {} definitions likely to be unused",
+            introduced.len()
+        )
+        .red()
+    );
+    for usage in introduced {
+        for definition in usage.result.token.ordered_definitions() {
+            let location = match definition.line(&DiskFileSystem) {
+                Some(line) => format!("{}:{}", definition.file_path.display(), line),
+                None => definition.file_path.display().to_string(),
+            };
+            eprintln!(
+                "   {} {} ({})",
+                usage.result.token.token.yellow(),
+                location.cyan(),
+                usage.usage_likelihood.reason
+            );
+        }
+    }
+    eprintln!();
+    eprintln!(
+        "Remove or use them before committing, or skip this check with `git commit --no-verify`."
+    );
+
+    ExitStatus::Findings
+}
+
+/// Install a git pre-commit hook running `unused hook pre-commit`, leaving any existing hook
+/// in place
+pub fn install(writes: Writes) -> ExitStatus {
+    let path = match hooks_dir() {
+        Ok(dir) => dir.join("pre-commit"),
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format!("Unable to find the git hooks directory: {}", e).red()
+            );
+            return ExitStatus::Error;
+        }
+    };
+
+    if path.exists() {
+        let installed = fs::read_to_string(&path)
+            .map(|script| script.contains("unused hook pre-commit"))
+            .unwrap_or(false);
+        if installed {
+            println!(
+                "The pre-commit hook at {} already runs unused",
+                path.display()
+            );
+            return ExitStatus::Clean;
+        }
+        eprintln!(
+            "{}",
+            format!("A pre-commit hook already exists at {}", path.display()).yellow()
+        );
+        eprintln!("Add `unused hook pre-commit` to it to run unused before each commit.");
+        return ExitStatus::Error;
+    }
+
+    match writes
+        .write(&path, PRE_COMMIT_SCRIPT)
+        .and_then(|_| make_executable(&path))
+    {
+        Ok(()) => {
+            println!(
+                "Installed a pre-commit hook at {}",
+                path.to_string_lossy().cyan()
+            );
+            ExitStatus::Clean
+        }
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format!("Unable to install the hook at {}: {}", path.display(), e).red()
+            );
+            ExitStatus::Error
+        }
+    }
+}
+
+// Is `token` new to a file, given the contents of the file as of `HEAD` (`None` when the file
+// isn't committed)?
+fn introduced(token: &str, committed: Option<&str>) -> bool {
+    let contents = match committed {
+        Some(contents) => contents,
+        None => return true,
+    };
+
+    !contents.match_indices(token).any(|(start, _)| {
+        let end = start + token.len();
+        !contents[..start].ends_with(is_identifier_char)
+            && !contents[end..].starts_with(is_identifier_char)
+    })
+}
+
+fn committed(path: &Path) -> Option<String> {
+    git(&["show", &format!("HEAD:./{}", path.display())]).ok()
+}
+
+fn hooks_dir() -> Result<PathBuf, String> {
+    git(&["rev-parse", "--git-path", "hooks"]).map(|dir| PathBuf::from(dir.trim()))
+}
+
+fn git(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).map_err(|e| e.to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_missing_from_committed_files_are_introduced() {
+        assert!(introduced("total", None));
+        assert!(introduced("total", Some("def subtotal\nend\n")));
+        assert!(!introduced("total", Some("def subtotal\n  total\nend\n")));
+    }
+}
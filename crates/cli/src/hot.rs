@@ -0,0 +1,137 @@
+use super::cli_configuration::CliConfiguration;
+use super::formatters::internal::{configuration_warnings, print_json};
+use super::Format;
+use colored::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use token_analysis::TokenUsage;
+
+#[derive(Serialize)]
+pub struct HotToken<'a> {
+    pub token: &'a str,
+    pub occurrences: usize,
+    pub files: usize,
+}
+
+#[derive(Serialize)]
+struct HotFile<'a> {
+    path: &'a Path,
+    references: usize,
+    tokens: usize,
+}
+
+pub fn render(cli_config: &CliConfiguration, top: usize) {
+    let usages = cli_config.included();
+    let tokens = hot_tokens(&usages, top);
+    let files = hot_files(&usages, top);
+
+    match cli_config.format() {
+        Format::Json => print_json(&serde_json::json!({ "tokens": tokens, "files": files })),
+        _ => {
+            println!("{}", "== MOST REFERENCED TOKENS ==".white());
+            for t in tokens.iter() {
+                println!(
+                    "   {} {} ({} files)",
+                    t.occurrences.to_string().yellow(),
+                    t.token.cyan(),
+                    t.files
+                );
+            }
+            println!();
+            println!("{}", "== MOST REFERENCED FILES ==".white());
+            for f in files.iter() {
+                println!(
+                    "   {} {} ({} tokens)",
+                    f.references.to_string().yellow(),
+                    f.path.to_string_lossy().cyan(),
+                    f.tokens
+                );
+            }
+        }
+    }
+
+    configuration_warnings(cli_config);
+}
+
+pub fn hot_tokens<'a>(usages: &[&'a TokenUsage], top: usize) -> Vec<HotToken<'a>> {
+    let mut tokens: Vec<HotToken> = usages
+        .iter()
+        .map(|u| {
+            let total = u.file_type_counts.total();
+            HotToken {
+                token: &u.result.token.token,
+                occurrences: total.occurrence_count,
+                files: total.file_count,
+            }
+        })
+        .collect();
+
+    tokens.sort_by(|a, b| b.occurrences.cmp(&a.occurrences).then(a.token.cmp(b.token)));
+    tokens.truncate(top);
+    tokens
+}
+
+// A file's references are occurrences, in other files, of the tokens it defines
+fn hot_files<'a>(usages: &[&'a TokenUsage], top: usize) -> Vec<HotFile<'a>> {
+    let mut by_path: HashMap<&Path, (usize, usize)> = HashMap::new();
+
+    for usage in usages {
+        for path in usage.result.token.defined_paths.iter() {
+            let references: usize = usage
+                .result
+                .occurrences
+                .iter()
+                .filter(|(p, _)| p.as_path() != &**path)
+                .map(|(_, count)| count)
+                .sum();
+            let entry = by_path.entry(&**path).or_insert((0, 0));
+            entry.0 += references;
+            entry.1 += 1;
+        }
+    }
+
+    let mut files: Vec<HotFile> = by_path
+        .into_iter()
+        .map(|(path, (references, tokens))| HotFile {
+            path,
+            references,
+            tokens,
+        })
+        .collect();
+
+    files.sort_by(|a, b| b.references.cmp(&a.references).then(a.path.cmp(b.path)));
+    files.truncate(top);
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TokenUsageBuilder;
+
+    #[test]
+    fn ranks_files_by_references_from_other_files() {
+        let usages = [
+            TokenUsageBuilder::new("name", "lib/person.rb")
+                .with_occurrences(&[("lib/person.rb", 4), ("lib/view.rb", 1)])
+                .build(),
+            TokenUsageBuilder::new("total", "lib/order.rb")
+                .with_occurrences(&[("lib/order.rb", 1), ("lib/view.rb", 3)])
+                .build(),
+        ];
+        let usages: Vec<&TokenUsage> = usages.iter().collect();
+
+        let files: Vec<(&Path, usize)> = hot_files(&usages, 1)
+            .into_iter()
+            .map(|f| (f.path, f.references))
+            .collect();
+        let tokens: Vec<&str> = hot_tokens(&usages, 2)
+            .into_iter()
+            .map(|t| t.token)
+            .collect();
+
+        assert_eq!(files, vec![(Path::new("lib/order.rb"), 3)]);
+        assert_eq!(tokens, vec!["name", "total"]);
+    }
+}
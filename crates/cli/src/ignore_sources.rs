@@ -0,0 +1,70 @@
+use super::evidence_cache::{CacheTtl, EvidenceCache};
+use codebase_files::writes::Writes;
+use project_configuration::IgnoreList;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+const CACHE_NAMESPACE: &str = "ignore_from";
+
+pub struct LoadedIgnores {
+    pub ignore_list: IgnoreList,
+    pub warnings: Vec<String>,
+}
+
+/// Load the ignore lists referenced by `ignore_from`
+///
+/// Remote lists are cached on disk for `ttl`, unless `writes` are disabled; when a list can't be
+/// fetched, the last cached copy is used.
+pub fn load(sources: &[String], ttl: CacheTtl, writes: Writes) -> LoadedIgnores {
+    let mut ignore_list = IgnoreList::default();
+    let mut warnings = vec![];
+
+    for source in sources {
+        match read_source(source, ttl, writes).and_then(|contents| IgnoreList::parse(&contents)) {
+            Ok(list) => ignore_list.extend(list),
+            Err(e) => warnings.push(format!("Unable to load ignore_from {}: {}", source, e)),
+        }
+    }
+
+    LoadedIgnores {
+        ignore_list,
+        warnings,
+    }
+}
+
+fn read_source(source: &str, ttl: CacheTtl, writes: Writes) -> Result<String, String> {
+    if is_remote(source) {
+        read_remote(source, ttl, writes)
+    } else {
+        fs::read_to_string(source).map_err(|e| e.to_string())
+    }
+}
+
+fn is_remote(source: &str) -> bool {
+    source.starts_with("https://") || source.starts_with("http://")
+}
+
+fn read_remote(url: &str, ttl: CacheTtl, writes: Writes) -> Result<String, String> {
+    EvidenceCache::new(CACHE_NAMESPACE, ttl, writes).get_or_fetch(url, || fetch(url))
+}
+
+fn fetch(url: &str) -> Result<String, String> {
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location"])
+        .args(["--max-time", "10"])
+        .arg(url)
+        .output()
+        .map_err(|e| format!("unable to run curl ({})", e))?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).map_err(|e| e.to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Directory where remote ignore lists are cached
+pub fn cache_root() -> Option<PathBuf> {
+    EvidenceCache::path(CACHE_NAMESPACE)
+}
@@ -0,0 +1,118 @@
+use super::exit_status::ExitStatus;
+use super::flags::KindsCommand;
+use codebase_files::writes::Writes;
+use colored::*;
+use read_ctags::KindTable;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use token_search::Token;
+
+pub fn render(command: KindsCommand, writes: Writes) -> ExitStatus {
+    match command {
+        KindsCommand::Import => import(writes),
+    }
+}
+
+/// Where kinds imported from ctags are stored, shared by every project
+pub fn default_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("unused").join("kinds.json"))
+}
+
+/// Resolve kinds the built-in tables don't know from the imported table, if there is one
+///
+/// Returns the tokens along with how many definitions were resolved.
+pub fn resolve(tokens: Vec<Token>) -> (Vec<Token>, usize) {
+    let table: Option<KindTable> = default_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok());
+    let table = match table {
+        Some(table) => table,
+        None => return (tokens, 0),
+    };
+
+    let mut resolved = 0;
+    let tokens = tokens
+        .into_iter()
+        .map(|token| {
+            if token.definitions.iter().all(|d| table.resolve(d).is_none()) {
+                return token;
+            }
+
+            let definitions = token
+                .definitions
+                .into_iter()
+                .map(|mut definition| {
+                    if let Some(kind) = table.resolve(&definition) {
+                        definition.kind = kind;
+                        resolved += 1;
+                    }
+                    definition
+                })
+                .collect();
+            Token::new(token.token, definitions)
+        })
+        .collect();
+
+    (tokens, resolved)
+}
+
+fn import(writes: Writes) -> ExitStatus {
+    let table = match list("--list-kinds-full")
+        .and_then(|kinds| list("--list-map-extensions").map(|exts| KindTable::parse(&kinds, &exts)))
+    {
+        Ok(table) if !table.is_empty() => table,
+        Ok(_) => {
+            eprintln!("{}", "ctags didn't list any kinds".red());
+            return ExitStatus::Error;
+        }
+        Err(e) => {
+            eprintln!("{}", format!("Unable to list kinds: {}", e).red());
+            return ExitStatus::Error;
+        }
+    };
+
+    let path = match default_path() {
+        Some(path) => path,
+        None => {
+            eprintln!("{}", "Unable to locate a cache directory".red());
+            return ExitStatus::Error;
+        }
+    };
+
+    let written = serde_json::to_string(&table)
+        .map_err(|e| e.to_string())
+        .and_then(|json| writes.write(&path, json).map_err(|e| e.to_string()));
+
+    match written {
+        Ok(_) => {
+            println!(
+                "Imported {} kinds across {} languages into {}",
+                table.len().to_string().yellow(),
+                table.kinds.len().to_string().yellow(),
+                path.to_string_lossy().cyan()
+            );
+            ExitStatus::Clean
+        }
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format!("Unable to write {}: {}", path.display(), e).red()
+            );
+            ExitStatus::Error
+        }
+    }
+}
+
+fn list(flag: &str) -> Result<String, String> {
+    let output = Command::new("ctags")
+        .arg(flag)
+        .output()
+        .map_err(|e| format!("unable to run ctags: {}", e))?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).map_err(|e| e.to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
@@ -1,36 +1,437 @@
 mod analyzed_token;
+mod baseline;
+mod blame;
+mod budgets;
+mod cache;
+mod changed_files;
 mod cli_configuration;
+mod compare;
+mod context;
+mod coverage;
+mod crash_report;
+mod daemon;
+mod dependency_tags;
+mod deprecations;
 mod doctor;
 mod error_message;
+mod evidence_cache;
+mod exit_status;
+mod explain;
+mod fix;
 mod flags;
 mod formatters;
+mod graph;
+mod history;
+mod hook;
+mod hot;
+mod ignore_sources;
+mod kinds;
+mod logging;
+mod messages;
+mod metrics;
+mod pagination;
 mod project_configurations_loader;
+mod project_defaults;
+mod remote_tags;
+mod report;
+mod report_files;
+mod rules;
+mod sarif_suppressions;
+mod schedule;
+mod serve;
+mod severity;
+mod snippets;
+mod sources;
+mod stats;
+mod tags;
+mod terminal;
+#[cfg(test)]
+mod test_support;
+mod timings;
+mod triage;
+mod visibility;
+mod watch;
+mod workspace;
 
-use cli_configuration::CliConfiguration;
+use cli_configuration::{CliConfiguration, TagsSource};
+use codebase_files::writes::Writes;
 use colored::*;
+use context::Context;
 use doctor::Doctor;
-use flags::{Flags, Format};
+pub use exit_status::ExitStatus;
+use flags::{
+    AnalysisFlags, ColorChoice, Command, Flags, Format, HistoryCommand, HookCommand, StatsCommand,
+};
+use messages::Lang;
 use project_configuration::ProjectConfigurations;
+use project_defaults::ProjectDefaults;
+use read_ctags::{
+    CscopeReader, FallbackTags, GlobalTags, ReadCtagsError, TagsFreshness, TagsGenerator,
+    TagsReader,
+};
+use std::io::{self, Write};
+use std::path::Path;
 use structopt::StructOpt;
+use timings::Timings;
+use token_analysis::UsageAnalyzer;
 use token_search::Token;
 
-pub fn run() {
-    let mut flags = Flags::from_args();
+pub fn run() -> ExitStatus {
+    run_with_analyzers(vec![])
+}
+
+/// Run like `run`, adjusting each token's likelihood with `analyzers` after the built-in rules
+pub fn run_with_analyzers(analyzers: Vec<Box<dyn UsageAnalyzer>>) -> ExitStatus {
+    let mut flags = match Flags::from_iter_safe(std::env::args_os()) {
+        Ok(flags) => flags,
+        Err(e) => {
+            let status = ExitStatus::for_usage(e.kind);
+            // Help is often piped into a pager or `head`, which may close STDOUT early
+            if status == ExitStatus::Clean {
+                writeln!(io::stdout(), "{}", e.message).ok();
+            } else {
+                eprintln!("{}", e.message);
+            }
+            return status;
+        }
+    };
+
+    let color = if flags.global.no_color {
+        ColorChoice::Never
+    } else {
+        flags.global.color.unwrap_or_default()
+    };
+    match color {
+        ColorChoice::Always => control::set_override(true),
+        ColorChoice::Never => control::set_override(false),
+        // Left to the NO_COLOR and CLICOLOR_FORCE environment variables, and whether STDOUT is
+        // a terminal
+        ColorChoice::Auto => {}
+    }
+
+    logging::init(flags.global.verbosity, flags.global.quiet);
+    messages::set_lang(flags.global.lang.unwrap_or_else(Lang::from_env));
 
-    if flags.json {
-        flags.format = Format::Json;
+    let project = match ProjectDefaults::load(Path::new(".")) {
+        Ok(project) => project,
+        Err(e) => {
+            error_message::invalid_project_settings(&e);
+            return ExitStatus::Error;
+        }
+    };
+
+    let writes = if flags.global.no_write {
+        Writes::Disabled
+    } else {
+        Writes::Enabled
+    };
+    // Remote tags are fetched into the user cache once, and read from there like a local file
+    if let Some(url) = flags.global.tags.as_deref().and_then(remote_tags::url) {
+        let checksum = flags.global.tags_checksum.as_ref();
+        match remote_tags::fetch(url, checksum, project.cache_ttl, writes) {
+            Ok(path) => flags.global.tags = Some(path),
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    format!("Unable to fetch tags from {}: {}", url, e).red()
+                );
+                return ExitStatus::Error;
+            }
+        }
     }
+    let context = Context::new(flags.global, project, analyzers);
 
-    if flags.no_color {
-        control::set_override(false);
+    if let Some(path) = &context.project.settings.path {
+        tracing::info!("Loaded project settings from {}", path.display());
     }
 
+    crash_report::install(context.config_path.clone(), context.writes);
+
     match flags.cmd {
-        Some(flags::Command::Doctor) => Doctor::new().render(),
-        Some(flags::Command::DefaultYaml) => println!("{}", ProjectConfigurations::default_yaml()),
-        _ => match Token::all() {
-            Ok((_, results)) => CliConfiguration::new(flags, results).render(),
-            Err(e) => error_message::failed_token_parse(e),
+        None => analyze(&context, flags.analysis),
+        Some(Command::Analyze(analysis)) => analyze(&context, analysis),
+        Some(Command::Report(analysis)) => with_configuration(&context, analysis, report::render),
+        Some(Command::Doctor) => {
+            Doctor::new(&context).render();
+            ExitStatus::Clean
+        }
+        Some(Command::Tags(command)) => tags::render(&context, command),
+        Some(Command::Index(_)) if !context.writes.enabled() => {
+            eprintln!("{}", "Unable to index occurrences in read-only mode".red());
+            ExitStatus::Error
+        }
+        Some(Command::Index(mut analysis)) => {
+            analysis.incremental = true;
+            with_configuration(&context, analysis, cache::index)
+        }
+        Some(Command::Cache(command)) => {
+            cache::render(command, context.writes);
+            ExitStatus::Clean
+        }
+        Some(Command::Kinds(command)) => kinds::render(command, context.writes),
+        Some(Command::Serve {
+            full_rescan,
+            analysis,
+        }) => serve::run(&context, full_rescan, analysis),
+        Some(Command::Daemon {
+            socket: Some(_), ..
+        }) if !context.writes.enabled() => {
+            eprintln!(
+                "{}",
+                "Unable to listen on a socket in read-only mode; omit --socket to use STDIN".red()
+            );
+            ExitStatus::Error
+        }
+        Some(Command::Daemon { socket, analysis }) => daemon::run(&context, socket, analysis),
+        Some(Command::Explain { token, analysis }) => {
+            with_configuration(&context, analysis, |c| explain::render(c, &token))
+        }
+        Some(Command::Fix { write: true, .. }) if !context.writes.enabled() => {
+            eprintln!("{}", "Unable to remove definitions in read-only mode".red());
+            ExitStatus::Error
+        }
+        Some(Command::Fix {
+            dry_run,
+            write,
+            min_confidence,
+            analysis,
+        }) => with_configuration(&context, analysis, |c| {
+            fix::render(c, write && !dry_run, min_confidence)
+        }),
+        Some(Command::Hot { top, analysis }) => {
+            with_configuration(&context, analysis, |c| hot::render(c, top))
+        }
+        Some(Command::Stats(StatsCommand::Tokens { top, analysis })) => {
+            with_configuration(&context, *analysis, |c| stats::tokens(c, top))
+        }
+        Some(Command::Stats(StatsCommand::Tags { top })) => stats::tags(&context, top),
+        Some(Command::History(HistoryCommand::Record(_))) if !context.writes.enabled() => {
+            eprintln!("{}", "Unable to record history in read-only mode".red());
+            ExitStatus::Error
+        }
+        Some(Command::History(HistoryCommand::Record(analysis))) => {
+            let path = history::path(&context);
+            with_configuration(&context, *analysis, |c| history::record(c, &path))
+        }
+        Some(Command::History(HistoryCommand::Show { last })) => {
+            history::show(&context, &history::path(&context), last);
+            ExitStatus::Clean
+        }
+        Some(Command::Hook(HookCommand::PreCommit(analysis))) => {
+            hook::pre_commit(&context, *analysis)
+        }
+        Some(Command::Hook(HookCommand::Install)) if !context.writes.enabled() => {
+            eprintln!("{}", "Unable to install the hook in read-only mode".red());
+            ExitStatus::Error
+        }
+        Some(Command::Hook(HookCommand::Install)) => hook::install(context.writes),
+        Some(Command::Graph { nodes, analysis }) => {
+            with_configuration(&context, analysis, |c| graph::render(c, nodes))
+        }
+        Some(Command::Visibility(analysis)) => {
+            with_configuration(&context, analysis, visibility::render)
+        }
+        Some(Command::Compare { old, new }) => compare::render(&context, &old, &new),
+        Some(Command::DefaultYaml) => {
+            println!("{}", ProjectConfigurations::default_yaml());
+            ExitStatus::Clean
+        }
+        Some(Command::Rules) => {
+            rules::render();
+            ExitStatus::Clean
+        }
+    }
+}
+
+fn analyze(context: &Context, flags: AnalysisFlags) -> ExitStatus {
+    if flags.interactive {
+        with_configuration(context, flags, triage::run)
+    } else if flags.watch {
+        watch::run(context, flags)
+    } else if !context.project.sub_projects().is_empty() {
+        workspace::run(context, flags)
+    } else if flags.generate_baseline {
+        with_configuration(context, flags, CliConfiguration::write_baseline)
+    } else {
+        with_configuration(context, flags, CliConfiguration::render)
+    }
+}
+
+fn with_configuration<F>(context: &Context, flags: AnalysisFlags, f: F) -> ExitStatus
+where
+    F: FnOnce(&CliConfiguration),
+{
+    match build_configuration(context, flags) {
+        Ok(cli_config) => {
+            f(&cli_config);
+            cli_config.exit_status()
+        }
+        Err(e) => {
+            error_message::failed_token_parse(e);
+            ExitStatus::Error
+        }
+    }
+}
+
+fn build_configuration(
+    context: &Context,
+    flags: AnalysisFlags,
+) -> Result<CliConfiguration, ReadCtagsError> {
+    crash_report::set_phase("loading tags");
+    let timings = Timings::start();
+    TagsReader::take_read_duration();
+    let (tokens, tags_source) = if flags.regenerate_tags {
+        load_fresh_tokens(context)?
+    } else {
+        let (tokens, tags_source) = load_tokens(context)?;
+        check_freshness(&tags_source, &tokens, flags.strict_freshness)?;
+        (tokens, tags_source)
+    };
+    // Whatever loading tags spent beyond reading them went to parsing (or generating) them
+    let read = TagsReader::take_read_duration();
+    timings.record("tags_read", read);
+    timings.record("tags_parse", timings.elapsed().saturating_sub(read));
+
+    Ok(CliConfiguration::new(
+        context,
+        flags,
+        tokens,
+        tags_source,
+        timings,
+    ))
+}
+
+// Tags are generated in memory when the project's are missing, stale, or malformed, rather than
+// overwriting them
+fn load_fresh_tokens(context: &Context) -> Result<(Vec<Token>, TagsSource), ReadCtagsError> {
+    match load_tokens(context) {
+        Ok((tokens, tags_source)) => match freshness(&tags_source, &tokens) {
+            Some(freshness) if freshness.is_stale() => tracing::info!("{}", freshness),
+            _ => return Ok((tokens, tags_source)),
         },
+        Err(e) => tracing::info!("Regenerating tags: {}", e),
+    }
+
+    let root = context
+        .sub_project
+        .as_ref()
+        .map_or(Path::new("."), |project| project.root.as_path());
+    let tags_file = TagsGenerator::new(root)
+        .with_format(context.tags_format)
+        .with_parse_mode(context.parse_mode)
+        .generate()?;
+
+    resolve_tokens(
+        context,
+        Ok((TagsSource::Generated, Token::from_tags(tags_file.tags))),
+    )
+}
+
+// Stale tags are only warned about, unless --strict-freshness rejects them
+fn check_freshness(
+    tags_source: &TagsSource,
+    tokens: &[Token],
+    strict: bool,
+) -> Result<(), ReadCtagsError> {
+    match freshness(tags_source, tokens) {
+        Some(freshness) if freshness.is_stale() && strict => {
+            Err(ReadCtagsError::StaleTags(freshness))
+        }
+        Some(freshness) if freshness.is_stale() => {
+            tracing::warn!("{}", freshness);
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn freshness(tags_source: &TagsSource, tokens: &[Token]) -> Option<TagsFreshness> {
+    let path = match tags_source {
+        TagsSource::TagsFile(path) | TagsSource::Gtags(path) => path,
+        TagsSource::Generated | TagsSource::RegexFallback => return None,
+    };
+    let tagged_paths = tokens
+        .iter()
+        .flat_map(|token| token.definitions.iter())
+        .map(|definition| definition.file_path.as_ref());
+
+    TagsFreshness::check(path, tagged_paths)
+        .map_err(|e| {
+            tracing::debug!("Unable to check whether {} is stale: {}", path.display(), e);
+        })
+        .ok()
+}
+
+fn load_tokens(context: &Context) -> Result<(Vec<Token>, TagsSource), ReadCtagsError> {
+    // Sub-projects keep their own tags file, at their root unless configured otherwise
+    let loaded = match &context.sub_project {
+        Some(project) => {
+            let path = project
+                .tags
+                .clone()
+                .unwrap_or_else(|| project.root.join("tags"));
+            Token::from_nested_tags_path(path.clone())
+                .map(|tokens| (TagsSource::TagsFile(path), tokens))
+        }
+        None if context.tags_path.is_some() => Token::from_reader(&context.tags_reader())
+            .map(|(path, tokens)| (TagsSource::TagsFile(path), tokens)),
+        None => load_default_tokens(context),
+    };
+
+    resolve_tokens(context, loaded)
+}
+
+fn resolve_tokens(
+    context: &Context,
+    loaded: Result<(TagsSource, Vec<Token>), ReadCtagsError>,
+) -> Result<(Vec<Token>, TagsSource), ReadCtagsError> {
+    match loaded {
+        Ok((tags_source, tokens)) => {
+            if let TagsSource::TagsFile(path) | TagsSource::Gtags(path) = &tags_source {
+                tracing::info!("Loaded {} tokens from {}", tokens.len(), path.display());
+            }
+            let tokens = map_languages(context, tokens);
+            let (tokens, resolved) = kinds::resolve(tokens);
+            if resolved > 0 {
+                tracing::info!("Resolved {} kinds from imported ctags kinds", resolved);
+            }
+            Ok((tokens, tags_source))
+        }
+        // A tags file passed with --tags must exist
+        Err(ReadCtagsError::NoCtagsFile(_, _))
+            if context.tags_path.is_none() && !FallbackTags::ctags_installed() =>
+        {
+            let tokens = map_languages(context, Token::fallback(&context.codebase_files().paths));
+            tracing::info!("Extracted {} tokens with regex fallback", tokens.len());
+            Ok((tokens, TagsSource::RegexFallback))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// Definitions in files matching `language_mappings` are read as the language they're mapped to,
+// whichever language their tags were written with
+fn map_languages(context: &Context, tokens: Vec<Token>) -> Vec<Token> {
+    let mappings = &context.project.language_mappings;
+    if mappings.is_empty() {
+        return tokens;
+    }
+
+    tokens
+        .into_iter()
+        .map(|token| token.with_language_mappings(mappings))
+        .collect()
+}
+
+fn load_default_tokens(context: &Context) -> Result<(TagsSource, Vec<Token>), ReadCtagsError> {
+    // C and C++ projects may only maintain a cscope or GNU GLOBAL database
+    match Token::from_reader(&context.tags_reader()) {
+        Err(ReadCtagsError::NoCtagsFile(_, _)) if CscopeReader::default().find().is_some() => {
+            Token::from_cscope().map(|(path, tokens)| (TagsSource::TagsFile(path), tokens))
+        }
+        Err(ReadCtagsError::NoCtagsFile(_, _)) if GlobalTags::default().find().is_some() => {
+            Token::from_gtags().map(|(path, tokens)| (TagsSource::Gtags(path), tokens))
+        }
+        loaded => loaded.map(|(path, tokens)| (TagsSource::TagsFile(path), tokens)),
     }
 }
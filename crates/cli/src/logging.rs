@@ -0,0 +1,52 @@
+use colored::control;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::Level;
+
+// Whether warnings and progress are suppressed, with `--quiet`; read by `quiet`
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Log diagnostics to STDERR at the level chosen by `-v`/`--quiet`
+///
+/// Warnings are logged by default; `-v` adds what was loaded and searched (such as which tags
+/// file was chosen and how many entries it had), `-vv` adds what was skipped and why, and `-vvv`
+/// everything else. `--quiet` only logs errors, and hides warnings and progress.
+pub fn init(verbosity: u8, quiet: bool) {
+    QUIET.store(quiet, Ordering::SeqCst);
+
+    tracing_subscriber::fmt()
+        .with_max_level(level(verbosity, quiet))
+        .with_writer(io::stderr)
+        .with_ansi(control::SHOULD_COLORIZE.should_colorize() && io::stderr().is_terminal())
+        .with_target(false)
+        .without_time()
+        .init();
+}
+
+/// Were warnings and progress suppressed with `--quiet`?
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::SeqCst)
+}
+
+fn level(verbosity: u8, quiet: bool) -> Level {
+    match (quiet, verbosity) {
+        (true, _) => Level::ERROR,
+        (false, 0) => Level::WARN,
+        (false, 1) => Level::INFO,
+        (false, 2) => Level::DEBUG,
+        _ => Level::TRACE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_verbosity_to_levels() {
+        assert_eq!(level(0, false), Level::WARN);
+        assert_eq!(level(2, false), Level::DEBUG);
+        assert_eq!(level(5, false), Level::TRACE);
+        assert_eq!(level(2, true), Level::ERROR);
+    }
+}
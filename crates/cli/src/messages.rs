@@ -0,0 +1,306 @@
+use std::env;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use token_analysis::Rule;
+
+// The language report strings are rendered in, from `--lang` or the locale
+static LANG: AtomicU8 = AtomicU8::new(Lang::En as u8);
+
+/// A language report strings can be rendered in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Lang {
+    En,
+    De,
+}
+
+impl Lang {
+    /// The language from the first of `LC_ALL`, `LC_MESSAGES`, or `LANG` that's set, falling back
+    /// to English for unsupported locales
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|key| env::var(key).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|locale| locale.parse().ok())
+            .unwrap_or(Lang::En)
+    }
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    // Accepts language codes along with locales such as de_DE.UTF-8
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.split(['_', '-', '.']).next();
+
+        match code.map(str::to_lowercase).as_deref() {
+            Some("en") | Some("c") | Some("posix") => Ok(Lang::En),
+            Some("de") => Ok(Lang::De),
+            _ => Err(format!("Unsupported language: {} (expected en or de)", s)),
+        }
+    }
+}
+
+/// Render report strings in `lang` for the rest of the process
+pub fn set_lang(lang: Lang) {
+    LANG.store(lang as u8, Ordering::SeqCst);
+}
+
+fn lang() -> Lang {
+    match LANG.load(Ordering::SeqCst) {
+        l if l == Lang::De as u8 => Lang::De,
+        _ => Lang::En,
+    }
+}
+
+/// A user-facing report string
+///
+/// Labels are rendered as-is; templates have a `{}` placeholder for each argument passed to
+/// `fill`, in order.
+#[derive(Clone, Copy, Debug)]
+pub enum Message {
+    Reason,
+    DefinedIn,
+    DefinedInFiles,
+    FoundIn,
+    GenericRules,
+    LastChanged,
+    Covered,
+    NeverRun,
+    RenameHint,
+    DeprecatedUsagesHeading,
+    OnlyUsedInTestsHeading,
+    SummaryHeading,
+    TokensFound,
+    FilesFound,
+    TagsSource,
+    LanguageFilters,
+    SortOrder,
+    UsageLikelihood,
+    ConfigurationSetting,
+    FrameworkProfiles,
+    AnalyzedWithGenericRules,
+    DeprecatedUsages,
+    OnlyUsedInTests,
+    SuppressedInline,
+    Budget,
+    BudgetFindings,
+    BudgetErrors,
+    BudgetWarnings,
+    OutsideBudgets,
+    OverBudget,
+    Sampled,
+    EstimatedFindings,
+    Showing,
+    ProjectHeading,
+    WorkspaceSummaryHeading,
+    ProjectFindings,
+    TotalFindings,
+}
+
+/// The string for `message` in the current language
+pub fn text(message: Message) -> &'static str {
+    catalog(lang(), message)
+}
+
+/// The template for `message` in the current language, with each `{}` replaced by the next
+/// argument
+pub fn fill(message: Message, args: &[&dyn Display]) -> String {
+    fill_template(text(message), args)
+}
+
+/// The reason a rule classified a token, in the current language
+///
+/// Reasons may end with details from the project settings (such as why a token is allowed),
+/// which are kept as written.
+pub fn reason(rule: Rule, reason: &str) -> String {
+    translate_reason(lang(), rule, reason)
+}
+
+fn catalog(lang: Lang, message: Message) -> &'static str {
+    use Message::*;
+
+    match (lang, message) {
+        (Lang::En, Reason) => "Reason",
+        (Lang::De, Reason) => "Grund",
+        (Lang::En, DefinedIn) => "Defined in",
+        (Lang::De, DefinedIn) => "Definiert in",
+        (Lang::En, DefinedInFiles) => "defined in {} files",
+        (Lang::De, DefinedInFiles) => "definiert in {} Dateien",
+        (Lang::En, FoundIn) => "Found in",
+        (Lang::De, FoundIn) => "Gefunden in",
+        (Lang::En, GenericRules) => "Generic rules: {} has no language-specific heuristics",
+        (Lang::De, GenericRules) => {
+            "Generische Regeln: {} hat keine sprachspezifischen Heuristiken"
+        }
+        (Lang::En, LastChanged) => "Last changed: {} ({} days ago) by {} in {}",
+        (Lang::De, LastChanged) => "Zuletzt geändert: {} (vor {} Tagen) von {} in {}",
+        (Lang::En, Covered) => "Coverage: {} of {} lines run",
+        (Lang::De, Covered) => "Abdeckung: {} von {} Zeilen ausgeführt",
+        (Lang::En, NeverRun) => "Coverage: none of {} lines run (high confidence dead)",
+        (Lang::De, NeverRun) => {
+            "Abdeckung: keine von {} Zeilen ausgeführt (sehr wahrscheinlich toter Code)"
+        }
+        (Lang::En, RenameHint) => "Hint: did {} get renamed to {}? ({} references)",
+        (Lang::De, RenameHint) => "Hinweis: Wurde {} in {} umbenannt? ({} Referenzen)",
+        (Lang::En, DeprecatedUsagesHeading) => "== DEPRECATED USAGES ==",
+        (Lang::De, DeprecatedUsagesHeading) => "== VERALTETE VERWENDUNGEN ==",
+        (Lang::En, OnlyUsedInTestsHeading) => "== ONLY USED IN TESTS ==",
+        (Lang::De, OnlyUsedInTestsHeading) => "== NUR IN TESTS VERWENDET ==",
+        (Lang::En, SummaryHeading) => "== UNUSED SUMMARY ==",
+        (Lang::De, SummaryHeading) => "== ZUSAMMENFASSUNG ==",
+        (Lang::En, TokensFound) => "Tokens found",
+        (Lang::De, TokensFound) => "Gefundene Tokens",
+        (Lang::En, FilesFound) => "Files found",
+        (Lang::De, FilesFound) => "Gefundene Dateien",
+        (Lang::En, TagsSource) => "Tags source",
+        (Lang::De, TagsSource) => "Tags-Quelle",
+        (Lang::En, LanguageFilters) => "Applied language filters",
+        (Lang::De, LanguageFilters) => "Angewandte Sprachfilter",
+        (Lang::En, SortOrder) => "Sort order",
+        (Lang::De, SortOrder) => "Sortierung",
+        (Lang::En, UsageLikelihood) => "Usage likelihood",
+        (Lang::De, UsageLikelihood) => "Wahrscheinlichkeit",
+        (Lang::En, ConfigurationSetting) => "Configuration setting",
+        (Lang::De, ConfigurationSetting) => "Konfiguration",
+        (Lang::En, FrameworkProfiles) => "Framework profiles",
+        (Lang::De, FrameworkProfiles) => "Framework-Profile",
+        (Lang::En, AnalyzedWithGenericRules) => {
+            "Analyzed with generic rules: {} — results may be less accurate"
+        }
+        (Lang::De, AnalyzedWithGenericRules) => {
+            "Mit generischen Regeln analysiert: {} — Ergebnisse sind möglicherweise ungenauer"
+        }
+        (Lang::En, DeprecatedUsages) => "Deprecated usages",
+        (Lang::De, DeprecatedUsages) => "Veraltete Verwendungen",
+        (Lang::En, OnlyUsedInTests) => "Only used in tests",
+        (Lang::De, OnlyUsedInTests) => "Nur in Tests verwendet",
+        (Lang::En, SuppressedInline) => "Suppressed inline",
+        (Lang::De, SuppressedInline) => "Im Code unterdrückt",
+        (Lang::En, Budget) => "Budget {}",
+        (Lang::De, Budget) => "Budget {}",
+        (Lang::En, BudgetFindings) => "{} of {} findings",
+        (Lang::De, BudgetFindings) => "{} von {} Funden",
+        (Lang::En, BudgetErrors) => "{} of {} errors",
+        (Lang::De, BudgetErrors) => "{} von {} Fehlern",
+        (Lang::En, BudgetWarnings) => "{} of {} warnings",
+        (Lang::De, BudgetWarnings) => "{} von {} Warnungen",
+        (Lang::En, OutsideBudgets) => "Outside budgets",
+        (Lang::De, OutsideBudgets) => "Außerhalb der Budgets",
+        (Lang::En, OverBudget) => "over budget",
+        (Lang::De, OverBudget) => "Budget überschritten",
+        (Lang::En, Sampled) => "Sampled: {} of {} tokens ({}, seed {})",
+        (Lang::De, Sampled) => "Stichprobe: {} von {} Tokens ({}, Seed {})",
+        (Lang::En, EstimatedFindings) => "Estimated findings: {} (95% confidence: {}-{})",
+        (Lang::De, EstimatedFindings) => "Geschätzte Funde: {} (95 % Konfidenz: {}-{})",
+        (Lang::En, Showing) => "Showing: {} of {} findings",
+        (Lang::De, Showing) => "Angezeigt: {} von {} Funden",
+        (Lang::En, ProjectHeading) => "== PROJECT {} ({}) ==",
+        (Lang::De, ProjectHeading) => "== PROJEKT {} ({}) ==",
+        (Lang::En, WorkspaceSummaryHeading) => "== WORKSPACE SUMMARY ==",
+        (Lang::De, WorkspaceSummaryHeading) => "== WORKSPACE-ZUSAMMENFASSUNG ==",
+        (Lang::En, ProjectFindings) => "{}: {} findings",
+        (Lang::De, ProjectFindings) => "{}: {} Funde",
+        (Lang::En, TotalFindings) => "Total: {} findings",
+        (Lang::De, TotalFindings) => "Gesamt: {} Funde",
+    }
+}
+
+fn fill_template(template: &str, args: &[&dyn Display]) -> String {
+    let mut parts = template.split("{}");
+    let mut filled = parts.next().unwrap_or_default().to_string();
+
+    for (i, part) in parts.enumerate() {
+        if let Some(arg) = args.get(i) {
+            filled.push_str(&arg.to_string());
+        }
+        filled.push_str(part);
+    }
+
+    filled
+}
+
+fn translate_reason(lang: Lang, rule: Rule, reason: &str) -> String {
+    let (en, de) = match rule {
+        Rule::OccurrenceZero => ("Only one occurrence exists", "Es gibt nur ein Vorkommen"),
+        Rule::TestOnly => (
+            "Only a test and definition exists",
+            "Es gibt nur die Definition und einen Test",
+        ),
+        Rule::WideUsage => ("Token has wide usage", "Token wird vielfach verwendet"),
+        Rule::OnlyUsedInTests => ("Only used in tests", "Nur in Tests verwendet"),
+        Rule::OnlyReferencedInDocs => (
+            "Only referenced in docs",
+            "Nur in der Dokumentation referenziert",
+        ),
+        Rule::UnusedInScope => ("Unused in", "Nicht verwendet in"),
+        Rule::UnroutedAction => (
+            "Controller action not routed",
+            "Controller-Aktion ohne Route",
+        ),
+        Rule::RoutedAction => ("Routed to", "Geroutet zu"),
+        Rule::PublicApi => (
+            "Public API, unused internally",
+            "Öffentliche API, intern nicht verwendet",
+        ),
+        Rule::DynamicReference => (
+            "Only referenced by string or symbol literals",
+            "Nur über String- oder Symbol-Literale referenziert",
+        ),
+        Rule::ConfiguredLowLikelihood => (
+            "Token is classified as low-likelihood",
+            "Token ist als geringe Wahrscheinlichkeit eingestuft",
+        ),
+        Rule::AllowedToken => ("Token is allowed", "Token ist erlaubt"),
+        Rule::DeniedToken => ("Token is denied", "Token ist verboten"),
+        Rule::ShadowsDependency => (
+            "Shadows a definition in dependency tags",
+            "Überdeckt eine Definition in den Abhängigkeits-Tags",
+        ),
+    };
+
+    match (lang, reason.strip_prefix(en)) {
+        (Lang::De, Some(details)) => format!("{}{}", de, details),
+        _ => reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_languages_and_locales() {
+        assert_eq!("de".parse(), Ok(Lang::De));
+        assert_eq!("de_DE.UTF-8".parse(), Ok(Lang::De));
+        assert_eq!("en-US".parse(), Ok(Lang::En));
+        assert_eq!("C".parse(), Ok(Lang::En));
+        assert!("fr".parse::<Lang>().is_err());
+    }
+
+    #[test]
+    fn renders_report_strings_in_the_selected_language() {
+        assert_eq!(
+            fill(Message::Sampled, &[&12, &240, &"5%", &0]),
+            "Sampled: 12 of 240 tokens (5%, seed 0)"
+        );
+
+        assert_eq!(
+            fill_template(catalog(Lang::De, Message::ProjectFindings), &[&"api", &3]),
+            "api: 3 Funde"
+        );
+        assert_eq!(
+            translate_reason(
+                Lang::De,
+                Rule::AllowedToken,
+                "Token is allowed: Loaded by Rails"
+            ),
+            "Token ist erlaubt: Loaded by Rails"
+        );
+        assert_eq!(
+            translate_reason(Lang::En, Rule::WideUsage, "Token has wide usage"),
+            "Token has wide usage"
+        );
+    }
+}
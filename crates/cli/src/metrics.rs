@@ -0,0 +1,178 @@
+use super::cli_configuration::CliConfiguration;
+use super::report_files;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+use token_analysis::TokenUsage;
+
+// Label for findings whose definitions have no known language
+const UNKNOWN_LANGUAGE: &str = "unknown";
+
+/// Write gauges summarizing the run to `path`, in the OpenMetrics text format (which Prometheus'
+/// textfile collector also reads), so dead code can be tracked over time
+pub fn write(cli_config: &CliConfiguration, path: &Path) -> Result<(), String> {
+    let contents = render(
+        &cli_config.included(),
+        &cli_config.for_json(),
+        cli_config.scan_duration(),
+    );
+    report_files::write(
+        path,
+        contents.as_bytes(),
+        cli_config.writes(),
+        cli_config.force(),
+    )
+}
+
+fn render(analyzed: &[&TokenUsage], findings: &[&TokenUsage], duration: Duration) -> String {
+    let mut out = String::new();
+
+    gauge(
+        &mut out,
+        "unused_tokens",
+        "Tokens analyzed, excluding ignored tokens",
+        &[(None, analyzed.len().to_string())],
+    );
+
+    let by_language: Vec<_> = findings_by_language(findings)
+        .into_iter()
+        .map(|(language, count)| (Some(("language", language)), count.to_string()))
+        .collect();
+    gauge(
+        &mut out,
+        "unused_findings",
+        "Tokens reported as unused, by the language of their definitions",
+        &by_language,
+    );
+
+    gauge(
+        &mut out,
+        "unused_dead_files",
+        "Files where every token defined is reported as unused",
+        &[(None, dead_files(analyzed, findings).len().to_string())],
+    );
+
+    gauge(
+        &mut out,
+        "unused_scan_duration_seconds",
+        "Time spent searching for and analyzing tokens",
+        &[(None, format!("{:.3}", duration.as_secs_f64()))],
+    );
+
+    out.push_str("# EOF\n");
+    out
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, samples: &[(Option<(&str, String)>, String)]) {
+    writeln!(out, "# HELP {} {}", name, help).ok();
+    writeln!(out, "# TYPE {} gauge", name).ok();
+    for (label, value) in samples {
+        match label {
+            Some((key, label)) => {
+                writeln!(out, "{}{{{}=\"{}\"}} {}", name, key, escape(label), value)
+            }
+            None => writeln!(out, "{} {}", name, value),
+        }
+        .ok();
+    }
+}
+
+// A finding defined in several languages is counted once for each
+fn findings_by_language(findings: &[&TokenUsage]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+
+    for usage in findings {
+        let languages: HashSet<String> = usage
+            .result
+            .token
+            .definitions
+            .iter()
+            .map(|d| {
+                d.language
+                    .map(|l| l.to_string().to_lowercase())
+                    .unwrap_or_else(|| String::from(UNKNOWN_LANGUAGE))
+            })
+            .collect();
+        for language in languages {
+            *counts.entry(language).or_default() += 1;
+        }
+    }
+
+    counts
+}
+
+/// Files where every token defined is reported as unused
+pub fn dead_files<'a>(analyzed: &[&'a TokenUsage], findings: &[&TokenUsage]) -> HashSet<&'a Path> {
+    let reported: HashSet<&str> = findings
+        .iter()
+        .map(|usage| usage.result.token.token.as_str())
+        .collect();
+    let mut files: HashMap<&'a Path, bool> = HashMap::new();
+
+    for usage in analyzed {
+        let unused = reported.contains(usage.result.token.token.as_str());
+        for definition in usage.result.token.definitions.iter() {
+            *files.entry(&definition.file_path).or_insert(true) &= unused;
+        }
+    }
+
+    files
+        .into_iter()
+        .filter(|(_, dead)| *dead)
+        .map(|(path, _)| path)
+        .collect()
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TokenUsageBuilder;
+    use read_ctags::Language;
+
+    #[test]
+    fn renders_gauges() {
+        let ruby = |token| {
+            TokenUsageBuilder::new(token, "app/person.rb")
+                .with_language(Language::Ruby)
+                .build()
+        };
+        let name = ruby("name");
+        let age = ruby("age");
+        let render_view = TokenUsageBuilder::new("renderView", "app/view.js")
+            .with_language(Language::JavaScript)
+            .build();
+        let notes = TokenUsageBuilder::new("notes", "docs/notes").build();
+
+        assert_eq!(
+            render(
+                &[&name, &age, &render_view, &notes],
+                &[&name, &render_view, &notes],
+                Duration::from_millis(1500)
+            ),
+            "# HELP unused_tokens Tokens analyzed, excluding ignored tokens
+# TYPE unused_tokens gauge
+unused_tokens 4
+# HELP unused_findings Tokens reported as unused, by the language of their definitions
+# TYPE unused_findings gauge
+unused_findings{language=\"javascript\"} 1
+unused_findings{language=\"ruby\"} 1
+unused_findings{language=\"unknown\"} 1
+# HELP unused_dead_files Files where every token defined is reported as unused
+# TYPE unused_dead_files gauge
+unused_dead_files 2
+# HELP unused_scan_duration_seconds Time spent searching for and analyzing tokens
+# TYPE unused_scan_duration_seconds gauge
+unused_scan_duration_seconds 1.500
+# EOF
+"
+        );
+    }
+}
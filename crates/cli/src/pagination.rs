@@ -0,0 +1,135 @@
+use super::flags::AnalysisFlags;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use token_analysis::TokenUsage;
+
+/// Which slice of the findings to report, so huge reports can be consumed a piece at a time
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Page {
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    pub top_files: Option<usize>,
+}
+
+impl Page {
+    pub fn new(flags: &AnalysisFlags) -> Self {
+        Page {
+            limit: flags.limit,
+            offset: flags.offset,
+            top_files: flags.top_files,
+        }
+    }
+
+    pub fn is_paged(&self) -> bool {
+        *self != Page::default()
+    }
+
+    /// Keep findings defined in the files with the most findings, then skip `offset` of them
+    /// and keep up to `limit`, in the order they're given
+    pub fn apply<'a>(&self, findings: Vec<&'a TokenUsage>) -> Vec<&'a TokenUsage> {
+        let findings = match self.top_files {
+            Some(count) => {
+                let files = top_files(&findings, count);
+                findings
+                    .into_iter()
+                    .filter(|usage| {
+                        usage
+                            .result
+                            .token
+                            .definitions
+                            .iter()
+                            .any(|d| files.contains(&*d.file_path))
+                    })
+                    .collect()
+            }
+            None => findings,
+        };
+
+        findings
+            .into_iter()
+            .skip(self.offset.unwrap_or(0))
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+}
+
+// Files defining the most findings, with ties going to the path that sorts first; a finding
+// defined in several files counts towards each
+fn top_files<'a>(findings: &[&'a TokenUsage], count: usize) -> HashSet<&'a Path> {
+    let mut counts: HashMap<&Path, usize> = HashMap::new();
+    for usage in findings {
+        let files: HashSet<&Path> = usage
+            .result
+            .token
+            .definitions
+            .iter()
+            .map(|d| &*d.file_path)
+            .collect();
+        for file in files {
+            *counts.entry(file).or_default() += 1;
+        }
+    }
+
+    let mut files: Vec<(&Path, usize)> = counts.into_iter().collect();
+    files.sort_by_key(|(path, findings)| (Reverse(*findings), *path));
+    files
+        .into_iter()
+        .take(count)
+        .map(|(path, _)| path)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::usage;
+
+    fn names(findings: Vec<&TokenUsage>) -> Vec<&str> {
+        findings
+            .into_iter()
+            .map(|usage| usage.result.token.token.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn pages_findings_from_the_top_files() {
+        let age = usage("age", "app/person.rb");
+        let name = usage("name", "app/person.rb");
+        let render_view = usage("renderView", "app/view.js");
+        let title = usage("title", "app/book.rb");
+        let findings = vec![&age, &name, &render_view, &title];
+
+        assert_eq!(
+            names(Page::default().apply(findings.clone())),
+            vec!["age", "name", "renderView", "title"]
+        );
+        assert_eq!(
+            names(
+                Page {
+                    limit: Some(2),
+                    offset: Some(1),
+                    top_files: None,
+                }
+                .apply(findings.clone())
+            ),
+            vec!["name", "renderView"]
+        );
+        assert_eq!(
+            names(
+                Page {
+                    top_files: Some(2),
+                    ..Page::default()
+                }
+                .apply(findings.clone())
+            ),
+            vec!["age", "name", "title"]
+        );
+        assert!(Page {
+            offset: Some(10),
+            ..Page::default()
+        }
+        .apply(findings)
+        .is_empty());
+    }
+}
@@ -1,21 +1,25 @@
-use dirs;
 use project_configuration::ProjectConfigurations;
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-pub fn load_and_parse_config() -> ProjectConfigurations {
-    let contents = file_path_in_home_dir(".config/unused/unused.yml")
-        .and_then(|path| read_file(&path).ok())
-        .unwrap_or(ProjectConfigurations::default_yaml());
-    ProjectConfigurations::parse(&contents)
+pub fn load_and_parse_config(config_path: Option<&Path>) -> ProjectConfigurations {
+    let contents = match config_path {
+        Some(path) => read_file(path)
+            .map_err(|e| eprintln!("Unable to read configuration {}: {}", path.display(), e))
+            .ok(),
+        None => file_path_in_home_dir(".config/unused/unused.yml")
+            .and_then(|path| read_file(&path).ok()),
+    };
+
+    ProjectConfigurations::parse(&contents.unwrap_or(ProjectConfigurations::default_yaml()))
 }
 
-fn file_path_in_home_dir(file_name: &str) -> Option<String> {
-    dirs::home_dir().and_then(|ref p| Path::new(p).join(file_name).to_str().map(|v| v.to_owned()))
+fn file_path_in_home_dir(file_name: &str) -> Option<PathBuf> {
+    dirs::home_dir().map(|p| p.join(file_name))
 }
 
-fn read_file(filename: &str) -> Result<String, io::Error> {
+fn read_file(filename: &Path) -> Result<String, io::Error> {
     let contents = fs::read_to_string(filename)?;
 
     Ok(contents)
@@ -0,0 +1,431 @@
+use super::budgets::Budgets;
+use super::evidence_cache::{parse_duration, CacheTtl};
+use super::flags::{AnalysisFlags, Format, GroupBy};
+use super::schedule::CronSchedule;
+use super::severity::Severities;
+use codebase_files::{ExcludeGlobs, PathGlobs};
+use project_configuration::{ProjectSettings, RootGlobs};
+use read_ctags::{Language, LanguageMappings, TokenKind};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+use token_analysis::{OrderField, UsageLikelihoodStatus};
+
+/// Project settings, validated against the values accepted by the corresponding flags
+#[derive(Clone, Default)]
+pub struct ProjectDefaults {
+    pub settings: ProjectSettings,
+    pub format: Option<Format>,
+    pub cache_ttl: CacheTtl,
+    /// When `serve` rescans from scratch
+    pub full_rescan: Option<CronSchedule>,
+    /// Severities of findings by category, under `severities`
+    pub severities: Severities,
+    /// Findings tolerated by path, under `budgets`
+    pub budgets: Budgets,
+    /// File name patterns mapped to languages, under `language_mappings`
+    pub language_mappings: LanguageMappings,
+    /// Points added to likelihood scores by kind, under `kind_weights`
+    pub kind_weights: Vec<(TokenKind, i32)>,
+    exclude_globs: ExcludeGlobs,
+    definition_globs: PathGlobs,
+    usage_globs: PathGlobs,
+    likelihoods: Vec<UsageLikelihoodStatus>,
+    sort_order: Option<OrderField>,
+    group_by: Option<GroupBy>,
+    disabled_languages: Vec<Language>,
+    only_languages: Vec<Language>,
+    sub_project_languages: BTreeMap<String, Vec<Language>>,
+}
+
+impl ProjectDefaults {
+    pub fn load(root: &Path) -> Result<Self, String> {
+        let settings = ProjectSettings::load(root)?;
+        let location = settings
+            .path
+            .as_ref()
+            .map(|p| format!("{}: ", p.display()))
+            .unwrap_or_default();
+
+        Self::from_settings(settings).map_err(|e| format!("{}{}", location, e))
+    }
+
+    fn from_settings(settings: ProjectSettings) -> Result<Self, String> {
+        let output = &settings.output;
+        let cache = &settings.cache;
+        let defaults = CacheTtl::default();
+        let language_mappings = language_mappings(&settings.language_mappings)?;
+        // Extensions mapped with `*.ext` can be enabled or disabled like built-in ones, and
+        // template extensions (such as `erb`) stand for the language they embed
+        let language = |ext: &str| {
+            language_mappings
+                .iter()
+                .find(|(pattern, _)| pattern.strip_prefix("*.") == Some(ext))
+                .map(|(_, language)| Ok(*language))
+                .unwrap_or_else(|| {
+                    ext.parse::<Language>()
+                        .or_else(|e| Language::from_template_extension(ext).ok_or(e))
+                })
+        };
+
+        Ok(ProjectDefaults {
+            format: parse("output.format", output.format.as_deref())?,
+            cache_ttl: CacheTtl {
+                positive: duration("cache.ttl", cache.ttl.as_deref())?.unwrap_or(defaults.positive),
+                negative: duration("cache.negative_ttl", cache.negative_ttl.as_deref())?
+                    .unwrap_or(defaults.negative),
+            },
+            full_rescan: parse("serve.full_rescan", settings.serve.full_rescan.as_deref())?,
+            severities: Severities::parse(&settings.severities)?,
+            budgets: Budgets::parse(&settings.budgets)?,
+            kind_weights: kind_weights(&settings.kind_weights)?,
+            likelihoods: output
+                .likelihoods
+                .iter()
+                .map(|l| l.parse().map_err(|e| format!("output.likelihoods: {}", e)))
+                .collect::<Result<_, String>>()?,
+            sort_order: parse("output.sort_order", output.sort_order.as_deref())?,
+            group_by: parse("output.group_by", output.group_by.as_deref())?,
+            disabled_languages: settings
+                .disabled_languages()
+                .into_iter()
+                .map(|l| language(l).map_err(|e| format!("languages.{}: {}", l, e)))
+                .collect::<Result<_, String>>()?,
+            exclude_globs: ExcludeGlobs::new(&settings.ignore_paths)
+                .map_err(|e| format!("ignore_paths: {}", e))?,
+            definition_globs: compile("roots.definitions", &settings.roots.definitions)?,
+            usage_globs: compile("roots.usages", &settings.roots.usages)?,
+            only_languages: vec![],
+            sub_project_languages: settings
+                .projects
+                .iter()
+                .map(|(name, project)| {
+                    let languages = project
+                        .languages
+                        .iter()
+                        .map(|l| {
+                            language(l).map_err(|e| format!("projects.{}.languages: {}", name, e))
+                        })
+                        .collect::<Result<_, String>>()?;
+                    Ok((name.to_string(), languages))
+                })
+                .collect::<Result<_, String>>()?,
+            language_mappings: LanguageMappings::new(language_mappings),
+            settings,
+        })
+    }
+
+    /// Globs excluded by `ignore_paths`, along with any passed with `--exclude`
+    pub fn exclude_globs(&self, excluded: &[String]) -> ExcludeGlobs {
+        if excluded.is_empty() {
+            self.exclude_globs.clone()
+        } else {
+            let patterns: Vec<&String> =
+                self.settings.ignore_paths.iter().chain(excluded).collect();
+            ExcludeGlobs::new(&patterns).unwrap_or_default()
+        }
+    }
+
+    /// Globs selecting where definitions are reported from, under `roots.definitions`, along
+    /// with any passed with `--include-definitions` and `--exclude-definitions`
+    pub fn definition_globs(&self, include: &[String], exclude: &[String]) -> PathGlobs {
+        with_flags(
+            &self.definition_globs,
+            &self.settings.roots.definitions,
+            include,
+            exclude,
+        )
+    }
+
+    /// Globs selecting where occurrences count as usages, under `roots.usages`, along with any
+    /// passed with `--include-usages` and `--exclude-usages`
+    pub fn usage_globs(&self, include: &[String], exclude: &[String]) -> PathGlobs {
+        with_flags(
+            &self.usage_globs,
+            &self.settings.roots.usages,
+            include,
+            exclude,
+        )
+    }
+
+    /// Names of the sub-projects declared under `projects`
+    pub fn sub_projects(&self) -> Vec<&str> {
+        self.settings.projects.keys().map(String::as_str).collect()
+    }
+
+    /// Defaults for a single sub-project, layering its ignores and languages over the project's
+    /// and confining analysis to its root
+    pub fn for_sub_project(&self, name: &str) -> ProjectDefaults {
+        let mut defaults = self.clone();
+
+        if let Some(project) = defaults.settings.projects.remove(name) {
+            defaults.settings.ignore.extend(project.ignore);
+            defaults.settings.roots = defaults.settings.roots.confined_to(&project.root);
+            defaults.only_languages = self
+                .sub_project_languages
+                .get(name)
+                .cloned()
+                .unwrap_or_default();
+        }
+        defaults.settings.projects.clear();
+        defaults.sub_project_languages.clear();
+        defaults
+    }
+
+    /// Fill in settings for any flags not passed on the command line
+    ///
+    /// Flags always take precedence; boolean settings can be enabled from either source.
+    pub fn apply(&self, mut flags: AnalysisFlags) -> AnalysisFlags {
+        let output = &self.settings.output;
+
+        if flags.likelihoods.is_empty() && flags.min_likelihood.is_none() {
+            flags.likelihoods = self.likelihoods.clone();
+        }
+
+        if flags.only_filetypes.is_empty()
+            && flags.except_filetypes.is_empty()
+            && flags.only_languages.is_empty()
+            && flags.ignore_languages.is_empty()
+        {
+            if self.only_languages.is_empty() {
+                flags.except_filetypes = self.disabled_languages.clone();
+            } else {
+                flags.only_filetypes = self.only_languages.clone();
+            }
+        }
+
+        flags.sort_order = flags.sort_order.or(self.sort_order);
+        flags.group_by = flags.group_by.or(self.group_by);
+        flags.reverse |= output.reverse;
+        flags.no_summary |= output.no_summary;
+        flags.no_progress |= output.no_progress;
+        flags
+    }
+}
+
+fn parse<T>(key: &str, value: Option<&str>) -> Result<Option<T>, String>
+where
+    T: std::str::FromStr<Err = String>,
+{
+    value
+        .map(|v| v.parse().map_err(|e| format!("{}: {}", key, e)))
+        .transpose()
+}
+
+// Languages are given by name (`ruby`) or by one of their extensions (`rb`)
+fn language_mappings(mappings: &[(String, String)]) -> Result<Vec<(String, Language)>, String> {
+    mappings
+        .iter()
+        .map(|(pattern, name)| {
+            name.parse()
+                .map(|language| (pattern.to_string(), language))
+                .map_err(|e| format!("language_mappings.{}: {}", pattern, e))
+        })
+        .collect()
+}
+
+// Weights are kept within a score's 0-100 range, so they can't overwhelm every other signal
+fn kind_weights(weights: &BTreeMap<String, i64>) -> Result<Vec<(TokenKind, i32)>, String> {
+    weights
+        .iter()
+        .map(|(name, weight)| {
+            let key = format!("kind_weights.{}", name);
+            let kind = TokenKind::from_name(name)
+                .ok_or_else(|| format!("{}: Unknown kind: {}", key, name))?;
+            if !(-100..=100).contains(weight) {
+                return Err(format!("{}: expected a weight from -100 to 100", key));
+            }
+
+            Ok((kind, *weight as i32))
+        })
+        .collect()
+}
+
+fn duration(key: &str, value: Option<&str>) -> Result<Option<Duration>, String> {
+    value
+        .map(|v| parse_duration(v).map_err(|e| format!("{}: {}", key, e)))
+        .transpose()
+}
+
+fn compile(key: &str, globs: &RootGlobs) -> Result<PathGlobs, String> {
+    PathGlobs::new(&globs.include, &globs.exclude).map_err(|e| format!("{}: {}", key, e))
+}
+
+// Globs passed as flags are added to those in the settings, which were validated when loaded
+fn with_flags(
+    compiled: &PathGlobs,
+    settings: &RootGlobs,
+    include: &[String],
+    exclude: &[String],
+) -> PathGlobs {
+    if include.is_empty() && exclude.is_empty() {
+        return compiled.clone();
+    }
+
+    let include: Vec<&String> = settings.include.iter().chain(include).collect();
+    let exclude: Vec<&String> = settings.exclude.iter().chain(exclude).collect();
+    PathGlobs::new(&include, &exclude).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use structopt::StructOpt;
+
+    #[test]
+    fn flags_take_precedence_over_project_settings() {
+        let settings = ProjectSettings::parse_yaml(
+            "languages:\n  md:\n    enabled: false\noutput:\n  sort_order: file\n  group_by: file\n  likelihoods: [medium]\n",
+        )
+        .unwrap();
+        let defaults = ProjectDefaults::from_settings(settings).unwrap();
+        let flags = defaults.apply(AnalysisFlags::from_iter(&[
+            "unused",
+            "--sort-order",
+            "token",
+        ]));
+
+        assert!(matches!(flags.sort_order, Some(OrderField::Token)));
+        assert!(matches!(flags.group_by, Some(GroupBy::File)));
+        assert_eq!(flags.likelihoods, vec![UsageLikelihoodStatus::Medium]);
+        assert_eq!(flags.except_filetypes, vec![Language::Markdown]);
+    }
+
+    #[test]
+    fn layers_sub_project_settings() {
+        let settings = ProjectSettings::parse_yaml(
+            "ignore:\n  tokens: [ApplicationJob]\nprojects:\n  web:\n    root: frontend\n    languages: [js]\n    ignore:\n      tokens: [registerServiceWorker]\n",
+        )
+        .unwrap();
+        let defaults = ProjectDefaults::from_settings(settings).unwrap();
+        let web = defaults.for_sub_project("web");
+        let flags = web.apply(AnalysisFlags::from_iter(&["unused"]));
+
+        assert_eq!(defaults.sub_projects(), vec!["web"]);
+        assert!(web.sub_projects().is_empty());
+        assert_eq!(
+            web.settings.ignore.tokens,
+            vec![
+                String::from("ApplicationJob"),
+                String::from("registerServiceWorker")
+            ]
+        );
+        assert!(!web.settings.roots.is_usage_root(Path::new("app/person.rb")));
+        assert_eq!(flags.only_filetypes, vec![Language::JavaScript]);
+        assert!(flags.except_filetypes.is_empty());
+
+        let settings =
+            ProjectSettings::parse_yaml("projects:\n  api:\n    languages: [rb, erb]\n").unwrap();
+        let api = ProjectDefaults::from_settings(settings)
+            .unwrap()
+            .for_sub_project("api");
+
+        assert_eq!(
+            api.apply(AnalysisFlags::from_iter(&["unused"]))
+                .only_filetypes,
+            vec![Language::Ruby, Language::Ruby]
+        );
+
+        let settings =
+            ProjectSettings::parse_yaml("projects:\n  web:\n    languages: [jsx2]\n").unwrap();
+
+        assert!(ProjectDefaults::from_settings(settings)
+            .err()
+            .is_some_and(|e| e.starts_with("projects.web.languages: ")));
+    }
+
+    #[test]
+    fn disables_mapped_extensions() {
+        let settings = ProjectSettings::parse_yaml(
+            "language_mappings:\n  '*.jbuilder': Ruby\n  '*.mjs': js\nlanguages:\n  jbuilder:\n    enabled: false\n",
+        )
+        .unwrap();
+        let defaults = ProjectDefaults::from_settings(settings).unwrap();
+
+        assert_eq!(
+            defaults.language_mappings,
+            LanguageMappings::new(vec![
+                (String::from("*.jbuilder"), Language::Ruby),
+                (String::from("*.mjs"), Language::JavaScript),
+            ])
+        );
+        assert_eq!(defaults.disabled_languages, vec![Language::Ruby]);
+    }
+
+    #[test]
+    fn rejects_values_flags_would_reject() {
+        let settings = ProjectSettings::parse_yaml("output:\n  format: xml\n").unwrap();
+
+        assert_eq!(
+            ProjectDefaults::from_settings(settings).err(),
+            Some(String::from("output.format: Unknown format: xml"))
+        );
+
+        let settings = ProjectSettings::parse_yaml("cache:\n  ttl: 1w\n").unwrap();
+
+        assert_eq!(
+            ProjectDefaults::from_settings(settings).err(),
+            Some(String::from("cache.ttl: Unable to parse duration: 1w"))
+        );
+
+        let settings =
+            ProjectSettings::parse_yaml("language_mappings:\n  '*.jb': cobol\n").unwrap();
+
+        assert_eq!(
+            ProjectDefaults::from_settings(settings).err(),
+            Some(String::from(
+                "language_mappings.*.jb: Unknown language: cobol"
+            ))
+        );
+
+        let settings = ProjectSettings::parse_yaml("serve:\n  full_rescan: 0 3 * *\n").unwrap();
+
+        assert!(ProjectDefaults::from_settings(settings)
+            .err()
+            .is_some_and(|e| e.starts_with("serve.full_rescan: Unable to parse schedule")));
+
+        let settings = ProjectSettings::parse_yaml("kind_weights:\n  widget: 10\n").unwrap();
+
+        assert_eq!(
+            ProjectDefaults::from_settings(settings).err(),
+            Some(String::from("kind_weights.widget: Unknown kind: widget"))
+        );
+
+        let settings = ProjectSettings::parse_yaml("kind_weights:\n  class: 500\n").unwrap();
+
+        assert_eq!(
+            ProjectDefaults::from_settings(settings).err(),
+            Some(String::from(
+                "kind_weights.class: expected a weight from -100 to 100"
+            ))
+        );
+
+        let settings = ProjectSettings::parse_yaml("severities:\n  stale: error\n").unwrap();
+
+        assert!(ProjectDefaults::from_settings(settings)
+            .err()
+            .is_some_and(|e| e.starts_with("severities.stale: Unknown category: stale")));
+    }
+
+    #[test]
+    fn adds_root_globs_from_flags() {
+        let settings =
+            ProjectSettings::parse_yaml("roots:\n  usages:\n    exclude: [db/schema.rb]\n")
+                .unwrap();
+        let defaults = ProjectDefaults::from_settings(settings).unwrap();
+
+        let usages = defaults.usage_globs(&[], &[String::from("spec/fixtures/**")]);
+
+        assert!(!usages.includes(Path::new("db/schema.rb")));
+        assert!(!usages.includes(Path::new("spec/fixtures/person.rb")));
+        assert!(usages.includes(Path::new("app/person.rb")));
+        assert!(defaults
+            .definition_globs(&[String::from("app/**")], &[])
+            .includes(Path::new("app/person.rb")));
+        assert!(ProjectDefaults::from_settings(
+            ProjectSettings::parse_yaml("roots:\n  definitions:\n    include: ['app/{a']\n")
+                .unwrap()
+        )
+        .is_err());
+    }
+}
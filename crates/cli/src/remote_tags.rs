@@ -0,0 +1,255 @@
+use super::evidence_cache::{self, CacheTtl, EvidenceCache};
+use codebase_files::writes::Writes;
+use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+const CACHE_NAMESPACE: &str = "remote_tags";
+
+// Stored alongside each cached tags file, recording the checksum of the artifact it came from
+const CHECKSUM_EXTENSION: &str = "sha256";
+
+// Gzip's magic bytes, checked rather than the URL's extension
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// SHA-256 digest a remote tags artifact must match, passed with `--tags-checksum`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checksum(String);
+
+impl FromStr for Checksum {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let hex = input.strip_prefix("sha256:").unwrap_or(input);
+
+        if hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            Ok(Checksum(hex.to_ascii_lowercase()))
+        } else {
+            Err(format!(
+                "Unable to parse checksum: {} (expected sha256:<64 hex digits>)",
+                input
+            ))
+        }
+    }
+}
+
+impl Checksum {
+    fn of(bytes: &[u8]) -> Self {
+        Checksum(
+            Sha256::digest(bytes)
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect(),
+        )
+    }
+}
+
+/// The URL passed with `--tags`, when it names a remote tags file rather than a local one
+pub fn url(path: &Path) -> Option<&str> {
+    path.to_str().filter(|path| {
+        ["https://", "http://", "s3://"]
+            .iter()
+            .any(|scheme| path.starts_with(scheme))
+    })
+}
+
+/// Fetch the tags file at `url` into the user cache, returning its local path
+///
+/// Gzipped artifacts are decompressed. With a `checksum`, the artifact must match it, and a
+/// cached copy fetched from the same URL with the same checksum is reused without fetching it
+/// again. Otherwise the cached copy is reused for the `ttl` of successful results, and after
+/// that whenever the URL can't be fetched.
+pub fn fetch(
+    url: &str,
+    checksum: Option<&Checksum>,
+    ttl: CacheTtl,
+    writes: Writes,
+) -> Result<PathBuf, String> {
+    let root = EvidenceCache::path(CACHE_NAMESPACE)
+        .ok_or_else(|| String::from("no cache directory is available to store them"))?;
+
+    fetch_into(&root, url, checksum, ttl, writes, download)
+}
+
+/// Directory where remote tags files are cached
+pub fn cache_root() -> Option<PathBuf> {
+    EvidenceCache::path(CACHE_NAMESPACE)
+}
+
+fn fetch_into<F>(
+    root: &Path,
+    url: &str,
+    checksum: Option<&Checksum>,
+    ttl: CacheTtl,
+    writes: Writes,
+    download: F,
+) -> Result<PathBuf, String>
+where
+    F: FnOnce(&str) -> Result<Vec<u8>, String>,
+{
+    let path = root.join(evidence_cache::file_name(url));
+    let checksum_path = path.with_extension(CHECKSUM_EXTENSION);
+    let cached_checksum = fs::read_to_string(&checksum_path).ok();
+    let reusable = match checksum {
+        Some(checksum) => cached_checksum.as_deref() == Some(checksum.0.as_str()),
+        None => is_fresh(&path, ttl),
+    };
+    if reusable && path.is_file() {
+        tracing::info!("Using tags from {} cached at {}", url, path.display());
+        return Ok(path);
+    }
+
+    let artifact = match download(url) {
+        Ok(artifact) => artifact,
+        // A pinned artifact that can't be fetched is never swapped for a different one
+        Err(e) if checksum.is_none() && path.is_file() => {
+            tracing::warn!(
+                "Unable to fetch {} ({}); using the copy cached at {}",
+                url,
+                e,
+                path.display()
+            );
+            return Ok(path);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let actual = Checksum::of(&artifact);
+    if let Some(expected) = checksum.filter(|expected| **expected != actual) {
+        return Err(format!(
+            "checksum mismatch (expected sha256:{}, got sha256:{})",
+            expected.0, actual.0
+        ));
+    }
+
+    writes
+        .write(&path, decompress(artifact)?)
+        .and_then(|_| writes.write(&checksum_path, &actual.0))
+        .map_err(|e| format!("unable to cache them at {} ({})", path.display(), e))?;
+    tracing::info!("Fetched tags from {} into {}", url, path.display());
+
+    Ok(path)
+}
+
+fn is_fresh(path: &Path, ttl: CacheTtl) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < ttl.positive)
+}
+
+fn decompress(artifact: Vec<u8>) -> Result<Vec<u8>, String> {
+    if !artifact.starts_with(&GZIP_MAGIC) {
+        return Ok(artifact);
+    }
+
+    let mut tags = vec![];
+    GzDecoder::new(artifact.as_slice())
+        .read_to_end(&mut tags)
+        .map_err(|e| format!("unable to decompress them ({})", e))?;
+    Ok(tags)
+}
+
+fn download(url: &str) -> Result<Vec<u8>, String> {
+    let output = if url.starts_with("s3://") {
+        s3_command(url)?.output()
+    } else {
+        Command::new("curl")
+            .args(["--fail", "--silent", "--show-error", "--location"])
+            .args(["--max-time", "120"])
+            .arg(url)
+            .output()
+    }
+    .map_err(|e| format!("unable to run the download command ({})", e))?;
+
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(feature = "s3")]
+fn s3_command(url: &str) -> Result<Command, String> {
+    let mut command = Command::new("aws");
+    command.args(["s3", "cp", "--quiet", url, "-"]);
+    Ok(command)
+}
+
+#[cfg(not(feature = "s3"))]
+fn s3_command(_url: &str) -> Result<Command, String> {
+    Err(String::from(
+        "s3:// tags require unused to be built with the `s3` feature",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    const TAGS: &str = "Person\tapp/person.rb\t1;\"\tc\n";
+
+    #[test]
+    fn caches_validated_and_decompressed_tags() {
+        let root = std::env::temp_dir().join("unused_remote_tags_caches");
+        fs::remove_dir_all(&root).ok();
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(TAGS.as_bytes()).unwrap();
+        let artifact = encoder.finish().unwrap();
+        let checksum = Checksum::of(&artifact);
+        let url = "https://ci.example.com/tags.gz";
+
+        assert_eq!(
+            fetch_into(
+                &root,
+                url,
+                Some(&Checksum::of(b"other")),
+                CacheTtl::default(),
+                Writes::Enabled,
+                |_| Ok(artifact.clone())
+            )
+            .unwrap_err(),
+            format!(
+                "checksum mismatch (expected sha256:{}, got sha256:{})",
+                Checksum::of(b"other").0,
+                checksum.0
+            )
+        );
+
+        let path = fetch_into(
+            &root,
+            url,
+            Some(&checksum),
+            CacheTtl::default(),
+            Writes::Enabled,
+            |_| Ok(artifact.clone()),
+        )
+        .unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), TAGS);
+
+        let cached = fetch_into(
+            &root,
+            url,
+            Some(&checksum),
+            CacheTtl::default(),
+            Writes::Enabled,
+            |_| Err(String::from("not fetched again")),
+        );
+        assert_eq!(cached, Ok(path));
+
+        assert_eq!(
+            format!("sha256:{}", checksum.0.to_uppercase()).parse(),
+            Ok(checksum)
+        );
+        assert!("sha256:abc".parse::<Checksum>().is_err());
+    }
+}
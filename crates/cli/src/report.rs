@@ -0,0 +1,64 @@
+use super::cli_configuration::CliConfiguration;
+use super::formatters::internal::{configuration_warnings, print_json};
+use super::Format;
+use colored::*;
+use serde_json::json;
+use std::collections::BTreeMap;
+use token_analysis::{Rule, UsageLikelihoodStatus};
+
+pub fn render(cli_config: &CliConfiguration) {
+    let findings = cli_config.for_json();
+    let mut by_likelihood: BTreeMap<String, usize> = UsageLikelihoodStatus::all()
+        .iter()
+        .map(|s| (s.to_string(), 0))
+        .collect();
+    let mut by_rule: BTreeMap<&str, usize> = BTreeMap::new();
+    let deprecated_usages: Option<usize> = cli_config
+        .deprecations()
+        .map(|d| d.iter().map(|d| d.usages).sum());
+
+    for usage in findings.iter() {
+        *by_likelihood
+            .entry(usage.usage_likelihood.status.to_string())
+            .or_insert(0) += 1;
+        *by_rule.entry(usage.usage_likelihood.rule.id()).or_insert(0) += 1;
+    }
+
+    match cli_config.format() {
+        Format::Json => {
+            let mut report = json!({
+                "findings": findings.len(),
+                "by_likelihood": by_likelihood,
+                "by_rule": by_rule,
+            });
+            if let Some(usages) = deprecated_usages {
+                report["deprecated_usages"] = json!(usages);
+            }
+            print_json(&report)
+        }
+        _ => {
+            println!("{}", "== UNUSED REPORT ==".white());
+            println!("   Findings: {}", findings.len().to_string().yellow());
+            println!("   By likelihood:");
+            for (likelihood, count) in by_likelihood {
+                println!("   * {}: {}", likelihood.cyan(), count);
+            }
+            println!("   By rule:");
+            for rule in Rule::all() {
+                if let Some(count) = by_rule.get(rule.id()) {
+                    println!(
+                        "   * {} {}: {}",
+                        rule.id().yellow(),
+                        rule.name().cyan(),
+                        count
+                    );
+                }
+            }
+            if let Some(usages) = deprecated_usages {
+                println!("   Deprecated usages: {}", usages.to_string().yellow());
+            }
+        }
+    }
+
+    configuration_warnings(cli_config);
+}
@@ -0,0 +1,106 @@
+use super::crash_report::fnv1a;
+use codebase_files::writes::Writes;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where the hashes of report files written by unused are recorded
+pub fn default_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("unused").join("report_files.json"))
+}
+
+/// Write a rendered report to `path`
+///
+/// An existing file is only replaced when unused wrote it and it hasn't changed since, unless
+/// `force` is set (with `--force`), so a mistyped path can't clobber someone's own file.
+pub fn write(path: &Path, contents: &[u8], writes: Writes, force: bool) -> Result<(), String> {
+    write_recorded(path, contents, default_path().as_deref(), force, writes)
+}
+
+fn write_recorded(
+    path: &Path,
+    contents: &[u8],
+    ledger_path: Option<&Path>,
+    force: bool,
+    writes: Writes,
+) -> Result<(), String> {
+    let mut ledger = ledger_path.map(read_ledger).unwrap_or_default();
+    let key = ledger_key(path);
+
+    if let Ok(existing) = fs::read(path) {
+        let generated = ledger.get(&key) == Some(&hash(&existing));
+
+        if !generated && !force {
+            return Err(format!(
+                "Refusing to overwrite {}, which wasn't written by unused (pass --force to replace it)",
+                path.display()
+            ));
+        }
+    }
+
+    writes
+        .write(path, contents)
+        .map_err(|e| format!("Unable to write {}: {}", path.display(), e))?;
+
+    if let Some(ledger_path) = ledger_path {
+        ledger.insert(key, hash(contents));
+        if let Ok(json) = serde_json::to_string(&ledger) {
+            writes.write(ledger_path, json).ok();
+        }
+    }
+
+    Ok(())
+}
+
+fn read_ledger(path: &Path) -> BTreeMap<String, String> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+// Files are recorded by absolute path, since reports are written from any project
+fn ledger_key(path: &Path) -> String {
+    env::current_dir()
+        .map(|dir| dir.join(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+fn hash(contents: &[u8]) -> String {
+    format!("{:016x}", fnv1a(contents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_replaces_reports_it_wrote() {
+        let dir = env::temp_dir().join("unused_report_files_replaces");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        let ledger = dir.join("report_files.json");
+        let report = dir.join("unused.json");
+        let notes = dir.join("notes.txt");
+        fs::write(&notes, "my notes").unwrap();
+
+        let first = write_recorded(&report, b"{}", Some(&ledger), false, Writes::Enabled);
+        let second = write_recorded(&report, b"[]", Some(&ledger), false, Writes::Enabled);
+        let clobbered = write_recorded(&notes, b"{}", Some(&ledger), false, Writes::Enabled);
+        fs::write(&report, "edited").unwrap();
+        let edited = write_recorded(&report, b"{}", Some(&ledger), false, Writes::Enabled);
+        let forced = write_recorded(&notes, b"{}", Some(&ledger), true, Writes::Enabled);
+        let notes_contents = fs::read_to_string(&notes).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(first, Ok(()));
+        assert_eq!(second, Ok(()));
+        assert!(clobbered.unwrap_err().contains("pass --force"));
+        assert!(edited.is_err());
+        assert_eq!(forced, Ok(()));
+        assert_eq!(notes_contents, "{}");
+    }
+}
@@ -0,0 +1,26 @@
+use colored::*;
+use token_analysis::Rule;
+
+pub fn render() {
+    let name_width = Rule::all()
+        .iter()
+        .map(|r| r.name().len())
+        .max()
+        .unwrap_or(0);
+
+    for rule in Rule::all() {
+        println!(
+            "{} {:name_width$} {}",
+            rule.id().yellow(),
+            rule.name().cyan(),
+            rule.description(),
+            name_width = name_width
+        );
+        println!(
+            "      {:name_width$} {}",
+            "",
+            rule.documentation_url().dimmed(),
+            name_width = name_width
+        );
+    }
+}
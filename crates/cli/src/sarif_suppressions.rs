@@ -0,0 +1,115 @@
+use project_configuration::IgnoreList;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+// Message suffix written by the SARIF formatter, used when results lack a `token` property
+const MESSAGE_SUFFIX: &str = " is likely unused";
+
+pub struct LoadedSuppressions {
+    pub ignore_list: IgnoreList,
+    pub warnings: Vec<String>,
+}
+
+/// Load tokens from suppressed results in previously emitted SARIF logs
+///
+/// A result is suppressed when it has a suppression whose status is `accepted` (or unset), as
+/// written by code scanning tools when an alert is dismissed.
+pub fn load(paths: &[PathBuf]) -> LoadedSuppressions {
+    let mut ignore_list = IgnoreList::default();
+    let mut warnings = vec![];
+
+    for path in paths {
+        match fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string()))
+        {
+            Ok(log) => ignore_list.tokens.extend(suppressed_tokens(&log)),
+            Err(e) => warnings.push(format!(
+                "Unable to load SARIF suppressions {}: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    ignore_list.tokens.sort();
+    ignore_list.tokens.dedup();
+
+    LoadedSuppressions {
+        ignore_list,
+        warnings,
+    }
+}
+
+fn suppressed_tokens(log: &Value) -> Vec<String> {
+    results(log)
+        .filter(|result| suppressed(result))
+        .filter_map(token)
+        .collect()
+}
+
+fn results(log: &Value) -> impl Iterator<Item = &Value> {
+    array(&log["runs"]).flat_map(|run| array(&run["results"]))
+}
+
+fn suppressed(result: &Value) -> bool {
+    array(&result["suppressions"])
+        .any(|suppression| matches!(suppression["status"].as_str(), None | Some("accepted")))
+}
+
+fn token(result: &Value) -> Option<String> {
+    result["properties"]["token"]
+        .as_str()
+        .or_else(|| {
+            result["message"]["text"]
+                .as_str()
+                .and_then(|text| text.split_once(MESSAGE_SUFFIX))
+                .map(|(token, _)| token)
+        })
+        .map(String::from)
+}
+
+fn array(value: &Value) -> impl Iterator<Item = &Value> {
+    value.as_array().into_iter().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_tokens_from_accepted_suppressions() {
+        let log = serde_json::json!({
+            "runs": [{
+                "results": [
+                    {
+                        "ruleId": "UN001",
+                        "message": { "text": "name is likely unused: Only one occurrence exists" },
+                        "properties": { "token": "name" },
+                        "suppressions": [{ "kind": "external", "status": "accepted" }]
+                    },
+                    {
+                        "ruleId": "UN001",
+                        "message": { "text": "total is likely unused: Only one occurrence exists" },
+                        "suppressions": [{ "kind": "external" }]
+                    },
+                    {
+                        "ruleId": "UN001",
+                        "message": { "text": "title is likely unused: Only one occurrence exists" },
+                        "suppressions": [{ "kind": "external", "status": "rejected" }]
+                    },
+                    {
+                        "ruleId": "UN001",
+                        "message": { "text": "email is likely unused: Only one occurrence exists" }
+                    }
+                ]
+            }]
+        });
+
+        assert_eq!(
+            suppressed_tokens(&log),
+            vec![String::from("name"), String::from("total")]
+        );
+    }
+}
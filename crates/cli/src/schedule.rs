@@ -0,0 +1,155 @@
+use super::blame::civil_date;
+use std::str::FromStr;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+// Name and range of each field, where 7 is also accepted for Sunday
+const FIELDS: [(&str, u32, u32); 5] = [
+    ("minute", 0, 59),
+    ("hour", 0, 23),
+    ("day of month", 1, 31),
+    ("month", 1, 12),
+    ("day of week", 0, 7),
+];
+
+/// A cron-style schedule, evaluated in UTC
+///
+/// Five space-separated fields give the minute, hour, day of month, month, and day of week (0
+/// or 7 is Sunday). Each field is `*` or a comma-separated list of values and ranges (`1-5`),
+/// optionally stepped (`*/15`, `0-30/10`). As with cron, when both the day of month and day of
+/// week are restricted, a time matching either is scheduled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CronSchedule {
+    // Bitmasks of the values allowed in each field
+    fields: [u64; 5],
+    days_restricted: (bool, bool),
+}
+
+impl CronSchedule {
+    /// Is a run scheduled during the minute containing `timestamp` (seconds since the epoch)?
+    pub fn matches(&self, timestamp: u64) -> bool {
+        let days = timestamp / SECONDS_PER_DAY;
+        let (_, month, day) = civil_date(timestamp);
+        // 1970-01-01 was a Thursday
+        let weekday = (days + 4) % 7;
+        let allows = |field: usize, value: u64| self.fields[field] & (1 << value) != 0;
+
+        let day_matches = match self.days_restricted {
+            (true, true) => allows(2, day as u64) || allows(4, weekday),
+            _ => allows(2, day as u64) && allows(4, weekday),
+        };
+
+        allows(0, timestamp / 60 % 60)
+            && allows(1, timestamp / 3600 % 24)
+            && allows(3, month as u64)
+            && day_matches
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        if parts.len() != FIELDS.len() {
+            return Err(format!(
+                "Unable to parse schedule: {} (expected 5 fields: minute hour day month weekday)",
+                s
+            ));
+        }
+
+        let mut fields = [0; 5];
+        for (i, (part, (name, min, max))) in parts.iter().zip(FIELDS.iter()).enumerate() {
+            fields[i] = parse_field(part, *min, *max)
+                .map_err(|e| format!("Unable to parse schedule: {} ({} {})", s, name, e))?;
+        }
+        // Sunday may be written as 7
+        if fields[4] & (1 << 7) != 0 {
+            fields[4] |= 1;
+        }
+
+        Ok(CronSchedule {
+            fields,
+            days_restricted: (parts[2] != "*", parts[4] != "*"),
+        })
+    }
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0;
+
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, parse_value(step, 1, max)?),
+            None => (item, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (parse_value(start, min, max)?, parse_value(end, min, max)?),
+            None => {
+                let value = parse_value(range, min, max)?;
+                (value, if step > 1 { max } else { value })
+            }
+        };
+        if start > end {
+            return Err(format!("range {} is reversed", range));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+fn parse_value(value: &str, min: u32, max: u32) -> Result<u32, String> {
+    match value.parse() {
+        Ok(n) if (min..=max).contains(&n) => Ok(n),
+        _ => Err(format!("{} is not between {} and {}", value, min, max)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-03-04 was a Monday
+    const MONDAY_MIDNIGHT: u64 = 1_709_510_400;
+
+    fn at(days: u64, hour: u64, minute: u64) -> u64 {
+        MONDAY_MIDNIGHT + days * SECONDS_PER_DAY + hour * 3600 + minute * 60
+    }
+
+    #[test]
+    fn matches_scheduled_minutes() {
+        let nightly: CronSchedule = "30 3 * * *".parse().unwrap();
+        let weekdays: CronSchedule = "*/15 9-17 * * 1-5".parse().unwrap();
+        let sundays: CronSchedule = "0 0 * * 7".parse().unwrap();
+
+        assert!(nightly.matches(at(0, 3, 30) + 59));
+        assert!(!nightly.matches(at(0, 3, 31)));
+        assert!(weekdays.matches(at(4, 9, 45)));
+        assert!(!weekdays.matches(at(5, 9, 45)));
+        assert!(!weekdays.matches(at(0, 9, 50)));
+        assert!(sundays.matches(at(6, 0, 0)));
+        assert!(!sundays.matches(at(5, 0, 0)));
+    }
+
+    #[test]
+    fn matches_either_restricted_day() {
+        let schedule: CronSchedule = "0 0 1 * 1".parse().unwrap();
+
+        assert!(schedule.matches(at(0, 0, 0)));
+        assert!(schedule.matches(MONDAY_MIDNIGHT - 3 * SECONDS_PER_DAY));
+        assert!(!schedule.matches(at(1, 0, 0)));
+    }
+
+    #[test]
+    fn rejects_invalid_schedules() {
+        assert!("0 3 * *".parse::<CronSchedule>().is_err());
+        assert!("60 3 * * *".parse::<CronSchedule>().is_err());
+        assert!("0 5-3 * * *".parse::<CronSchedule>().is_err());
+        assert!("0 3 * * mon".parse::<CronSchedule>().is_err());
+    }
+}
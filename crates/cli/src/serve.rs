@@ -0,0 +1,144 @@
+use super::cli_configuration::CliConfiguration;
+use super::context::Context;
+use super::error_message;
+use super::exit_status::ExitStatus;
+use super::flags::AnalysisFlags;
+use super::formatters;
+use super::schedule::CronSchedule;
+use colored::*;
+use serde_json::json;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+enum Event {
+    Query(String),
+    FullRescanDue,
+    Closed,
+}
+
+/// Keep analysis results in memory and answer line-delimited queries on STDIN
+///
+/// Each query receives a single line of JSON in response. Supported queries are `findings`,
+/// `explain TOKEN`, `rescan` (reload tags and search again, reusing per-file scans for files
+/// that haven't changed), `rescan full` (search every file again), and `quit`.
+///
+/// With a `full_rescan` schedule (from the flag or the project settings), full rescans also run
+/// in the background on that schedule, so long-lived servers don't drift from the codebase.
+pub fn run(
+    context: &Context,
+    full_rescan: Option<CronSchedule>,
+    mut flags: AnalysisFlags,
+) -> ExitStatus {
+    flags.no_progress = true;
+    flags.scan_cache = true;
+
+    let mut cli_config = match super::build_configuration(context, flags.clone()) {
+        Ok(cli_config) => cli_config,
+        Err(e) => {
+            error_message::failed_token_parse(e);
+            return ExitStatus::Error;
+        }
+    };
+
+    let (sender, events) = mpsc::channel();
+    read_queries(sender.clone());
+    if let Some(schedule) = full_rescan.or_else(|| context.project.full_rescan.clone()) {
+        schedule_full_rescans(schedule, sender);
+    }
+
+    let mut stdout = io::stdout();
+
+    for event in events {
+        let line = match event {
+            Event::Query(line) => line,
+            Event::FullRescanDue => {
+                tracing::info!("Running scheduled full rescan");
+                match rescan(context, &flags, true) {
+                    Ok(rescanned) => cli_config = rescanned,
+                    Err(e) => eprintln!("{}", format!("Scheduled full rescan failed: {}", e).red()),
+                }
+                continue;
+            }
+            Event::Closed => break,
+        };
+        let mut parts = line.split_whitespace();
+
+        let response = match (parts.next(), parts.next()) {
+            (None, _) => continue,
+            (Some("quit"), _) => break,
+            (Some("findings"), _) => formatters::json::report(&cli_config),
+            (Some("explain"), Some(token)) => cli_config
+                .find(token)
+                .map(formatters::json::finding)
+                .unwrap_or_else(|| json!({ "error": format!("{} wasn't analyzed", token) })),
+            (Some("rescan"), mode @ (None | Some("full"))) => {
+                let full = mode.is_some();
+                match rescan(context, &flags, full) {
+                    Ok(rescanned) => {
+                        cli_config = rescanned;
+                        json!({
+                            "status": "ok",
+                            "rescan": if full { "full" } else { "incremental" }
+                        })
+                    }
+                    Err(e) => json!({ "error": e }),
+                }
+            }
+            (Some(_), _) => json!({ "error": format!("Unknown query: {}", line.trim()) }),
+        };
+
+        if writeln!(stdout, "{}", response)
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+
+    ExitStatus::Clean
+}
+
+// Full rescans search every file, rather than reusing per-file scans or indexed occurrences
+pub fn rescan(
+    context: &Context,
+    flags: &AnalysisFlags,
+    full: bool,
+) -> Result<CliConfiguration, String> {
+    let mut flags = flags.clone();
+    if full {
+        flags.scan_cache = false;
+        flags.incremental = false;
+    }
+
+    super::build_configuration(context, flags).map_err(|e| e.to_string())
+}
+
+fn read_queries(sender: Sender<Event>) {
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let sent = line.map(|line| sender.send(Event::Query(line)));
+            if !matches!(sent, Ok(Ok(_))) {
+                break;
+            }
+        }
+        sender.send(Event::Closed).ok();
+    });
+}
+
+// Checks the schedule at the start of each minute, until the server stops listening
+fn schedule_full_rescans(schedule: CronSchedule, sender: Sender<Event>) {
+    thread::spawn(move || loop {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let next_minute = now - now % 60 + 60;
+        thread::sleep(Duration::from_secs(next_minute - now));
+
+        if schedule.matches(next_minute) && sender.send(Event::FullRescanDue).is_err() {
+            break;
+        }
+    });
+}
@@ -0,0 +1,219 @@
+use super::metrics;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::str::FromStr;
+use token_analysis::{Rule, TokenUsage, UsageLikelihoodStatus};
+
+/// A kind of finding, which can be reported at its own severity under `severities` in the
+/// project settings
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Category {
+    /// Defined only in files where every token is reported
+    DeadFile,
+    /// Referenced only from tests
+    TestOnly,
+    /// Reported with high likelihood
+    Unused,
+    /// Reported with medium or low likelihood
+    LowUsage,
+}
+
+impl FromStr for Category {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_ref() {
+            "dead-file" => Ok(Category::DeadFile),
+            "test-only" => Ok(Category::TestOnly),
+            "unused" => Ok(Category::Unused),
+            "low-usage" => Ok(Category::LowUsage),
+            v => Err(format!(
+                "Unknown category: {} (expected one of: unused, test-only, dead-file, low-usage)",
+                v
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    /// Fails the run whenever reported
+    Error,
+    /// Fails the run with --fail-on-unused or --max-unused
+    Warning,
+    /// Reported, but never fails the run
+    Info,
+}
+
+impl Severity {
+    pub fn sarif_level(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "note",
+        }
+    }
+
+    pub fn code_quality(self) -> &'static str {
+        match self {
+            Severity::Error => "major",
+            Severity::Warning => "minor",
+            Severity::Info => "info",
+        }
+    }
+
+    pub fn checkstyle(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "error" => Ok(Severity::Error),
+            "warning" => Ok(Severity::Warning),
+            "info" => Ok(Severity::Info),
+            v => Err(format!("Unknown severity: {}", v)),
+        }
+    }
+}
+
+/// Severities configured for categories of findings
+///
+/// Findings in categories without one are reported as before: at a level chosen from their
+/// likelihood, failing the run only with --fail-on-unused or --max-unused.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Severities(BTreeMap<Category, Severity>);
+
+impl Severities {
+    pub fn parse(settings: &BTreeMap<String, String>) -> Result<Self, String> {
+        settings
+            .iter()
+            .map(|(category, severity)| {
+                let key = format!("severities.{}", category);
+                let category = category.parse().map_err(|e| format!("{}: {}", key, e))?;
+                let severity = severity.parse().map_err(|e| format!("{}: {}", key, e))?;
+                Ok((category, severity))
+            })
+            .collect::<Result<_, String>>()
+            .map(Severities)
+    }
+
+    /// Categorize `findings`, given every token analyzed so dead files can be told apart
+    pub fn resolve<'a>(
+        &'a self,
+        analyzed: &[&'a TokenUsage],
+        findings: &[&'a TokenUsage],
+    ) -> FindingSeverities<'a> {
+        self.resolve_with(analyzed, findings, self.0.contains_key(&Category::DeadFile))
+    }
+
+    /// Categorize `findings` as `resolve` does, telling dead files apart even when no severity
+    /// is configured for them
+    pub fn categorize<'a>(
+        &'a self,
+        analyzed: &[&'a TokenUsage],
+        findings: &[&'a TokenUsage],
+    ) -> FindingSeverities<'a> {
+        self.resolve_with(analyzed, findings, true)
+    }
+
+    fn resolve_with<'a>(
+        &'a self,
+        analyzed: &[&'a TokenUsage],
+        findings: &[&'a TokenUsage],
+        find_dead_files: bool,
+    ) -> FindingSeverities<'a> {
+        let dead_files = if find_dead_files {
+            metrics::dead_files(analyzed, findings)
+        } else {
+            HashSet::new()
+        };
+
+        FindingSeverities {
+            configured: &self.0,
+            dead_files,
+        }
+    }
+}
+
+/// Severities of the findings from a single run
+pub struct FindingSeverities<'a> {
+    configured: &'a BTreeMap<Category, Severity>,
+    dead_files: HashSet<&'a Path>,
+}
+
+impl FindingSeverities<'_> {
+    pub fn category(&self, usage: &TokenUsage) -> Category {
+        let definitions = &usage.result.token.definitions;
+        let likelihood = &usage.usage_likelihood;
+
+        if !definitions.is_empty()
+            && definitions
+                .iter()
+                .all(|d| self.dead_files.contains(&*d.file_path))
+        {
+            Category::DeadFile
+        } else if matches!(likelihood.rule, Rule::TestOnly | Rule::OnlyUsedInTests) {
+            Category::TestOnly
+        } else if likelihood.status == UsageLikelihoodStatus::High {
+            Category::Unused
+        } else {
+            Category::LowUsage
+        }
+    }
+
+    /// The severity configured for the finding's category, if there is one
+    pub fn of(&self, usage: &TokenUsage) -> Option<Severity> {
+        if self.configured.is_empty() {
+            return None;
+        }
+        self.configured.get(&self.category(usage)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{usage, TokenUsageBuilder};
+
+    #[test]
+    fn resolves_severities_by_category() {
+        let severities = Severities::parse(&BTreeMap::from([
+            (String::from("dead-file"), String::from("error")),
+            (String::from("test_only"), String::from("info")),
+        ]))
+        .unwrap();
+        let legacy = usage("legacy", "app/legacy.rb");
+        let tested = TokenUsageBuilder::new("tested", "app/person.rb")
+            .with_occurrences(&[("app/person.rb", 1), ("test/person_test.rb", 1)])
+            .build();
+        let name = TokenUsageBuilder::new("name", "app/person.rb")
+            .with_occurrences(&[
+                ("app/person.rb", 1),
+                ("app/view.rb", 1),
+                ("app/report.rb", 1),
+            ])
+            .build();
+        let resolved = severities.resolve(&[&legacy, &tested, &name], &[&legacy, &tested]);
+
+        assert_eq!(resolved.category(&legacy), Category::DeadFile);
+        assert_eq!(resolved.of(&legacy), Some(Severity::Error));
+        assert_eq!(resolved.category(&tested), Category::TestOnly);
+        assert_eq!(resolved.of(&tested), Some(Severity::Info));
+
+        assert!(Severities::parse(&BTreeMap::from([(
+            String::from("unused"),
+            String::from("fatal")
+        )]))
+        .is_err_and(|e| e == "severities.unused: Unknown severity: fatal"));
+    }
+}
@@ -0,0 +1,150 @@
+use codebase_files::text;
+use read_ctags::CtagItem;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use token_analysis::TokenUsage;
+
+/// Source lines of a definition, along with any surrounding lines requested with `--context`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Snippet {
+    /// Line number of the first line
+    pub start_line: usize,
+    /// Line number of the definition
+    pub line: usize,
+    pub lines: Vec<String>,
+}
+
+impl Snippet {
+    /// Each line along with its line number
+    pub fn numbered(&self) -> impl Iterator<Item = (usize, &str)> {
+        (self.start_line..).zip(self.lines.iter().map(String::as_str))
+    }
+}
+
+/// Snippets for the definitions of reported findings, keyed by path and tag address
+#[derive(Default)]
+pub struct Snippets {
+    snippets: HashMap<(Arc<Path>, String), Snippet>,
+}
+
+impl Snippets {
+    /// Read each defining file of `usages` once, keeping `context` lines either side of each
+    /// definition
+    ///
+    /// Definitions whose file can't be read or whose address no longer matches are skipped.
+    pub fn load(usages: &[&TokenUsage], context: usize) -> Self {
+        let mut by_file: BTreeMap<&Path, Vec<&CtagItem>> = BTreeMap::new();
+
+        for definition in usages
+            .iter()
+            .flat_map(|u| u.result.token.definitions.iter())
+        {
+            by_file
+                .entry(&*definition.file_path)
+                .or_default()
+                .push(definition);
+        }
+
+        let mut snippets = HashMap::new();
+
+        for (path, definitions) in by_file {
+            let contents = match text::read(path) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    tracing::debug!("Unable to read {} for snippets: {}", path.display(), e);
+                    continue;
+                }
+            };
+
+            for definition in definitions {
+                if let Some(snippet) = definition
+                    .resolve_line(&contents)
+                    .and_then(|line| extract(&contents, line, context))
+                {
+                    snippets.insert(
+                        (definition.file_path.clone(), definition.address.clone()),
+                        snippet,
+                    );
+                }
+            }
+        }
+
+        Snippets { snippets }
+    }
+
+    /// Snippet for a single definition
+    pub fn get(&self, definition: &CtagItem) -> Option<&Snippet> {
+        self.snippets
+            .get(&(definition.file_path.clone(), definition.address.clone()))
+    }
+
+    /// Snippets for a token's definitions, by the path of each definition
+    pub fn for_usage(&self, usage: &TokenUsage) -> Vec<(PathBuf, Snippet)> {
+        let mut snippets: Vec<(PathBuf, Snippet)> = usage
+            .result
+            .token
+            .definitions
+            .iter()
+            .filter_map(|d| self.get(d).map(|s| (d.file_path.to_path_buf(), s.clone())))
+            .collect();
+        snippets.sort_by_key(|(path, snippet)| (path.clone(), snippet.line));
+        snippets
+    }
+}
+
+// `line` (1-based) of `contents` with up to `context` lines either side, trailing whitespace
+// removed
+fn extract(contents: &str, line: usize, context: usize) -> Option<Snippet> {
+    let start_line = line.saturating_sub(context).max(1);
+    let lines: Vec<String> = contents
+        .lines()
+        .skip(start_line - 1)
+        .take(line - start_line + context + 1)
+        .map(|l| l.trim_end().to_string())
+        .collect();
+
+    if lines.len() <= line - start_line {
+        return None;
+    }
+
+    Some(Snippet {
+        start_line,
+        line,
+        lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "class Person\n  def full_name  \n    name\n  end\nend\n";
+
+    #[test]
+    fn extracts_lines_around_a_definition() {
+        assert_eq!(
+            extract(SOURCE, 2, 0),
+            Some(Snippet {
+                start_line: 2,
+                line: 2,
+                lines: vec![String::from("  def full_name")],
+            })
+        );
+        assert_eq!(
+            extract(SOURCE, 1, 2),
+            Some(Snippet {
+                start_line: 1,
+                line: 1,
+                lines: vec![
+                    String::from("class Person"),
+                    String::from("  def full_name"),
+                    String::from("    name"),
+                ],
+            })
+        );
+        assert_eq!(extract(SOURCE, 5, 1).map(|s| s.lines.len()), Some(2));
+        assert_eq!(extract(SOURCE, 9, 1), None);
+    }
+}
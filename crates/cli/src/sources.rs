@@ -0,0 +1,83 @@
+use codebase_files::{DiskFileSystem, FileSystem};
+use read_ctags::CtagItem;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Contents of the files definitions are in, each read at most once however many of its
+/// definitions are located
+pub struct Sources {
+    file_system: Arc<dyn FileSystem>,
+    contents: RefCell<HashMap<Arc<Path>, Option<Rc<str>>>>,
+}
+
+impl Default for Sources {
+    fn default() -> Self {
+        Sources::new(Arc::new(DiskFileSystem))
+    }
+}
+
+impl Sources {
+    /// Sources read from `file_system`
+    pub fn new(file_system: Arc<dyn FileSystem>) -> Self {
+        Sources {
+            file_system,
+            contents: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Contents of the file at `path`, or `None` when it can't be read
+    pub fn contents(&self, path: &Arc<Path>) -> Option<Rc<str>> {
+        self.contents
+            .borrow_mut()
+            .entry(path.clone())
+            .or_insert_with(|| self.file_system.read(path).ok().map(Rc::from))
+            .clone()
+    }
+
+    /// Line and column (1-based, in bytes) of `definition`, as `CtagItem::line` and
+    /// `CtagItem::column` resolve them
+    pub fn position(&self, definition: &CtagItem) -> (Option<usize>, Option<usize>) {
+        let source = self.contents(&definition.file_path);
+        let line = match &source {
+            Some(source) => definition.resolve_line(source),
+            None => definition.address.parse().ok(),
+        };
+        let column = source
+            .zip(line)
+            .and_then(|(source, line)| definition.resolve_column(&source, line));
+
+        (line, column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codebase_files::MemoryFileSystem;
+
+    #[test]
+    fn locates_definitions_from_files_read_once() {
+        let file_system = Arc::new(MemoryFileSystem::default());
+        file_system.insert("app/person.rb", "class Person\n  def name\n  end\nend\n");
+        let sources = Sources::new(file_system.clone());
+        let definition = |address: &str| {
+            crate::test_support::TokenUsageBuilder::new("name", "app/person.rb")
+                .with_address(address)
+                .definition()
+        };
+
+        assert_eq!(
+            sources.position(&definition("/^  def name$/")),
+            (Some(2), Some(7))
+        );
+        file_system.insert("app/person.rb", "");
+        assert_eq!(sources.position(&definition("2")), (Some(2), Some(7)));
+        assert_eq!(
+            Sources::default().position(&definition("2")),
+            (Some(2), None)
+        );
+    }
+}
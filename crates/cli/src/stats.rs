@@ -0,0 +1,339 @@
+use super::cli_configuration::CliConfiguration;
+use super::context::Context;
+use super::error_message;
+use super::exit_status::ExitStatus;
+use super::formatters::internal::{configuration_warnings, print_json};
+use super::hot::{hot_tokens, HotToken};
+use super::Format;
+use colored::*;
+use read_ctags::{ParseWarning, TagsFile};
+use serde::Serialize;
+use serde_json::json;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use token_analysis::TokenUsage;
+
+// Label for tags whose language ctags didn't record and couldn't be inferred from their path
+const UNKNOWN_LANGUAGE: &str = "(unknown)";
+
+// Upper bounds of the occurrence count buckets; counts include each token's definitions
+const BUCKETS: [(usize, &str); 5] = [
+    (1, "1"),
+    (5, "2-5"),
+    (20, "6-20"),
+    (100, "21-100"),
+    (usize::MAX, "101+"),
+];
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Bucket {
+    occurrences: &'static str,
+    tokens: usize,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct KindStats {
+    tokens: usize,
+    used_once: usize,
+    occurrences: usize,
+}
+
+impl KindStats {
+    fn average_occurrences(&self) -> f64 {
+        self.occurrences as f64 / self.tokens.max(1) as f64
+    }
+}
+
+#[derive(Serialize)]
+struct DefiningFile<'a> {
+    path: &'a Path,
+    definitions: usize,
+}
+
+#[derive(Serialize)]
+struct TokenStats<'a> {
+    tokens: usize,
+    distribution: Vec<Bucket>,
+    most_referenced: Vec<HotToken<'a>>,
+    kinds: BTreeMap<String, KindStats>,
+}
+
+/// Summarize how often tokens occur across the codebase, overall and by kind
+pub fn tokens(cli_config: &CliConfiguration, top: usize) {
+    let usages = cli_config.included();
+    let stats = TokenStats {
+        tokens: usages.len(),
+        distribution: distribution(&usages),
+        most_referenced: hot_tokens(&usages, top),
+        kinds: by_kind(&usages),
+    };
+
+    match cli_config.format() {
+        Format::Json => print_json(&stats),
+        _ => {
+            println!("{}", "== OCCURRENCE DISTRIBUTION ==".white());
+            for bucket in stats.distribution.iter() {
+                println!(
+                    "   {} occurrences: {} tokens ({:.1}%)",
+                    bucket.occurrences,
+                    bucket.tokens.to_string().yellow(),
+                    percentage(bucket.tokens, stats.tokens)
+                );
+            }
+            println!();
+            println!("{}", "== MOST REFERENCED TOKENS ==".white());
+            for t in stats.most_referenced.iter() {
+                println!(
+                    "   {} {} ({} files)",
+                    t.occurrences.to_string().yellow(),
+                    t.token.cyan(),
+                    t.files
+                );
+            }
+            println!();
+            println!("{}", "== TOKENS BY KIND ==".white());
+            for (kind, kind_stats) in stats.kinds.iter() {
+                println!(
+                    "   {}: {} tokens, {} used once, {:.1} occurrences on average",
+                    kind.cyan(),
+                    kind_stats.tokens.to_string().yellow(),
+                    kind_stats.used_once,
+                    kind_stats.average_occurrences()
+                );
+            }
+        }
+    }
+
+    configuration_warnings(cli_config);
+}
+
+/// Summarize the tags file's entries by language and kind, as a check on how ctags was
+/// configured
+pub fn tags(context: &Context, top: usize) -> ExitStatus {
+    match context.tags_reader().load_lenient() {
+        Ok((tags_file, warnings)) => {
+            render_tags(context, &tags_file, &warnings, top);
+            ExitStatus::Clean
+        }
+        Err(e) => {
+            error_message::failed_token_parse(e);
+            ExitStatus::Error
+        }
+    }
+}
+
+fn render_tags(context: &Context, tags_file: &TagsFile, warnings: &[ParseWarning], top: usize) {
+    let (languages, kinds) = entries_by_language_and_kind(tags_file);
+    let files = most_definitions(tags_file, top);
+
+    match context.format {
+        Format::Json => print_json(&json!({
+            "path": tags_file.path,
+            "entries": tags_file.tags.len(),
+            "languages": languages,
+            "kinds": kinds,
+            "most_definitions": files,
+            "malformed": warnings
+                .iter()
+                .map(|w| json!({ "line": w.line, "content": w.content }))
+                .collect::<Vec<_>>(),
+        })),
+        _ => {
+            println!("   Path: {}", tags_file.path.to_string_lossy().cyan());
+            println!("   Entries: {}", tags_file.tags.len().to_string().yellow());
+            println!(
+                "   Malformed lines: {}",
+                warnings.len().to_string().yellow()
+            );
+            for warning in warnings {
+                println!(
+                    "   {} {}",
+                    format!("{}:", warning.line).red(),
+                    warning.content.escape_debug()
+                );
+            }
+            println!();
+            println!("{}", "== ENTRIES BY LANGUAGE ==".white());
+            for (language, entries) in languages.iter() {
+                println!("   {} {}", entries.to_string().yellow(), language.cyan());
+            }
+            println!();
+            println!("{}", "== ENTRIES BY KIND ==".white());
+            for (kind, entries) in kinds.iter() {
+                println!("   {} {}", entries.to_string().yellow(), kind.cyan());
+            }
+            println!();
+            println!("{}", "== FILES WITH THE MOST DEFINITIONS ==".white());
+            for file in files.iter() {
+                println!(
+                    "   {} {}",
+                    file.definitions.to_string().yellow(),
+                    file.path.to_string_lossy().cyan()
+                );
+            }
+        }
+    }
+}
+
+fn entries_by_language_and_kind(
+    tags_file: &TagsFile,
+) -> (BTreeMap<String, usize>, BTreeMap<String, usize>) {
+    let mut languages: BTreeMap<String, usize> = BTreeMap::new();
+    let mut kinds: BTreeMap<String, usize> = BTreeMap::new();
+
+    for tag in tags_file.tags.iter() {
+        let language = tag
+            .language
+            .map(|l| l.to_string())
+            .unwrap_or_else(|| String::from(UNKNOWN_LANGUAGE));
+        *languages.entry(language).or_default() += 1;
+        *kinds.entry(format!("{:?}", tag.kind)).or_default() += 1;
+    }
+
+    (languages, kinds)
+}
+
+fn most_definitions(tags_file: &TagsFile, top: usize) -> Vec<DefiningFile<'_>> {
+    let mut by_path: BTreeMap<&Path, usize> = BTreeMap::new();
+    for tag in tags_file.tags.iter() {
+        *by_path.entry(&tag.file_path).or_default() += 1;
+    }
+
+    let mut files: Vec<DefiningFile> = by_path
+        .into_iter()
+        .map(|(path, definitions)| DefiningFile { path, definitions })
+        .collect();
+    // Stable, so files with as many definitions stay ordered by path
+    files.sort_by_key(|file| Reverse(file.definitions));
+    files.truncate(top);
+    files
+}
+
+fn distribution(usages: &[&TokenUsage]) -> Vec<Bucket> {
+    let mut buckets: Vec<Bucket> = BUCKETS
+        .iter()
+        .map(|(_, occurrences)| Bucket {
+            occurrences,
+            tokens: 0,
+        })
+        .collect();
+
+    for usage in usages {
+        let occurrences = usage.file_type_counts.total().occurrence_count;
+        if let Some(index) = BUCKETS.iter().position(|(max, _)| occurrences <= *max) {
+            buckets[index].tokens += 1;
+        }
+    }
+
+    buckets
+}
+
+// Tokens defined as several kinds (such as a class and a method) count towards each
+fn by_kind(usages: &[&TokenUsage]) -> BTreeMap<String, KindStats> {
+    let mut kinds: BTreeMap<String, KindStats> = BTreeMap::new();
+
+    for usage in usages {
+        let occurrences = usage.file_type_counts.total().occurrence_count;
+        let token_kinds: BTreeSet<String> = usage
+            .result
+            .token
+            .definitions
+            .iter()
+            .map(|d| format!("{:?}", d.kind))
+            .collect();
+
+        for kind in token_kinds {
+            let stats = kinds.entry(kind).or_default();
+            stats.tokens += 1;
+            stats.occurrences += occurrences;
+            if occurrences <= 1 {
+                stats.used_once += 1;
+            }
+        }
+    }
+
+    kinds
+}
+
+fn percentage(count: usize, total: usize) -> f64 {
+    100.0 * count as f64 / total.max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TokenUsageBuilder;
+    use read_ctags::{CtagItem, TokenKind};
+    use std::path::PathBuf;
+
+    #[test]
+    fn buckets_tokens_by_occurrences_and_kind() {
+        let usages = [
+            TokenUsageBuilder::new("name", "lib/person.rb").build(),
+            TokenUsageBuilder::new("total", "lib/person.rb")
+                .with_occurrences(&[("lib/person.rb", 5)])
+                .build(),
+            TokenUsageBuilder::new("Person", "lib/person.rb")
+                .with_kind(TokenKind::Class)
+                .with_occurrences(&[("lib/person.rb", 150)])
+                .build(),
+        ];
+        let usages: Vec<&TokenUsage> = usages.iter().collect();
+
+        assert_eq!(
+            distribution(&usages)
+                .into_iter()
+                .map(|b| b.tokens)
+                .collect::<Vec<_>>(),
+            vec![1, 1, 0, 0, 1]
+        );
+        assert_eq!(
+            by_kind(&usages).get("Method"),
+            Some(&KindStats {
+                tokens: 2,
+                used_once: 1,
+                occurrences: 6,
+            })
+        );
+        assert_eq!(by_kind(&usages)["Class"].average_occurrences(), 150.0);
+    }
+
+    #[test]
+    fn summarizes_tags_by_language_kind_and_file() {
+        let tags_file = CtagItem::parse(
+            PathBuf::from("tags"),
+            "Person\tapp/person.rb\t1;\"\tc\n\
+             full_name\tapp/person.rb\t2;\"\tf\n\
+             Order\tapp/order.rb\t1;\"\tc\n\
+             build\tscripts/build.unknown\t1;\"\tf\n",
+        )
+        .unwrap();
+
+        let (languages, kinds) = entries_by_language_and_kind(&tags_file);
+        let files: Vec<_> = most_definitions(&tags_file, 2)
+            .into_iter()
+            .map(|file| (file.path.to_string_lossy().to_string(), file.definitions))
+            .collect();
+
+        assert_eq!(
+            languages,
+            BTreeMap::from([(String::from("(unknown)"), 1), (String::from("Ruby"), 3)])
+        );
+        assert_eq!(
+            kinds,
+            BTreeMap::from([
+                (String::from("Class"), 2),
+                (String::from("Method"), 1),
+                (String::from("Unknown('f')"), 1)
+            ])
+        );
+        assert_eq!(
+            files,
+            vec![
+                (String::from("app/person.rb"), 2),
+                (String::from("app/order.rb"), 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,342 @@
+use super::context::Context;
+use super::error_message;
+use super::exit_status::ExitStatus;
+use super::flags::TagsCommand;
+use super::formatters::internal::print_json;
+use super::Format;
+use codebase_files::writes::Writes;
+use codebase_files::DiskFileSystem;
+use colored::*;
+use read_ctags::{
+    CtagItem, DanglingTags, ParseWarning, TagQuery, TagsDiff, TagsFile, TagsReader, TagsWriteError,
+    TagsWriter,
+};
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+
+pub fn render(context: &Context, command: TagsCommand) -> ExitStatus {
+    match command {
+        TagsCommand::Info => match context.tags_reader().load() {
+            Ok(tags_file) => {
+                info(context, &tags_file);
+                ExitStatus::Clean
+            }
+            Err(e) => {
+                error_message::failed_token_parse(e);
+                ExitStatus::Error
+            }
+        },
+        TagsCommand::Parse { path } => match reader(context, path).load() {
+            Ok(tags_file) => {
+                parse(context, &tags_file);
+                ExitStatus::Clean
+            }
+            Err(e) => {
+                error_message::failed_token_parse(e);
+                ExitStatus::Error
+            }
+        },
+        TagsCommand::Validate { path } => match reader(context, path).load_lenient() {
+            Ok((tags_file, warnings)) => {
+                let dangling = DanglingTags::check(&tags_file.tags);
+                validate(context, &tags_file, &warnings, &dangling);
+                if warnings.is_empty() && dangling.is_empty() {
+                    ExitStatus::Clean
+                } else {
+                    ExitStatus::Findings
+                }
+            }
+            Err(e) => {
+                error_message::failed_token_parse(e);
+                ExitStatus::Error
+            }
+        },
+        TagsCommand::Find {
+            name,
+            prefix,
+            icase,
+            kind,
+        } => match context.tags_reader().load() {
+            Ok(tags_file) => {
+                let query = TagQuery {
+                    name,
+                    prefix,
+                    ignore_case: icase,
+                    kinds: kind,
+                };
+                find(context, &query.find(&tags_file.tags));
+                ExitStatus::Clean
+            }
+            Err(e) => {
+                error_message::failed_token_parse(e);
+                ExitStatus::Error
+            }
+        },
+        TagsCommand::Diff { old, new } => {
+            match load(&old, context.writes).and_then(|o| Ok((o, load(&new, context.writes)?))) {
+                Ok((old, new)) => {
+                    diff(context, &TagsDiff::between(&old, &new));
+                    ExitStatus::Clean
+                }
+                Err(e) => {
+                    eprintln!("{}", e.red());
+                    ExitStatus::Error
+                }
+            }
+        }
+    }
+}
+
+// A tags file named on the command line, otherwise the one used for analysis
+fn reader(context: &Context, path: Option<PathBuf>) -> TagsReader {
+    match path {
+        Some(path) => TagsReader::for_path(&path)
+            .with_format(context.tags_format)
+            .with_parse_mode(context.parse_mode),
+        None => context.tags_reader(),
+    }
+}
+
+fn info(context: &Context, tags_file: &TagsFile) {
+    let names: HashSet<&str> = tags_file.tags.iter().map(|t| t.name.as_str()).collect();
+    let program = tags_file.program.name.clone().unwrap_or_default();
+    let version = tags_file.program.version.clone().unwrap_or_default();
+
+    match context.format {
+        Format::Json => print_json(&json!({
+            "path": tags_file.path,
+            "sources": tags_file.sources,
+            "program": program,
+            "version": version,
+            "entries": tags_file.tags.len(),
+            "names": names.len(),
+        })),
+        _ => {
+            println!("   Path: {}", tags_file.path.to_string_lossy().cyan());
+            if tags_file.sources.len() > 1 {
+                let sources: Vec<_> = tags_file
+                    .sources
+                    .iter()
+                    .map(|path| path.to_string_lossy())
+                    .collect();
+                println!("   Merged from: {}", sources.join(", ").cyan());
+            }
+            println!("   Program: {} {}", program.cyan(), version);
+            println!("   Entries: {}", tags_file.tags.len().to_string().yellow());
+            println!("   Unique names: {}", names.len().to_string().yellow());
+        }
+    }
+}
+
+fn parse(context: &Context, tags_file: &TagsFile) {
+    let mut tags: Vec<&CtagItem> = tags_file.tags.iter().collect();
+    tags.sort_by(|a, b| {
+        (&a.file_path, &a.name, &a.address).cmp(&(&b.file_path, &b.name, &b.address))
+    });
+
+    match context.format {
+        Format::Json => print_json(&tags),
+        _ => {
+            for tag in tags {
+                println!("   {} {}", describe(tag), details(tag));
+            }
+        }
+    }
+}
+
+// Tags are listed as `readtags` lists them, as lines of a tags file; tags that wouldn't read
+// back unchanged are reported rather than listed
+fn find(context: &Context, tags: &[&CtagItem]) {
+    match context.format {
+        Format::Json => print_json(tags),
+        _ => {
+            let mut writer = TagsWriter::new(io::stdout().lock());
+            for tag in tags {
+                match writer.write(tag) {
+                    Ok(()) => {}
+                    Err(e @ TagsWriteError::Invalid { .. }) => {
+                        eprintln!("{}", e.to_string().yellow())
+                    }
+                    Err(TagsWriteError::Io(_)) => break,
+                }
+            }
+        }
+    }
+}
+
+fn validate(
+    context: &Context,
+    tags_file: &TagsFile,
+    warnings: &[ParseWarning],
+    dangling: &DanglingTags,
+) {
+    let problems = [
+        ("Missing files", &dangling.missing_files),
+        ("Unresolved addresses", &dangling.unresolved),
+        ("Duplicate entries", &dangling.duplicates),
+    ];
+
+    match context.format {
+        Format::Json => print_json(&json!({
+            "path": tags_file.path,
+            "entries": tags_file.tags.len(),
+            "malformed": warnings
+                .iter()
+                .map(|w| json!({ "line": w.line, "content": w.content }))
+                .collect::<Vec<_>>(),
+            "missing_files": dangling.missing_files,
+            "unresolved": dangling.unresolved,
+            "duplicates": dangling.duplicates,
+        })),
+        _ => {
+            println!("   Path: {}", tags_file.path.to_string_lossy().cyan());
+            println!("   Entries: {}", tags_file.tags.len().to_string().yellow());
+            println!(
+                "   Malformed lines: {}",
+                warnings.len().to_string().yellow()
+            );
+            for warning in warnings {
+                println!(
+                    "   {} {}",
+                    format!("{}:", warning.line).red(),
+                    warning.content.escape_debug()
+                );
+            }
+            for (label, tags) in problems {
+                println!("   {}: {}", label, tags.len().to_string().yellow());
+                for tag in tags {
+                    println!("   {} {}", describe(tag), details(tag));
+                }
+            }
+        }
+    }
+}
+
+fn diff(context: &Context, diff: &TagsDiff) {
+    match context.format {
+        Format::Json => print_json(diff),
+        _ => {
+            println!("   Added: {}", diff.added.len().to_string().yellow());
+            for tag in &diff.added {
+                println!("   {} {}", "+".green(), describe(tag));
+            }
+            println!("   Removed: {}", diff.removed.len().to_string().yellow());
+            for tag in &diff.removed {
+                println!("   {} {}", "-".red(), describe(tag));
+            }
+            println!("   Changed: {}", diff.changed.len().to_string().yellow());
+            for change in &diff.changed {
+                println!("   {} {}", "~".cyan(), describe(&change.new));
+                println!("     {} {}", "-".red(), details(&change.old));
+                println!("     {} {}", "+".green(), details(&change.new));
+            }
+        }
+    }
+}
+
+fn describe(tag: &CtagItem) -> String {
+    format!(
+        "{} ({:?}) {}",
+        tag.name,
+        tag.kind,
+        tag.file_path.to_string_lossy().cyan()
+    )
+}
+
+fn details(tag: &CtagItem) -> String {
+    tag.tags
+        .iter()
+        .filter(|(key, _)| key.as_ref() != "line")
+        .fold(tag.address.to_string(), |details, (key, value)| {
+            format!("{} {}:{}", details, key, value)
+        })
+}
+
+// A tags file when `source` names one, otherwise tags generated for a git revision
+fn load(source: &str, writes: Writes) -> Result<HashSet<CtagItem>, String> {
+    let path = Path::new(source);
+
+    if path.is_file() {
+        let contents = fs::read_to_string(path).map_err(|e| format!("{}: {}", source, e))?;
+        CtagItem::parse(path.to_path_buf(), &contents)
+            .map(|tags_file| tags_file.detect_languages(&DiskFileSystem).tags)
+            .map_err(|e| format!("{}: {}", source, e))
+    } else if is_revision(source) {
+        generate_at(source, writes)
+            .map_err(|e| format!("Unable to generate tags for {}: {}", source, e))
+    } else {
+        Err(format!(
+            "{} is neither a tags file nor a git revision",
+            source
+        ))
+    }
+}
+
+fn is_revision(source: &str) -> bool {
+    Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("{}^{{commit}}", source))
+        .output()
+        .is_ok_and(|o| o.status.success())
+}
+
+// Export the revision's files to a temporary directory and run ctags over them
+fn generate_at(revision: &str, writes: Writes) -> Result<HashSet<CtagItem>, String> {
+    let dir = std::env::temp_dir().join(format!(
+        "unused-tags-{}-{}",
+        process::id(),
+        revision
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>()
+    ));
+    writes.create_dir(&dir).map_err(|e| e.to_string())?;
+
+    let generated = export(revision, &dir).and_then(|_| ctags(&dir));
+    writes.remove(&dir).ok();
+
+    let contents = generated?;
+    CtagItem::parse(PathBuf::from(revision), &contents)
+        .map(|tags_file| tags_file.tags)
+        .map_err(|e| e.to_string())
+}
+
+fn export(revision: &str, dir: &Path) -> Result<(), String> {
+    let mut archive = Command::new("git")
+        .args(["archive", "--format=tar", revision])
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("unable to run git ({})", e))?;
+    let extracted = Command::new("tar")
+        .arg("-x")
+        .arg("-C")
+        .arg(dir)
+        .stdin(archive.stdout.take().map_or(Stdio::null(), Stdio::from))
+        .status()
+        .map_err(|e| format!("unable to run tar ({})", e))?;
+    let archived = archive.wait().map_err(|e| e.to_string())?;
+
+    if archived.success() && extracted.success() {
+        Ok(())
+    } else {
+        Err(String::from("unable to export files"))
+    }
+}
+
+fn ctags(dir: &Path) -> Result<String, String> {
+    let output = Command::new("ctags")
+        .args(["-R", "-f", "-", "--fields=+n", "."])
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("unable to run ctags ({})", e))?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).map_err(|e| e.to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
@@ -0,0 +1,103 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+// Narrower terminals wrap rather than truncate, as too little would be left to read
+const MIN_WIDTH: usize = 40;
+
+const ELLIPSIS: char = '…';
+
+/// Columns available to output written to STDOUT
+///
+/// `COLUMNS` takes precedence, so output piped to a pager can still be fit to the screen;
+/// otherwise the terminal is asked for its size, and output that isn't written to a terminal has
+/// no width.
+pub fn width() -> Option<usize> {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .or_else(|| {
+            io::stdout()
+                .is_terminal()
+                .then(crossterm::terminal::size)
+                .and_then(Result::ok)
+                .map(|(columns, _)| usize::from(columns))
+        })
+        .filter(|columns| *columns >= MIN_WIDTH)
+}
+
+/// Shorten text to at most `width` characters, ending it with an ellipsis when cut
+pub fn fit(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+
+    let mut fitted: String = text.chars().take(width.saturating_sub(1)).collect();
+    fitted.push(ELLIPSIS);
+    fitted
+}
+
+/// Shorten a path to at most `width` characters, keeping its file name and as many of the
+/// directories nearest it as fit, since those tell similar paths apart
+pub fn fit_path(path: &str, width: usize) -> String {
+    if path.chars().count() <= width {
+        return path.to_string();
+    }
+
+    let separator = if path.contains('\\') && !path.contains('/') {
+        '\\'
+    } else {
+        '/'
+    };
+    let mut kept = String::new();
+
+    for segment in path.rsplit(separator) {
+        let candidate = if kept.is_empty() {
+            segment.to_string()
+        } else {
+            format!("{}{}{}", segment, separator, kept)
+        };
+        // Leave room for the ellipsis and separator marking the directories left out
+        if candidate.chars().count() + 2 > width {
+            break;
+        }
+        kept = candidate;
+    }
+
+    if kept.is_empty() {
+        // Not even the file name fits, so its start is cut instead
+        let name = path.rsplit(separator).next().unwrap_or(path);
+        let skipped = name.chars().count() + 1 - width.max(1);
+        format!(
+            "{}{}",
+            ELLIPSIS,
+            name.chars().skip(skipped).collect::<String>()
+        )
+    } else {
+        format!("{}{}{}", ELLIPSIS, separator, kept)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_text_and_paths_to_a_width() {
+        assert_eq!(
+            fit("Only one occurrence exists", 40),
+            "Only one occurrence exists"
+        );
+        assert_eq!(fit("Only one occurrence exists", 10), "Only one …");
+
+        assert_eq!(fit_path("app/models/person.rb", 20), "app/models/person.rb");
+        assert_eq!(
+            fit_path("app/models/concerns/person.rb", 20),
+            "…/concerns/person.rb"
+        );
+        assert_eq!(
+            fit_path("app\\models\\concerns\\person.rb", 12),
+            "…\\person.rb"
+        );
+        assert_eq!(fit_path("app/models/a_very_long_name.rb", 10), "…g_name.rb");
+    }
+}
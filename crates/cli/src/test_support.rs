@@ -0,0 +1,7 @@
+pub use token_analysis::test_util::TokenUsageBuilder;
+use token_analysis::TokenUsage;
+
+/// The usage of `token`, defined once at line 1 of `path` and occurring only there
+pub fn usage(token: &str, path: &str) -> TokenUsage {
+    TokenUsageBuilder::new(token, path).build()
+}
@@ -0,0 +1,136 @@
+use serde::Serialize;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use token_search::ScanTimings;
+
+// Directories reported as scan hotspots
+const HOTSPOTS: usize = 10;
+
+/// Wall-clock time spent in each phase of a run, and the directories searching spent the longest
+/// in, reported with --timings
+#[derive(Debug)]
+pub struct Timings {
+    started: Instant,
+    phases: RefCell<Vec<(&'static str, Duration)>>,
+    scan: Arc<ScanTimings>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimingsReport {
+    /// Seconds from loading tags until the report was rendered
+    pub total_seconds: f64,
+    /// Each phase timed, in the order it ran
+    pub phases: Vec<PhaseTiming>,
+    /// Directories searching spent the longest in, slowest first
+    pub hotspots: Vec<Hotspot>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PhaseTiming {
+    pub phase: &'static str,
+    pub seconds: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Hotspot {
+    /// Directory containing the files searched, not counting its subdirectories
+    pub directory: PathBuf,
+    pub files: usize,
+    pub bytes: u64,
+    /// Time spent reading and searching the directory's files, summed across threads
+    pub seconds: f64,
+}
+
+impl Timings {
+    pub fn start() -> Self {
+        Timings {
+            started: Instant::now(),
+            phases: RefCell::new(vec![]),
+            scan: Arc::new(ScanTimings::default()),
+        }
+    }
+
+    /// Time since the run started
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Where searching totals the time spent in each directory
+    pub fn scan(&self) -> Arc<ScanTimings> {
+        self.scan.clone()
+    }
+
+    pub fn record(&self, phase: &'static str, duration: Duration) {
+        self.phases.borrow_mut().push((phase, duration));
+    }
+
+    /// Run `f`, recording how long it took as `phase`
+    pub fn time<T>(&self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = f();
+        self.record(phase, started.elapsed());
+        result
+    }
+
+    pub fn report(&self) -> TimingsReport {
+        TimingsReport {
+            total_seconds: self.elapsed().as_secs_f64(),
+            phases: self
+                .phases
+                .borrow()
+                .iter()
+                .map(|(phase, duration)| PhaseTiming {
+                    phase,
+                    seconds: duration.as_secs_f64(),
+                })
+                .collect(),
+            hotspots: self
+                .scan
+                .hotspots(HOTSPOTS)
+                .into_iter()
+                .map(|(directory, timing)| Hotspot {
+                    directory,
+                    files: timing.files,
+                    bytes: timing.bytes,
+                    seconds: timing.duration.as_secs_f64(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn reports_phases_in_order_with_hotspots() {
+        let timings = Timings::start();
+        timings.record("tags_read", Duration::from_millis(2));
+        let scanned = timings.time("scan", || 42);
+        timings
+            .scan()
+            .record(Path::new("vendor/blob.js"), 512, Duration::from_millis(9));
+        timings
+            .scan()
+            .record(Path::new("app/person.rb"), 64, Duration::from_millis(1));
+
+        let report = timings.report();
+
+        assert_eq!(scanned, 42);
+        let phases: Vec<_> = report.phases.iter().map(|p| p.phase).collect();
+        assert_eq!(phases, vec!["tags_read", "scan"]);
+        assert_eq!(report.phases[0].seconds, 0.002);
+        assert!(report.total_seconds >= report.phases[1].seconds);
+        assert_eq!(report.hotspots[0].directory, PathBuf::from("vendor"));
+        assert_eq!(report.hotspots[0].bytes, 512);
+        assert_eq!(report.hotspots.len(), 2);
+        assert_eq!(
+            serde_json::to_value(&report.hotspots[1]).unwrap(),
+            serde_json::json!({"directory": "app", "files": 1, "bytes": 64, "seconds": 0.001})
+        );
+    }
+}
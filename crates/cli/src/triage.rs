@@ -0,0 +1,333 @@
+use super::cli_configuration::CliConfiguration;
+use super::formatters::internal::configuration_warnings;
+use super::sources::Sources;
+use colored::*;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use itertools::Itertools;
+use project_configuration::{IgnoreList, ProjectSettings};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use token_analysis::TokenUsage;
+
+// Lines of the definition site shown above and below it in the preview
+const PREVIEW_CONTEXT: usize = 8;
+
+const HELP: &str =
+    "↑/↓ move  i ignore token  f ignore file  u undo  e open in $EDITOR  q save & quit  esc discard";
+
+struct Finding<'a> {
+    token: &'a str,
+    likelihood: String,
+    reason: &'a str,
+    path: &'a Path,
+    line: Option<usize>,
+}
+
+impl<'a> Finding<'a> {
+    fn new(usage: &'a TokenUsage, sources: &Sources) -> Self {
+        let definition = usage
+            .result
+            .token
+            .definitions
+            .iter()
+            .sorted_by_key(|d| (&d.file_path, &d.address))
+            .next();
+
+        Finding {
+            token: &usage.result.token.token,
+            likelihood: usage.usage_likelihood.status.to_string(),
+            reason: &usage.usage_likelihood.reason,
+            path: definition
+                .map(|d| &*d.file_path)
+                .unwrap_or_else(|| usage.result.token.first_path()),
+            line: definition.and_then(|d| sources.position(d).0),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Action {
+    Continue,
+    Edit(PathBuf, Option<usize>),
+    Save,
+    Discard,
+}
+
+/// Findings being triaged, with the ignores accepted so far
+struct Triage<'a> {
+    findings: Vec<Finding<'a>>,
+    selected: usize,
+    ignores: IgnoreList,
+}
+
+impl<'a> Triage<'a> {
+    fn new(findings: Vec<Finding<'a>>) -> Self {
+        Triage {
+            findings,
+            selected: 0,
+            ignores: IgnoreList::default(),
+        }
+    }
+
+    fn handle(&mut self, code: KeyCode) -> Action {
+        let finding = match self.findings.get(self.selected) {
+            Some(finding) => finding,
+            None => {
+                return match code {
+                    KeyCode::Char('q') => Action::Save,
+                    KeyCode::Esc => Action::Discard,
+                    _ => Action::Continue,
+                }
+            }
+        };
+        let token = finding.token.to_string();
+        let path = finding.path.to_string_lossy().to_string();
+
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.findings.len() - 1)
+            }
+            KeyCode::Char('i') => add(&mut self.ignores.tokens, token),
+            KeyCode::Char('f') => add(&mut self.ignores.paths, path),
+            KeyCode::Char('u') => {
+                self.ignores.tokens.retain(|t| *t != token);
+                self.ignores.paths.retain(|p| *p != path);
+            }
+            KeyCode::Char('e') => return Action::Edit(finding.path.to_path_buf(), finding.line),
+            KeyCode::Char('q') => return Action::Save,
+            KeyCode::Esc => return Action::Discard,
+            _ => {}
+        }
+
+        Action::Continue
+    }
+
+    fn ignored(&self, finding: &Finding) -> bool {
+        self.ignores.tokens.iter().any(|t| t == finding.token)
+            || self
+                .ignores
+                .paths
+                .iter()
+                .any(|p| Path::new(p) == finding.path)
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, help] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [list, preview] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(main);
+
+        let items: Vec<ListItem> = self
+            .findings
+            .iter()
+            .map(|f| {
+                let item = ListItem::new(Line::from(vec![
+                    Span::raw(f.token),
+                    Span::styled(format!(" ({})", f.likelihood), dimmed()),
+                ]));
+                if self.ignored(f) {
+                    item.style(dimmed().add_modifier(Modifier::CROSSED_OUT))
+                } else {
+                    item
+                }
+            })
+            .collect();
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::bordered().title(format!(" Findings ({}) ", self.findings.len())))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            list,
+            &mut state,
+        );
+
+        let (title, lines) = match self.findings.get(self.selected) {
+            Some(finding) => (
+                format!(" {} ", finding.path.display()),
+                preview_lines(finding),
+            ),
+            None => (String::new(), vec![Line::from("No findings to triage")]),
+        };
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            preview,
+        );
+        frame.render_widget(Paragraph::new(HELP).style(dimmed()), help);
+    }
+}
+
+pub fn run(cli_config: &CliConfiguration) {
+    if !io::stdout().is_terminal() {
+        eprintln!("--interactive requires a terminal");
+        return;
+    }
+
+    let findings = cli_config
+        .for_json()
+        .into_iter()
+        .map(|usage| Finding::new(usage, cli_config.sources()))
+        .collect();
+    let mut triage = Triage::new(findings);
+    let mut terminal = ratatui::init();
+    let outcome = event_loop(&mut terminal, &mut triage);
+    ratatui::restore();
+
+    match outcome {
+        Ok(Action::Save)
+            if !triage.ignores.tokens.is_empty() || !triage.ignores.paths.is_empty() =>
+        {
+            match ProjectSettings::add_ignores(Path::new("."), &triage.ignores, cli_config.writes())
+            {
+                Ok(path) => println!(
+                    "Added {} tokens and {} paths to ignore in {}",
+                    triage.ignores.tokens.len().to_string().yellow(),
+                    triage.ignores.paths.len().to_string().yellow(),
+                    path.display().to_string().cyan()
+                ),
+                Err(e) => eprintln!("Unable to save ignores: {}", e),
+            }
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Interactive mode failed: {}", e),
+    }
+
+    configuration_warnings(cli_config);
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, triage: &mut Triage) -> io::Result<Action> {
+    loop {
+        terminal.draw(|frame| triage.draw(frame))?;
+
+        let code = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key.code,
+            _ => continue,
+        };
+
+        match triage.handle(code) {
+            Action::Continue => {}
+            Action::Edit(path, line) => {
+                ratatui::restore();
+                open_in_editor(&path, line);
+                *terminal = ratatui::init();
+            }
+            action => return Ok(action),
+        }
+    }
+}
+
+fn open_in_editor(path: &Path, line: Option<usize>) {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| String::from("vi"));
+    let mut command = Command::new(editor);
+
+    if let Some(line) = line {
+        command.arg(format!("+{}", line));
+    }
+    command.arg(path).status().ok();
+}
+
+fn preview_lines<'a>(finding: &Finding) -> Vec<Line<'a>> {
+    let contents = match fs::read_to_string(finding.path) {
+        Ok(contents) => contents,
+        Err(e) => return vec![Line::from(e.to_string())],
+    };
+    let target = finding.line.unwrap_or(1);
+    let first = target.saturating_sub(PREVIEW_CONTEXT).max(1);
+
+    let mut lines = vec![
+        Line::styled(
+            finding.reason.to_string(),
+            Style::new().add_modifier(Modifier::ITALIC),
+        ),
+        Line::from(""),
+    ];
+    lines.extend(
+        contents
+            .lines()
+            .enumerate()
+            .skip(first - 1)
+            .take(PREVIEW_CONTEXT * 2 + 1)
+            .map(|(index, text)| {
+                let line = Line::from(format!("{:>5} {}", index + 1, text));
+                if Some(index + 1) == finding.line {
+                    line.style(Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+                } else {
+                    line
+                }
+            }),
+    );
+    lines
+}
+
+fn dimmed() -> Style {
+    Style::new().fg(Color::DarkGray)
+}
+
+fn add(values: &mut Vec<String>, value: String) {
+    if !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding<'a>(token: &'a str, path: &'a str) -> Finding<'a> {
+        Finding {
+            token,
+            likelihood: String::from("high"),
+            reason: "Only one occurrence exists",
+            path: Path::new(path),
+            line: Some(3),
+        }
+    }
+
+    #[test]
+    fn accepts_and_undoes_ignores() {
+        let mut triage = Triage::new(vec![
+            finding("name", "app/models/person.rb"),
+            finding("total", "app/models/order.rb"),
+        ]);
+
+        assert_eq!(triage.handle(KeyCode::Char('i')), Action::Continue);
+        assert_eq!(triage.handle(KeyCode::Down), Action::Continue);
+        assert_eq!(triage.handle(KeyCode::Down), Action::Continue);
+        assert_eq!(triage.selected, 1);
+        triage.handle(KeyCode::Char('f'));
+        triage.handle(KeyCode::Char('f'));
+
+        assert_eq!(triage.ignores.tokens, vec![String::from("name")]);
+        assert_eq!(
+            triage.ignores.paths,
+            vec![String::from("app/models/order.rb")]
+        );
+        assert!(triage.ignored(&triage.findings[1]));
+
+        triage.handle(KeyCode::Char('u'));
+        assert!(triage.ignores.paths.is_empty());
+        assert_eq!(
+            triage.handle(KeyCode::Char('e')),
+            Action::Edit(PathBuf::from("app/models/order.rb"), Some(3))
+        );
+        assert_eq!(triage.handle(KeyCode::Char('q')), Action::Save);
+    }
+
+    #[test]
+    fn quits_when_there_is_nothing_to_triage() {
+        let mut triage = Triage::new(vec![]);
+
+        assert_eq!(triage.handle(KeyCode::Down), Action::Continue);
+        assert_eq!(triage.handle(KeyCode::Esc), Action::Discard);
+    }
+}
@@ -0,0 +1,251 @@
+use super::cli_configuration::CliConfiguration;
+use super::formatters::internal::{configuration_warnings, print_json};
+use super::Format;
+use colored::*;
+use itertools::Itertools;
+use read_ctags::{CtagItem, Language};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use token_analysis::TokenUsage;
+use token_search::ImportGraph;
+
+// Scopes whose items can't be narrowed to `pub(crate)`: trait items share the trait's visibility
+// and items nested in functions aren't reachable from other crates
+const FIXED_VISIBILITY_SCOPES: [&str; 3] = ["interface", "function", "method"];
+
+/// A `pub` Rust item only referenced within its own crate, and a candidate for `pub(crate)`
+#[derive(Serialize)]
+struct Candidate<'a> {
+    token: &'a str,
+    path: &'a Path,
+    address: &'a str,
+    #[serde(rename = "crate")]
+    crate_name: String,
+}
+
+pub fn render(cli_config: &CliConfiguration) {
+    let usages = cli_config.included();
+    let graph = ImportGraph::build(cli_config.searched_files());
+    let crates = CrateRoots::default();
+    let crate_of = |path: &Path| crates.crate_of(path);
+    let workspace_crates = workspace_crates(&usages, &crate_of);
+    let candidates = if workspace_crates.len() > 1 {
+        candidates(&usages, &graph, &crate_of)
+    } else {
+        vec![]
+    };
+
+    match cli_config.format() {
+        Format::Json => print_json(&serde_json::json!({ "crates": workspace_crates, "candidates": candidates })
+        ),
+        _ if workspace_crates.len() < 2 => println!(
+            "No Rust workspace with more than one crate was found; pub items may be used by other projects"
+        ),
+        _ => {
+            println!("{}", "== PUB ITEMS ONLY USED WITHIN THEIR CRATE ==".white());
+            for (crate_name, items) in candidates
+                .iter()
+                .group_by(|c| c.crate_name.as_str())
+                .into_iter()
+            {
+                println!("   {}", crate_name.yellow());
+                for c in items {
+                    println!(
+                        "   * {} {}",
+                        c.token.cyan(),
+                        c.path.to_string_lossy().dimmed()
+                    );
+                }
+            }
+            println!();
+            println!(
+                "   {} candidates for pub(crate) across {} crates",
+                candidates.len(),
+                workspace_crates.len()
+            );
+        }
+    }
+
+    configuration_warnings(cli_config);
+}
+
+fn workspace_crates<F>(usages: &[&TokenUsage], crate_of: &F) -> BTreeSet<String>
+where
+    F: Fn(&Path) -> Option<String>,
+{
+    usages
+        .iter()
+        .flat_map(|u| u.result.token.definitions.iter())
+        .filter(|d| d.language == Some(Language::Rust))
+        .filter_map(|d| crate_of(&d.file_path))
+        .collect()
+}
+
+fn candidates<'a, F>(
+    usages: &[&'a TokenUsage],
+    graph: &ImportGraph,
+    crate_of: &F,
+) -> Vec<Candidate<'a>>
+where
+    F: Fn(&Path) -> Option<String>,
+{
+    let mut candidates: Vec<Candidate> = usages
+        .iter()
+        .flat_map(|usage| {
+            let token = &usage.result.token.token;
+
+            usage
+                .result
+                .token
+                .definitions
+                .iter()
+                .filter(|d| is_narrowable_pub(d))
+                .filter_map(move |definition| {
+                    let crate_name = crate_of(&definition.file_path)?;
+                    let used_elsewhere = usage.result.occurrences.keys().any(|path| {
+                        Language::from_path(path) == Some(Language::Rust)
+                            && crate_of(path).is_some_and(|c| c != crate_name)
+                            && graph.plausibly_imports(path, &definition.file_path, token)
+                    });
+
+                    if used_elsewhere {
+                        None
+                    } else {
+                        Some(Candidate {
+                            token,
+                            path: &definition.file_path,
+                            address: &definition.address,
+                            crate_name,
+                        })
+                    }
+                })
+        })
+        .collect();
+
+    candidates
+        .sort_by(|a, b| (&a.crate_name, a.path, a.token).cmp(&(&b.crate_name, b.path, b.token)));
+    candidates
+}
+
+// Visibility comes from the `access` field when ctags provides it, or else the definition's
+// search pattern; tags addressed by line number can't be checked
+fn is_narrowable_pub(definition: &CtagItem) -> bool {
+    if definition.language != Some(Language::Rust)
+        || FIXED_VISIBILITY_SCOPES
+            .iter()
+            .any(|scope| definition.tags.contains_key(*scope))
+    {
+        return false;
+    }
+
+    match definition.tags.get("access") {
+        Some(access) => access.as_ref() == "public",
+        None => definition
+            .address
+            .strip_prefix("/^")
+            .is_some_and(|pattern| pattern.trim_start().starts_with("pub ")),
+    }
+}
+
+/// Crates containing each path, found from the nearest `Cargo.toml` with a `[package]` section
+#[derive(Default)]
+struct CrateRoots {
+    names: RefCell<HashMap<PathBuf, Option<String>>>,
+}
+
+impl CrateRoots {
+    fn crate_of(&self, path: &Path) -> Option<String> {
+        path.ancestors()
+            .skip(1)
+            .find_map(|dir| self.package_name(dir))
+    }
+
+    fn package_name(&self, dir: &Path) -> Option<String> {
+        self.names
+            .borrow_mut()
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                fs::read_to_string(dir.join("Cargo.toml"))
+                    .ok()
+                    .and_then(|contents| package_name(&contents))
+            })
+            .clone()
+    }
+}
+
+fn package_name(manifest: &str) -> Option<String> {
+    let value = manifest.parse::<toml::Value>().ok()?;
+
+    value
+        .get("package")?
+        .get("name")?
+        .as_str()
+        .map(|name| name.replace('-', "_"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TokenUsageBuilder;
+    use read_ctags::TokenKind;
+
+    #[test]
+    fn flags_pub_items_only_used_within_their_crate() {
+        let function = |token, path| {
+            TokenUsageBuilder::new(token, path)
+                .with_kind(TokenKind::Function)
+                .with_language(Language::Rust)
+        };
+        let usages = [
+            function("parse", "crates/parser/src/lib.rs")
+                .with_address("/^pub fn parse(input: &str) {$/")
+                .with_occurrences(&[
+                    ("crates/parser/src/lib.rs", 1),
+                    ("crates/parser/src/ast.rs", 1),
+                ])
+                .build(),
+            function("render", "crates/parser/src/render.rs")
+                .with_address("/^pub fn render() {$/")
+                .with_occurrences(&[
+                    ("crates/parser/src/render.rs", 1),
+                    ("crates/cli/src/main.rs", 1),
+                ])
+                .build(),
+            function("helper", "crates/parser/src/lib.rs")
+                .with_address("/^pub(crate) fn helper() {$/")
+                .with_occurrences(&[("crates/parser/src/lib.rs", 1)])
+                .build(),
+        ];
+        let usages: Vec<&TokenUsage> = usages.iter().collect();
+        let crate_of = |path: &Path| {
+            path.strip_prefix("crates/")
+                .ok()
+                .and_then(|p| p.iter().next())
+                .map(|c| c.to_string_lossy().to_string())
+        };
+        let graph = ImportGraph::build(&[]);
+
+        let found: Vec<&str> = candidates(&usages, &graph, &crate_of)
+            .iter()
+            .map(|c| c.token)
+            .collect();
+
+        assert_eq!(found, vec!["parse"]);
+        assert_eq!(workspace_crates(&usages, &crate_of).len(), 1);
+    }
+
+    #[test]
+    fn reads_package_names_from_manifests() {
+        assert_eq!(
+            package_name("[package]\nname = \"read-ctags\"\nversion = \"0.1.0\"\n"),
+            Some(String::from("read_ctags"))
+        );
+        assert_eq!(
+            package_name("[workspace]\nmembers = [\"crates/*\"]\n"),
+            None
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use super::cli_configuration::CliConfiguration;
+use super::context::Context;
+use super::error_message;
+use super::exit_status::ExitStatus;
+use super::flags::AnalysisFlags;
+use colored::*;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Modification time and length of each watched file
+type Snapshot = HashMap<PathBuf, (Option<SystemTime>, u64)>;
+
+/// Render findings, then re-analyze whenever files change and print new and resolved findings
+///
+/// Changes are detected by polling. Per-file scan results are cached by content, so each
+/// re-analysis only rescans the files that changed (unless the tags file did).
+pub fn run(context: &Context, mut flags: AnalysisFlags) -> ExitStatus {
+    flags.scan_cache = true;
+
+    let cli_config = match super::build_configuration(context, flags.clone()) {
+        Ok(cli_config) => cli_config,
+        Err(e) => {
+            error_message::failed_token_parse(e);
+            return ExitStatus::Error;
+        }
+    };
+    cli_config.render();
+
+    flags.no_progress = true;
+    let mut findings = findings(&cli_config);
+    // The tags file is often ignored by git, so it's watched alongside the codebase's files
+    let tags_path = cli_config.tags_path().map(|path| {
+        std::env::current_dir()
+            .ok()
+            .and_then(|cwd| path.strip_prefix(cwd).ok().map(PathBuf::from))
+            .unwrap_or_else(|| path.clone())
+    });
+    let mut watched = snapshot(context, tags_path.as_ref());
+    println!(
+        "\n   Watching {} files for changes (Ctrl-C to stop)",
+        watched.len().to_string().yellow()
+    );
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let current = snapshot(context, tags_path.as_ref());
+        let changed = changed_paths(&watched, &current);
+        if changed.is_empty() {
+            continue;
+        }
+        watched = current;
+
+        match super::build_configuration(context, flags.clone()) {
+            Ok(cli_config) => {
+                let updated = self::findings(&cli_config);
+                print_delta(&changed, &findings, &updated);
+                findings = updated;
+            }
+            Err(e) => eprintln!("{}", format!("Unable to re-analyze: {}", e).red()),
+        }
+    }
+}
+
+// Summaries of reported findings, keyed by token
+fn findings(cli_config: &CliConfiguration) -> BTreeMap<String, String> {
+    cli_config
+        .for_json()
+        .into_iter()
+        .map(|usage| {
+            let likelihood = &usage.usage_likelihood;
+            (
+                usage.result.token.token.to_string(),
+                format!(
+                    "{} ({} likelihood, {}): {}",
+                    usage.result.token.first_path().to_string_lossy(),
+                    likelihood.status,
+                    likelihood.rule,
+                    likelihood.reason
+                ),
+            )
+        })
+        .collect()
+}
+
+fn snapshot(context: &Context, tags_path: Option<&PathBuf>) -> Snapshot {
+    context
+        .codebase_files()
+        .paths
+        .into_iter()
+        .chain(tags_path.cloned())
+        .map(|path| {
+            let metadata = fs::metadata(&path).ok();
+            let stamp = (
+                metadata.as_ref().and_then(|m| m.modified().ok()),
+                metadata.map(|m| m.len()).unwrap_or(0),
+            );
+            (path, stamp)
+        })
+        .collect()
+}
+
+fn changed_paths(previous: &Snapshot, current: &Snapshot) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = current
+        .iter()
+        .filter(|(path, stamp)| previous.get(*path) != Some(stamp))
+        .map(|(path, _)| path.clone())
+        .chain(
+            previous
+                .keys()
+                .filter(|path| !current.contains_key(*path))
+                .cloned(),
+        )
+        .collect();
+    changed.sort();
+    changed
+}
+
+fn print_delta(
+    changed: &[PathBuf],
+    previous: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) {
+    let (added, resolved) = delta(previous, current);
+
+    println!(
+        "\n   Re-analyzed after changes to {}",
+        match changed {
+            [path] => path.to_string_lossy().cyan().to_string(),
+            _ => format!("{} files", changed.len().to_string().yellow()),
+        }
+    );
+
+    if added.is_empty() && resolved.is_empty() {
+        println!("   No change in findings");
+    }
+    for (token, summary) in added {
+        println!("   {} {} {}", "+".red(), token.red(), summary);
+    }
+    for token in resolved {
+        println!("   {} {}", "-".green(), token.green());
+    }
+}
+
+// Findings that are new or have changed, and tokens no longer reported
+fn delta<'a>(
+    previous: &'a BTreeMap<String, String>,
+    current: &'a BTreeMap<String, String>,
+) -> (Vec<(&'a String, &'a String)>, Vec<&'a str>) {
+    let added = current
+        .iter()
+        .filter(|(token, summary)| previous.get(*token) != Some(summary))
+        .collect();
+    let resolved = previous
+        .keys()
+        .filter(|token| !current.contains_key(*token))
+        .map(String::as_str)
+        .collect();
+
+    (added, resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_new_and_resolved_findings() {
+        let summaries = |entries: &[(&str, &str)]| -> BTreeMap<String, String> {
+            entries
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let previous = summaries(&[("age", "low"), ("full_name", "high"), ("Report", "high")]);
+        let current = summaries(&[("age", "high"), ("full_name", "high"), ("total", "high")]);
+
+        let (added, resolved) = delta(&previous, &current);
+
+        assert_eq!(
+            added,
+            vec![
+                (&String::from("age"), &String::from("high")),
+                (&String::from("total"), &String::from("high")),
+            ]
+        );
+        assert_eq!(resolved, vec!["Report"]);
+    }
+
+    #[test]
+    fn detects_changed_files() {
+        let stamp = (Some(SystemTime::UNIX_EPOCH), 10);
+        let previous = Snapshot::from([
+            (PathBuf::from("app/person.rb"), stamp),
+            (PathBuf::from("app/order.rb"), stamp),
+            (PathBuf::from("app/report.rb"), stamp),
+        ]);
+        let current = Snapshot::from([
+            (PathBuf::from("app/person.rb"), stamp),
+            (PathBuf::from("app/order.rb"), (stamp.0, 12)),
+            (PathBuf::from("app/total.rb"), stamp),
+        ]);
+
+        assert_eq!(
+            changed_paths(&previous, &current),
+            vec![
+                PathBuf::from("app/order.rb"),
+                PathBuf::from("app/report.rb"),
+                PathBuf::from("app/total.rb"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use super::cli_configuration::CliConfiguration;
+use super::context::Context;
+use super::exit_status::ExitStatus;
+use super::flags::{AnalysisFlags, Format};
+use super::formatters;
+use super::messages::{fill, text, Message};
+use colored::*;
+use serde_json::{json, Map};
+use std::io::{self, Write};
+
+/// Analyze each sub-project declared under `projects` and render a combined report
+///
+/// Each sub-project is analyzed with its own tags file, languages, and ignores, confined to its
+/// root. Reports are rendered to STDOUT, in sections for standard and compact output or as a
+/// single JSON document keyed by project.
+pub fn run(context: &Context, flags: AnalysisFlags) -> ExitStatus {
+    if let Some(e) = unsupported_output(context) {
+        eprintln!("{}", e.red());
+        return ExitStatus::Error;
+    }
+
+    let mut status = ExitStatus::Clean;
+    let mut reports = vec![];
+
+    for name in context.project.sub_projects() {
+        let sub_context = context.for_sub_project(name);
+        tracing::info!("Analyzing sub-project {}", name);
+
+        match super::build_configuration(&sub_context, flags.clone()) {
+            // Each sub-project's baseline is kept at its root
+            Ok(cli_config) if flags.generate_baseline => {
+                cli_config.write_baseline();
+                status = worst(status, cli_config.exit_status());
+            }
+            Ok(cli_config) => {
+                status = worst(status, cli_config.exit_status());
+                reports.push((name, sub_context, cli_config));
+            }
+            Err(e) => {
+                eprintln!("{}", format!("Unable to analyze {}: {}", name, e).red());
+                status = ExitStatus::Error;
+            }
+        }
+    }
+
+    if flags.generate_baseline {
+        return status;
+    }
+
+    let rendered = match context.format {
+        Format::Json => {
+            let projects: Map<String, serde_json::Value> = reports
+                .iter()
+                .map(|(name, _, cli_config)| {
+                    (name.to_string(), formatters::json::report(cli_config))
+                })
+                .collect();
+            writeln!(io::stdout(), "{}", json!({ "projects": projects }))
+        }
+        format => render_sections(format, &reports),
+    };
+
+    for (_, _, cli_config) in &reports {
+        formatters::internal::configuration_warnings(cli_config);
+    }
+
+    match rendered {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+            eprintln!("{}", format!("Unable to write output: {}", e).red());
+            ExitStatus::Error
+        }
+        _ => status,
+    }
+}
+
+fn unsupported_output(context: &Context) -> Option<String> {
+    if context.outputs.iter().any(|t| t.path.is_some()) {
+        Some(String::from(
+            "Sub-project reports can't be written to files; redirect STDOUT instead",
+        ))
+    } else if !matches!(
+        context.format,
+        Format::Standard | Format::Compact | Format::Json
+    ) {
+        Some(String::from(
+            "Sub-project reports support standard, compact, and JSON output",
+        ))
+    } else {
+        None
+    }
+}
+
+fn render_sections(
+    format: Format,
+    reports: &[(&str, Context, CliConfiguration)],
+) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+
+    for (name, sub_context, cli_config) in reports {
+        let root = sub_context
+            .sub_project
+            .as_ref()
+            .map(|p| p.root.to_string_lossy().to_string())
+            .unwrap_or_default();
+        writeln!(
+            out,
+            "{}",
+            fill(Message::ProjectHeading, &[&name.cyan(), &root])
+        )?;
+        cli_config.render_format(format, &mut out)?;
+        writeln!(out)?;
+    }
+
+    if matches!(format, Format::Standard) {
+        writeln!(out, "{}", text(Message::WorkspaceSummaryHeading))?;
+        for (name, _, cli_config) in reports {
+            writeln!(
+                out,
+                "   {}",
+                fill(
+                    Message::ProjectFindings,
+                    &[name, &cli_config.for_json().len().to_string().yellow()]
+                )
+            )?;
+        }
+        let total: usize = reports
+            .iter()
+            .map(|(_, _, cli_config)| cli_config.for_json().len())
+            .sum();
+        writeln!(
+            out,
+            "   {}",
+            fill(Message::TotalFindings, &[&total.to_string().yellow()])
+        )?;
+    }
+
+    Ok(())
+}
+
+// The most severe status across sub-projects
+fn worst(a: ExitStatus, b: ExitStatus) -> ExitStatus {
+    if b.code() > a.code() {
+        b
+    } else {
+        a
+    }
+}
@@ -0,0 +1,119 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where GitHub and GitLab look for a CODEOWNERS file, in the order they look
+pub const CODEOWNERS_PATHS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// Owners of paths, read from a CODEOWNERS file
+///
+/// Each line pairs a gitignore-style pattern with the owners of the paths it matches, and the
+/// last line matching a path decides its owners, as on GitHub. A pattern listed without owners
+/// leaves the paths it matches unowned. GitLab's `[Section]` headers are skipped, so rules from
+/// every section apply.
+#[derive(Clone, Debug, Default)]
+pub struct CodeOwners {
+    rules: Vec<(Gitignore, Vec<String>)>,
+}
+
+impl CodeOwners {
+    /// Read the first CODEOWNERS file found in the current directory, if there is one
+    pub fn find() -> Result<Option<(PathBuf, Self)>, String> {
+        let path = match CODEOWNERS_PATHS
+            .iter()
+            .map(PathBuf::from)
+            .find(|p| p.is_file())
+        {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+
+        Self::load(&path).map(|owners| Some((path, owners)))
+    }
+
+    /// Read the CODEOWNERS file at `path`
+    pub fn load(path: &Path) -> Result<Self, String> {
+        fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| Self::parse(&contents))
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Parse the contents of a CODEOWNERS file, failing on the first invalid pattern
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut rules = vec![];
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || is_section_header(line) {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let pattern = fields.next().unwrap_or_default();
+            let owners = fields
+                .take_while(|field| !field.starts_with('#'))
+                .map(String::from)
+                .collect();
+            let mut builder = GitignoreBuilder::new("");
+            builder
+                .add_line(None, pattern)
+                .and_then(|builder| builder.build())
+                .map(|matcher| rules.push((matcher, owners)))
+                .map_err(|e| format!("line {}: {}", index + 1, e))?;
+        }
+
+        Ok(CodeOwners { rules })
+    }
+
+    /// Are there no rules, as when no CODEOWNERS file was found?
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Owners of the file at `path`, relative to the current directory; empty when it's unowned
+    pub fn owners(&self, path: &Path) -> &[String] {
+        let path = path.strip_prefix("./").unwrap_or(path);
+
+        self.rules
+            .iter()
+            .rev()
+            .find(|(matcher, _)| {
+                !path.has_root() && matcher.matched_path_or_any_parents(path, false).is_ignore()
+            })
+            .map_or(&[], |(_, owners)| owners.as_slice())
+    }
+}
+
+// GitLab groups rules under `[Section]` or `^[Optional section][2]` headers
+fn is_section_header(line: &str) -> bool {
+    line.trim_start_matches('^').starts_with('[')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_matching_rule_decides_owners() {
+        let owners = CodeOwners::parse(
+            "# Owners\n* @org/everyone\n/app/models/ @org/data @alice\n*.js @org/frontend # scripts\n[Docs]\ndocs/ @bob\n/app/models/legacy.rb\n",
+        )
+        .unwrap();
+
+        assert_eq!(owners.owners(Path::new("Gemfile")), ["@org/everyone"]);
+        assert_eq!(
+            owners.owners(Path::new("./app/models/person.rb")),
+            ["@org/data", "@alice"]
+        );
+        assert_eq!(
+            owners.owners(Path::new("app/models/view.js")),
+            ["@org/frontend"]
+        );
+        assert_eq!(owners.owners(Path::new("guides/docs/intro.md")), ["@bob"]);
+        assert!(owners.owners(Path::new("app/models/legacy.rb")).is_empty());
+        assert!(CodeOwners::default()
+            .owners(Path::new("Gemfile"))
+            .is_empty());
+    }
+}
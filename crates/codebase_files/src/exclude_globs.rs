@@ -0,0 +1,101 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::Path;
+
+/// Paths excluded with gitignore-style globs (such as `spec/**` or `*.generated.ts`), relative
+/// to the current directory
+#[derive(Clone, Debug)]
+pub struct ExcludeGlobs(Gitignore);
+
+impl Default for ExcludeGlobs {
+    fn default() -> Self {
+        ExcludeGlobs(Gitignore::empty())
+    }
+}
+
+impl ExcludeGlobs {
+    /// Compile `patterns`, failing on the first invalid glob
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self, String> {
+        let mut builder = GitignoreBuilder::new("");
+
+        for pattern in patterns {
+            builder
+                .add_line(None, pattern.as_ref())
+                .map_err(|e| e.to_string())?;
+        }
+
+        builder.build().map(ExcludeGlobs).map_err(|e| e.to_string())
+    }
+
+    /// Check that `pattern` is a valid glob
+    pub fn validate(pattern: String) -> Result<(), String> {
+        Self::new(&[pattern]).map(|_| ())
+    }
+
+    /// Does a pattern match `path` or any of its parent directories?
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let path = path.strip_prefix("./").unwrap_or(path);
+
+        !path.has_root() && self.0.matched_path_or_any_parents(path, false).is_ignore()
+    }
+}
+
+/// Paths selected with gitignore-style globs: those matching an include glob (or every path,
+/// when none are given) and no exclude glob
+#[derive(Clone, Debug, Default)]
+pub struct PathGlobs {
+    include: Option<ExcludeGlobs>,
+    exclude: ExcludeGlobs,
+}
+
+impl PathGlobs {
+    /// Compile `include` and `exclude`, failing on the first invalid glob
+    pub fn new<S: AsRef<str>>(include: &[S], exclude: &[S]) -> Result<Self, String> {
+        Ok(PathGlobs {
+            include: match include {
+                [] => None,
+                include => Some(ExcludeGlobs::new(include)?),
+            },
+            exclude: ExcludeGlobs::new(exclude)?,
+        })
+    }
+
+    /// Is `path` selected?
+    pub fn includes(&self, path: &Path) -> bool {
+        self.include
+            .as_ref()
+            .is_none_or(|include| include.is_excluded(path))
+            && !self.exclude.is_excluded(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn excludes_paths_matching_globs() {
+        let globs =
+            ExcludeGlobs::new(&["spec/**", "*.generated.ts", "!keep.generated.ts"]).unwrap();
+
+        assert!(globs.is_excluded(Path::new("spec/models/person_spec.rb")));
+        assert!(globs.is_excluded(Path::new("./spec/models/person_spec.rb")));
+        assert!(globs.is_excluded(Path::new("web/src/api.generated.ts")));
+        assert!(!globs.is_excluded(Path::new("web/src/keep.generated.ts")));
+        assert!(!globs.is_excluded(Path::new("app/models/spec.rb")));
+        assert!(!ExcludeGlobs::default().is_excluded(Path::new("spec/person_spec.rb")));
+        assert!(ExcludeGlobs::validate(String::from("spec/{a,b")).is_err());
+    }
+
+    #[test]
+    fn includes_paths_matching_globs() {
+        let globs = PathGlobs::new(&["app/**", "lib/**"], &["app/generated/**"]).unwrap();
+
+        assert!(globs.includes(Path::new("app/models/person.rb")));
+        assert!(!globs.includes(Path::new("app/generated/schema.rb")));
+        assert!(!globs.includes(Path::new("db/schema.rb")));
+        assert!(PathGlobs::default().includes(Path::new("db/schema.rb")));
+        assert!(!PathGlobs::new(&[], &["db/schema.rb"])
+            .unwrap()
+            .includes(Path::new("db/schema.rb")));
+    }
+}
@@ -0,0 +1,101 @@
+use super::text;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::SystemTime;
+
+/// Where files are read from, so reading tags and searching files can happen without a disk
+/// (such as in tests, or when embedding unused with files held in memory)
+pub trait FileSystem: Send + Sync {
+    /// Read the file at `path` as text
+    fn read(&self, path: &Path) -> io::Result<String>;
+
+    /// Whether a file exists at `path`
+    fn exists(&self, path: &Path) -> bool;
+
+    /// When the file at `path` was last modified
+    fn modified(&self, path: &Path) -> io::Result<SystemTime>;
+
+    /// Size of the file at `path`, in bytes
+    fn size(&self, path: &Path) -> io::Result<u64>;
+}
+
+/// Files on disk, read with `text::read`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DiskFileSystem;
+
+impl FileSystem for DiskFileSystem {
+    fn read(&self, path: &Path) -> io::Result<String> {
+        text::read(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn modified(&self, path: &Path) -> io::Result<SystemTime> {
+        fs::metadata(path)?.modified()
+    }
+
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        fs::metadata(path).map(|metadata| metadata.len())
+    }
+}
+
+/// Files held in memory, each modified when it was last inserted
+#[derive(Debug, Default)]
+pub struct MemoryFileSystem {
+    files: RwLock<HashMap<PathBuf, (String, SystemTime)>>,
+}
+
+impl MemoryFileSystem {
+    /// Add (or replace) the file at `path`
+    pub fn insert<P: Into<PathBuf>, C: Into<String>>(&self, path: P, contents: C) {
+        self.files
+            .write()
+            .unwrap()
+            .insert(path.into(), (contents.into(), SystemTime::now()));
+    }
+
+    fn missing(path: &Path) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} not found in memory", path.display()),
+        )
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read(&self, path: &Path) -> io::Result<String> {
+        self.files
+            .read()
+            .unwrap()
+            .get(path)
+            .map(|(contents, _)| contents.clone())
+            .ok_or_else(|| Self::missing(path))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.read().unwrap().contains_key(path)
+    }
+
+    fn modified(&self, path: &Path) -> io::Result<SystemTime> {
+        self.files
+            .read()
+            .unwrap()
+            .get(path)
+            .map(|(_, modified)| *modified)
+            .ok_or_else(|| Self::missing(path))
+    }
+
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        self.files
+            .read()
+            .unwrap()
+            .get(path)
+            .map(|(contents, _)| contents.len() as u64)
+            .ok_or_else(|| Self::missing(path))
+    }
+}
@@ -1,5 +1,31 @@
-use std::path::PathBuf;
-use std::process::{Command, Output};
+mod code_owners;
+mod exclude_globs;
+mod file_system;
+pub mod paths;
+pub mod text;
+pub mod writes;
+
+pub use code_owners::{CodeOwners, CODEOWNERS_PATHS};
+pub use exclude_globs::{ExcludeGlobs, PathGlobs};
+pub use file_system::{DiskFileSystem, FileSystem, MemoryFileSystem};
+use ignore::{DirEntry, WalkBuilder, WalkState};
+use std::collections::btree_map::{BTreeMap, Entry};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+const SKIPPED_DIRECTORIES: [&str; 4] = [".git", ".hg", ".svn", "node_modules"];
+
+/// Whether a walk follows symlinked files and directories, which it does with
+/// `--follow-symlinks`
+///
+/// When followed, loops are skipped, and a file reachable through several paths is only listed
+/// once, so its occurrences aren't counted twice.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Symlinks {
+    #[default]
+    Skip,
+    Follow,
+}
 
 pub struct CodebaseFiles {
     pub paths: Vec<PathBuf>,
@@ -7,35 +33,188 @@ pub struct CodebaseFiles {
 
 impl CodebaseFiles {
     pub fn all() -> CodebaseFiles {
-        let output = Command::new("git").arg("ls-files").output();
-        let mut paths = Self::process_ls_files(output);
-        paths.extend(Self::process_ls_files(
-            Command::new("git")
-                .arg("ls-files")
-                .arg("--others")
-                .arg("--exclude-standard")
-                .output(),
-        ));
+        Self::from_root(Path::new("."))
+    }
 
+    pub fn from_root(root: &Path) -> CodebaseFiles {
+        Self::walk(root, Symlinks::Skip)
+    }
+
+    /// Walk files under `root`, following symlinks or not as `symlinks` says
+    pub fn walk(root: &Path, symlinks: Symlinks) -> CodebaseFiles {
+        Self::from_root_reporting(root, symlinks, || {})
+    }
+
+    /// Walk files from the current directory, calling `on_found` as each file is found
+    pub fn all_reporting(symlinks: Symlinks, on_found: impl Fn() + Sync) -> CodebaseFiles {
+        Self::from_root_reporting(Path::new("."), symlinks, on_found)
+    }
+
+    /// Walk files under `root`, calling `on_found` as each file is found (such as to report
+    /// progress through large codebases)
+    pub fn from_root_reporting(
+        root: &Path,
+        symlinks: Symlinks,
+        on_found: impl Fn() + Sync,
+    ) -> CodebaseFiles {
+        let follow_symlinks = symlinks == Symlinks::Follow;
+        let (sender, receiver) = mpsc::channel();
+        let on_found = &on_found;
+
+        WalkBuilder::new(root)
+            .hidden(false)
+            .require_git(false)
+            .follow_links(follow_symlinks)
+            .filter_entry(|entry| !Self::skipped_directory(entry))
+            .build_parallel()
+            .run(|| {
+                let sender = sender.clone();
+                Box::new(move |result| {
+                    match result {
+                        Ok(entry) if entry.file_type().is_some_and(|t| t.is_file()) => {
+                            let path = entry.path();
+                            let relative = path.strip_prefix(root).unwrap_or(path);
+                            sender.send(relative.to_path_buf()).ok();
+                            on_found();
+                        }
+                        Ok(entry) if !follow_symlinks && entry.path_is_symlink() => {
+                            tracing::debug!(
+                                "Skipping symlink {}: pass --follow-symlinks to follow it",
+                                entry.path().display()
+                            )
+                        }
+                        Ok(_) => {}
+                        // Including loops, when following symlinks
+                        Err(e) => tracing::debug!("Skipping {}", e),
+                    }
+                    WalkState::Continue
+                })
+            });
+
+        drop(sender);
+
+        let mut paths: Vec<PathBuf> = receiver.into_iter().collect();
         paths.sort();
         paths.dedup();
-        CodebaseFiles {
-            paths: paths.into_iter().map(PathBuf::from).collect(),
+        if follow_symlinks {
+            paths = Self::distinct_files(root, paths);
         }
+        CodebaseFiles { paths }
     }
 
-    fn process_ls_files<T>(output: Result<Output, T>) -> Vec<String> {
-        match output {
-            Ok(o) => {
-                if o.status.success() {
-                    std::str::from_utf8(&o.stdout).map_or(vec![], |v| {
-                        v.lines().map(|k| k.to_string()).collect::<Vec<String>>()
-                    })
-                } else {
-                    vec![]
+    // Files reached through more than one path (such as through a symlinked directory) are
+    // listed once, at their real path when it was walked
+    fn distinct_files(root: &Path, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        let real_root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let mut by_target: BTreeMap<PathBuf, PathBuf> = BTreeMap::new();
+
+        for path in paths {
+            let target = root
+                .join(&path)
+                .canonicalize()
+                .unwrap_or_else(|_| path.clone());
+            let is_real = target == real_root.join(&path);
+
+            match by_target.entry(target) {
+                Entry::Vacant(entry) => {
+                    entry.insert(path);
+                }
+                Entry::Occupied(mut entry) => {
+                    let skipped = if is_real { entry.insert(path) } else { path };
+                    tracing::debug!(
+                        "Skipping file {}: the same file as {}",
+                        skipped.display(),
+                        entry.get().display()
+                    );
                 }
             }
-            _ => vec![],
         }
+
+        let mut paths: Vec<PathBuf> = by_target.into_values().collect();
+        paths.sort();
+        paths
+    }
+
+    fn skipped_directory(entry: &DirEntry) -> bool {
+        let skipped = entry.file_type().is_some_and(|t| t.is_dir())
+            && entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| SKIPPED_DIRECTORIES.contains(&name));
+
+        if skipped {
+            tracing::debug!(
+                "Skipping directory {}: version control or vendored dependencies",
+                entry.path().display()
+            );
+        }
+        skipped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn skips_ignored_and_vendored_paths() {
+        let root = std::env::temp_dir().join("codebase_files_skips_ignored");
+        fs::remove_dir_all(&root).ok();
+        for dir in &["app", "node_modules/left-pad", ".git", "log"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join(".gitignore"), "log/\n").unwrap();
+        fs::write(root.join("app/person.rb"), "class Person; end").unwrap();
+        fs::write(root.join("node_modules/left-pad/index.js"), "").unwrap();
+        fs::write(root.join(".git/HEAD"), "").unwrap();
+        fs::write(root.join("log/development.log"), "").unwrap();
+
+        let files = CodebaseFiles::from_root(&root);
+        fs::remove_dir_all(&root).ok();
+
+        assert_eq!(
+            files.paths,
+            vec![PathBuf::from(".gitignore"), PathBuf::from("app/person.rb")]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follows_symlinks_without_loops_or_duplicates() {
+        let root = std::env::temp_dir().join("codebase_files_follows_symlinks");
+        fs::remove_dir_all(&root).ok();
+        fs::create_dir_all(root.join("app/models")).unwrap();
+        fs::write(root.join("app/models/person.rb"), "class Person; end").unwrap();
+        std::os::unix::fs::symlink(root.join("app"), root.join("linked")).unwrap();
+        std::os::unix::fs::symlink(root.join("app"), root.join("app/models/loop")).unwrap();
+
+        let skipped = CodebaseFiles::walk(&root, Symlinks::Skip);
+        let followed = CodebaseFiles::walk(&root, Symlinks::Follow);
+        fs::remove_dir_all(&root).ok();
+
+        assert_eq!(skipped.paths, vec![PathBuf::from("app/models/person.rb")]);
+        assert_eq!(followed.paths, vec![PathBuf::from("app/models/person.rb")]);
+    }
+
+    #[test]
+    fn reports_each_file_found() {
+        let root = std::env::temp_dir().join("codebase_files_reports_each_file");
+        fs::remove_dir_all(&root).ok();
+        fs::create_dir_all(root.join("app/models")).unwrap();
+        fs::create_dir_all(root.join("node_modules/left-pad")).unwrap();
+        fs::write(root.join("app/models/person.rb"), "class Person; end").unwrap();
+        fs::write(root.join("app/models/order.rb"), "class Order; end").unwrap();
+        fs::write(root.join("node_modules/left-pad/index.js"), "").unwrap();
+
+        let found = AtomicUsize::new(0);
+        let files = CodebaseFiles::from_root_reporting(&root, Symlinks::Skip, || {
+            found.fetch_add(1, Ordering::Relaxed);
+        });
+        fs::remove_dir_all(&root).ok();
+
+        assert_eq!(files.paths.len(), 2);
+        assert_eq!(found.into_inner(), 2);
     }
 }
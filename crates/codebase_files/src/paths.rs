@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+// Windows and macOS filesystems are case-insensitive by default
+const CASE_INSENSITIVE: bool = cfg!(any(windows, target_os = "macos"));
+
+/// Normalize a path written by another tool, such as a tags file generated on Windows
+///
+/// Backslashes (including escaped, doubled backslashes) are treated as separators and a leading
+/// `./` (or `.\`) is removed, so the path matches those found when walking the codebase.
+pub fn normalize(path: &str) -> PathBuf {
+    let mut path = path.replace('\\', "/");
+    while path.contains("//") {
+        path = path.replace("//", "/");
+    }
+
+    PathBuf::from(path.strip_prefix("./").unwrap_or(&path))
+}
+
+/// `path` relative to `root`, when it's absolute and beneath `root`
+///
+/// Components (including Windows drive letters) are compared case-insensitively on
+/// case-insensitive filesystems.
+pub fn relative_to(path: &Path, root: &Path) -> Option<PathBuf> {
+    strip_root(path, root, CASE_INSENSITIVE)
+}
+
+fn strip_root(path: &Path, root: &Path, case_insensitive: bool) -> Option<PathBuf> {
+    if !path.is_absolute() {
+        return None;
+    }
+
+    let mut components = path.components();
+    for root_component in root.components() {
+        let component = components.next()?;
+        if !same_component(component, root_component, case_insensitive) {
+            return None;
+        }
+    }
+
+    Some(components.as_path().to_path_buf())
+}
+
+fn same_component(a: Component, b: Component, case_insensitive: bool) -> bool {
+    if case_insensitive {
+        a.as_os_str().to_string_lossy().to_lowercase()
+            == b.as_os_str().to_string_lossy().to_lowercase()
+    } else {
+        a == b
+    }
+}
+
+/// Files found in the codebase, for finding the file another tool's path refers to
+pub struct PathLookup {
+    by_lowercase: HashMap<String, PathBuf>,
+    case_insensitive: bool,
+}
+
+impl PathLookup {
+    pub fn new(files: &[PathBuf]) -> Self {
+        Self::with_case_sensitivity(files, CASE_INSENSITIVE)
+    }
+
+    fn with_case_sensitivity(files: &[PathBuf], case_insensitive: bool) -> Self {
+        PathLookup {
+            by_lowercase: if case_insensitive {
+                files.iter().map(|f| (lowercase(f), f.clone())).collect()
+            } else {
+                HashMap::new()
+            },
+            case_insensitive,
+        }
+    }
+
+    /// The path of the file `path` refers to, as found in the codebase
+    ///
+    /// On case-insensitive filesystems, a path differing only in case from a file in the
+    /// codebase refers to that file; otherwise, `path` is returned as-is.
+    pub fn resolve(&self, path: &Path) -> PathBuf {
+        if self.case_insensitive {
+            if let Some(found) = self.by_lowercase.get(&lowercase(path)) {
+                return found.clone();
+            }
+        }
+
+        path.to_path_buf()
+    }
+}
+
+fn lowercase(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_windows_separators() {
+        assert_eq!(
+            normalize(".\\app\\models\\person.rb"),
+            PathBuf::from("app/models/person.rb")
+        );
+        assert_eq!(
+            normalize(".\\\\app\\\\models\\\\person.rb"),
+            PathBuf::from("app/models/person.rb")
+        );
+        assert_eq!(
+            normalize("./app/models/person.rb"),
+            PathBuf::from("app/models/person.rb")
+        );
+    }
+
+    #[test]
+    fn resolves_paths_differing_in_case() {
+        let files = vec![PathBuf::from("app/models/person.rb")];
+
+        assert_eq!(
+            PathLookup::with_case_sensitivity(&files, true)
+                .resolve(Path::new("App/Models/Person.rb")),
+            PathBuf::from("app/models/person.rb")
+        );
+        assert_eq!(
+            PathLookup::with_case_sensitivity(&files, false)
+                .resolve(Path::new("App/Models/Person.rb")),
+            PathBuf::from("App/Models/Person.rb")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn strips_absolute_roots() {
+        let root = Path::new("/home/me/Project");
+
+        assert_eq!(
+            strip_root(Path::new("/home/me/project/app/person.rb"), root, true),
+            Some(PathBuf::from("app/person.rb"))
+        );
+        assert_eq!(
+            strip_root(Path::new("/home/me/project/app/person.rb"), root, false),
+            None
+        );
+        assert_eq!(strip_root(Path::new("app/person.rb"), root, true), None);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn strips_drive_letter_roots() {
+        assert_eq!(
+            strip_root(
+                Path::new("c:\\Users\\me\\project\\app\\person.rb"),
+                Path::new("C:\\Users\\me\\project"),
+                true
+            ),
+            Some(PathBuf::from("app\\person.rb"))
+        );
+        assert_eq!(
+            normalize("C:\\Users\\me\\project\\app\\person.rb"),
+            PathBuf::from("C:/Users/me/project/app/person.rb")
+        );
+        assert_eq!(Path::new("app/person.rb"), Path::new("app\\person.rb"));
+    }
+}
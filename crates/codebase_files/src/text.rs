@@ -0,0 +1,114 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::OnceLock;
+
+// STDIN can only be read once, so its contents (or the error reading it) are kept for the rest
+// of the process
+static STDIN: OnceLock<Result<String, (io::ErrorKind, String)>> = OnceLock::new();
+
+/// Read the file at `path` as text, tolerating encodings other than UTF-8
+///
+/// Files that aren't valid UTF-8 (such as source with Latin-1 comments) are decoded as Latin-1,
+/// which maps every byte to a character, with a warning. Files containing NUL bytes are
+/// treated as binary and return an `InvalidData` error.
+pub fn read(path: &Path) -> io::Result<String> {
+    let bytes = fs::read(path)?;
+
+    decode(bytes)
+        .inspect(|(_, utf8)| {
+            if !utf8 {
+                tracing::warn!("{} isn't valid UTF-8; decoded as Latin-1", path.display());
+            }
+        })
+        .map(|(contents, _)| contents)
+}
+
+/// Read STDIN to its end as text, decoded as `read` decodes files
+///
+/// STDIN is only read the first time; later calls (such as when re-analyzing in watch mode)
+/// return the same contents.
+pub fn read_stdin() -> io::Result<String> {
+    STDIN
+        .get_or_init(|| read_to_end(io::stdin().lock()).map_err(|e| (e.kind(), e.to_string())))
+        .clone()
+        .map_err(|(kind, message)| io::Error::new(kind, message))
+}
+
+// A stream's contents to its end, decoded as `read` decodes files
+fn read_to_end(mut reader: impl Read) -> io::Result<String> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+
+    decode(bytes).map(|(contents, _)| contents)
+}
+
+/// Can `c` be part of an identifier?
+///
+/// Letters and digits of any script and underscores are, as are the other characters Unicode
+/// allows within identifiers (`XID_Continue`, as Ruby, Rust, and Elixir follow), such as the
+/// combining accent in an `é` written as `e` and U+0301, so a match isn't cut off mid-identifier.
+pub fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || unicode_ident::is_xid_continue(c)
+}
+
+/// Decode a single line of a stream as `read` decodes files, as Latin-1 when it isn't UTF-8
+pub fn decode_line(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes)
+        .unwrap_or_else(|e| e.into_bytes().into_iter().map(char::from).collect())
+}
+
+// The decoded text, and whether it was valid UTF-8
+fn decode(bytes: Vec<u8>) -> io::Result<(String, bool)> {
+    if bytes.contains(&0) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "binary file"));
+    }
+
+    match String::from_utf8(bytes) {
+        Ok(contents) => Ok((contents, true)),
+        Err(e) => Ok((e.into_bytes().into_iter().map(char::from).collect(), false)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_identifier_characters_of_any_script() {
+        for c in ['a', 'Z', '9', '_', 'é', 'ж', '名', '\u{301}'] {
+            assert!(is_identifier_char(c), "{:?}", c);
+        }
+        for c in [' ', '.', '?', '-', '$', '\u{3000}'] {
+            assert!(!is_identifier_char(c), "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn decodes_latin_1_and_rejects_binary_files() {
+        assert_eq!(
+            decode(b"def name # caf\xe9\n".to_vec()).unwrap(),
+            (String::from("def name # café\n"), false)
+        );
+        assert_eq!(
+            decode("def name # café\n".as_bytes().to_vec()).unwrap(),
+            (String::from("def name # café\n"), true)
+        );
+        assert_eq!(
+            decode(b"\x89PNG\r\n\x1a\n\0\0".to_vec()).map_err(|e| e.kind()),
+            Err(io::ErrorKind::InvalidData)
+        );
+    }
+
+    #[test]
+    fn reads_piped_tags_as_files_are_read() {
+        assert_eq!(
+            read_to_end(&b"full_name\tapp/caf\xe9.rb\t1;\"\tf\n"[..]).unwrap(),
+            "full_name\tapp/café.rb\t1;\"\tf\n"
+        );
+        assert_eq!(
+            read_to_end(&b"\x7fELF\0\0"[..]).map_err(|e| e.kind()),
+            Err(io::ErrorKind::InvalidData)
+        );
+    }
+}
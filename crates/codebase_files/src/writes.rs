@@ -0,0 +1,134 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Distinguishes temporary files written concurrently by the same process
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether files may be written, passed to everything that writes so read-only mode
+/// (`--no-write`) is enforced in one place
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Writes {
+    /// Files are written as usual
+    #[default]
+    Enabled,
+    /// Every write is refused with a `PermissionDenied` error
+    Disabled,
+}
+
+impl Writes {
+    /// Are writes allowed?
+    pub fn enabled(self) -> bool {
+        self == Writes::Enabled
+    }
+
+    /// Write `contents` to `path`, creating its parent directory if necessary
+    ///
+    /// Contents are written to a temporary file alongside `path` and then renamed over it, so an
+    /// interrupted run leaves either the previous file or the new one, never a partial write.
+    pub fn write<C: AsRef<[u8]>>(self, path: &Path, contents: C) -> io::Result<()> {
+        self.allow(path)?;
+        replace(path, contents.as_ref())
+    }
+
+    /// Create the directory at `path`, along with any missing parents
+    pub fn create_dir(self, path: &Path) -> io::Result<()> {
+        self.allow(path)?;
+        fs::create_dir_all(path)
+    }
+
+    /// Create (or truncate) the file at `path` for writing
+    pub fn create(self, path: &Path) -> io::Result<File> {
+        self.allow(path)?;
+        File::create(path)
+    }
+
+    /// Remove the file or directory at `path`
+    pub fn remove(self, path: &Path) -> io::Result<()> {
+        self.allow(path)?;
+
+        if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        }
+    }
+
+    fn allow(self, path: &Path) -> io::Result<()> {
+        if self.enabled() {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} was not written in read-only mode", path.display()),
+            ))
+        }
+    }
+}
+
+fn replace(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+
+    let temp = temp_path(path);
+    let written = fs::write(&temp, contents).and_then(|_| fs::rename(&temp, path));
+    if written.is_err() {
+        fs::remove_file(&temp).ok();
+    }
+
+    written
+}
+
+// A hidden file in the same directory as `path`, since renames across filesystems aren't atomic
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        name,
+        process::id(),
+        TEMP_FILES.fetch_add(1, Ordering::SeqCst)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_files_without_leaving_temporary_files() {
+        let dir = std::env::temp_dir().join("codebase_files_replaces_files");
+        fs::remove_dir_all(&dir).ok();
+        let path = dir.join("report.json");
+
+        replace(&path, b"first").unwrap();
+        replace(&path, b"second").unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        let entries = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(contents, "second");
+        assert_eq!(entries, 1);
+    }
+
+    #[test]
+    fn refuses_writes_when_disabled() {
+        let path = std::env::temp_dir().join("codebase_files_refuses_writes.txt");
+        fs::remove_file(&path).ok();
+
+        let outcome = Writes::Disabled.write(&path, "contents");
+
+        assert_eq!(
+            outcome.map_err(|e| e.kind()),
+            Err(io::ErrorKind::PermissionDenied)
+        );
+        assert!(!path.exists());
+        assert!(Writes::Disabled.create(&path).is_err());
+    }
+}
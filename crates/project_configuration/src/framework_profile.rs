@@ -0,0 +1,141 @@
+use super::loader::ProjectConfigurations;
+use super::project_configuration::LowLikelihoodConfig;
+use std::fs;
+use std::path::Path;
+
+/// A framework whose conventions reference tokens implicitly, such as controller actions invoked
+/// by a router
+///
+/// Implement this trait and register the profile with `FrameworkProfiles` to add profiles beyond
+/// the built-in ones.
+pub trait FrameworkProfile {
+    /// Name of the framework
+    fn name(&self) -> &str;
+
+    /// Whether the project at `root` uses the framework
+    fn detect(&self, root: &Path) -> bool;
+
+    /// Rules lowering the likelihood of tokens the framework references by convention
+    fn low_likelihood(&self) -> Vec<LowLikelihoodConfig>;
+}
+
+// Built-in profiles are detected by a dependency declared in the project's manifest
+struct ManifestProfile {
+    name: &'static str,
+    manifest: &'static str,
+    dependency_markers: &'static [&'static str],
+    rules: &'static str,
+}
+
+impl FrameworkProfile for ManifestProfile {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn detect(&self, root: &Path) -> bool {
+        fs::read_to_string(root.join(self.manifest)).is_ok_and(|contents| {
+            contents.lines().any(|line| {
+                self.dependency_markers
+                    .iter()
+                    .any(|marker| line.trim_start().starts_with(marker))
+            })
+        })
+    }
+
+    fn low_likelihood(&self) -> Vec<LowLikelihoodConfig> {
+        ProjectConfigurations::parse_low_likelihood_rules(self.rules)
+    }
+}
+
+/// The set of profiles checked against a project
+pub struct FrameworkProfiles(Vec<Box<dyn FrameworkProfile>>);
+
+impl FrameworkProfiles {
+    /// Profiles for Rails (detected from the Gemfile) and Phoenix (detected from mix.exs)
+    pub fn builtin() -> Self {
+        FrameworkProfiles(vec![
+            Box::new(ManifestProfile {
+                name: "Rails",
+                manifest: "Gemfile",
+                dependency_markers: &["gem \"rails\"", "gem 'rails'"],
+                rules: include_str!("profiles/rails.yml"),
+            }),
+            Box::new(ManifestProfile {
+                name: "Phoenix",
+                manifest: "mix.exs",
+                dependency_markers: &["{:phoenix,"],
+                rules: include_str!("profiles/phoenix.yml"),
+            }),
+        ])
+    }
+
+    /// Add a profile to check alongside those already registered
+    pub fn register(&mut self, profile: Box<dyn FrameworkProfile>) {
+        self.0.push(profile);
+    }
+
+    /// Profiles for frameworks used by the project at `root`
+    pub fn detect(&self, root: &Path) -> Vec<&dyn FrameworkProfile> {
+        self.0
+            .iter()
+            .filter(|profile| profile.detect(root))
+            .map(|profile| profile.as_ref())
+            .collect()
+    }
+}
+
+impl Default for FrameworkProfiles {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Sinatra;
+
+    impl FrameworkProfile for Sinatra {
+        fn name(&self) -> &str {
+            "Sinatra"
+        }
+
+        fn detect(&self, root: &Path) -> bool {
+            root.join("config.ru").is_file()
+        }
+
+        fn low_likelihood(&self) -> Vec<LowLikelihoodConfig> {
+            vec![]
+        }
+    }
+
+    #[test]
+    fn detects_frameworks_from_manifests() {
+        let root = std::env::temp_dir().join("unused_detects_frameworks_from_manifests");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("Gemfile"),
+            "source \"https://rubygems.org\"\n\ngem \"rails\", \"~> 7.1\"\n",
+        )
+        .unwrap();
+        fs::write(root.join("config.ru"), "run App\n").unwrap();
+
+        let mut profiles = FrameworkProfiles::builtin();
+        profiles.register(Box::new(Sinatra));
+        let detected: Vec<&str> = profiles.detect(&root).iter().map(|p| p.name()).collect();
+        fs::remove_dir_all(&root).ok();
+
+        assert_eq!(detected, vec!["Rails", "Sinatra"]);
+    }
+
+    #[test]
+    fn builtin_profiles_parse() {
+        for profile in FrameworkProfiles::builtin().0 {
+            let rules = profile.low_likelihood();
+
+            assert!(!rules.is_empty());
+            assert!(rules.iter().all(|r| !r.matchers.is_empty()));
+        }
+    }
+}
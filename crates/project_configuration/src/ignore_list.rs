@@ -0,0 +1,115 @@
+use super::value_assertion::{Assertion, TokenPattern, ValueMatcher};
+use yaml_rust::{Yaml, YamlLoader};
+
+/// Tokens and paths that should never be reported, typically shared via `ignore_from`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IgnoreList {
+    pub tokens: Vec<String>,
+    /// Regular expressions; any token matching one is ignored
+    pub tokens_matching: Vec<String>,
+    pub paths: Vec<String>,
+}
+
+impl IgnoreList {
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        match YamlLoader::load_from_str(contents) {
+            Ok(docs) => match &docs[..] {
+                [doc @ Yaml::Hash(_)] => {
+                    let tokens_matching = Self::parse_strings("tokens_matching", doc);
+                    Self::validate_patterns("tokens_matching", &tokens_matching)?;
+
+                    Ok(IgnoreList {
+                        tokens: Self::parse_strings("tokens", doc),
+                        tokens_matching,
+                        paths: Self::parse_strings("paths", doc),
+                    })
+                }
+                [] => Ok(IgnoreList::default()),
+                _ => Err("expected a mapping with 'tokens' and/or 'paths'".to_string()),
+            },
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    pub fn extend(&mut self, other: IgnoreList) {
+        self.tokens.extend(other.tokens);
+        self.tokens_matching.extend(other.tokens_matching);
+        self.paths.extend(other.paths);
+    }
+
+    pub fn assertions(&self) -> Vec<Assertion> {
+        let tokens = self
+            .tokens
+            .iter()
+            .map(|t| Assertion::TokenAssertion(ValueMatcher::Equals(t.to_string())));
+        // Patterns are validated when parsed
+        let tokens_matching = self
+            .tokens_matching
+            .iter()
+            .filter_map(|p| TokenPattern::new(p).ok())
+            .map(|p| Assertion::TokenAssertion(ValueMatcher::Matches(p)));
+        let paths = self
+            .paths
+            .iter()
+            .map(|p| Assertion::PathAssertion(ValueMatcher::Contains(p.to_string())));
+
+        tokens.chain(tokens_matching).chain(paths).collect()
+    }
+
+    /// Check each of `patterns`, listed under `key`, is a valid regular expression
+    pub fn validate_patterns(key: &str, patterns: &[String]) -> Result<(), String> {
+        patterns
+            .iter()
+            .try_for_each(|p| TokenPattern::new(p).map(|_| ()))
+            .map_err(|e| format!("{}: {}", key, e))
+    }
+
+    fn parse_strings(key: &str, contents: &Yaml) -> Vec<String> {
+        match &contents[key] {
+            Yaml::Array(items) => items
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(String::from)
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tokens_and_paths() {
+        let list = IgnoreList::parse(
+            "tokens:\n  - ApplicationJob\ntokens_matching:\n  - ^Test\npaths:\n  - app/legacy/\n",
+        );
+
+        assert_eq!(
+            list,
+            Ok(IgnoreList {
+                tokens: vec![String::from("ApplicationJob")],
+                tokens_matching: vec![String::from("^Test")],
+                paths: vec![String::from("app/legacy/")],
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert_eq!(
+            IgnoreList::parse("tokens_matching:\n  - '[a-'\n").unwrap_err(),
+            IgnoreList::validate_patterns("tokens_matching", &[String::from("[a-")]).unwrap_err()
+        );
+        assert!(IgnoreList::parse("tokens_matching:\n  - '[a-'\n")
+            .unwrap_err()
+            .starts_with("tokens_matching: invalid pattern '[a-'"));
+    }
+
+    #[test]
+    fn rejects_unexpected_documents() {
+        assert!(IgnoreList::parse("- foo\n- bar\n").is_err());
+        assert_eq!(IgnoreList::parse(""), Ok(IgnoreList::default()));
+    }
+}
@@ -1,7 +1,21 @@
+mod framework_profile;
+mod ignore_list;
 mod loader;
+mod package_exports;
 mod project_configuration;
+mod project_settings;
+mod rails_routes;
 mod value_assertion;
 
-pub use crate::project_configuration::{PathPrefix, ProjectConfiguration};
+pub use crate::framework_profile::{FrameworkProfile, FrameworkProfiles};
+pub use crate::ignore_list::IgnoreList;
+pub use crate::package_exports::PackageExports;
+pub use crate::project_configuration::{LowLikelihoodConfig, PathPrefix, ProjectConfiguration};
+pub use crate::project_settings::{
+    CacheSettings, DeprecatedTokens, FindingsBudget, LanguageSettings, OutputDefaults,
+    ProjectSettings, RootGlobs, SearchRoots, ServeSettings, SubProject, TokenDecision, TokenPolicy,
+    PROJECT_SETTINGS_FILES, VENDORED_PATHS,
+};
+pub use crate::rails_routes::{controller_path, RailsRoutes, Routing};
 pub use loader::ProjectConfigurations;
-pub use value_assertion::{Assertion, AssertionConflict, ValueMatcher};
+pub use value_assertion::{Assertion, AssertionConflict, TokenPattern, ValueMatcher};
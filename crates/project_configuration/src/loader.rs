@@ -1,4 +1,6 @@
+use super::package_exports::PackageExports;
 use super::project_configuration::*;
+use super::rails_routes::RailsRoutes;
 use super::value_assertion::{Assertion, ValueMatcher};
 use std::collections::{HashMap, HashSet};
 use std::include_str;
@@ -14,7 +16,7 @@ const TOKEN_STARTS_WITH: &str = "token_starts_with";
 const TOKEN_ENDS_WITH: &str = "token_ends_with";
 const CLASS_OR_MODULE: &str = "class_or_module";
 const ALLOWED_TOKENS: &str = "allowed_tokens";
-const SUPPORTED_ASSERTIONS: [&'static str; 9] = [
+const SUPPORTED_ASSERTIONS: [&str; 9] = [
     PATH_STARTS_WITH,
     PATH_ENDS_WITH,
     PATH_EQUALS,
@@ -36,7 +38,7 @@ impl ProjectConfigurations {
     }
 
     pub fn get(&self, name: &str) -> Option<ProjectConfiguration> {
-        self.configs.get(name).map(|v| v.clone())
+        self.configs.get(name).cloned()
     }
 
     pub fn parse(contents: &str) -> Self {
@@ -47,6 +49,17 @@ impl ProjectConfigurations {
         ProjectConfigurations { configs }
     }
 
+    /// Parse a list of rules in the same shape as `auto_low_likelihood` entries
+    pub fn parse_low_likelihood_rules(contents: &str) -> Vec<LowLikelihoodConfig> {
+        match YamlLoader::load_from_str(contents).as_deref() {
+            Ok([Yaml::Array(items)]) => items
+                .iter()
+                .filter_map(Self::parse_low_likelihood_item)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
     pub fn project_config_names(&self) -> Vec<String> {
         self.configs.keys().map(|v| v.to_owned()).collect()
     }
@@ -67,12 +80,14 @@ impl ProjectConfigurations {
             Assertion::TokenAssertion(ValueMatcher::ExactMatchOnAnyOf(_)) => Some(ALLOWED_TOKENS),
             Assertion::TokenAssertion(ValueMatcher::StartsWithCapital) => Some(CLASS_OR_MODULE),
             Assertion::TokenAssertion(ValueMatcher::Contains(_)) => None,
+            Assertion::TokenAssertion(ValueMatcher::Matches(_)) => None,
             Assertion::PathAssertion(ValueMatcher::StartsWith(_)) => Some(PATH_STARTS_WITH),
             Assertion::PathAssertion(ValueMatcher::EndsWith(_)) => Some(PATH_ENDS_WITH),
             Assertion::PathAssertion(ValueMatcher::Equals(_)) => Some(PATH_EQUALS),
             Assertion::PathAssertion(ValueMatcher::ExactMatchOnAnyOf(_)) => None,
             Assertion::PathAssertion(ValueMatcher::StartsWithCapital) => None,
             Assertion::PathAssertion(ValueMatcher::Contains(_)) => Some(PATH_CONTAINS),
+            Assertion::PathAssertion(ValueMatcher::Matches(_)) => None,
         }
     }
 
@@ -84,7 +99,7 @@ impl ProjectConfigurations {
                     let config_name = doc_with_name["name"].as_str().unwrap_or("").to_string();
                     acc.insert(
                         config_name.to_string(),
-                        Self::parse_from_yaml(&config_name, &doc_with_name),
+                        Self::parse_from_yaml(&config_name, doc_with_name),
                     );
                     acc
                 },
@@ -101,6 +116,11 @@ impl ProjectConfigurations {
             config_file: Self::parse_path_prefixes("config_files", contents),
             low_likelihood: Self::parse_low_likelihoods(contents),
             matches_if: Self::parse_matches_if(contents),
+            ignore_from: Self::parse_ignore_from(contents),
+            routes: RailsRoutes::default(),
+            exports: PackageExports::default(),
+            doc_references: false,
+            kind_weights: vec![],
         }
     }
 
@@ -108,9 +128,20 @@ impl ProjectConfigurations {
         match &contents[key] {
             Yaml::Array(items) => items
                 .iter()
-                .map(|v| v.as_str())
-                .filter_map(|v| v)
-                .map(|v| PathPrefix::new(v))
+                .filter_map(|v| v.as_str())
+                .map(PathPrefix::new)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn parse_ignore_from(contents: &Yaml) -> Vec<String> {
+        match &contents["ignore_from"] {
+            Yaml::String(source) => vec![source.to_string()],
+            Yaml::Array(items) => items
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(String::from)
                 .collect(),
             _ => vec![],
         }
@@ -120,8 +151,7 @@ impl ProjectConfigurations {
         match &contents["auto_low_likelihood"] {
             Yaml::Array(items) => items
                 .iter()
-                .map(|i| Self::parse_low_likelihood_item(i))
-                .filter_map(|i| i)
+                .filter_map(Self::parse_low_likelihood_item)
                 .collect(),
             _ => vec![],
         }
@@ -131,7 +161,7 @@ impl ProjectConfigurations {
         match &contents["matches_if"] {
             Yaml::Array(items) => items
                 .iter()
-                .flat_map(|i| Self::parse_individual_matches_if(i))
+                .flat_map(Self::parse_individual_matches_if)
                 .collect(),
             _ => vec![],
         }
@@ -140,11 +170,10 @@ impl ProjectConfigurations {
     fn parse_individual_matches_if(contents: &Yaml) -> Vec<Assertion> {
         SUPPORTED_ASSERTIONS
             .iter()
-            .map(|&k| match &contents[k] {
-                Yaml::String(v) => Self::parse_single_assertion(k, &v),
+            .filter_map(|&k| match &contents[k] {
+                Yaml::String(v) => Self::parse_single_assertion(k, v),
                 _ => None,
             })
-            .filter_map(|a| a)
             .collect()
     }
 
@@ -154,8 +183,7 @@ impl ProjectConfigurations {
                 name: name.to_string(),
                 matchers: SUPPORTED_ASSERTIONS
                     .iter()
-                    .map(|a| Self::parse_assertion_row(a, contents))
-                    .filter_map(|a| a)
+                    .filter_map(|a| Self::parse_assertion_row(a, contents))
                     .collect(),
             }),
             _ => None,
@@ -168,7 +196,7 @@ impl ProjectConfigurations {
             Yaml::String(val) => Self::parse_single_assertion(key, val),
             Yaml::Array(vals) => Self::parse_multiple_assertions(
                 key,
-                vals.into_iter()
+                vals.iter()
                     .filter_map(|v| v.clone().into_string())
                     .collect(),
             ),
@@ -245,6 +273,9 @@ mod tests {
   config_files:
   - priv/
 - name: Rails
+  ignore_from:
+  - https://internal.example.com/unused-ignores.yml
+  - config/unused-ignores.yml
   application_files:
   - app/
   - lib/
@@ -327,7 +358,7 @@ mod tests {
                 matchers: vec![
                     Assertion::PathAssertion(ValueMatcher::EndsWith(String::from(".rb"))),
                     Assertion::TokenAssertion(ValueMatcher::ExactMatchOnAnyOf(
-                        vec![
+                        [
                             String::from("new?"),
                             String::from("index?"),
                             String::from("show?")
@@ -371,6 +402,15 @@ mod tests {
             ]
         );
 
+        assert_eq!(
+            rails_config.ignore_from,
+            vec![
+                String::from("https://internal.example.com/unused-ignores.yml"),
+                String::from("config/unused-ignores.yml"),
+            ]
+        );
+        assert_eq!(phoenix_config.ignore_from, Vec::<String>::new());
+
         assert_eq!(phoenix_config.test_file, vec![PathPrefix::new("test/"),]);
 
         assert_eq!(phoenix_config.config_file, vec![PathPrefix::new("priv/"),]);
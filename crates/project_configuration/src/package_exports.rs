@@ -0,0 +1,488 @@
+use read_ctags::Language;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use token_search::TokenSearchResult;
+
+// Directories builds are commonly written to, whose sources are usually found under `src`
+const BUILD_DIRECTORIES: [&str; 6] = ["dist", "lib", "build", "out", "esm", "cjs"];
+
+// Extensions tried, in order, when resolving a JavaScript or TypeScript module
+const MODULE_EXTENSIONS: [&str; 8] = ["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs"];
+
+/// Tokens exported from a package's entry points, which other packages may consume
+///
+/// JavaScript and TypeScript entry points are the `main`, `module`, `types`, and `exports`
+/// files of `package.json`, resolved from build output back to `src` when needed; their
+/// `export` statements (following `export * from` re-exports) and CommonJS `exports`
+/// assignments name the exported tokens. The Rust entry point is the library root of
+/// `Cargo.toml` (`src/lib.rs` unless `[lib] path` says otherwise), exporting its `pub` items,
+/// items re-exported with `pub use`, and the `pub` items of its `pub mod` modules.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PackageExports {
+    javascript: BTreeMap<String, PathBuf>,
+    rust: BTreeMap<String, PathBuf>,
+    rust_source: Option<PathBuf>,
+}
+
+impl PackageExports {
+    /// Read the exports of the package at `root`, from `package.json` and `Cargo.toml`
+    ///
+    /// Returns `Ok(None)` when there's neither manifest, or when neither names an entry point.
+    pub fn load(root: &Path) -> io::Result<Option<Self>> {
+        let mut exports = PackageExports::default();
+
+        if let Some(manifest) = read_optional(&root.join("package.json"))? {
+            let manifest: Value = serde_json::from_str(&manifest)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let mut visited = HashSet::new();
+            for entry in package_entries(&manifest) {
+                if let Some(path) = resolve_module(root, Path::new(&entry)) {
+                    let entry_point = relative(root, &path);
+                    exports.read_javascript(root, &path, &entry_point, &mut visited);
+                }
+            }
+        }
+
+        if let Some(manifest) = read_optional(&root.join("Cargo.toml"))? {
+            let manifest: toml::Value = manifest
+                .parse()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let library = manifest
+                .get("lib")
+                .and_then(|lib| lib.get("path"))
+                .and_then(|path| path.as_str())
+                .unwrap_or("src/lib.rs");
+            let path = root.join(library);
+            if path.is_file() {
+                let entry_point = relative(root, &path);
+                exports.rust_source = entry_point.parent().map(|p| p.to_path_buf());
+                exports.read_rust(&path, true, &entry_point, &mut HashSet::new());
+            }
+        }
+
+        if exports.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(exports))
+        }
+    }
+
+    /// Were no exports read?
+    pub fn is_empty(&self) -> bool {
+        self.javascript.is_empty() && self.rust.is_empty()
+    }
+
+    /// The entry point exporting `result`'s token, when one of its JavaScript, TypeScript, or
+    /// Rust definitions is exported (Rust definitions only count within the library's sources)
+    pub fn entry_point(&self, result: &TokenSearchResult) -> Option<&Path> {
+        result.token.definitions.iter().find_map(|definition| {
+            let language = definition
+                .language
+                .or_else(|| Language::from_path(&definition.file_path))?;
+            match language {
+                Language::JavaScript | Language::TypeScript => {
+                    self.javascript.get(&definition.name)
+                }
+                Language::Rust if self.within_rust_source(&definition.file_path) => {
+                    self.rust.get(&definition.name)
+                }
+                _ => None,
+            }
+            .map(|path| path.as_path())
+        })
+    }
+
+    fn within_rust_source(&self, path: &Path) -> bool {
+        self.rust_source
+            .as_ref()
+            .is_some_and(|source| normal(path).starts_with(normal(source)))
+    }
+
+    fn read_javascript(
+        &mut self,
+        root: &Path,
+        path: &Path,
+        entry_point: &Path,
+        visited: &mut HashSet<PathBuf>,
+    ) {
+        if !visited.insert(path.to_path_buf()) {
+            return;
+        }
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(_) => return,
+        };
+        let mut export = |name: &str| {
+            if name != "default" && is_identifier(name) {
+                self.javascript
+                    .entry(name.to_string())
+                    .or_insert_with(|| entry_point.to_path_buf());
+            }
+        };
+
+        let declaration = Regex::new(
+            r"(?m)^\s*export\s+(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(?:async\s+)?(?:function\s*\*?|class|const|let|var|interface|type|enum|namespace)\s+([A-Za-z_$][\w$]*)",
+        )
+        .unwrap();
+        for captures in declaration.captures_iter(&source) {
+            export(&captures[1]);
+        }
+
+        let list = Regex::new(r"export\s+(?:type\s+)?\{([^}]*)\}").unwrap();
+        let commonjs_list = Regex::new(r"module\.exports\s*=\s*\{([^}]*)\}").unwrap();
+        for captures in list
+            .captures_iter(&source)
+            .chain(commonjs_list.captures_iter(&source))
+        {
+            for item in captures[1].split(',') {
+                for name in item.split(|c: char| c == ':' || c.is_whitespace()) {
+                    if name != "as" && name != "type" {
+                        export(name);
+                    }
+                }
+            }
+        }
+
+        let assignment = Regex::new(
+            r"(?m)^\s*(?:module\.)?exports(?:\.([A-Za-z_$][\w$]*))?\s*=\s*([A-Za-z_$][\w$]*)?",
+        )
+        .unwrap();
+        // `exports.area = ...` exports `area`, and `module.exports = Shape` exports `Shape`
+        for captures in assignment.captures_iter(&source) {
+            match (captures.get(1), captures.get(2)) {
+                (Some(name), _) => export(name.as_str()),
+                (None, Some(name))
+                    if !["class", "function", "require"].contains(&name.as_str()) =>
+                {
+                    export(name.as_str())
+                }
+                _ => {}
+            }
+        }
+
+        let namespace = Regex::new(r"export\s+\*\s+as\s+([A-Za-z_$][\w$]*)").unwrap();
+        for captures in namespace.captures_iter(&source) {
+            export(&captures[1]);
+        }
+
+        let re_export = Regex::new(r#"export\s+\*\s+from\s*['"](\.[^'"]*)['"]"#).unwrap();
+        let modules: Vec<PathBuf> = re_export
+            .captures_iter(&source)
+            .filter_map(|captures| {
+                resolve_module(path.parent().unwrap_or(root), Path::new(&captures[1]))
+            })
+            .collect();
+        for module in modules {
+            self.read_javascript(root, &module, entry_point, visited);
+        }
+    }
+
+    fn read_rust(
+        &mut self,
+        path: &Path,
+        module_root: bool,
+        entry_point: &Path,
+        visited: &mut HashSet<PathBuf>,
+    ) {
+        if !visited.insert(path.to_path_buf()) {
+            return;
+        }
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(_) => return,
+        };
+
+        let item = Regex::new(
+            r#"(?m)^\s*pub\s+(?:(?:async|const|unsafe|extern\s+"[^"]*")\s+)*(?:fn|struct|enum|trait|type|const|static|union|mod)\s+(?:mut\s+)?([A-Za-z_]\w*)"#,
+        )
+        .unwrap();
+        for captures in item.captures_iter(&source) {
+            self.export_rust(&captures[1], entry_point);
+        }
+
+        let re_export = Regex::new(r"(?m)^\s*pub\s+use\s+([^;]+);").unwrap();
+        let mut globs = vec![];
+        for captures in re_export.captures_iter(&source) {
+            let (names, glob) = use_tree_names(&captures[1]);
+            for name in names {
+                self.export_rust(&name, entry_point);
+            }
+            globs.extend(glob);
+        }
+
+        let public_modules = Regex::new(r"(?m)^\s*pub\s+mod\s+([A-Za-z_]\w*)\s*;").unwrap();
+        let modules: Vec<Vec<String>> = public_modules
+            .captures_iter(&source)
+            .map(|captures| vec![captures[1].to_string()])
+            .chain(globs)
+            .collect();
+        for segments in modules {
+            if let Some(module) = resolve_rust_module(path, module_root, &segments) {
+                self.read_rust(&module, is_module_root(&module), entry_point, visited);
+            }
+        }
+    }
+
+    fn export_rust(&mut self, name: &str, entry_point: &Path) {
+        self.rust
+            .entry(name.to_string())
+            .or_insert_with(|| entry_point.to_path_buf());
+    }
+}
+
+// Entry point paths named in package.json, skipping `exports` subpath patterns
+fn package_entries(manifest: &Value) -> Vec<String> {
+    fn collect(value: &Value, entries: &mut Vec<String>) {
+        match value {
+            Value::String(entry) if !entry.contains('*') => entries.push(entry.clone()),
+            Value::Object(conditions) => conditions
+                .iter()
+                .filter(|(key, _)| !key.contains('*'))
+                .for_each(|(_, value)| collect(value, entries)),
+            Value::Array(values) => values.iter().for_each(|value| collect(value, entries)),
+            _ => {}
+        }
+    }
+
+    let mut entries = vec![];
+    for field in ["main", "module", "types", "typings", "exports"].iter() {
+        if let Some(value) = manifest.get(field) {
+            collect(value, &mut entries);
+        }
+    }
+    entries
+}
+
+// The source file of a module: the path itself, with another extension, as a directory's
+// `index`, or under `src` in place of a build directory
+fn resolve_module(base: &Path, module: &Path) -> Option<PathBuf> {
+    let mut candidates = vec![base.join(module)];
+    let mut components = module.strip_prefix(".").unwrap_or(module).components();
+    if let Some(Component::Normal(first)) = components.next() {
+        if BUILD_DIRECTORIES.iter().any(|dir| first == *dir) {
+            candidates.push(base.join("src").join(components.as_path()));
+        }
+    }
+
+    candidates.into_iter().find_map(|candidate| {
+        if candidate.is_file() && !is_declaration(&candidate) {
+            return Some(candidate);
+        }
+        let stem = strip_module_extension(&candidate);
+        MODULE_EXTENSIONS
+            .iter()
+            .map(|ext| PathBuf::from(format!("{}.{}", stem.display(), ext)))
+            .chain(
+                MODULE_EXTENSIONS
+                    .iter()
+                    .map(|ext| stem.join(format!("index.{}", ext))),
+            )
+            .find(|path| path.is_file())
+    })
+}
+
+fn strip_module_extension(path: &Path) -> PathBuf {
+    let display = path.to_string_lossy();
+    let stem = [".d.ts", ".d.mts", ".d.cts"]
+        .iter()
+        .find_map(|ext| display.strip_suffix(ext))
+        .map(PathBuf::from);
+
+    stem.unwrap_or_else(|| match path.extension() {
+        Some(ext) if MODULE_EXTENSIONS.iter().any(|e| ext == *e) => path.with_extension(""),
+        _ => path.to_path_buf(),
+    })
+}
+
+fn is_declaration(path: &Path) -> bool {
+    strip_module_extension(path) != path.with_extension("")
+}
+
+// Names a `pub use` tree makes public (including aliases), and the module path of a glob
+// re-export such as `pub use self::parser::*`
+fn use_tree_names(tree: &str) -> (Vec<String>, Option<Vec<String>>) {
+    let token = Regex::new(r"[A-Za-z_]\w*|\*|[{},]").unwrap();
+    let tokens: Vec<&str> = token.find_iter(tree).map(|m| m.as_str()).collect();
+    let mut names = vec![];
+    let mut glob = None;
+
+    for (index, token) in tokens.iter().enumerate() {
+        let next = tokens.get(index + 1).copied();
+        let previous = index.checked_sub(1).and_then(|i| tokens.get(i)).copied();
+        match *token {
+            "*" if tokens.len() == index + 1 && !tree.contains('{') => {
+                glob = Some(
+                    tokens[..index]
+                        .iter()
+                        .filter(|t| **t != "self" && **t != "crate")
+                        .map(|t| t.to_string())
+                        .collect(),
+                );
+            }
+            "self" | "super" | "crate" | "as" | "{" | "}" | "," | "*" => {}
+            name if previous == Some("as")
+                || matches!(next, None | Some(",") | Some("}") | Some("as")) =>
+            {
+                names.push(name.to_string())
+            }
+            _ => {}
+        }
+    }
+
+    (names, glob)
+}
+
+// The file of module `segments` declared within the module at `path`
+fn resolve_rust_module(path: &Path, module_root: bool, segments: &[String]) -> Option<PathBuf> {
+    let mut directory = if module_root {
+        path.parent()?.to_path_buf()
+    } else {
+        path.with_extension("")
+    };
+
+    let mut file = None;
+    for segment in segments {
+        let candidates = [
+            directory.join(format!("{}.rs", segment)),
+            directory.join(segment).join("mod.rs"),
+        ];
+        let found = candidates.iter().find(|c| c.is_file())?.clone();
+        directory = directory.join(segment);
+        file = Some(found);
+    }
+    file
+}
+
+fn is_module_root(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == "mod.rs" || name == "lib.rs" || name == "main.rs")
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+fn read_optional(path: &Path) -> io::Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn relative(root: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(root).unwrap_or(path).to_path_buf()
+}
+
+fn normal(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use token_search::Token;
+
+    fn result(name: &str, path: &str) -> TokenSearchResult {
+        let definitions: HashSet<_> = read_ctags::CtagItem::parse(
+            PathBuf::from("tags"),
+            &format!("{}\t{}\t1;\"\tf", name, path),
+        )
+        .unwrap()
+        .tags;
+
+        TokenSearchResult {
+            token: Token::new(name.to_string(), definitions),
+            occurrences: Default::default(),
+            mentions: Default::default(),
+            scopes: None,
+        }
+    }
+
+    #[test]
+    fn reads_exports_from_package_entry_points() {
+        let root = std::env::temp_dir().join("unused_package_exports");
+        fs::remove_dir_all(&root).ok();
+        fs::create_dir_all(root.join("src/parser")).unwrap();
+        fs::create_dir_all(root.join("src/format")).unwrap();
+        fs::write(
+            root.join("package.json"),
+            r#"{"name": "shapes", "main": "dist/index.js", "exports": {".": {"import": "./dist/index.mjs"}, "./*": "./dist/*.js"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/index.ts"),
+            "export function area() {}\nexport default class Shape {}\nexport { perimeter as edges, scale } from './geometry';\nexport * from './colors';\nfunction helper() {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/colors.ts"),
+            "export const RED = 1;\nconst internal = 2;\n",
+        )
+        .unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"shapes\"\n").unwrap();
+        fs::write(
+            root.join("src/lib.rs"),
+            "mod format;\npub mod parser;\npub use format::{render, Style as Theme};\npub use format::width::*;\n\npub fn parse() {}\npub(crate) fn tokenize() {}\nfn helper() {}\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/parser/mod.rs"), "pub struct Parser;\n").unwrap();
+        fs::write(
+            root.join("src/format.rs"),
+            "pub mod width;\npub fn render() {}\npub fn indent() {}\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/format/width.rs"), "pub fn measure() {}\n").unwrap();
+
+        let exports = PackageExports::load(&root).unwrap().unwrap();
+        assert_eq!(PackageExports::load(&root.join("missing")).unwrap(), None);
+        fs::remove_dir_all(&root).ok();
+
+        let entry_point = |name: &str, path: &str| {
+            exports
+                .entry_point(&result(name, path))
+                .map(|p| p.to_string_lossy().to_string())
+        };
+        for name in ["area", "Shape", "perimeter", "edges", "scale"] {
+            assert_eq!(
+                entry_point(name, "src/index.ts").as_deref(),
+                Some("src/index.ts"),
+                "{}",
+                name
+            );
+        }
+        assert_eq!(
+            entry_point("RED", "src/colors.ts").as_deref(),
+            Some("src/index.ts")
+        );
+        assert_eq!(entry_point("helper", "src/index.ts"), None);
+        assert_eq!(entry_point("internal", "src/colors.ts"), None);
+
+        for name in [
+            "parse", "parser", "Parser", "render", "Style", "Theme", "measure",
+        ] {
+            assert_eq!(
+                entry_point(name, "src/lib.rs").as_deref(),
+                Some("src/lib.rs"),
+                "{}",
+                name
+            );
+        }
+        assert_eq!(entry_point("tokenize", "src/lib.rs"), None);
+        assert_eq!(entry_point("indent", "src/format.rs"), None);
+        assert_eq!(entry_point("parse", "benches/parse.rs"), None);
+        assert_eq!(entry_point("area", "src/index.rb"), None);
+    }
+}
@@ -1,4 +1,7 @@
+use super::package_exports::PackageExports;
+use super::rails_routes::RailsRoutes;
 use super::value_assertion::{Assertion, AssertionConflict};
+use read_ctags::TokenKind;
 use std::path::Path;
 use token_search::{TokenSearchResult, TokenSearchResults};
 
@@ -10,6 +13,17 @@ pub struct ProjectConfiguration {
     pub config_file: Vec<PathPrefix>,
     pub low_likelihood: Vec<LowLikelihoodConfig>,
     pub matches_if: Vec<Assertion>,
+    pub ignore_from: Vec<String>,
+    /// Rails routes reaching controller actions, read from the project rather than the
+    /// configuration file
+    pub routes: RailsRoutes,
+    /// Tokens exported from the package's entry points, read from its manifests
+    pub exports: PackageExports,
+    /// Whether tokens referenced only in documentation (Markdown and RDoc files, and comments
+    /// such as YARD tags) are reported under their own rule, with `--doc-references`
+    pub doc_references: bool,
+    /// Points added to likelihood scores by kind, under `kind_weights` in the project settings
+    pub kind_weights: Vec<(TokenKind, i32)>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -42,7 +56,7 @@ impl LowLikelihoodConfig {
             Self::build_conflicts(self.token_assertions()).map(AssertionConflict::TokenConflict),
         ]
         .into_iter()
-        .filter_map(|v| v)
+        .flatten()
         .collect()
     }
 
@@ -57,7 +71,7 @@ impl LowLikelihoodConfig {
             .filter(|m| !m.matcher().full_equals())
             .collect();
 
-        if equals_assertions.len() > 0 && partial_equals_assertions.len() > 0 {
+        if !equals_assertions.is_empty() && !partial_equals_assertions.is_empty() {
             let mut results = equals_assertions.clone();
             results.extend(partial_equals_assertions.clone());
             Some(results.into_iter().map(|v| v.to_owned()).collect())
@@ -87,8 +101,8 @@ impl LowLikelihoodConfig {
     }
 }
 
-impl ProjectConfiguration {
-    pub fn default() -> Self {
+impl Default for ProjectConfiguration {
+    fn default() -> Self {
         ProjectConfiguration {
             name: "Default".to_string(),
             application_file: vec![PathPrefix::new("src/"), PathPrefix::new("lib/")],
@@ -96,9 +110,16 @@ impl ProjectConfiguration {
             config_file: vec![],
             low_likelihood: vec![],
             matches_if: vec![],
+            ignore_from: vec![],
+            routes: RailsRoutes::default(),
+            exports: PackageExports::default(),
+            doc_references: false,
+            kind_weights: vec![],
         }
     }
+}
 
+impl ProjectConfiguration {
     pub fn low_likelihood_match(
         &self,
         token_search_result: &TokenSearchResult,
@@ -154,7 +175,7 @@ mod tests {
         let ends_with = ValueMatcher::EndsWith("o".to_string());
         let equals = ValueMatcher::Equals("foo".to_string());
         let exact_match = ValueMatcher::ExactMatchOnAnyOf(
-            vec![String::from("foo"), String::from("bar")]
+            [String::from("foo"), String::from("bar")]
                 .iter()
                 .cloned()
                 .collect(),
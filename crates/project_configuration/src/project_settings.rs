@@ -0,0 +1,1134 @@
+use super::ignore_list::IgnoreList;
+use codebase_files::writes::Writes;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use yaml_rust::{yaml, Yaml, YamlEmitter, YamlLoader};
+
+/// File names checked for project settings, relative to the project root
+pub const PROJECT_SETTINGS_FILES: [&str; 2] = [".unused.yml", "unused.toml"];
+
+/// Directories of vendored dependencies, whose definitions are never reported by default
+pub const VENDORED_PATHS: [&str; 3] = ["vendor/", "node_modules/", "third_party/"];
+
+const TOP_LEVEL_KEYS: [&str; 15] = [
+    "ignore",
+    "ignore_paths",
+    "deprecated",
+    "allowed_tokens",
+    "denied_tokens",
+    "languages",
+    "language_mappings",
+    "output",
+    "cache",
+    "roots",
+    "projects",
+    "serve",
+    "severities",
+    "kind_weights",
+    "budgets",
+];
+const IGNORE_KEYS: [&str; 3] = ["tokens", "tokens_matching", "paths"];
+const DEPRECATED_KEYS: [&str; 2] = ["tokens", "from"];
+const LANGUAGE_KEYS: [&str; 1] = ["enabled"];
+const CACHE_KEYS: [&str; 2] = ["ttl", "negative_ttl"];
+const SERVE_KEYS: [&str; 1] = ["full_rescan"];
+const ROOTS_KEYS: [&str; 4] = [
+    "exclude_definitions",
+    "exclude_usages",
+    "definitions",
+    "usages",
+];
+const ROOT_GLOBS_KEYS: [&str; 2] = ["include", "exclude"];
+const PROJECT_KEYS: [&str; 4] = ["root", "tags", "languages", "ignore"];
+const OUTPUT_KEYS: [&str; 7] = [
+    "format",
+    "likelihoods",
+    "sort_order",
+    "group_by",
+    "reverse",
+    "no_summary",
+    "no_progress",
+];
+
+/// Settings checked into a project, applied beneath any flags passed on the command line
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProjectSettings {
+    pub path: Option<PathBuf>,
+    pub ignore: IgnoreList,
+    /// Gitignore-style globs for paths that are neither searched nor reported
+    pub ignore_paths: Vec<String>,
+    pub deprecated: DeprecatedTokens,
+    pub token_policy: TokenPolicy,
+    pub languages: BTreeMap<String, LanguageSettings>,
+    /// File name patterns (e.g. `*.jbuilder`) and the language of matching files, in the order
+    /// written; languages are kept as written so the CLI can validate them
+    pub language_mappings: Vec<(String, String)>,
+    pub output: OutputDefaults,
+    /// Finding categories (e.g. `test-only`) and the severity they're reported with, kept as
+    /// written so the CLI can validate them
+    pub severities: BTreeMap<String, String>,
+    /// Kinds (e.g. `class`) and the points added to the likelihood scores of tokens of that
+    /// kind, kept as written so the CLI can validate them
+    pub kind_weights: BTreeMap<String, i64>,
+    /// Findings tolerated in paths matching each glob, in the order written
+    pub budgets: Vec<FindingsBudget>,
+    pub cache: CacheSettings,
+    pub serve: ServeSettings,
+    pub roots: SearchRoots,
+    pub projects: BTreeMap<String, SubProject>,
+}
+
+/// Tokens whose remaining usages are tracked, listed inline or in files of one token per line
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeprecatedTokens {
+    pub tokens: Vec<String>,
+    pub from: Vec<String>,
+}
+
+/// Tokens that are never reported (`allowed_tokens`) or always reported (`denied_tokens`),
+/// regardless of their usage, each with an optional reason shown alongside the finding
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TokenPolicy {
+    pub allowed: BTreeMap<String, Option<String>>,
+    pub denied: BTreeMap<String, Option<String>>,
+}
+
+/// How a token listed in a `TokenPolicy` is treated, with the reason given in the settings
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TokenDecision<'a> {
+    Allowed(Option<&'a str>),
+    Denied(Option<&'a str>),
+}
+
+impl TokenPolicy {
+    pub fn decide(&self, token: &str) -> Option<TokenDecision<'_>> {
+        if let Some(reason) = self.allowed.get(token) {
+            Some(TokenDecision::Allowed(reason.as_deref()))
+        } else {
+            self.denied
+                .get(token)
+                .map(|reason| TokenDecision::Denied(reason.as_deref()))
+        }
+    }
+}
+
+/// Settings for tokens defined in files of a single language, keyed by file extension
+#[derive(Clone, Debug, PartialEq)]
+pub struct LanguageSettings {
+    pub enabled: bool,
+}
+
+/// Findings tolerated in paths matching a gitignore-style glob (such as `app/legacy/**`), under
+/// `budgets`
+///
+/// A budget is either a number of findings (`app/legacy/**: 500`), or numbers by severity
+/// (`app/legacy/**: { error: 0, warning: 500 }`). The glob and severities are kept as written
+/// so the CLI can validate them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FindingsBudget {
+    pub glob: String,
+    pub findings: Option<usize>,
+    pub severities: BTreeMap<String, usize>,
+}
+
+/// Output settings used when the corresponding flag isn't provided
+///
+/// Values are kept as written so the CLI can validate them against its own flag values.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OutputDefaults {
+    pub format: Option<String>,
+    pub likelihoods: Vec<String>,
+    pub sort_order: Option<String>,
+    pub group_by: Option<String>,
+    pub reverse: bool,
+    pub no_summary: bool,
+    pub no_progress: bool,
+}
+
+/// How long results from external sources, such as remote ignore lists, are cached
+///
+/// Durations are kept as written (e.g. `24h`) so the CLI can validate them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CacheSettings {
+    pub ttl: Option<String>,
+    pub negative_ttl: Option<String>,
+}
+
+/// When `unused serve` rescans from scratch rather than reusing per-file scans
+///
+/// The schedule is kept as written (e.g. `0 3 * * *`) so the CLI can validate it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ServeSettings {
+    pub full_rescan: Option<String>,
+}
+
+/// Where definitions can be reported from ("definition roots") and where usages are counted
+/// ("usage roots"), each given as path prefixes excluded from the codebase
+///
+/// Vendored dependencies are excluded as definition roots but remain usage roots, so their calls
+/// into the project still count as evidence.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchRoots {
+    pub exclude_definitions: Vec<String>,
+    pub exclude_usages: Vec<String>,
+    /// Globs selecting where definitions can be reported from, under `roots.definitions`
+    pub definitions: RootGlobs,
+    /// Globs selecting where occurrences count as usages, under `roots.usages`
+    pub usages: RootGlobs,
+    /// Directory all roots are confined to, such as a sub-project's root
+    pub within: Option<PathBuf>,
+}
+
+/// Gitignore-style globs including and excluding paths in one role (definitions or usages),
+/// kept as written so the CLI can compile them alongside any passed as flags
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RootGlobs {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+/// A project within a monorepo, analyzed on its own and reported alongside its siblings
+///
+/// Paths are relative to the directory containing the settings file. Languages are kept as
+/// written (file extensions) so the CLI can validate them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubProject {
+    pub root: PathBuf,
+    pub tags: Option<PathBuf>,
+    pub languages: Vec<String>,
+    pub ignore: IgnoreList,
+}
+
+impl Default for SearchRoots {
+    fn default() -> Self {
+        SearchRoots {
+            exclude_definitions: VENDORED_PATHS.iter().map(|p| p.to_string()).collect(),
+            exclude_usages: vec![],
+            definitions: RootGlobs::default(),
+            usages: RootGlobs::default(),
+            within: None,
+        }
+    }
+}
+
+impl SearchRoots {
+    /// Whether definitions within `path` can be reported
+    pub fn is_definition_root(&self, path: &Path) -> bool {
+        self.confines(path) && !within(&self.exclude_definitions, path)
+    }
+
+    /// Whether occurrences within `path` count as usages
+    pub fn is_usage_root(&self, path: &Path) -> bool {
+        self.confines(path) && !within(&self.exclude_usages, path)
+    }
+
+    /// Confine roots to `directory`, where exclusions also apply relative to `directory`
+    pub fn confined_to(&self, directory: &Path) -> Self {
+        let nested = |prefixes: &[String]| -> Vec<String> {
+            prefixes
+                .iter()
+                .cloned()
+                .chain(
+                    prefixes
+                        .iter()
+                        .map(|p| directory.join(p).to_string_lossy().to_string()),
+                )
+                .collect()
+        };
+
+        SearchRoots {
+            exclude_definitions: nested(&self.exclude_definitions),
+            exclude_usages: nested(&self.exclude_usages),
+            definitions: self.definitions.clone(),
+            usages: self.usages.clone(),
+            within: Some(directory.to_path_buf()),
+        }
+    }
+
+    fn confines(&self, path: &Path) -> bool {
+        self.within.as_ref().is_none_or(|directory| {
+            path.strip_prefix("./")
+                .unwrap_or(path)
+                .starts_with(directory.strip_prefix("./").unwrap_or(directory))
+        })
+    }
+}
+
+fn within(prefixes: &[String], path: &Path) -> bool {
+    let path = path.strip_prefix("./").unwrap_or(path);
+
+    prefixes.iter().any(|prefix| path.starts_with(prefix))
+}
+
+impl Default for LanguageSettings {
+    fn default() -> Self {
+        LanguageSettings { enabled: true }
+    }
+}
+
+impl ProjectSettings {
+    /// Load settings from `.unused.yml` or `unused.toml` within `root`, if either exists
+    pub fn load(root: &Path) -> Result<Self, String> {
+        let found: Vec<PathBuf> = PROJECT_SETTINGS_FILES
+            .iter()
+            .map(|name| root.join(name))
+            .filter(|path| path.is_file())
+            .collect();
+
+        match &found[..] {
+            [] => Ok(ProjectSettings::default()),
+            [path] => {
+                let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+                let parsed = if path.extension().is_some_and(|e| e == "toml") {
+                    Self::parse_toml(&contents)
+                } else {
+                    Self::parse_yaml(&contents)
+                };
+
+                parsed
+                    .map(|settings| ProjectSettings {
+                        path: Some(path.to_path_buf()),
+                        ..settings
+                    })
+                    .map_err(|e| format!("{}: {}", path.display(), e))
+            }
+            _ => Err(format!(
+                "found both {} and {}; keep only one",
+                PROJECT_SETTINGS_FILES[0], PROJECT_SETTINGS_FILES[1]
+            )),
+        }
+    }
+
+    /// Add tokens and paths to `ignore` in the settings file within `root`, creating
+    /// `.unused.yml` if neither settings file exists
+    ///
+    /// The file is rewritten from its parsed contents, so comments and formatting aren't kept.
+    pub fn add_ignores(
+        root: &Path,
+        ignores: &IgnoreList,
+        writes: Writes,
+    ) -> Result<PathBuf, String> {
+        let path = PROJECT_SETTINGS_FILES
+            .iter()
+            .map(|name| root.join(name))
+            .find(|path| path.is_file())
+            .unwrap_or_else(|| root.join(PROJECT_SETTINGS_FILES[0]));
+        let contents = fs::read_to_string(&path).unwrap_or_default();
+        let updated = if path.extension().is_some_and(|e| e == "toml") {
+            add_toml_ignores(&contents, ignores)
+        } else {
+            add_yaml_ignores(&contents, ignores)
+        };
+
+        updated
+            .and_then(|updated| writes.write(&path, updated).map_err(|e| e.to_string()))
+            .map(|_| path.to_path_buf())
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Names of languages disabled with `enabled: false`
+    pub fn disabled_languages(&self) -> Vec<&str> {
+        self.languages
+            .iter()
+            .filter(|(_, settings)| !settings.enabled)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    pub fn parse_yaml(contents: &str) -> Result<Self, String> {
+        match YamlLoader::load_from_str(contents) {
+            Ok(docs) => match &docs[..] {
+                [doc @ Yaml::Hash(_)] => Self::from_yaml(doc),
+                [] => Ok(ProjectSettings::default()),
+                _ => Err("expected a mapping of settings".to_string()),
+            },
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    pub fn parse_toml(contents: &str) -> Result<Self, String> {
+        let value = contents.parse::<toml::Value>().map_err(|e| e.to_string())?;
+
+        Self::from_yaml(&toml_to_yaml(value))
+    }
+
+    fn from_yaml(contents: &Yaml) -> Result<Self, String> {
+        check_keys("", contents, &TOP_LEVEL_KEYS)?;
+
+        let ignore = &contents["ignore"];
+        check_keys("ignore", ignore, &IGNORE_KEYS)?;
+
+        let deprecated = &contents["deprecated"];
+        check_keys("deprecated", deprecated, &DEPRECATED_KEYS)?;
+
+        let output = &contents["output"];
+        check_keys("output", output, &OUTPUT_KEYS)?;
+
+        let cache = &contents["cache"];
+        check_keys("cache", cache, &CACHE_KEYS)?;
+
+        let serve = &contents["serve"];
+        check_keys("serve", serve, &SERVE_KEYS)?;
+
+        let roots = &contents["roots"];
+        check_keys("roots", roots, &ROOTS_KEYS)?;
+
+        let token_policy = TokenPolicy {
+            allowed: token_reasons("allowed_tokens", &contents["allowed_tokens"])?,
+            denied: token_reasons("denied_tokens", &contents["denied_tokens"])?,
+        };
+        if let Some(token) = token_policy
+            .allowed
+            .keys()
+            .find(|t| token_policy.denied.contains_key(*t))
+        {
+            return Err(format!(
+                "{} is listed in both allowed_tokens and denied_tokens",
+                token
+            ));
+        }
+
+        Ok(ProjectSettings {
+            path: None,
+            ignore: IgnoreList {
+                tokens: strings("ignore.tokens", &ignore["tokens"])?,
+                tokens_matching: patterns("ignore.tokens_matching", &ignore["tokens_matching"])?,
+                paths: strings("ignore.paths", &ignore["paths"])?,
+            },
+            ignore_paths: strings("ignore_paths", &contents["ignore_paths"])?,
+            deprecated: DeprecatedTokens {
+                tokens: strings("deprecated.tokens", &deprecated["tokens"])?,
+                from: strings("deprecated.from", &deprecated["from"])?,
+            },
+            token_policy,
+            languages: Self::parse_languages(&contents["languages"])?,
+            language_mappings: Self::parse_language_mappings(&contents["language_mappings"])?,
+            output: OutputDefaults {
+                format: string("output.format", &output["format"])?,
+                likelihoods: strings("output.likelihoods", &output["likelihoods"])?,
+                sort_order: string("output.sort_order", &output["sort_order"])?,
+                group_by: string("output.group_by", &output["group_by"])?,
+                reverse: boolean("output.reverse", &output["reverse"])?.unwrap_or(false),
+                no_summary: boolean("output.no_summary", &output["no_summary"])?.unwrap_or(false),
+                no_progress: boolean("output.no_progress", &output["no_progress"])?
+                    .unwrap_or(false),
+            },
+            severities: Self::parse_severities(&contents["severities"])?,
+            kind_weights: Self::parse_kind_weights(&contents["kind_weights"])?,
+            budgets: Self::parse_budgets(&contents["budgets"])?,
+            cache: CacheSettings {
+                ttl: string("cache.ttl", &cache["ttl"])?,
+                negative_ttl: string("cache.negative_ttl", &cache["negative_ttl"])?,
+            },
+            serve: ServeSettings {
+                full_rescan: string("serve.full_rescan", &serve["full_rescan"])?,
+            },
+            roots: SearchRoots {
+                exclude_definitions: match &roots["exclude_definitions"] {
+                    Yaml::BadValue => SearchRoots::default().exclude_definitions,
+                    paths => strings("roots.exclude_definitions", paths)?,
+                },
+                exclude_usages: strings("roots.exclude_usages", &roots["exclude_usages"])?,
+                definitions: root_globs("roots.definitions", &roots["definitions"])?,
+                usages: root_globs("roots.usages", &roots["usages"])?,
+                within: None,
+            },
+            projects: Self::parse_projects(&contents["projects"])?,
+        })
+    }
+
+    // Each project's root defaults to a directory named after it
+    fn parse_projects(contents: &Yaml) -> Result<BTreeMap<String, SubProject>, String> {
+        match contents {
+            Yaml::BadValue => Ok(BTreeMap::new()),
+            Yaml::Hash(projects) => projects
+                .iter()
+                .map(|(name, settings)| {
+                    let name = name
+                        .as_str()
+                        .ok_or_else(|| "projects: expected project names as keys".to_string())?;
+                    let key = format!("projects.{}", name);
+                    check_keys(&key, settings, &PROJECT_KEYS)?;
+                    let ignore = &settings["ignore"];
+                    check_keys(&format!("{}.ignore", key), ignore, &IGNORE_KEYS)?;
+
+                    Ok((
+                        name.to_string(),
+                        SubProject {
+                            root: PathBuf::from(
+                                string(&format!("{}.root", key), &settings["root"])?
+                                    .unwrap_or_else(|| name.to_string()),
+                            ),
+                            tags: string(&format!("{}.tags", key), &settings["tags"])?
+                                .map(PathBuf::from),
+                            languages: strings(
+                                &format!("{}.languages", key),
+                                &settings["languages"],
+                            )?,
+                            ignore: IgnoreList {
+                                tokens: strings(
+                                    &format!("{}.ignore.tokens", key),
+                                    &ignore["tokens"],
+                                )?,
+                                tokens_matching: patterns(
+                                    &format!("{}.ignore.tokens_matching", key),
+                                    &ignore["tokens_matching"],
+                                )?,
+                                paths: strings(&format!("{}.ignore.paths", key), &ignore["paths"])?,
+                            },
+                        },
+                    ))
+                })
+                .collect(),
+            _ => Err("projects: expected a mapping".to_string()),
+        }
+    }
+
+    fn parse_language_mappings(contents: &Yaml) -> Result<Vec<(String, String)>, String> {
+        match contents {
+            Yaml::BadValue => Ok(vec![]),
+            Yaml::Hash(mappings) => mappings
+                .iter()
+                .map(|(pattern, language)| {
+                    let pattern = pattern.as_str().ok_or_else(|| {
+                        "language_mappings: expected file name patterns as keys".to_string()
+                    })?;
+                    let language = string(&format!("language_mappings.{}", pattern), language)?
+                        .ok_or_else(|| {
+                            format!("language_mappings.{}: expected a language", pattern)
+                        })?;
+
+                    Ok((pattern.to_string(), language))
+                })
+                .collect(),
+            _ => Err("language_mappings: expected a mapping".to_string()),
+        }
+    }
+
+    fn parse_severities(contents: &Yaml) -> Result<BTreeMap<String, String>, String> {
+        match contents {
+            Yaml::BadValue => Ok(BTreeMap::new()),
+            Yaml::Hash(severities) => severities
+                .iter()
+                .map(|(category, severity)| {
+                    let category = category
+                        .as_str()
+                        .ok_or_else(|| "severities: expected categories as keys".to_string())?;
+                    let severity = string(&format!("severities.{}", category), severity)?
+                        .ok_or_else(|| format!("severities.{}: expected a severity", category))?;
+
+                    Ok((category.to_string(), severity))
+                })
+                .collect(),
+            _ => Err("severities: expected a mapping".to_string()),
+        }
+    }
+
+    fn parse_kind_weights(contents: &Yaml) -> Result<BTreeMap<String, i64>, String> {
+        match contents {
+            Yaml::BadValue => Ok(BTreeMap::new()),
+            Yaml::Hash(weights) => weights
+                .iter()
+                .map(|(kind, weight)| {
+                    let kind = kind
+                        .as_str()
+                        .ok_or_else(|| "kind_weights: expected kinds as keys".to_string())?;
+                    let weight = weight
+                        .as_i64()
+                        .ok_or_else(|| format!("kind_weights.{}: expected a number", kind))?;
+
+                    Ok((kind.to_string(), weight))
+                })
+                .collect(),
+            _ => Err("kind_weights: expected a mapping".to_string()),
+        }
+    }
+
+    fn parse_budgets(contents: &Yaml) -> Result<Vec<FindingsBudget>, String> {
+        let count = |key: &str, value: &Yaml| {
+            value
+                .as_i64()
+                .filter(|n| *n >= 0)
+                .map(|n| n as usize)
+                .ok_or_else(|| format!("{}: expected a number of findings", key))
+        };
+
+        match contents {
+            Yaml::BadValue => Ok(vec![]),
+            Yaml::Hash(budgets) => budgets
+                .iter()
+                .map(|(glob, budget)| {
+                    let glob = glob
+                        .as_str()
+                        .ok_or_else(|| "budgets: expected globs as keys".to_string())?;
+                    let key = format!("budgets.{}", glob);
+                    let (findings, severities) = match budget {
+                        Yaml::Hash(severities) => (
+                            None,
+                            severities
+                                .iter()
+                                .map(|(severity, limit)| {
+                                    let severity = severity.as_str().ok_or_else(|| {
+                                        format!("{}: expected severities as keys", key)
+                                    })?;
+                                    let limit = count(&format!("{}.{}", key, severity), limit)?;
+                                    Ok((severity.to_string(), limit))
+                                })
+                                .collect::<Result<_, String>>()?,
+                        ),
+                        limit => (Some(count(&key, limit)?), BTreeMap::new()),
+                    };
+
+                    Ok(FindingsBudget {
+                        glob: glob.to_string(),
+                        findings,
+                        severities,
+                    })
+                })
+                .collect(),
+            _ => Err("budgets: expected a mapping".to_string()),
+        }
+    }
+
+    fn parse_languages(contents: &Yaml) -> Result<BTreeMap<String, LanguageSettings>, String> {
+        match contents {
+            Yaml::BadValue => Ok(BTreeMap::new()),
+            Yaml::Hash(languages) => languages
+                .iter()
+                .map(|(name, settings)| {
+                    let name = name
+                        .as_str()
+                        .ok_or_else(|| "languages: expected file extensions as keys".to_string())?;
+                    let key = format!("languages.{}", name);
+                    check_keys(&key, settings, &LANGUAGE_KEYS)?;
+
+                    Ok((
+                        name.to_lowercase(),
+                        LanguageSettings {
+                            enabled: boolean(&format!("{}.enabled", key), &settings["enabled"])?
+                                .unwrap_or(true),
+                        },
+                    ))
+                })
+                .collect(),
+            _ => Err("languages: expected a mapping".to_string()),
+        }
+    }
+}
+
+fn check_keys(section: &str, contents: &Yaml, supported: &[&str]) -> Result<(), String> {
+    match contents {
+        Yaml::BadValue => Ok(()),
+        Yaml::Hash(items) => match items
+            .keys()
+            .find(|k| !k.as_str().is_some_and(|k| supported.contains(&k)))
+        {
+            Some(key) => Err(format!(
+                "unknown setting {}{} (expected one of: {})",
+                if section.is_empty() {
+                    String::new()
+                } else {
+                    format!("{}.", section)
+                },
+                key.as_str().unwrap_or("?"),
+                supported.join(", ")
+            )),
+            None => Ok(()),
+        },
+        _ => Err(format!("{}: expected a mapping", section)),
+    }
+}
+
+fn string(key: &str, contents: &Yaml) -> Result<Option<String>, String> {
+    match contents {
+        Yaml::BadValue => Ok(None),
+        Yaml::String(value) => Ok(Some(value.to_string())),
+        _ => Err(format!("{}: expected a string", key)),
+    }
+}
+
+fn root_globs(key: &str, contents: &Yaml) -> Result<RootGlobs, String> {
+    check_keys(key, contents, &ROOT_GLOBS_KEYS)?;
+
+    Ok(RootGlobs {
+        include: strings(&format!("{}.include", key), &contents["include"])?,
+        exclude: strings(&format!("{}.exclude", key), &contents["exclude"])?,
+    })
+}
+
+fn strings(key: &str, contents: &Yaml) -> Result<Vec<String>, String> {
+    match contents {
+        Yaml::BadValue => Ok(vec![]),
+        Yaml::String(value) => Ok(vec![value.to_string()]),
+        Yaml::Array(items) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .map(String::from)
+                    .ok_or_else(|| format!("{}: expected a list of strings", key))
+            })
+            .collect(),
+        _ => Err(format!("{}: expected a list of strings", key)),
+    }
+}
+
+// Tokens are listed on their own, or as a mapping from each token to the reason it's listed
+fn patterns(key: &str, contents: &Yaml) -> Result<Vec<String>, String> {
+    let patterns = strings(key, contents)?;
+    IgnoreList::validate_patterns(key, &patterns)?;
+    Ok(patterns)
+}
+
+fn token_reasons(key: &str, contents: &Yaml) -> Result<BTreeMap<String, Option<String>>, String> {
+    match contents {
+        Yaml::Hash(items) => items
+            .iter()
+            .map(|(token, reason)| {
+                let token = token
+                    .as_str()
+                    .ok_or_else(|| format!("{}: expected tokens as keys", key))?;
+                let reason = match reason {
+                    Yaml::Null => None,
+                    Yaml::String(reason) => Some(reason.to_string()),
+                    _ => return Err(format!("{}.{}: expected a reason", key, token)),
+                };
+
+                Ok((token.to_string(), reason))
+            })
+            .collect(),
+        _ => Ok(strings(key, contents)?
+            .into_iter()
+            .map(|token| (token, None))
+            .collect()),
+    }
+}
+
+fn boolean(key: &str, contents: &Yaml) -> Result<Option<bool>, String> {
+    match contents {
+        Yaml::BadValue => Ok(None),
+        Yaml::Boolean(value) => Ok(Some(*value)),
+        _ => Err(format!("{}: expected true or false", key)),
+    }
+}
+
+fn add_yaml_ignores(contents: &str, ignores: &IgnoreList) -> Result<String, String> {
+    let mut settings = match YamlLoader::load_from_str(contents)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .next()
+    {
+        Some(Yaml::Hash(settings)) => settings,
+        None => yaml::Hash::new(),
+        Some(_) => return Err("expected a mapping of settings".to_string()),
+    };
+    let ignore = match settings
+        .entry(Yaml::String("ignore".to_string()))
+        .or_insert_with(|| Yaml::Hash(yaml::Hash::new()))
+    {
+        Yaml::Hash(ignore) => ignore,
+        _ => return Err("ignore: expected a mapping".to_string()),
+    };
+
+    for (key, values) in [("tokens", &ignores.tokens), ("paths", &ignores.paths)] {
+        if values.is_empty() {
+            continue;
+        }
+
+        let mut entries = strings(
+            &format!("ignore.{}", key),
+            ignore
+                .get(&Yaml::String(key.to_string()))
+                .unwrap_or(&Yaml::BadValue),
+        )?;
+        for value in values {
+            if !entries.contains(value) {
+                entries.push(value.to_string());
+            }
+        }
+        ignore.insert(
+            Yaml::String(key.to_string()),
+            Yaml::Array(entries.into_iter().map(Yaml::String).collect()),
+        );
+    }
+
+    let mut output = String::new();
+    YamlEmitter::new(&mut output)
+        .dump(&Yaml::Hash(settings))
+        .map_err(|e| format!("{:?}", e))?;
+
+    Ok(format!("{}\n", output.trim_start_matches("---\n")))
+}
+
+fn add_toml_ignores(contents: &str, ignores: &IgnoreList) -> Result<String, String> {
+    let mut settings = contents.parse::<toml::Value>().map_err(|e| e.to_string())?;
+    let ignore = settings
+        .as_table_mut()
+        .ok_or_else(|| "expected a table of settings".to_string())?
+        .entry("ignore")
+        .or_insert_with(|| toml::Value::Table(toml::value::Table::new()))
+        .as_table_mut()
+        .ok_or_else(|| "ignore: expected a table".to_string())?;
+
+    for (key, values) in [("tokens", &ignores.tokens), ("paths", &ignores.paths)] {
+        if values.is_empty() {
+            continue;
+        }
+
+        let mut entries = match ignore.get(key).cloned().map(toml_to_yaml) {
+            Some(value) => strings(&format!("ignore.{}", key), &value)?,
+            None => vec![],
+        };
+        for value in values {
+            if !entries.contains(value) {
+                entries.push(value.to_string());
+            }
+        }
+        ignore.insert(
+            key.to_string(),
+            toml::Value::Array(entries.into_iter().map(toml::Value::String).collect()),
+        );
+    }
+
+    toml::to_string(&settings).map_err(|e| e.to_string())
+}
+
+// TOML settings share the YAML parser so both formats support exactly the same keys
+fn toml_to_yaml(value: toml::Value) -> Yaml {
+    match value {
+        toml::Value::String(v) => Yaml::String(v),
+        toml::Value::Integer(v) => Yaml::Integer(v),
+        toml::Value::Float(v) => Yaml::Real(v.to_string()),
+        toml::Value::Boolean(v) => Yaml::Boolean(v),
+        toml::Value::Datetime(v) => Yaml::String(v.to_string()),
+        toml::Value::Array(items) => Yaml::Array(items.into_iter().map(toml_to_yaml).collect()),
+        toml::Value::Table(table) => {
+            let mut hash = yaml::Hash::new();
+            for (k, v) in table {
+                hash.insert(Yaml::String(k), toml_to_yaml(v));
+            }
+            Yaml::Hash(hash)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_equivalent_yaml_and_toml() {
+        let yaml = "
+ignore:
+  tokens:
+    - ApplicationJob
+  tokens_matching:
+    - ^Test
+  paths:
+    - vendor/
+deprecated:
+  tokens: [legacy_total]
+  from: config/deprecated.txt
+languages:
+  ex:
+    enabled: false
+output:
+  format: json
+  likelihoods: [high, medium]
+  no_progress: true
+";
+        let toml = "
+[ignore]
+tokens = [\"ApplicationJob\"]
+tokens_matching = [\"^Test\"]
+paths = [\"vendor/\"]
+
+[deprecated]
+tokens = [\"legacy_total\"]
+from = [\"config/deprecated.txt\"]
+
+[languages.ex]
+enabled = false
+
+[output]
+format = \"json\"
+likelihoods = [\"high\", \"medium\"]
+no_progress = true
+";
+
+        let settings = ProjectSettings::parse_yaml(yaml).unwrap();
+
+        assert_eq!(ProjectSettings::parse_toml(toml), Ok(settings.clone()));
+        assert_eq!(settings.ignore.tokens, vec![String::from("ApplicationJob")]);
+        assert_eq!(
+            settings.deprecated.from,
+            vec![String::from("config/deprecated.txt")]
+        );
+        assert_eq!(settings.disabled_languages(), vec!["ex"]);
+        assert_eq!(settings.output.format, Some(String::from("json")));
+        assert_eq!(
+            settings.output.likelihoods,
+            vec![String::from("high"), String::from("medium")]
+        );
+        assert!(settings.output.no_progress);
+        assert!(!settings.output.no_summary);
+    }
+
+    #[test]
+    fn parses_allowed_and_denied_tokens_with_reasons() {
+        let yaml = "
+allowed_tokens:
+  ApplicationJob: Loaded by Rails
+denied_tokens:
+  - legacy_total
+";
+        let toml = "
+denied_tokens = [\"legacy_total\"]
+
+[allowed_tokens]
+ApplicationJob = \"Loaded by Rails\"
+";
+
+        let settings = ProjectSettings::parse_yaml(yaml).unwrap();
+        let policy = &settings.token_policy;
+
+        assert_eq!(ProjectSettings::parse_toml(toml), Ok(settings.clone()));
+        assert_eq!(
+            policy.decide("ApplicationJob"),
+            Some(TokenDecision::Allowed(Some("Loaded by Rails")))
+        );
+        assert_eq!(
+            policy.decide("legacy_total"),
+            Some(TokenDecision::Denied(None))
+        );
+        assert_eq!(policy.decide("Person"), None);
+        assert_eq!(
+            ProjectSettings::parse_yaml("allowed_tokens: [Person]\ndenied_tokens: [Person]\n"),
+            Err(String::from(
+                "Person is listed in both allowed_tokens and denied_tokens"
+            ))
+        );
+    }
+
+    #[test]
+    fn separates_definition_and_usage_roots() {
+        let defaults = ProjectSettings::parse_yaml("ignore:\n  tokens: [Person]\n")
+            .unwrap()
+            .roots;
+
+        assert!(!defaults.is_definition_root(Path::new("vendor/bundle/gems/rake.rb")));
+        assert!(!defaults.is_definition_root(Path::new("./third_party/zlib/zlib.h")));
+        assert!(defaults.is_definition_root(Path::new("app/vendors/payment.rb")));
+        assert!(defaults.is_usage_root(Path::new("vendor/bundle/gems/rake.rb")));
+
+        let roots = ProjectSettings::parse_yaml(
+            "roots:\n  exclude_definitions: [generated/]\n  exclude_usages: [vendor/]\n",
+        )
+        .unwrap()
+        .roots;
+
+        assert!(roots.is_definition_root(Path::new("vendor/bundle/gems/rake.rb")));
+        assert!(!roots.is_definition_root(Path::new("generated/schema.rb")));
+        assert!(!roots.is_usage_root(Path::new("vendor/bundle/gems/rake.rb")));
+
+        let globs = ProjectSettings::parse_yaml(
+            "roots:\n  definitions:\n    include: ['app/**']\n  usages:\n    exclude: [db/schema.rb]\n",
+        )
+        .unwrap()
+        .roots;
+
+        assert_eq!(globs.definitions.include, vec!["app/**"]);
+        assert_eq!(globs.usages.exclude, vec!["db/schema.rb"]);
+        assert!(ProjectSettings::parse_yaml("roots:\n  usages:\n    only: [app/]\n").is_err());
+
+        let confined = defaults.confined_to(Path::new("services/api"));
+
+        assert!(confined.is_definition_root(Path::new("services/api/app/person.rb")));
+        assert!(!confined.is_definition_root(Path::new("services/web/app.js")));
+        assert!(!confined.is_definition_root(Path::new("services/api/vendor/gems/rake.rb")));
+        assert!(confined.is_usage_root(Path::new("services/api/vendor/gems/rake.rb")));
+        assert!(!confined.is_usage_root(Path::new("services/web/app.js")));
+    }
+
+    #[test]
+    fn parses_sub_projects() {
+        let projects = ProjectSettings::parse_yaml(
+            "projects:\n  api:\n    root: services/api\n    tags: services/api/tags\n    languages: [rb]\n    ignore:\n      tokens: [ApplicationJob]\n  frontend:\n    languages: [js, ts]\n",
+        )
+        .unwrap()
+        .projects;
+
+        assert_eq!(
+            projects.get("api"),
+            Some(&SubProject {
+                root: PathBuf::from("services/api"),
+                tags: Some(PathBuf::from("services/api/tags")),
+                languages: vec![String::from("rb")],
+                ignore: IgnoreList {
+                    tokens: vec![String::from("ApplicationJob")],
+                    tokens_matching: vec![],
+                    paths: vec![],
+                },
+            })
+        );
+        assert_eq!(
+            projects.get("frontend").map(|p| (&p.root, &p.tags)),
+            Some((&PathBuf::from("frontend"), &None))
+        );
+        assert_eq!(
+            ProjectSettings::parse_yaml("projects:\n  api:\n    tag: api/tags\n"),
+            Err(String::from(
+                "unknown setting projects.api.tag (expected one of: root, tags, languages, ignore)"
+            ))
+        );
+    }
+
+    #[test]
+    fn parses_ignore_paths() {
+        assert_eq!(
+            ProjectSettings::parse_yaml("ignore_paths:\n  - spec/**\n  - '*.generated.ts'\n")
+                .unwrap()
+                .ignore_paths,
+            vec![String::from("spec/**"), String::from("*.generated.ts")]
+        );
+        assert!(ProjectSettings::parse_yaml("ignore_paths:\n  spec: true\n").is_err());
+    }
+
+    #[test]
+    fn parses_ignored_token_patterns() {
+        assert_eq!(
+            ProjectSettings::parse_yaml("ignore:\n  tokens_matching: ['^Test', 'Helper$']\n")
+                .unwrap()
+                .ignore
+                .tokens_matching,
+            vec![String::from("^Test"), String::from("Helper$")]
+        );
+        assert!(
+            ProjectSettings::parse_yaml("ignore:\n  tokens_matching: ['(Test']\n")
+                .unwrap_err()
+                .starts_with("ignore.tokens_matching: invalid pattern '(Test'")
+        );
+    }
+
+    #[test]
+    fn parses_language_mappings_in_order() {
+        let yaml = "language_mappings:\n  '*.jbuilder': ruby\n  Gemfile: Ruby\n  '*.tsx': js\n";
+
+        assert_eq!(
+            ProjectSettings::parse_yaml(yaml).unwrap().language_mappings,
+            vec![
+                (String::from("*.jbuilder"), String::from("ruby")),
+                (String::from("Gemfile"), String::from("Ruby")),
+                (String::from("*.tsx"), String::from("js")),
+            ]
+        );
+        assert!(ProjectSettings::parse_yaml("language_mappings:\n  - ruby\n").is_err());
+        assert!(ProjectSettings::parse_yaml("language_mappings:\n  '*.jb': [ruby]\n").is_err());
+    }
+
+    #[test]
+    fn parses_severities_by_category() {
+        let yaml = "severities:\n  unused: error\n  test-only: info\n";
+
+        assert_eq!(
+            ProjectSettings::parse_yaml(yaml).unwrap().severities,
+            BTreeMap::from([
+                (String::from("test-only"), String::from("info")),
+                (String::from("unused"), String::from("error")),
+            ])
+        );
+        assert!(ProjectSettings::parse_yaml("severities:\n  - error\n").is_err());
+    }
+
+    #[test]
+    fn parses_budgets_in_order() {
+        let yaml = "budgets:\n  app/legacy/**: 500\n  lib/**: { error: 0, warning: 20 }\n";
+
+        assert_eq!(
+            ProjectSettings::parse_yaml(yaml).unwrap().budgets,
+            vec![
+                FindingsBudget {
+                    glob: String::from("app/legacy/**"),
+                    findings: Some(500),
+                    severities: BTreeMap::new(),
+                },
+                FindingsBudget {
+                    glob: String::from("lib/**"),
+                    findings: None,
+                    severities: BTreeMap::from([
+                        (String::from("error"), 0),
+                        (String::from("warning"), 20),
+                    ]),
+                },
+            ]
+        );
+        assert!(ProjectSettings::parse_yaml("budgets:\n  app/**: -1\n").is_err());
+        assert!(ProjectSettings::parse_yaml("budgets:\n  app/**: { warning: many }\n").is_err());
+        assert!(ProjectSettings::parse_yaml("budgets:\n  - app/**\n").is_err());
+    }
+
+    #[test]
+    fn parses_kind_weights() {
+        let yaml = "kind_weights:\n  class: 10\n  test: -30\n";
+
+        assert_eq!(
+            ProjectSettings::parse_yaml(yaml).unwrap().kind_weights,
+            BTreeMap::from([(String::from("class"), 10), (String::from("test"), -30)])
+        );
+        assert_eq!(
+            ProjectSettings::parse_toml("[kind_weights]\nconstant = -5\n")
+                .unwrap()
+                .kind_weights,
+            BTreeMap::from([(String::from("constant"), -5)])
+        );
+        assert!(ProjectSettings::parse_yaml("kind_weights:\n  class: heavy\n").is_err());
+    }
+
+    #[test]
+    fn adds_ignores_without_duplicating_entries() {
+        let ignores = IgnoreList {
+            tokens: vec![String::from("ApplicationJob"), String::from("legacy_total")],
+            tokens_matching: vec![],
+            paths: vec![String::from("app/legacy/report.rb")],
+        };
+        let yaml = add_yaml_ignores(
+            "ignore:\n  tokens:\n    - ApplicationJob\noutput:\n  format: json\n",
+            &ignores,
+        )
+        .unwrap();
+        let toml = add_toml_ignores("[ignore]\ntokens = [\"ApplicationJob\"]\n", &ignores).unwrap();
+
+        for settings in [
+            ProjectSettings::parse_yaml(&yaml).unwrap(),
+            ProjectSettings::parse_toml(&toml).unwrap(),
+        ] {
+            assert_eq!(settings.ignore, ignores);
+        }
+        assert_eq!(
+            ProjectSettings::parse_yaml(&yaml).unwrap().output.format,
+            Some(String::from("json"))
+        );
+        assert_eq!(
+            ProjectSettings::parse_yaml(&add_yaml_ignores("", &ignores).unwrap())
+                .unwrap()
+                .ignore,
+            ignores
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_mismatched_types() {
+        assert_eq!(
+            ProjectSettings::parse_yaml("output:\n  formatt: json\n"),
+            Err(String::from("unknown setting output.formatt (expected one of: format, likelihoods, sort_order, group_by, reverse, no_summary, no_progress)"))
+        );
+        assert_eq!(
+            ProjectSettings::parse_yaml("languages:\n  rb:\n    enabled: nope\n"),
+            Err(String::from("languages.rb.enabled: expected true or false"))
+        );
+        assert_eq!(
+            ProjectSettings::parse_yaml(""),
+            Ok(ProjectSettings::default())
+        );
+    }
+}
@@ -0,0 +1,589 @@
+use read_ctags::TokenKind;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Component, Path};
+use token_search::TokenSearchResult;
+
+// Actions routed by `resources :photos`, and by `resource :profile` (which has no index)
+const RESOURCES_ACTIONS: [&str; 7] = [
+    "index", "show", "new", "create", "edit", "update", "destroy",
+];
+const RESOURCE_ACTIONS: [&str; 6] = ["show", "new", "create", "edit", "update", "destroy"];
+
+// Methods routing a single request, such as `get "search", to: "photos#search"`
+const VERBS: [&str; 7] = ["get", "post", "put", "patch", "delete", "match", "root"];
+
+/// Controller actions reachable through a Rails application's routes
+///
+/// Actions are named by controller path and action, as `rails routes` lists them (such as
+/// `admin/users#show` for `Admin::UsersController#show`). Routes are read from `config/routes.rb`,
+/// following `resources`, `resource`, `namespace`, `scope module:`, `controller`, and verbs
+/// routing to actions (`get "search", to: "photos#search"` or `get :preview` within
+/// `resources`), or from saved `rails routes` output. Routes built dynamically aren't followed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RailsRoutes {
+    actions: BTreeSet<(String, String)>,
+    controllers: BTreeSet<String>,
+    non_actions: BTreeSet<(String, String)>,
+}
+
+/// How a token defined as controller actions is reached through routes
+#[derive(Clone, Debug, PartialEq)]
+pub enum Routing {
+    /// A route leads to one of its actions, such as `photos#index`
+    Routed(String),
+    /// Its actions' controllers are routed, but none of these actions are
+    Unrouted(Vec<String>),
+}
+
+// A block opened with `do` in config/routes.rb, and the controller or module it applies
+#[derive(Debug)]
+enum Block {
+    Module(String),
+    Controller(String),
+    Other,
+}
+
+impl RailsRoutes {
+    /// Read the routes of the Rails application at `root`, from `rails routes` output saved to
+    /// `routes_output` when given, or else from `config/routes.rb`
+    ///
+    /// Public methods of routed controllers are read from `app/controllers`, so private and
+    /// protected methods (such as filters) aren't treated as actions. Returns `Ok(None)` when
+    /// there's no `config/routes.rb`.
+    pub fn load(root: &Path, routes_output: Option<&Path>) -> io::Result<Option<Self>> {
+        let mut routes = match routes_output {
+            Some(path) => Self::parse_output(&fs::read_to_string(path)?),
+            None => match fs::read_to_string(root.join("config/routes.rb")) {
+                Ok(contents) => Self::parse(&contents),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(e),
+            },
+        };
+
+        let controllers: Vec<String> = routes.controllers.iter().cloned().collect();
+        for controller in controllers {
+            let path = root.join(format!("app/controllers/{}_controller.rb", controller));
+            if let Ok(source) = fs::read_to_string(path) {
+                for method in non_public_methods(&source) {
+                    routes.non_actions.insert((controller.clone(), method));
+                }
+            }
+        }
+
+        Ok(Some(routes))
+    }
+
+    /// Read routes from `config/routes.rb`
+    pub fn parse(routes: &str) -> Self {
+        let mut parsed = RailsRoutes::default();
+        let mut blocks: Vec<Block> = vec![];
+
+        for line in routes.lines() {
+            let line = strip_comment(line).trim();
+            let (statement, opens_block) = match line.strip_suffix(" do") {
+                Some(statement) => (statement.trim(), true),
+                None => match line.find(" do |") {
+                    Some(index) if line.ends_with('|') => (line[..index].trim(), true),
+                    _ => (line, false),
+                },
+            };
+            let (method, args) = match statement.find([' ', '(']) {
+                Some(index) => (&statement[..index], statement[index..].trim()),
+                None => (statement, ""),
+            };
+            let module = module_path(&blocks);
+
+            if method == "end" {
+                blocks.pop();
+                continue;
+            }
+
+            let block = match method {
+                "resources" | "resource" => {
+                    let singular = method == "resource";
+                    let module = match option(args, "module") {
+                        Some(name) => qualify(&module, &name),
+                        None => module,
+                    };
+                    let defaults: &[&str] = if singular {
+                        &RESOURCE_ACTIONS
+                    } else {
+                        &RESOURCES_ACTIONS
+                    };
+                    let only = option_list(args, "only");
+                    let except = option_list(args, "except").unwrap_or_default();
+                    let mut last = None;
+
+                    for name in symbols(args) {
+                        let name = match (option(args, "controller"), singular) {
+                            (Some(controller), _) => controller,
+                            (None, true) => pluralize(&name),
+                            (None, false) => name,
+                        };
+                        let controller = qualify(&module, &name);
+                        for action in defaults {
+                            let listed =
+                                only.as_ref().is_none_or(|o| o.iter().any(|a| a == action));
+                            if listed && !except.iter().any(|a| a == action) {
+                                parsed.add(&controller, action);
+                            }
+                        }
+                        parsed.controllers.insert(controller.clone());
+                        last = Some(controller);
+                    }
+                    last.map(Block::Controller).unwrap_or(Block::Other)
+                }
+                "namespace" => match symbols(args).into_iter().next() {
+                    Some(name) => Block::Module(name),
+                    None => Block::Other,
+                },
+                "scope" => match option(args, "module") {
+                    Some(name) => Block::Module(name),
+                    None => Block::Other,
+                },
+                "controller" => match symbols(args).into_iter().next() {
+                    Some(name) => Block::Controller(qualify(&module, &name)),
+                    None => Block::Other,
+                },
+                verb if VERBS.contains(&verb) => {
+                    if let Some((controller, action)) = verb_route(args, &module, &blocks) {
+                        parsed.add(&controller, &action);
+                    }
+                    Block::Other
+                }
+                _ => Block::Other,
+            };
+
+            if opens_block || opens_ruby_block(method) {
+                blocks.push(block);
+            }
+        }
+
+        parsed
+    }
+
+    /// Read routes from the output of `rails routes`, which names each route's action in its
+    /// `Controller#Action` column (such as `admin/users#show`)
+    pub fn parse_output(output: &str) -> Self {
+        let mut parsed = RailsRoutes::default();
+        for word in output.split_whitespace() {
+            let route = word.split_once('#').filter(|(controller, action)| {
+                !controller.is_empty()
+                    && controller.chars().all(|c| {
+                        c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '/')
+                    })
+                    && is_identifier(action)
+            });
+            if let Some((controller, action)) = route {
+                parsed.add(controller, action);
+            }
+        }
+        parsed
+    }
+
+    /// Were no routes read?
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Is `action` of `controller` (such as `admin/users`) routed?
+    pub fn routes(&self, controller: &str, action: &str) -> bool {
+        self.actions
+            .contains(&(controller.to_string(), action.to_string()))
+    }
+
+    /// How `result`'s token is reached through routes, when every definition of it is a public
+    /// method of a routed controller
+    pub fn routing(&self, result: &TokenSearchResult) -> Option<Routing> {
+        if self.is_empty() || result.token.definitions.is_empty() {
+            return None;
+        }
+
+        let mut unrouted = vec![];
+        for definition in result.token.definitions.iter() {
+            let controller = controller_path(&definition.file_path)?;
+            let key = (controller, definition.name.clone());
+            if definition.kind != TokenKind::Method
+                || !self.controllers.contains(&key.0)
+                || self.non_actions.contains(&key)
+            {
+                return None;
+            }
+            if self.actions.contains(&key) {
+                return Some(Routing::Routed(format!("{}#{}", key.0, key.1)));
+            }
+            unrouted.push(format!("{}#{}", key.0, key.1));
+        }
+
+        unrouted.sort();
+        Some(Routing::Unrouted(unrouted))
+    }
+
+    fn add(&mut self, controller: &str, action: &str) {
+        self.controllers.insert(controller.to_string());
+        self.actions
+            .insert((controller.to_string(), action.to_string()));
+    }
+}
+
+/// The controller path of a Rails controller file, such as `admin/users` for
+/// `app/controllers/admin/users_controller.rb`
+pub fn controller_path(path: &Path) -> Option<String> {
+    let components: Vec<&str> = path
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect();
+    let start = components
+        .windows(2)
+        .rposition(|pair| pair == ["app", "controllers"])?;
+    let relative = components[start + 2..].join("/");
+
+    relative
+        .strip_suffix("_controller.rb")
+        .map(|controller| controller.to_string())
+}
+
+// The controller and action a verb routes to: `to: "photos#search"` (or `=> "photos#search"`),
+// `controller:` and `action:` options, or an action of the enclosing controller block
+fn verb_route(args: &str, module: &str, blocks: &[Block]) -> Option<(String, String)> {
+    let target = option(args, "to").or_else(|| {
+        args.split_once("=>")
+            .and_then(|(_, target)| first_string(target))
+    });
+    let target = target.or_else(|| first_string(args).filter(|s| s.contains('#')));
+    if let Some((controller, action)) = target.as_deref().and_then(|t| t.split_once('#')) {
+        return Some((qualify(module, controller), action.to_string()));
+    }
+
+    let current = blocks.iter().rev().find_map(|block| match block {
+        Block::Controller(controller) => Some(controller.clone()),
+        _ => None,
+    });
+    let controller = option(args, "controller")
+        .map(|c| qualify(module, &c))
+        .or(current);
+    let action = option(args, "action");
+
+    match (controller, action) {
+        (Some(controller), Some(action)) => Some((controller, action)),
+        (Some(controller), None) => {
+            let path = symbols(args).into_iter().next()?;
+            let action = path.trim_matches('/').rsplit('/').next()?.to_string();
+            Some((controller, action)).filter(|(_, a)| is_identifier(a))
+        }
+        (None, _) => {
+            // `get "photos/search"` routes to photos#search
+            let path = first_string(args)?;
+            let (controller, action) = path.trim_matches('/').rsplit_once('/')?;
+            Some((qualify(module, controller), action.to_string()))
+                .filter(|(c, a)| is_identifier(a) && c.split('/').all(is_identifier))
+        }
+    }
+}
+
+fn module_path(blocks: &[Block]) -> String {
+    blocks
+        .iter()
+        .filter_map(|block| match block {
+            Block::Module(name) => Some(name.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn qualify(module: &str, name: &str) -> String {
+    let name = name.trim_start_matches('/');
+    match module {
+        "" => name.to_string(),
+        module => format!("{}/{}", module, name),
+    }
+}
+
+// `resources :photos, :videos` names two resources; options (`only: [:index]`) aren't names
+fn symbols(args: &str) -> Vec<String> {
+    args.trim_start_matches('(')
+        .split(',')
+        .map(|arg| arg.trim().trim_end_matches(')'))
+        .take_while(|arg| !is_option(arg))
+        .filter_map(|arg| {
+            arg.strip_prefix(':')
+                .map(str::to_string)
+                .or_else(|| first_string(arg))
+        })
+        .collect()
+}
+
+fn is_option(arg: &str) -> bool {
+    arg.split_once(':')
+        .is_some_and(|(key, _)| is_identifier(key.trim()))
+        || arg.contains("=>")
+}
+
+// The value of `key: :value`, `key: "value"`, or `:key => "value"`
+fn option(args: &str, key: &str) -> Option<String> {
+    [format!("{}:", key), format!(":{} =>", key)]
+        .iter()
+        .find_map(|prefix| {
+            let start = args
+                .match_indices(prefix.as_str())
+                .find(|(index, _)| {
+                    *index == 0
+                        || !args[..*index]
+                            .ends_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+                })?
+                .0;
+            let value = args[start + prefix.len()..].trim_start();
+            match value.strip_prefix(':') {
+                Some(symbol) => Some(
+                    symbol
+                        .chars()
+                        .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                        .collect(),
+                ),
+                None => first_string(value).filter(|_| value.starts_with(['"', '\''])),
+            }
+        })
+}
+
+// The actions listed by `only: [:index, :show]`, `only: :index`, or `only: %i[index show]`
+fn option_list(args: &str, key: &str) -> Option<Vec<String>> {
+    let start = args.find(&format!("{}:", key))? + key.len() + 1;
+    let value = args[start..].trim_start();
+    let list = match value.find(']') {
+        Some(end) if value.starts_with(['[', '%']) => &value[..end],
+        _ => value.split(',').next().unwrap_or_default(),
+    };
+
+    Some(
+        list.trim_start_matches("%i")
+            .trim_start_matches("%w")
+            .split([',', ' ', '['])
+            .map(|item| item.trim().trim_matches([':', '"', '\'']))
+            .filter(|item| is_identifier(item))
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+fn first_string(value: &str) -> Option<String> {
+    let start = value.find(['"', '\''])?;
+    let quote = value[start..].chars().next()?;
+    let rest = &value[start + 1..];
+    let end = rest.find(quote)?;
+    Some(rest[..end].to_string())
+}
+
+fn is_identifier(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '?' | '!'))
+}
+
+// Keywords whose blocks are closed by `end`, so nesting stays balanced
+fn opens_ruby_block(method: &str) -> bool {
+    matches!(method, "if" | "unless" | "case" | "begin" | "def")
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+// Rails names a singular resource's controller in the plural (`resource :profile` routes to
+// ProfilesController)
+fn pluralize(name: &str) -> String {
+    if name.ends_with(['s', 'x']) || name.ends_with("ch") || name.ends_with("sh") {
+        format!("{}es", name)
+    } else if let Some(stem) = name
+        .strip_suffix('y')
+        .filter(|stem| !stem.ends_with(['a', 'e', 'i', 'o', 'u']))
+    {
+        format!("{}ies", stem)
+    } else {
+        format!("{}s", name)
+    }
+}
+
+// Methods defined after `private` or `protected`, or marked with `private def name` or
+// `private :name`
+fn non_public_methods(source: &str) -> Vec<String> {
+    let mut methods = vec![];
+    let mut hidden = false;
+
+    for line in source.lines() {
+        let line = strip_comment(line).trim();
+        let (modifier, rest) = match line.split_once(' ') {
+            Some((word, rest)) => (word, rest.trim()),
+            None => (line, ""),
+        };
+
+        match (modifier, rest) {
+            ("private", "") | ("protected", "") => hidden = true,
+            ("public", "") => hidden = false,
+            ("class", _) => hidden = false,
+            ("private", rest) | ("protected", rest) => match rest.strip_prefix("def ") {
+                Some(definition) => methods.extend(method_name(definition)),
+                None => methods.extend(
+                    rest.split(',')
+                        .filter_map(|name| name.trim().strip_prefix(':'))
+                        .map(str::to_string),
+                ),
+            },
+            ("def", definition) if hidden => methods.extend(method_name(definition)),
+            _ => {}
+        }
+    }
+
+    methods
+}
+
+fn method_name(definition: &str) -> Option<String> {
+    let name: String = definition
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '?' | '!'))
+        .collect();
+    Some(name).filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+    use token_search::Token;
+
+    fn result(name: &str, path: &str) -> TokenSearchResult {
+        let definitions: HashSet<_> = read_ctags::CtagItem::parse(
+            PathBuf::from("tags"),
+            &format!("{}\t{}\t1;\"\tf", name, path),
+        )
+        .unwrap()
+        .tags;
+
+        TokenSearchResult {
+            token: Token::new(name.to_string(), definitions),
+            occurrences: Default::default(),
+            mentions: Default::default(),
+            scopes: None,
+        }
+    }
+
+    #[test]
+    fn reads_routes_and_routing_of_actions() {
+        let routes = RailsRoutes::parse(
+            r#"
+Rails.application.routes.draw do
+  root "pages#home"
+  get "about" => "pages#about"
+  get "photos/search"
+
+  resources :photos, only: [:index, :show] do
+    member do
+      get :preview
+    end
+    resources :comments, except: %i[edit update]
+  end
+  resource :profile, only: :show
+
+  namespace :admin do
+    resources :users
+    get "reports", to: "reports#summary" # dashboards
+  end
+
+  scope module: :api do
+    controller :exports do
+      post "exports/run", action: :run
+    end
+  end
+end
+"#,
+        );
+
+        for (controller, action) in [
+            ("pages", "home"),
+            ("pages", "about"),
+            ("photos", "search"),
+            ("photos", "index"),
+            ("photos", "preview"),
+            ("comments", "destroy"),
+            ("profiles", "show"),
+            ("admin/users", "edit"),
+            ("admin/reports", "summary"),
+            ("api/exports", "run"),
+        ] {
+            assert!(
+                routes.routes(controller, action),
+                "{}#{}",
+                controller,
+                action
+            );
+        }
+        for (controller, action) in [
+            ("photos", "destroy"),
+            ("comments", "edit"),
+            ("profiles", "update"),
+            ("users", "index"),
+        ] {
+            assert!(
+                !routes.routes(controller, action),
+                "{}#{}",
+                controller,
+                action
+            );
+        }
+
+        let output = RailsRoutes::parse_output(
+            "   Prefix Verb URI Pattern            Controller#Action\n   photos GET  /photos(.:format)      photos#index\n          GET  /admin/users/:id       admin/users#show\n",
+        );
+        assert!(output.routes("photos", "index"));
+        assert!(output.routes("admin/users", "show"));
+        assert!(!output.routes("Controller", "Action"));
+
+        let root = std::env::temp_dir().join("unused_rails_routes");
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::create_dir_all(root.join("app/controllers")).unwrap();
+        fs::write(
+            root.join("config/routes.rb"),
+            "Rails.application.routes.draw do\n  resources :photos, only: :show\nend\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("app/controllers/photos_controller.rb"),
+            "class PhotosController\n  def archive\n  end\n\n  private\n\n  def set_photo\n  end\nend\n",
+        )
+        .unwrap();
+        let loaded = RailsRoutes::load(&root, None).unwrap().unwrap();
+        fs::remove_dir_all(&root).ok();
+
+        let controller = "app/controllers/photos_controller.rb";
+        assert_eq!(
+            loaded.routing(&result("show", controller)),
+            Some(Routing::Routed(String::from("photos#show")))
+        );
+        assert_eq!(
+            loaded.routing(&result("archive", controller)),
+            Some(Routing::Unrouted(vec![String::from("photos#archive")]))
+        );
+        assert_eq!(loaded.routing(&result("set_photo", controller)), None);
+        assert_eq!(
+            loaded.routing(&result("archive", "app/models/photo.rb")),
+            None
+        );
+        assert_eq!(RailsRoutes::load(&root, None).unwrap(), None);
+    }
+}
@@ -1,3 +1,4 @@
+use regex::Regex;
 use std::collections::HashSet;
 use token_search::TokenSearchResult;
 
@@ -51,6 +52,7 @@ pub enum ValueMatcher {
     Contains(String),
     ExactMatchOnAnyOf(HashSet<String>),
     StartsWithCapital,
+    Matches(TokenPattern),
 }
 
 impl ValueMatcher {
@@ -62,15 +64,46 @@ impl ValueMatcher {
             ValueMatcher::Contains(v) => haystack.contains(v),
             ValueMatcher::ExactMatchOnAnyOf(vs) => vs.contains(haystack),
             ValueMatcher::StartsWithCapital => haystack.starts_with(|v: char| v.is_uppercase()),
+            ValueMatcher::Matches(pattern) => pattern.is_match(haystack),
         }
     }
 
     pub fn full_equals(&self) -> bool {
-        match self {
-            ValueMatcher::Equals(_) => true,
-            ValueMatcher::ExactMatchOnAnyOf(_) => true,
-            _ => false,
-        }
+        matches!(
+            self,
+            ValueMatcher::Equals(_) | ValueMatcher::ExactMatchOnAnyOf(_)
+        )
+    }
+}
+
+/// A regular expression matched anywhere within a value, e.g. `^Test|Helper$`
+#[derive(Clone, Debug)]
+pub struct TokenPattern(Regex);
+
+impl TokenPattern {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        Regex::new(pattern)
+            .map(TokenPattern)
+            .map_err(|e| format!("invalid pattern '{}': {}", pattern, e))
+    }
+
+    /// Validate a pattern passed on the command line
+    pub fn validate(pattern: String) -> Result<(), String> {
+        Self::new(&pattern).map(|_| ())
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    pub fn is_match(&self, value: &str) -> bool {
+        self.0.is_match(value)
+    }
+}
+
+impl PartialEq for TokenPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
     }
 }
 
@@ -88,39 +121,49 @@ mod tests {
 
     #[test]
     fn matches_starts_with() {
-        assert!(ValueMatcher::StartsWith(foo()).check(&"foobar"));
-        assert!(!ValueMatcher::StartsWith(bar()).check(&"foobar"));
+        assert!(ValueMatcher::StartsWith(foo()).check("foobar"));
+        assert!(!ValueMatcher::StartsWith(bar()).check("foobar"));
     }
 
     #[test]
     fn matches_ends_with() {
-        assert!(ValueMatcher::EndsWith(bar()).check(&"foobar"));
-        assert!(!ValueMatcher::EndsWith(foo()).check(&"foobar"));
+        assert!(ValueMatcher::EndsWith(bar()).check("foobar"));
+        assert!(!ValueMatcher::EndsWith(foo()).check("foobar"));
     }
 
     #[test]
     fn matches_contains() {
-        assert!(ValueMatcher::Contains(bar()).check(&"barar"));
-        assert!(ValueMatcher::Contains(bar()).check(&"bar"));
-        assert!(ValueMatcher::Contains(bar()).check(&" bar"));
-        assert!(!ValueMatcher::Contains(bar()).check(&" "));
-        assert!(!ValueMatcher::Contains(bar()).check(&"nope"));
-        assert!(!ValueMatcher::Contains(bar()).check(&"ar"));
+        assert!(ValueMatcher::Contains(bar()).check("barar"));
+        assert!(ValueMatcher::Contains(bar()).check("bar"));
+        assert!(ValueMatcher::Contains(bar()).check(" bar"));
+        assert!(!ValueMatcher::Contains(bar()).check(" "));
+        assert!(!ValueMatcher::Contains(bar()).check("nope"));
+        assert!(!ValueMatcher::Contains(bar()).check("ar"));
     }
 
     #[test]
     fn matches_any_of() {
-        let values: HashSet<_> = vec![foo(), bar()].iter().cloned().collect();
+        let values: HashSet<_> = [foo(), bar()].iter().cloned().collect();
 
-        assert!(ValueMatcher::ExactMatchOnAnyOf(values.clone()).check(&"foo"));
-        assert!(ValueMatcher::ExactMatchOnAnyOf(values.clone()).check(&"bar"));
-        assert!(!ValueMatcher::ExactMatchOnAnyOf(values.clone()).check(&"foobar"));
+        assert!(ValueMatcher::ExactMatchOnAnyOf(values.clone()).check("foo"));
+        assert!(ValueMatcher::ExactMatchOnAnyOf(values.clone()).check("bar"));
+        assert!(!ValueMatcher::ExactMatchOnAnyOf(values.clone()).check("foobar"));
     }
 
     #[test]
     fn matches_capital() {
-        assert!(ValueMatcher::StartsWithCapital.check(&"Foo"));
-        assert!(!ValueMatcher::StartsWithCapital.check(&"foo"));
+        assert!(ValueMatcher::StartsWithCapital.check("Foo"));
+        assert!(!ValueMatcher::StartsWithCapital.check("foo"));
+    }
+
+    #[test]
+    fn matches_patterns() {
+        let pattern = TokenPattern::new("^Test|Helper$").unwrap();
+
+        assert!(ValueMatcher::Matches(pattern.clone()).check("TestPerson"));
+        assert!(ValueMatcher::Matches(pattern.clone()).check("PersonHelper"));
+        assert!(!ValueMatcher::Matches(pattern).check("PersonTest"));
+        assert!(TokenPattern::validate(String::from("(unclosed")).is_err());
     }
 
     #[test]
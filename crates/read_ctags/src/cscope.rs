@@ -0,0 +1,248 @@
+use super::{interner::Interner, CtagItem, CtagsParseError, ReadCtagsError, TagsFile};
+use super::{language::Language, tag_program::TagProgram, token_kind::TokenKind};
+use codebase_files::paths;
+use std::collections::{BTreeMap, HashSet};
+use std::env::current_dir;
+use std::fs;
+use std::path::PathBuf;
+
+/// Name reported as the tags program when definitions were read from a cscope database
+pub const CSCOPE_PROGRAM_NAME: &str = "cscope";
+
+// The most frequent pairs of characters are stored as a single byte unless cscope was run with
+// `-c`; these tables are the ones cscope uses to compress them
+const DIGRAPH_FIRST: &[u8; 16] = b" teisaprnl(of)=c";
+const DIGRAPH_SECOND: &[u8; 8] = b" tnerpla";
+
+/// CscopeReader reads definitions from a cscope cross-reference database (`cscope.out`)
+///
+/// This lets projects that only maintain a cscope database (common for C and C++ codebases) be
+/// analyzed without running ctags.
+pub struct CscopeReader {
+    filenames: Vec<PathBuf>,
+}
+
+impl Default for CscopeReader {
+    fn default() -> Self {
+        CscopeReader {
+            filenames: current_dir()
+                .map(|dir| vec![dir.join("cscope.out")])
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl CscopeReader {
+    /// The first cscope database that exists, if any
+    pub fn find(&self) -> Option<PathBuf> {
+        self.filenames.iter().find(|path| path.is_file()).cloned()
+    }
+
+    /// Loads and parses the first cscope database it finds
+    pub fn load(&self) -> Result<TagsFile, ReadCtagsError> {
+        let path = self.find().unwrap_or_default();
+        let contents =
+            fs::read(&path).map_err(|e| ReadCtagsError::NoCtagsFile(self.filenames.clone(), e))?;
+        tracing::info!("Using cscope database {}", path.display());
+
+        let tags_file = parse(path, &contents)?;
+        tracing::info!(
+            "Parsed {} definitions from {}",
+            tags_file.tags.len(),
+            tags_file.path.display()
+        );
+        Ok(tags_file)
+    }
+}
+
+/// Parse a cscope cross-reference database into the definitions it marks
+///
+/// Each source file starts with a `\t@` mark followed by its path, and each line of source
+/// follows an empty line, starting with its line number. Symbols sit on their own lines, and
+/// definitions (functions, macros, classes, structs, unions, enums, typedefs, globals and
+/// members) are prefixed with a tab and a mark character. Function-local definitions and
+/// parameters are skipped, as they are by ctags.
+pub fn parse(path: PathBuf, input: &[u8]) -> Result<TagsFile, CtagsParseError> {
+    let mut lines = input.split(|byte| *byte == b'\n');
+
+    match lines.next() {
+        Some(header) if header.starts_with(b"cscope ") => {}
+        _ => return Err(CtagsParseError::InvalidCscope),
+    }
+
+    let mut tags = HashSet::new();
+    let interner = Interner::default();
+    let mut file: Option<String> = None;
+    let mut line_number = 0;
+    let mut record_start = false;
+
+    for line in lines {
+        match line {
+            [] => record_start = true,
+            // An empty file name ends the files, before cscope's list of source directories
+            [b'\t', b'@'] => break,
+            [b'\t', b'@', name @ ..] => file = Some(decode(name)),
+            [b'\t', mark, symbol @ ..] => {
+                if let (Some(file), Some(kind)) = (&file, definition_kind(*mark)) {
+                    let file_path = interner.share_path(&paths::normalize(file));
+                    tags.insert(CtagItem {
+                        name: decode(symbol),
+                        language: Language::from_path(&file_path),
+                        file_path,
+                        raw_path: interner.share(file),
+                        address: line_number.to_string(),
+                        tags: BTreeMap::new(),
+                        kind,
+                    });
+                }
+            }
+            _ if record_start => {
+                record_start = false;
+                let digits = line.iter().take_while(|byte| byte.is_ascii_digit()).count();
+                if let Some(number) = std::str::from_utf8(&line[..digits])
+                    .ok()
+                    .and_then(|digits| digits.parse().ok())
+                {
+                    line_number = number;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(TagsFile {
+        sources: vec![path.clone()],
+        path,
+        program: TagProgram {
+            name: Some(String::from(CSCOPE_PROGRAM_NAME)),
+            ..TagProgram::default()
+        },
+        tags,
+    })
+}
+
+// Marks of definitions, as documented in cscope's `src/constants.h`
+fn definition_kind(mark: u8) -> Option<TokenKind> {
+    match mark {
+        b'$' => Some(TokenKind::Function),
+        b'#' => Some(TokenKind::Macro),
+        b'c' => Some(TokenKind::Class),
+        b's' | b'u' => Some(TokenKind::Struct),
+        b'e' => Some(TokenKind::Enum),
+        b't' => Some(TokenKind::Typedef),
+        b'g' => Some(TokenKind::Variable),
+        b'm' => Some(TokenKind::Member),
+        _ => None,
+    }
+}
+
+// Expand compressed character pairs
+fn decode(bytes: &[u8]) -> String {
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    for byte in bytes {
+        if byte & 0x80 == 0 {
+            decoded.push(*byte);
+        } else {
+            let pair = usize::from(byte & 0x7f);
+            decoded.push(DIGRAPH_FIRST[pair / 8]);
+            decoded.push(DIGRAPH_SECOND[pair % 8]);
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    const DATABASE: &[u8] = b"cscope 15 /home/me/project -c 0000000321
+\t@src/users.c
+
+1 #include \"
+\t~users.h
+\"
+
+3 #define
+\t#MAX_USERS
+ 10
+
+5 struct
+\tsuser
+ {
+
+6 char *
+\tmname
+;
+
+7 };
+\t;
+
+9 static int
+\tgcount
+;
+
+11 int
+\t$add_user
+(struct
+user
+ *
+\tpu
+) {
+
+12
+\t`\xb4\x9atf
+(\"adding\");
+
+13
+count
+++;
+
+14 }
+\t}
+
+\t@
+
+1
+.
+";
+
+    #[test]
+    fn parses_cscope_definitions() {
+        let tags_file = parse(PathBuf::from("cscope.out"), DATABASE).unwrap();
+        let find = |name: &str| tags_file.tags.iter().find(|t| t.name == name);
+
+        assert_eq!(tags_file.program.name, Some(String::from("cscope")));
+        assert_eq!(tags_file.tags.len(), 5);
+        assert_eq!(
+            find("add_user").map(|t| &t.kind),
+            Some(&TokenKind::Function)
+        );
+        assert_eq!(find("add_user").map(|t| t.address.as_str()), Some("11"));
+        assert_eq!(
+            find("add_user").map(|t| &*t.file_path),
+            Some(Path::new("src/users.c"))
+        );
+        assert_eq!(find("MAX_USERS").map(|t| &t.kind), Some(&TokenKind::Macro));
+        assert_eq!(find("user").map(|t| &t.kind), Some(&TokenKind::Struct));
+        assert_eq!(find("name").map(|t| t.address.as_str()), Some("6"));
+        assert_eq!(find("count").map(|t| &t.kind), Some(&TokenKind::Variable));
+        assert_eq!(find("u"), None);
+    }
+
+    #[test]
+    fn expands_compressed_pairs() {
+        assert_eq!(decode(b"\xb4\x9atf"), "printf");
+        assert_eq!(decode(b"main"), "main");
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(matches!(
+            parse(PathBuf::from("tags"), b"!_TAG_FILE_FORMAT\t2\n"),
+            Err(CtagsParseError::InvalidCscope)
+        ));
+    }
+}
@@ -1,25 +1,55 @@
 use super::language::Language;
+use super::parse_mode::{ParseMode, ParseWarning};
 use super::parser;
 use super::tags_file::TagsFile;
+use super::tags_format::TagsFormat;
 use super::token_kind::TokenKind;
+use codebase_files::text::{self, is_identifier_char};
+use codebase_files::FileSystem;
 use serde::Serialize;
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
-use std::path::PathBuf;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+// Fields naming the scope enclosing a tag, written by ctags with `--fields=+s` (or `+Z`)
+const SCOPE_FIELDS: [&str; 8] = [
+    "scope",
+    "class",
+    "module",
+    "namespace",
+    "struct",
+    "implementation",
+    "interface",
+    "enum",
+];
+
+// Separators between a scope and a name in tags written with `--extras=+q` (e.g. `Person.name`,
+// `Person#name`, or `Admin::Person`)
+const QUALIFIED_SEPARATORS: [&str; 3] = [".", "#", "::"];
 
 /// Represents a single entry in a tags file
+///
+/// Paths and metadata tags are shared, so tags parsed from the same file store each path and
+/// scope (such as `class:Person`) once however many tags repeat it.
 #[derive(Clone, Hash, Debug, Eq, Serialize, PartialEq)]
 pub struct CtagItem {
     /// Name of the tag
     pub name: String,
-    /// Path identified by ctags
-    pub file_path: PathBuf,
+    /// Path identified by ctags, normalized to match the files searched
+    pub file_path: Arc<Path>,
+    /// Path as written in the tags file
+    #[serde(skip)]
+    pub raw_path: Arc<str>,
     /// Tag address
     pub address: String,
     /// Language, based on file path
     pub language: Option<Language>,
     /// Metadata tags
-    pub tags: BTreeMap<String, String>,
+    pub tags: BTreeMap<Arc<str>, Arc<str>>,
     /// Kind of tag
     pub kind: TokenKind,
 }
@@ -35,37 +65,653 @@ impl Display for CtagItem {
 }
 
 /// A struct capturing possible failures when attempting to parse a tags file
+#[derive(Debug, Error)]
 pub enum CtagsParseError {
-    /// Incomplete parse; parsing was successful but didn't consume all input
-    IncompleteParse,
-    /// Parsing failed
-    FailedParse(nom::Err<(String, nom::error::ErrorKind)>),
+    /// A line of a tags file isn't a valid tag
+    #[error("Failed to parse ctags file on line {line}: {reason}\n    {snippet}")]
+    InvalidTag {
+        /// Line number within the tags file, starting at 1
+        line: usize,
+        /// What's wrong with the line, such as an unexpected field
+        reason: String,
+        /// The start of the line, with tabs and other special characters escaped
+        snippet: String,
+    },
+    /// A line of JSON tags (`ctags --output-format=json`) isn't a valid tag
+    #[error("Invalid JSON tag on line {0}")]
+    InvalidJson(usize, #[source] Option<serde_json::Error>),
+    /// A cscope database doesn't start with cscope's header
+    #[error("Not a cscope database")]
+    InvalidCscope,
+    /// Reading tags from a stream failed partway through
+    #[error("Unable to read tags: {0}")]
+    Read(#[source] io::Error),
 }
 
-impl Display for CtagsParseError {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        match *self {
-            CtagsParseError::IncompleteParse => write!(f, "Unable to parse ctags file fully"),
-            CtagsParseError::FailedParse(ref err) => {
-                write!(f, "Failed to parse ctags file: {}", err)
-            }
-        }
-    }
+/// Why a tag can't be written to a tags file and read back unchanged
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum TagValidationError {
+    /// The tag's name is empty
+    #[error("Tag has an empty name")]
+    EmptyName,
+    /// The tag's name contains a tab or line break, which would split its line
+    #[error("Name {0:?} contains a tab or line break")]
+    InvalidName(String),
+    /// The tag's path is empty or contains a tab or line break
+    #[error("Path {0:?} is empty or contains a tab or line break")]
+    InvalidPath(String),
+    /// The tag's address is neither a line number nor a `/.../` or `?...?` search pattern
+    #[error("Address {0:?} is not a line number or search pattern")]
+    InvalidAddress(String),
+    /// A metadata tag's key is empty or isn't alphanumeric
+    #[error("Field {0:?} is not alphanumeric")]
+    InvalidField(String),
+    /// The tag's kind has no letter in its language, and no `kind` field names it
+    #[error("Kind {0:?} has no letter in this tag's language")]
+    UnencodableKind(TokenKind),
+    /// The tag's line parses, but not to the same tag
+    #[error("Tag reads back with a different {0}")]
+    RoundTrip(&'static str),
 }
 
 impl CtagItem {
     /// Parse tags generatd by Universal Ctags to generate `CtagItem`s
+    ///
+    /// JSON tags are detected from the first line (see `parse_as`). Parsing fails at the first
+    /// malformed line; `parse_lenient` skips them instead.
     pub fn parse(path: PathBuf, input: &str) -> Result<TagsFile, CtagsParseError> {
-        match parser::parse(input) {
-            Ok(("", (program, tags))) => Ok(TagsFile {
-                path,
-                program,
-                tags,
+        Self::parse_as(path, input, TagsFormat::Auto)
+    }
+
+    /// Parse tags like `parse`, reading them as `format`, such as one passed with `--tags-format`
+    pub fn parse_as(
+        path: PathBuf,
+        input: &str,
+        format: TagsFormat,
+    ) -> Result<TagsFile, CtagsParseError> {
+        let (program, tags) = match format.resolve(input) {
+            TagsFormat::Json => parser::json::parse(input)?,
+            _ => match parser::parse(input) {
+                Ok(("", parsed)) => parsed,
+                Ok((rest, _)) => return Err(parser::diagnostics::invalid_tag(input, rest)),
+                Err(e) => return Err(parser::diagnostics::from_nom(input, e)),
+            },
+        };
+
+        Ok(TagsFile {
+            sources: vec![path.clone()],
+            path,
+            program,
+            tags,
+        })
+    }
+
+    /// Parse tags like `parse_as`, reading them a line at a time from `reader`
+    ///
+    /// Only the line being parsed is held in memory, so tags can be read as they're written
+    /// (such as from ctags' output, a network stream, or a decompressing reader). Lines that
+    /// aren't valid UTF-8 are decoded as Latin-1, as files are. In `ParseMode::Lenient`,
+    /// malformed lines are logged and skipped.
+    pub fn parse_reader<R: BufRead>(
+        path: PathBuf,
+        mut reader: R,
+        format: TagsFormat,
+        mode: ParseMode,
+    ) -> Result<TagsFile, CtagsParseError> {
+        let lenient = mode == ParseMode::Lenient;
+        let mut lines = parser::lines::Lines::new(format);
+        let mut buffer = vec![];
+
+        loop {
+            buffer.clear();
+            if reader
+                .read_until(b'\n', &mut buffer)
+                .map_err(CtagsParseError::Read)?
+                == 0
+            {
+                break;
+            }
+            if buffer.last() == Some(&b'\n') {
+                buffer.pop();
+            }
+
+            let line = text::decode_line(std::mem::take(&mut buffer));
+            match lines.push(&line) {
+                Err(_) if lenient => {
+                    let warning = ParseWarning {
+                        line: lines.line(),
+                        content: line,
+                    };
+                    tracing::warn!("{} in {}", warning, path.display());
+                }
+                result => result?,
+            }
+        }
+
+        let (program, tags) = lines.finish();
+        Ok(TagsFile {
+            sources: vec![path.clone()],
+            path,
+            program,
+            tags,
+        })
+    }
+
+    /// Parse tags like `parse_as`, skipping malformed lines rather than failing
+    ///
+    /// Each skipped line is reported, with its line number, alongside the tags that could be
+    /// parsed.
+    pub fn parse_lenient(
+        path: PathBuf,
+        input: &str,
+        format: TagsFormat,
+    ) -> (TagsFile, Vec<ParseWarning>) {
+        let (program, tags, warnings) = match format.resolve(input) {
+            TagsFormat::Json => parser::json::parse_lenient(input),
+            _ => parser::parse_lenient(input),
+        };
+
+        let tags_file = TagsFile {
+            sources: vec![path.clone()],
+            path,
+            program,
+            tags,
+        };
+        (tags_file, warnings)
+    }
+
+    /// Path of the tag's file, resolving a relative path against `root`
+    pub fn absolute_path(&self, root: &Path) -> PathBuf {
+        root.join(&self.file_path)
+    }
+
+    /// Encode this tag as a line of a tags file, with its path as originally written
+    ///
+    /// Tabs, newlines, carriage returns, and backslashes in metadata tags are escaped, so lines
+    /// written by Universal Ctags encode byte for byte; an unknown escape another tool wrote
+    /// (such as `\d`) is normalized to an escaped backslash (`\\d`), with the same value. Kinds are
+    /// written as they were read, as a letter or a `kind:` field, except that tags read from
+    /// Exuberant Ctags are written with Universal Ctags' letters.
+    pub fn encode(&self) -> String {
+        let mut line = format!("{}\t{}\t{};\"", self.name, self.raw_path, self.address);
+        if let Some(kind) = self
+            .kind
+            .to_ctag(self.language)
+            .filter(|_| !self.tags.contains_key("kind"))
+        {
+            line.push_str(&format!("\t{}", kind));
+        }
+        for (key, value) in &self.tags {
+            line.push_str(&format!("\t{}:{}", key, parser::escapes::escape(value)));
+        }
+        line
+    }
+
+    /// Check this tag can be encoded as a line of a tags file that parses back to the same tag
+    ///
+    /// Catches tags that `encode` would write corruptly, such as names or paths containing tabs,
+    /// empty names, addresses that aren't line numbers or search patterns, and kinds with no
+    /// letter in the tag's language. Kinds are compared in the tag's own language, so tags whose
+    /// language was detected from their file's contents are checked as they'd be read back.
+    /// Every tag parsed from a valid tags file passes, which makes this suited to round-trip
+    /// property and fuzz tests.
+    pub fn validate(&self) -> Result<(), TagValidationError> {
+        if self.name.is_empty() {
+            return Err(TagValidationError::EmptyName);
+        }
+        if self.name.contains(['\t', '\n', '\r']) {
+            return Err(TagValidationError::InvalidName(self.name.clone()));
+        }
+        if self.raw_path.is_empty() || self.raw_path.contains(['\t', '\n', '\r']) {
+            return Err(TagValidationError::InvalidPath(self.raw_path.to_string()));
+        }
+        if !is_address(&self.address) {
+            return Err(TagValidationError::InvalidAddress(self.address.clone()));
+        }
+        if let Some(key) = self
+            .tags
+            .keys()
+            .find(|key| key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric()))
+        {
+            return Err(TagValidationError::InvalidField(key.to_string()));
+        }
+        let letter = self.kind.to_ctag(self.language);
+        if self.kind != TokenKind::Undefined && letter.is_none() && !self.tags.contains_key("kind")
+        {
+            return Err(TagValidationError::UnencodableKind(self.kind.clone()));
+        }
+
+        let parsed =
+            parser::parse_line(&self.encode()).ok_or(TagValidationError::RoundTrip("line"))?;
+        // Tags whose language was detected from their file's contents read back in their path's
+        // language, so the letter is read in the tag's own
+        let kind = match (parsed.language == self.language, letter) {
+            (false, Some(letter)) if !self.tags.contains_key("kind") => {
+                TokenKind::from_ctag(self.language, letter)
+            }
+            _ => parsed.kind.clone(),
+        };
+        let differences = [
+            ("name", parsed.name != self.name),
+            ("path", parsed.raw_path != self.raw_path),
+            ("address", parsed.address != self.address),
+            ("fields", parsed.tags != self.tags),
+            ("kind", kind != self.kind),
+        ];
+        match differences.iter().find(|(_, differs)| *differs) {
+            Some((field, _)) => Err(TagValidationError::RoundTrip(field)),
+            None => Ok(()),
+        }
+    }
+
+    /// Compare tags by path, then line, then name, the order tags are reported in so output is
+    /// the same between runs
+    ///
+    /// Tags addressed by a search pattern rather than a line number follow those addressed by
+    /// line in the same file, ordered by pattern.
+    pub fn cmp_location(&self, other: &CtagItem) -> Ordering {
+        self.location().cmp(&other.location())
+    }
+
+    fn location(&self) -> (&Path, usize, &str, &str, &str) {
+        let line = self.address.parse::<usize>().unwrap_or(usize::MAX);
+        (
+            &self.file_path,
+            line,
+            &self.address,
+            &self.name,
+            &self.raw_path,
+        )
+    }
+
+    /// Name of this tag's language when it isn't one `Language` recognizes, from the `language`
+    /// field written by ctags (e.g. with `--fields=+l`) or else the file extension
+    pub fn unrecognized_language(&self) -> Option<String> {
+        if self.language.is_some() {
+            return None;
+        }
+
+        self.tags
+            .get("language")
+            .map(|l| l.to_string())
+            .or_else(|| {
+                self.file_path
+                    .extension()
+                    .map(|ext| format!(".{}", ext.to_string_lossy()))
+            })
+    }
+
+    /// Line number of the tag, reading its file from `file_system` when the address is a search
+    /// pattern
+    pub fn line(&self, file_system: &dyn FileSystem) -> Option<usize> {
+        match self.address.parse::<usize>() {
+            Ok(line) => Some(line),
+            Err(_) => self.resolve_line(&file_system.read(&self.file_path).ok()?),
+        }
+    }
+
+    /// Line number of the tag within `source`, the contents of its file
+    ///
+    /// Addresses are either line numbers or ex search patterns such as `/^  def name$/`, which
+    /// are run against `source` to find the first matching line.
+    pub fn resolve_line(&self, source: &str) -> Option<usize> {
+        if let Ok(line) = self.address.parse::<usize>() {
+            return Some(line);
+        }
+
+        let pattern = self
+            .address
+            .strip_prefix('/')
+            .and_then(|p| p.strip_suffix('/'))?
+            .replace("\\/", "/")
+            .replace("\\\\", "\\");
+        let anchored_start = pattern.starts_with('^');
+        let pattern = pattern.trim_start_matches('^');
+        let (pattern, anchored_end) = match pattern.strip_suffix('$') {
+            Some(p) => (p, true),
+            None => (pattern, false),
+        };
+
+        source
+            .lines()
+            .position(|line| match (anchored_start, anchored_end) {
+                (true, true) => line == pattern,
+                (true, false) => line.starts_with(pattern),
+                (false, true) => line.ends_with(pattern),
+                (false, false) => line.contains(pattern),
+            })
+            .map(|index| index + 1)
+    }
+
+    /// Column of the tag's name on its line, reading its file from `file_system`
+    pub fn column(&self, file_system: &dyn FileSystem) -> Option<usize> {
+        let source = file_system.read(&self.file_path).ok()?;
+
+        self.resolve_column(&source, self.resolve_line(&source)?)
+    }
+
+    /// Last line of the definition, from the `end` field ctags writes with `--fields=+e`
+    pub fn end_line(&self) -> Option<usize> {
+        self.tags.get("end").and_then(|end| end.parse().ok())
+    }
+
+    /// Column (1-based, in bytes) of the tag's name on `line` of `source`
+    ///
+    /// The name is preferably matched as a whole word, so `name` is found in `def name` rather
+    /// than in an earlier `named_scope`.
+    pub fn resolve_column(&self, source: &str, line: usize) -> Option<usize> {
+        let text = source.lines().nth(line.checked_sub(1)?)?;
+
+        let mut matches = text.match_indices(self.name.as_str()).map(|(i, _)| i);
+        let first = matches.next()?;
+        let whole_word = std::iter::once(first).chain(matches).find(|i| {
+            !text[..*i].ends_with(is_identifier_char)
+                && !text[i + self.name.len()..].starts_with(is_identifier_char)
+        });
+
+        Some(whole_word.unwrap_or(first) + 1)
+    }
+
+    /// Name of the scope enclosing this tag (e.g. `Admin::UsersController`), from its `scope`
+    /// field or a field named for the enclosing kind, such as `class` or `module`
+    pub fn scope(&self) -> Option<&str> {
+        let value = SCOPE_FIELDS
+            .iter()
+            .find_map(|field| self.tags.get(*field))?;
+
+        // `scope` values are prefixed with the enclosing kind (e.g. `class:Person`)
+        match value.split_once(':') {
+            Some((kind, name)) if !kind.is_empty() && !name.starts_with(':') => Some(name),
+            _ => Some(value),
+        }
+    }
+
+    /// Is this an extra entry written by ctags with `--extras=+q`, qualifying the name of another
+    /// tag with its scope (e.g. `Person.full_name` alongside `full_name`)?
+    ///
+    /// Qualified tags are recognized by their `extras` field (written with `--fields=+E`) or by a
+    /// name starting with the tag's scope.
+    pub fn is_qualified(&self) -> bool {
+        match self.tags.get("extras") {
+            Some(extras) => extras.split(',').any(|extra| extra == "qualified"),
+            None => self.unqualified_name() != self.name,
+        }
+    }
+
+    /// Name of this tag without the scope qualifying it, if any
+    pub fn unqualified_name(&self) -> &str {
+        self.scope()
+            .and_then(|scope| self.name.strip_prefix(scope))
+            .and_then(|rest| {
+                QUALIFIED_SEPARATORS
+                    .iter()
+                    .find_map(|separator| rest.strip_prefix(separator))
+            })
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&self.name)
+    }
+
+    /// This tag in `language` (such as the one its file is mapped to), with its kind read again
+    /// as that language's kind letter
+    pub fn with_language(self, language: Option<Language>) -> CtagItem {
+        if language == self.language {
+            return self;
+        }
+
+        let kind = match self.kind.to_ctag(self.language) {
+            Some(c) => TokenKind::from_ctag(language, c),
+            None => self.kind.clone(),
+        };
+        CtagItem {
+            language,
+            kind,
+            ..self
+        }
+    }
+}
+
+// A line number, or a search pattern whose closing delimiter ends the address, as ctags writes
+fn is_address(address: &str) -> bool {
+    if address.contains(['\n', '\r']) {
+        return false;
+    }
+    if !address.is_empty() && address.bytes().all(|b| b.is_ascii_digit()) {
+        return true;
+    }
+
+    let bytes = address.as_bytes();
+    let delimiter = match bytes.first() {
+        Some(d @ b'/') | Some(d @ b'?') => *d,
+        _ => return false,
+    };
+    let mut index = 1;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 1,
+            c if c == delimiter => return index == bytes.len() - 1,
+            _ => {}
+        }
+        index += 1;
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(path: &str, tags: &[(&str, &str)]) -> CtagItem {
+        CtagItem {
+            name: String::from("main"),
+            file_path: PathBuf::from(path).into(),
+            raw_path: path.into(),
+            address: String::from("1"),
+            language: Language::from_path(path),
+            tags: tags
+                .iter()
+                .map(|(k, v)| ((*k).into(), (*v).into()))
+                .collect(),
+            kind: TokenKind::Undefined,
+        }
+    }
+
+    #[test]
+    fn parses_tags_from_readers_a_line_at_a_time() {
+        let input: &[u8] = b"!_TAG_PROGRAM_NAME\tUniversal Ctags\t//\nPerson\tapp/person.rb\t1;\"\tc\ncaf\xe9\tapp/person.rb\t2;\"\tf\n";
+
+        let tags_file = CtagItem::parse_reader(
+            PathBuf::from("-"),
+            input,
+            TagsFormat::Auto,
+            ParseMode::Strict,
+        )
+        .unwrap();
+
+        assert_eq!(tags_file.program.name.as_deref(), Some("Universal Ctags"));
+        let mut names: Vec<&str> = tags_file.tags.iter().map(|t| t.name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["Person", "café"]);
+
+        let invalid: &[u8] =
+            b"Person\tapp/person.rb\t1;\"\tc\n\nname\tapp/person.rb\t2;\"\tf\tclass";
+        assert_eq!(
+            CtagItem::parse_reader(PathBuf::from("-"), invalid, TagsFormat::Auto, ParseMode::Strict)
+                .map(|_| ())
+                .unwrap_err()
+                .to_string(),
+            "Failed to parse ctags file on line 3: unexpected field 'class'\n    name\\tapp/person.rb\\t2;\\\"\\tf\\tclass"
+        );
+    }
+
+    #[test]
+    fn names_unrecognized_languages() {
+        assert_eq!(item("src/main.rs", &[]).unrecognized_language(), None);
+        assert_eq!(
+            item("src/main.vala", &[("language", "Vala")]).unrecognized_language(),
+            Some(String::from("Vala"))
+        );
+        assert_eq!(
+            item("src/main.zig", &[]).unrecognized_language(),
+            Some(String::from(".zig"))
+        );
+    }
+
+    #[test]
+    fn resolves_search_pattern_addresses() {
+        let file_system = codebase_files::MemoryFileSystem::default();
+        file_system.insert("app/person.rb", "class Person\n  def name\n  end\nend\n");
+        let definition = CtagItem {
+            name: String::from("name"),
+            address: String::from("/^  def name$/"),
+            ..item("app/person.rb", &[])
+        };
+
+        assert_eq!(definition.line(&file_system), Some(2));
+        assert_eq!(definition.column(&file_system), Some(7));
+        assert_eq!(item("app/missing.rb", &[]).line(&file_system), Some(1));
+    }
+
+    #[test]
+    fn resolves_paths_against_a_root() {
+        let root = Path::new("/home/me/project");
+
+        assert_eq!(
+            item("app/person.rb", &[]).absolute_path(root),
+            PathBuf::from("/home/me/project/app/person.rb")
+        );
+        assert_eq!(
+            item("/tmp/person.rb", &[]).absolute_path(root),
+            PathBuf::from("/tmp/person.rb")
+        );
+    }
+
+    #[test]
+    fn locates_names_within_their_line() {
+        let source = "class Person\n  named_scope :name\n  def name; end\nend\n";
+        let definition = CtagItem {
+            name: String::from("name"),
+            ..item("app/person.rb", &[])
+        };
+
+        assert_eq!(definition.resolve_column(source, 3), Some(7));
+        assert_eq!(definition.resolve_column(source, 2), Some(16));
+        assert_eq!(definition.resolve_column(source, 1), None);
+        assert_eq!(definition.resolve_column(source, 0), None);
+    }
+
+    #[test]
+    fn names_enclosing_scopes() {
+        assert_eq!(item("app/person.rb", &[]).scope(), None);
+        assert_eq!(
+            item(
+                "app/users_controller.rb",
+                &[("class", "Admin::UsersController")]
+            )
+            .scope(),
+            Some("Admin::UsersController")
+        );
+        assert_eq!(
+            item("src/lib.rs", &[("scope", "implementation:Parser")]).scope(),
+            Some("Parser")
+        );
+    }
+
+    #[test]
+    fn recognizes_qualified_tags() {
+        let qualified = |name: &str, tags: &[(&str, &str)]| CtagItem {
+            name: String::from(name),
+            ..item("app/person.rb", tags)
+        };
+
+        assert!(qualified("Person.full_name", &[("class", "Person")]).is_qualified());
+        assert!(qualified("Admin::Person#name", &[("class", "Admin::Person")]).is_qualified());
+        assert!(qualified("Person.name", &[("extras", "fileScope,qualified")]).is_qualified());
+        assert!(!qualified("full_name", &[("class", "Person")]).is_qualified());
+        assert!(!qualified(
+            "Person.name",
+            &[("class", "Person"), ("extras", "fileScope")]
+        )
+        .is_qualified());
+        assert_eq!(
+            qualified("Person.full_name", &[("class", "Person")]).unqualified_name(),
+            "full_name"
+        );
+        assert_eq!(
+            qualified("Personal.name", &[("class", "Person")]).unqualified_name(),
+            "Personal.name"
+        );
+    }
+
+    #[test]
+    fn validates_tags_round_trip() {
+        let input = "full_name\t./app/person.rb\t/^  def full_name; end$/;\"\tf\tclass:Person\nrender\tapp/view.js\t3;\"\tkind:function\njoin\tsrc/lib.rs\t12;\"\tf\tsignature:(a,\\tb: &str)\n";
+        let tags_file = CtagItem::parse(PathBuf::from("tags"), input).unwrap();
+        for tag in &tags_file.tags {
+            assert_eq!(tag.validate(), Ok(()), "{}", tag.encode());
+        }
+
+        let unknown_escape = "match\tapp/person.rb\t1;\"\tf\tpattern:\\d+\n";
+        let tag = CtagItem::parse(PathBuf::from("tags"), unknown_escape)
+            .map(|file| file.tags.into_iter().next().unwrap())
+            .unwrap();
+        assert_eq!(tag.tags.get("pattern").map(|v| v.as_ref()), Some("\\d+"));
+        assert_eq!(
+            tag.encode(),
+            "match\tapp/person.rb\t1;\"\tf\tpattern:\\\\d+"
+        );
+        assert_eq!(tag.validate(), Ok(()));
+
+        let valid = item("app/person.rb", &[("class", "Person")]);
+        let invalid = |tag: CtagItem| tag.validate().unwrap_err();
+        assert_eq!(
+            invalid(CtagItem {
+                name: String::new(),
+                ..valid.clone()
+            }),
+            TagValidationError::EmptyName
+        );
+        assert!(matches!(
+            invalid(CtagItem {
+                name: String::from("full\tname"),
+                ..valid.clone()
             }),
-            Ok(_) => Err(CtagsParseError::IncompleteParse),
-            Err(e) => Err(CtagsParseError::FailedParse(
-                e.map(|(v1, v2)| (v1.to_string(), v2)),
-            )),
+            TagValidationError::InvalidName(_)
+        ));
+        assert!(matches!(
+            invalid(CtagItem {
+                raw_path: "app/\nperson.rb".into(),
+                ..valid.clone()
+            }),
+            TagValidationError::InvalidPath(_)
+        ));
+        for address in ["", "twelve", "/^def name$", "/^a$/;\"\tf", "/^a$/x"] {
+            assert!(matches!(
+                invalid(CtagItem {
+                    address: String::from(address),
+                    ..valid.clone()
+                }),
+                TagValidationError::InvalidAddress(_)
+            ));
         }
+        assert!(matches!(
+            invalid(item("app/person.rb", &[("class name", "Person")])),
+            TagValidationError::InvalidField(_)
+        ));
+        assert_eq!(
+            invalid(CtagItem {
+                kind: TokenKind::Class,
+                language: None,
+                ..valid.clone()
+            }),
+            TagValidationError::UnencodableKind(TokenKind::Class)
+        );
+        assert_eq!(
+            invalid(CtagItem {
+                kind: TokenKind::Class,
+                ..item("app/view.js", &[("kind", "function")])
+            }),
+            TagValidationError::RoundTrip("kind")
+        );
     }
 }
@@ -0,0 +1,129 @@
+use super::ctag_item::{CtagItem, CtagsParseError};
+use super::interner::Interner;
+use super::language::Language;
+use super::parser;
+use super::tag_program::TagProgram;
+use super::token_kind::TokenKind;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A single entry in a tags file, borrowing its fields from the file's contents
+///
+/// Parsing borrowed tags avoids copying every name, path, and address out of the tags file,
+/// roughly halving peak memory for large files when the tags are only read (counted, filtered,
+/// or written elsewhere). Convert the ones to keep with `to_owned`.
+#[derive(Clone, Hash, Debug, Eq, Serialize, PartialEq)]
+pub struct CtagItemRef<'a> {
+    /// Name of the tag
+    pub name: &'a str,
+    /// Path identified by ctags, normalized to match the files searched
+    ///
+    /// Only paths that need normalizing (such as those starting with `./`) are copied.
+    pub file_path: Cow<'a, Path>,
+    /// Path as written in the tags file
+    #[serde(skip)]
+    pub raw_path: &'a str,
+    /// Tag address
+    pub address: &'a str,
+    /// Language, based on file path
+    pub language: Option<Language>,
+    /// Metadata tags
+    ///
+    /// Only values containing escapes (such as `\t` for a tab) are copied.
+    pub tags: BTreeMap<&'a str, Cow<'a, str>>,
+    /// Kind of tag
+    pub kind: TokenKind,
+}
+
+impl<'a> CtagItemRef<'a> {
+    /// Parse tab-separated tags, borrowing each tag's fields from `input`
+    ///
+    /// Tags are returned in the order they're written. Unlike `CtagItem::parse`, JSON tags aren't
+    /// supported (their strings may be escaped, so they can't be borrowed), and extensionless
+    /// files keep the language of their path rather than one detected from their contents.
+    pub fn parse(input: &'a str) -> Result<(TagProgram, Vec<CtagItemRef<'a>>), CtagsParseError> {
+        match parser::parse_borrowed(input) {
+            Ok(("", parsed)) => Ok(parsed),
+            Ok((rest, _)) => Err(parser::diagnostics::invalid_tag(input, rest)),
+            Err(e) => Err(parser::diagnostics::from_nom(input, e)),
+        }
+    }
+
+    /// Copy this tag's fields into a `CtagItem` that doesn't borrow from the tags file
+    pub fn to_owned(&self) -> CtagItem {
+        self.to_owned_in(&Interner::default())
+    }
+
+    /// Copy this tag's fields into a `CtagItem`, sharing its paths and metadata tags with others
+    /// copied through `interner`
+    pub fn to_owned_in(&self, interner: &Interner) -> CtagItem {
+        CtagItem {
+            name: self.name.to_string(),
+            file_path: interner.share_path(&self.file_path),
+            raw_path: interner.share(self.raw_path),
+            address: self.address.to_string(),
+            language: self.language,
+            tags: self
+                .tags
+                .iter()
+                .map(|(k, v)| (interner.share(k), interner.share(v)))
+                .collect(),
+            kind: self.kind.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    #[test]
+    fn borrows_fields_from_input() {
+        let input = "!_TAG_PROGRAM_NAME\tUniversal Ctags\t//\nPerson\tapp/person.rb\t/^class Person$/;\"\tc\nfull_name\t./app/person.rb\t/^  def full_name$/;\"\tf\tclass:Person\n";
+        let (program, tags) = CtagItemRef::parse(input).unwrap();
+
+        assert_eq!(program.name, Some(String::from("Universal Ctags")));
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].name, "Person");
+        assert!(matches!(tags[0].file_path, Cow::Borrowed(_)));
+        assert_eq!(tags[1].file_path, Path::new("app/person.rb"));
+        assert_eq!(tags[1].raw_path, "./app/person.rb");
+        assert_eq!(tags[1].tags.get("class").map(|v| &**v), Some("Person"));
+        assert_eq!(tags[1].kind, TokenKind::Method);
+    }
+
+    #[test]
+    fn converts_to_owned_tags() {
+        let line = "full_name\t./app/person.rb\t/^  def full_name$/;\"\tf\tclass:Person";
+        let (_, tags) = CtagItemRef::parse(line).unwrap();
+        let owned = tags[0].to_owned();
+
+        assert_eq!(&*owned.file_path, Path::new("app/person.rb"));
+        assert_eq!(owned.language, Some(Language::Ruby));
+        assert_eq!(owned.encode(), line);
+        assert_eq!(
+            CtagItem::parse(PathBuf::from("tags"), line)
+                .unwrap()
+                .tags
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![owned]
+        );
+    }
+
+    #[test]
+    fn shares_repeated_paths_and_scopes() {
+        let input = "first_name\tapp/person.rb\t1;\"\tf\tclass:Person\nlast_name\tapp/person.rb\t2;\"\tf\tclass:Person\n";
+        let (_, tags) = CtagItemRef::parse(input).unwrap();
+        let interner = Interner::default();
+        let first = tags[0].to_owned_in(&interner);
+        let last = tags[1].to_owned_in(&interner);
+
+        assert!(Arc::ptr_eq(&first.file_path, &last.file_path));
+        assert!(Arc::ptr_eq(&first.tags["class"], &last.tags["class"]));
+    }
+}
@@ -0,0 +1,115 @@
+use super::CtagItem;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Tags that no longer match the working tree
+///
+/// Every tag is checked against the file it names, reading each file once: tags in files that
+/// no longer exist, tags whose address no longer resolves to a line, and tags repeating the
+/// name and line of another tag.
+#[derive(Debug, Default, PartialEq)]
+pub struct DanglingTags<'a> {
+    /// Tags in files that no longer exist
+    pub missing_files: Vec<&'a CtagItem>,
+    /// Tags whose search pattern matches no line of their file, or whose line number is past its
+    /// end
+    pub unresolved: Vec<&'a CtagItem>,
+    /// Tags with the same name and line as a tag listed before them
+    pub duplicates: Vec<&'a CtagItem>,
+}
+
+impl<'a> DanglingTags<'a> {
+    /// Check `tags` against the files they're defined in
+    pub fn check<I>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = &'a CtagItem>,
+    {
+        let mut by_path: BTreeMap<&Path, Vec<&CtagItem>> = BTreeMap::new();
+        for tag in tags {
+            by_path.entry(&tag.file_path).or_default().push(tag);
+        }
+
+        let mut dangling = DanglingTags::default();
+        for (path, mut tags) in by_path {
+            tags.sort_by(|a, b| a.cmp_location(b));
+            match fs::read(path) {
+                Ok(bytes) => dangling.check_file(&String::from_utf8_lossy(&bytes), tags),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    dangling.missing_files.extend(tags)
+                }
+                Err(e) => tracing::debug!("Unable to check {}: {}", path.display(), e),
+            }
+        }
+
+        dangling
+    }
+
+    /// Number of dangling tags, counting each tag once
+    pub fn len(&self) -> usize {
+        self.missing_files.len() + self.unresolved.len() + self.duplicates.len()
+    }
+
+    /// Does every tag still match the working tree?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn check_file(&mut self, contents: &str, tags: Vec<&'a CtagItem>) {
+        let lines = contents.lines().count();
+        let mut seen: HashSet<(&str, usize)> = HashSet::new();
+
+        for tag in tags {
+            match tag
+                .resolve_line(contents)
+                .filter(|line| (1..=lines).contains(line))
+            {
+                Some(line) if !seen.insert((&tag.name, line)) => self.duplicates.push(tag),
+                Some(_) => {}
+                None => self.unresolved.push(tag),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn finds_missing_files_unresolved_addresses_and_duplicates() {
+        let root = std::env::temp_dir().join("dangling_tags_finds_problems");
+        fs::create_dir_all(&root).unwrap();
+        let person = root.join("person.rb");
+        fs::write(&person, "class Person\n  def name\n  end\nend\n").unwrap();
+        let missing = root.join("missing.rb");
+        let tags = CtagItem::parse(
+            PathBuf::from("tags"),
+            &[
+                format!("Person\t{}\t/^class Person$/;\"\tc", person.display()),
+                format!("name\t{}\t/^  def name$/;\"\tf", person.display()),
+                format!("name\t{}\t2;\"\tf\tclass:Person", person.display()),
+                format!("age\t{}\t/^  def age$/;\"\tf", person.display()),
+                format!("title\t{}\t9;\"\tf", person.display()),
+                format!("legacy\t{}\t1;\"\tf", missing.display()),
+            ]
+            .join("\n"),
+        )
+        .unwrap()
+        .tags;
+        let names = |tags: &[&CtagItem]| -> Vec<String> {
+            let mut names: Vec<String> = tags.iter().map(|t| t.name.clone()).collect();
+            names.sort();
+            names
+        };
+
+        let dangling = DanglingTags::check(&tags);
+
+        assert_eq!(names(&dangling.missing_files), vec!["legacy"]);
+        assert_eq!(names(&dangling.unresolved), vec!["age", "title"]);
+        assert_eq!(names(&dangling.duplicates), vec!["name"]);
+        assert_eq!(dangling.len(), 4);
+    }
+}
@@ -0,0 +1,284 @@
+#[cfg(feature = "tree-sitter")]
+use super::SyntaxTags;
+use super::{language::Language, tag_program::TagProgram, token_kind::TokenKind};
+use super::{CtagItem, TagsFile};
+use codebase_files::text;
+use regex::Regex;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+/// Name reported as the tags program when definitions were extracted by `FallbackTags`
+pub const FALLBACK_PROGRAM_NAME: &str = "unused regex fallback";
+
+/// FallbackTags extracts approximate definitions from source files with regular expressions
+///
+/// This is a degraded alternative for when no tags file exists and ctags isn't installed; it only
+/// recognizes common class, module, method, and function definitions. With the `tree-sitter`
+/// feature, languages with a grammar (see `SyntaxTags`) are parsed instead.
+pub struct FallbackTags {
+    patterns: Vec<(Language, char, Regex)>,
+    #[cfg(feature = "tree-sitter")]
+    syntax: SyntaxTags,
+}
+
+impl Default for FallbackTags {
+    fn default() -> Self {
+        let definitions: Vec<(Language, char, &str)> = vec![
+            (
+                Language::Ruby,
+                'c',
+                r"^\s*class\s+(?:\p{Lu}\w*::)*(\p{Lu}\w*)",
+            ),
+            (
+                Language::Ruby,
+                'm',
+                r"^\s*module\s+(?:\p{Lu}\w*::)*(\p{Lu}\w*)",
+            ),
+            (
+                Language::Ruby,
+                'S',
+                r"^\s*def\s+self\.([\p{XID_Start}_]\w*[?!=]?)",
+            ),
+            (
+                Language::Ruby,
+                'f',
+                r"^\s*def\s+([\p{XID_Start}_]\w*[?!=]?)",
+            ),
+            (
+                Language::Elixir,
+                'm',
+                r"^\s*defmodule\s+(?:[A-Z]\w*\.)*([A-Z]\w*)",
+            ),
+            (
+                Language::Elixir,
+                'a',
+                r"^\s*defmacrop?\s+([\p{Ll}\p{Lo}_]\w*[?!]?)",
+            ),
+            (
+                Language::Elixir,
+                'f',
+                r"^\s*defp?\s+([\p{Ll}\p{Lo}_]\w*[?!]?)",
+            ),
+            (
+                Language::JavaScript,
+                'c',
+                r"^\s*(?:export\s+)?class\s+([\p{XID_Start}_$][\w$]*)",
+            ),
+            (
+                Language::JavaScript,
+                'f',
+                r"^\s*(?:export\s+)?(?:async\s+)?function\s*\*?\s*([\p{XID_Start}_$][\w$]*)",
+            ),
+            (
+                Language::JavaScript,
+                'C',
+                r"^\s*(?:export\s+)?const\s+([\p{XID_Start}_$][\w$]*)\s*=",
+            ),
+            (
+                Language::TypeScript,
+                'c',
+                r"^\s*(?:export\s+)?class\s+([\p{XID_Start}_$][\w$]*)",
+            ),
+            (
+                Language::TypeScript,
+                'f',
+                r"^\s*(?:export\s+)?(?:async\s+)?function\s*\*?\s*([\p{XID_Start}_$][\w$]*)",
+            ),
+            (
+                Language::TypeScript,
+                'i',
+                r"^\s*(?:export\s+)?interface\s+([\p{XID_Start}_$][\w$]*)",
+            ),
+            (Language::Python, 'c', r"^\s*class\s+([\p{XID_Start}_]\w*)"),
+            (
+                Language::Python,
+                'f',
+                r"^\s*(?:async\s+)?def\s+([\p{XID_Start}_]\w*)",
+            ),
+            (
+                Language::Rust,
+                'f',
+                r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:const\s+)?(?:async\s+)?(?:unsafe\s+)?fn\s+([\p{XID_Start}_]\w*)",
+            ),
+            (
+                Language::Rust,
+                's',
+                r"^\s*(?:pub(?:\([^)]*\))?\s+)?struct\s+([\p{XID_Start}_]\w*)",
+            ),
+            (
+                Language::Rust,
+                'g',
+                r"^\s*(?:pub(?:\([^)]*\))?\s+)?enum\s+([\p{XID_Start}_]\w*)",
+            ),
+            (
+                Language::Rust,
+                'i',
+                r"^\s*(?:pub(?:\([^)]*\))?\s+)?trait\s+([\p{XID_Start}_]\w*)",
+            ),
+            (
+                Language::Sh,
+                'f',
+                r"^\s*(?:function\s+)?([A-Za-z_][\w-]*)\s*\(\)",
+            ),
+        ];
+
+        FallbackTags {
+            patterns: definitions
+                .into_iter()
+                .map(|(language, kind, pattern)| (language, kind, Regex::new(pattern).unwrap()))
+                .collect(),
+            #[cfg(feature = "tree-sitter")]
+            syntax: SyntaxTags::default(),
+        }
+    }
+}
+
+impl FallbackTags {
+    /// Is a ctags executable available on the `PATH`?
+    pub fn ctags_installed() -> bool {
+        Command::new("ctags")
+            .arg("--version")
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    /// Extract definitions from each readable file in the provided list
+    pub fn extract(&self, paths: &[PathBuf]) -> TagsFile {
+        let tags = paths
+            .iter()
+            .filter_map(|path| text::read(path).ok().map(|c| (path, c)))
+            .flat_map(|(path, contents)| self.extract_from(path, &contents))
+            .collect();
+
+        TagsFile {
+            path: PathBuf::new(),
+            sources: vec![],
+            program: TagProgram {
+                name: Some(FALLBACK_PROGRAM_NAME.to_string()),
+                ..TagProgram::default()
+            },
+            tags,
+        }
+    }
+
+    fn extract_from(&self, path: &Path, contents: &str) -> HashSet<CtagItem> {
+        let language = Language::detect(path, contents);
+
+        #[cfg(feature = "tree-sitter")]
+        if let Some(definitions) = self.syntax.extract_from(path, contents, language) {
+            return definitions;
+        }
+
+        let patterns: Vec<_> = self
+            .patterns
+            .iter()
+            .filter(|(l, _, _)| Some(*l) == language)
+            .collect();
+
+        if patterns.is_empty() {
+            return HashSet::new();
+        }
+
+        let mut results = HashSet::new();
+        let file_path: Arc<Path> = path.into();
+        let raw_path: Arc<str> = path.to_string_lossy().into();
+
+        for (index, line) in contents.lines().enumerate() {
+            if let Some((kind, name)) = patterns.iter().find_map(|(_, kind, pattern)| {
+                pattern
+                    .captures(line)
+                    .and_then(|c| c.get(1))
+                    .map(|m| (*kind, m.as_str()))
+            }) {
+                results.insert(CtagItem {
+                    name: name.to_string(),
+                    file_path: file_path.clone(),
+                    raw_path: raw_path.clone(),
+                    address: (index + 1).to_string(),
+                    language,
+                    tags: BTreeMap::new(),
+                    kind: TokenKind::from_ctag(language, kind),
+                });
+            }
+        }
+
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_ruby_definitions() {
+        let contents = "module Admin\n  class UsersController < ApplicationController\n    def self.build\n    end\n\n    def index?\n    end\n  end\nend\n";
+        let result = FallbackTags::default().extract_from(Path::new("app/users.rb"), contents);
+        let mut found: Vec<_> = result
+            .iter()
+            .map(|t| (t.name.as_str(), t.address.as_str(), t.kind.clone()))
+            .collect();
+        found.sort_by_key(|(_, address, _)| address.parse::<usize>().unwrap());
+
+        assert_eq!(
+            found,
+            vec![
+                ("Admin", "1", TokenKind::Module),
+                ("UsersController", "2", TokenKind::Class),
+                ("build", "3", TokenKind::SingletonMethod),
+                ("index?", "6", TokenKind::Method),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_non_ascii_identifiers() {
+        let extract = |path: &str, contents: &str| {
+            let mut names: Vec<String> = FallbackTags::default()
+                .extract_from(Path::new(path), contents)
+                .into_iter()
+                .map(|t| t.name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(
+            extract(
+                "app/café.rb",
+                "class Über
+  def größe
+  end
+end
+"
+            ),
+            vec!["größe", "Über"]
+        );
+        assert_eq!(
+            extract(
+                "lib/shop.ex",
+                "  def 名前(x), do: x
+"
+            ),
+            vec!["名前"]
+        );
+        assert_eq!(
+            extract(
+                "src/lib.rs",
+                "pub fn répondre() {}
+"
+            ),
+            vec!["répondre"]
+        );
+    }
+
+    #[test]
+    fn skips_unsupported_languages() {
+        let result = FallbackTags::default().extract_from(Path::new("README.md"), "# def foo\n");
+
+        assert!(result.is_empty());
+    }
+}
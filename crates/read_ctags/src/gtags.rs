@@ -0,0 +1,169 @@
+use super::{interner::Interner, CtagItem, ReadCtagsError, TagsFile};
+use super::{language::Language, tag_program::TagProgram, token_kind::TokenKind};
+use codebase_files::paths;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env::current_dir;
+use std::io::{self, Error};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Name reported as the tags program when definitions were read from GNU GLOBAL's databases
+pub const GTAGS_PROGRAM_NAME: &str = "GNU GLOBAL";
+
+/// GlobalTags reads definitions and references from GNU GLOBAL's `GTAGS` and `GRTAGS` databases
+///
+/// The databases are read with the `global` command, which is installed alongside `gtags`.
+/// GLOBAL doesn't record the kind of each definition, so definitions are read without one.
+pub struct GlobalTags {
+    filenames: Vec<PathBuf>,
+}
+
+impl Default for GlobalTags {
+    fn default() -> Self {
+        GlobalTags {
+            filenames: current_dir()
+                .map(|dir| vec![dir.join("GTAGS")])
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl GlobalTags {
+    /// The first GTAGS database that exists, if any
+    pub fn find(&self) -> Option<PathBuf> {
+        self.filenames.iter().find(|path| path.is_file()).cloned()
+    }
+
+    /// Loads the definitions recorded in the first GTAGS database it finds
+    pub fn load(&self) -> Result<TagsFile, ReadCtagsError> {
+        let path = self
+            .find()
+            .ok_or_else(|| Error::new(io::ErrorKind::NotFound, "No GTAGS database found"))
+            .map_err(|e| ReadCtagsError::NoCtagsFile(self.filenames.clone(), e))?;
+        tracing::info!("Using GTAGS database {}", path.display());
+
+        let output = run_global(&path, &[]).map_err(ReadCtagsError::GlobalFailed)?;
+        let tags_file = TagsFile {
+            tags: parse_definitions(&output),
+            program: TagProgram {
+                name: Some(String::from(GTAGS_PROGRAM_NAME)),
+                ..TagProgram::default()
+            },
+            sources: vec![path.clone()],
+            path,
+        };
+        tracing::info!(
+            "Parsed {} definitions from {}",
+            tags_file.tags.len(),
+            tags_file.path.display()
+        );
+        Ok(tags_file)
+    }
+
+    /// References recorded in GRTAGS, counted by name and then by the referencing file
+    pub fn references(&self) -> Result<HashMap<String, HashMap<PathBuf, usize>>, Error> {
+        let path = self
+            .find()
+            .ok_or_else(|| Error::new(io::ErrorKind::NotFound, "No GTAGS database found"))?;
+
+        run_global(&path, &["-r"]).map(|output| parse_references(&output))
+    }
+}
+
+// Lists every definition (or, with `-r`, every reference) as `name\tpath\tline`, with paths
+// relative to the directory holding the databases
+fn run_global(gtags_path: &Path, args: &[&str]) -> Result<String, Error> {
+    let output = Command::new("global")
+        .current_dir(gtags_path.parent().unwrap_or_else(|| Path::new(".")))
+        .arg("--result=ctags")
+        .args(args)
+        .arg(".*")
+        .output()?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+fn parse_definitions(output: &str) -> HashSet<CtagItem> {
+    let interner = Interner::default();
+
+    output
+        .lines()
+        .filter_map(split_line)
+        .map(|(name, raw_path, line)| {
+            let file_path = interner.share_path(&paths::normalize(raw_path));
+            CtagItem {
+                name: name.to_string(),
+                language: Language::from_path(&file_path),
+                file_path,
+                raw_path: interner.share(raw_path),
+                address: line.to_string(),
+                tags: BTreeMap::new(),
+                kind: TokenKind::Undefined,
+            }
+        })
+        .collect()
+}
+
+fn parse_references(output: &str) -> HashMap<String, HashMap<PathBuf, usize>> {
+    let mut references: HashMap<String, HashMap<PathBuf, usize>> = HashMap::new();
+
+    for (name, raw_path, _) in output.lines().filter_map(split_line) {
+        *references
+            .entry(name.to_string())
+            .or_default()
+            .entry(paths::normalize(raw_path))
+            .or_default() += 1;
+    }
+
+    references
+}
+
+fn split_line(line: &str) -> Option<(&str, &str, usize)> {
+    let mut fields = line.splitn(3, '\t');
+    let name = fields.next()?;
+    let path = fields.next()?;
+    let line = fields.next()?.trim().parse().ok()?;
+
+    Some((name, path, line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_global_definitions() {
+        let tags = parse_definitions("add_user\tsrc/users.c\t11\nmain\t./src/main.c\t3\nbroken\n");
+        let add_user = tags.iter().find(|t| t.name == "add_user").unwrap();
+        let main = tags.iter().find(|t| t.name == "main").unwrap();
+
+        assert_eq!(tags.len(), 2);
+        assert_eq!(&*add_user.file_path, Path::new("src/users.c"));
+        assert_eq!(add_user.address, "11");
+        assert_eq!(add_user.kind, TokenKind::Undefined);
+        assert_eq!(&*main.file_path, Path::new("src/main.c"));
+        assert_eq!(&*main.raw_path, "./src/main.c");
+    }
+
+    #[test]
+    fn counts_references_by_file() {
+        let references = parse_references(
+            "add_user\tsrc/main.c\t3\nadd_user\tsrc/main.c\t8\nadd_user\tsrc/admin.c\t2\n",
+        );
+
+        assert_eq!(
+            references["add_user"].get(Path::new("src/main.c")),
+            Some(&2)
+        );
+        assert_eq!(
+            references["add_user"].get(Path::new("src/admin.c")),
+            Some(&1)
+        );
+    }
+}
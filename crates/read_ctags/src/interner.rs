@@ -0,0 +1,138 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// A cheap, copyable handle to a string stored in an `Interner`
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Symbol(u32);
+
+/// Interner deduplicates repeated strings (such as names and paths), handing out `Symbol`s
+///
+/// It also hands out shared copies of strings and paths with `share` and `share_path`, which is
+/// how tags parsed from one file store each path and scope only once. Handles are cheap to clone and safe to share across threads; every clone refers to the same
+/// underlying storage.
+#[derive(Clone, Default)]
+pub struct Interner(Arc<RwLock<InternerState>>);
+
+#[derive(Default)]
+struct InternerState {
+    symbols: HashMap<Arc<str>, Symbol>,
+    values: Vec<Arc<str>>,
+    paths: HashSet<Arc<Path>>,
+}
+
+impl Interner {
+    /// Return the symbol for `value`, storing it if it hasn't been seen before
+    pub fn intern(&self, value: &str) -> Symbol {
+        if let Some(symbol) = self.get(value) {
+            return symbol;
+        }
+
+        let mut state = self.0.write().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(symbol) = state.symbols.get(value) {
+            return *symbol;
+        }
+
+        let symbol = Symbol(state.values.len() as u32);
+        let stored: Arc<str> = Arc::from(value);
+        state.values.push(stored.clone());
+        state.symbols.insert(stored, symbol);
+        symbol
+    }
+
+    /// A shared copy of `value`, storing it if it hasn't been seen before
+    pub fn share(&self, value: &str) -> Arc<str> {
+        let symbol = self.intern(value);
+        let state = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        state.values[symbol.0 as usize].clone()
+    }
+
+    /// A shared copy of `path`, storing it if it hasn't been seen before
+    pub fn share_path(&self, path: &Path) -> Arc<Path> {
+        {
+            let state = self.0.read().unwrap_or_else(PoisonError::into_inner);
+            if let Some(shared) = state.paths.get(path) {
+                return shared.clone();
+            }
+        }
+
+        let mut state = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        if let Some(shared) = state.paths.get(path) {
+            return shared.clone();
+        }
+
+        let shared: Arc<Path> = Arc::from(path);
+        state.paths.insert(shared.clone());
+        shared
+    }
+
+    /// Look up the symbol for `value` without storing it
+    pub fn get(&self, value: &str) -> Option<Symbol> {
+        let state = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        state.symbols.get(value).copied()
+    }
+
+    /// The string a symbol refers to, if it was produced by this interner
+    pub fn resolve(&self, symbol: Symbol) -> Option<Arc<str>> {
+        let state = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        state.values.get(symbol.0 as usize).cloned()
+    }
+
+    /// Number of distinct strings stored
+    pub fn len(&self) -> usize {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values
+            .len()
+    }
+
+    /// Has nothing been interned yet?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn shares_symbols_across_threads() {
+        let interner = Interner::default();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let interner = interner.clone();
+                thread::spawn(move || {
+                    vec![
+                        interner.intern("app/models/person.rb"),
+                        interner.intern("name"),
+                    ]
+                })
+            })
+            .collect();
+        let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert!(results.windows(2).all(|w| w[0] == w[1]));
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.resolve(results[0][1]).as_deref(), Some("name"));
+    }
+
+    #[test]
+    fn shares_copies_of_repeated_values() {
+        let interner = Interner::default();
+        let path = Path::new("app/models/person.rb");
+
+        assert!(Arc::ptr_eq(
+            &interner.share("Person"),
+            &interner.share("Person")
+        ));
+        assert!(Arc::ptr_eq(
+            &interner.share_path(path),
+            &interner.share_path(path)
+        ));
+        assert_eq!(interner.len(), 1);
+    }
+}
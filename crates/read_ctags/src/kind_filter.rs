@@ -0,0 +1,69 @@
+use super::ctag_item::CtagItem;
+use super::token_kind::TokenKind;
+use std::str::FromStr;
+
+/// A kind of definition, written as the letter ctags writes for it (e.g. `f`) or its full name
+/// (e.g. `function`)
+///
+/// Letters mean different kinds in different languages, so a letter matches tags whose kind
+/// ctags writes with that letter in the tag's own language.
+#[derive(Clone, Debug, PartialEq)]
+pub enum KindPattern {
+    /// A ctags kind letter
+    Letter(char),
+    /// A kind named in full
+    Kind(TokenKind),
+}
+
+impl KindPattern {
+    /// Is `tag` of this kind?
+    pub fn matches(&self, tag: &CtagItem) -> bool {
+        match self {
+            KindPattern::Letter(letter) => tag.kind.to_ctag(tag.language) == Some(*letter),
+            KindPattern::Kind(kind) => tag.kind == *kind,
+        }
+    }
+}
+
+impl FromStr for KindPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.trim().chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(letter), None) => Ok(KindPattern::Letter(letter)),
+            _ => TokenKind::from_name(s.trim())
+                .map(KindPattern::Kind)
+                .ok_or_else(|| format!("Unknown kind: {}", s)),
+        }
+    }
+}
+
+/// Which kinds of definitions to keep: those matching any of `only` (when given), and none of
+/// `ignore`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KindFilter {
+    /// Kinds to keep, or every kind when empty
+    pub only: Vec<KindPattern>,
+    /// Kinds to drop
+    pub ignore: Vec<KindPattern>,
+}
+
+impl KindFilter {
+    /// Filter keeping `only` these kinds (or every kind when empty), except those to `ignore`
+    pub fn new(only: Vec<KindPattern>, ignore: Vec<KindPattern>) -> Self {
+        KindFilter { only, ignore }
+    }
+
+    /// Does the filter keep every tag?
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.ignore.is_empty()
+    }
+
+    /// Is `tag` kept?
+    pub fn includes(&self, tag: &CtagItem) -> bool {
+        (self.only.is_empty() || self.only.iter().any(|kind| kind.matches(tag)))
+            && !self.ignore.iter().any(|kind| kind.matches(tag))
+    }
+}
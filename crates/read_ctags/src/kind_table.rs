@@ -0,0 +1,161 @@
+use super::ctag_item::CtagItem;
+use super::token_kind::TokenKind;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Kind letters and file extensions for each language a ctags installation supports
+///
+/// Imported from `ctags --list-kinds-full` and `ctags --list-map-extensions`, the table resolves
+/// kinds `TokenKind::from_ctag` doesn't know (for languages added to ctags after this crate was
+/// released, or letters it doesn't cover) by the kind's name.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct KindTable {
+    /// Kind names by letter, for each language
+    pub kinds: BTreeMap<String, BTreeMap<char, String>>,
+    /// Languages by lowercase file extension
+    pub extensions: BTreeMap<String, String>,
+}
+
+impl KindTable {
+    /// Build a table from the output of `ctags --list-kinds-full` and
+    /// `ctags --list-map-extensions`
+    pub fn parse(kinds: &str, extensions: &str) -> Self {
+        let mut table = KindTable::default();
+
+        for columns in rows(kinds) {
+            if let [language, letter, name, ..] = columns[..] {
+                if let Some(letter) = single_char(letter) {
+                    table
+                        .kinds
+                        .entry(language.to_string())
+                        .or_default()
+                        .insert(letter, name.to_string());
+                }
+            }
+        }
+
+        for columns in rows(extensions) {
+            if let [language, extension, ..] = columns[..] {
+                table
+                    .extensions
+                    .insert(extension.to_lowercase(), language.to_string());
+            }
+        }
+
+        table
+    }
+
+    /// Number of kinds across all languages
+    pub fn len(&self) -> usize {
+        self.kinds.values().map(BTreeMap::len).sum()
+    }
+
+    /// Does the table have no kinds?
+    pub fn is_empty(&self) -> bool {
+        self.kinds.is_empty()
+    }
+
+    /// The kind of `item` from this table, when `TokenKind::from_ctag` couldn't determine it and
+    /// the kind's name corresponds to a `TokenKind`
+    pub fn resolve(&self, item: &CtagItem) -> Option<TokenKind> {
+        let (language, letter) = match &item.kind {
+            TokenKind::MissingLanguageToken(language, letter) => {
+                (format!("{:?}", language), *letter)
+            }
+            TokenKind::Unknown(letter) => (self.language(item)?, *letter),
+            _ => return None,
+        };
+
+        self.kinds
+            .get(&language)
+            .and_then(|kinds| kinds.get(&letter))
+            .and_then(|name| TokenKind::from_name(name))
+    }
+
+    // The ctags language name, from the tag's `language` field or its file extension
+    fn language(&self, item: &CtagItem) -> Option<String> {
+        item.tags
+            .get("language")
+            .map(|l| l.to_string())
+            .or_else(|| {
+                item.file_path
+                    .extension()
+                    .and_then(|ext| self.extensions.get(&ext.to_string_lossy().to_lowercase()))
+                    .cloned()
+            })
+    }
+}
+
+// Whitespace-separated columns of each line, skipping headers (which start with `#`)
+fn rows(output: &str) -> impl Iterator<Item = Vec<&str>> {
+    output
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| line.split_whitespace().collect())
+}
+
+fn single_char(value: &str) -> Option<char> {
+    let mut chars = value.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Language;
+    use std::path::PathBuf;
+
+    const KINDS: &str = "#LANGUAGE      LETTER NAME       ENABLED REFONLY NROLES MASTER DESCRIPTION
+Go             f      func       yes     no      0      NONE   functions
+Go             s      struct     yes     no      0      NONE   structs
+Go             R      receiver   no      no      0      NONE   receivers
+Ruby           A      accessor   yes     no      0      NONE   accessors
+Ruby           S      singletonMethod yes no     0      NONE   singleton methods
+";
+    const EXTENSIONS: &str = "#LANGUAGE      EXTENSION
+Go             go
+Ruby           rb
+";
+
+    fn tag(path: &str, kind: TokenKind) -> CtagItem {
+        CtagItem {
+            name: String::from("Serve"),
+            file_path: PathBuf::from(path).into(),
+            raw_path: path.into(),
+            address: String::from("1"),
+            language: Language::from_path(path),
+            tags: BTreeMap::new(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn resolves_kinds_missing_from_the_built_in_tables() {
+        let table = KindTable::parse(KINDS, EXTENSIONS);
+
+        assert_eq!(table.len(), 5);
+        assert_eq!(
+            table.resolve(&tag("server.go", TokenKind::Unknown('f'))),
+            Some(TokenKind::Function)
+        );
+        assert_eq!(
+            table.resolve(&tag("server.go", TokenKind::Unknown('R'))),
+            None
+        );
+        assert_eq!(
+            table.resolve(&tag(
+                "app/person.rb",
+                TokenKind::MissingLanguageToken(Language::Ruby, 'A')
+            )),
+            Some(TokenKind::Property)
+        );
+        assert_eq!(
+            table.resolve(&tag("app/person.rb", TokenKind::Method)),
+            None
+        );
+    }
+}
@@ -1,8 +1,24 @@
-use serde::Serialize;
+use serde::{de, Deserialize, Deserializer, Serialize};
 use std::path::Path;
 use std::str::FromStr;
 
+// Vim reads modelines from this many lines at the start and end of a file
+const MODELINES: usize = 5;
+
+// Template extensions, by the language of the code they embed
+const TEMPLATES: [(&str, Language); 4] = [
+    ("erb", Language::Ruby),
+    ("haml", Language::Ruby),
+    ("slim", Language::Ruby),
+    ("ejs", Language::JavaScript),
+];
+
 /// Enum representing languages currently supported
+///
+/// Languages are parsed from their name (`Ruby`, or an interpreter such as `python3`) or one of
+/// their file extensions (`rb`), and deserialized the same way, so settings and downstream tools
+/// can refer to them either way. They're displayed by name and serialized as the variant's name,
+/// both of which parse back to the same language.
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, Serialize)]
 #[allow(missing_docs)]
 pub enum Language {
@@ -49,11 +65,117 @@ impl Language {
     /// Given a path with file extension, calculate its language
     pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Language> {
         match path.as_ref().extension() {
-            Some(v) => v.to_str().and_then(|x| Language::from_str(x).ok()),
+            Some(v) => v.to_str().and_then(Language::from_extension),
             None => Some(Language::Sh),
         }
     }
 
+    /// Calculate the language of a file from its path, falling back to its contents
+    ///
+    /// Files without a recognized extension, such as extensionless scripts in `bin/`, are
+    /// detected by a shebang or vim modeline when they have one.
+    pub fn detect<P: AsRef<Path>>(path: P, contents: &str) -> Option<Language> {
+        let path = path.as_ref();
+        let from_extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Language::from_extension);
+
+        from_extension
+            .or_else(|| Language::from_contents(contents))
+            .or_else(|| Language::from_path(path))
+    }
+
+    /// The language of code embedded in a template, such as Ruby in `.erb`, `.haml`, and `.slim`
+    /// views or JavaScript in `.ejs` views
+    ///
+    /// Templates have no language of their own, since ctags doesn't read definitions from them,
+    /// but they reference tokens from the language they embed.
+    pub fn embedded_in<P: AsRef<Path>>(path: P) -> Option<Language> {
+        path.as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Language::from_template_extension)
+    }
+
+    /// The language embedded in templates with this file extension (such as `erb`)
+    pub fn from_template_extension(ext: &str) -> Option<Language> {
+        TEMPLATES
+            .iter()
+            .find(|(template, _)| template.eq_ignore_ascii_case(ext))
+            .map(|(_, language)| *language)
+    }
+
+    /// Calculate a language from a shebang (`#!/usr/bin/env ruby`) or vim modeline
+    /// (`# vim: ft=python`)
+    pub fn from_contents(contents: &str) -> Option<Language> {
+        let lines: Vec<&str> = contents.lines().collect();
+        let shebang = lines
+            .first()
+            .and_then(|line| line.strip_prefix("#!"))
+            .and_then(interpreter)
+            .and_then(Language::from_name);
+
+        shebang.or_else(|| {
+            let head = lines.iter().take(MODELINES);
+            let tail = lines
+                .iter()
+                .skip(MODELINES.max(lines.len().saturating_sub(MODELINES)));
+
+            head.chain(tail)
+                .find_map(|line| modeline_filetype(line))
+                .and_then(Language::from_name)
+        })
+    }
+
+    /// Construct a Language from its name (e.g. `Ruby`) or an interpreter or vim filetype naming
+    /// it, ignoring versions such as `python3` or `ruby2.7`
+    pub fn from_name(name: &str) -> Option<Language> {
+        let name = name.to_lowercase();
+        let name = name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+
+        match name {
+            "css" => Some(Language::CSS),
+            "elixir" | "iex" => Some(Language::Elixir),
+            "elm" => Some(Language::Elm),
+            "html" => Some(Language::HTML),
+            "json" => Some(Language::JSON),
+            "javascript" | "node" | "nodejs" => Some(Language::JavaScript),
+            "markdown" => Some(Language::Markdown),
+            "python" | "pypy" => Some(Language::Python),
+            "ruby" | "jruby" => Some(Language::Ruby),
+            "rust" => Some(Language::Rust),
+            "scss" => Some(Language::SCSS),
+            "sh" | "shell" | "bash" | "zsh" | "dash" | "ksh" => Some(Language::Sh),
+            "svg" => Some(Language::SVG),
+            "typescript" | "ts-node" | "deno" => Some(Language::TypeScript),
+            "xml" => Some(Language::XML),
+            _ => None,
+        }
+    }
+
+    /// The language of files with this extension (such as `rb`)
+    pub fn from_extension(ext: &str) -> Option<Language> {
+        match ext.to_lowercase().as_ref() {
+            "css" => Some(Language::CSS),
+            "ex" | "exs" => Some(Language::Elixir),
+            "elm" => Some(Language::Elm),
+            "html" => Some(Language::HTML),
+            "json" => Some(Language::JSON),
+            "js" | "jsx" => Some(Language::JavaScript),
+            "md" => Some(Language::Markdown),
+            "py" => Some(Language::Python),
+            "rb" => Some(Language::Ruby),
+            "rs" => Some(Language::Rust),
+            "scss" => Some(Language::SCSS),
+            "sh" | "" => Some(Language::Sh),
+            "svg" => Some(Language::SVG),
+            "ts" | "tsx" => Some(Language::TypeScript),
+            "xml" => Some(Language::XML),
+            _ => None,
+        }
+    }
+
     /// All file extensions supported
     pub fn extensions() -> Vec<&'static str> {
         vec![
@@ -63,35 +185,123 @@ impl Language {
     }
 }
 
+/// File name patterns mapped to languages (such as `*.jbuilder` to Ruby), consulted ahead of the
+/// built-in extensions
+///
+/// Patterns match file names, or whole paths when they contain a `/`, with `*` matching any run
+/// of characters; the first matching pattern wins.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LanguageMappings(Vec<(String, Language)>);
+
+impl LanguageMappings {
+    /// Map files matching each pattern to its language
+    pub fn new(mappings: Vec<(String, Language)>) -> Self {
+        LanguageMappings(mappings)
+    }
+
+    /// Are no files mapped?
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The language files at `path` are mapped to, if any
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<Language> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let path = path.as_ref().to_string_lossy().replace('\\', "/");
+        let path = path.strip_prefix("./").unwrap_or(&path);
+        let name = path.rsplit('/').next().unwrap_or(path);
+
+        self.0.iter().find_map(|(pattern, language)| {
+            let subject = if pattern.contains('/') { path } else { name };
+            matches_pattern(pattern, subject).then_some(*language)
+        })
+    }
+
+    /// The language of the file at `path`, as mapped or else as `Language::from_path` calculates
+    pub fn from_path<P: AsRef<Path>>(&self, path: P) -> Option<Language> {
+        self.get(&path).or_else(|| Language::from_path(path))
+    }
+
+    /// The language of a file, as mapped or else as `Language::detect` calculates
+    pub fn detect<P: AsRef<Path>>(&self, path: P, contents: &str) -> Option<Language> {
+        self.get(&path).or_else(|| Language::detect(path, contents))
+    }
+}
+
+// Does `subject` match `pattern`, where `*` matches any run of characters?
+fn matches_pattern(pattern: &str, subject: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match subject.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let parts: Vec<&str> = parts.collect();
+
+    match parts.split_last() {
+        None => rest.is_empty(),
+        Some((last, middle)) => {
+            for part in middle {
+                match rest.find(part) {
+                    Some(index) => rest = &rest[index + part.len()..],
+                    None => return false,
+                }
+            }
+            rest.len() >= last.len() && rest.ends_with(last)
+        }
+    }
+}
+
+// The program named by a shebang, looking past `env` and its options
+fn interpreter(shebang: &str) -> Option<&str> {
+    let mut words = shebang.split_whitespace();
+    let program = words.next()?.rsplit('/').next()?;
+
+    if program == "env" {
+        words.find(|word| !word.starts_with('-') && !word.contains('='))
+    } else {
+        Some(program)
+    }
+}
+
+// The filetype set by a modeline such as `# vim: ft=python` or `/* vim: set filetype=ruby: */`
+fn modeline_filetype(line: &str) -> Option<&str> {
+    let start = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+        line.match_indices(marker)
+            .find(|(i, _)| *i == 0 || line[..*i].ends_with(char::is_whitespace))
+            .map(|(i, _)| i + marker.len())
+    })?;
+
+    line[start..]
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .find_map(|option| {
+            let (key, value) = option.split_once('=')?;
+            match key {
+                "ft" | "filetype" | "syn" | "syntax" => Some(value),
+                _ => None,
+            }
+        })
+}
+
 impl FromStr for Language {
     type Err = String;
 
+    /// Parse a language from its name or one of its file extensions
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_ref() {
-            "css" => Ok(Language::CSS),
-            "ex" => Ok(Language::Elixir),
-            "exs" => Ok(Language::Elixir),
-            "elm" => Ok(Language::Elm),
-            "html" => Ok(Language::HTML),
-            "json" => Ok(Language::JSON),
-            "js" => Ok(Language::JavaScript),
-            "jsx" => Ok(Language::JavaScript),
-            "md" => Ok(Language::Markdown),
-            "py" => Ok(Language::Python),
-            "rb" => Ok(Language::Ruby),
-            "rs" => Ok(Language::Rust),
-            "scss" => Ok(Language::SCSS),
-            "sh" => Ok(Language::Sh),
-            "svg" => Ok(Language::SVG),
-            "ts" => Ok(Language::TypeScript),
-            "tsx" => Ok(Language::TypeScript),
-            "xml" => Ok(Language::XML),
-            "" => Ok(Language::Sh),
-            ext => Err(String::from(format!(
-                "Unable to parse file extension: {}",
-                ext
-            ))),
-        }
+        (!s.is_empty())
+            .then(|| Language::from_name(s).or_else(|| Language::from_extension(s)))
+            .flatten()
+            .ok_or_else(|| format!("Unknown language: {}", s))
+    }
+}
+
+impl<'de> Deserialize<'de> for Language {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(de::Error::custom)
     }
 }
 
@@ -108,10 +318,121 @@ mod tests {
         assert_eq!(Language::from_path("file.unknown"), None);
     }
 
+    #[test]
+    fn reads_languages_embedded_in_templates() {
+        assert_eq!(Language::from_path("app/views/people/show.html.erb"), None);
+        assert_eq!(
+            Language::embedded_in("app/views/people/show.html.erb"),
+            Some(Language::Ruby)
+        );
+        assert_eq!(
+            Language::embedded_in("app/views/people/_form.html.haml"),
+            Some(Language::Ruby)
+        );
+        assert_eq!(
+            Language::embedded_in("views/people.ejs"),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(Language::embedded_in("app/models/person.rb"), None);
+    }
+
+    #[test]
+    fn detects_languages_from_contents() {
+        assert_eq!(
+            Language::detect("bin/rails", "#!/usr/bin/env ruby\nrequire 'rails'\n"),
+            Some(Language::Ruby)
+        );
+        assert_eq!(
+            Language::detect("bin/deploy", "#!/usr/bin/env -S python3 -u\n"),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            Language::detect("bin/serve", "#!/usr/local/bin/node\n"),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(
+            Language::detect("script.unknown", "import os\n\n# vim: set ft=python:\n"),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            Language::detect("lib/tasks.rb", "#!/usr/bin/env python\n"),
+            Some(Language::Ruby)
+        );
+        assert_eq!(
+            Language::detect("bin/setup", "echo hi\n"),
+            Some(Language::Sh)
+        );
+        assert_eq!(Language::detect("file.unknown", "novim:ft=ruby\n"), None);
+    }
+
+    #[test]
+    fn maps_files_ahead_of_their_extension() {
+        let mappings = LanguageMappings::new(vec![
+            (String::from("*.jbuilder"), Language::Ruby),
+            (String::from("app/*/*.tsx"), Language::JavaScript),
+            (String::from("Gemfile"), Language::Ruby),
+        ]);
+
+        assert_eq!(
+            mappings.from_path("app/views/show.json.jbuilder"),
+            Some(Language::Ruby)
+        );
+        assert_eq!(
+            mappings.from_path("./app/views/list.tsx"),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(
+            mappings.from_path("lib/list.tsx"),
+            Some(Language::TypeScript)
+        );
+        assert_eq!(
+            mappings.detect("Gemfile", "#!/bin/sh\n"),
+            Some(Language::Ruby)
+        );
+        assert_eq!(mappings.get("app/person.rb"), None);
+        assert_eq!(Language::from_path("show.json.jbuilder"), None);
+    }
+
+    #[test]
+    fn matches_mapping_patterns() {
+        assert!(matches_pattern("*.jbuilder", "show.json.jbuilder"));
+        assert!(matches_pattern("Gemfile", "Gemfile"));
+        assert!(matches_pattern("app/*/*.tsx", "app/views/list.tsx"));
+        assert!(matches_pattern("*.html.*", "index.html.erb"));
+        assert!(!matches_pattern("*.jbuilder", "jbuilder.rb"));
+        assert!(!matches_pattern("Gemfile", "Gemfile.lock"));
+        assert!(!matches_pattern("*.ab*ab", "x.ab"));
+    }
+
     #[test]
     fn all_extensions_are_supported() {
         for ext in Language::extensions().iter() {
             assert_ok!(Language::from_str(ext));
         }
     }
+
+    #[test]
+    fn parses_names_and_extensions() {
+        assert_eq!("Ruby".parse(), Ok(Language::Ruby));
+        assert_eq!("rb".parse(), Ok(Language::Ruby));
+        assert_eq!("Shell".parse(), Ok(Language::Sh));
+        assert_eq!("tsx".parse(), Ok(Language::TypeScript));
+        assert_eq!(
+            "cobol".parse::<Language>(),
+            Err(String::from("Unknown language: cobol"))
+        );
+        assert!("".parse::<Language>().is_err());
+        assert_eq!(Language::from_path("notes.python"), None);
+
+        for ext in Language::extensions().iter() {
+            let language = Language::from_extension(ext).unwrap();
+            assert_eq!(language.to_string().parse(), Ok(language));
+            let json = serde_json::to_string(&language).unwrap();
+            assert_eq!(serde_json::from_str::<Language>(&json).unwrap(), language);
+        }
+        assert_eq!(
+            serde_json::from_str::<Vec<Language>>(r#"["ruby", "ts"]"#).unwrap(),
+            vec![Language::Ruby, Language::TypeScript]
+        );
+    }
 }
@@ -18,17 +18,92 @@
 //!   Err(e) => eprintln!("{}", e),
 //! }
 //! ```
+//!
+//! # Features
+//!
+//! - `fs` (default): reading tags files and databases from disk, and falling back to extracting
+//!   definitions from source files. Without it, tags are parsed from strings with
+//!   `CtagItem::parse`, so the crate compiles to `wasm32-unknown-unknown`.
+//! - `wasm`: a JavaScript-facing wrapper, `parseTags` and `Tags`, built with wasm-bindgen:
+//!   `cargo build -p read_ctags --no-default-features --features wasm --target wasm32-unknown-unknown`.
+//! - `tree-sitter`: extracting fallback definitions with tree-sitter grammars.
+//! - `async`: `TagsReader::load_async`, for async servers on tokio, which reads and parses tags
+//!   on tokio's blocking thread pool rather than an executor thread.
 
+#[cfg(feature = "fs")]
+mod cscope;
 mod ctag_item;
+mod ctag_item_ref;
+#[cfg(feature = "fs")]
+mod dangling_tags;
+#[cfg(feature = "fs")]
+mod fallback_tags;
+#[cfg(feature = "fs")]
+mod gtags;
+mod interner;
+mod kind_filter;
+mod kind_table;
 mod language;
+#[cfg(feature = "fs")]
+mod locale_keys;
+#[cfg(feature = "fs")]
+mod location;
+mod parse_mode;
 mod parser;
+mod read_ctags_error;
+#[cfg(feature = "tree-sitter")]
+mod syntax_tags;
 mod tag_program;
+mod tag_query;
+mod tags_diff;
 mod tags_file;
+mod tags_format;
+#[cfg(feature = "fs")]
+mod tags_freshness;
+#[cfg(feature = "fs")]
+mod tags_generator;
+mod tags_index;
+#[cfg(feature = "fs")]
 mod tags_reader;
+mod tags_writer;
 mod token_kind;
+#[cfg(feature = "wasm")]
+mod wasm;
 
+#[cfg(feature = "fs")]
+pub use self::cscope::{CscopeReader, CSCOPE_PROGRAM_NAME};
 pub use self::ctag_item::*;
+pub use self::ctag_item_ref::*;
+#[cfg(feature = "fs")]
+pub use self::dangling_tags::*;
+#[cfg(feature = "fs")]
+pub use self::fallback_tags::*;
+#[cfg(feature = "fs")]
+pub use self::gtags::{GlobalTags, GTAGS_PROGRAM_NAME};
+pub use self::interner::*;
+pub use self::kind_filter::*;
+pub use self::kind_table::*;
 pub use self::language::*;
+#[cfg(feature = "fs")]
+pub use self::locale_keys::*;
+#[cfg(feature = "fs")]
+pub use self::location::*;
+pub use self::parse_mode::*;
+pub use self::read_ctags_error::*;
+#[cfg(feature = "tree-sitter")]
+pub use self::syntax_tags::*;
+pub use self::tag_query::*;
+pub use self::tags_diff::*;
 pub use self::tags_file::*;
+pub use self::tags_format::*;
+#[cfg(feature = "fs")]
+pub use self::tags_freshness::*;
+#[cfg(feature = "fs")]
+pub use self::tags_generator::*;
+pub use self::tags_index::*;
+#[cfg(feature = "fs")]
 pub use self::tags_reader::*;
+pub use self::tags_writer::*;
 pub use self::token_kind::*;
+#[cfg(feature = "wasm")]
+pub use self::wasm::*;
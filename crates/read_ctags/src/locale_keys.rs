@@ -0,0 +1,204 @@
+use super::{token_kind::TokenKind, CtagItem};
+use codebase_files::text;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// Keys holding a translation's plural forms, which are looked up by their parent's key
+const PLURAL_FORMS: [&str; 6] = ["zero", "one", "two", "few", "many", "other"];
+
+/// Is `path` a locale file, a YAML file within a `locales` directory (such as Rails'
+/// `config/locales/en.yml`)?
+pub fn is_locale_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("yml") | Some("yaml")
+    ) && path
+        .parent()
+        .is_some_and(|dir| dir.components().any(|c| c.as_os_str() == "locales"))
+}
+
+/// Extract the translation keys defined in each locale file among `files`
+///
+/// Keys are named by their full dotted path below the locale (so `users.show.title` for `title`
+/// nested under `en` and `users: show:`), as passed to translation helpers such as Rails' `t`.
+/// Plural forms (`one`, `other`, ...) are folded into the key holding them.
+pub fn translation_keys(files: &[PathBuf]) -> HashSet<CtagItem> {
+    files
+        .iter()
+        .filter(|path| is_locale_file(path))
+        .flat_map(|path| {
+            let contents = text::read(path).unwrap_or_default();
+            let file_path: Arc<Path> = path.as_path().into();
+            let raw_path: Arc<str> = path.to_string_lossy().into();
+
+            parse_keys(&contents)
+                .into_iter()
+                .map(move |(name, line)| CtagItem {
+                    name,
+                    file_path: file_path.clone(),
+                    raw_path: raw_path.clone(),
+                    address: line.to_string(),
+                    language: None,
+                    tags: BTreeMap::new(),
+                    kind: TokenKind::TranslationKey,
+                })
+        })
+        .collect()
+}
+
+// A mapping key, its indentation, and whether a value follows it on the same line
+struct Entry {
+    line: usize,
+    indent: usize,
+    key: String,
+    has_value: bool,
+}
+
+// Dotted keys of each translation below the locale, with the line defining it
+fn parse_keys(contents: &str) -> Vec<(String, usize)> {
+    let entries = entries(contents);
+    let mut parents: Vec<&Entry> = vec![];
+    let mut keys: Vec<(String, usize)> = vec![];
+
+    for (index, entry) in entries.iter().enumerate() {
+        while parents.last().is_some_and(|p| p.indent >= entry.indent) {
+            parents.pop();
+        }
+
+        let is_leaf = entry.has_value
+            || entries
+                .get(index + 1)
+                .is_none_or(|next| next.indent <= entry.indent);
+
+        // The outermost key is the locale itself
+        if is_leaf && !parents.is_empty() {
+            let mut path: Vec<&str> = parents[1..].iter().map(|p| p.key.as_str()).collect();
+            let line = if !path.is_empty() && PLURAL_FORMS.contains(&entry.key.as_str()) {
+                parents[parents.len() - 1].line
+            } else {
+                path.push(&entry.key);
+                entry.line
+            };
+            let key = path.join(".");
+            if !keys.iter().any(|(k, _)| *k == key) {
+                keys.push((key, line));
+            }
+        }
+
+        parents.push(entry);
+    }
+
+    keys
+}
+
+// Mapping keys, skipping the contents of block scalars and sequences, which hold values
+fn entries(contents: &str) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = vec![];
+    // Lines indented past this belong to a value rather than holding keys
+    let mut value_indent: Option<usize> = None;
+
+    for (index, line) in contents.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("---") {
+            continue;
+        }
+
+        let indent = line.len() - trimmed.len();
+        match value_indent {
+            Some(owner) if indent > owner => continue,
+            _ => value_indent = None,
+        }
+
+        if trimmed.starts_with("- ") || trimmed == "-" {
+            value_indent = entries.last().map(|e| e.indent).filter(|i| *i < indent);
+            if let Some(owner) = entries.last_mut().filter(|e| e.indent < indent) {
+                owner.has_value = true;
+            }
+            continue;
+        }
+
+        if let Some((key, value)) = mapping(trimmed) {
+            let value = value.trim();
+            if value.starts_with('|') || value.starts_with('>') {
+                value_indent = Some(indent);
+            }
+            entries.push(Entry {
+                line: index + 1,
+                indent,
+                key,
+                has_value: !value.is_empty() && !value.starts_with('#'),
+            });
+        }
+    }
+
+    entries
+}
+
+// `key: value`, with the key optionally quoted
+fn mapping(line: &str) -> Option<(String, &str)> {
+    let (key, rest) = match line.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let end = line[1..].find(quote)? + 1;
+            (&line[1..end], line[end + 1..].strip_prefix(':')?)
+        }
+        _ => {
+            let colon = line
+                .char_indices()
+                .find(|(i, c)| *c == ':' && line[i + 1..].chars().next().is_none_or(|n| n == ' '))
+                .map(|(i, _)| i)?;
+            (line[..colon].trim_end(), &line[colon + 1..])
+        }
+    };
+
+    (!key.is_empty()).then(|| (key.to_string(), rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(expected: &[(&str, usize)]) -> Vec<(String, usize)> {
+        expected
+            .iter()
+            .map(|(key, line)| (key.to_string(), *line))
+            .collect()
+    }
+
+    #[test]
+    fn names_keys_by_their_path_below_the_locale() {
+        assert_eq!(
+            parse_keys(
+                "# Users\nen:\n  users:\n    show:\n      title: \"Profile\"\n      \"empty\": ''\n    bio: |\n      Line: one\n      Line: two\n  greeting: Hello\n"
+            ),
+            keys(&[
+                ("users.show.title", 5),
+                ("users.show.empty", 6),
+                ("users.bio", 7),
+                ("greeting", 10)
+            ])
+        );
+    }
+
+    #[test]
+    fn folds_plural_forms_and_sequences_into_their_key() {
+        assert_eq!(
+            parse_keys(
+                "en:\n  inbox:\n    messages:\n      one: 1 message\n      other: \"%{count} messages\"\n  date:\n    day_names:\n      - Sunday\n      - Monday\n    order: [year, month]\n"
+            ),
+            keys(&[
+                ("inbox.messages", 3),
+                ("date.day_names", 7),
+                ("date.order", 10)
+            ])
+        );
+    }
+
+    #[test]
+    fn recognizes_locale_files() {
+        assert!(is_locale_file(Path::new("config/locales/en.yml")));
+        assert!(is_locale_file(Path::new("config/locales/users/de.yaml")));
+        assert!(!is_locale_file(Path::new("config/database.yml")));
+        assert!(!is_locale_file(Path::new("app/locales/en.json")));
+    }
+}
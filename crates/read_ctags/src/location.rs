@@ -0,0 +1,39 @@
+use super::CtagItem;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Where a tag is defined, with its line and column resolved from its file
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Location {
+    /// Path of the file defining the tag, as recorded in the tags file
+    pub path: Arc<Path>,
+    /// Line of the definition, when its address could be resolved
+    pub line: Option<usize>,
+    /// Column (1-based, in bytes) of the tag's name on its line, when it could be found
+    pub column: Option<usize>,
+}
+
+impl Location {
+    /// Resolve where `tag` is defined, reading its file at most once
+    ///
+    /// Tags addressed by line number keep their line when the file can't be read.
+    pub fn of(tag: &CtagItem) -> Self {
+        let source = fs::read_to_string(&tag.file_path).ok();
+        let line = match &source {
+            Some(source) => tag.resolve_line(source),
+            None => tag.address.parse().ok(),
+        };
+        let column = source
+            .as_deref()
+            .zip(line)
+            .and_then(|(source, line)| tag.resolve_column(source, line));
+
+        Location {
+            path: tag.file_path.clone(),
+            line,
+            column,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::fmt::{Display, Formatter};
+
+/// How a `TagsReader` or `TagsGenerator` treats malformed lines in a tags file
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ParseMode {
+    /// Fail the whole parse at the first malformed line
+    #[default]
+    Strict,
+    /// Skip malformed lines, logging a warning for each
+    Lenient,
+}
+
+/// A malformed line skipped when parsing leniently
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseWarning {
+    /// Line number within the tags file, starting at 1
+    pub line: usize,
+    /// The line as written
+    pub content: String,
+}
+
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Skipped malformed tag on line {}: {}",
+            self.line, self.content
+        )
+    }
+}
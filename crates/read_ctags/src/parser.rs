@@ -1,20 +1,31 @@
+pub mod diagnostics;
+pub mod escapes;
 mod internal;
+pub mod json;
+pub mod lines;
 use super::ctag_item::CtagItem;
+use super::ctag_item_ref::CtagItemRef;
+use super::interner::Interner;
 use super::language::Language;
+use super::parse_mode::ParseWarning;
 use super::tag_program::TagProgram;
 use super::token_kind::TokenKind;
+use codebase_files::paths;
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_until, take_while},
+    bytes::complete::{tag, take_until, take_while, take_while1},
     character::complete::{alphanumeric1, anychar},
     combinator::{map, opt, verify},
     error::context,
-    multi::separated_list,
+    multi::{many0, separated_list},
     sequence::{preceded, separated_pair, terminated, tuple},
     IResult,
 };
+use std::borrow::Cow;
 use std::collections::{BTreeMap, HashSet};
 use std::iter::FromIterator;
+use std::path::Path;
+#[cfg(test)]
 use std::path::PathBuf;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -24,18 +35,79 @@ enum ParsedField<'a> {
 }
 
 pub fn parse(input: &str) -> IResult<&str, (TagProgram, HashSet<CtagItem>)> {
-    tuple((
-        map(opt(internal::tag_metadata), |v| {
-            v.unwrap_or(TagProgram::default())
-        }),
-        tags_body,
-    ))(input)
+    let interner = Interner::default();
+    let (input, program) = program_parser(input)?;
+    let (input, tags) = tags_body(input, &program, &interner)?;
+
+    Ok((input, (program, tags)))
+}
+
+/// Parse tags line by line, skipping lines that aren't valid tags
+pub fn parse_lenient(input: &str) -> (TagProgram, HashSet<CtagItem>, Vec<ParseWarning>) {
+    let interner = Interner::default();
+    let (body, program) = program_parser(input).unwrap_or((input, TagProgram::default()));
+    let metadata_lines = input[..input.len() - body.len()].matches('\n').count();
+    let mut tags = HashSet::new();
+    let mut warnings = vec![];
+
+    for (index, line) in body.split('\n').enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+
+        match ctag_item_ref_parser(&program, line) {
+            Ok(("", item)) => {
+                tags.insert(item.to_owned_in(&interner));
+            }
+            _ => warnings.push(ParseWarning {
+                line: metadata_lines + index + 1,
+                content: line.to_string(),
+            }),
+        }
+    }
+
+    (program, tags, warnings)
+}
+
+pub fn parse_borrowed(input: &str) -> IResult<&str, (TagProgram, Vec<CtagItemRef<'_>>)> {
+    let (input, program) = program_parser(input)?;
+    let (input, tags) = terminated(
+        separated_list(tag("\n"), |i| ctag_item_ref_parser(&program, i)),
+        opt(tag("\n")),
+    )(input)?;
+
+    Ok((input, (program, tags)))
 }
 
-fn tags_body(input: &str) -> IResult<&str, HashSet<CtagItem>> {
+/// Parse a single line of tags written by Universal Ctags, such as one from `CtagItem::encode`
+pub(crate) fn parse_line(line: &str) -> Option<CtagItem> {
+    match ctag_item_ref_parser(&TagProgram::default(), line) {
+        Ok(("", item)) => Some(item.to_owned()),
+        _ => None,
+    }
+}
+
+fn program_parser(input: &str) -> IResult<&str, TagProgram> {
+    map(opt(internal::tag_metadata), |v| {
+        v.unwrap_or(TagProgram::default())
+    })(input)
+}
+
+// Each tag is copied out of the input as it's parsed, so borrowed tags never accumulate, with
+// repeated paths and scopes shared through `interner`
+fn tags_body<'a>(
+    input: &'a str,
+    program: &TagProgram,
+    interner: &Interner,
+) -> IResult<&'a str, HashSet<CtagItem>> {
     terminated(
         map(
-            separated_list(tag("\n"), ctag_item_parser),
+            separated_list(tag("\n"), |i| {
+                map(
+                    |i| ctag_item_ref_parser(program, i),
+                    |item| item.to_owned_in(interner),
+                )(i)
+            }),
             HashSet::from_iter,
         ),
         opt(tag("\n")),
@@ -43,13 +115,10 @@ fn tags_body(input: &str) -> IResult<&str, HashSet<CtagItem>> {
 }
 
 fn is_kind(field: &ParsedField) -> bool {
-    match field {
-        ParsedField::KindField(_) => true,
-        _ => false,
-    }
+    matches!(field, ParsedField::KindField(_))
 }
 
-fn key_value_parser(input: &str) -> IResult<&str, ParsedField> {
+fn key_value_parser(input: &str) -> IResult<&str, ParsedField<'_>> {
     map(
         separated_pair(
             alphanumeric1,
@@ -60,51 +129,124 @@ fn key_value_parser(input: &str) -> IResult<&str, ParsedField> {
     )(input)
 }
 
-fn kind_parser(input: &str) -> IResult<&str, ParsedField> {
+fn kind_parser(input: &str) -> IResult<&str, ParsedField<'_>> {
     map(anychar, ParsedField::KindField)(input)
 }
 
-fn fields_parser(input: &str) -> IResult<&str, Vec<ParsedField>> {
+// The kind's letter, or its full name as Exuberant Ctags writes with `--fields=+K`
+fn first_kind_parser(input: &str) -> IResult<&str, ParsedField<'_>> {
+    let field = take_while1(|c| c != '\t' && c != '\n');
+    map(
+        verify(field, |kind: &str| !kind.contains(':')),
+        |kind: &str| {
+            let mut chars = kind.chars();
+            match (chars.next(), chars.next()) {
+                (Some(letter), None) => ParsedField::KindField(letter),
+                _ => ParsedField::ParsedField("kind", kind),
+            }
+        },
+    )(input)
+}
+
+fn fields_parser(input: &str) -> IResult<&str, Vec<ParsedField<'_>>> {
     let field_parser = alt((key_value_parser, kind_parser));
     preceded(tag("\t"), separated_list(tag("\t"), field_parser))(input)
 }
 
-fn address_and_fields_parser(input: &str) -> IResult<&str, (String, Vec<ParsedField>)> {
+// Only the first field, where Exuberant Ctags writes the kind, may be a kind's full name
+fn exuberant_fields_parser(input: &str) -> IResult<&str, Vec<ParsedField<'_>>> {
+    let (input, first) =
+        preceded(tag("\t"), opt(alt((key_value_parser, first_kind_parser))))(input)?;
+    let (input, rest) = match first {
+        Some(_) => many0(preceded(tag("\t"), alt((key_value_parser, kind_parser))))(input)?,
+        None => (input, vec![]),
+    };
+
+    Ok((input, first.into_iter().chain(rest).collect()))
+}
+
+fn address_and_fields_parser<'a>(
+    program: &TagProgram,
+    input: &'a str,
+) -> IResult<&'a str, (&'a str, Vec<ParsedField<'a>>)> {
+    let fields = if program.is_exuberant() {
+        exuberant_fields_parser
+    } else {
+        fields_parser
+    };
+
     alt((
-        tuple((tag_address_parser, fields_parser)),
+        tuple((tag_address_parser, fields)),
         tuple((
             tag_address_without_fields_parser,
-            internal::succeed(|| vec![]),
+            internal::succeed(Vec::new),
         )),
     ))(input)
 }
 
-fn tag_address_parser(input: &str) -> IResult<&str, String> {
-    terminated(
-        map(
+fn tag_address_parser(input: &str) -> IResult<&str, &str> {
+    match address_length(input) {
+        Some(length) => Ok((&input[length + 2..], &input[..length])),
+        None => terminated(
             verify(take_until(";\""), |s: &str| !s.contains('\n')),
-            |v: &str| v.to_string(),
-        ),
-        tag(";\""),
-    )(input)
+            tag(";\""),
+        )(input),
+    }
 }
 
-fn tag_address_without_fields_parser(input: &str) -> IResult<&str, String> {
-    map(internal::to_newline, |v| v.to_string())(input)
+// Length of the address starting `input`, up to the `;"` ending it
+//
+// Search patterns (`/^x = ";"$/` or `?...?`) are written as they appear in the source, so may
+// contain tabs or `;"`; only a `;"` outside of one ends the address. Returns `None` when no `;"`
+// is found before the end of the line, such as for an unterminated pattern.
+fn address_length(input: &str) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let mut delimiter = None;
+    let mut index = 0;
+
+    while index < bytes.len() {
+        match (delimiter, bytes[index]) {
+            (_, b'\n') => return None,
+            (Some(_), b'\\') => index += 1,
+            (Some(d), c) if c == d => delimiter = None,
+            (None, c @ b'/') | (None, c @ b'?') => delimiter = Some(c),
+            (None, b';') if bytes.get(index + 1) == Some(&b'"') => return Some(index),
+            _ => {}
+        }
+        index += 1;
+    }
+
+    None
 }
 
+fn tag_address_without_fields_parser(input: &str) -> IResult<&str, &str> {
+    internal::to_newline(input)
+}
+
+#[cfg(test)]
 fn ctag_item_parser(input: &str) -> IResult<&str, CtagItem> {
+    let (input, item) = ctag_item_ref_parser(&TagProgram::default(), input)?;
+    Ok((input, item.to_owned()))
+}
+
+// Tags written by Exuberant Ctags use its own kind letters, and may name the kind in full
+fn ctag_item_ref_parser<'a>(
+    program: &TagProgram,
+    input: &'a str,
+) -> IResult<&'a str, CtagItemRef<'a>> {
     let (input, name) = context("tagName", internal::to_tab)(input)?;
-    let (input, file_path) = context("tagPath", map(internal::to_tab, PathBuf::from))(input)?;
-    let (input, (address, parsed_fields)) = address_and_fields_parser(input)?;
+    let (input, raw_path) = context("tagPath", internal::to_tab)(input)?;
+    let file_path = tag_path(raw_path);
+    let (input, (address, parsed_fields)) = address_and_fields_parser(program, input)?;
     let language = Language::from_path(&file_path);
-    let (kind, tags) = build_kind_and_fields(language, parsed_fields);
+    let (kind, tags) = build_kind_and_fields(program, language, parsed_fields);
 
     Ok((
         input,
-        CtagItem {
-            name: name.to_string(),
+        CtagItemRef {
+            name,
             file_path,
+            raw_path,
             address,
             language,
             tags,
@@ -113,10 +255,21 @@ fn ctag_item_parser(input: &str) -> IResult<&str, CtagItem> {
     ))
 }
 
+// `ctags -R .` prefixes paths with `./` (and uses backslashes on Windows), but searched files
+// are relative paths without it; paths already in that form are borrowed as written
+pub(crate) fn tag_path(input: &str) -> Cow<'_, Path> {
+    if input.starts_with("./") || input.contains('\\') || input.contains("//") {
+        Cow::Owned(paths::normalize(input))
+    } else {
+        Cow::Borrowed(Path::new(input))
+    }
+}
+
 fn build_kind_and_fields<'a>(
+    program: &TagProgram,
     language: Option<Language>,
     parsed_fields: Vec<ParsedField<'a>>,
-) -> (TokenKind, BTreeMap<String, String>) {
+) -> (TokenKind, BTreeMap<&'a str, Cow<'a, str>>) {
     let (kind, rest): (Vec<ParsedField>, Vec<ParsedField>) =
         parsed_fields.iter().partition(|&f| is_kind(f));
 
@@ -124,22 +277,45 @@ fn build_kind_and_fields<'a>(
 
     for field in rest.iter() {
         match field {
-            ParsedField::ParsedField(k, v) => hash.insert((*k).to_string(), (*v).to_string()),
+            ParsedField::ParsedField(k, v) => hash.insert(*k, escapes::unescape(v)),
             _ => None,
         };
     }
 
-    match (kind.len(), kind.get(0)) {
-        (1, Some(ParsedField::KindField(c))) => (TokenKind::from_ctag(language, *c), hash),
+    match (kind.len(), kind.first()) {
+        (1, Some(ParsedField::KindField(c))) => (letter_kind(program, language, *c), hash),
+        // Written as `kind:f` or `kind:function` with `--fields=+z`, or in full with `+K`
+        // The field is kept, so the tag is encoded as written
+        (0, _) => match hash.get("kind") {
+            Some(kind) => (named_kind(program, language, kind), hash),
+            None => (TokenKind::Undefined, hash),
+        },
         (_, _) => (TokenKind::Undefined, hash),
     }
 }
 
+fn letter_kind(program: &TagProgram, language: Option<Language>, letter: char) -> TokenKind {
+    if program.is_exuberant() {
+        TokenKind::from_exuberant_ctag(language, letter)
+    } else {
+        TokenKind::from_ctag(language, letter)
+    }
+}
+
+fn named_kind(program: &TagProgram, language: Option<Language>, kind: &str) -> TokenKind {
+    let mut chars = kind.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) => letter_kind(program, language, letter),
+        _ => TokenKind::from_name(kind).unwrap_or(TokenKind::Undefined),
+    }
+}
+
 #[test]
 fn parses_without_metadata() {
-    let result: HashSet<CtagItem> = vec![CtagItem {
+    let result: HashSet<CtagItem> = [CtagItem {
         name: String::from("withInfo"),
-        file_path: PathBuf::from("path/to/file.rb"),
+        file_path: PathBuf::from("path/to/file.rb").into(),
+        raw_path: "path/to/file.rb".into(),
         address: String::from("45"),
         language: Some(Language::Ruby),
         tags: BTreeMap::new(),
@@ -159,6 +335,22 @@ fn parses_without_metadata() {
     );
 }
 
+#[test]
+fn skips_malformed_lines_when_lenient() {
+    let input = "!_TAG_PROGRAM_NAME\tUniversal Ctags\t//\nPerson\tapp/person.rb\t1;\"\tc\ntruncated\n\nname\tapp/person.rb\t2;\"\tf\n";
+    let (program, tags, warnings) = parse_lenient(input);
+
+    assert_eq!(program.name, Some(String::from("Universal Ctags")));
+    assert_eq!(tags.len(), 2);
+    assert_eq!(
+        warnings,
+        vec![ParseWarning {
+            line: 3,
+            content: String::from("truncated")
+        }]
+    );
+}
+
 #[test]
 fn parses_fields() {
     assert_eq!(
@@ -175,7 +367,8 @@ fn parses_item_lines() {
             "",
             CtagItem {
                 name: String::from("withInfo"),
-                file_path: PathBuf::from("path/to/file.rb"),
+                file_path: PathBuf::from("path/to/file.rb").into(),
+                raw_path: "path/to/file.rb".into(),
                 address: String::from("45"),
                 language: Some(Language::Ruby),
                 tags: BTreeMap::new(),
@@ -193,10 +386,11 @@ fn parses_multiple_lines() {
             "",
             (
                 TagProgram::default(),
-                vec![
+                [
                     CtagItem {
                         name: String::from("first"),
-                        file_path: PathBuf::from("path/to/file.rb"),
+                        file_path: PathBuf::from("path/to/file.rb").into(),
+                        raw_path: "path/to/file.rb".into(),
                         address: String::from("1"),
                         language: Some(Language::Ruby),
                         tags: BTreeMap::new(),
@@ -204,7 +398,8 @@ fn parses_multiple_lines() {
                     },
                     CtagItem {
                         name: String::from("second"),
-                        file_path: PathBuf::from("path/to/file.rb"),
+                        file_path: PathBuf::from("path/to/file.rb").into(),
+                        raw_path: "path/to/file.rb".into(),
                         address: String::from("2"),
                         language: Some(Language::Ruby),
                         tags: BTreeMap::new(),
@@ -245,6 +440,22 @@ fn parses_fields_with_non_alphanumeric_values() {
     );
 }
 
+#[test]
+fn parses_non_ascii_names_addresses_and_fields() {
+    let tags_file = CtagItem::parse(
+        PathBuf::from("tags"),
+        "größe\tapp/café.rb\t/^  def größe; \"ü\" end$/;\"\tf\tclass:Über\n",
+    )
+    .unwrap();
+    let tag = tags_file.tags.iter().next().unwrap();
+
+    assert_eq!(tag.name, "größe");
+    assert_eq!(&*tag.file_path, Path::new("app/café.rb"));
+    assert_eq!(tag.address, "/^  def größe; \"ü\" end$/");
+    assert_eq!(tag.scope(), Some("Über"));
+    assert_eq!(tag.resolve_column("def größe; \"ü\" end", 1), Some(5));
+}
+
 #[test]
 fn parses_kinds_only() {
     assert_eq!(
@@ -256,27 +467,128 @@ fn parses_kinds_only() {
 #[test]
 fn parses_addresses_with_fields() {
     assert_eq!(
-        address_and_fields_parser("/^  context \"#active\" do$/;\"\tc"),
+        address_and_fields_parser(&TagProgram::default(), "/^  context \"#active\" do$/;\"\tc"),
         Ok((
             "",
             (
-                String::from("/^  context \"#active\" do$/"),
+                "/^  context \"#active\" do$/",
                 vec![ParsedField::KindField('c'),]
             )
         ))
     );
 }
 
+#[test]
+fn parses_paths_with_spaces() {
+    let (_, item) =
+        ctag_item_parser("Heading\tdocs/My Notes/file name.md\t/^# Heading$/;\"\tc").unwrap();
+
+    assert_eq!(&*item.file_path, Path::new("docs/My Notes/file name.md"));
+    assert_eq!(item.address, "/^# Heading$/");
+    assert_eq!(item.language, Some(Language::Markdown));
+
+    let (_, item) = ctag_item_parser("Person\t./app/my models/person.rb\t12").unwrap();
+
+    assert_eq!(&*item.file_path, Path::new("app/my models/person.rb"));
+    assert_eq!(item.address, "12");
+}
+
+#[test]
+fn parses_windows_paths() {
+    let (_, item) = ctag_item_parser("Person\t.\\app\\models\\person.rb\t12").unwrap();
+
+    assert_eq!(&*item.file_path, Path::new("app/models/person.rb"));
+    assert_eq!(&*item.raw_path, ".\\app\\models\\person.rb");
+    assert_eq!(item.language, Some(Language::Ruby));
+}
+
+#[test]
+fn encodes_tags_as_written() {
+    let line = ".full_name\t.\\app\\models\\person.rb\t/^  def full_name$/;\"\tf\tclass:Person";
+    let (_, item) = ctag_item_parser(line).unwrap();
+
+    assert_eq!(item.kind, TokenKind::Method);
+    assert_eq!(item.encode(), line);
+}
+
+#[test]
+fn encodes_kinds_unchanged() {
+    for line in [
+        "render\tapp/view.js\t3;\"\tkind:function",
+        "main\tcmd/main.go\t5;\"\tf",
+        "name\tapp/person.rb\t2;\"\tX\tclass:Person",
+    ] {
+        let (_, item) = ctag_item_parser(line).unwrap();
+        assert_eq!(item.encode(), line);
+    }
+}
+
+#[test]
+fn unescapes_field_values() {
+    let line = "join\tsrc/lib.rs\t12;\"\tf\tdoc:C:\\\\src\tsignature:(a,\\tb: &str)";
+    let (_, item) = ctag_item_parser(line).unwrap();
+
+    assert_eq!(
+        item.tags.get("signature").map(|v| &**v),
+        Some("(a,\tb: &str)")
+    );
+    assert_eq!(item.tags.get("doc").map(|v| &**v), Some("C:\\src"));
+    assert_eq!(item.encode(), line);
+}
+
+#[test]
+fn parses_search_patterns_containing_tabs_and_field_separators() {
+    let line = "foo\tapp/foo.rb\t/^\tdef foo;\"bar\\/baz;\"$/;\"\tf\tclass:Foo";
+    let (rest, item) = ctag_item_parser(line).unwrap();
+
+    assert_eq!(rest, "");
+    assert_eq!(item.address, "/^\tdef foo;\"bar\\/baz;\"$/");
+    assert_eq!(item.kind, TokenKind::Method);
+    assert_eq!(item.tags.get("class").map(|v| &**v), Some("Foo"));
+    assert_eq!(item.encode(), line);
+    assert_eq!(
+        address_and_fields_parser(&TagProgram::default(), "?^x = \";\"?;\"\tv"),
+        Ok(("", ("?^x = \";\"?", vec![ParsedField::KindField('v')])))
+    );
+}
+
 #[test]
 fn parses_when_address_includes_semicolon() {
     assert_eq!(
-        address_and_fields_parser("/^$z-tooltip: $base-z-index + 18;$/;\"\tv"),
+        address_and_fields_parser(
+            &TagProgram::default(),
+            "/^$z-tooltip: $base-z-index + 18;$/;\"\tv"
+        ),
         Ok((
             "",
             (
-                String::from("/^$z-tooltip: $base-z-index + 18;$/"),
+                "/^$z-tooltip: $base-z-index + 18;$/",
                 vec![ParsedField::KindField('v'),]
             )
         ))
     );
 }
+
+#[test]
+fn parses_kinds_written_by_exuberant_ctags() {
+    let input = "!_TAG_PROGRAM_NAME\tExuberant Ctags\t//\n!_TAG_PROGRAM_VERSION\t5.8\t//\nPerson\tapp/person.rb\t1;\"\tclass\ncreate\tapp/person.rb\t2;\"\tF\tclass:Person\nrender\tapp/view.js\t3;\"\tkind:function\n";
+    let (_, (program, tags)) = parse(input).unwrap();
+    let kind = |name: &str| tags.iter().find(|t| t.name == name).map(|t| t.kind.clone());
+
+    assert!(program.is_exuberant());
+    assert_eq!(kind("Person"), Some(TokenKind::Class));
+    assert_eq!(kind("create"), Some(TokenKind::SingletonMethod));
+    assert_eq!(kind("render"), Some(TokenKind::Function));
+
+    let (_, (_, tags)) = parse("create\tapp/person.rb\t2;\"\tF\n").unwrap();
+    assert_ne!(
+        tags.iter().next().map(|t| t.kind.clone()),
+        Some(TokenKind::SingletonMethod)
+    );
+    assert_eq!(
+        parse_lenient("Person\tapp/person.rb\t1;\"\tclass\n")
+            .2
+            .len(),
+        1
+    );
+}
@@ -0,0 +1,153 @@
+use crate::ctag_item::CtagsParseError;
+use nom::error::ErrorKind;
+
+// Longest snippet of an invalid line included in an error, in characters
+const SNIPPET_LENGTH: usize = 80;
+
+/// Describe the line of `input` where parsing stopped, given the `rest` of `input` left unparsed
+pub fn invalid_tag(input: &str, rest: &str) -> CtagsParseError {
+    // Parsing stops before the newline preceding a line that isn't a valid tag
+    let offset = input.len() - rest.len() + usize::from(rest.starts_with('\n'));
+    let start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = input[offset..]
+        .find('\n')
+        .map_or(input.len(), |i| offset + i);
+
+    invalid_line(input[..start].matches('\n').count() + 1, &input[start..end])
+}
+
+/// Describe `line`, the `number`th line of a tags file, which isn't a valid tag
+pub fn invalid_line(number: usize, line: &str) -> CtagsParseError {
+    CtagsParseError::InvalidTag {
+        line: number,
+        reason: reason(line),
+        snippet: snippet(line),
+    }
+}
+
+/// Describe the line of `input` where a nom parser failed
+pub fn from_nom(input: &str, error: nom::Err<(&str, ErrorKind)>) -> CtagsParseError {
+    match error {
+        nom::Err::Error((rest, _)) | nom::Err::Failure((rest, _)) => invalid_tag(input, rest),
+        nom::Err::Incomplete(_) => invalid_tag(input, ""),
+    }
+}
+
+fn reason(line: &str) -> String {
+    let mut columns = line.splitn(3, '\t');
+    columns.next();
+
+    match (columns.next(), columns.next()) {
+        (None, _) => String::from("expected a tab after the tag name"),
+        (Some(_), None) => String::from("expected a tab after the path"),
+        (Some(_), Some(address_and_fields)) => fields_reason(address_and_fields),
+    }
+}
+
+// Fields follow an address ending in `;"`; without one, the rest of the line is the address
+fn fields_reason(address_and_fields: &str) -> String {
+    let fields = super::address_length(address_and_fields)
+        .and_then(|length| address_and_fields[length + 2..].strip_prefix('\t'))
+        .or_else(|| {
+            address_and_fields
+                .split_once(";\"\t")
+                .map(|(_, fields)| fields)
+        });
+    let fields = match fields {
+        Some(fields) => fields,
+        None => return String::from("unexpected content"),
+    };
+    let fields: Vec<&str> = fields.split('\t').collect();
+
+    fields
+        .iter()
+        .enumerate()
+        .find(|(index, field)| !is_field(field, *index == fields.len() - 1))
+        .map_or_else(
+            || String::from("unexpected content"),
+            |(_, field)| format!("unexpected field '{}'", field.escape_debug()),
+        )
+}
+
+// A field is a single-character kind or a `key:value` pair; only the last may be empty
+fn is_field(field: &str, last: bool) -> bool {
+    match field.split_once(':') {
+        _ if field.chars().count() == 1 => true,
+        Some((key, _)) => !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric()),
+        None => field.is_empty() && last,
+    }
+}
+
+fn snippet(line: &str) -> String {
+    let mut snippet: String = line.chars().take(SNIPPET_LENGTH).collect();
+    if snippet.len() < line.len() {
+        snippet.push_str("...");
+    }
+    snippet.escape_debug().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe(input: &str, rest: &str) -> (usize, String, String) {
+        match invalid_tag(input, rest) {
+            CtagsParseError::InvalidTag {
+                line,
+                reason,
+                snippet,
+            } => (line, reason, snippet),
+            e => panic!("Unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn describes_invalid_fields() {
+        let input =
+            "Person\tapp/person.rb\t1;\"\tc\nname\tapp/person.rb\t2;\"\tfoo\tclass:Person\n";
+        let rest = &input[input.find("oo\t").unwrap()..];
+
+        assert_eq!(
+            describe(input, rest),
+            (
+                2,
+                String::from("unexpected field 'foo'"),
+                String::from("name\\tapp/person.rb\\t2;\\\"\\tfoo\\tclass:Person")
+            )
+        );
+        assert_eq!(
+            invalid_tag(input, rest).to_string(),
+            "Failed to parse ctags file on line 2: unexpected field 'foo'\n    name\\tapp/person.rb\\t2;\\\"\\tfoo\\tclass:Person"
+        );
+    }
+
+    #[test]
+    fn describes_lines_missing_columns() {
+        let input = "Person\tapp/person.rb\t1;\"\tc\ntruncated\tapp";
+        let rest = &input[input.find('\n').unwrap()..];
+
+        assert_eq!(describe(input, rest).0, 2);
+        assert_eq!(describe(input, rest).1, "expected a tab after the path");
+        assert_eq!(
+            describe("truncated", "truncated").1,
+            "expected a tab after the tag name"
+        );
+    }
+
+    #[test]
+    fn skips_search_patterns() {
+        let input = "foo\tapp/foo.rb\t/^  x = \";\"\tfoo$/;\"\tf\tbar";
+
+        assert_eq!(describe(input, input).1, "unexpected field 'bar'");
+    }
+
+    #[test]
+    fn truncates_long_lines() {
+        let input = format!("name\tpath\t1;\"\t{}", "x".repeat(100));
+        let (_, reason, snippet) = describe(&input, &input);
+
+        assert!(reason.starts_with("unexpected field 'xxx"));
+        assert_eq!(snippet.chars().count(), SNIPPET_LENGTH + 7);
+        assert!(snippet.ends_with("x..."));
+    }
+}
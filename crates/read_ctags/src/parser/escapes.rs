@@ -0,0 +1,90 @@
+use std::borrow::Cow;
+
+/// Unescape a field value as written in a tags file
+///
+/// Tabs, newlines, carriage returns, and backslashes in field values are written as `\t`,
+/// `\n`, `\r`, and `\\`; a backslash before any other character is kept as written. Values
+/// without a backslash are borrowed.
+pub fn unescape(value: &str) -> Cow<'_, str> {
+    if !value.contains('\\') {
+        return Cow::Borrowed(value);
+    }
+
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    Cow::Owned(unescaped)
+}
+
+/// Escape a field value to be written in a tags file, reversing `unescape`
+///
+/// Every backslash is doubled, as Universal Ctags writes them, so values read from its tags
+/// files encode byte for byte. An unknown escape kept as written by `unescape` (such as `\d`
+/// from another tool) is normalized: it encodes as `\\d`, which reads back as the same value.
+pub fn escape(value: &str) -> Cow<'_, str> {
+    if !value.contains(['\t', '\n', '\r', '\\']) {
+        return Cow::Borrowed(value);
+    }
+
+    let mut escaped = String::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        match c {
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\\' => escaped.push_str("\\\\"),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescapes_field_values() {
+        assert_eq!(unescape("(a, b)"), Cow::Borrowed("(a, b)"));
+        assert_eq!(unescape("(a,\\tb)"), "(a,\tb)");
+        assert_eq!(unescape("\\r\\n"), "\r\n");
+        assert_eq!(unescape("C:\\\\src"), "C:\\src");
+        assert_eq!(unescape("\\d+\\"), "\\d+\\");
+    }
+
+    #[test]
+    fn escapes_field_values() {
+        for value in ["(a, b)", "(a,\tb)", "\r\n", "C:\\src", "\\t"] {
+            assert_eq!(unescape(&escape(value)), value);
+        }
+        assert_eq!(escape("(a,\tb)"), "(a,\\tb)");
+        assert_eq!(escape("C:\\src"), "C:\\\\src");
+    }
+
+    #[test]
+    fn normalizes_unknown_escapes() {
+        let value = unescape("\\d+");
+        let normalized = escape(&value);
+
+        assert_eq!(normalized, "\\\\d+");
+        assert_eq!(unescape(&normalized), value);
+        assert_eq!(escape(&unescape(&normalized)), normalized);
+    }
+}
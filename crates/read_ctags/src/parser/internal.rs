@@ -13,6 +13,7 @@ enum ProgramMetadata {
     Author(String),
     Name(String),
     Version(String),
+    Cwd(String),
     Other,
 }
 
@@ -37,6 +38,13 @@ impl ProgramMetadata {
             _ => None,
         }
     }
+
+    fn cwd(&self) -> Option<String> {
+        match &self {
+            ProgramMetadata::Cwd(v) => Some(v.to_string()),
+            _ => None,
+        }
+    }
 }
 
 fn metadata_to_tag_program(metadata: Vec<ProgramMetadata>) -> TagProgram {
@@ -53,10 +61,13 @@ fn metadata_to_tag_program(metadata: Vec<ProgramMetadata>) -> TagProgram {
         .find(|m| m.version().is_some())
         .and_then(|m| m.version());
 
+    let cwd = metadata.iter().find_map(|m| m.cwd());
+
     TagProgram {
         name,
         author,
         version,
+        cwd,
     }
 }
 
@@ -68,7 +79,13 @@ pub fn tag_metadata(input: &str) -> IResult<&str, TagProgram> {
 }
 
 fn tag_annotation(input: &str) -> IResult<&str, ProgramMetadata> {
-    alt((program_author, program_name, program_version, program_other))(input)
+    alt((
+        program_author,
+        program_name,
+        program_version,
+        program_cwd,
+        program_other,
+    ))(input)
 }
 
 fn tag_value<'a>(tag_name: &'a str) -> impl Fn(&'a str) -> IResult<&'a str, String> {
@@ -120,6 +137,10 @@ fn program_version(input: &str) -> IResult<&str, ProgramMetadata> {
     map(tag_value("!_TAG_PROGRAM_VERSION"), ProgramMetadata::Version)(input)
 }
 
+fn program_cwd(input: &str) -> IResult<&str, ProgramMetadata> {
+    map(tag_value("!_TAG_PROC_CWD"), ProgramMetadata::Cwd)(input)
+}
+
 pub fn succeed<I: Clone, O, F: Copy + FnOnce() -> O, E: ParseError<I>>(
     success: F,
 ) -> impl Fn(I) -> IResult<I, O, E> {
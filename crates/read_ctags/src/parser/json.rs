@@ -0,0 +1,222 @@
+use super::tag_path;
+use crate::ctag_item::{CtagItem, CtagsParseError};
+use crate::interner::Interner;
+use crate::language::Language;
+use crate::parse_mode::ParseWarning;
+use crate::tag_program::TagProgram;
+use crate::token_kind::TokenKind;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+// Fields mapped onto `CtagItem` itself rather than kept as metadata tags
+const ITEM_FIELDS: [&str; 7] = [
+    "_type",
+    "name",
+    "path",
+    "pattern",
+    "kind",
+    "scope",
+    "scopeKind",
+];
+
+/// Parse tags written with `ctags --output-format=json`, one object per line
+///
+/// Pseudo-tags (`"_type": "ptag"`) describe the program; every other field of a tag is kept
+/// under `tags` the way the tab-separated format writes it, so a scope is recorded under its
+/// kind (e.g. `class`) and line numbers under `line`.
+pub fn parse(input: &str) -> Result<(TagProgram, HashSet<CtagItem>), CtagsParseError> {
+    let mut program = TagProgram::default();
+    let mut tags = HashSet::new();
+    let interner = Interner::default();
+
+    for (index, line) in input.lines().enumerate() {
+        read_line(line, &mut program, &mut tags, &interner)
+            .map_err(|e| CtagsParseError::InvalidJson(index + 1, e))?;
+    }
+
+    Ok((program, tags))
+}
+
+/// Parse JSON tags, skipping lines that aren't valid tags
+pub fn parse_lenient(input: &str) -> (TagProgram, HashSet<CtagItem>, Vec<ParseWarning>) {
+    let mut program = TagProgram::default();
+    let mut tags = HashSet::new();
+    let mut warnings = vec![];
+    let interner = Interner::default();
+
+    for (index, line) in input.lines().enumerate() {
+        if read_line(line, &mut program, &mut tags, &interner).is_err() {
+            warnings.push(ParseWarning {
+                line: index + 1,
+                content: line.to_string(),
+            });
+        }
+    }
+
+    (program, tags, warnings)
+}
+
+/// Read one line of JSON tags into `program` and `tags`, erring when it isn't a valid tag
+pub fn read_line(
+    line: &str,
+    program: &mut TagProgram,
+    tags: &mut HashSet<CtagItem>,
+    interner: &Interner,
+) -> Result<(), Option<serde_json::Error>> {
+    if line.trim().is_empty() {
+        return Ok(());
+    }
+
+    let object = match serde_json::from_str(line) {
+        Ok(Value::Object(object)) => object,
+        Ok(_) => return Err(None),
+        Err(e) => return Err(Some(e)),
+    };
+
+    match object.get("_type").and_then(Value::as_str) {
+        Some("ptag") => read_pseudo_tag(&object, program),
+        Some("tag") => {
+            tags.insert(tag(&object, interner).ok_or(None)?);
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+// Pseudo-tags record their value under `path`
+fn read_pseudo_tag(object: &Map<String, Value>, program: &mut TagProgram) {
+    let value = object.get("path").and_then(Value::as_str).map(String::from);
+
+    match object.get("name").and_then(Value::as_str) {
+        Some("TAG_PROGRAM_NAME") => program.name = value,
+        Some("TAG_PROGRAM_AUTHOR") => program.author = value,
+        Some("TAG_PROGRAM_VERSION") => program.version = value,
+        Some("TAG_PROC_CWD") => program.cwd = value,
+        _ => {}
+    }
+}
+
+// Paths and fields repeated across tags are shared through `interner`
+fn tag(object: &Map<String, Value>, interner: &Interner) -> Option<CtagItem> {
+    let name = object.get("name")?.as_str()?;
+    let raw_path = object.get("path")?.as_str()?;
+    let file_path = interner.share_path(&tag_path(raw_path));
+
+    let mut tags: BTreeMap<Arc<str>, Arc<str>> = object
+        .iter()
+        .filter(|(key, _)| !ITEM_FIELDS.contains(&key.as_str()))
+        .filter_map(|(key, value)| {
+            field_value(value).map(|v| (interner.share(key), interner.share(&v)))
+        })
+        .collect();
+    if let Some(scope) = object.get("scope").and_then(Value::as_str) {
+        let scope_kind = object.get("scopeKind").and_then(Value::as_str);
+        tags.insert(
+            interner.share(scope_kind.unwrap_or("scope")),
+            interner.share(scope),
+        );
+    }
+
+    let address = match object.get("pattern").and_then(Value::as_str) {
+        Some(pattern) => pattern.to_string(),
+        None => tags.get("line")?.to_string(),
+    };
+    let language = Language::from_path(&file_path).or_else(|| {
+        tags.get("language")
+            .and_then(|language| Language::from_name(language))
+    });
+    let kind = match object.get("kind").and_then(Value::as_str) {
+        Some(kind) if kind.chars().count() == 1 => {
+            TokenKind::from_ctag(language, kind.chars().next()?)
+        }
+        Some(kind) => TokenKind::from_name(kind).unwrap_or(TokenKind::Undefined),
+        None => TokenKind::Undefined,
+    };
+
+    Some(CtagItem {
+        name: name.to_string(),
+        file_path,
+        raw_path: interner.share(raw_path),
+        address,
+        language,
+        tags,
+        kind,
+    })
+}
+
+fn field_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.to_string()),
+        Value::Number(value) => Some(value.to_string()),
+        Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    const TAGS: &str = r#"{"_type": "ptag", "name": "JSON_OUTPUT_VERSION", "path": "0.0", "pattern": "in development"}
+{"_type": "ptag", "name": "TAG_PROGRAM_NAME", "path": "Universal Ctags", "pattern": "Derived from Exuberant Ctags"}
+{"_type": "ptag", "name": "TAG_PROGRAM_VERSION", "path": "6.1.0", "pattern": "p6.1.20240421.0"}
+{"_type": "tag", "name": "full_name", "path": "./app/models/person.rb", "pattern": "/^  def full_name$/", "language": "Ruby", "line": 12, "kind": "method", "scope": "Person", "scopeKind": "class"}
+{"_type": "tag", "name": "Person", "path": "app/models/person.rb", "line": 1, "kind": "c"}
+"#;
+
+    #[test]
+    fn parses_json_tags() {
+        let (program, tags) = parse(TAGS).unwrap();
+        let full_name = tags.iter().find(|t| t.name == "full_name").unwrap();
+        let person = tags.iter().find(|t| t.name == "Person").unwrap();
+
+        assert_eq!(program.name, Some(String::from("Universal Ctags")));
+        assert_eq!(program.version, Some(String::from("6.1.0")));
+        assert_eq!(&*full_name.file_path, Path::new("app/models/person.rb"));
+        assert_eq!(&*full_name.raw_path, "./app/models/person.rb");
+        assert_eq!(full_name.address, "/^  def full_name$/");
+        assert_eq!(full_name.kind, TokenKind::Method);
+        assert_eq!(full_name.scope(), Some("Person"));
+        assert_eq!(full_name.tags.get("line").map(|v| &**v), Some("12"));
+        assert_eq!(person.address, "1");
+        assert_eq!(person.kind, TokenKind::Class);
+        assert_eq!(person.language, Some(Language::Ruby));
+    }
+
+    #[test]
+    fn reports_the_line_of_invalid_json() {
+        assert!(matches!(
+            parse("{\"_type\": \"tag\"}\nnot json\n"),
+            Err(CtagsParseError::InvalidJson(1, None))
+        ));
+        assert!(matches!(
+            parse("{\"_type\": \"ptag\"}\nnot json\n"),
+            Err(CtagsParseError::InvalidJson(2, Some(_)))
+        ));
+    }
+
+    #[test]
+    fn skips_invalid_lines_when_lenient() {
+        let input = format!("{}not json\n{{\"_type\": \"tag\"}}\n", TAGS);
+        let (program, tags, warnings) = parse_lenient(&input);
+
+        assert_eq!(program.name, Some(String::from("Universal Ctags")));
+        assert_eq!(tags.len(), 2);
+        assert_eq!(
+            warnings,
+            vec![
+                ParseWarning {
+                    line: 6,
+                    content: String::from("not json")
+                },
+                ParseWarning {
+                    line: 7,
+                    content: String::from("{\"_type\": \"tag\"}")
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use super::{ctag_item_ref_parser, diagnostics, internal, json};
+use crate::ctag_item::{CtagItem, CtagsParseError};
+use crate::interner::Interner;
+use crate::tag_program::TagProgram;
+use crate::tags_format::TagsFormat;
+use std::collections::HashSet;
+
+/// Tags parsed a line at a time, for tags read from a stream rather than a whole file
+///
+/// The format is resolved from the first line that isn't blank, and program metadata (`!_TAG`
+/// lines) is only read before the first tag, as when parsing a whole file.
+#[derive(Default)]
+pub struct Lines {
+    line: usize,
+    format: TagsFormat,
+    reading_tags: bool,
+    program: TagProgram,
+    tags: HashSet<CtagItem>,
+    interner: Interner,
+}
+
+impl Lines {
+    /// Lines of tags in `format`, detecting JSON tags from the first line when it's `Auto`
+    pub fn new(format: TagsFormat) -> Self {
+        Lines {
+            format,
+            ..Default::default()
+        }
+    }
+
+    /// Number of the last line pushed, starting at 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Parse the next line, without its trailing newline
+    pub fn push(&mut self, line: &str) -> Result<(), CtagsParseError> {
+        self.line += 1;
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+
+        // Once resolved from the first line, the format is never `Auto` again
+        self.format = self.format.resolve(line);
+        match self.format {
+            TagsFormat::Json => {
+                json::read_line(line, &mut self.program, &mut self.tags, &self.interner)
+                    .map_err(|e| CtagsParseError::InvalidJson(self.line, e))
+            }
+            _ => self.push_tag(line),
+        }
+    }
+
+    /// The program metadata and tags parsed
+    pub fn finish(self) -> (TagProgram, HashSet<CtagItem>) {
+        (self.program, self.tags)
+    }
+
+    fn push_tag(&mut self, line: &str) -> Result<(), CtagsParseError> {
+        if !self.reading_tags && line.starts_with("!_TAG") {
+            if let Ok(("", program)) = internal::tag_metadata(&format!("{}\n", line)) {
+                self.program.name = program.name.or_else(|| self.program.name.take());
+                self.program.author = program.author.or_else(|| self.program.author.take());
+                self.program.version = program.version.or_else(|| self.program.version.take());
+                self.program.cwd = program.cwd.or_else(|| self.program.cwd.take());
+                return Ok(());
+            }
+        }
+        self.reading_tags = true;
+
+        match ctag_item_ref_parser(&self.program, line) {
+            Ok(("", item)) => {
+                self.tags.insert(item.to_owned_in(&self.interner));
+                Ok(())
+            }
+            _ => Err(diagnostics::invalid_line(self.line, line)),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use super::CtagsParseError;
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// A struct capturing possible failures when attempting to find and read tags files
+#[derive(Debug, Error)]
+pub enum ReadCtagsError {
+    /// No tags file found
+    ///
+    /// This provides the paths attempted
+    #[error("Unable to find ctags file (searched in {}): {1}", searched(.0))]
+    NoCtagsFile(Vec<PathBuf>, #[source] io::Error),
+    /// Error parsing tags
+    #[error(transparent)]
+    CtagsParseError(#[from] CtagsParseError),
+    /// Running GNU GLOBAL's `global` to read a GTAGS database failed
+    #[error("Unable to read GTAGS with `global` (from GNU GLOBAL): {0}")]
+    GlobalFailed(#[source] io::Error),
+    /// Running Universal Ctags to generate tags failed
+    #[cfg(feature = "fs")]
+    #[error("Unable to generate tags with `ctags` (Universal Ctags): {0}")]
+    CtagsFailed(#[source] io::Error),
+    /// The tags file is older than files it names, and stale tags were rejected
+    #[cfg(feature = "fs")]
+    #[error("{0}")]
+    StaleTags(super::TagsFreshness),
+}
+
+fn searched(file_list: &[PathBuf]) -> String {
+    file_list
+        .iter()
+        .filter_map(|f| f.to_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,256 @@
+use super::{language::Language, token_kind::TokenKind, CtagItem};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use tree_sitter::{Parser, Query, QueryCursor, StreamingIterator};
+
+/// SyntaxTags extracts definitions by parsing source files with tree-sitter grammars
+///
+/// Definitions are found with each grammar's own tags query (the one used for code navigation
+/// on GitHub), so they're more complete than `FallbackTags`' regular expressions. Ruby,
+/// JavaScript, TypeScript, and Rust are supported.
+pub struct SyntaxTags {
+    grammars: Vec<Grammar>,
+}
+
+struct Grammar {
+    language: Language,
+    tsx: bool,
+    grammar: tree_sitter::Language,
+    query: Query,
+}
+
+impl Grammar {
+    fn new(language: Language, tsx: bool, grammar: tree_sitter::Language, query: &str) -> Self {
+        Grammar {
+            language,
+            tsx,
+            query: Query::new(&grammar, query).unwrap(),
+            grammar,
+        }
+    }
+}
+
+impl Default for SyntaxTags {
+    fn default() -> Self {
+        // TypeScript's tags query only covers what it adds to JavaScript
+        let typescript_query = format!(
+            "{}\n{}",
+            tree_sitter_javascript::TAGS_QUERY,
+            tree_sitter_typescript::TAGS_QUERY
+        );
+
+        SyntaxTags {
+            grammars: vec![
+                Grammar::new(
+                    Language::Ruby,
+                    false,
+                    tree_sitter_ruby::LANGUAGE.into(),
+                    tree_sitter_ruby::TAGS_QUERY,
+                ),
+                Grammar::new(
+                    Language::JavaScript,
+                    false,
+                    tree_sitter_javascript::LANGUAGE.into(),
+                    tree_sitter_javascript::TAGS_QUERY,
+                ),
+                Grammar::new(
+                    Language::TypeScript,
+                    false,
+                    tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+                    &typescript_query,
+                ),
+                Grammar::new(
+                    Language::TypeScript,
+                    true,
+                    tree_sitter_typescript::LANGUAGE_TSX.into(),
+                    &typescript_query,
+                ),
+                Grammar::new(
+                    Language::Rust,
+                    false,
+                    tree_sitter_rust::LANGUAGE.into(),
+                    tree_sitter_rust::TAGS_QUERY,
+                ),
+            ],
+        }
+    }
+}
+
+impl SyntaxTags {
+    /// Definitions in `contents`, or `None` when there's no grammar for `language` or the file
+    /// can't be parsed without errors
+    ///
+    /// Grammars can lag behind their languages (tree-sitter-ruby doesn't take `Über` for a
+    /// constant, though Ruby does), and definitions within the unparsed parts of a file would
+    /// be missed, so such files are left to `FallbackTags`' regular expressions.
+    pub fn extract_from(
+        &self,
+        path: &Path,
+        contents: &str,
+        language: Option<Language>,
+    ) -> Option<HashSet<CtagItem>> {
+        let tsx = path.extension().is_some_and(|e| e == "tsx");
+        let grammar = self
+            .grammars
+            .iter()
+            .find(|g| Some(g.language) == language && g.tsx == tsx)?;
+
+        let mut parser = Parser::new();
+        parser.set_language(&grammar.grammar).ok()?;
+        let tree = parser
+            .parse(contents, None)
+            .filter(|tree| !tree.root_node().has_error())?;
+
+        let capture_names = grammar.query.capture_names();
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&grammar.query, tree.root_node(), contents.as_bytes());
+        let mut defined_nodes = HashSet::new();
+        let mut results = HashSet::new();
+        let file_path: Arc<Path> = path.into();
+        let raw_path: Arc<str> = path.to_string_lossy().into();
+
+        while let Some(found) = matches.next() {
+            let name = found
+                .captures
+                .iter()
+                .find(|c| capture_names[c.index as usize] == "name")
+                .map(|c| c.node);
+            let definition = found.captures.iter().find_map(|c| {
+                capture_names[c.index as usize]
+                    .strip_prefix("definition.")
+                    .map(|kind| (kind, c.node))
+            });
+
+            // Functions in Rust's `impl` blocks match both as methods and as functions
+            if let (Some(name), Some((kind, node))) = (name, definition) {
+                if !defined_nodes.insert(node.id()) {
+                    continue;
+                }
+
+                if let Ok(text) = name.utf8_text(contents.as_bytes()) {
+                    results.insert(CtagItem {
+                        name: text.to_string(),
+                        file_path: file_path.clone(),
+                        raw_path: raw_path.clone(),
+                        address: (name.start_position().row + 1).to_string(),
+                        language,
+                        tags: BTreeMap::new(),
+                        kind: token_kind(kind, node.kind()),
+                    });
+                }
+            }
+        }
+
+        Some(results)
+    }
+}
+
+// Tags queries only distinguish broad kinds of definitions, so the node is used where ctags
+// would be more specific
+fn token_kind(definition: &str, node: &str) -> TokenKind {
+    match (definition, node) {
+        (_, "singleton_method") => TokenKind::SingletonMethod,
+        (_, "struct_item") | (_, "union_item") => TokenKind::Struct,
+        (_, "enum_item") => TokenKind::Enum,
+        (_, "type_item") => TokenKind::Typedef,
+        ("class", _) => TokenKind::Class,
+        ("method", _) => TokenKind::Method,
+        ("function", _) => TokenKind::Function,
+        ("module", _) => TokenKind::Module,
+        ("interface", _) => TokenKind::Interface,
+        ("macro", _) => TokenKind::Macro,
+        ("constant", _) => TokenKind::Constant,
+        _ => TokenKind::Undefined,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definitions(path: &str, contents: &str) -> Vec<(String, String, TokenKind)> {
+        let path = Path::new(path);
+        let mut found: Vec<_> = SyntaxTags::default()
+            .extract_from(path, contents, Language::from_path(path))
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.name, t.address, t.kind))
+            .collect();
+        found.sort_by_key(|(name, address, _)| (address.parse::<usize>().unwrap(), name.clone()));
+        found
+    }
+
+    fn definition(name: &str, line: usize, kind: TokenKind) -> (String, String, TokenKind) {
+        (name.to_string(), line.to_string(), kind)
+    }
+
+    #[test]
+    fn extracts_ruby_definitions() {
+        let contents = "module Admin\n  class UsersController < ApplicationController\n    def self.build\n    end\n\n    def index?\n    end\n  end\nend\n";
+
+        assert_eq!(
+            definitions("app/users.rb", contents),
+            vec![
+                definition("Admin", 1, TokenKind::Module),
+                definition("UsersController", 2, TokenKind::Class),
+                definition("build", 3, TokenKind::SingletonMethod),
+                definition("index?", 6, TokenKind::Method),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_typescript_definitions() {
+        let contents = "export interface User {\n  name: string;\n}\n\nexport class Users {\n  find(name: string) {}\n}\n\nconst build = () => new Users();\n";
+
+        assert_eq!(
+            definitions("src/users.ts", contents),
+            vec![
+                definition("User", 1, TokenKind::Interface),
+                definition("Users", 5, TokenKind::Class),
+                definition("find", 6, TokenKind::Method),
+                definition("build", 9, TokenKind::Function),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_rust_definitions() {
+        let contents =
+            "pub struct User;\n\nimpl User {\n    pub fn name(&self) {}\n}\n\nfn main() {}\n";
+
+        assert_eq!(
+            definitions("src/main.rs", contents),
+            vec![
+                definition("User", 1, TokenKind::Struct),
+                definition("name", 4, TokenKind::Method),
+                definition("main", 7, TokenKind::Function),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_languages_without_grammars() {
+        assert_eq!(
+            SyntaxTags::default().extract_from(
+                Path::new("lib/users.ex"),
+                "defmodule Users do\nend\n",
+                Some(Language::Elixir)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn skips_files_with_syntax_errors() {
+        assert_eq!(
+            SyntaxTags::default().extract_from(
+                Path::new("app/café.rb"),
+                "class Über\n  def größe\n  end\nend\n",
+                Some(Language::Ruby)
+            ),
+            None
+        );
+    }
+}
@@ -1,19 +1,19 @@
 use serde::Serialize;
-use std::default::Default;
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Debug, Default, PartialEq, Serialize)]
 pub struct TagProgram {
     pub name: Option<String>,
     pub author: Option<String>,
     pub version: Option<String>,
+    /// Directory ctags ran in, recorded by Universal Ctags as `!_TAG_PROC_CWD`
+    pub cwd: Option<String>,
 }
 
-impl Default for TagProgram {
-    fn default() -> Self {
-        TagProgram {
-            name: None,
-            author: None,
-            version: None,
-        }
+impl TagProgram {
+    /// Were the tags written by Exuberant Ctags, as named by `!_TAG_PROGRAM_NAME`?
+    pub fn is_exuberant(&self) -> bool {
+        self.name
+            .as_deref()
+            .is_some_and(|name| name.starts_with("Exuberant Ctags"))
     }
 }
@@ -0,0 +1,97 @@
+use super::kind_filter::KindPattern;
+use super::CtagItem;
+
+/// A lookup of tags by name, as the `readtags` utility makes
+///
+/// Names match exactly unless `prefix` is set, and case-sensitively unless `ignore_case` is
+/// set (as with `readtags -p` and `-i`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TagQuery {
+    /// Name (or start of the name) to find
+    pub name: String,
+    /// Match tags whose names start with `name`
+    pub prefix: bool,
+    /// Ignore case when comparing names
+    pub ignore_case: bool,
+    /// Kinds to keep, or every kind when empty
+    pub kinds: Vec<KindPattern>,
+}
+
+impl TagQuery {
+    /// Query for tags named exactly `name`
+    pub fn new(name: &str) -> Self {
+        TagQuery {
+            name: name.to_string(),
+            ..TagQuery::default()
+        }
+    }
+
+    /// Does `tag` match the query?
+    pub fn matches(&self, tag: &CtagItem) -> bool {
+        let matches_name = match (self.prefix, self.ignore_case) {
+            (false, false) => tag.name == self.name,
+            (true, false) => tag.name.starts_with(&self.name),
+            (false, true) => tag.name.to_lowercase() == self.name.to_lowercase(),
+            (true, true) => tag
+                .name
+                .to_lowercase()
+                .starts_with(&self.name.to_lowercase()),
+        };
+
+        matches_name && (self.kinds.is_empty() || self.kinds.iter().any(|k| k.matches(tag)))
+    }
+
+    /// Tags matching the query, sorted by name and then location
+    pub fn find<'a, I>(&self, tags: I) -> Vec<&'a CtagItem>
+    where
+        I: IntoIterator<Item = &'a CtagItem>,
+    {
+        let mut found: Vec<&CtagItem> = tags.into_iter().filter(|t| self.matches(t)).collect();
+        found.sort_by(|a, b| {
+            (&a.name, &a.file_path, &a.address).cmp(&(&b.name, &b.file_path, &b.address))
+        });
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn finds_tags_by_name_prefix_and_kind() {
+        let tags = CtagItem::parse(
+            PathBuf::from("tags"),
+            "Person\tapp/person.rb\t1;\"\tc\nperson_name\tapp/person.rb\t2;\"\tf\tclass:Person\npersonalize\tapp/view.rb\t4;\"\tf\n",
+        )
+        .unwrap()
+        .tags;
+        let names = |query: TagQuery| -> Vec<String> {
+            query
+                .find(&tags)
+                .into_iter()
+                .map(|t| t.name.clone())
+                .collect()
+        };
+
+        assert_eq!(names(TagQuery::new("Person")), vec!["Person"]);
+        assert!(names(TagQuery::new("person")).is_empty());
+        assert_eq!(
+            names(TagQuery {
+                prefix: true,
+                ..TagQuery::new("person")
+            }),
+            vec!["person_name", "personalize"]
+        );
+        assert_eq!(
+            names(TagQuery {
+                prefix: true,
+                ignore_case: true,
+                kinds: vec!["c".parse().unwrap()],
+                ..TagQuery::new("PERSON")
+            }),
+            vec!["Person"]
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use super::CtagItem;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// Definitions added, removed, or changed between two sets of tags
+///
+/// Definitions are matched by name, path, and kind. A matched definition has changed when its
+/// language, metadata (other than its line number), or search pattern address differ; numeric
+/// addresses are ignored so definitions that only moved within a file aren't reported.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct TagsDiff {
+    /// Definitions only present in the new tags
+    pub added: Vec<CtagItem>,
+    /// Definitions only present in the old tags
+    pub removed: Vec<CtagItem>,
+    /// Matched definitions whose details differ
+    pub changed: Vec<ChangedTag>,
+}
+
+/// A definition present in both sets of tags with differing details
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ChangedTag {
+    /// The definition in the old tags
+    pub old: CtagItem,
+    /// The definition in the new tags
+    pub new: CtagItem,
+}
+
+type Identity<'a> = (&'a str, &'a Path, String);
+
+impl TagsDiff {
+    /// Compare old and new tags
+    pub fn between(old: &HashSet<CtagItem>, new: &HashSet<CtagItem>) -> Self {
+        let mut matched: BTreeMap<Identity<'_>, (Vec<&CtagItem>, Vec<&CtagItem>)> = BTreeMap::new();
+
+        for tag in old {
+            matched.entry(identity(tag)).or_default().0.push(tag);
+        }
+        for tag in new {
+            matched.entry(identity(tag)).or_default().1.push(tag);
+        }
+
+        let mut diff = TagsDiff::default();
+
+        for (old, new) in matched.into_values() {
+            let mut removed: Vec<&CtagItem> = old
+                .iter()
+                .filter(|o| !new.iter().any(|n| same_shape(o, n)))
+                .copied()
+                .collect();
+            let mut added: Vec<&CtagItem> = new
+                .iter()
+                .filter(|n| !old.iter().any(|o| same_shape(o, n)))
+                .copied()
+                .collect();
+            removed.sort_by(|a, b| a.address.cmp(&b.address));
+            added.sort_by(|a, b| a.address.cmp(&b.address));
+
+            // Unmatched definitions sharing an identity are paired up as changes, in address order
+            let paired = removed.len().min(added.len());
+            diff.changed.extend(
+                removed
+                    .iter()
+                    .zip(added.iter())
+                    .map(|(old, new)| ChangedTag {
+                        old: (*old).clone(),
+                        new: (*new).clone(),
+                    }),
+            );
+            diff.removed
+                .extend(removed[paired..].iter().map(|&o| o.clone()));
+            diff.added
+                .extend(added[paired..].iter().map(|&n| n.clone()));
+        }
+
+        diff
+    }
+
+    /// Were no definitions added, removed, or changed?
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn identity(tag: &CtagItem) -> Identity<'_> {
+    (&tag.name, &tag.file_path, format!("{:?}", tag.kind))
+}
+
+fn same_shape(old: &CtagItem, new: &CtagItem) -> bool {
+    let details = |tag: &CtagItem| {
+        tag.tags
+            .iter()
+            .filter(|(key, _)| key.as_ref() != "line")
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<Vec<_>>()
+    };
+    let pattern = |tag: &CtagItem| {
+        if tag.address.parse::<usize>().is_ok() {
+            None
+        } else {
+            Some(tag.address.to_string())
+        }
+    };
+
+    old.language == new.language && details(old) == details(new) && pattern(old) == pattern(new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Language, TokenKind};
+
+    fn tag(name: &str, path: &str, address: &str, tags: &[(&str, &str)]) -> CtagItem {
+        CtagItem {
+            name: name.to_string(),
+            file_path: Path::new(path).into(),
+            raw_path: path.into(),
+            address: address.to_string(),
+            language: Language::from_path(path),
+            tags: tags
+                .iter()
+                .map(|(k, v)| ((*k).into(), (*v).into()))
+                .collect(),
+            kind: TokenKind::Method,
+        }
+    }
+
+    #[test]
+    fn reports_added_removed_and_changed_definitions() {
+        let old: HashSet<CtagItem> = vec![
+            tag("full_name", "app/person.rb", "3", &[("line", "3")]),
+            tag("age", "app/person.rb", "/^  def age$/", &[]),
+            tag("total", "app/order.rb", "7", &[("access", "public")]),
+        ]
+        .into_iter()
+        .collect();
+        let new: HashSet<CtagItem> = vec![
+            tag("full_name", "app/person.rb", "9", &[("line", "9")]),
+            tag("age", "app/person.rb", "/^  def age(now)$/", &[]),
+            tag("total", "app/models/order.rb", "7", &[("access", "public")]),
+        ]
+        .into_iter()
+        .collect();
+
+        let diff = TagsDiff::between(&old, &new);
+
+        assert_eq!(
+            diff.added,
+            vec![tag(
+                "total",
+                "app/models/order.rb",
+                "7",
+                &[("access", "public")]
+            )]
+        );
+        assert_eq!(
+            diff.removed,
+            vec![tag("total", "app/order.rb", "7", &[("access", "public")])]
+        );
+        assert_eq!(
+            diff.changed,
+            vec![ChangedTag {
+                old: tag("age", "app/person.rb", "/^  def age$/", &[]),
+                new: tag("age", "app/person.rb", "/^  def age(now)$/", &[]),
+            }]
+        );
+        assert!(TagsDiff::between(&old, &old).is_empty());
+    }
+}
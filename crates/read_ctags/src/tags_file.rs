@@ -1,15 +1,268 @@
-use super::{ctag_item::CtagItem, tag_program::TagProgram};
+use super::{
+    ctag_item::CtagItem, language::Language, tag_program::TagProgram, token_kind::TokenKind,
+};
+use codebase_files::{paths, FileSystem};
 use serde::Serialize;
-use std::collections::HashSet;
-use std::path::PathBuf;
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::env;
+use std::hash::Hash;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
 
 /// Parsed tags outcome
 #[derive(Serialize)]
 pub struct TagsFile {
     /// Path of the tags file
     pub path: PathBuf,
+    /// Tags files the tags were read from: `path` alone, unless a reader merged every file it
+    /// found (see `LoadMode::All`), or none when tags weren't read from a file
+    pub sources: Vec<PathBuf>,
     /// Tags file program metadata
     pub program: TagProgram,
     /// Tags found in the tags file
     pub tags: HashSet<CtagItem>,
 }
+
+impl TagsFile {
+    /// Tags grouped by their language, ordered by path and line within each group
+    ///
+    /// Tags in files of an unknown language are left out.
+    pub fn by_language(&self) -> HashMap<Language, Vec<&CtagItem>> {
+        group(&self.tags, |tag| tag.language)
+    }
+
+    /// Tags grouped by their kind, ordered by path and line within each group
+    pub fn by_kind(&self) -> HashMap<TokenKind, Vec<&CtagItem>> {
+        group(&self.tags, |tag| Some(tag.kind.clone()))
+    }
+
+    /// Tags ordered by path, then line, then name (see `CtagItem::cmp_location`), rather than in
+    /// the set's arbitrary order, which differs between runs
+    pub fn ordered(&self) -> Vec<&CtagItem> {
+        let mut tags: Vec<&CtagItem> = self.tags.iter().collect();
+        tags.sort_by(|a, b| a.cmp_location(b));
+        tags
+    }
+
+    /// Give tags in extensionless files (otherwise assumed to be shell scripts) the language of
+    /// the file's shebang or modeline, with kinds resolved again for the detected language
+    ///
+    /// Each file is read from `file_system` once, however many tags it has.
+    pub fn detect_languages(self, file_system: &dyn FileSystem) -> TagsFile {
+        let mut detected: HashMap<PathBuf, Option<Language>> = HashMap::new();
+        let tags = self
+            .tags
+            .into_iter()
+            .map(|tag| {
+                if tag.file_path.extension().is_some() {
+                    return tag;
+                }
+
+                let language = *detected
+                    .entry(tag.file_path.to_path_buf())
+                    .or_insert_with(|| match file_system.read(&tag.file_path) {
+                        Ok(contents) => Language::detect(&tag.file_path, &contents),
+                        Err(_) => tag.language,
+                    });
+                tag.with_language(language)
+            })
+            .collect();
+
+        TagsFile { tags, ..self }
+    }
+
+    /// Make each tag's path relative to the project at `root`, so tags match the files searched
+    /// however ctags wrote their paths
+    ///
+    /// Absolute paths beneath `root` are made relative to it, and relative paths are resolved
+    /// against the directory ctags ran in, when recorded as `!_TAG_PROC_CWD`. Paths outside
+    /// `root` are only cleaned of `..` components. Tags differing only in how their path was written are then
+    /// kept once, with the shortest path as written.
+    pub fn normalize_paths(self, root: &Path) -> TagsFile {
+        let root = clean(&env::current_dir().unwrap_or_default().join(root));
+        let cwd = self
+            .program
+            .cwd
+            .as_deref()
+            .map(|cwd| clean(&paths::normalize(cwd)))
+            .filter(|cwd| cwd.is_absolute() && *cwd != root);
+
+        let mut kept: HashMap<CtagItem, CtagItem> = HashMap::new();
+        for mut tag in self.tags {
+            tag.file_path = resolve(&tag.file_path, cwd.as_deref(), &root)
+                .unwrap_or_else(|| clean(&tag.file_path))
+                .into();
+            let key = CtagItem {
+                raw_path: Arc::from(""),
+                ..tag.clone()
+            };
+            match kept.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    let existing = &entry.get().raw_path;
+                    if (tag.raw_path.len(), &tag.raw_path) < (existing.len(), existing) {
+                        entry.insert(tag);
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(tag);
+                }
+            }
+        }
+
+        TagsFile {
+            tags: kept.into_values().collect(),
+            ..self
+        }
+    }
+}
+
+// `path` relative to `root`, or `None` when it can't be placed beneath `root`
+fn resolve(path: &Path, cwd: Option<&Path>, root: &Path) -> Option<PathBuf> {
+    let absolute = if path.is_absolute() {
+        clean(path)
+    } else {
+        clean(&cwd?.join(path))
+    };
+    paths::relative_to(&absolute, root)
+}
+
+// `path` with each `..` removing the component before it, as `.` components already are
+fn clean(path: &Path) -> PathBuf {
+    let mut cleaned = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(cleaned.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                cleaned.pop();
+            }
+            component => cleaned.push(component),
+        }
+    }
+    cleaned
+}
+
+fn group<K: Eq + Hash>(
+    tags: &HashSet<CtagItem>,
+    key: impl Fn(&CtagItem) -> Option<K>,
+) -> HashMap<K, Vec<&CtagItem>> {
+    let mut groups: HashMap<K, Vec<&CtagItem>> = HashMap::new();
+    for tag in tags {
+        if let Some(key) = key(tag) {
+            groups.entry(key).or_default().push(tag);
+        }
+    }
+
+    for tags in groups.values_mut() {
+        tags.sort_by(|a, b| a.cmp_location(b));
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names<'a>(tags: Option<&Vec<&'a CtagItem>>) -> Option<Vec<&'a str>> {
+        tags.map(|tags| tags.iter().map(|t| t.name.as_str()).collect())
+    }
+
+    #[test]
+    fn groups_tags_by_language_and_kind() {
+        let tags_file = CtagItem::parse(
+            PathBuf::from("tags"),
+            "Person\tapp/person.rb\t1;\"\tc\nname\tapp/person.rb\t2;\"\tf\nrender\tapp/view.js\t3;\"\tf\nintro\tdocs/notes.xyz\t1\n",
+        )
+        .unwrap();
+        let by_language = tags_file.by_language();
+        assert_eq!(
+            names(by_language.get(&Language::Ruby)),
+            Some(vec!["Person", "name"])
+        );
+        assert_eq!(
+            names(by_language.get(&Language::JavaScript)),
+            Some(vec!["render"])
+        );
+        assert_eq!(by_language.len(), 2);
+
+        let by_kind = tags_file.by_kind();
+        assert_eq!(names(by_kind.get(&TokenKind::Method)), Some(vec!["name"]));
+        assert_eq!(
+            names(by_kind.get(&TokenKind::Function)),
+            Some(vec!["render"])
+        );
+        assert_eq!(
+            names(by_kind.get(&TokenKind::Undefined)),
+            Some(vec!["intro"])
+        );
+    }
+
+    #[test]
+    fn detects_languages_of_extensionless_files() {
+        let file_system = codebase_files::MemoryFileSystem::default();
+        file_system.insert("bin/run", "#!/usr/bin/env ruby\ndef run; end\n");
+        let tags_file = CtagItem::parse(
+            PathBuf::from("tags"),
+            "run\tbin/run\t2;\"\tf\nsetup\tbin/setup\t1;\"\tf\n",
+        )
+        .unwrap()
+        .detect_languages(&file_system);
+
+        let languages: HashMap<&str, (Option<Language>, &TokenKind)> = tags_file
+            .tags
+            .iter()
+            .map(|tag| (tag.name.as_str(), (tag.language, &tag.kind)))
+            .collect();
+        assert_eq!(languages["run"], (Some(Language::Ruby), &TokenKind::Method));
+        assert_eq!(
+            languages["setup"],
+            (Some(Language::Sh), &TokenKind::Function)
+        );
+    }
+
+    #[test]
+    fn orders_tags_by_path_line_and_name() {
+        let tags_file = CtagItem::parse(
+            PathBuf::from("tags"),
+            "age\tapp/person.rb\t10;\"\tf\nname\tapp/person.rb\t2;\"\tf\nfull_name\tapp/person.rb\t/^  def full_name$/;\"\tf\nPerson\tapp/person.rb\t2;\"\tc\nrender\tapp/a.rb\t30;\"\tf\n",
+        )
+        .unwrap();
+
+        let names: Vec<&str> = tags_file
+            .ordered()
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["render", "Person", "name", "age", "full_name"]);
+    }
+
+    #[test]
+    fn normalizes_paths_against_the_project_root() {
+        let tags_file = CtagItem::parse(
+            PathBuf::from("tags"),
+            "!_TAG_PROC_CWD\t/app/lib/\t//\nPerson\t/app/app/person.rb\t1;\"\tc\nPerson\t../app/person.rb\t1;\"\tc\nname\t./person.rb\t2;\"\tf\nrender\t/elsewhere/view.js\t3;\"\tf\n",
+        )
+        .unwrap()
+        .normalize_paths(Path::new("/app"));
+
+        let mut tags: Vec<_> = tags_file
+            .tags
+            .iter()
+            .map(|t| (t.name.as_str(), t.file_path.to_path_buf(), &*t.raw_path))
+            .collect();
+        tags.sort();
+        assert_eq!(
+            tags,
+            vec![
+                ("Person", PathBuf::from("app/person.rb"), "../app/person.rb"),
+                ("name", PathBuf::from("lib/person.rb"), "./person.rb"),
+                (
+                    "render",
+                    PathBuf::from("/elsewhere/view.js"),
+                    "/elsewhere/view.js"
+                ),
+            ]
+        );
+    }
+}
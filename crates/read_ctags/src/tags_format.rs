@@ -0,0 +1,63 @@
+use std::str::FromStr;
+
+/// Formats of tags files written by Universal Ctags
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TagsFormat {
+    /// Detect the format from the file's contents
+    #[default]
+    Auto,
+    /// Tab-separated tags (`ctags --output-format=u-ctags`, the default)
+    Ctags,
+    /// One JSON object per line (`ctags --output-format=json`)
+    Json,
+}
+
+impl TagsFormat {
+    /// The format `input` is parsed as, detecting JSON by a first line holding an object
+    pub fn resolve(self, input: &str) -> TagsFormat {
+        match self {
+            TagsFormat::Auto if input.trim_start().starts_with('{') => TagsFormat::Json,
+            TagsFormat::Auto => TagsFormat::Ctags,
+            format => format,
+        }
+    }
+
+    /// Names accepted by `FromStr`
+    pub fn variants() -> [&'static str; 3] {
+        ["auto", "ctags", "json"]
+    }
+}
+
+impl FromStr for TagsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "auto" => Ok(TagsFormat::Auto),
+            "ctags" | "u-ctags" => Ok(TagsFormat::Ctags),
+            "json" => Ok(TagsFormat::Json),
+            other => Err(format!("Unknown tags format: {}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_json_tags() {
+        assert_eq!(
+            TagsFormat::Auto.resolve("{\"_type\": \"tag\", \"name\": \"main\"}\n"),
+            TagsFormat::Json
+        );
+        assert_eq!(
+            TagsFormat::Auto.resolve("!_TAG_FILE_FORMAT\t2\t/extended format/\n"),
+            TagsFormat::Ctags
+        );
+        assert_eq!(
+            TagsFormat::Json.resolve("main\tsrc/main.rs\t1\n"),
+            TagsFormat::Json
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Most files named in a tags file checked for changes, spread evenly across its paths
+const SAMPLE_SIZE: usize = 200;
+
+// Most example paths named when describing a stale tags file
+const EXAMPLES: usize = 3;
+
+/// Whether a tags file still reflects the files it names
+///
+/// Tags generated before recent changes silently produce wrong results: new definitions go
+/// unchecked and deleted ones are reported. Rather than walking the whole project, a sample of
+/// the files named in the tags file is compared against the tags file's modification time.
+#[derive(Debug, PartialEq)]
+pub struct TagsFreshness {
+    /// Path of the tags file checked
+    pub path: PathBuf,
+    /// Number of files sampled
+    pub sampled: usize,
+    /// Sampled files modified after the tags file
+    pub modified: Vec<PathBuf>,
+    /// Sampled files that no longer exist
+    pub missing: Vec<PathBuf>,
+}
+
+impl TagsFreshness {
+    /// Compare the tags file at `path` against a sample of the files its tags are defined in
+    pub fn check<'a, I>(path: &Path, tagged_paths: I) -> io::Result<Self>
+    where
+        I: IntoIterator<Item = &'a Path>,
+    {
+        let generated = fs::metadata(path)?.modified()?;
+        let tagged_paths: BTreeSet<&Path> = tagged_paths.into_iter().collect();
+        let step = (tagged_paths.len() / SAMPLE_SIZE).max(1);
+        let mut freshness = TagsFreshness {
+            path: path.to_path_buf(),
+            sampled: 0,
+            modified: vec![],
+            missing: vec![],
+        };
+
+        for tagged_path in tagged_paths.into_iter().step_by(step).take(SAMPLE_SIZE) {
+            freshness.sampled += 1;
+            match fs::metadata(tagged_path).and_then(|m| m.modified()) {
+                Ok(modified) if modified > generated => {
+                    freshness.modified.push(tagged_path.to_path_buf())
+                }
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    freshness.missing.push(tagged_path.to_path_buf())
+                }
+                Err(e) => {
+                    tracing::debug!("Unable to check {}: {}", tagged_path.display(), e);
+                }
+            }
+        }
+
+        Ok(freshness)
+    }
+
+    /// Were any sampled files changed or removed since the tags file was generated?
+    pub fn is_stale(&self) -> bool {
+        !self.modified.is_empty() || !self.missing.is_empty()
+    }
+}
+
+impl fmt::Display for TagsFreshness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut problems = vec![];
+        if !self.modified.is_empty() {
+            problems.push(format!(
+                "{} of {} sampled file(s) changed since it was generated ({})",
+                self.modified.len(),
+                self.sampled,
+                examples(&self.modified)
+            ));
+        }
+        if !self.missing.is_empty() {
+            problems.push(format!(
+                "{} sampled file(s) no longer exist ({})",
+                self.missing.len(),
+                examples(&self.missing)
+            ));
+        }
+
+        if problems.is_empty() {
+            write!(f, "{} is up to date", self.path.display())
+        } else {
+            write!(
+                f,
+                "{} may be stale: {}; regenerate it with ctags",
+                self.path.display(),
+                problems.join(", and ")
+            )
+        }
+    }
+}
+
+fn examples(paths: &[PathBuf]) -> String {
+    let mut examples: Vec<String> = paths
+        .iter()
+        .take(EXAMPLES)
+        .map(|p| p.display().to_string())
+        .collect();
+    if paths.len() > EXAMPLES {
+        examples.push(String::from("..."));
+    }
+    examples.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn detects_changed_and_missing_files() {
+        let root = std::env::temp_dir().join("tags_freshness_detects_changes");
+        fs::create_dir_all(&root).unwrap();
+        let tags = root.join("tags");
+        let unchanged = root.join("unchanged.rb");
+        let changed = root.join("changed.rb");
+        let missing = root.join("missing.rb");
+        for path in [&tags, &unchanged, &changed] {
+            fs::write(path, "").unwrap();
+        }
+        let generated = SystemTime::now() - Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&tags)
+            .and_then(|f| f.set_modified(generated))
+            .unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&unchanged)
+            .and_then(|f| f.set_modified(generated - Duration::from_secs(60)))
+            .unwrap();
+
+        let freshness = TagsFreshness::check(
+            &tags,
+            vec![
+                unchanged.as_path(),
+                changed.as_path(),
+                missing.as_path(),
+                changed.as_path(),
+            ],
+        );
+        fs::remove_dir_all(&root).ok();
+        let freshness = freshness.unwrap();
+
+        assert_eq!(freshness.sampled, 3);
+        assert_eq!(freshness.modified, vec![changed.clone()]);
+        assert_eq!(freshness.missing, vec![missing.clone()]);
+        assert!(freshness.is_stale());
+        assert_eq!(
+            freshness.to_string(),
+            format!(
+                "{} may be stale: 1 of 3 sampled file(s) changed since it was generated ({}), and 1 sampled file(s) no longer exist ({}); regenerate it with ctags",
+                tags.display(),
+                changed.display(),
+                missing.display()
+            )
+        );
+    }
+}
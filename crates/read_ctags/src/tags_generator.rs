@@ -0,0 +1,129 @@
+use super::{CtagItem, ParseMode, ReadCtagsError, TagsFile, TagsFormat, STDIN_PATH};
+use codebase_files::DiskFileSystem;
+use std::ffi::OsString;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+// Directories that are slow to tag and rarely define the project's own tokens
+const EXCLUDED_DIRECTORIES: [&str; 2] = ["node_modules", "tmp"];
+
+/// TagsGenerator runs Universal Ctags over a project, for when its tags file is missing or stale
+///
+/// Tags are parsed from ctags' output as it runs, so nothing is written to disk and the project's
+/// own tags file is never overwritten.
+pub struct TagsGenerator {
+    root: PathBuf,
+    format: TagsFormat,
+    mode: ParseMode,
+}
+
+impl TagsGenerator {
+    /// Generator for the files under `root`, relative to the current directory
+    pub fn new(root: &Path) -> Self {
+        TagsGenerator {
+            root: root.to_path_buf(),
+            format: TagsFormat::Auto,
+            mode: ParseMode::Strict,
+        }
+    }
+
+    /// Have ctags write tags as `format`, reading them back as it, rather than tab-separated
+    pub fn with_format(mut self, format: TagsFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Skip malformed tags in ctags' output in `ParseMode::Lenient`, rather than failing
+    pub fn with_parse_mode(mut self, mode: ParseMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Arguments passed to ctags, which writes tags to STDOUT with line numbers
+    ///
+    /// Tags are written as JSON when the generator's format is JSON (`--tags-format json`).
+    pub fn arguments(&self) -> Vec<OsString> {
+        let mut arguments: Vec<OsString> = vec!["-R".into(), "--fields=+n".into()];
+        arguments.extend(
+            EXCLUDED_DIRECTORIES
+                .iter()
+                .map(|directory| format!("--exclude={}", directory).into()),
+        );
+        if self.format == TagsFormat::Json {
+            arguments.push("--output-format=json".into());
+        }
+        arguments.extend(vec!["-f".into(), "-".into(), self.root.clone().into()]);
+        arguments
+    }
+
+    /// Run ctags, parsing the tags it writes to STDOUT as they're written
+    pub fn generate(&self) -> Result<TagsFile, ReadCtagsError> {
+        tracing::info!("Generating tags for {} with ctags", self.root.display());
+
+        let mut child = Command::new("ctags")
+            .args(self.arguments())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(ReadCtagsError::CtagsFailed)?;
+        // Read on its own thread, so ctags can't block writing warnings while tags are parsed
+        let stderr = child.stderr.take().map(|mut stderr| {
+            thread::spawn(move || {
+                let mut errors = String::new();
+                stderr.read_to_string(&mut errors).map(|_| errors)
+            })
+        });
+        let parsed = child.stdout.take().map(|stdout| {
+            CtagItem::parse_reader(
+                PathBuf::from(STDIN_PATH),
+                BufReader::new(stdout),
+                self.format,
+                self.mode,
+            )
+        });
+
+        // Parsing stops at the first malformed tag in strict mode, and ctags exits once its
+        // output is closed, so it's waited for either way
+        let status = child.wait().map_err(ReadCtagsError::CtagsFailed)?;
+        if !status.success() {
+            let errors = stderr
+                .and_then(|reading| reading.join().ok())
+                .and_then(Result::ok)
+                .unwrap_or_default();
+            return Err(ReadCtagsError::CtagsFailed(io::Error::other(
+                errors.trim().to_string(),
+            )));
+        }
+
+        let tags_file = parsed
+            .ok_or_else(|| ReadCtagsError::CtagsFailed(io::Error::other("no output")))??
+            .detect_languages(&DiskFileSystem);
+        tracing::info!("Generated {} tags", tags_file.tags.len());
+        Ok(tags_file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_tags_for_the_root_to_stdout() {
+        let generator = TagsGenerator::new(Path::new("services/api"));
+
+        assert_eq!(
+            generator.arguments(),
+            vec![
+                "-R",
+                "--fields=+n",
+                "--exclude=node_modules",
+                "--exclude=tmp",
+                "-f",
+                "-",
+                "services/api"
+            ]
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use super::interner::{Interner, Symbol};
+use super::kind_filter::KindFilter;
+#[cfg(feature = "fs")]
+use super::location::Location;
+use super::CtagItem;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// TagsIndex provides lookups of parsed tags by name and by path
+///
+/// The index is immutable once built; handles are cheap to clone and safe to share across
+/// threads. Names and paths are interned with the index's `Interner`, which can be reused by
+/// consumers building their own structures on top of it.
+#[derive(Clone)]
+pub struct TagsIndex {
+    interner: Interner,
+    tags: Arc<Vec<CtagItem>>,
+    by_name: Arc<HashMap<Symbol, Vec<usize>>>,
+    by_path: Arc<HashMap<Symbol, Vec<usize>>>,
+}
+
+impl TagsIndex {
+    /// Build an index over the provided tags
+    pub fn new<I>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = CtagItem>,
+    {
+        Self::with_interner(tags, Interner::default())
+    }
+
+    /// Build an index over the provided tags, interning names and paths with `interner`
+    pub fn with_interner<I>(tags: I, interner: Interner) -> Self
+    where
+        I: IntoIterator<Item = CtagItem>,
+    {
+        let mut tags: Vec<CtagItem> = tags.into_iter().collect();
+        tags.sort_by(|a, b| (&a.file_path, &a.address).cmp(&(&b.file_path, &b.address)));
+
+        let mut by_name: HashMap<Symbol, Vec<usize>> = HashMap::new();
+        let mut by_path: HashMap<Symbol, Vec<usize>> = HashMap::new();
+
+        for (position, tag) in tags.iter().enumerate() {
+            by_name
+                .entry(interner.intern(&tag.name))
+                .or_default()
+                .push(position);
+            by_path
+                .entry(interner.intern(&tag.file_path.to_string_lossy()))
+                .or_default()
+                .push(position);
+        }
+
+        TagsIndex {
+            interner,
+            tags: Arc::new(tags),
+            by_name: Arc::new(by_name),
+            by_path: Arc::new(by_path),
+        }
+    }
+
+    /// The interner used for names and paths
+    pub fn interner(&self) -> &Interner {
+        &self.interner
+    }
+
+    /// All indexed tags, ordered by path and address
+    pub fn tags(&self) -> &[CtagItem] {
+        &self.tags
+    }
+
+    /// Tags with exactly this name
+    pub fn named(&self, name: &str) -> Vec<&CtagItem> {
+        self.lookup(&self.by_name, name)
+    }
+
+    /// Where tags with exactly this name are defined, ordered by path and address
+    ///
+    /// Each definition's line and column are resolved by reading its file, giving a
+    /// go-to-definition lookup over the index.
+    #[cfg(feature = "fs")]
+    pub fn definitions_of(&self, name: &str) -> Vec<Location> {
+        self.named(name).into_iter().map(Location::of).collect()
+    }
+
+    /// Tags defined in this file
+    pub fn defined_in(&self, path: &Path) -> Vec<&CtagItem> {
+        self.lookup(&self.by_path, &path.to_string_lossy())
+    }
+
+    /// An index over only the tags `filter` keeps, sharing this index's interner
+    pub fn filter_kinds(&self, filter: &KindFilter) -> TagsIndex {
+        Self::with_interner(
+            self.tags.iter().filter(|tag| filter.includes(tag)).cloned(),
+            self.interner.clone(),
+        )
+    }
+
+    fn lookup(&self, index: &HashMap<Symbol, Vec<usize>>, key: &str) -> Vec<&CtagItem> {
+        self.interner
+            .get(key)
+            .and_then(|symbol| index.get(&symbol))
+            .map(|positions| positions.iter().map(|p| &self.tags[*p]).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KindPattern, Language, TokenKind};
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    fn tag(name: &str, path: &str, address: &str) -> CtagItem {
+        CtagItem {
+            name: name.to_string(),
+            file_path: PathBuf::from(path).into(),
+            raw_path: path.into(),
+            address: address.to_string(),
+            language: Some(Language::Ruby),
+            tags: BTreeMap::new(),
+            kind: TokenKind::Method,
+        }
+    }
+
+    #[test]
+    fn looks_up_tags_by_name_and_path() {
+        let index = TagsIndex::new(vec![
+            tag("name", "app/models/person.rb", "2"),
+            tag("name", "app/models/company.rb", "4"),
+            tag("age", "app/models/person.rb", "5"),
+        ]);
+
+        let names: Vec<_> = index.named("name").iter().map(|t| &*t.file_path).collect();
+        let defined: Vec<_> = index
+            .defined_in(Path::new("app/models/person.rb"))
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                Path::new("app/models/company.rb"),
+                Path::new("app/models/person.rb")
+            ]
+        );
+        assert_eq!(defined, vec!["name", "age"]);
+        assert!(index.named("missing").is_empty());
+    }
+
+    #[test]
+    fn filters_tags_by_kind() {
+        let index = TagsIndex::new(vec![
+            CtagItem {
+                kind: TokenKind::Class,
+                ..tag("Person", "app/models/person.rb", "1")
+            },
+            tag("name", "app/models/person.rb", "2"),
+            CtagItem {
+                kind: TokenKind::Constant,
+                ..tag("LIMIT", "app/models/person.rb", "3")
+            },
+        ]);
+        let names = |filter: &KindFilter| -> Vec<String> {
+            index
+                .filter_kinds(filter)
+                .tags()
+                .iter()
+                .map(|t| t.name.clone())
+                .collect()
+        };
+
+        assert_eq!(
+            names(&KindFilter::new(vec!["f".parse().unwrap()], vec![])),
+            vec!["name"]
+        );
+        assert_eq!(
+            names(&KindFilter::new(vec![], vec!["constant".parse().unwrap()])),
+            vec!["Person", "name"]
+        );
+        assert_eq!(names(&KindFilter::default()).len(), 3);
+        assert!("widget".parse::<KindPattern>().is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn resolves_definitions_by_name() {
+        let path = std::env::temp_dir().join("tags_index_resolves_definitions.rb");
+        std::fs::write(
+            &path,
+            "class UserSerializer
+  def name; end
+end
+",
+        )
+        .unwrap();
+        let path = path.to_str().unwrap();
+        let index = TagsIndex::new(vec![
+            tag("UserSerializer", path, "/^class UserSerializer$/"),
+            tag("UserSerializer", "app/missing.rb", "7"),
+        ]);
+
+        let definitions = index.definitions_of("UserSerializer");
+        std::fs::remove_file(path).ok();
+
+        assert_eq!(
+            definitions,
+            vec![
+                Location {
+                    path: PathBuf::from(path).into(),
+                    line: Some(1),
+                    column: Some(7),
+                },
+                Location {
+                    path: PathBuf::from("app/missing.rb").into(),
+                    line: Some(7),
+                    column: None,
+                },
+            ]
+        );
+        assert!(index.definitions_of("missing").is_empty());
+    }
+}
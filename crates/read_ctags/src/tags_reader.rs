@@ -1,52 +1,49 @@
-use super::{CtagItem, CtagsParseError, TagsFile};
-use std::convert::From;
+use super::{
+    CtagItem, CtagsParseError, ParseMode, ParseWarning, ReadCtagsError, TagsFile, TagsFormat,
+};
+use codebase_files::{text, DiskFileSystem, FileSystem};
 use std::default::Default;
 use std::env::current_dir;
-use std::fmt::{Display, Formatter};
-use std::fs;
-use std::io;
 use std::io::Error;
-use std::path::PathBuf;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
-/// TagsReader provides a mechanism for attempting to read multiple ctags files until the first is
-/// found
-pub struct TagsReader {
-    filenames: Vec<PathBuf>,
-}
+/// Path naming STDIN, so tags can be piped in (`ctags -R -f - | unused --tags -`)
+pub const STDIN_PATH: &str = "-";
 
-/// A struct capturing possible failures when attempting to find and read tags files
-pub enum ReadCtagsError {
-    /// No tags file found
-    ///
-    /// This provides the paths attempted
-    NoCtagsFile(Vec<PathBuf>, io::Error),
-    /// Error parsing tags
-    CtagsParseError(CtagsParseError),
-}
+// Tags files read and parsed at once when several are loaded, such as one per sub-project
+const MAX_LOADING_THREADS: usize = 8;
 
-impl From<CtagsParseError> for ReadCtagsError {
-    fn from(err: CtagsParseError) -> Self {
-        ReadCtagsError::CtagsParseError(err)
-    }
+// Nanoseconds spent reading tags files, before parsing them, since the total was last taken
+static READ_NANOS: AtomicU64 = AtomicU64::new(0);
+
+/// Which tags files a `TagsReader` loads
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoadMode {
+    /// The first of the reader's candidate files that can be read
+    FirstFound,
+    /// Every candidate file that can be read, merging their tags
+    All,
+    /// These files, each of which must be read, merging their tags
+    Explicit(Vec<PathBuf>),
 }
 
-impl Display for ReadCtagsError {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        match *self {
-            ReadCtagsError::NoCtagsFile(ref file_list, ref err) => write!(
-                f,
-                "Unable to find ctags file (searched in {}): {}",
-                file_list
-                    .iter()
-                    .filter_map(|f| f.to_str())
-                    .collect::<Vec<_>>()
-                    .join(", "),
-                err
-            ),
-            ReadCtagsError::CtagsParseError(ref err) => write!(f, "{}", err),
-        }
-    }
+/// TagsReader provides a mechanism for attempting to read multiple ctags files until the first is
+/// found, or loading several (see `LoadMode`)
+#[derive(Clone)]
+pub struct TagsReader {
+    filenames: Vec<PathBuf>,
+    mode: LoadMode,
+    format: TagsFormat,
+    parse_mode: ParseMode,
+    file_system: Arc<dyn FileSystem>,
+    // Project root tag paths are made relative to, the current directory unless set
+    root: Option<PathBuf>,
 }
 
 fn git_path() -> Option<PathBuf> {
@@ -78,7 +75,7 @@ impl Default for TagsReader {
 
         if let Ok(current_dir) = current_dir() {
             if let Some(app_git_path) = git_path() {
-                if app_git_path == PathBuf::from(".git") {
+                if app_git_path == Path::new(".git") {
                     filenames.push(current_dir.join(app_git_path).join("tags"));
                     filenames.extend(cwd_tags_paths(current_dir));
                 } else {
@@ -90,39 +87,438 @@ impl Default for TagsReader {
             }
         }
 
-        TagsReader { filenames }
+        TagsReader::from_filenames(filenames)
     }
 }
 
 impl TagsReader {
-    /// Loads and parses the first tags file it finds
+    /// Reader for the tags file of the project at `root`, rather than the current directory
+    pub fn for_root(root: &Path) -> Self {
+        let mut filenames = vec![root.join(".git/tags")];
+        filenames.extend(cwd_tags_paths(root.to_path_buf()));
+
+        TagsReader::from_filenames(filenames).with_root(root)
+    }
+
+    /// Reader for a specific tags file, such as one passed with `--tags`, or STDIN when `path` is
+    /// `-`
+    pub fn for_path(path: &Path) -> Self {
+        TagsReader::from_filenames(vec![]).with_mode(LoadMode::Explicit(vec![path.to_path_buf()]))
+    }
+
+    /// Load tags files as `mode` describes, rather than only the first found
+    pub fn with_mode(mut self, mode: LoadMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Parse tags files as `format`, such as one passed with `--tags-format`, rather than
+    /// detecting JSON tags from each file's first line
+    pub fn with_format(mut self, format: TagsFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Skip malformed lines in `ParseMode::Lenient`, logging a warning for each, rather than
+    /// failing to load at the first
+    pub fn with_parse_mode(mut self, mode: ParseMode) -> Self {
+        self.parse_mode = mode;
+        self
+    }
+
+    /// Read tags files from `file_system` rather than from disk
+    pub fn with_file_system(mut self, file_system: Arc<dyn FileSystem>) -> Self {
+        self.file_system = file_system;
+        self
+    }
+
+    /// Make tag paths relative to `root`, such as `/app` for tags generated in a container, rather
+    /// than the current directory (see `TagsFile::normalize_paths`)
+    pub fn with_root(mut self, root: &Path) -> Self {
+        self.root = Some(root.to_path_buf());
+        self
+    }
+
+    fn from_filenames(filenames: Vec<PathBuf>) -> Self {
+        TagsReader {
+            filenames,
+            mode: LoadMode::FirstFound,
+            format: TagsFormat::Auto,
+            parse_mode: ParseMode::Strict,
+            file_system: Arc::new(DiskFileSystem),
+            root: None,
+        }
+    }
+
+    /// Time spent reading tags files from disk (not parsing them) by any reader since this was
+    /// last called, for profiling where loading tags spends its time
+    ///
+    /// Files read concurrently each add the time they took, so this can exceed the wall-clock time
+    /// loading took.
+    pub fn take_read_duration() -> Duration {
+        Duration::from_nanos(READ_NANOS.swap(0, Ordering::Relaxed))
+    }
+
+    /// Loads and parses the first tags file it finds (or each file its `LoadMode` names), with
+    /// paths relative to the project root
+    ///
+    /// The files read are listed in the result's `sources`. When several files are loaded, they're
+    /// read and parsed concurrently, on up to eight threads.
     pub fn load(&self) -> Result<TagsFile, ReadCtagsError> {
-        self.read().and_then(|(ctags_path, contents)| {
-            CtagItem::parse(ctags_path, &contents).map_err(|e| e.into())
-        })
+        let started = Instant::now();
+        let mut tags_files = vec![];
+        for (parsed, elapsed) in self.load_each(|path, input| self.parse(path, input))? {
+            let tags_file = parsed?
+                .detect_languages(self.file_system.as_ref())
+                .normalize_paths(self.root());
+            tracing::info!(
+                "Parsed {} entries from {} in {:.1?}",
+                tags_file.tags.len(),
+                tags_file.path.display(),
+                elapsed
+            );
+            tags_files.push(tags_file);
+        }
+        if tags_files.len() > 1 {
+            tracing::info!(
+                "Loaded {} tags files in {:.1?}",
+                tags_files.len(),
+                started.elapsed()
+            );
+        }
+        Ok(merge(tags_files))
     }
 
-    fn read(&self) -> Result<(PathBuf, String), ReadCtagsError> {
-        Self::first_success(
-            &self.filenames,
-            Error::new(io::ErrorKind::Other, "No file provided"),
-            fs::read_to_string,
-        )
-        .map_err(|e| ReadCtagsError::NoCtagsFile(self.filenames.clone(), e))
+    /// Loads the first tags file it finds (or each file its `LoadMode` names), skipping
+    /// malformed lines regardless of its `ParseMode` and returning a warning for each
+    pub fn load_lenient(&self) -> Result<(TagsFile, Vec<ParseWarning>), ReadCtagsError> {
+        let mut tags_files = vec![];
+        let mut all_warnings = vec![];
+        for ((tags_file, warnings), _) in
+            self.load_each(|path, input| CtagItem::parse_lenient(path, input, self.format))?
+        {
+            tags_files.push(
+                tags_file
+                    .detect_languages(self.file_system.as_ref())
+                    .normalize_paths(self.root()),
+            );
+            all_warnings.extend(warnings);
+        }
+        Ok((merge(tags_files), all_warnings))
+    }
+
+    /// Loads and parses the first tags file it finds, without blocking the async executor
+    ///
+    /// The file is read and parsed on tokio's blocking thread pool, so this must be awaited
+    /// within a tokio runtime.
+    #[cfg(feature = "async")]
+    pub async fn load_async(&self) -> Result<TagsFile, ReadCtagsError> {
+        let reader = self.clone();
+
+        tokio::task::spawn_blocking(move || reader.load())
+            .await
+            .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+    }
+
+    fn parse(&self, path: PathBuf, input: &str) -> Result<TagsFile, CtagsParseError> {
+        if self.parse_mode == ParseMode::Strict {
+            return CtagItem::parse_as(path, input, self.format);
+        }
+
+        let (tags_file, warnings) = CtagItem::parse_lenient(path, input, self.format);
+        for warning in warnings {
+            tracing::warn!("{} in {}", warning, tags_file.path.display());
+        }
+        Ok(tags_file)
+    }
+
+    fn root(&self) -> &Path {
+        self.root.as_deref().unwrap_or_else(|| Path::new("."))
     }
 
-    fn first_success<A, B, C, F>(values: &[A], default: C, f: F) -> Result<(A, B), C>
+    // Each tags file loaded, in order, parsed with `parse` and with the time taken to read and
+    // parse it; at least one file is always read
+    fn load_each<T, F>(&self, parse: F) -> Result<Vec<(T, Duration)>, ReadCtagsError>
     where
-        A: Clone,
-        F: Fn(A) -> Result<B, C>,
+        T: Send,
+        F: Fn(PathBuf, &str) -> T + Sync,
     {
-        let mut outcome = Err(default);
-        for x in values.iter() {
-            outcome = f(x.clone()).map(|v| (x.clone(), v));
-            if outcome.is_ok() {
-                break;
+        let load = |path: &PathBuf| {
+            let started = Instant::now();
+            let contents = self.read_file(path)?;
+            READ_NANOS.fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+            tracing::info!("Using tags file {}", path.display());
+            let parsed = parse(path.clone(), &contents);
+            Ok((parsed, started.elapsed()))
+        };
+
+        match &self.mode {
+            LoadMode::FirstFound => {
+                let mut last_error = Error::other("No file provided");
+                for path in &self.filenames {
+                    match load(path) {
+                        Ok(loaded) => return Ok(vec![loaded]),
+                        Err(e) => last_error = e,
+                    }
+                }
+                Err(ReadCtagsError::NoCtagsFile(
+                    self.filenames.clone(),
+                    last_error,
+                ))
             }
+            LoadMode::All => {
+                let mut last_error = Error::other("No file provided");
+                let loaded: Vec<_> = concurrently(&self.filenames, load)
+                    .into_iter()
+                    .filter_map(|loaded| loaded.map_err(|e| last_error = e).ok())
+                    .collect();
+                if loaded.is_empty() {
+                    Err(ReadCtagsError::NoCtagsFile(
+                        self.filenames.clone(),
+                        last_error,
+                    ))
+                } else {
+                    Ok(loaded)
+                }
+            }
+            LoadMode::Explicit(paths) if paths.is_empty() => Err(ReadCtagsError::NoCtagsFile(
+                vec![],
+                Error::other("No file provided"),
+            )),
+            LoadMode::Explicit(paths) => concurrently(paths, load)
+                .into_iter()
+                .zip(paths)
+                .map(|(loaded, path)| {
+                    loaded.map_err(|e| ReadCtagsError::NoCtagsFile(vec![path.clone()], e))
+                })
+                .collect(),
+        }
+    }
+
+    fn read_file(&self, path: &Path) -> Result<String, Error> {
+        let contents = if path == Path::new(STDIN_PATH) {
+            text::read_stdin()
+        } else {
+            self.file_system.read(path)
+        };
+        contents.inspect_err(|e| {
+            tracing::debug!("Skipping tags file {}: {}", path.display(), e);
+        })
+    }
+}
+
+// Apply `f` to each of `items` on a bounded number of threads, keeping the order of `items`
+fn concurrently<A, B, F>(items: &[A], f: F) -> Vec<B>
+where
+    A: Sync,
+    B: Send,
+    F: Fn(&A) -> B + Sync,
+{
+    let threads = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(MAX_LOADING_THREADS)
+        .min(items.len());
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, B)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = vec![];
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        match items.get(index) {
+                            Some(item) => results.push((index, f(item))),
+                            None => return results,
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect()
+    });
+    results.sort_by_key(|(index, _)| *index);
+
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+// Tags of every file read, with the first file's path and program
+fn merge(tags_files: Vec<TagsFile>) -> TagsFile {
+    let mut tags_files = tags_files.into_iter();
+    let mut merged = tags_files
+        .next()
+        .expect("at least one tags file is always read");
+    for tags_file in tags_files {
+        merged.sources.extend(tags_file.sources);
+        merged.tags.extend(tags_file.tags);
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn chains_error_sources() {
+        let reader = TagsReader::for_path(Path::new("missing/tags"));
+        let err: Box<dyn std::error::Error> = Box::new(reader.load().err().unwrap());
+
+        assert!(err
+            .to_string()
+            .starts_with("Unable to find ctags file (searched in missing/tags): "));
+        assert_eq!(
+            err.source()
+                .and_then(|source| source.downcast_ref::<io::Error>())
+                .map(io::Error::kind),
+            Some(io::ErrorKind::NotFound)
+        );
+
+        let parse_error =
+            CtagItem::parse(PathBuf::from("tags"), "Person\tapp/person.rb\t1;\"\tclass")
+                .map(|_| ())
+                .map_err(ReadCtagsError::from)
+                .unwrap_err();
+        assert_eq!(
+            parse_error.to_string(),
+            "Failed to parse ctags file on line 1: unexpected field 'class'\n    Person\\tapp/person.rb\\t1;\\\"\\tclass"
+        );
+    }
+
+    #[test]
+    fn loads_specific_files_leniently() {
+        let path = std::env::temp_dir().join("tags_reader_loads_specific_files_leniently");
+        std::fs::write(
+            &path,
+            "Person\tapp/person.rb\t1;\"\tc\nname\tapp/person.rb\t2;\"\tf\tclass\n",
+        )
+        .unwrap();
+
+        let loaded = TagsReader::for_path(&path).load_lenient();
+        std::fs::remove_file(&path).ok();
+        let (tags_file, warnings) = loaded.unwrap();
+
+        assert_eq!(tags_file.path, path);
+        assert_eq!(tags_file.tags.len(), 1);
+        assert_eq!(
+            warnings,
+            vec![ParseWarning {
+                line: 2,
+                content: String::from("name\tapp/person.rb\t2;\"\tf\tclass"),
+            }]
+        );
+    }
+
+    #[test]
+    fn reads_tags_from_a_file_system() {
+        let file_system = codebase_files::MemoryFileSystem::default();
+        file_system.insert("project/tmp/tags", "Person\tapp/person.rb\t1;\"\tc\n");
+
+        let tags_file = TagsReader::for_root(Path::new("project"))
+            .with_file_system(Arc::new(file_system))
+            .load()
+            .unwrap();
+
+        assert_eq!(tags_file.path, PathBuf::from("project/tmp/tags"));
+        assert_eq!(tags_file.tags.len(), 1);
+    }
+
+    #[test]
+    fn skips_malformed_lines_in_lenient_mode() {
+        let file_system = Arc::new(codebase_files::MemoryFileSystem::default());
+        file_system.insert(
+            "project/tags",
+            "Person\tapp/person.rb\t1;\"\tc\nname\tapp/person.rb\t2;\"\tf\tclass\n",
+        );
+        let reader = TagsReader::for_root(Path::new("project")).with_file_system(file_system);
+
+        assert!(reader.load().is_err());
+        let tags_file = reader.with_parse_mode(ParseMode::Lenient).load().unwrap();
+        assert_eq!(tags_file.tags.len(), 1);
+    }
+
+    #[test]
+    fn loads_tags_files_by_mode() {
+        let file_system = Arc::new(codebase_files::MemoryFileSystem::default());
+        file_system.insert("project/tags", "Person\tapp/person.rb\t1;\"\tc\n");
+        file_system.insert("project/tmp/tags", "render\tapp/view.js\t3;\"\tf\n");
+        let load = |mode: LoadMode| {
+            TagsReader::for_root(Path::new("project"))
+                .with_file_system(file_system.clone())
+                .with_mode(mode)
+                .load()
+        };
+
+        let first = load(LoadMode::FirstFound).unwrap();
+        assert_eq!(first.sources, vec![PathBuf::from("project/tags")]);
+        assert_eq!(first.tags.len(), 1);
+
+        let all = load(LoadMode::All).unwrap();
+        assert_eq!(
+            all.sources,
+            vec![
+                PathBuf::from("project/tags"),
+                PathBuf::from("project/tmp/tags")
+            ]
+        );
+        assert_eq!(all.path, PathBuf::from("project/tags"));
+        assert_eq!(all.tags.len(), 2);
+
+        let explicit = load(LoadMode::Explicit(vec![PathBuf::from("project/tmp/tags")])).unwrap();
+        assert_eq!(explicit.sources, vec![PathBuf::from("project/tmp/tags")]);
+        assert!(load(LoadMode::Explicit(vec![
+            PathBuf::from("project/tags"),
+            PathBuf::from("project/.git/tags")
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn loads_many_tags_files_concurrently_in_order() {
+        let file_system = Arc::new(codebase_files::MemoryFileSystem::default());
+        let paths: Vec<PathBuf> = (0..20)
+            .map(|i| PathBuf::from(format!("projects/{}/tags", i)))
+            .collect();
+        for (i, path) in paths.iter().enumerate() {
+            file_system.insert(path, format!("Model{}\tapp/model.rb\t1;\"\tc\n", i));
         }
-        outcome
+
+        let tags_file = TagsReader::for_path(Path::new("tags"))
+            .with_file_system(file_system)
+            .with_mode(LoadMode::Explicit(paths.clone()))
+            .load()
+            .unwrap();
+
+        assert_eq!(tags_file.sources, paths);
+        assert_eq!(tags_file.path, paths[0]);
+        assert_eq!(tags_file.tags.len(), 20);
+        assert_eq!(concurrently(&[3, 1, 2], |n| n * 10), vec![30, 10, 20]);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn loads_tags_asynchronously() {
+        let root = std::env::temp_dir().join("tags_reader_loads_asynchronously");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("tags"), "Person\tapp/person.rb\t1;\"\tc\n").unwrap();
+
+        let tags_file = TagsReader::for_root(&root).load_async().await.unwrap();
+        std::fs::remove_dir_all(&root).ok();
+
+        assert_eq!(tags_file.path, root.join("tags"));
+        assert_eq!(tags_file.tags.len(), 1);
+        assert!(TagsReader::for_root(&root).load_async().await.is_err());
     }
 }
@@ -0,0 +1,92 @@
+use super::ctag_item::{CtagItem, TagValidationError};
+use std::io::{self, Write};
+use thiserror::Error;
+
+/// Writes tags as lines of a tags file, refusing tags that wouldn't read back unchanged
+///
+/// Each tag is checked with `CtagItem::validate` before it's written, so a name containing a
+/// tab or an address that isn't a line number or search pattern is reported rather than
+/// corrupting the file. Refused tags write nothing, and later tags can still be written.
+pub struct TagsWriter<W: Write> {
+    out: W,
+}
+
+/// Why a tag wasn't written
+#[derive(Debug, Error)]
+pub enum TagsWriteError {
+    /// The tag can't be encoded as a line that reads back as the same tag
+    #[error("Refusing to write tag {name:?}: {source}")]
+    Invalid {
+        /// Name of the refused tag
+        name: String,
+        /// What's wrong with the tag
+        #[source]
+        source: TagValidationError,
+    },
+    /// Writing the line failed
+    #[error("Unable to write tags: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl<W: Write> TagsWriter<W> {
+    /// Write tags to `out`
+    pub fn new(out: W) -> Self {
+        TagsWriter { out }
+    }
+
+    /// Validate `tag`, then write it as a line of the tags file
+    pub fn write(&mut self, tag: &CtagItem) -> Result<(), TagsWriteError> {
+        tag.validate().map_err(|source| TagsWriteError::Invalid {
+            name: tag.name.clone(),
+            source,
+        })?;
+        writeln!(self.out, "{}", tag.encode())?;
+        Ok(())
+    }
+
+    /// The writer tags were written to
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags_format::TagsFormat;
+
+    fn parse(line: &str) -> CtagItem {
+        CtagItem::parse_lenient("tags".into(), line, TagsFormat::Auto)
+            .0
+            .tags
+            .into_iter()
+            .next()
+            .unwrap()
+    }
+
+    #[test]
+    fn writes_valid_tags_and_refuses_corrupt_ones() {
+        let person = parse("Person\tapp/person.rb\t/^class Person$/;\"\tc");
+        let full_name = parse("full_name\tapp/person.rb\t2;\"\tf\tclass:Person");
+        let mut writer = TagsWriter::new(vec![]);
+
+        writer.write(&person).unwrap();
+        let refused = writer.write(&CtagItem {
+            name: String::from("first\tname"),
+            ..full_name.clone()
+        });
+        writer.write(&full_name).unwrap();
+
+        assert!(matches!(
+            refused,
+            Err(TagsWriteError::Invalid {
+                source: TagValidationError::InvalidName(_),
+                ..
+            })
+        ));
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "Person\tapp/person.rb\t/^class Person$/;\"\tc\nfull_name\tapp/person.rb\t2;\"\tf\tclass:Person\n"
+        );
+    }
+}
@@ -34,6 +34,7 @@ pub enum TokenKind {
     Heading1,
     Heading2,
     Heading3,
+    Title,
     // JSON
     Array,
     Boolean,
@@ -56,11 +57,14 @@ pub enum TokenKind {
     Section,
     SubSubsection,
     L5Subsection,
+    Footnote,
+    Hashtag,
     // Python
     Member,
     // Ruby
     SingletonMethod,
     RSpecDescribe,
+    Library,
     // Rust
     Field,
     Struct,
@@ -81,7 +85,10 @@ pub enum TokenKind {
     // XML
     NSPrefix,
     Root,
+    // Locale files
+    TranslationKey,
     Undefined,
+    // Letters without a corresponding kind, kept as written so tags can be encoded unchanged
     MissingLanguageToken(Language, char),
     Unknown(char),
 }
@@ -92,7 +99,8 @@ impl TokenKind {
     /// This is based off of Universal Ctags' generated list:
     ///   $ ctags --list-kinds-full
     ///
-    /// This list is not comprehensive in that it is based off of the languages accounted for.
+    /// Every kind of each language accounted for is covered; letters of other languages are kept
+    /// as `Unknown` or `MissingLanguageToken`, so `to_ctag` returns them unchanged.
     pub fn from_ctag(language: Option<Language>, char: char) -> Self {
         match (language, char) {
             (Some(Language::CSS), 'c') => TokenKind::Class,
@@ -126,6 +134,7 @@ impl TokenKind {
             (Some(Language::HTML), 'h') => TokenKind::Heading1,
             (Some(Language::HTML), 'i') => TokenKind::Heading2,
             (Some(Language::HTML), 'j') => TokenKind::Heading3,
+            (Some(Language::HTML), 't') => TokenKind::Title,
             (Some(Language::JSON), 'a') => TokenKind::Array,
             (Some(Language::JSON), 'b') => TokenKind::Boolean,
             (Some(Language::JSON), 'n') => TokenKind::Number,
@@ -134,6 +143,7 @@ impl TokenKind {
             (Some(Language::JSON), 'z') => TokenKind::Null,
             (Some(Language::JavaScript), 'C') => TokenKind::Constant,
             (Some(Language::JavaScript), 'G') => TokenKind::Getter,
+            (Some(Language::JavaScript), 'M') => TokenKind::Field,
             (Some(Language::JavaScript), 'S') => TokenKind::Setter,
             (Some(Language::JavaScript), 'c') => TokenKind::Class,
             (Some(Language::JavaScript), 'f') => TokenKind::Function,
@@ -144,6 +154,8 @@ impl TokenKind {
             (Some(Language::Markdown), 'S') => TokenKind::Subsection,
             (Some(Language::Markdown), 'T') => TokenKind::L4Subsection,
             (Some(Language::Markdown), 'c') => TokenKind::Chapter,
+            (Some(Language::Markdown), 'h') => TokenKind::Hashtag,
+            (Some(Language::Markdown), 'n') => TokenKind::Footnote,
             (Some(Language::Markdown), 's') => TokenKind::Section,
             (Some(Language::Markdown), 't') => TokenKind::SubSubsection,
             (Some(Language::Markdown), 'u') => TokenKind::L5Subsection,
@@ -156,7 +168,11 @@ impl TokenKind {
             (Some(Language::Python), 'v') => TokenKind::Variable,
             (Some(Language::Python), 'x') => TokenKind::Unknown('x'),
             (Some(Language::Python), 'z') => TokenKind::Parameter,
+            (Some(Language::Ruby), 'A') => TokenKind::Property,
+            (Some(Language::Ruby), 'C') => TokenKind::Constant,
+            (Some(Language::Ruby), 'L') => TokenKind::Library,
             (Some(Language::Ruby), 'S') => TokenKind::SingletonMethod,
+            (Some(Language::Ruby), 'a') => TokenKind::Alias,
             (Some(Language::Ruby), 'c') => TokenKind::Class,
             (Some(Language::Ruby), 'f') => TokenKind::Method,
             (Some(Language::Ruby), 'm') => TokenKind::Module,
@@ -207,4 +223,120 @@ impl TokenKind {
             (None, c) => TokenKind::Unknown(c),
         }
     }
+
+    /// Construct a TokenKind given a language with a character written by Exuberant Ctags
+    ///
+    /// Exuberant Ctags, the predecessor of Universal Ctags, writes different letters for a few
+    /// kinds; the rest are shared.
+    pub fn from_exuberant_ctag(language: Option<Language>, char: char) -> Self {
+        match (language, char) {
+            (Some(Language::HTML), 'f') => TokenKind::Function,
+            (Some(Language::Ruby), 'F') => TokenKind::SingletonMethod,
+            _ => Self::from_ctag(language, char),
+        }
+    }
+
+    /// The character ctags writes for this kind in `language`, the inverse of `from_ctag`
+    ///
+    /// Every letter `from_ctag` reads for a language is returned unchanged.
+    pub fn to_ctag(&self, language: Option<Language>) -> Option<char> {
+        match self {
+            TokenKind::Undefined => None,
+            TokenKind::MissingLanguageToken(_, c) | TokenKind::Unknown(c) => Some(*c),
+            kind => ('a'..='z')
+                .chain('A'..='Z')
+                .find(|c| Self::from_ctag(language, *c) == *kind),
+        }
+    }
+
+    /// Construct a TokenKind from the full name of a ctags kind (e.g. `method`), as listed by
+    /// `ctags --list-kinds-full`, for names with a corresponding TokenKind
+    pub fn from_name(name: &str) -> Option<Self> {
+        let kind = match name.to_lowercase().as_ref() {
+            "class" => TokenKind::Class,
+            "id" => TokenKind::Id,
+            "selector" => TokenKind::Selector,
+            "macro" => TokenKind::Macro,
+            "callback" => TokenKind::Callback,
+            "delegate" => TokenKind::Delegate,
+            "exception" => TokenKind::Exception,
+            "function" | "func" => TokenKind::Function,
+            "guard" => TokenKind::Guard,
+            "implementation" | "impl" => TokenKind::Implementation,
+            "module" | "package" => TokenKind::Module,
+            "operator" => TokenKind::Operator,
+            "protocol" => TokenKind::Protocol,
+            "record" => TokenKind::Record,
+            "test" => TokenKind::Test,
+            "type" => TokenKind::Type,
+            "alias" => TokenKind::Alias,
+            "constructor" => TokenKind::Constructor,
+            "namespace" => TokenKind::Namespace,
+            "constant" | "const" => TokenKind::Constant,
+            "getter" => TokenKind::Getter,
+            "setter" => TokenKind::Setter,
+            "generator" => TokenKind::Generator,
+            "method" | "singletonmethod" | "singleton method" => TokenKind::Method,
+            "property" | "accessor" => TokenKind::Property,
+            "variable" | "var" => TokenKind::Variable,
+            "member" => TokenKind::Member,
+            "field" => TokenKind::Field,
+            "struct" => TokenKind::Struct,
+            "typedef" => TokenKind::Typedef,
+            "enumerator" => TokenKind::Enumerator,
+            "enum" => TokenKind::Enum,
+            "interface" => TokenKind::Interface,
+            "local" => TokenKind::Local,
+            "parameter" => TokenKind::Parameter,
+            "mixin" => TokenKind::Mixin,
+            "placeholder" => TokenKind::Placeholder,
+            "heredoc" => TokenKind::Heredoc,
+            "title" => TokenKind::Title,
+            "footnote" => TokenKind::Footnote,
+            "hashtag" => TokenKind::Hashtag,
+            "library" => TokenKind::Library,
+            "translationkey" | "translation-key" => TokenKind::TranslationKey,
+            _ => return None,
+        };
+
+        Some(kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_letter() {
+        let languages = [
+            Language::CSS,
+            Language::Elixir,
+            Language::Elm,
+            Language::HTML,
+            Language::JSON,
+            Language::JavaScript,
+            Language::Markdown,
+            Language::Python,
+            Language::Ruby,
+            Language::Rust,
+            Language::SCSS,
+            Language::Sh,
+            Language::SVG,
+            Language::TypeScript,
+            Language::XML,
+        ];
+
+        for language in languages.iter().map(|l| Some(*l)).chain(Some(None)) {
+            for letter in ('a'..='z').chain('A'..='Z') {
+                assert_eq!(
+                    TokenKind::from_ctag(language, letter).to_ctag(language),
+                    Some(letter),
+                    "{:?} in {:?}",
+                    letter,
+                    language
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,80 @@
+use super::{CtagItem, CtagItemRef, TagsFormat, TagsIndex};
+use std::path::{Path, PathBuf};
+use wasm_bindgen::prelude::*;
+
+/// Parse the contents of a tags file, returning its tags as a JSON array
+#[wasm_bindgen(js_name = parseTags)]
+pub fn parse_tags(input: &str) -> Result<String, JsError> {
+    // Tab-separated tags are serialized without copying them out of `input`
+    if TagsFormat::Auto.resolve(input) == TagsFormat::Json {
+        let tags_file = CtagItem::parse(PathBuf::from("tags"), input)?;
+        return Ok(serde_json::to_string(&tags_file.tags)?);
+    }
+
+    let (_, tags) = CtagItemRef::parse(input)?;
+    Ok(serde_json::to_string(&tags)?)
+}
+
+/// Tags parsed from the contents of a tags file, indexed for lookups by name and by path
+///
+/// Lookups return JSON arrays of tags, in the same shape as `parseTags`.
+#[wasm_bindgen]
+pub struct Tags {
+    index: TagsIndex,
+}
+
+#[wasm_bindgen]
+impl Tags {
+    /// Parse and index the contents of a tags file
+    #[wasm_bindgen(constructor)]
+    pub fn new(input: &str) -> Result<Tags, JsError> {
+        let tags_file = CtagItem::parse(PathBuf::from("tags"), input)?;
+
+        Ok(Tags {
+            index: TagsIndex::new(tags_file.tags),
+        })
+    }
+
+    /// Number of tags
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.index.tags().len()
+    }
+
+    /// Tags with exactly this name
+    pub fn named(&self, name: &str) -> String {
+        to_json(&self.index.named(name))
+    }
+
+    /// Tags defined in this file
+    #[wasm_bindgen(js_name = definedIn)]
+    pub fn defined_in(&self, path: &str) -> String {
+        to_json(&self.index.defined_in(Path::new(path)))
+    }
+}
+
+fn to_json(tags: &[&CtagItem]) -> String {
+    serde_json::to_string(tags).unwrap_or_else(|_| String::from("[]"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TAGS: &str = "Person\tapp/models/person.rb\t/^class Person$/;\"\tc\nfull_name\tapp/models/person.rb\t/^  def full_name$/;\"\tf\tclass:Person\n";
+
+    #[test]
+    fn looks_up_parsed_tags() {
+        let tags = Tags::new(TAGS).unwrap();
+        let named: Vec<serde_json::Value> = serde_json::from_str(&tags.named("Person")).unwrap();
+        let defined: Vec<serde_json::Value> =
+            serde_json::from_str(&tags.defined_in("app/models/person.rb")).unwrap();
+
+        assert_eq!(tags.length(), 2);
+        assert_eq!(named.len(), 1);
+        assert_eq!(named[0]["name"], "Person");
+        assert_eq!(defined.len(), 2);
+        assert_eq!(tags.named("Missing"), "[]");
+        assert!(parse_tags(TAGS).unwrap().contains("full_name"));
+    }
+}
@@ -1,14 +1,19 @@
+use super::baseline::Baseline;
+use super::token_usage::TokenUsage;
 use super::usage_likelihood::UsageLikelihoodStatus;
 use project_configuration::{Assertion, ValueMatcher};
 use std::default::Default;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
-use token_search::TokenSearchResult;
+use token_search::{InlineSuppression, InlineSuppressions, TokenSearchResult};
 
 pub struct AnalysisFilter {
     pub usage_likelihood_filter: Vec<UsageLikelihoodStatus>,
     pub sort_order: SortOrder,
-    ignored_by_path: Vec<Assertion>,
+    ignored: Vec<Assertion>,
+    baseline: Baseline,
+    inline_suppressions: InlineSuppressions,
+    max_occurrences: Option<usize>,
 }
 
 pub enum SortOrder {
@@ -16,16 +21,15 @@ pub enum SortOrder {
     Descending(OrderField),
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default)]
 pub enum OrderField {
+    #[default]
     Token,
     File,
-}
-
-impl Default for OrderField {
-    fn default() -> Self {
-        OrderField::Token
-    }
+    /// Estimated cleanup value; see `CleanupValue`
+    Value,
+    /// Confidence the token can be deleted safely; see `Confidence`
+    Confidence,
 }
 
 impl FromStr for OrderField {
@@ -35,14 +39,24 @@ impl FromStr for OrderField {
         match s.to_lowercase().as_ref() {
             "file" => Ok(OrderField::File),
             "token" => Ok(OrderField::Token),
-            val => Err(String::from(format!("Unable to parse order: {}", val))),
+            "value" => Ok(OrderField::Value),
+            "confidence" => Ok(OrderField::Confidence),
+            val => Err(format!("Unable to parse order: {}", val)),
         }
     }
 }
 
 impl OrderField {
     pub fn variants() -> Vec<&'static str> {
-        vec!["token", "file"]
+        vec!["token", "file", "value", "confidence"]
+    }
+}
+
+impl SortOrder {
+    pub fn field(&self) -> OrderField {
+        match self {
+            SortOrder::Ascending(field) | SortOrder::Descending(field) => *field,
+        }
     }
 }
 
@@ -55,29 +69,82 @@ impl AnalysisFilter {
     }
 
     pub fn set_order_ascending(&mut self) {
-        match &self.sort_order {
-            SortOrder::Descending(field) => self.sort_order = SortOrder::Ascending(*field),
-            _ => (),
+        if let SortOrder::Descending(field) = &self.sort_order {
+            self.sort_order = SortOrder::Ascending(*field)
         }
     }
 
     pub fn set_order_descending(&mut self) {
-        match &self.sort_order {
-            SortOrder::Ascending(field) => self.sort_order = SortOrder::Descending(*field),
-            _ => (),
+        if let SortOrder::Ascending(field) = &self.sort_order {
+            self.sort_order = SortOrder::Descending(*field)
         }
     }
 
+    /// Only include tokens at least as likely to be unused as `minimum`
+    pub fn set_min_likelihood(&mut self, minimum: &UsageLikelihoodStatus) {
+        self.usage_likelihood_filter = UsageLikelihoodStatus::at_least(minimum);
+    }
+
     pub fn set_ignored(&mut self, substrings: Vec<String>) {
-        self.ignored_by_path = substrings
+        self.ignored = substrings
             .into_iter()
             .map(|s| Assertion::PathAssertion(ValueMatcher::Contains(s)))
             .collect()
     }
 
-    pub fn ignores_path(&self, result: &TokenSearchResult) -> bool {
-        if self.ignored_by_path.len() > 0 {
-            !self.ignored_by_path.iter().any(|a| a.matches(result))
+    pub fn add_ignored(&mut self, assertions: Vec<Assertion>) {
+        self.ignored.extend(assertions)
+    }
+
+    /// Also include tokens referenced at most `max` times outside their definitions, whatever
+    /// their likelihood, to find nearly-dead code and single-caller indirection
+    pub fn set_max_occurrences(&mut self, max: usize) {
+        self.max_occurrences = Some(max)
+    }
+
+    /// Is the token reported at its likelihood, or referenced few enough times to be reported
+    /// regardless?
+    pub fn includes_usage(&self, usage: &TokenUsage) -> bool {
+        self.usage_likelihood_filter
+            .contains(&usage.usage_likelihood.status)
+            || self
+                .max_occurrences
+                .is_some_and(|max| usage.references() <= max)
+    }
+
+    /// Exclude findings recorded in `baseline`
+    pub fn set_baseline(&mut self, baseline: Baseline) {
+        self.baseline = baseline
+    }
+
+    /// Exclude tokens whose definitions are annotated with `unused:ignore` comments
+    pub fn set_inline_suppressions(&mut self, inline_suppressions: InlineSuppressions) {
+        self.inline_suppressions = inline_suppressions
+    }
+
+    /// The annotation suppressing a token that would otherwise be reported, so suppressions can
+    /// be counted and audited apart from findings
+    pub fn suppression(&self, usage: &TokenUsage) -> Option<&InlineSuppression> {
+        if !self.includes_usage(usage)
+            || usage.allowed()
+            || !self.includes_unsuppressed(&usage.result)
+        {
+            return None;
+        }
+
+        self.inline_suppressions.get(&usage.result.token.token)
+    }
+
+    pub fn includes(&self, result: &TokenSearchResult) -> bool {
+        self.includes_unsuppressed(result)
+            && self.inline_suppressions.get(&result.token.token).is_none()
+    }
+
+    fn includes_unsuppressed(&self, result: &TokenSearchResult) -> bool {
+        if self.baseline.contains(result) {
+            false
+        } else if !self.ignored.is_empty() {
+            !self.ignored.iter().any(|a| a.matches(result))
         } else {
             true
         }
@@ -89,7 +156,10 @@ impl Default for AnalysisFilter {
         AnalysisFilter {
             usage_likelihood_filter: vec![UsageLikelihoodStatus::High],
             sort_order: SortOrder::Ascending(OrderField::Token),
-            ignored_by_path: vec![],
+            ignored: vec![],
+            baseline: Baseline::default(),
+            inline_suppressions: InlineSuppressions::default(),
+            max_occurrences: None,
         }
     }
 }
@@ -99,6 +169,8 @@ impl Display for OrderField {
         match self {
             OrderField::Token => write!(f, "token"),
             OrderField::File => write!(f, "file"),
+            OrderField::Value => write!(f, "value"),
+            OrderField::Confidence => write!(f, "confidence"),
         }
     }
 }
@@ -111,3 +183,28 @@ impl Display for SortOrder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TokenUsageBuilder;
+    use read_ctags::Language;
+
+    #[test]
+    fn includes_tokens_with_few_references_whatever_their_likelihood() {
+        let usage = |callers| {
+            TokenUsageBuilder::new("full_name", "app/person.rb")
+                .with_language(Language::Ruby)
+                .with_occurrences(&[("app/person.rb", 1), ("app/report.rb", callers)])
+                .build()
+        };
+        let mut filter = AnalysisFilter::default();
+
+        assert!(!filter.includes_usage(&usage(1)));
+
+        filter.set_max_occurrences(2);
+        assert!(filter.includes_usage(&usage(1)));
+        assert!(filter.includes_usage(&usage(2)));
+        assert!(!filter.includes_usage(&usage(3)));
+    }
+}
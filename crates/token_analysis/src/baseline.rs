@@ -0,0 +1,105 @@
+use super::token_usage::TokenUsage;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use token_search::TokenSearchResult;
+
+/// Findings accepted when adopting unused in an existing codebase, so only new findings are
+/// reported
+///
+/// Each token is recorded with the files defining it. A token stays suppressed while every one
+/// of its definitions is in a recorded file; defining it somewhere new reports it again.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    findings: BTreeMap<String, BTreeSet<PathBuf>>,
+}
+
+impl Baseline {
+    pub fn new<'a, I>(usages: I) -> Self
+    where
+        I: IntoIterator<Item = &'a TokenUsage>,
+    {
+        Baseline {
+            findings: usages
+                .into_iter()
+                .map(|usage| {
+                    (
+                        usage.result.token.token.to_string(),
+                        usage.result.defined_paths().into_iter().collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.findings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Was this token a finding when the baseline was generated?
+    pub fn contains(&self, result: &TokenSearchResult) -> bool {
+        self.findings.get(&result.token.token).is_some_and(|paths| {
+            result
+                .token
+                .defined_paths
+                .iter()
+                .all(|path| paths.contains(&**path))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use project_configuration::{ProjectConfiguration, TokenPolicy};
+    use read_ctags::{CtagItem, TokenKind};
+    use std::collections::{HashMap, HashSet};
+    use token_search::Token;
+
+    fn result(token: &str, defined_in: &[&str]) -> TokenSearchResult {
+        let definitions = defined_in
+            .iter()
+            .map(|path| CtagItem {
+                name: token.to_string(),
+                file_path: PathBuf::from(path).into(),
+                raw_path: (*path).into(),
+                address: String::from("1"),
+                language: None,
+                tags: BTreeMap::new(),
+                kind: TokenKind::Method,
+            })
+            .collect();
+
+        TokenSearchResult {
+            token: Token::new(token.to_string(), definitions),
+            occurrences: defined_in
+                .iter()
+                .map(|path| (PathBuf::from(path), 1))
+                .collect(),
+            mentions: HashMap::new(),
+            scopes: None,
+        }
+    }
+
+    #[test]
+    fn suppresses_findings_defined_in_recorded_files() {
+        let usage = TokenUsage::new(
+            &ProjectConfiguration::default(),
+            result("legacy", &["app/a.rb", "app/b.rb"]),
+            &HashSet::new(),
+            &TokenPolicy::default(),
+            &[],
+        );
+        let baseline = Baseline::new(vec![&usage]);
+
+        assert_eq!(baseline.len(), 1);
+        assert!(baseline.contains(&result("legacy", &["app/a.rb", "app/b.rb"])));
+        assert!(baseline.contains(&result("legacy", &["app/a.rb"])));
+        assert!(!baseline.contains(&result("legacy", &["app/a.rb", "app/c.rb"])));
+        assert!(!baseline.contains(&result("current", &["app/a.rb"])));
+    }
+}
@@ -0,0 +1,128 @@
+use super::token_usage::TokenUsage;
+use codebase_files::text::is_identifier_char;
+use codebase_files::DiskFileSystem;
+use read_ctags::CtagItem;
+use serde::Serialize;
+use std::fs;
+
+// Lines that close a block, counted as part of the definition they follow
+const BLOCK_CLOSERS: [&str; 4] = ["end", "}", ")", "]"];
+
+/// How much removing a token is likely to clean up
+///
+/// The value is the number of lines its definitions span, weighted by the confidence (its
+/// likelihood score out of 100) that it's unused, so the largest safe deletions rank highest.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct CleanupValue {
+    /// Estimated lines deleted by removing every definition
+    pub lines: usize,
+    /// Confidence the token is unused, from 0 to 1
+    pub confidence: f64,
+    pub value: f64,
+}
+
+impl CleanupValue {
+    pub fn estimate(usage: &TokenUsage) -> Self {
+        let lines = usage
+            .result
+            .token
+            .definitions
+            .iter()
+            .map(definition_lines)
+            .sum();
+        let confidence = f64::from(usage.usage_likelihood.score) / 100.0;
+
+        CleanupValue {
+            lines,
+            confidence,
+            value: (lines as f64 * confidence * 100.0).round() / 100.0,
+        }
+    }
+}
+
+// Lines spanned by a definition, from its `end` field when ctags recorded one (with
+// `--fields=+e`), otherwise by following its indentation; definitions that can't be located
+// count as a single line
+fn definition_lines(definition: &CtagItem) -> usize {
+    let start = match definition.line(&DiskFileSystem) {
+        Some(start) => start,
+        None => return 1,
+    };
+
+    let end = definition
+        .tags
+        .get("end")
+        .and_then(|end| end.parse::<usize>().ok());
+    if let Some(end) = end.filter(|end| *end >= start) {
+        return end - start + 1;
+    }
+
+    fs::read_to_string(&definition.file_path)
+        .map(|contents| indented_lines(&contents, start))
+        .unwrap_or(1)
+}
+
+// The line at `start` (1-based), followed by lines indented beneath it and a closing line at
+// the same indentation, such as `end` or `}`
+fn indented_lines(contents: &str, start: usize) -> usize {
+    let mut lines = contents.lines().skip(start - 1);
+    let indent = match lines.next() {
+        Some(line) => indentation(line),
+        None => return 1,
+    };
+
+    let mut count = 1;
+    let mut blank = 0;
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            blank += 1;
+        } else if indentation(line) > indent {
+            count += blank + 1;
+            blank = 0;
+        } else {
+            if closes_block(trimmed) {
+                count += blank + 1;
+            }
+            break;
+        }
+    }
+
+    count
+}
+
+fn closes_block(line: &str) -> bool {
+    BLOCK_CLOSERS.iter().any(|closer| {
+        line.strip_prefix(closer)
+            .is_some_and(|rest| !rest.starts_with(is_identifier_char))
+    })
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "class Person
+  def full_name
+    [first, last]
+
+      .join(' ')
+  end
+
+  def age; end
+  end_date = nil
+end
+";
+
+    #[test]
+    fn follows_indentation_to_the_end_of_a_definition() {
+        assert_eq!(indented_lines(SOURCE, 2), 5);
+        assert_eq!(indented_lines(SOURCE, 8), 1);
+        assert_eq!(indented_lines(SOURCE, 1), 10);
+        assert_eq!(indented_lines(SOURCE, 42), 1);
+    }
+}
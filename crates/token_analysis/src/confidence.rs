@@ -0,0 +1,131 @@
+use super::token_usage::TokenUsage;
+use super::usage_likelihood::CONVENTIONALLY_INVOKED_KINDS;
+use read_ctags::{CtagItem, Language, TokenKind};
+use serde::Serialize;
+
+// Kinds usually called by name, which may be built at runtime (e.g. `send("#{action}_later")`)
+const CALLABLE_KINDS: [TokenKind; 5] = [
+    TokenKind::Method,
+    TokenKind::Function,
+    TokenKind::SingletonMethod,
+    TokenKind::Alias,
+    TokenKind::Delegate,
+];
+
+/// How safe deleting a token is likely to be, so the safest deletions can be tackled first
+///
+/// The token's likelihood score is weighted by the kinds of its definitions, the risk that its
+/// language calls it dynamically rather than by a name the search could find, and the number of
+/// references found.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Confidence {
+    /// Weight of the token's kinds, from 0 to 1, lowest for kinds frameworks call by convention
+    pub kind_weight: f64,
+    /// Occurrences found across the project, including definitions
+    pub occurrences: usize,
+    /// Risk the token is called dynamically (such as with Ruby's `send` or Python's `getattr`),
+    /// from 0 to 1
+    pub dynamic_dispatch_risk: f64,
+    /// Confidence the token can be deleted safely, from 0 to 100
+    pub score: u8,
+}
+
+impl Confidence {
+    pub fn estimate(usage: &TokenUsage) -> Self {
+        let definitions = &usage.result.token.definitions;
+        let kind_weight = definitions
+            .iter()
+            .map(|d| kind_weight(&d.kind))
+            .fold(1.0, f64::min);
+        let dynamic_dispatch_risk = definitions
+            .iter()
+            .map(dynamic_dispatch_risk)
+            .fold(0.0, f64::max);
+        let occurrences = usage.file_type_counts.total().occurrence_count;
+        let references = usage.references();
+
+        let score = f64::from(usage.usage_likelihood.score)
+            * kind_weight
+            * (1.0 - dynamic_dispatch_risk / 2.0)
+            / (1.0 + 0.1 * references as f64);
+
+        Confidence {
+            kind_weight,
+            occurrences,
+            dynamic_dispatch_risk,
+            score: score.round().clamp(0.0, 100.0) as u8,
+        }
+    }
+}
+
+fn kind_weight(kind: &TokenKind) -> f64 {
+    if CONVENTIONALLY_INVOKED_KINDS.contains(kind) {
+        0.5
+    } else if CALLABLE_KINDS.contains(kind) {
+        0.8
+    } else if *kind == TokenKind::Undefined {
+        0.7
+    } else {
+        1.0
+    }
+}
+
+// Callables are most at risk; other kinds (such as classes looked up with `constantize`) less so
+fn dynamic_dispatch_risk(definition: &CtagItem) -> f64 {
+    let risk = match definition.language {
+        Some(Language::Ruby) => 0.6,
+        Some(Language::Python) => 0.5,
+        Some(Language::JavaScript) | Some(Language::TypeScript) => 0.4,
+        Some(Language::Elixir) => 0.3,
+        Some(Language::Rust) | Some(Language::Elm) => 0.0,
+        _ => 0.3,
+    };
+
+    if CALLABLE_KINDS.contains(&definition.kind) {
+        risk
+    } else {
+        risk / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TokenUsageBuilder;
+
+    #[test]
+    fn ranks_static_definitions_above_dynamically_callable_ones() {
+        let rust_struct = Confidence::estimate(
+            &TokenUsageBuilder::new("legacy", "src/lib.rs")
+                .with_language(Language::Rust)
+                .with_kind(TokenKind::Struct)
+                .build(),
+        );
+        let ruby_method = TokenUsageBuilder::new("legacy", "app/person.rb")
+            .with_language(Language::Ruby)
+            .with_kind(TokenKind::Method);
+        let ruby_callback =
+            Confidence::estimate(&ruby_method.clone().with_kind(TokenKind::Callback).build());
+        let ruby_method = Confidence::estimate(&ruby_method.build());
+
+        assert_eq!(rust_struct.kind_weight, 1.0);
+        assert_eq!(rust_struct.dynamic_dispatch_risk, 0.0);
+        assert_eq!(rust_struct.score, 95);
+        assert_eq!(ruby_method.dynamic_dispatch_risk, 0.6);
+        assert_eq!(ruby_method.score, 53);
+        assert!(ruby_callback.score < ruby_method.score);
+    }
+
+    #[test]
+    fn lowers_confidence_as_references_grow() {
+        let rust_struct = TokenUsageBuilder::new("legacy", "src/lib.rs")
+            .with_language(Language::Rust)
+            .with_kind(TokenKind::Struct);
+        let once = Confidence::estimate(&rust_struct.build());
+        let often =
+            Confidence::estimate(&rust_struct.with_occurrences(&[("src/lib.rs", 6)]).build());
+
+        assert_eq!(often.occurrences, 6);
+        assert!(often.score < once.score);
+    }
+}
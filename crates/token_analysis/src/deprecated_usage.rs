@@ -0,0 +1,105 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use token_search::{TokenSearchResult, TokenSearchResults};
+
+/// Remaining usages of a token marked as deprecated
+///
+/// This is the inverse of the unused analysis: deprecated tokens should trend towards zero
+/// usages, so tracking the count over time drives their removal.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DeprecatedUsage {
+    pub token: String,
+    pub usages: usize,
+    pub files: BTreeMap<PathBuf, usize>,
+}
+
+impl DeprecatedUsage {
+    /// Calculate usages of each deprecated token from search results, most used first
+    ///
+    /// Tokens without any occurrences are included with zero usages.
+    pub fn calculate(tokens: &BTreeSet<String>, results: &TokenSearchResults) -> Vec<Self> {
+        let mut usages: Vec<Self> = results.value().iter().map(Self::from).collect();
+        let found: BTreeSet<String> = usages.iter().map(|u| u.token.to_string()).collect();
+        usages.extend(tokens.difference(&found).map(|token| DeprecatedUsage {
+            token: token.to_string(),
+            usages: 0,
+            files: BTreeMap::new(),
+        }));
+        usages.sort_by(|a, b| b.usages.cmp(&a.usages).then(a.token.cmp(&b.token)));
+        usages
+    }
+}
+
+impl From<&TokenSearchResult> for DeprecatedUsage {
+    // Each definition contributes one occurrence which isn't a usage
+    fn from(result: &TokenSearchResult) -> Self {
+        let mut files: BTreeMap<PathBuf, usize> = result
+            .occurrences
+            .iter()
+            .map(|(path, count)| (path.to_path_buf(), *count))
+            .collect();
+
+        for definition in result.token.definitions.iter() {
+            if let Some(count) = files.get_mut(&*definition.file_path) {
+                *count = count.saturating_sub(1);
+            }
+        }
+
+        files.retain(|_, count| *count > 0);
+
+        DeprecatedUsage {
+            token: result.token.token.to_string(),
+            usages: files.values().sum(),
+            files,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use read_ctags::{CtagItem, TokenKind};
+    use std::collections::HashMap;
+    use token_search::Token;
+
+    #[test]
+    fn excludes_definitions_from_usages() {
+        let definition = CtagItem {
+            name: String::from("legacy_total"),
+            file_path: PathBuf::from("app/models/order.rb").into(),
+            raw_path: "app/models/order.rb".into(),
+            address: String::from("3"),
+            language: None,
+            tags: BTreeMap::new(),
+            kind: TokenKind::Method,
+        };
+        let result = TokenSearchResult {
+            token: Token::new(
+                String::from("legacy_total"),
+                [definition].iter().cloned().collect(),
+            ),
+            occurrences: [
+                (PathBuf::from("app/models/order.rb"), 1),
+                (PathBuf::from("app/views/orders/show.erb"), 2),
+            ]
+            .iter()
+            .cloned()
+            .collect::<HashMap<_, _>>(),
+            mentions: HashMap::new(),
+            scopes: None,
+        };
+
+        assert_eq!(
+            DeprecatedUsage::from(&result),
+            DeprecatedUsage {
+                token: String::from("legacy_total"),
+                usages: 2,
+                files: [(PathBuf::from("app/views/orders/show.erb"), 2)]
+                    .iter()
+                    .cloned()
+                    .collect(),
+            }
+        );
+    }
+}
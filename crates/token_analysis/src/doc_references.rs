@@ -0,0 +1,27 @@
+use super::references::references;
+use project_configuration::ProjectConfiguration;
+use token_search::TokenSearchResult;
+
+// Extensions of documentation files, such as READMEs and guides
+const DOC_EXTENSIONS: [&str; 6] = ["md", "markdown", "mdx", "rdoc", "rst", "adoc"];
+
+/// Are all of the token's references (its occurrences away from its definitions) in
+/// documentation files or comments, when counting them separately?
+///
+/// Otherwise, references in documentation count as usage like any other.
+pub(crate) fn only_referenced_in_docs(
+    project_configuration: &ProjectConfiguration,
+    result: &TokenSearchResult,
+) -> bool {
+    project_configuration.doc_references
+        && references(result).is_some_and(|references| {
+            references.iter().all(|reference| {
+                reference.in_comment()
+                    || reference
+                        .path
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .is_some_and(|ext| DOC_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+            })
+        })
+}
@@ -0,0 +1,18 @@
+use super::references::{references, Reference};
+use read_ctags::TokenKind;
+use token_search::TokenSearchResult;
+
+/// Are all of the token's references (its occurrences away from its definitions) string or
+/// symbol literals?
+///
+/// Literals name methods and constants resolved at runtime, such as `send(:name)`,
+/// `public_send("name")`, `method(:name)`, or `const_get("Person")`, which are weaker evidence
+/// of usage than a reference by name: the literal may be unrelated to the token, or the call it's
+/// passed to may never run. Translation keys are always referenced by literals, so they're left
+/// out.
+pub(crate) fn only_referenced_dynamically(result: &TokenSearchResult) -> bool {
+    !result
+        .token
+        .only_ctag(|definition| definition.kind == TokenKind::TranslationKey)
+        && references(result).is_some_and(|references| references.iter().all(Reference::is_literal))
+}
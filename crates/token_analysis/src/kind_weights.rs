@@ -0,0 +1,79 @@
+use super::usage_likelihood::CONVENTIONALLY_INVOKED_KINDS;
+use read_ctags::{CtagItem, TokenKind};
+use std::collections::HashSet;
+
+// Built-in points for kinds frameworks and test runners often invoke without a reference
+const CONVENTIONALLY_INVOKED_WEIGHT: i32 = -15;
+
+/// Points added to the likelihood scores of tokens defined as `kind`: its weight among `weights`
+/// (the project's `kind_weights`), or else -15 for kinds often invoked by convention (such as
+/// callbacks and tests) and 0 for others
+///
+/// A kind's weight is added within the range of the token's status, so weights order findings
+/// without moving them between statuses.
+pub fn kind_weight(weights: &[(TokenKind, i32)], kind: &TokenKind) -> i32 {
+    let configured = weights
+        .iter()
+        .find(|(weighted, _)| weighted == kind)
+        .map(|(_, weight)| *weight);
+
+    configured.unwrap_or_else(|| {
+        if CONVENTIONALLY_INVOKED_KINDS.contains(kind) {
+            CONVENTIONALLY_INVOKED_WEIGHT
+        } else {
+            0
+        }
+    })
+}
+
+// The highest weight among a token's definitions, so a token is only discounted when every
+// definition is of a discounted kind
+pub(crate) fn weigh(weights: &[(TokenKind, i32)], definitions: &HashSet<CtagItem>) -> i32 {
+    definitions
+        .iter()
+        .map(|definition| kind_weight(weights, &definition.kind))
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    fn definition(kind: TokenKind) -> CtagItem {
+        let path = Path::new("app/models/person.rb");
+
+        CtagItem {
+            name: String::from("Person"),
+            file_path: path.into(),
+            raw_path: path.to_string_lossy().into(),
+            address: format!("{:?}", kind),
+            language: None,
+            tags: BTreeMap::new(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn configured_weights_replace_built_in_ones() {
+        let weights = [(TokenKind::Constant, -5), (TokenKind::Interface, 10)];
+
+        assert_eq!(kind_weight(&weights, &TokenKind::Interface), 10);
+        assert_eq!(kind_weight(&weights, &TokenKind::Callback), -15);
+        assert_eq!(kind_weight(&weights, &TokenKind::Enum), 0);
+        assert_eq!(kind_weight(&[], &TokenKind::Constant), 0);
+        assert_eq!(
+            weigh(
+                &weights,
+                &HashSet::from([
+                    definition(TokenKind::Constant),
+                    definition(TokenKind::Callback)
+                ])
+            ),
+            -5
+        );
+        assert_eq!(weigh(&weights, &HashSet::new()), 0);
+    }
+}
@@ -1,8 +1,34 @@
 mod analysis_filter;
+mod baseline;
+mod cleanup_value;
+mod confidence;
+mod deprecated_usage;
+mod doc_references;
+mod dynamic_reference;
+mod kind_weights;
 mod occurrence_count;
+mod references;
+mod rename_hints;
+mod rule;
+mod sample;
+// Fixtures for tests consuming `TokenUsage`s, shared with other crates' tests through the
+// `test-util` feature
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 mod token_usage;
+mod usage_analyzer;
 mod usage_likelihood;
 
 pub use analysis_filter::*;
+pub use baseline::Baseline;
+pub use cleanup_value::CleanupValue;
+pub use confidence::Confidence;
+pub use deprecated_usage::DeprecatedUsage;
+pub use kind_weights::kind_weight;
+pub use occurrence_count::{Counts, FileTypeCounts};
+pub use rename_hints::{RenameHint, RenameHints};
+pub use rule::Rule;
+pub use sample::{Estimate, Sample, SampleRate};
 pub use token_usage::{TokenUsage, TokenUsageResults};
+pub use usage_analyzer::*;
 pub use usage_likelihood::*;
@@ -5,13 +5,14 @@ use std::ops::Add;
 use std::path::{Path, PathBuf};
 use token_search::TokenSearchResult;
 
-#[derive(Clone, Copy, Serialize)]
+#[derive(Clone, Copy, Serialize, Default)]
 pub struct Counts {
     pub file_count: usize,
     pub occurrence_count: usize,
 }
 
 #[derive(PartialEq)]
+#[allow(clippy::enum_variant_names)]
 pub enum FileType {
     ApplicationFile,
     TestFile,
@@ -19,15 +20,6 @@ pub enum FileType {
     UnknownFile,
 }
 
-impl Default for Counts {
-    fn default() -> Self {
-        Self {
-            file_count: 0,
-            occurrence_count: 0,
-        }
-    }
-}
-
 impl Add for Counts {
     type Output = Self;
 
@@ -48,7 +40,7 @@ impl Counts {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Default)]
 pub struct FileTypeCounts {
     pub app: Counts,
     pub config: Counts,
@@ -56,29 +48,32 @@ pub struct FileTypeCounts {
     pub unknown: Counts,
 }
 
-impl Default for FileTypeCounts {
-    fn default() -> Self {
-        Self {
-            app: Counts::default(),
-            config: Counts::default(),
-            test: Counts::default(),
-            unknown: Counts::default(),
-        }
-    }
-}
-
 impl FileTypeCounts {
     pub fn new(
         project_configuration: &ProjectConfiguration,
         token_search_result: &TokenSearchResult,
     ) -> Self {
-        let results = token_search_result.occurrences.clone();
+        Self::from_counts(project_configuration, &token_search_result.occurrences)
+    }
+
+    /// Counts of the token's references, leaving out the occurrence at each of its definitions
+    pub fn references(
+        project_configuration: &ProjectConfiguration,
+        token_search_result: &TokenSearchResult,
+    ) -> Self {
+        Self::from_counts(project_configuration, &token_search_result.references())
+    }
+
+    fn from_counts(
+        project_configuration: &ProjectConfiguration,
+        results: &HashMap<PathBuf, usize>,
+    ) -> Self {
         let mut app: HashMap<&PathBuf, usize> = HashMap::new();
         let mut config: HashMap<&PathBuf, usize> = HashMap::new();
         let mut test: HashMap<&PathBuf, usize> = HashMap::new();
         let mut unknown: HashMap<&PathBuf, usize> = HashMap::new();
 
-        for (k, v) in &results {
+        for (k, v) in results {
             if Self::is_application_file(project_configuration, k) {
                 app.insert(k, *v);
             }
@@ -105,7 +100,7 @@ impl FileTypeCounts {
     }
 
     pub fn total(&self) -> Counts {
-        vec![self.app, self.config, self.test, self.unknown]
+        [self.app, self.config, self.test, self.unknown]
             .iter()
             .fold(Counts::default(), |mut acc, o| {
                 acc = acc + *o;
@@ -121,7 +116,10 @@ impl FileTypeCounts {
         Self::file_type(project_configuration, path) == FileType::ConfigFile
     }
 
-    fn is_test_file(project_configuration: &ProjectConfiguration, path: &PathBuf) -> bool {
+    pub(crate) fn is_test_file(
+        project_configuration: &ProjectConfiguration,
+        path: &PathBuf,
+    ) -> bool {
         Self::file_type(project_configuration, path) == FileType::TestFile
     }
 
@@ -0,0 +1,146 @@
+use codebase_files::text::is_identifier_char;
+use read_ctags::{CtagItem, Language};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use token_search::{Region, Regions, TokenSearchResult};
+
+// Tokens with more references than this are treated as used without reading their files
+const MAX_REFERENCES: usize = 5;
+
+/// A word-bounded occurrence of a token away from its definitions
+pub(crate) struct Reference<'a> {
+    pub path: &'a Path,
+    pub before: String,
+    pub after: String,
+}
+
+impl Reference<'_> {
+    /// Is the reference a string or symbol literal, such as `:name` or `"name"`?
+    pub fn is_literal(&self) -> bool {
+        let symbol = self.before.ends_with(':') && !self.before.ends_with("::");
+        let quoted = ['"', '\'', '`']
+            .iter()
+            .any(|quote| self.before.ends_with(*quote) && self.after.starts_with(*quote));
+        symbol || quoted
+    }
+
+    /// Is the reference on a comment line, such as a YARD or rustdoc comment?
+    pub fn in_comment(&self) -> bool {
+        let line = self.before.trim_start();
+        line.starts_with("//")
+            || line.starts_with("/*")
+            || line.starts_with('*')
+            || (line.starts_with('#') && !["#[", "#!", "#{"].iter().any(|p| line.starts_with(p)))
+    }
+}
+
+/// The token's references, read from the files it occurs in
+///
+/// This is `None` when the token has no references, more than a few (so reading its files isn't
+/// worthwhile), or occurs in a file that can't be read.
+pub(crate) fn references(result: &TokenSearchResult) -> Option<Vec<Reference<'_>>> {
+    let definitions = &result.token.definitions;
+    let count: usize = result.references().values().sum();
+    if count == 0 || count > MAX_REFERENCES {
+        return None;
+    }
+
+    let mut references = vec![];
+    for path in result.occurrences.keys() {
+        let contents = fs::read_to_string(path).ok()?;
+        // Comments weren't counted when searching syntax-aware, so they aren't references either
+        let regions = result
+            .mentions
+            .get(path)
+            .filter(|mentions| mentions.comments > 0)
+            .map(|_| Regions::lex(Language::detect(path, &contents), &contents));
+        let definition_lines: HashSet<usize> = definitions
+            .iter()
+            .filter(|d| *d.file_path == **path)
+            .filter_map(|d: &CtagItem| d.resolve_line(&contents))
+            .collect();
+
+        for (index, line) in contents.lines().enumerate() {
+            if definition_lines.contains(&(index + 1)) {
+                continue;
+            }
+            let offset = line.as_ptr() as usize - contents.as_ptr() as usize;
+            references.extend(
+                occurrences_in(line, &result.token.token)
+                    .filter(|start| {
+                        regions
+                            .as_ref()
+                            .is_none_or(|r| r.at(offset + start) != Region::Comment)
+                    })
+                    .map(|start| Reference {
+                        path,
+                        before: line[..start].to_string(),
+                        after: line[start + result.token.token.len()..].to_string(),
+                    }),
+            );
+        }
+    }
+
+    Some(references).filter(|references| !references.is_empty())
+}
+
+// Offsets of each word-bounded occurrence of `token` on the line
+fn occurrences_in<'a>(line: &'a str, token: &'a str) -> impl Iterator<Item = usize> + 'a {
+    line.match_indices(token)
+        .map(|(start, _)| start)
+        .filter(move |start| {
+            !line[..*start].ends_with(is_identifier_char)
+                && !line[start + token.len()..].starts_with(is_identifier_char)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn references(line: &str, token: &str) -> Vec<Reference<'static>> {
+        occurrences_in(line, token)
+            .map(|start| Reference {
+                path: Path::new("app/report.rb"),
+                before: line[..start].to_string(),
+                after: line[start + token.len()..].to_string(),
+            })
+            .collect()
+    }
+
+    fn literals(line: &str, token: &str) -> Vec<bool> {
+        references(line, token)
+            .iter()
+            .map(Reference::is_literal)
+            .collect()
+    }
+
+    #[test]
+    fn recognizes_string_and_symbol_literals() {
+        assert_eq!(
+            literals("person.public_send(:full_name)", "full_name"),
+            vec![true]
+        );
+        assert_eq!(
+            literals("Object.const_get(\"Person\") || Person::NAME", "Person"),
+            vec![true, false]
+        );
+        assert_eq!(
+            literals("Billing::Person.new(full_name: 'x')", "Person"),
+            vec![false]
+        );
+        assert!(literals("full_names.map(&:upcase)", "full_name").is_empty());
+    }
+
+    #[test]
+    fn recognizes_comments() {
+        let in_comment = |line: &str| references(line, "full_name")[0].in_comment();
+
+        assert!(in_comment("  # @see #full_name"));
+        assert!(in_comment("/// Calls [`full_name`]"));
+        assert!(in_comment(" * full_name is memoized"));
+        assert!(!in_comment("puts \"#{full_name}\""));
+        assert!(!in_comment("person.full_name # memoized"));
+    }
+}
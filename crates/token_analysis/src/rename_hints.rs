@@ -0,0 +1,181 @@
+use super::token_usage::TokenUsage;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+// References a token needs before an unused token named like it is taken for a leftover rename
+const MIN_REFERENCES: usize = 3;
+
+/// A heavily used token named almost like an unused one, suggesting the unused one is a leftover
+/// from renaming it (such as `UserSerialzer` left behind by `UserSerializer`)
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RenameHint {
+    /// Name of the heavily used token
+    pub token: String,
+    /// Characters inserted, deleted, substituted, or transposed between the two names
+    pub distance: usize,
+    /// Occurrences of the heavily used token outside its definitions
+    pub references: usize,
+}
+
+/// Rename hints for unused tokens, by the unused token's name
+#[derive(Clone, Debug, Default)]
+pub struct RenameHints(HashMap<String, RenameHint>);
+
+impl RenameHints {
+    /// Pair each of `findings` with no references to the most referenced of `analyzed` named
+    /// within an edit distance of 1 (or 2, for names of 8 characters or more)
+    pub fn find(analyzed: &[TokenUsage], findings: &[&TokenUsage]) -> Self {
+        let mut by_length: BTreeMap<usize, Vec<(&str, usize)>> = BTreeMap::new();
+        for usage in analyzed {
+            let references = usage.references();
+            if references >= MIN_REFERENCES {
+                let token = usage.result.token.token.as_str();
+                by_length
+                    .entry(token.chars().count())
+                    .or_default()
+                    .push((token, references));
+            }
+        }
+
+        let hints = findings
+            .par_iter()
+            .filter(|usage| usage.references() == 0)
+            .filter_map(|usage| {
+                let token = &usage.result.token.token;
+                let length = token.chars().count();
+                let max_distance = max_distance(length)?;
+
+                by_length
+                    .range(length.saturating_sub(max_distance)..=length + max_distance)
+                    .flat_map(|(_, candidates)| candidates.iter())
+                    .filter(|(candidate, _)| candidate != token)
+                    .filter_map(|(candidate, references)| {
+                        let distance = edit_distance(token, candidate, max_distance)?;
+                        Some(RenameHint {
+                            token: candidate.to_string(),
+                            distance,
+                            references: *references,
+                        })
+                    })
+                    .min_by(|a, b| {
+                        (a.distance, b.references, &a.token).cmp(&(
+                            b.distance,
+                            a.references,
+                            &b.token,
+                        ))
+                    })
+                    .map(|hint| (token.clone(), hint))
+            })
+            .collect();
+
+        RenameHints(hints)
+    }
+
+    /// The hint for `token`, if it may be a leftover from a rename
+    pub fn get(&self, token: &str) -> Option<&RenameHint> {
+        self.0.get(token)
+    }
+}
+
+// Short names differing by a character or two are usually unrelated (`id` and `ip`)
+fn max_distance(length: usize) -> Option<usize> {
+    match length {
+        0..=3 => None,
+        4..=7 => Some(1),
+        _ => Some(2),
+    }
+}
+
+// Optimal string alignment distance between `a` and `b`, when it's at most `max`
+fn edit_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    let mut previous: Vec<usize> = vec![0; b.len() + 1];
+    let mut current: Vec<usize> = (0..=b.len()).collect();
+    let mut next = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        next[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            next[j] = (current[j] + 1)
+                .min(next[j - 1] + 1)
+                .min(current[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                next[j] = next[j].min(previous[j - 2] + 1);
+            }
+        }
+        if next.iter().all(|d| *d > max) {
+            return None;
+        }
+        previous.clone_from(&current);
+        current.clone_from(&next);
+    }
+
+    Some(current[b.len()]).filter(|d| *d <= max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TokenUsageBuilder;
+    use read_ctags::TokenKind;
+
+    #[test]
+    fn hints_at_heavily_used_tokens_named_alike() {
+        let usage = |token: &str, references| {
+            let path = format!("app/{}.rb", token.to_lowercase());
+            let mut occurrences = vec![(path.as_str(), 1)];
+            if references > 0 {
+                occurrences.push(("app/controller.rb", references));
+            }
+
+            TokenUsageBuilder::new(token, &path)
+                .with_kind(TokenKind::Class)
+                .with_occurrences(&occurrences)
+                .build()
+        };
+        let analyzed = vec![
+            usage("UserSerializer", 12),
+            usage("UserSerialzer", 0),
+            usage("UserSerailizer", 0),
+            usage("Order", 0),
+            usage("Ordre", 0),
+            usage("Orders", 5),
+            usage("Customer", 2),
+            usage("Costumer", 0),
+            usage("ids", 4),
+            usage("idx", 0),
+        ];
+        let findings: Vec<&TokenUsage> = analyzed.iter().filter(|u| u.references() == 0).collect();
+
+        let hints = RenameHints::find(&analyzed, &findings);
+
+        assert_eq!(
+            hints.get("UserSerialzer"),
+            Some(&RenameHint {
+                token: String::from("UserSerializer"),
+                distance: 1,
+                references: 12,
+            })
+        );
+        assert_eq!(hints.get("UserSerailizer").map(|h| h.distance), Some(1));
+        assert_eq!(hints.get("Order").map(|h| h.token.as_str()), Some("Orders"));
+        assert_eq!(hints.get("Ordre"), None);
+        assert_eq!(hints.get("Costumer"), None);
+        assert_eq!(hints.get("idx"), None);
+        assert_eq!(hints.get("UserSerializer"), None);
+    }
+
+    #[test]
+    fn measures_edit_distance_within_a_maximum() {
+        assert_eq!(edit_distance("kitten", "sitting", 3), Some(3));
+        assert_eq!(edit_distance("kitten", "sitting", 2), None);
+        assert_eq!(edit_distance("recieve", "receive", 1), Some(1));
+        assert_eq!(edit_distance("ä", "a", 1), Some(1));
+    }
+}
@@ -0,0 +1,200 @@
+use serde::{Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+// Where each rule is documented, under an anchor named by its ID
+const DOCUMENTATION_URL: &str = "https://github.com/unused-code/unused_rs/blob/main/docs/rules.md";
+
+/// A Rule identifies which heuristic classified a token, with an ID that stays stable across
+/// releases so findings can be referenced precisely
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rule {
+    OccurrenceZero,
+    TestOnly,
+    WideUsage,
+    OnlyUsedInTests,
+    DynamicReference,
+    OnlyReferencedInDocs,
+    UnusedInScope,
+    UnroutedAction,
+    RoutedAction,
+    ConfiguredLowLikelihood,
+    AllowedToken,
+    DeniedToken,
+    PublicApi,
+    ShadowsDependency,
+}
+
+impl Rule {
+    pub fn all() -> Vec<Rule> {
+        vec![
+            Rule::OccurrenceZero,
+            Rule::TestOnly,
+            Rule::WideUsage,
+            Rule::OnlyUsedInTests,
+            Rule::DynamicReference,
+            Rule::OnlyReferencedInDocs,
+            Rule::UnusedInScope,
+            Rule::UnroutedAction,
+            Rule::RoutedAction,
+            Rule::ConfiguredLowLikelihood,
+            Rule::AllowedToken,
+            Rule::DeniedToken,
+            Rule::PublicApi,
+            Rule::ShadowsDependency,
+        ]
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Rule::OccurrenceZero => "UN001",
+            Rule::TestOnly => "UN002",
+            Rule::WideUsage => "UN003",
+            Rule::OnlyUsedInTests => "UN004",
+            Rule::DynamicReference => "UN005",
+            Rule::OnlyReferencedInDocs => "UN006",
+            Rule::UnusedInScope => "UN007",
+            Rule::UnroutedAction => "UN008",
+            Rule::RoutedAction => "UN009",
+            Rule::ConfiguredLowLikelihood => "UN010",
+            Rule::AllowedToken => "UN011",
+            Rule::DeniedToken => "UN012",
+            Rule::PublicApi => "UN013",
+            Rule::ShadowsDependency => "UN020",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::OccurrenceZero => "occurrence-zero",
+            Rule::TestOnly => "test-only",
+            Rule::WideUsage => "wide-usage",
+            Rule::OnlyUsedInTests => "only-used-in-tests",
+            Rule::DynamicReference => "dynamic-reference",
+            Rule::OnlyReferencedInDocs => "only-referenced-in-docs",
+            Rule::UnusedInScope => "unused-in-scope",
+            Rule::UnroutedAction => "unrouted-action",
+            Rule::RoutedAction => "routed-action",
+            Rule::ConfiguredLowLikelihood => "configured-low-likelihood",
+            Rule::AllowedToken => "allowed-token",
+            Rule::DeniedToken => "denied-token",
+            Rule::PublicApi => "public-api",
+            Rule::ShadowsDependency => "shadows-dependency",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rule::OccurrenceZero => {
+                "The token occurs only once in the codebase, most likely at its definition"
+            }
+            Rule::TestOnly => "The token occurs only at its definition and once within a test",
+            Rule::WideUsage => "The token occurs in several places across the codebase",
+            Rule::OnlyUsedInTests => {
+                "The token is referenced outside the files defining it, but only from test files"
+            }
+            Rule::DynamicReference => {
+                "The token is only referenced by string or symbol literals, such as `send(:name)`"
+            }
+            Rule::OnlyReferencedInDocs => {
+                "The token is only referenced in documentation files or comments, as counted with --doc-references"
+            }
+            Rule::UnusedInScope => {
+                "The token is defined in several scopes, and no occurrence refers to some of them, as attributed with --attribute-scopes"
+            }
+            Rule::UnroutedAction => {
+                "The token is an action of a Rails controller with routes, but no route leads to it"
+            }
+            Rule::RoutedAction => {
+                "The token is a Rails controller action reached through config/routes.rb, so it's an entry point"
+            }
+            Rule::ConfiguredLowLikelihood => {
+                "The token matches an auto_low_likelihood entry in the project configuration"
+            }
+            Rule::AllowedToken => {
+                "The token is listed in allowed_tokens in the project settings, so it's never reported"
+            }
+            Rule::DeniedToken => {
+                "The token is listed in denied_tokens in the project settings, so it's always reported"
+            }
+            Rule::PublicApi => {
+                "The token is exported from the package's entry points (package.json or lib.rs), so it may be used by other packages though it's unused internally"
+            }
+            Rule::ShadowsDependency => {
+                "The token is also defined in dependency tags, so its occurrences may belong to either"
+            }
+        }
+    }
+
+    /// A link to this rule's section of the rules documentation
+    pub fn documentation_url(&self) -> String {
+        format!("{}#{}", DOCUMENTATION_URL, self.id().to_lowercase())
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.id())
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rule::all()
+            .into_iter()
+            .find(|r| r.id().eq_ignore_ascii_case(s) || r.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown rule: {}", s))
+    }
+}
+
+impl Serialize for Rule {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rule_ids_are_unique() {
+        let mut ids: Vec<_> = Rule::all().iter().map(|r| r.id()).collect();
+        ids.sort();
+        ids.dedup();
+
+        assert_eq!(ids.len(), Rule::all().len());
+    }
+
+    #[test]
+    fn parses_rules_by_id_or_name() {
+        assert_eq!(Rule::from_str("UN001"), Ok(Rule::OccurrenceZero));
+        assert_eq!(Rule::from_str("test-only"), Ok(Rule::TestOnly));
+        assert_eq!(
+            Rule::from_str("UN999"),
+            Err(String::from("Unknown rule: UN999"))
+        );
+    }
+
+    #[test]
+    fn links_each_rule_to_its_documentation() {
+        assert_eq!(
+            Rule::OnlyUsedInTests.documentation_url(),
+            "https://github.com/unused-code/unused_rs/blob/main/docs/rules.md#un004"
+        );
+
+        let documentation = include_str!("../../../docs/rules.md");
+        for rule in Rule::all() {
+            assert!(
+                documentation.contains(&format!("\n## {}\n", rule.id())),
+                "{}",
+                rule
+            );
+        }
+    }
+}
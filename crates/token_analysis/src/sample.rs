@@ -0,0 +1,171 @@
+use serde::Serialize;
+use std::str::FromStr;
+
+/// Z-score for a 95% confidence interval
+const CONFIDENCE_Z: f64 = 1.96;
+
+/// A reproducible sample of tokens, selected by hashing each token with a seed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    pub rate: SampleRate,
+    pub seed: u64,
+}
+
+/// Fraction of tokens to sample, parsed from values like `5%` or `0.05`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampleRate(f64);
+
+impl SampleRate {
+    pub fn fraction(&self) -> f64 {
+        self.0
+    }
+}
+
+impl FromStr for SampleRate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        let fraction = match value.strip_suffix('%') {
+            Some(percent) => percent.trim().parse::<f64>().map(|v| v / 100.0),
+            None => value.parse::<f64>(),
+        }
+        .map_err(|_| format!("Unable to parse sample rate: {}", s))?;
+
+        if fraction > 0.0 && fraction <= 1.0 {
+            Ok(SampleRate(fraction))
+        } else {
+            Err(format!("Sample rate must be between 0% and 100%: {}", s))
+        }
+    }
+}
+
+impl std::fmt::Display for SampleRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}%", self.0 * 100.0)
+    }
+}
+
+/// Extrapolated number of findings across the full population of tokens
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Estimate {
+    pub population: usize,
+    pub sampled: usize,
+    pub sampled_findings: usize,
+    pub estimated_findings: usize,
+    pub lower_bound: usize,
+    pub upper_bound: usize,
+}
+
+impl Sample {
+    pub fn includes(&self, value: &str) -> bool {
+        let threshold = (self.rate.fraction() * u64::MAX as f64) as u64;
+        Self::hash(self.seed, value) <= threshold
+    }
+
+    pub fn select<T, F>(&self, values: Vec<T>, key: F) -> Vec<T>
+    where
+        F: Fn(&T) -> &str,
+    {
+        values
+            .into_iter()
+            .filter(|v| self.includes(key(v)))
+            .collect()
+    }
+
+    // FNV-1a, mixed with the seed, so selection is stable across runs and platforms
+    fn hash(seed: u64, value: &str) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325 ^ seed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        for byte in value.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        hash ^ (hash >> 33)
+    }
+}
+
+impl Estimate {
+    pub fn extrapolate(population: usize, sampled: usize, sampled_findings: usize) -> Self {
+        if sampled == 0 {
+            return Estimate {
+                population,
+                sampled,
+                sampled_findings,
+                estimated_findings: 0,
+                lower_bound: 0,
+                upper_bound: population,
+            };
+        }
+
+        let n = sampled as f64;
+        let total = population as f64;
+        let proportion = sampled_findings as f64 / n;
+        let finite_population_correction = if population > 1 {
+            ((total - n) / (total - 1.0)).max(0.0)
+        } else {
+            0.0
+        };
+        let standard_error =
+            (proportion * (1.0 - proportion) / n * finite_population_correction).sqrt();
+        let margin = CONFIDENCE_Z * standard_error;
+
+        Estimate {
+            population,
+            sampled,
+            sampled_findings,
+            estimated_findings: (proportion * total).round() as usize,
+            lower_bound: ((proportion - margin).max(0.0) * total).floor() as usize,
+            upper_bound: ((proportion + margin).min(1.0) * total).ceil() as usize,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sample_rates() {
+        assert_eq!(SampleRate::from_str("5%").map(|r| r.fraction()), Ok(0.05));
+        assert_eq!(SampleRate::from_str("0.25").map(|r| r.fraction()), Ok(0.25));
+        assert!(SampleRate::from_str("0%").is_err());
+        assert!(SampleRate::from_str("150%").is_err());
+        assert!(SampleRate::from_str("lots").is_err());
+    }
+
+    #[test]
+    fn selection_is_reproducible_and_roughly_proportional() {
+        let sample = Sample {
+            rate: SampleRate::from_str("10%").unwrap(),
+            seed: 42,
+        };
+        let tokens: Vec<String> = (0..10_000).map(|i| format!("token_{}", i)).collect();
+
+        let first = sample.select(tokens.clone(), |t| t);
+        let second = sample.select(tokens, |t| t);
+
+        assert_eq!(first, second);
+        assert!(first.len() > 900 && first.len() < 1100);
+    }
+
+    #[test]
+    fn extrapolates_with_bounds() {
+        let estimate = Estimate::extrapolate(1000, 100, 20);
+
+        assert_eq!(estimate.estimated_findings, 200);
+        assert!(estimate.lower_bound < 200 && estimate.lower_bound > 100);
+        assert!(estimate.upper_bound > 200 && estimate.upper_bound < 300);
+    }
+
+    #[test]
+    fn full_sample_has_no_uncertainty() {
+        let estimate = Estimate::extrapolate(50, 50, 5);
+
+        assert_eq!(
+            (estimate.lower_bound, estimate.upper_bound),
+            (estimate.estimated_findings, estimate.estimated_findings)
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use super::TokenUsage;
+use project_configuration::{ProjectConfiguration, TokenPolicy};
+use read_ctags::{CtagItem, Language, TokenKind};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use token_search::{Token, TokenSearchResult};
+
+/// Builds the `TokenUsage` of a token defined once, as a method of no known language at line 1
+/// of its file, and occurring only there unless other occurrences are given
+#[derive(Clone, Debug)]
+pub struct TokenUsageBuilder {
+    token: String,
+    path: PathBuf,
+    address: String,
+    kind: TokenKind,
+    language: Option<Language>,
+    tags: BTreeMap<Arc<str>, Arc<str>>,
+    occurrences: Option<HashMap<PathBuf, usize>>,
+}
+
+impl TokenUsageBuilder {
+    pub fn new(token: &str, path: &str) -> Self {
+        TokenUsageBuilder {
+            token: token.to_string(),
+            path: PathBuf::from(path),
+            address: String::from("1"),
+            kind: TokenKind::Method,
+            language: None,
+            tags: BTreeMap::new(),
+            occurrences: None,
+        }
+    }
+
+    /// Define the token at this line number or search pattern
+    pub fn with_address(mut self, address: &str) -> Self {
+        self.address = address.to_string();
+        self
+    }
+
+    pub fn with_kind(mut self, kind: TokenKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    /// Add an extension field (such as `class` or `end`) to the definition
+    pub fn with_tag(mut self, key: &str, value: &str) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    /// Occurrence counts by path, replacing the single occurrence at the definition
+    pub fn with_occurrences(mut self, occurrences: &[(&str, usize)]) -> Self {
+        self.occurrences = Some(
+            occurrences
+                .iter()
+                .map(|(path, count)| (PathBuf::from(path), *count))
+                .collect(),
+        );
+        self
+    }
+
+    pub fn definition(&self) -> CtagItem {
+        CtagItem {
+            name: self.token.clone(),
+            file_path: self.path.as_path().into(),
+            raw_path: self.path.to_string_lossy().into(),
+            address: self.address.clone(),
+            language: self.language,
+            tags: self.tags.clone(),
+            kind: self.kind.clone(),
+        }
+    }
+
+    pub fn result(&self) -> TokenSearchResult {
+        TokenSearchResult {
+            token: Token::new(
+                self.token.clone(),
+                [self.definition()].iter().cloned().collect(),
+            ),
+            occurrences: self
+                .occurrences
+                .clone()
+                .unwrap_or_else(|| HashMap::from([(self.path.clone(), 1)])),
+            mentions: HashMap::new(),
+            scopes: None,
+        }
+    }
+
+    /// Analyze the token with the default project configuration and token policy, and no analyzers
+    pub fn build(&self) -> TokenUsage {
+        TokenUsage::new(
+            &ProjectConfiguration::default(),
+            self.result(),
+            &HashSet::new(),
+            &TokenPolicy::default(),
+            &[],
+        )
+    }
+}
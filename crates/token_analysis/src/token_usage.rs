@@ -1,12 +1,21 @@
 use super::analysis_filter::{AnalysisFilter, OrderField, SortOrder};
+use super::cleanup_value::CleanupValue;
+use super::confidence::Confidence;
 use super::occurrence_count::FileTypeCounts;
+use super::rule::Rule;
+use super::usage_analyzer::{apply_analyzers, AnalysisContext, UsageAnalyzer};
 use super::usage_likelihood::UsageLikelihood;
+use codebase_files::CodebaseFiles;
 use indicatif::ParallelProgressIterator;
 use itertools::{rev, Itertools};
-use project_configuration::ProjectConfiguration;
+use project_configuration::{ProjectConfiguration, TokenPolicy};
 use rayon::prelude::*;
+use read_ctags::CtagItem;
 use serde::Serialize;
-use token_search::{TokenSearchConfig, TokenSearchResult, TokenSearchResults};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::path::Path;
+use token_search::{Token, TokenSearchConfig, TokenSearchResult, TokenSearchResults};
 
 #[derive(Serialize)]
 pub struct TokenUsage {
@@ -19,12 +28,25 @@ impl TokenUsage {
     pub fn new(
         project_configuration: &ProjectConfiguration,
         token_search_result: TokenSearchResult,
+        dependency_tokens: &HashSet<String>,
+        token_policy: &TokenPolicy,
+        analyzers: &[Box<dyn UsageAnalyzer>],
     ) -> Self {
         let file_type_counts = FileTypeCounts::new(project_configuration, &token_search_result);
-        let usage_likelihood = UsageLikelihood::calculate(
-            project_configuration,
-            &token_search_result,
-            &file_type_counts,
+        let usage_likelihood = apply_analyzers(
+            UsageLikelihood::calculate(
+                project_configuration,
+                &token_search_result,
+                &FileTypeCounts::references(project_configuration, &token_search_result),
+                dependency_tokens.contains(&token_search_result.token.token),
+                token_policy,
+            ),
+            &AnalysisContext {
+                result: &token_search_result,
+                file_type_counts: &file_type_counts,
+                project_configuration,
+            },
+            analyzers,
         );
         TokenUsage {
             file_type_counts,
@@ -32,51 +54,140 @@ impl TokenUsage {
             result: token_search_result,
         }
     }
+
+    /// Occurrences outside the token's definitions
+    pub fn references(&self) -> usize {
+        self.result.references().values().sum()
+    }
+
+    /// Is the token allowed in the project settings, and therefore never reported?
+    pub fn allowed(&self) -> bool {
+        self.usage_likelihood.rule == Rule::AllowedToken
+    }
 }
 
 #[derive(Serialize)]
 pub struct TokenUsageResults(Vec<TokenUsage>);
 
 impl TokenUsageResults {
+    /// Classify each search result, leaving out those not yet classified if the search's
+    /// `cancellation` is cancelled
+    ///
+    /// Each likelihood is adjusted by `analyzers` after the built-in rules.
     pub fn calculate(
         token_search_config: &TokenSearchConfig,
         results: TokenSearchResults,
         config: &ProjectConfiguration,
+        dependency_tokens: &HashSet<String>,
+        token_policy: &TokenPolicy,
+        analyzers: &[Box<dyn UsageAnalyzer>],
     ) -> Self {
         let unwrapped_results = results.value().to_vec();
         let size = &unwrapped_results.len();
 
+        let cancellation = &token_search_config.cancellation;
+
         let results = unwrapped_results
             .into_par_iter()
             .progress_with(token_search_config.toggleable_progress_bar("🧐 Analyzing...", *size))
-            .map(move |r| TokenUsage::new(config, r))
+            .filter(|_| !cancellation.skip())
+            .map(move |r| TokenUsage::new(config, r, dependency_tokens, token_policy, analyzers))
             .collect::<Vec<_>>();
         TokenUsageResults(results)
     }
 
-    pub fn filter(&self, config: &AnalysisFilter) -> Vec<&TokenUsage> {
-        let final_result = (*self.0)
-            .into_iter()
-            .filter(|a| {
-                config
-                    .usage_likelihood_filter
-                    .contains(&a.usage_likelihood.status)
+    /// Search the files under `root` for each token in `tags`, with the default configuration
+    ///
+    /// Paths in `tags` are relative to `root`, as when ctags is run there; paths in the results
+    /// are joined to `root`. Files are found the way the `unused` command finds them, honoring
+    /// `.gitignore`. This is how programs embedding unused analyze a project other than the
+    /// current directory.
+    pub fn for_root(tags: &HashSet<CtagItem>, root: &Path) -> Self {
+        let definitions = tags
+            .iter()
+            .map(|tag| CtagItem {
+                file_path: tag.absolute_path(root).into(),
+                ..tag.clone()
             })
-            .filter(|a| config.ignores_path(&a.result))
-            .sorted_by_key(|a| match config.sort_order {
-                SortOrder::Ascending(OrderField::Token) => a.result.token.token.to_string(),
-                SortOrder::Descending(OrderField::Token) => a.result.token.token.to_string(),
-                SortOrder::Ascending(OrderField::File) => {
-                    a.result.token.first_path().to_string_lossy().into_owned()
-                }
-                SortOrder::Descending(OrderField::File) => {
-                    a.result.token.first_path().to_string_lossy().into_owned()
-                }
-            });
+            .collect();
+        let files = CodebaseFiles::from_root(root)
+            .paths
+            .into_iter()
+            .map(|path| root.join(path))
+            .collect();
+        let config = TokenSearchConfig {
+            tokens: Token::from_tags(definitions),
+            display_progress: false,
+            ..TokenSearchConfig::for_files(files)
+        };
+        let results = TokenSearchResults::generate_with_config(&config);
+
+        Self::calculate(
+            &config,
+            results,
+            &ProjectConfiguration::default(),
+            &HashSet::new(),
+            &TokenPolicy::default(),
+            &[],
+        )
+    }
+
+    pub fn value(&self) -> &[TokenUsage] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn find(&self, token: &str) -> Option<&TokenUsage> {
+        self.0.iter().find(|u| u.result.token.token == token)
+    }
+
+    pub fn filter(&self, config: &AnalysisFilter) -> Vec<&TokenUsage> {
+        let filtered = (*self.0)
+            .iter()
+            .filter(|a| config.includes_usage(a))
+            .filter(|a| config.includes(&a.result) && !a.allowed())
+            // Sorts below are stable, so ties keep this order rather than the tokens' arbitrary
+            // order, which differs between runs
+            .sorted_by(|a, b| by_location(a, b));
+
+        let final_result: Vec<&TokenUsage> = match config.sort_order.field() {
+            OrderField::Token => filtered
+                .sorted_by_key(|a| a.result.token.token.to_string())
+                .collect(),
+            OrderField::File => filtered
+                .sorted_by_key(|a| a.result.token.first_path().to_string_lossy().into_owned())
+                .collect(),
+            // Values are estimated once per token, since estimating may read source files
+            OrderField::Value => filtered
+                .map(|a| (CleanupValue::estimate(a).value, a))
+                .sorted_by(|(x, _), (y, _)| x.total_cmp(y))
+                .map(|(_, a)| a)
+                .collect(),
+            OrderField::Confidence => filtered
+                .sorted_by_key(|a| (Confidence::estimate(a).score, a.usage_likelihood.score))
+                .collect(),
+        };
 
         match config.sort_order {
-            SortOrder::Ascending(_) => final_result.collect(),
+            SortOrder::Ascending(_) => final_result,
             SortOrder::Descending(_) => rev(final_result).collect(),
         }
     }
 }
+
+// Where each token is first defined (by path, then line), then its name
+fn by_location(a: &TokenUsage, b: &TokenUsage) -> Ordering {
+    let (a, b) = (&a.result.token, &b.result.token);
+    match (a.first_definition(), b.first_definition()) {
+        (Some(x), Some(y)) => x.cmp_location(y),
+        (x, y) => x.is_some().cmp(&y.is_some()),
+    }
+    .then_with(|| a.token.cmp(&b.token))
+}
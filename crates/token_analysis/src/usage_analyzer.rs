@@ -0,0 +1,166 @@
+use super::occurrence_count::FileTypeCounts;
+use super::rule::Rule;
+use super::usage_likelihood::{UsageLikelihood, UsageLikelihoodStatus};
+use project_configuration::ProjectConfiguration;
+use token_search::TokenSearchResult;
+
+/// What a `UsageAnalyzer` knows about a token: where it's defined and occurs, its occurrences by
+/// file type, and the project's configuration
+pub struct AnalysisContext<'a> {
+    pub result: &'a TokenSearchResult,
+    pub file_type_counts: &'a FileTypeCounts,
+    pub project_configuration: &'a ProjectConfiguration,
+}
+
+/// A change a `UsageAnalyzer` makes to a token's usage likelihood
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Adjustment {
+    /// Status replacing the one the built-in rules chose, if any
+    pub status: Option<UsageLikelihoodStatus>,
+    /// Points added to the score (or removed, when negative), kept within the status's range
+    pub score: i32,
+    /// Why the likelihood changed, added to the finding's reason
+    pub evidence: String,
+}
+
+/// A UsageAnalyzer encodes conventions the built-in rules can't see, such as classes resolved by
+/// a dependency injection container or methods called from generated code
+///
+/// Analyzers run in the order they're given, after the built-in rules; tokens allowed or
+/// denied in the project settings aren't adjusted.
+pub trait UsageAnalyzer: Send + Sync {
+    /// Short name identifying the analyzer in adjusted findings
+    fn name(&self) -> &str;
+
+    /// Adjust how likely `context`'s token is unused, or `None` to leave it unchanged
+    fn analyze(
+        &self,
+        context: &AnalysisContext,
+        likelihood: &UsageLikelihood,
+    ) -> Option<Adjustment>;
+}
+
+/// Apply each analyzer's adjustment to `likelihood`, in order
+pub fn apply_analyzers(
+    mut likelihood: UsageLikelihood,
+    context: &AnalysisContext,
+    analyzers: &[Box<dyn UsageAnalyzer>],
+) -> UsageLikelihood {
+    if matches!(likelihood.rule, Rule::AllowedToken | Rule::DeniedToken) {
+        return likelihood;
+    }
+
+    for analyzer in analyzers {
+        if let Some(adjustment) = analyzer.analyze(context, &likelihood) {
+            if let Some(status) = adjustment.status {
+                likelihood.status = status;
+            }
+            likelihood.score = likelihood
+                .status
+                .clamp_score(i32::from(likelihood.score) + adjustment.score);
+            likelihood.reason = format!(
+                "{}; {} [{}]",
+                likelihood.reason,
+                adjustment.evidence,
+                analyzer.name()
+            );
+        }
+    }
+
+    likelihood
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use read_ctags::{CtagItem, Language, TokenKind};
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::PathBuf;
+    use token_search::Token;
+
+    struct Injected;
+
+    impl UsageAnalyzer for Injected {
+        fn name(&self) -> &str {
+            "container"
+        }
+
+        fn analyze(&self, context: &AnalysisContext, _: &UsageLikelihood) -> Option<Adjustment> {
+            context
+                .result
+                .token
+                .token
+                .ends_with("Service")
+                .then(|| Adjustment {
+                    status: Some(UsageLikelihoodStatus::Low),
+                    score: 0,
+                    evidence: String::from("Resolved by the service container"),
+                })
+        }
+    }
+
+    fn result(token: &str) -> TokenSearchResult {
+        let path = PathBuf::from("app/services/billing_service.rb");
+        let definition = CtagItem {
+            name: token.to_string(),
+            file_path: path.clone().into(),
+            raw_path: path.to_string_lossy().into(),
+            address: String::from("1"),
+            language: Some(Language::Ruby),
+            tags: BTreeMap::new(),
+            kind: TokenKind::Class,
+        };
+
+        TokenSearchResult {
+            token: Token::new(token.to_string(), [definition].iter().cloned().collect()),
+            occurrences: [(path, 1)].iter().cloned().collect::<HashMap<_, _>>(),
+            mentions: HashMap::new(),
+            scopes: None,
+        }
+    }
+
+    fn likelihood(rule: Rule) -> UsageLikelihood {
+        UsageLikelihood {
+            status: UsageLikelihoodStatus::High,
+            score: 95,
+            reason: String::from("Only one occurrence exists"),
+            rule,
+        }
+    }
+
+    #[test]
+    fn applies_adjustments() {
+        let project_configuration = ProjectConfiguration::default();
+        let analyzers: Vec<Box<dyn UsageAnalyzer>> = vec![Box::new(Injected)];
+        let analyze = |token: &str, rule: Rule| {
+            let result = result(token);
+            let file_type_counts = FileTypeCounts::new(&project_configuration, &result);
+            let context = AnalysisContext {
+                result: &result,
+                file_type_counts: &file_type_counts,
+                project_configuration: &project_configuration,
+            };
+            apply_analyzers(likelihood(rule), &context, &analyzers)
+        };
+
+        assert_eq!(
+            analyze("BillingService", Rule::OccurrenceZero),
+            UsageLikelihood {
+                status: UsageLikelihoodStatus::Low,
+                score: 39,
+                reason: String::from(
+                    "Only one occurrence exists; Resolved by the service container [container]"
+                ),
+                rule: Rule::OccurrenceZero,
+            }
+        );
+        assert_eq!(
+            analyze("Billing", Rule::OccurrenceZero),
+            likelihood(Rule::OccurrenceZero)
+        );
+        assert_eq!(
+            analyze("BillingService", Rule::DeniedToken),
+            likelihood(Rule::DeniedToken)
+        );
+    }
+}
@@ -1,30 +1,47 @@
+use super::doc_references::only_referenced_in_docs;
+use super::dynamic_reference::only_referenced_dynamically;
+use super::kind_weights::weigh;
 use super::occurrence_count::FileTypeCounts;
-use project_configuration::ProjectConfiguration;
+use super::rule::Rule;
+use project_configuration::{ProjectConfiguration, Routing, TokenDecision, TokenPolicy};
+use read_ctags::TokenKind;
 use serde::Serialize;
 use std::default::Default;
 use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
 use std::str::FromStr;
 use token_search::TokenSearchResult;
 
+// Kinds often called by a framework or test runner rather than referenced by name
+pub(crate) const CONVENTIONALLY_INVOKED_KINDS: [TokenKind; 9] = [
+    TokenKind::Callback,
+    TokenKind::Implementation,
+    TokenKind::Test,
+    TokenKind::RSpecDescribe,
+    TokenKind::Getter,
+    TokenKind::Setter,
+    TokenKind::Field,
+    TokenKind::Property,
+    TokenKind::Member,
+];
+
 #[derive(Debug, PartialEq, Serialize)]
 pub struct UsageLikelihood {
     pub status: UsageLikelihoodStatus,
+    /// How likely the token is unused, from 0 to 100, within the range of its status
+    pub score: u8,
     pub reason: String,
+    pub rule: Rule,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Default)]
 pub enum UsageLikelihoodStatus {
+    #[default]
     High,
     Medium,
     Low,
 }
 
-impl Default for UsageLikelihoodStatus {
-    fn default() -> Self {
-        UsageLikelihoodStatus::High
-    }
-}
-
 impl FromStr for UsageLikelihoodStatus {
     type Err = String;
 
@@ -33,10 +50,7 @@ impl FromStr for UsageLikelihoodStatus {
             "high" => Ok(UsageLikelihoodStatus::High),
             "medium" => Ok(UsageLikelihoodStatus::Medium),
             "low" => Ok(UsageLikelihoodStatus::Low),
-            val => Err(String::from(format!(
-                "Unable to parse usage likelihood: {}",
-                val
-            ))),
+            val => Err(format!("Unable to parse usage likelihood: {}", val)),
         }
     }
 }
@@ -59,66 +73,239 @@ impl UsageLikelihoodStatus {
             UsageLikelihoodStatus::Low,
         ]
     }
+
+    /// Statuses at least as likely to be unused as `minimum`
+    pub fn at_least(minimum: &UsageLikelihoodStatus) -> Vec<UsageLikelihoodStatus> {
+        Self::all()
+            .into_iter()
+            .filter(|status| status.score_range().start() >= minimum.score_range().start())
+            .collect()
+    }
+
+    /// Scores given to tokens with this status
+    pub fn score_range(&self) -> RangeInclusive<u8> {
+        match self {
+            UsageLikelihoodStatus::High => 70..=100,
+            UsageLikelihoodStatus::Medium => 40..=69,
+            UsageLikelihoodStatus::Low => 0..=39,
+        }
+    }
+
+    pub(crate) fn clamp_score(&self, score: i32) -> u8 {
+        let range = self.score_range();
+
+        score.clamp(*range.start() as i32, *range.end() as i32) as u8
+    }
 }
 
 impl UsageLikelihood {
     pub fn calculate(
         project_configuration: &ProjectConfiguration,
         token_search_result: &TokenSearchResult,
-        all_counts: &FileTypeCounts,
+        references: &FileTypeCounts,
+        shadows_dependency: bool,
+        token_policy: &TokenPolicy,
     ) -> Self {
-        match project_configuration.low_likelihood_match(token_search_result) {
-            Some(low_likelihood_config) => UsageLikelihood {
-                status: UsageLikelihoodStatus::Low,
-                reason: format!(
-                    "Token is classified as low-likelihood: {}",
-                    low_likelihood_config.name
+        let public_api = project_configuration
+            .exports
+            .entry_point(token_search_result)
+            .filter(|_| {
+                references.total().occurrence_count == 0
+                    || only_used_in_tests(project_configuration, token_search_result)
+            })
+            .map(|entry_point| {
+                format!(
+                    "Public API, unused internally: exported from {}",
+                    entry_point.display()
+                )
+            });
+        let (status, reason, rule) = match token_policy.decide(&token_search_result.token.token) {
+            Some(TokenDecision::Allowed(reason)) => (
+                UsageLikelihoodStatus::Low,
+                with_reason("Token is allowed", reason),
+                Rule::AllowedToken,
+            ),
+            Some(TokenDecision::Denied(reason)) => (
+                UsageLikelihoodStatus::High,
+                with_reason("Token is denied", reason),
+                Rule::DeniedToken,
+            ),
+            None => match project_configuration.routes.routing(token_search_result) {
+                Some(Routing::Routed(action)) => (
+                    UsageLikelihoodStatus::Low,
+                    format!("Routed to {}", action),
+                    Rule::RoutedAction,
                 ),
-            },
-            None => {
-                if all_counts.total().occurrence_count == 1 {
-                    UsageLikelihood {
-                        status: UsageLikelihoodStatus::High,
-                        reason: String::from("Only one occurrence exists"),
-                    }
-                } else {
-                    if all_counts.total().occurrence_count == 2
-                        && all_counts.test.occurrence_count == 1
+                Some(Routing::Unrouted(actions)) => (
+                    UsageLikelihoodStatus::High,
+                    format!("Controller action not routed: {}", actions.join(", ")),
+                    Rule::UnroutedAction,
+                ),
+                None => match project_configuration.low_likelihood_match(token_search_result) {
+                    Some(low_likelihood_config) => (
+                        UsageLikelihoodStatus::Low,
+                        format!(
+                            "Token is classified as low-likelihood: {}",
+                            low_likelihood_config.name
+                        ),
+                        Rule::ConfiguredLowLikelihood,
+                    ),
+                    None if public_api.is_some() => (
+                        UsageLikelihoodStatus::Medium,
+                        public_api.unwrap_or_default(),
+                        Rule::PublicApi,
+                    ),
+                    None if shadows_dependency => (
+                        UsageLikelihoodStatus::Medium,
+                        String::from("Shadows a definition in dependency tags"),
+                        Rule::ShadowsDependency,
+                    ),
+                    None if references.total().occurrence_count == 0 => (
+                        UsageLikelihoodStatus::High,
+                        String::from("Only one occurrence exists"),
+                        Rule::OccurrenceZero,
+                    ),
+                    None if references.total().occurrence_count == 1
+                        && references.test.occurrence_count == 1 =>
                     {
-                        UsageLikelihood {
-                            status: UsageLikelihoodStatus::Medium,
-                            reason: String::from("Only a test and definition exists"),
-                        }
-                    } else {
-                        UsageLikelihood {
-                            status: UsageLikelihoodStatus::Low,
-                            reason: String::from("Token has wide usage"),
-                        }
+                        (
+                            UsageLikelihoodStatus::Medium,
+                            String::from("Only a test and definition exists"),
+                            Rule::TestOnly,
+                        )
                     }
-                }
-            }
+                    None if only_used_in_tests(project_configuration, token_search_result) => (
+                        UsageLikelihoodStatus::Medium,
+                        String::from("Only used in tests"),
+                        Rule::OnlyUsedInTests,
+                    ),
+                    None if only_referenced_in_docs(project_configuration, token_search_result) => {
+                        (
+                            UsageLikelihoodStatus::Medium,
+                            String::from("Only referenced in docs"),
+                            Rule::OnlyReferencedInDocs,
+                        )
+                    }
+                    None if only_referenced_dynamically(token_search_result) => (
+                        UsageLikelihoodStatus::Medium,
+                        String::from("Only referenced by string or symbol literals"),
+                        Rule::DynamicReference,
+                    ),
+                    None => match token_search_result.scopes.as_ref() {
+                        Some(scopes) if !scopes.unused_scopes().is_empty() => (
+                            UsageLikelihoodStatus::Medium,
+                            format!(
+                                "Unused in {}; only used in {}",
+                                scopes.unused_scopes().join(", "),
+                                scopes.used_scopes().join(", ")
+                            ),
+                            Rule::UnusedInScope,
+                        ),
+                        Some(scopes) if scopes.ambiguous > 0 => (
+                            UsageLikelihoodStatus::Low,
+                            format!(
+                                "Token has wide usage ({} occurrences not attributed to a scope)",
+                                scopes.ambiguous
+                            ),
+                            Rule::WideUsage,
+                        ),
+                        _ => (
+                            UsageLikelihoodStatus::Low,
+                            String::from("Token has wide usage"),
+                            Rule::WideUsage,
+                        ),
+                    },
+                },
+            },
+        };
+
+        UsageLikelihood {
+            score: status.clamp_score(score(
+                rule,
+                project_configuration,
+                token_search_result,
+                references,
+            )),
+            status,
+            reason,
+            rule,
         }
     }
 }
 
+// Points out of 100, before clamping to the status's band: each rule starts from a base score,
+// lowered as references grow, plus the weight of the token's kind (see `kind_weight`)
+fn score(
+    rule: Rule,
+    project_configuration: &ProjectConfiguration,
+    token_search_result: &TokenSearchResult,
+    references: &FileTypeCounts,
+) -> i32 {
+    let references = references.total().occurrence_count as i32;
+    let base = match rule {
+        Rule::DeniedToken => 100,
+        Rule::OccurrenceZero => 95,
+        Rule::TestOnly => 60,
+        Rule::OnlyUsedInTests => 50,
+        Rule::DynamicReference => 45,
+        Rule::OnlyReferencedInDocs => 50,
+        Rule::ShadowsDependency => 55,
+        Rule::PublicApi => 45,
+        Rule::UnusedInScope => 55,
+        Rule::UnroutedAction => 85,
+        Rule::RoutedAction => 10,
+        Rule::WideUsage => 35 - 3 * (references - 1).clamp(0, 10),
+        Rule::ConfiguredLowLikelihood => 20,
+        Rule::AllowedToken => 0,
+    };
+
+    base + weigh(
+        &project_configuration.kind_weights,
+        &token_search_result.token.definitions,
+    )
+}
+
+// Tests often keep otherwise dead code alive, so references from tests alone don't count as usage
+fn only_used_in_tests(
+    project_configuration: &ProjectConfiguration,
+    token_search_result: &TokenSearchResult,
+) -> bool {
+    let references = token_search_result.occurred_paths();
+
+    !references.is_empty()
+        && references
+            .iter()
+            .all(|path| FileTypeCounts::is_test_file(project_configuration, path))
+}
+
+fn with_reason(description: &str, reason: Option<&str>) -> String {
+    match reason {
+        Some(reason) => format!("{}: {}", description, reason),
+        None => description.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use project_configuration::RailsRoutes;
     use read_ctags::{CtagItem, Language, TokenKind};
     use std::collections::{BTreeMap, HashMap};
-    use std::path::PathBuf;
-    use token_search::Token;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use token_search::{ScopeAttribution, Token};
 
-    fn build_ruby_file(token: &str, path: &PathBuf, kind: TokenKind) -> Token {
+    fn build_ruby_file(token: &str, path: &Path, kind: TokenKind) -> Token {
         Token::new(
             token.to_string(),
-            vec![CtagItem {
+            [CtagItem {
                 name: token.to_string(),
-                file_path: path.to_path_buf(),
+                file_path: path.into(),
+                raw_path: path.to_string_lossy().into(),
                 address: String::from("1"),
                 language: Some(Language::Ruby),
                 tags: BTreeMap::new(),
-                kind: kind,
+                kind,
             }]
             .iter()
             .cloned()
@@ -132,22 +319,416 @@ mod tests {
         let token = build_ruby_file("Person", &path, TokenKind::Class);
         let mut occurrences = HashMap::new();
         occurrences.insert(path, 1);
-        let result = TokenSearchResult { token, occurrences };
-        let file_type_counts = FileTypeCounts::new(&ProjectConfiguration::default(), &result);
+        let result = TokenSearchResult {
+            token,
+            occurrences,
+            mentions: HashMap::new(),
+            scopes: None,
+        };
+        let file_type_counts =
+            FileTypeCounts::references(&ProjectConfiguration::default(), &result);
+
+        assert_eq!(
+            UsageLikelihood::calculate(
+                &ProjectConfiguration::default(),
+                &result,
+                &file_type_counts,
+                false,
+                &TokenPolicy::default()
+            ),
+            UsageLikelihood {
+                status: UsageLikelihoodStatus::High,
+                score: 95,
+                reason: String::from("Only one occurrence exists"),
+                rule: Rule::OccurrenceZero,
+            }
+        );
+    }
+
+    #[test]
+    fn controller_actions_are_classified_by_routes() {
+        let project_configuration = ProjectConfiguration {
+            routes: RailsRoutes::parse("resources :photos, only: :index\n"),
+            ..ProjectConfiguration::default()
+        };
+        let likelihood = |action: &str| {
+            let path = PathBuf::from("app/controllers/photos_controller.rb");
+            let result = TokenSearchResult {
+                token: build_ruby_file(action, &path, TokenKind::Method),
+                occurrences: HashMap::from([(path, 1)]),
+                mentions: HashMap::new(),
+                scopes: None,
+            };
+            UsageLikelihood::calculate(
+                &project_configuration,
+                &result,
+                &FileTypeCounts::references(&project_configuration, &result),
+                false,
+                &TokenPolicy::default(),
+            )
+        };
+
+        let routed = likelihood("index");
+        assert_eq!(routed.rule, Rule::RoutedAction);
+        assert_eq!(routed.status, UsageLikelihoodStatus::Low);
+        assert_eq!(routed.reason, "Routed to photos#index");
+
+        let unrouted = likelihood("archive");
+        assert_eq!(unrouted.rule, Rule::UnroutedAction);
+        assert_eq!(unrouted.status, UsageLikelihoodStatus::High);
+        assert_eq!(
+            unrouted.reason,
+            "Controller action not routed: photos#archive"
+        );
+    }
+
+    #[test]
+    fn definition_sites_are_not_counted_as_usages() {
+        let person = PathBuf::from("app/models/person.rb");
+        let admin = PathBuf::from("app/models/admin.rb");
+        let definition = |path: &Path, scope: Option<&str>| CtagItem {
+            name: String::from("full_name"),
+            file_path: path.into(),
+            raw_path: path.to_string_lossy().into(),
+            address: String::from("4"),
+            language: Some(Language::Ruby),
+            tags: scope
+                .map(|scope| (Arc::from("class"), Arc::from(scope)))
+                .into_iter()
+                .collect(),
+            kind: TokenKind::Method,
+        };
+        let token = Token::new(
+            String::from("full_name"),
+            [
+                definition(&person, None),
+                definition(&person, Some("Person")),
+                definition(&admin, None),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        let mut result = TokenSearchResult {
+            token,
+            occurrences: HashMap::from([(person.clone(), 1), (admin, 1)]),
+            mentions: HashMap::new(),
+            scopes: None,
+        };
+        let likelihood = |result: &TokenSearchResult| {
+            UsageLikelihood::calculate(
+                &ProjectConfiguration::default(),
+                result,
+                &FileTypeCounts::references(&ProjectConfiguration::default(), result),
+                false,
+                &TokenPolicy::default(),
+            )
+        };
+
+        assert_eq!(likelihood(&result).rule, Rule::OccurrenceZero);
+
+        result.occurrences.insert(person, 2);
+
+        assert_eq!(likelihood(&result).rule, Rule::WideUsage);
+    }
+
+    #[test]
+    fn references_only_from_tests_are_reported_separately() {
+        let path = PathBuf::from("app/models/person.rb");
+        let token = build_ruby_file("full_name", &path, TokenKind::Method);
+        let mut occurrences = HashMap::new();
+        occurrences.insert(path, 1);
+        occurrences.insert(PathBuf::from("test/models/person_test.rb"), 3);
+        occurrences.insert(PathBuf::from("test/system/people_test.rb"), 1);
+        let mut result = TokenSearchResult {
+            token,
+            occurrences,
+            mentions: HashMap::new(),
+            scopes: None,
+        };
+        let likelihood = |result: &TokenSearchResult| {
+            UsageLikelihood::calculate(
+                &ProjectConfiguration::default(),
+                result,
+                &FileTypeCounts::references(&ProjectConfiguration::default(), result),
+                false,
+                &TokenPolicy::default(),
+            )
+        };
+
+        assert_eq!(
+            likelihood(&result),
+            UsageLikelihood {
+                status: UsageLikelihoodStatus::Medium,
+                score: 50,
+                reason: String::from("Only used in tests"),
+                rule: Rule::OnlyUsedInTests,
+            }
+        );
+
+        result
+            .occurrences
+            .insert(PathBuf::from("app/views/people/show.rb"), 1);
+        assert_eq!(likelihood(&result).rule, Rule::WideUsage);
+    }
+
+    #[test]
+    fn references_only_by_literals_are_medium_likelihood() {
+        let dir = std::env::temp_dir().join("unused dynamic references");
+        std::fs::create_dir_all(&dir).unwrap();
+        let definition = dir.join("person.rb");
+        let caller = dir.join("report.rb");
+        std::fs::write(&definition, "def full_name\nend\n").unwrap();
+        std::fs::write(&caller, "person.public_send(:full_name)\n").unwrap();
+        let token = build_ruby_file("full_name", &definition, TokenKind::Method);
+        let result = TokenSearchResult {
+            token,
+            occurrences: HashMap::from([(definition, 1), (caller.clone(), 1)]),
+            mentions: HashMap::new(),
+            scopes: None,
+        };
+        let likelihood = |result: &TokenSearchResult| {
+            UsageLikelihood::calculate(
+                &ProjectConfiguration::default(),
+                result,
+                &FileTypeCounts::references(&ProjectConfiguration::default(), result),
+                false,
+                &TokenPolicy::default(),
+            )
+        };
+
+        assert_eq!(
+            likelihood(&result),
+            UsageLikelihood {
+                status: UsageLikelihoodStatus::Medium,
+                score: 45,
+                reason: String::from("Only referenced by string or symbol literals"),
+                rule: Rule::DynamicReference,
+            }
+        );
+
+        std::fs::write(&caller, "person.full_name\n").unwrap();
+        assert_eq!(likelihood(&result).rule, Rule::WideUsage);
+    }
+
+    #[test]
+    fn references_only_in_docs_are_medium_likelihood() {
+        let dir = std::env::temp_dir().join("unused doc references");
+        std::fs::create_dir_all(&dir).unwrap();
+        let definition = dir.join("person.rb");
+        let readme = dir.join("README.md");
+        std::fs::write(
+            &definition,
+            "# Call full_name for display\ndef full_name\nend\n",
+        )
+        .unwrap();
+        std::fs::write(&readme, "Use `Person#full_name`.\n").unwrap();
+        let token = build_ruby_file("full_name", &definition, TokenKind::Method);
+        let result = TokenSearchResult {
+            token: Token::new(
+                token.token,
+                token
+                    .definitions
+                    .into_iter()
+                    .map(|d| CtagItem {
+                        address: String::from("2"),
+                        ..d
+                    })
+                    .collect(),
+            ),
+            occurrences: HashMap::from([(definition, 2), (readme, 1)]),
+            mentions: HashMap::new(),
+            scopes: None,
+        };
+        let project_configuration = ProjectConfiguration {
+            doc_references: true,
+            ..ProjectConfiguration::default()
+        };
+
+        assert_eq!(
+            UsageLikelihood::calculate(
+                &project_configuration,
+                &result,
+                &FileTypeCounts::references(&project_configuration, &result),
+                false,
+                &TokenPolicy::default(),
+            ),
+            UsageLikelihood {
+                status: UsageLikelihoodStatus::Medium,
+                score: 50,
+                reason: String::from("Only referenced in docs"),
+                rule: Rule::OnlyReferencedInDocs,
+            }
+        );
+    }
+
+    #[test]
+    fn shadowing_a_dependency_is_medium_likelihood() {
+        let path = PathBuf::from("lib/core_ext/string.rb");
+        let token = build_ruby_file("titleize", &path, TokenKind::Method);
+        let mut occurrences = HashMap::new();
+        occurrences.insert(path, 1);
+        let result = TokenSearchResult {
+            token,
+            occurrences,
+            mentions: HashMap::new(),
+            scopes: None,
+        };
+        let file_type_counts =
+            FileTypeCounts::references(&ProjectConfiguration::default(), &result);
 
         assert_eq!(
             UsageLikelihood::calculate(
                 &ProjectConfiguration::default(),
                 &result,
-                &file_type_counts
+                &file_type_counts,
+                true,
+                &TokenPolicy::default()
+            )
+            .rule,
+            Rule::ShadowsDependency
+        );
+    }
+
+    #[test]
+    fn scopes_no_occurrence_refers_to_are_medium_likelihood() {
+        let order = PathBuf::from("app/models/order.rb");
+        let checkout = PathBuf::from("app/models/checkout.rb");
+        let token = build_ruby_file("build", &order, TokenKind::Method);
+        let mut result = TokenSearchResult {
+            token,
+            occurrences: HashMap::from([(order, 1), (checkout, 2)]),
+            mentions: HashMap::new(),
+            scopes: Some(ScopeAttribution {
+                scopes: BTreeMap::from([(String::from("Invoice"), 0), (String::from("Order"), 2)]),
+                ambiguous: 0,
+            }),
+        };
+        let file_type_counts =
+            FileTypeCounts::references(&ProjectConfiguration::default(), &result);
+        let calculate = |result: &TokenSearchResult| {
+            UsageLikelihood::calculate(
+                &ProjectConfiguration::default(),
+                result,
+                &file_type_counts,
+                false,
+                &TokenPolicy::default(),
+            )
+        };
+
+        assert_eq!(
+            calculate(&result),
+            UsageLikelihood {
+                status: UsageLikelihoodStatus::Medium,
+                score: 55,
+                reason: String::from("Unused in Invoice; only used in Order"),
+                rule: Rule::UnusedInScope,
+            }
+        );
+
+        result.scopes.as_mut().unwrap().ambiguous = 1;
+        assert_eq!(calculate(&result).rule, Rule::WideUsage);
+    }
+
+    #[test]
+    fn token_policy_overrides_usage() {
+        let path = PathBuf::from("app/jobs/application_job.rb");
+        let token = build_ruby_file("ApplicationJob", &path, TokenKind::Class);
+        let mut occurrences = HashMap::new();
+        occurrences.insert(path, 1);
+        let result = TokenSearchResult {
+            token,
+            occurrences,
+            mentions: HashMap::new(),
+            scopes: None,
+        };
+        let file_type_counts =
+            FileTypeCounts::references(&ProjectConfiguration::default(), &result);
+        let mut policy = TokenPolicy::default();
+        policy.allowed.insert(
+            String::from("ApplicationJob"),
+            Some(String::from("Loaded by Rails")),
+        );
+
+        assert_eq!(
+            UsageLikelihood::calculate(
+                &ProjectConfiguration::default(),
+                &result,
+                &file_type_counts,
+                false,
+                &policy
+            ),
+            UsageLikelihood {
+                status: UsageLikelihoodStatus::Low,
+                score: 0,
+                reason: String::from("Token is allowed: Loaded by Rails"),
+                rule: Rule::AllowedToken,
+            }
+        );
+
+        let mut policy = TokenPolicy::default();
+        policy.denied.insert(String::from("ApplicationJob"), None);
+
+        assert_eq!(
+            UsageLikelihood::calculate(
+                &ProjectConfiguration::default(),
+                &result,
+                &file_type_counts,
+                false,
+                &policy
             ),
             UsageLikelihood {
                 status: UsageLikelihoodStatus::High,
-                reason: String::from("Only one occurrence exists")
+                score: 100,
+                reason: String::from("Token is denied"),
+                rule: Rule::DeniedToken,
             }
         );
     }
 
+    #[test]
+    fn scores_fall_within_their_status_range() {
+        let path = PathBuf::from("app/models/person.rb");
+        let mut occurrences = HashMap::new();
+        occurrences.insert(path.clone(), 1);
+        occurrences.insert(PathBuf::from("app/models/team.rb"), 6);
+        let score_for = |kind| {
+            let token = build_ruby_file("name", &path, kind);
+            let result = TokenSearchResult {
+                token,
+                occurrences: occurrences.clone(),
+                mentions: HashMap::new(),
+                scopes: None,
+            };
+            let file_type_counts =
+                FileTypeCounts::references(&ProjectConfiguration::default(), &result);
+
+            UsageLikelihood::calculate(
+                &ProjectConfiguration::default(),
+                &result,
+                &file_type_counts,
+                false,
+                &TokenPolicy::default(),
+            )
+            .score
+        };
+
+        assert_eq!(score_for(TokenKind::Method), 20);
+        assert_eq!(score_for(TokenKind::Callback), 5);
+    }
+
+    #[test]
+    fn minimum_likelihood_includes_more_likely_statuses() {
+        assert_eq!(
+            UsageLikelihoodStatus::at_least(&UsageLikelihoodStatus::Medium),
+            vec![UsageLikelihoodStatus::High, UsageLikelihoodStatus::Medium]
+        );
+        assert_eq!(
+            UsageLikelihoodStatus::at_least(&UsageLikelihoodStatus::Low),
+            UsageLikelihoodStatus::all()
+        );
+    }
+
     #[test]
     fn parse_usage_likelihood_status() {
         assert_eq!(
@@ -0,0 +1,247 @@
+use codebase_files::text::is_identifier_char;
+use codebase_files::FileSystem;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use read_ctags::{Language, LanguageMappings};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum AliasSyntax {
+    Ruby,
+    Rust,
+    JavaScript,
+}
+
+impl AliasSyntax {
+    fn for_path(path: &Path, mappings: &LanguageMappings) -> Option<Self> {
+        match mappings.from_path(path)? {
+            Language::Ruby => Some(AliasSyntax::Ruby),
+            Language::Rust => Some(AliasSyntax::Rust),
+            Language::JavaScript | Language::TypeScript => Some(AliasSyntax::JavaScript),
+            _ => None,
+        }
+    }
+}
+
+/// An alias declared for another name, along with the file declaring it
+#[derive(Clone, Debug, PartialEq)]
+struct Declaration {
+    original: String,
+    path: PathBuf,
+}
+
+/// Names declared as aliases of other names (Ruby's `alias` and `alias_method`, `as` renames in
+/// JavaScript/TypeScript imports and exports, and `use ... as` in Rust)
+///
+/// A method or function may only be referenced by an alias, such as one re-exported under a
+/// different name; occurrences of the alias are credited to the name it aliases, so it isn't
+/// reported as unused.
+#[derive(Default)]
+pub struct Aliases {
+    declarations: HashMap<String, Vec<Declaration>>,
+}
+
+impl Aliases {
+    /// Read and parse alias declarations from each file written in a supported language
+    ///
+    /// Files' languages are told apart by `mappings`, ahead of their extensions.
+    pub fn build(
+        files: &[PathBuf],
+        file_system: &dyn FileSystem,
+        mappings: &LanguageMappings,
+    ) -> Self {
+        let found: Vec<(String, Declaration)> = files
+            .par_iter()
+            .filter_map(|path| Some((path, AliasSyntax::for_path(path, mappings)?)))
+            .flat_map_iter(|(path, syntax)| {
+                let contents = file_system.read(path).unwrap_or_default();
+                parse_aliases(syntax, &contents)
+                    .into_iter()
+                    .map(move |(alias, original)| {
+                        (
+                            alias,
+                            Declaration {
+                                original,
+                                path: path.to_path_buf(),
+                            },
+                        )
+                    })
+            })
+            .collect();
+
+        let mut aliases = Aliases::default();
+        for (alias, declaration) in found {
+            aliases
+                .declarations
+                .entry(alias)
+                .or_default()
+                .push(declaration);
+        }
+        aliases
+    }
+
+    /// Every name declared as an alias
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.declarations.keys()
+    }
+
+    /// Each alias, the name it aliases, and the file declaring it
+    pub fn links(&self) -> impl Iterator<Item = (&str, &str, &Path)> {
+        self.declarations.iter().flat_map(|(alias, declarations)| {
+            declarations
+                .iter()
+                .map(move |d| (alias.as_str(), d.original.as_str(), d.path.as_path()))
+        })
+    }
+}
+
+// Pairs of an alias and the name it aliases
+fn parse_aliases(syntax: AliasSyntax, contents: &str) -> Vec<(String, String)> {
+    match syntax {
+        AliasSyntax::Ruby => contents
+            .lines()
+            .filter_map(|line| ruby_alias(line.trim()))
+            .collect(),
+        AliasSyntax::Rust | AliasSyntax::JavaScript => statements(syntax, contents)
+            .iter()
+            .flat_map(|statement| renames(statement))
+            .collect(),
+    }
+}
+
+// `alias new_name old_name` or `alias_method :new_name, :old_name`
+fn ruby_alias(line: &str) -> Option<(String, String)> {
+    let arguments = line
+        .strip_prefix("alias_method")
+        .or_else(|| line.strip_prefix("alias "))?;
+    let mut names = arguments
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '(' || c == ')')
+        .filter(|part| !part.is_empty())
+        .map(|part| part.trim_start_matches(':').trim_matches(['"', '\'']));
+
+    match (names.next(), names.next()) {
+        (Some(alias), Some(original)) if is_name(alias) && is_name(original) => {
+            Some((alias.to_string(), original.to_string()))
+        }
+        _ => None,
+    }
+}
+
+// `use` declarations in Rust, and import and export lists in JavaScript/TypeScript, which may
+// span several lines
+fn statements(syntax: AliasSyntax, contents: &str) -> Vec<String> {
+    let mut statements = vec![];
+    let mut current: Option<String> = None;
+
+    for line in contents.lines().map(str::trim) {
+        if let Some(statement) = current.as_mut() {
+            statement.push(' ');
+            statement.push_str(line);
+        } else if starts_statement(syntax, line) {
+            current = Some(line.to_string());
+        }
+
+        if current
+            .as_deref()
+            .is_some_and(|statement| statement_complete(syntax, statement))
+        {
+            statements.extend(current.take());
+        }
+    }
+
+    statements
+}
+
+fn starts_statement(syntax: AliasSyntax, line: &str) -> bool {
+    match syntax {
+        AliasSyntax::Rust => line
+            .strip_prefix("pub(crate) ")
+            .or_else(|| line.strip_prefix("pub "))
+            .unwrap_or(line)
+            .starts_with("use "),
+        AliasSyntax::JavaScript => {
+            line.starts_with("import ")
+                || line.starts_with("export {")
+                || line.starts_with("export type {")
+        }
+        AliasSyntax::Ruby => false,
+    }
+}
+
+fn statement_complete(syntax: AliasSyntax, statement: &str) -> bool {
+    match syntax {
+        AliasSyntax::Rust => statement.ends_with(';'),
+        _ => statement.matches('{').count() <= statement.matches('}').count(),
+    }
+}
+
+// Each `original as alias` within a statement, by the last segment of the original's path
+fn renames(statement: &str) -> Vec<(String, String)> {
+    let words: Vec<&str> = statement
+        .split(|c: char| c.is_whitespace() || ",{}();".contains(c))
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    words
+        .windows(3)
+        .filter(|window| window[1] == "as")
+        .filter_map(|window| {
+            let original = window[0].rsplit("::").next()?;
+            let alias = window[2];
+            (is_name(original) && is_name(alias) && original != alias)
+                .then(|| (alias.to_string(), original.to_string()))
+        })
+        .collect()
+}
+
+fn is_name(word: &str) -> bool {
+    !word.is_empty()
+        && word
+            .chars()
+            .all(|c| is_identifier_char(c) || c == '$' || c == '?' || c == '!')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(aliases: &[(&str, &str)]) -> Vec<(String, String)> {
+        aliases
+            .iter()
+            .map(|(alias, original)| (alias.to_string(), original.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_ruby_aliases() {
+        assert_eq!(
+            parse_aliases(
+                AliasSyntax::Ruby,
+                "class Person\n  alias_method :name, :full_name\n  alias to_s inspect\n  alias_method(\"valid?\", \"present?\")\n  def aliased; end\nend\n"
+            ),
+            pairs(&[
+                ("name", "full_name"),
+                ("to_s", "inspect"),
+                ("valid?", "present?")
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_renamed_imports_and_exports() {
+        assert_eq!(
+            parse_aliases(
+                AliasSyntax::JavaScript,
+                "import * as api from './api';\nexport {\n  fullName as name,\n  age,\n};\nexport { default as Person } from './person';\n"
+            ),
+            pairs(&[("name", "fullName"), ("Person", "default")])
+        );
+        assert_eq!(
+            parse_aliases(
+                AliasSyntax::Rust,
+                "pub use crate::person::full_name as name;\nuse std::{\n    fmt::Result as FmtResult,\n    io,\n};\nfn main() { let x = y as u8; }\n"
+            ),
+            pairs(&[("name", "full_name"), ("FmtResult", "Result")])
+        );
+    }
+}
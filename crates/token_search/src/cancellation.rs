@@ -0,0 +1,139 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Aborts a search, and the analysis of its results, from another thread or at a deadline
+///
+/// Clones share the same state, so a token kept by an editor or daemon can cancel a search
+/// running on another thread. Once cancelled, files not yet searched are skipped and tokens not
+/// yet analyzed are left out, so results are partial: check `interrupted` to tell whether any
+/// work was skipped. The default token is never cancelled.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    interrupted: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    /// A token cancelled only by calling `cancel`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token cancelled once `deadline` passes, or by calling `cancel` before then
+    pub fn with_deadline(deadline: Instant) -> Self {
+        CancellationToken {
+            deadline: Some(deadline),
+            ..Self::default()
+        }
+    }
+
+    /// A token cancelled once `timeout` elapses from now, or by calling `cancel` before then
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_deadline(Instant::now() + timeout)
+    }
+
+    /// Abort searches and analyses using this token (or any of its clones)
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Was the token cancelled, or has its deadline passed?
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Was work skipped because the token was cancelled, leaving results incomplete?
+    ///
+    /// A token cancelled after a search finished hasn't interrupted it, so its results are
+    /// still complete.
+    pub fn interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::Relaxed)
+    }
+
+    /// Should the work about to be done be skipped? Skipping it marks the token interrupted
+    pub fn skip(&self) -> bool {
+        let cancelled = self.is_cancelled();
+        if cancelled {
+            self.interrupted.store(true, Ordering::Relaxed);
+        }
+        cancelled
+    }
+}
+
+impl Debug for CancellationToken {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.cancelled.load(Ordering::Relaxed))
+            .field("interrupted", &self.interrupted())
+            .field("deadline", &self.deadline)
+            .finish()
+    }
+}
+
+// Tokens are equal when they're clones of one another
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.cancelled, &other.cancelled) && self.deadline == other.deadline
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Token, TokenSearchConfig, TokenSearchResults};
+    use codebase_files::MemoryFileSystem;
+    use std::path::PathBuf;
+
+    fn config(cancellation: CancellationToken) -> TokenSearchConfig {
+        let file_system = MemoryFileSystem::default();
+        file_system.insert("app/person.rb", "def full_name\nend\n");
+        file_system.insert("app/order.rb", "full_name\n");
+        let tags = "full_name\tapp/person.rb\t1;\"\tf";
+        let definitions = read_ctags::CtagItem::parse(PathBuf::from("tags"), tags)
+            .map(|file| file.tags)
+            .unwrap_or_default();
+        TokenSearchConfig {
+            tokens: vec![Token::new(String::from("full_name"), definitions)],
+            display_progress: false,
+            cancellation,
+            file_system: Arc::new(file_system),
+            ..TokenSearchConfig::for_files(vec![
+                PathBuf::from("app/person.rb"),
+                PathBuf::from("app/order.rb"),
+            ])
+        }
+    }
+
+    #[test]
+    fn skips_files_once_cancelled() {
+        let cancellation = CancellationToken::new();
+        let complete = TokenSearchResults::generate_with_config(&config(cancellation.clone()));
+        cancellation.cancel();
+
+        assert_eq!(complete.value()[0].occurrences.len(), 2);
+        assert!(cancellation.is_cancelled());
+        assert!(!cancellation.interrupted());
+
+        let partial = TokenSearchResults::generate_with_config(&config(cancellation.clone()));
+
+        assert!(partial.value().is_empty());
+        assert!(cancellation.interrupted());
+    }
+
+    #[test]
+    fn cancels_at_deadlines() {
+        let expired = CancellationToken::with_timeout(Duration::ZERO);
+        let later = CancellationToken::with_timeout(Duration::from_secs(3600));
+
+        assert!(expired.is_cancelled());
+        assert!(!later.is_cancelled());
+        assert_eq!(later, later.clone());
+        assert_ne!(later, CancellationToken::new());
+    }
+}
@@ -0,0 +1,186 @@
+use super::token_search::TokenSearchResult;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// DependencyGraph links each file to the files defining the tokens it uses
+///
+/// A file depends on another when a token defined in the other file occurs in it. Following
+/// dependencies from a project's entry points (such as `src/main.rs` or `bin/server`) finds the
+/// files reachable from them; tokens only defined in unreachable files are likely unused, even
+/// when those files reference each other. Like `UsageIndex`, the graph is immutable once built
+/// and cheap to clone.
+#[derive(Clone)]
+pub struct DependencyGraph {
+    files: Arc<BTreeSet<PathBuf>>,
+    // Tokens each file uses, keyed by the file using them and then by the file defining them
+    dependencies: Arc<BTreeMap<PathBuf, BTreeMap<PathBuf, BTreeSet<String>>>>,
+    dependents: Arc<BTreeMap<PathBuf, BTreeSet<PathBuf>>>,
+}
+
+impl DependencyGraph {
+    /// Build a graph from search results
+    pub fn new(results: &[TokenSearchResult]) -> Self {
+        let mut files = BTreeSet::new();
+        let mut dependencies: BTreeMap<PathBuf, BTreeMap<PathBuf, BTreeSet<String>>> =
+            BTreeMap::new();
+        let mut dependents: BTreeMap<PathBuf, BTreeSet<PathBuf>> = BTreeMap::new();
+
+        for result in results {
+            let defined_paths = result.defined_paths();
+            files.extend(defined_paths.iter().cloned());
+
+            for (path, count) in result.occurrences.iter() {
+                if *count == 0 {
+                    continue;
+                }
+                files.insert(path.to_path_buf());
+
+                for defined_path in defined_paths.iter().filter(|p| *p != path) {
+                    dependencies
+                        .entry(path.to_path_buf())
+                        .or_default()
+                        .entry(defined_path.to_path_buf())
+                        .or_default()
+                        .insert(result.token.token.to_string());
+                    dependents
+                        .entry(defined_path.to_path_buf())
+                        .or_default()
+                        .insert(path.to_path_buf());
+                }
+            }
+        }
+
+        DependencyGraph {
+            files: Arc::new(files),
+            dependencies: Arc::new(dependencies),
+            dependents: Arc::new(dependents),
+        }
+    }
+
+    /// Every file defining or using a token, ordered by path
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(PathBuf::as_path)
+    }
+
+    /// Files defining tokens used in this file
+    pub fn dependencies(&self, path: &Path) -> Vec<&Path> {
+        self.dependencies
+            .get(path)
+            .map(|by_path| by_path.keys().map(PathBuf::as_path).collect())
+            .unwrap_or_default()
+    }
+
+    /// Files using tokens defined in this file
+    pub fn dependents(&self, path: &Path) -> Vec<&Path> {
+        self.dependents
+            .get(path)
+            .map(|paths| paths.iter().map(PathBuf::as_path).collect())
+            .unwrap_or_default()
+    }
+
+    /// Tokens defined in `dependency` and used in `path`
+    pub fn tokens_used(&self, path: &Path, dependency: &Path) -> Option<&BTreeSet<String>> {
+        self.dependencies
+            .get(path)
+            .and_then(|by_path| by_path.get(dependency))
+    }
+
+    /// Files reachable by following dependencies from `entry_points`, including the entry points
+    /// found in the graph
+    pub fn reachable_from<P: AsRef<Path>>(&self, entry_points: &[P]) -> BTreeSet<&Path> {
+        let mut reachable = BTreeSet::new();
+        let mut pending: Vec<&Path> = entry_points
+            .iter()
+            .filter_map(|entry_point| self.files.get(entry_point.as_ref()))
+            .map(PathBuf::as_path)
+            .collect();
+
+        while let Some(path) = pending.pop() {
+            if reachable.insert(path) {
+                pending.extend(self.dependencies(path));
+            }
+        }
+
+        reachable
+    }
+
+    /// Files that can't be reached by following dependencies from `entry_points`
+    pub fn unreachable_from<P: AsRef<Path>>(&self, entry_points: &[P]) -> BTreeSet<&Path> {
+        let reachable = self.reachable_from(entry_points);
+
+        self.files()
+            .filter(|path| !reachable.contains(path))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Token;
+    use read_ctags::{CtagItem, TokenKind};
+    use std::collections::HashMap;
+
+    fn result(token: &str, defined_in: &str, occurrences: &[(&str, usize)]) -> TokenSearchResult {
+        let definition = CtagItem {
+            name: token.to_string(),
+            file_path: PathBuf::from(defined_in).into(),
+            raw_path: defined_in.into(),
+            address: String::from("1"),
+            language: None,
+            tags: BTreeMap::new(),
+            kind: TokenKind::Method,
+        };
+
+        TokenSearchResult {
+            token: Token::new(token.to_string(), [definition].iter().cloned().collect()),
+            occurrences: occurrences
+                .iter()
+                .map(|(path, count)| (PathBuf::from(path), *count))
+                .collect(),
+            mentions: HashMap::new(),
+            scopes: None,
+        }
+    }
+
+    #[test]
+    fn finds_files_unreachable_from_entry_points() {
+        let graph = DependencyGraph::new(&[
+            result(
+                "run",
+                "src/app.rs",
+                &[("src/app.rs", 1), ("src/main.rs", 1)],
+            ),
+            result(
+                "render",
+                "src/view.rs",
+                &[("src/view.rs", 1), ("src/app.rs", 2)],
+            ),
+            result("legacy", "src/legacy.rs", &[("src/legacy.rs", 1)]),
+            result("helper", "src/helper.rs", &[("src/legacy.rs", 1)]),
+        ]);
+        let path = Path::new;
+
+        assert_eq!(
+            graph.dependencies(path("src/main.rs")),
+            vec![path("src/app.rs")]
+        );
+        assert_eq!(
+            graph.dependents(path("src/helper.rs")),
+            vec![path("src/legacy.rs")]
+        );
+        assert_eq!(
+            graph.tokens_used(path("src/app.rs"), path("src/view.rs")),
+            Some(&BTreeSet::from([String::from("render")]))
+        );
+        assert_eq!(
+            graph.reachable_from(&["src/main.rs"]),
+            BTreeSet::from([path("src/app.rs"), path("src/main.rs"), path("src/view.rs")])
+        );
+        assert_eq!(
+            graph.unreachable_from(&["src/main.rs", "src/missing.rs"]),
+            BTreeSet::from([path("src/helper.rs"), path("src/legacy.rs")])
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Lines changed in a single file, as reported by `git diff --unified=0`
+#[derive(Debug, PartialEq)]
+pub struct FileDiff {
+    /// Path of the changed file, relative to the current directory
+    pub path: PathBuf,
+    /// Lines removed from the file
+    pub removed: Vec<String>,
+    /// Lines added to the file
+    pub added: Vec<String>,
+    /// Whether the change can't be expressed as line hunks (added, deleted, binary, or
+    /// conflicted files) and the file must be rescanned instead
+    pub requires_rescan: bool,
+}
+
+impl FileDiff {
+    fn new(path: PathBuf) -> Self {
+        FileDiff {
+            path,
+            removed: vec![],
+            added: vec![],
+            requires_rescan: false,
+        }
+    }
+}
+
+/// Run `git diff` between `revision` and the working tree and parse the resulting hunks
+pub fn diff_against(revision: &str) -> Result<Vec<FileDiff>, String> {
+    let output = Command::new("git")
+        .args(["-c", "core.quotePath=false", "diff"])
+        .args(["--relative", "--unified=0", "--no-color", "--no-renames"])
+        .arg("--no-ext-diff")
+        .arg(revision)
+        .arg("--")
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let diff = String::from_utf8(output.stdout).map_err(|e| e.to_string())?;
+    parse(&diff)
+}
+
+/// Parse the output of `git diff --unified=0 --no-renames`
+pub fn parse(diff: &str) -> Result<Vec<FileDiff>, String> {
+    let mut results: Vec<FileDiff> = vec![];
+    let mut lines = diff.lines();
+
+    while let Some(line) = lines.next() {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            results.push(FileDiff::new(header_path(header)?));
+        } else if line.starts_with("diff --cc ") || line.starts_with("* Unmerged path ") {
+            let path = line
+                .trim_start_matches("diff --cc ")
+                .trim_start_matches("* Unmerged path ");
+            let mut file_diff = FileDiff::new(PathBuf::from(unquoted(path)?));
+            file_diff.requires_rescan = true;
+            results.push(file_diff);
+        } else if let Some(current) = results.last_mut() {
+            if line.starts_with("new file mode")
+                || line.starts_with("deleted file mode")
+                || line.starts_with("Binary files")
+            {
+                current.requires_rescan = true;
+            } else if let Some(ranges) = line.strip_prefix("@@ ") {
+                let (mut removed, mut added) = hunk_lengths(ranges)?;
+
+                while removed > 0 || added > 0 {
+                    match lines.next() {
+                        Some(l) if l.starts_with('-') && removed > 0 => {
+                            current.removed.push(l[1..].to_string());
+                            removed -= 1;
+                        }
+                        Some(l) if l.starts_with('+') && added > 0 => {
+                            current.added.push(l[1..].to_string());
+                            added -= 1;
+                        }
+                        Some(l) if l.starts_with('\\') => {}
+                        _ => return Err(format!("Malformed hunk in {:?}", current.path)),
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+// "a/<path> b/<path>"; without renames both sides name the same path
+fn header_path(header: &str) -> Result<PathBuf, String> {
+    let malformed = || format!("Unable to parse diff header: {}", header);
+    let path_length = header
+        .len()
+        .checked_sub(5)
+        .filter(|l| l % 2 == 0)
+        .ok_or_else(malformed)?
+        / 2;
+    let path = header
+        .get(2..2 + path_length)
+        .filter(|p| header.starts_with("a/") && header[2 + path_length..] == format!(" b/{}", p))
+        .ok_or_else(malformed)?;
+
+    Ok(PathBuf::from(unquoted(path)?))
+}
+
+fn unquoted(path: &str) -> Result<&str, String> {
+    if path.starts_with('"') {
+        Err(format!("Unable to parse quoted path: {}", path))
+    } else {
+        Ok(path)
+    }
+}
+
+// "-12,3 +12,0 @@ ..." yields (3, 0); an omitted length means one line
+fn hunk_lengths(ranges: &str) -> Result<(usize, usize), String> {
+    let mut parts = ranges.split_whitespace();
+    let mut length = |prefix: char| {
+        parts
+            .next()
+            .and_then(|p| p.strip_prefix(prefix))
+            .and_then(|p| match p.split_once(',') {
+                Some((_, length)) => length.parse().ok(),
+                None => p.parse::<usize>().ok().map(|_| 1),
+            })
+            .ok_or_else(|| format!("Unable to parse hunk header: {}", ranges))
+    };
+
+    Ok((length('-')?, length('+')?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hunks_and_rescan_markers() {
+        let diff = "diff --git a/app/person.rb b/app/person.rb
+index 1111111..2222222 100644
+--- a/app/person.rb
++++ b/app/person.rb
+@@ -2 +2,2 @@ class Person
+-  def name
++  def full_name
++  end
+@@ -9,0 +11 @@ end
++Person.new
+\\ No newline at end of file
+diff --git a/app/new file.rb b/app/new file.rb
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/app/new file.rb
+@@ -0,0 +1 @@
++class NewFile; end
+diff --git a/logo.png b/logo.png
+index 4444444..5555555 100644
+Binary files a/logo.png and b/logo.png differ
+";
+
+        let mut new_file = FileDiff::new(PathBuf::from("app/new file.rb"));
+        new_file.requires_rescan = true;
+        new_file.added = vec!["class NewFile; end".to_string()];
+        let mut binary = FileDiff::new(PathBuf::from("logo.png"));
+        binary.requires_rescan = true;
+
+        assert_eq!(
+            parse(diff),
+            Ok(vec![
+                FileDiff {
+                    path: PathBuf::from("app/person.rb"),
+                    removed: vec!["  def name".to_string()],
+                    added: vec![
+                        "  def full_name".to_string(),
+                        "  end".to_string(),
+                        "Person.new".to_string()
+                    ],
+                    requires_rescan: false,
+                },
+                new_file,
+                binary,
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_hunks() {
+        let diff = "diff --git a/a.rb b/a.rb\n--- a/a.rb\n+++ b/a.rb\n@@ -1,2 +1 @@\n-one\n";
+
+        assert!(parse(diff).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+use codebase_files::text;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use read_ctags::Language;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// File stems naming their directory's module rather than a module of their own
+const DIRECTORY_MODULE_STEMS: [&str; 5] = ["mod", "index", "__init__", "lib", "main"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ImportSyntax {
+    Rust,
+    Go,
+    Python,
+    JavaScript,
+}
+
+impl ImportSyntax {
+    fn for_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(ImportSyntax::Rust),
+            "go" => Some(ImportSyntax::Go),
+            "py" => Some(ImportSyntax::Python),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" => Some(ImportSyntax::JavaScript),
+            _ => None,
+        }
+    }
+
+    // Extensionless scripts, by their shebang or modeline
+    fn for_contents(contents: &str) -> Option<Self> {
+        match Language::from_contents(contents)? {
+            Language::Rust => Some(ImportSyntax::Rust),
+            Language::Python => Some(ImportSyntax::Python),
+            Language::JavaScript | Language::TypeScript => Some(ImportSyntax::JavaScript),
+            _ => None,
+        }
+    }
+}
+
+/// File-level import statements for languages with explicit imports (Rust, Go, Python, and
+/// JavaScript/TypeScript)
+///
+/// This is used to decide whether a file plausibly references a token defined elsewhere: common
+/// names such as `Client` or `Config` often have unrelated definitions across a codebase, and an
+/// occurrence only counts towards a definition when the file could have imported it.
+pub struct ImportGraph {
+    imports: HashMap<PathBuf, Vec<String>>,
+}
+
+impl ImportGraph {
+    /// Read and parse import statements from each file written in a supported language
+    pub fn build(files: &[PathBuf]) -> Self {
+        let imports = files
+            .par_iter()
+            .filter_map(|path| {
+                let syntax = ImportSyntax::for_path(path);
+                if syntax.is_none() && path.extension().is_some() {
+                    return None;
+                }
+                let contents = text::read(path).ok()?;
+                let syntax = syntax.or_else(|| ImportSyntax::for_contents(&contents))?;
+                Some((path.to_path_buf(), parse_imports(syntax, &contents)))
+            })
+            .collect();
+
+        ImportGraph { imports }
+    }
+
+    /// Import statements parsed from a file, if it's written in a supported language
+    pub fn imports_of(&self, path: &Path) -> Option<&[String]> {
+        self.imports.get(path).map(|i| i.as_slice())
+    }
+
+    /// Could occurrences of `token` in `file` refer to its definition in `definition`?
+    ///
+    /// This is deliberately lenient: files in other languages, files in the same directory
+    /// (sharing a Go package or reachable via Rust's `super::`), and glob imports are always
+    /// plausible. Otherwise, one of the file's imports must mention the token or the module
+    /// defining it.
+    pub fn plausibly_imports(&self, file: &Path, definition: &Path, token: &str) -> bool {
+        if file == definition || file.parent() == definition.parent() {
+            return true;
+        }
+
+        let imports = match (self.imports.get(file), ImportSyntax::for_path(definition)) {
+            (Some(imports), Some(_)) => imports,
+            _ => return true,
+        };
+        let module = module_name(definition);
+
+        imports.iter().any(|statement| {
+            statement.contains('*')
+                || statement
+                    .split(|c: char| !text::is_identifier_char(c))
+                    .any(|word| word == token)
+                || module.as_deref().is_some_and(|m| statement.contains(m))
+        })
+    }
+}
+
+// The name other files use to import the module defined in `path`
+fn module_name(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let parent = path.parent();
+
+    let name = if ImportSyntax::for_path(path) == Some(ImportSyntax::Go)
+        || DIRECTORY_MODULE_STEMS.contains(&stem)
+    {
+        match parent.and_then(|p| p.file_name()).and_then(|p| p.to_str()) {
+            // Rust crate roots live in `src/`, beneath a directory named for the crate
+            Some("src") => parent?.parent()?.file_name()?.to_str()?,
+            Some(dir) => dir,
+            None => return None,
+        }
+    } else {
+        stem
+    };
+
+    Some(name.replace('-', "_"))
+}
+
+fn parse_imports(syntax: ImportSyntax, contents: &str) -> Vec<String> {
+    let mut statements = vec![];
+    let mut current: Option<String> = None;
+
+    for line in contents.lines().map(str::trim) {
+        if let Some(statement) = current.as_mut() {
+            statement.push(' ');
+            statement.push_str(line);
+
+            if statement_complete(syntax, statement) {
+                statements.extend(current.take());
+            }
+        } else if starts_import(syntax, line) {
+            if statement_complete(syntax, line) {
+                statements.push(line.to_string());
+            } else {
+                current = Some(line.to_string());
+            }
+        } else if syntax == ImportSyntax::JavaScript && line.contains("require(") {
+            statements.push(line.to_string());
+        }
+    }
+
+    statements.extend(current);
+    statements
+}
+
+fn starts_import(syntax: ImportSyntax, line: &str) -> bool {
+    match syntax {
+        ImportSyntax::Rust => {
+            let line = line
+                .strip_prefix("pub(crate) ")
+                .or_else(|| line.strip_prefix("pub "))
+                .unwrap_or(line);
+            line.starts_with("use ")
+                || line.starts_with("mod ")
+                || line.starts_with("extern crate ")
+        }
+        ImportSyntax::Go | ImportSyntax::Python => {
+            line.starts_with("import ") || line.starts_with("import(") || line.starts_with("from ")
+        }
+        ImportSyntax::JavaScript => {
+            line.starts_with("import ") || (line.starts_with("export ") && line.contains(" from "))
+        }
+    }
+}
+
+fn statement_complete(syntax: ImportSyntax, statement: &str) -> bool {
+    match syntax {
+        // Inline modules (`mod tests {`) end at their opening brace; `use a::{` continues
+        ImportSyntax::Rust => {
+            statement.ends_with(';') || (statement.ends_with('{') && !statement.ends_with("::{"))
+        }
+        ImportSyntax::Go => !statement.ends_with('(') && balanced(statement, '(', ')'),
+        ImportSyntax::Python => balanced(statement, '(', ')') && !statement.ends_with('\\'),
+        ImportSyntax::JavaScript => {
+            statement.contains(" from ")
+                || statement.ends_with(';')
+                || (balanced(statement, '{', '}') && statement.contains(['\'', '"']))
+        }
+    }
+}
+
+fn balanced(statement: &str, open: char, close: char) -> bool {
+    statement.matches(open).count() <= statement.matches(close).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_multiline_imports() {
+        assert_eq!(
+            parse_imports(
+                ImportSyntax::Rust,
+                "use std::fs;\npub use crate::client::{\n    Client,\n    Config,\n};\nfn main() {}\n"
+            ),
+            vec!["use std::fs;", "pub use crate::client::{ Client, Config, };"]
+        );
+        assert_eq!(
+            parse_imports(
+                ImportSyntax::Python,
+                "from app.client import (\n    Client,\n)\nimport os\n"
+            ),
+            vec!["from app.client import ( Client, )", "import os"]
+        );
+        assert_eq!(
+            parse_imports(
+                ImportSyntax::JavaScript,
+                "import {\n  Client\n} from './client';\nconst fs = require('fs');\n"
+            ),
+            vec![
+                "import { Client } from './client';",
+                "const fs = require('fs');"
+            ]
+        );
+        assert_eq!(
+            parse_imports(
+                ImportSyntax::Go,
+                "import (\n\t\"fmt\"\n\t\"example.com/api/client\"\n)\n"
+            ),
+            vec!["import ( \"fmt\" \"example.com/api/client\" )"]
+        );
+    }
+
+    #[test]
+    fn scopes_usages_to_plausible_importers() {
+        let mut imports = HashMap::new();
+        imports.insert(
+            PathBuf::from("web/app.js"),
+            vec![String::from("import { Client } from '../api/client';")],
+        );
+        imports.insert(
+            PathBuf::from("worker/main.py"),
+            vec![String::from("from billing.config import Settings")],
+        );
+        imports.insert(
+            PathBuf::from("cli/run.py"),
+            vec![String::from("from billing.api_client import *")],
+        );
+        let graph = ImportGraph { imports };
+        let definition = Path::new("api/client.js");
+
+        assert!(graph.plausibly_imports(Path::new("web/app.js"), definition, "Client"));
+        assert!(graph.plausibly_imports(Path::new("api/other.js"), definition, "Client"));
+        assert!(graph.plausibly_imports(Path::new("README.md"), definition, "Client"));
+        assert!(graph.plausibly_imports(
+            Path::new("cli/run.py"),
+            Path::new("billing/api_client.py"),
+            "Client"
+        ));
+        assert!(!graph.plausibly_imports(
+            Path::new("worker/main.py"),
+            Path::new("api/client.py"),
+            "Client"
+        ));
+        assert!(graph.plausibly_imports(
+            Path::new("worker/main.py"),
+            Path::new("billing/config.py"),
+            "Config"
+        ));
+    }
+
+    #[test]
+    fn names_modules_after_directories_for_entry_points() {
+        assert_eq!(
+            module_name(Path::new("crates/read-ctags/src/lib.rs")),
+            Some(String::from("read_ctags"))
+        );
+        assert_eq!(
+            module_name(Path::new("src/client/mod.rs")),
+            Some(String::from("client"))
+        );
+        assert_eq!(
+            module_name(Path::new("pkg/billing/invoice.go")),
+            Some(String::from("billing"))
+        );
+        assert_eq!(
+            module_name(Path::new("web/client.ts")),
+            Some(String::from("client"))
+        );
+    }
+}
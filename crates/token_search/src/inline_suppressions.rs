@@ -0,0 +1,200 @@
+use super::token_search::{TokenSearchConfig, TokenSearchResults};
+use rayon::prelude::*;
+use read_ctags::CtagItem;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// Written after a comment marker, optionally followed by the reason for the opt-out
+const ANNOTATION: &str = "unused:ignore";
+
+// Markers starting line comments, or block comments written on a single line
+const COMMENT_MARKERS: [&str; 4] = ["#", "//", "--", "/*"];
+
+/// A definition opted out of reporting with an `unused:ignore` comment
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct InlineSuppression {
+    /// Name of the token suppressed
+    pub token: String,
+    /// File defining the token
+    pub path: PathBuf,
+    /// Line of the definition the comment is on or above
+    pub line: usize,
+    /// Text following `unused:ignore`, explaining why the token is kept
+    pub reason: Option<String>,
+}
+
+/// Tokens whose definitions are annotated with `unused:ignore` comments
+///
+/// A definition is annotated by a comment ending its line (`def legacy # unused:ignore`), or by
+/// a line holding only a comment directly above it (`// unused:ignore called from templates`).
+/// A token defined in several places is suppressed when any of its definitions is annotated.
+#[derive(Clone, Debug, Default)]
+pub struct InlineSuppressions(HashMap<String, InlineSuppression>);
+
+impl InlineSuppressions {
+    /// Read the comments on and above each definition of the tokens in `results`
+    ///
+    /// Only files defining tokens are read, honoring the config's `content_overrides` and
+    /// `max_file_size`.
+    pub fn scan(config: &TokenSearchConfig, results: &TokenSearchResults) -> Self {
+        let mut definitions: HashMap<&Path, Vec<&CtagItem>> = HashMap::new();
+        for result in results.value() {
+            for definition in result.token.definitions.iter() {
+                definitions
+                    .entry(&definition.file_path)
+                    .or_default()
+                    .push(definition);
+            }
+        }
+
+        let mut suppressions = definitions
+            .into_par_iter()
+            .flat_map_iter(|(path, definitions)| {
+                let contents = match config.content_overrides.get(path) {
+                    Some(contents) => Some(contents.clone()),
+                    None => TokenSearchResults::read_file(config, path).ok(),
+                };
+                let contents = match contents {
+                    Some(contents) if contents.contains(ANNOTATION) => contents,
+                    _ => return vec![],
+                };
+                let lines: Vec<&str> = contents.lines().collect();
+
+                definitions
+                    .into_iter()
+                    .filter_map(|definition| {
+                        let line = definition.resolve_line(&contents)?;
+                        Some(InlineSuppression {
+                            token: definition.name.clone(),
+                            path: path.to_path_buf(),
+                            line,
+                            reason: annotation(&lines, line)?,
+                        })
+                    })
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        suppressions.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+
+        // Tokens annotated at several definitions are reported with the first
+        let mut by_token: HashMap<String, InlineSuppression> = HashMap::new();
+        for suppression in suppressions {
+            by_token
+                .entry(suppression.token.clone())
+                .or_insert(suppression);
+        }
+        tracing::info!("Found {} inline suppressions", by_token.len());
+
+        InlineSuppressions(by_token)
+    }
+
+    /// The annotation suppressing `token`, if any
+    pub fn get(&self, token: &str) -> Option<&InlineSuppression> {
+        self.0.get(token)
+    }
+
+    /// Number of tokens suppressed
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Were no tokens suppressed?
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+// The reason given by an annotation on `line` (1-based), or on a comment-only line above it;
+// `Some(None)` when annotated without a reason
+fn annotation(lines: &[&str], line: usize) -> Option<Option<String>> {
+    let on_line = lines
+        .get(line.checked_sub(1)?)
+        .and_then(|l| comment_reason(l));
+    let above = line
+        .checked_sub(2)
+        .and_then(|index| lines.get(index))
+        .filter(|l| {
+            let trimmed = l.trim_start();
+            COMMENT_MARKERS.iter().any(|m| trimmed.starts_with(m))
+        })
+        .and_then(|l| comment_reason(l));
+
+    on_line.or(above)
+}
+
+fn comment_reason(line: &str) -> Option<Option<String>> {
+    let start = line.find(ANNOTATION)?;
+    let before = line[..start].trim_end();
+    let after = &line[start + ANNOTATION.len()..];
+    if !COMMENT_MARKERS.iter().any(|m| before.ends_with(m))
+        || after.chars().next().is_some_and(|c| !c.is_whitespace())
+    {
+        return None;
+    }
+
+    let reason = after.trim().trim_end_matches("*/").trim();
+    Some(Some(reason.to_string()).filter(|r| !r.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token::Token;
+    use codebase_files::{FileSystem, MemoryFileSystem};
+    use std::sync::Arc;
+
+    #[test]
+    fn reads_annotations_on_and_above_definitions() {
+        let file_system = MemoryFileSystem::default();
+        file_system.insert(
+            "app/person.rb",
+            "class Person\n  def full_name # unused:ignore\n  end\n\n  # unused:ignore called from ERB helpers\n  def legacy_name\n  end\n\n  def nickname # unused:ignored\n  end\n  puts 1 # unused:ignore\n  def email\n  end\nend\n",
+        );
+        let file_system: Arc<dyn FileSystem> = Arc::new(file_system);
+        let definitions = read_ctags::CtagItem::parse(
+            PathBuf::from("tags"),
+            "full_name\tapp/person.rb\t/^  def full_name # unused:ignore$/;\"\tf\nlegacy_name\tapp/person.rb\t6;\"\tf\nnickname\tapp/person.rb\t9;\"\tf\nemail\tapp/person.rb\t12;\"\tf",
+        )
+        .map(|file| file.tags)
+        .unwrap();
+        let config = TokenSearchConfig {
+            tokens: Token::from_tags(definitions),
+            display_progress: false,
+            file_system,
+            ..TokenSearchConfig::for_files(vec![PathBuf::from("app/person.rb")])
+        };
+
+        let suppressions =
+            InlineSuppressions::scan(&config, &TokenSearchResults::generate_with_config(&config));
+
+        assert_eq!(suppressions.len(), 2);
+        assert_eq!(
+            suppressions.get("full_name"),
+            Some(&InlineSuppression {
+                token: String::from("full_name"),
+                path: PathBuf::from("app/person.rb"),
+                line: 2,
+                reason: None,
+            })
+        );
+        assert_eq!(
+            suppressions
+                .get("legacy_name")
+                .and_then(|s| s.reason.as_deref()),
+            Some("called from ERB helpers")
+        );
+        assert!(suppressions.get("nickname").is_none());
+        assert!(suppressions.get("email").is_none());
+    }
+
+    #[test]
+    fn reads_reasons_from_comments() {
+        assert_eq!(comment_reason("  // unused:ignore"), Some(None));
+        assert_eq!(
+            comment_reason("fn main() {} /* unused:ignore FFI entry point */"),
+            Some(Some(String::from("FFI entry point")))
+        );
+        assert_eq!(comment_reason("let unused:ignore = 1;"), None);
+    }
+}
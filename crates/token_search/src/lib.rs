@@ -3,9 +3,50 @@
 //! `token_search` is a crate for searching a set of files for occurrences of tokens.
 //!
 //! It does so relatively quickly by leveraging Aho-Corasick. It constructs the trie-like structure
-//! with the provided tokens and does a single pass over each file.
+//! with the provided tokens and does a single pass over each file, only counting matches that
+//! fall on identifier boundaries. Counts can optionally be cached between runs and updated from
+//! `git diff` hunks instead of rescanning every changed file, or keyed by file contents so only
+//! modified files are rescanned. With a memory limit, tokens are instead searched in shards, one
+//! after another, spilling counts to disk so huge codebases can be searched. When syntax-aware,
+//! occurrences in comments and string literals are told apart with a lightweight lexer for each
+//! language, and occurrences in comments aren't counted. Occurrences of a name defined in
+//! several scopes can be attributed to the scope each refers to, by its receiver, and
+//! definitions annotated with `unused:ignore` comments are found with `InlineSuppressions`.
+//! `ScanTimings` totals the time spent searching each directory, to profile slow searches, and
+//! a `CancellationToken` aborts a search from another thread or at a deadline.
+//! Results can be loaded into a
+//! `UsageIndex`, which is cheap to clone and share across threads for custom analyses, or a
+//! `DependencyGraph` of the files each file uses tokens from, to find files unreachable from a
+//! project's entry points.
+//!
+//! With the `async` feature, `TokenSearchResults::generate_async` searches on tokio's blocking
+//! thread pool, for async servers embedding the search.
+mod aliases;
+mod cancellation;
+mod dependency_graph;
+mod git_diff;
+mod import_graph;
+mod inline_suppressions;
+mod occurrence_cache;
+mod scan_cache;
+mod scan_timings;
+mod scope_attribution;
+mod shards;
+mod syntax;
 mod token;
 mod token_search;
+mod usage_index;
 
+pub use self::aliases::Aliases;
+pub use self::cancellation::CancellationToken;
+pub use self::dependency_graph::DependencyGraph;
+pub use self::import_graph::ImportGraph;
+pub use self::inline_suppressions::{InlineSuppression, InlineSuppressions};
+pub use self::occurrence_cache::{OccurrenceCache, Occurrences};
+pub use self::scan_cache::ScanCache;
+pub use self::scan_timings::{DirectoryTiming, ScanTimings};
+pub use self::scope_attribution::ScopeAttribution;
+pub use self::syntax::{Mentions, Region, Regions};
 pub use self::token::*;
 pub use self::token_search::*;
+pub use self::usage_index::UsageIndex;
@@ -0,0 +1,244 @@
+use super::git_diff::{self, FileDiff};
+use codebase_files::writes::Writes;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Occurrences per token, keyed by path
+pub type Occurrences = HashMap<String, HashMap<PathBuf, usize>>;
+
+/// Occurrence counts persisted between runs, anchored to the git revision they were computed at
+///
+/// Subsequent runs diff the working tree against that revision and adjust counts from the
+/// changed hunks rather than rescanning every changed file.
+#[derive(Deserialize, Serialize)]
+pub struct OccurrenceCache {
+    revision: String,
+    dirty_paths: HashSet<PathBuf>,
+    files: HashSet<PathBuf>,
+    occurrences: Occurrences,
+}
+
+/// The result of applying a diff to cached occurrences
+pub struct CachedOccurrences {
+    /// Occurrences for files that did not require rescanning
+    pub occurrences: Occurrences,
+    /// Files whose occurrences must be recounted from scratch
+    pub rescan: Vec<PathBuf>,
+}
+
+impl OccurrenceCache {
+    /// Location of the cache within the current repository's git directory
+    pub fn default_path() -> Option<PathBuf> {
+        git(&["rev-parse", "--git-path", "unused/occurrences.json"])
+            .map(|path| PathBuf::from(path.trim()))
+    }
+
+    /// Capture the current occurrences for writing, or `None` outside of a git repository
+    pub fn capture(files: &[PathBuf], occurrences: Occurrences) -> Option<Self> {
+        let revision = git(&["rev-parse", "HEAD"])?.trim().to_string();
+        let changed = git(&["diff", "--relative", "--name-only", "-z", "HEAD", "--"])?;
+        let untracked = git(&["ls-files", "-z", "--others", "--exclude-standard"])?;
+
+        Some(OccurrenceCache {
+            revision,
+            dirty_paths: changed
+                .split('\0')
+                .chain(untracked.split('\0'))
+                .filter(|p| !p.is_empty())
+                .map(PathBuf::from)
+                .collect(),
+            files: files.iter().cloned().collect(),
+            occurrences,
+        })
+    }
+
+    /// Read a previously written cache
+    pub fn read(path: &Path) -> Option<Self> {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+    }
+
+    /// Write the cache, creating its parent directory if necessary
+    pub fn write(&self, path: &Path, writes: Writes) -> std::io::Result<()> {
+        writes.write(path, serde_json::to_string(self)?)
+    }
+
+    /// Bring cached occurrences up to date with the working tree
+    ///
+    /// Returns `None` when the cache can't be used at all (tokens missing from the cache or an
+    /// unparseable diff), in which case every file should be rescanned.
+    pub fn refresh<F>(
+        &self,
+        tokens: &[&String],
+        files: &[PathBuf],
+        count: F,
+    ) -> Option<CachedOccurrences>
+    where
+        F: Fn(&str) -> Vec<(usize, usize)>,
+    {
+        if !tokens.iter().all(|t| self.occurrences.contains_key(*t)) {
+            return None;
+        }
+
+        let diffs = git_diff::diff_against(&self.revision).ok()?;
+        Some(self.apply(tokens, files, &diffs, count))
+    }
+
+    fn apply<F>(
+        &self,
+        tokens: &[&String],
+        files: &[PathBuf],
+        diffs: &[FileDiff],
+        count: F,
+    ) -> CachedOccurrences
+    where
+        F: Fn(&str) -> Vec<(usize, usize)>,
+    {
+        let searched: HashSet<&PathBuf> = files.iter().collect();
+        let mut rescan: HashSet<PathBuf> = files
+            .iter()
+            .filter(|f| !self.files.contains(*f) || self.dirty_paths.contains(*f))
+            .cloned()
+            .collect();
+        rescan.extend(
+            diffs
+                .iter()
+                .filter(|d| d.requires_rescan && searched.contains(&d.path))
+                .map(|d| d.path.clone()),
+        );
+
+        let mut occurrences: Occurrences = tokens
+            .iter()
+            .map(|&token| {
+                let by_path = self.occurrences[token]
+                    .iter()
+                    .filter(|(path, _)| searched.contains(path))
+                    .map(|(path, count)| (path.clone(), *count))
+                    .collect();
+                (token.to_string(), by_path)
+            })
+            .collect();
+
+        let applicable: Vec<&FileDiff> = diffs
+            .iter()
+            .filter(|d| searched.contains(&d.path) && !rescan.contains(&d.path))
+            .collect();
+
+        for diff in applicable {
+            let removed = count(&diff.removed.join("\n"));
+            let added = count(&diff.added.join("\n"));
+
+            if !Self::adjust(&mut occurrences, tokens, &diff.path, &removed, &added) {
+                rescan.insert(diff.path.clone());
+            }
+        }
+
+        for by_path in occurrences.values_mut() {
+            by_path.retain(|path, count| *count > 0 && !rescan.contains(path));
+        }
+
+        CachedOccurrences {
+            occurrences,
+            rescan: rescan.into_iter().collect(),
+        }
+    }
+
+    // Returns false when the hunks disagree with cached counts (e.g. removing an occurrence that
+    // was never counted), which means the file needs a full rescan
+    fn adjust(
+        occurrences: &mut Occurrences,
+        tokens: &[&String],
+        path: &Path,
+        removed: &[(usize, usize)],
+        added: &[(usize, usize)],
+    ) -> bool {
+        for &(index, amount) in removed {
+            let by_path = occurrences.entry(tokens[index].to_string()).or_default();
+            match by_path.get(path).and_then(|c| c.checked_sub(amount)) {
+                Some(remaining) => {
+                    by_path.insert(path.to_path_buf(), remaining);
+                }
+                None => return false,
+            }
+        }
+
+        for &(index, amount) in added {
+            *occurrences
+                .entry(tokens[index].to_string())
+                .or_default()
+                .entry(path.to_path_buf())
+                .or_insert(0) += amount;
+        }
+
+        true
+    }
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_words(tokens: &'static [&'static str]) -> impl Fn(&str) -> Vec<(usize, usize)> {
+        move |contents| {
+            tokens
+                .iter()
+                .enumerate()
+                .map(|(i, t)| (i, contents.split_whitespace().filter(|w| w == t).count()))
+                .filter(|(_, c)| *c > 0)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn adjusts_counts_from_hunks_and_rescans_conflicts() {
+        let name = "name".to_string();
+        let age = "age".to_string();
+        let tokens = vec![&name, &age];
+        let files = vec![PathBuf::from("a.rb"), PathBuf::from("b.rb")];
+        let mut occurrences = Occurrences::new();
+        occurrences.insert(
+            "name".to_string(),
+            vec![(PathBuf::from("a.rb"), 2)].into_iter().collect(),
+        );
+        occurrences.insert("age".to_string(), HashMap::new());
+        let cache = OccurrenceCache {
+            revision: "abc123".to_string(),
+            dirty_paths: HashSet::new(),
+            files: files.iter().cloned().collect(),
+            occurrences,
+        };
+        let diffs = vec![
+            FileDiff {
+                path: PathBuf::from("a.rb"),
+                removed: vec!["name".to_string()],
+                added: vec!["age age".to_string()],
+                requires_rescan: false,
+            },
+            FileDiff {
+                path: PathBuf::from("b.rb"),
+                removed: vec!["name".to_string()],
+                added: vec![],
+                requires_rescan: false,
+            },
+        ];
+
+        let result = cache.apply(&tokens, &files, &diffs, count_words(&["name", "age"]));
+
+        assert_eq!(result.occurrences["name"][&PathBuf::from("a.rb")], 1);
+        assert_eq!(result.occurrences["age"][&PathBuf::from("a.rb")], 2);
+        assert_eq!(result.rescan, vec![PathBuf::from("b.rb")]);
+    }
+}
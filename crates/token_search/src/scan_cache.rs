@@ -0,0 +1,124 @@
+use codebase_files::writes::Writes;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+/// Per-file occurrence counts persisted between runs, keyed by a hash of each file's contents
+///
+/// Counts are indexes into the token list they were computed for, so the cache is only reused
+/// when the searched tokens are unchanged. Unlike `OccurrenceCache`, this doesn't rely on git
+/// history; any file whose contents are unchanged is skipped, wherever it lives.
+#[derive(Default, Deserialize, Serialize)]
+pub struct ScanCache {
+    tokens: String,
+    files: HashMap<String, Vec<(usize, usize)>>,
+    #[serde(skip)]
+    scanned: Mutex<HashMap<String, Vec<(usize, usize)>>>,
+}
+
+impl ScanCache {
+    /// Location of the cache within the current repository's git directory
+    pub fn default_path() -> Option<PathBuf> {
+        let output = Command::new("git")
+            .args(["rev-parse", "--git-path", "unused/scans.json"])
+            .output()
+            .ok()?;
+
+        if output.status.success() {
+            String::from_utf8(output.stdout)
+                .ok()
+                .map(|path| PathBuf::from(path.trim()))
+        } else {
+            None
+        }
+    }
+
+    /// Read a previously written cache, discarding it if it was computed for other tokens
+    pub fn read(path: &Path, tokens: &[&String]) -> Self {
+        let tokens = hash(tokens);
+
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<Self>(&contents).ok())
+            .filter(|cache| cache.tokens == tokens)
+            .unwrap_or_else(|| ScanCache {
+                tokens,
+                ..ScanCache::default()
+            })
+    }
+
+    /// Counts for `contents`, reusing the previous run's counts when the contents are unchanged
+    pub fn counts<F>(&self, contents: &str, count: F) -> Vec<(usize, usize)>
+    where
+        F: Fn(&str) -> Vec<(usize, usize)>,
+    {
+        let key = hash(contents);
+        let counts = match self.files.get(&key) {
+            Some(counts) => counts.clone(),
+            None => count(contents),
+        };
+
+        if let Ok(mut scanned) = self.scanned.lock() {
+            scanned.insert(key, counts.clone());
+        }
+
+        counts
+    }
+
+    /// Write the counts seen during this run, dropping entries for contents no longer present
+    pub fn write(self, path: &Path, writes: Writes) -> std::io::Result<()> {
+        let cache = ScanCache {
+            tokens: self.tokens,
+            files: self.scanned.into_inner().unwrap_or_default(),
+            scanned: Mutex::default(),
+        };
+
+        writes.write(path, serde_json::to_string(&cache)?)
+    }
+}
+
+fn hash<T: Hash + ?Sized>(value: &T) -> String {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn reuses_counts_for_unchanged_contents() {
+        let path = std::env::temp_dir().join("token_search_reuses_scan_cache.json");
+        let tokens = [String::from("full_name"), String::from("age")];
+        let tokens: Vec<&String> = tokens.iter().collect();
+        let scans = Cell::new(0);
+        let count = |_: &str| {
+            scans.set(scans.get() + 1);
+            vec![(0, 1)]
+        };
+
+        let cache = ScanCache::read(&path, &tokens);
+        cache.counts("def full_name", count);
+        cache.counts("def age", count);
+        cache.write(&path, Writes::Enabled).unwrap();
+
+        let cache = ScanCache::read(&path, &tokens);
+        assert_eq!(cache.counts("def full_name", count), vec![(0, 1)]);
+        assert_eq!(scans.get(), 2);
+        cache.counts("def full_name\ndef age", count);
+        assert_eq!(scans.get(), 3);
+
+        let cache = ScanCache::read(&path, &tokens[..1]);
+        cache.counts("def full_name", count);
+        fs::remove_file(&path).ok();
+
+        assert_eq!(scans.get(), 4);
+    }
+}
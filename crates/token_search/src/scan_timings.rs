@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Time spent reading and searching files, totalled by the directory containing each file
+///
+/// Set as a `TokenSearchConfig`'s `scan_timings` to find the directories a search spends its time
+/// in, such as vendored or generated code worth excluding. Files are searched in parallel, so
+/// each directory's duration is summed across threads rather than wall-clock time.
+#[derive(Debug, Default)]
+pub struct ScanTimings(Mutex<HashMap<PathBuf, DirectoryTiming>>);
+
+/// Files searched within a single directory (not counting its subdirectories), which is `.` for
+/// files at the project root
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DirectoryTiming {
+    /// Files read and searched
+    pub files: usize,
+    /// Bytes read across those files
+    pub bytes: u64,
+    /// Time spent reading and searching those files
+    pub duration: Duration,
+}
+
+impl ScanTimings {
+    /// Add the time taken reading and searching `file`, `bytes` long, to its directory's total
+    pub fn record(&self, file: &Path, bytes: usize, duration: Duration) {
+        let directory = file
+            .parent()
+            .filter(|directory| !directory.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        let mut directories = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let timing = match directories.get_mut(directory) {
+            Some(timing) => timing,
+            None => directories.entry(directory.to_path_buf()).or_default(),
+        };
+        timing.files += 1;
+        timing.bytes += bytes as u64;
+        timing.duration += duration;
+    }
+
+    /// The `limit` directories searching spent the longest in, slowest first
+    pub fn hotspots(&self, limit: usize) -> Vec<(PathBuf, DirectoryTiming)> {
+        let directories = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let mut hotspots: Vec<_> = directories
+            .iter()
+            .map(|(directory, timing)| (directory.clone(), timing.clone()))
+            .collect();
+        hotspots.sort_by(|(a, a_timing), (b, b_timing)| {
+            b_timing.duration.cmp(&a_timing.duration).then(a.cmp(b))
+        });
+        hotspots.truncate(limit);
+        hotspots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Token, TokenSearchConfig, TokenSearchResults};
+    use codebase_files::MemoryFileSystem;
+    use std::sync::Arc;
+
+    #[test]
+    fn totals_scan_time_by_directory() {
+        let file_system = MemoryFileSystem::default();
+        file_system.insert("app/models/person.rb", "def full_name\nend\n");
+        file_system.insert("app/models/order.rb", "full_name\n");
+        file_system.insert("app/views/index.erb", "full_name\n");
+        let timings = Arc::new(ScanTimings::default());
+        let config = TokenSearchConfig {
+            tokens: vec![Token::new(String::from("full_name"), Default::default())],
+            display_progress: false,
+            scan_timings: Some(timings.clone()),
+            file_system: Arc::new(file_system),
+            ..TokenSearchConfig::for_files(vec![
+                PathBuf::from("app/models/person.rb"),
+                PathBuf::from("app/models/order.rb"),
+                PathBuf::from("app/views/index.erb"),
+            ])
+        };
+
+        TokenSearchResults::generate_with_config(&config);
+
+        let mut hotspots = timings.hotspots(10);
+        hotspots.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let totals: Vec<_> = hotspots
+            .iter()
+            .map(|(directory, timing)| (directory.to_str().unwrap(), timing.files, timing.bytes))
+            .collect();
+        assert_eq!(totals, vec![("app/models", 2, 28), ("app/views", 1, 10)]);
+        assert_eq!(timings.hotspots(1).len(), 1);
+    }
+
+    #[test]
+    fn orders_hotspots_slowest_first() {
+        let timings = ScanTimings::default();
+        timings.record(Path::new("lib/fast.rb"), 10, Duration::from_millis(1));
+        timings.record(Path::new("vendor/slow.js"), 100, Duration::from_millis(5));
+        timings.record(Path::new("vendor/slower.js"), 100, Duration::from_millis(7));
+        timings.record(Path::new("root.rb"), 10, Duration::from_millis(3));
+
+        let hotspots: Vec<_> = timings
+            .hotspots(2)
+            .into_iter()
+            .map(|(directory, timing)| (directory, timing.files, timing.duration))
+            .collect();
+
+        assert_eq!(
+            hotspots,
+            vec![
+                (PathBuf::from("vendor"), 2, Duration::from_millis(12)),
+                (PathBuf::from("."), 1, Duration::from_millis(3)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use super::token::Token;
+use super::token_search::{TokenSearchConfig, TokenSearchResult, TokenSearchResults};
+use codebase_files::text::is_identifier_char;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+// Written between a receiver and the member called on it, longest first
+const ACCESSORS: [&str; 5] = ["&.", "?.", "::", "->", "."];
+
+/// Occurrences of a token defined in several scopes (such as `build` defined in ten classes),
+/// attributed to the scope each one refers to
+///
+/// An occurrence is attributed by its receiver: `Person.build`, `Person.new.build`, or a variable
+/// named after the scope (`person.build`, `@order_item.build`). Occurrences without a receiver
+/// (or on `self`) are attributed to the scope defining the token in the same file, when there's
+/// only one. Any other occurrence could refer to any of the scopes, and is counted as ambiguous.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ScopeAttribution {
+    /// Occurrences attributed to each scope defining the token, excluding the definitions
+    pub scopes: BTreeMap<String, usize>,
+    /// Occurrences that couldn't be attributed to a single scope
+    pub ambiguous: usize,
+}
+
+// Whatever an occurrence is called on
+#[derive(Debug, PartialEq)]
+enum Receiver<'a> {
+    /// Nothing, as in a bare call from within the scope
+    Implicit,
+    /// `self` or `this`
+    SelfReference,
+    /// A constant or variable
+    Named(&'a str),
+    /// Something else, such as the result of a method call
+    Unknown,
+}
+
+impl ScopeAttribution {
+    /// An empty attribution for `token`, or `None` unless every definition is within a scope and
+    /// there are at least two scopes
+    pub fn for_token(token: &Token) -> Option<Self> {
+        let scopes: Option<BTreeMap<String, usize>> = token
+            .definitions
+            .iter()
+            .map(|definition| definition.scope().map(|scope| (scope.to_string(), 0)))
+            .collect();
+
+        scopes
+            .filter(|scopes| scopes.len() > 1)
+            .map(|scopes| ScopeAttribution {
+                scopes,
+                ambiguous: 0,
+            })
+    }
+
+    /// Scopes no occurrence refers to, only listed when every occurrence could be attributed
+    pub fn unused_scopes(&self) -> Vec<&str> {
+        if self.ambiguous > 0 {
+            return vec![];
+        }
+
+        self.scopes
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(scope, _)| scope.as_str())
+            .collect()
+    }
+
+    /// Scopes with at least one occurrence attributed to them
+    pub fn used_scopes(&self) -> Vec<&str> {
+        self.scopes
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(scope, _)| scope.as_str())
+            .collect()
+    }
+
+    fn attribute(&mut self, token: &Token, path: &Path, contents: &str) {
+        let definitions: Vec<_> = token
+            .definitions
+            .iter()
+            .filter(|definition| &*definition.file_path == path)
+            .collect();
+        let definition_lines: HashSet<usize> = definitions
+            .iter()
+            .filter_map(|definition| definition.resolve_line(contents))
+            .collect();
+        let local_scopes: HashSet<&str> = definitions
+            .iter()
+            .filter_map(|definition| definition.scope())
+            .collect();
+
+        let mut line = 1;
+        let mut counted_to = 0;
+        for (start, _) in contents.match_indices(token.token.as_str()) {
+            if !TokenSearchResults::word_bounded(contents, start, start + token.token.len()) {
+                continue;
+            }
+            line += contents[counted_to..start].matches('\n').count();
+            counted_to = start;
+            if definition_lines.contains(&line) {
+                continue;
+            }
+
+            let scope = match receiver(&contents[..start]) {
+                Receiver::Implicit | Receiver::SelfReference if local_scopes.len() == 1 => {
+                    local_scopes.iter().next().copied()
+                }
+                Receiver::Named(name) => self.scope_named(name),
+                _ => None,
+            }
+            .map(String::from);
+            match scope.and_then(|scope| self.scopes.get_mut(&scope)) {
+                Some(count) => *count += 1,
+                None => self.ambiguous += 1,
+            }
+        }
+    }
+
+    // The only scope whose name (its last segment, e.g. `Billing` for `Admin::Billing`) matches
+    // `name` ignoring case and underscores, so `order_item` names `OrderItem`
+    fn scope_named(&self, name: &str) -> Option<&str> {
+        let name = normalize(name);
+        let mut matching = self.scopes.keys().filter(|scope| {
+            let last = scope.rsplit([':', '.', '#', '/']).next().unwrap_or(scope);
+            normalize(last) == name
+        });
+
+        match (matching.next(), matching.next()) {
+            (Some(scope), None) => Some(scope),
+            _ => None,
+        }
+    }
+}
+
+/// Attribute the occurrences of tokens defined in several scopes to those scopes, reading each
+/// file they occur in once
+pub(crate) fn attribute(config: &TokenSearchConfig, results: &mut [TokenSearchResult]) {
+    let mut attributions: HashMap<usize, ScopeAttribution> = results
+        .iter()
+        .enumerate()
+        .filter_map(|(index, result)| {
+            ScopeAttribution::for_token(&result.token).map(|attribution| (index, attribution))
+        })
+        .collect();
+    tracing::info!(
+        "Attributing occurrences of {} tokens defined in several scopes",
+        attributions.len()
+    );
+
+    let attributed: Vec<(usize, ScopeAttribution)> = {
+        let results = &*results;
+        let mut by_path: HashMap<&Path, Vec<usize>> = HashMap::new();
+        for index in attributions.keys() {
+            for path in results[*index].occurrences.keys() {
+                by_path.entry(path).or_default().push(*index);
+            }
+        }
+        let attributions = &attributions;
+
+        by_path
+            .par_iter()
+            .flat_map_iter(|(path, indices)| {
+                let contents = match config.content_overrides.get(*path) {
+                    Some(contents) => Ok(contents.clone()),
+                    None => TokenSearchResults::read_file(config, path),
+                };
+
+                indices.iter().filter_map(move |index| {
+                    let contents = contents.as_ref().ok()?;
+                    let mut attribution = attributions.get(index)?.clone();
+                    attribution.scopes.values_mut().for_each(|count| *count = 0);
+                    attribution.attribute(&results[*index].token, path, contents);
+                    Some((*index, attribution))
+                })
+            })
+            .collect()
+    };
+
+    for (index, partial) in attributed {
+        if let Some(attribution) = attributions.get_mut(&index) {
+            for (scope, count) in partial.scopes {
+                *attribution.scopes.entry(scope).or_default() += count;
+            }
+            attribution.ambiguous += partial.ambiguous;
+        }
+    }
+    for (index, attribution) in attributions {
+        results[index].scopes = Some(attribution);
+    }
+}
+
+fn receiver(before: &str) -> Receiver<'_> {
+    let before = before.trim_end();
+    let rest = match ACCESSORS
+        .iter()
+        .find_map(|accessor| before.strip_suffix(accessor))
+    {
+        Some(rest) => rest.trim_end(),
+        None => return Receiver::Implicit,
+    };
+    // `Person.new(name).build` calls `build` on a `Person` too, so arguments to `new` are skipped
+    let called = rest.ends_with(')');
+    let rest = without_arguments(rest);
+    let identifier = trailing_identifier(rest);
+
+    match identifier {
+        "" => Receiver::Unknown,
+        _ if called && identifier != "new" => Receiver::Unknown,
+        "self" | "this" | "@" => Receiver::SelfReference,
+        // `Person.new.build` calls `build` on a `Person`
+        "new" => match receiver(&rest[..rest.len() - identifier.len()]) {
+            Receiver::Named(name) => Receiver::Named(name),
+            _ => Receiver::Unknown,
+        },
+        name => Receiver::Named(name.trim_start_matches(['@', '$'])),
+    }
+}
+
+// `text` before the parenthesized arguments it ends with, if any
+fn without_arguments(text: &str) -> &str {
+    if !text.ends_with(')') {
+        return text;
+    }
+
+    let mut depth = 0;
+    for (index, c) in text.char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' if depth == 1 => return text[..index].trim_end(),
+            '(' => depth -= 1,
+            _ => {}
+        }
+    }
+    text
+}
+
+fn trailing_identifier(text: &str) -> &str {
+    let start = text
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_identifier_char(*c) || matches!(c, '@' | '$'))
+        .last()
+        .map_or(text.len(), |(index, _)| index);
+
+    &text[start..]
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use read_ctags::CtagItem;
+    use std::path::PathBuf;
+
+    fn token(definitions: &[(&str, &str, usize)]) -> Token {
+        let contents = definitions
+            .iter()
+            .map(|(path, scope, line)| format!("build\t{}\t{};\"\tf\tclass:{}", path, line, scope))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let tags = CtagItem::parse(PathBuf::from("tags"), &contents)
+            .unwrap()
+            .tags;
+
+        Token::new(String::from("build"), tags.into_iter().collect())
+    }
+
+    #[test]
+    fn attributes_occurrences_by_receiver() {
+        let token = token(&[
+            ("app/order.rb", "Order", 2),
+            ("app/invoice.rb", "Invoice", 2),
+            ("app/admin/order_item.rb", "Admin::OrderItem", 2),
+        ]);
+        let mut attribution = ScopeAttribution::for_token(&token).unwrap();
+
+        attribution.attribute(
+            &token,
+            Path::new("app/order.rb"),
+            "class Order\n  def build\n    self.build && build\n  end\nend\n",
+        );
+        attribution.attribute(
+            &token,
+            Path::new("app/checkout.rb"),
+            "Order.build\nOrder.new(total: 1).build\n@order_item&.build\n",
+        );
+
+        assert_eq!(
+            attribution.scopes,
+            BTreeMap::from([
+                (String::from("Admin::OrderItem"), 1),
+                (String::from("Invoice"), 0),
+                (String::from("Order"), 4),
+            ])
+        );
+        assert_eq!(attribution.unused_scopes(), vec!["Invoice"]);
+
+        attribution.attribute(
+            &token,
+            Path::new("app/checkout.rb"),
+            "builder.build\nbuild\nfind(1).build\n",
+        );
+
+        assert_eq!(attribution.ambiguous, 3);
+        assert!(attribution.unused_scopes().is_empty());
+        assert!(
+            ScopeAttribution::for_token(&self::token(&[("app/order.rb", "Order", 2)])).is_none()
+        );
+    }
+}
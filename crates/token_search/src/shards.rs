@@ -0,0 +1,241 @@
+use super::token_search::{TokenSearchConfig, TokenSearchResults};
+use aho_corasick::{AhoCorasickBuilder, MatchKind};
+use codebase_files::writes::Writes;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Estimated bytes the automaton holds for each byte of the tokens it searches for
+const AUTOMATON_BYTES_PER_BYTE: u64 = 64;
+
+// Estimated bytes held for each file a token occurs in, beyond the file's path
+const BYTES_PER_COUNT: u64 = 64;
+
+// Distinguishes spill files written by searches running at once within a process
+static SPILLS: AtomicUsize = AtomicUsize::new(0);
+
+type Counts = HashMap<usize, HashMap<PathBuf, usize>>;
+
+/// Search for `tokens` in shards, holding roughly `limit` bytes of automata and counts at once
+///
+/// Tokens are split into shards whose automaton fits in half the limit, which are searched one
+/// after another, reading every file once per shard. Tokens are sharded in sorted order, so
+/// tokens sharing a prefix (such as `valid` and `valid?`) are usually searched together and
+/// matched as they would be otherwise. Counts are spilled to a temporary file whenever those
+/// held exceed the other half of the limit, and read back once every shard is searched. When
+/// writes are disabled, counts are held in memory instead, whatever the limit.
+pub(crate) fn scan(
+    config: &TokenSearchConfig,
+    tokens: &[&String],
+    limit: u64,
+) -> io::Result<Counts> {
+    let shards = shards(tokens, limit / 2);
+    tracing::info!(
+        "Searching {} tokens in {} shard(s) to stay within {} bytes",
+        tokens.len(),
+        shards.len(),
+        limit
+    );
+
+    let mut spill = Spill::new(config.writes);
+    let mut held = Counts::new();
+    let mut held_bytes = 0;
+
+    for (number, shard) in shards.iter().enumerate() {
+        tracing::debug!(
+            "Searching shard {} of {} ({} tokens)",
+            number + 1,
+            shards.len(),
+            shard.len()
+        );
+        let patterns: Vec<&String> = shard.iter().map(|index| tokens[*index]).collect();
+        let ac = AhoCorasickBuilder::new()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(&patterns);
+
+        for (index, by_path) in TokenSearchResults::scan(config, &ac, &config.files, None) {
+            held_bytes += by_path.keys().map(|path| count_bytes(path)).sum::<u64>();
+            held.insert(shard[index], by_path);
+        }
+
+        if held_bytes > limit / 2 && !config.writes.enabled() {
+            if !spill.refused {
+                tracing::warn!(
+                    "Holding counts in memory beyond the {}-byte limit, since writes are disabled",
+                    limit
+                );
+                spill.refused = true;
+            }
+        } else if held_bytes > limit / 2 {
+            match spill.write(&held) {
+                Ok(()) => {
+                    held.clear();
+                    held_bytes = 0;
+                }
+                Err(e) => tracing::warn!("Unable to spill counts to disk: {}", e),
+            }
+        }
+    }
+
+    spill.read_into(&mut held)?;
+    Ok(held)
+}
+
+// Indexes of `tokens`, in sorted order, grouped so each group's automaton fits in `budget`
+fn shards(tokens: &[&String], budget: u64) -> Vec<Vec<usize>> {
+    let mut sorted: Vec<usize> = (0..tokens.len()).collect();
+    sorted.sort_by_key(|index| tokens[*index]);
+
+    let mut shards: Vec<Vec<usize>> = vec![];
+    let mut shard_bytes = 0;
+    for index in sorted {
+        let bytes = tokens[index].len() as u64 * AUTOMATON_BYTES_PER_BYTE;
+        match shards.last_mut() {
+            Some(shard) if shard_bytes + bytes <= budget => {
+                shard.push(index);
+                shard_bytes += bytes;
+            }
+            _ => {
+                shards.push(vec![index]);
+                shard_bytes = bytes;
+            }
+        }
+    }
+    shards
+}
+
+fn count_bytes(path: &Path) -> u64 {
+    path.as_os_str().len() as u64 + BYTES_PER_COUNT
+}
+
+// Counts written to a temporary file as JSON lines, one token's counts per line; the file is
+// removed when dropped
+struct Spill {
+    path: PathBuf,
+    writes: Writes,
+    writer: Option<BufWriter<File>>,
+    created: bool,
+    // Whether spilling was skipped because writes are disabled
+    refused: bool,
+}
+
+impl Spill {
+    fn new(writes: Writes) -> Self {
+        let name = format!(
+            "unused-counts-{}-{}.jsonl",
+            process::id(),
+            SPILLS.fetch_add(1, Ordering::Relaxed)
+        );
+        Spill {
+            path: std::env::temp_dir().join(name),
+            writes,
+            writer: None,
+            created: false,
+            refused: false,
+        }
+    }
+
+    fn write(&mut self, counts: &Counts) -> io::Result<()> {
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => {
+                let file = self.writes.create(&self.path)?;
+                self.created = true;
+                self.writer.insert(BufWriter::new(file))
+            }
+        };
+        for entry in counts {
+            serde_json::to_writer(&mut *writer, &entry)?;
+            writeln!(writer)?;
+        }
+        tracing::debug!(
+            "Spilled counts of {} tokens to {}",
+            counts.len(),
+            self.path.display()
+        );
+        writer.flush()
+    }
+
+    fn read_into(&mut self, counts: &mut Counts) -> io::Result<()> {
+        if self.writer.take().is_none() {
+            return Ok(());
+        }
+
+        for line in BufReader::new(File::open(&self.path)?).lines() {
+            let (index, by_path): (usize, HashMap<PathBuf, usize>) = serde_json::from_str(&line?)?;
+            counts.insert(index, by_path);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        if self.created {
+            self.writes.remove(&self.path).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn shards_tokens_in_sorted_order_within_the_budget() {
+        let (valid, name, valid_q) = (
+            String::from("valid"),
+            String::from("name"),
+            String::from("valid?"),
+        );
+        let tokens = vec![&valid, &name, &valid_q];
+
+        assert_eq!(
+            shards(&tokens, 11 * AUTOMATON_BYTES_PER_BYTE),
+            vec![vec![1, 0], vec![2]]
+        );
+        assert_eq!(shards(&tokens, 0), vec![vec![1], vec![0], vec![2]]);
+    }
+
+    #[test]
+    fn matches_unsharded_counts_when_spilling() {
+        let file_system = codebase_files::MemoryFileSystem::default();
+        file_system.insert("app/person.rb", "def full_name\nend\ndef age\nend\n");
+        file_system.insert("app/report.rb", "person.full_name\nperson.age\n");
+        let config = TokenSearchConfig {
+            display_progress: false,
+            file_system: Arc::new(file_system),
+            ..TokenSearchConfig::for_files(vec![
+                PathBuf::from("app/person.rb"),
+                PathBuf::from("app/report.rb"),
+            ])
+        };
+        let (full_name, age) = (String::from("full_name"), String::from("age"));
+        let tokens = [&full_name, &age];
+
+        let unsharded = TokenSearchResults::scan(
+            &config,
+            &AhoCorasickBuilder::new()
+                .match_kind(MatchKind::LeftmostLongest)
+                .build(tokens),
+            &config.files,
+            None,
+        );
+        assert_eq!(scan(&config, &tokens, 0).unwrap(), unsharded);
+        assert_eq!(unsharded.len(), 2);
+    }
+
+    #[test]
+    fn returns_errors_reading_spilled_counts() {
+        let mut spill = Spill::new(Writes::Enabled);
+        let counts = Counts::from([(0, HashMap::from([(PathBuf::from("app/person.rb"), 1)]))]);
+        spill.write(&counts).unwrap();
+        std::fs::write(&spill.path, "not json\n").unwrap();
+
+        assert!(spill.read_into(&mut Counts::new()).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+use super::token_search::{TokenSearchConfig, TokenSearchResults};
+use aho_corasick::AhoCorasick;
+use read_ctags::Language;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Occurrences of a token within a file's comments and string literals
+///
+/// These are counted when searching with `syntax_aware`. Occurrences in comments aren't counted
+/// as occurrences of the token, while those in string literals still are, since they may be
+/// dynamic references (such as `send("name")`) or interpolate code.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mentions {
+    /// Occurrences within comments
+    pub comments: usize,
+    /// Occurrences within string literals
+    pub strings: usize,
+}
+
+/// What part of a file's syntax an offset falls in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Region {
+    /// Code, or anything not recognized as a comment or string literal
+    Code,
+    /// A line or block comment
+    Comment,
+    /// A string literal, including any code interpolated within it
+    String,
+}
+
+// Delimiters recognized by a lightweight lexer for one language; strings quoted with a single
+// `"` or `'` end at the end of the line, so an apostrophe that isn't a quote can't swallow the
+// rest of the file
+struct Syntax {
+    line_comments: &'static [&'static str],
+    block_comments: &'static [(&'static str, &'static str)],
+    strings: &'static [&'static str],
+    // Line comments only start at the beginning of a word, as in shell (`${#list[@]}`)
+    comments_start_words: bool,
+}
+
+const HASH: Syntax = Syntax {
+    line_comments: &["#"],
+    block_comments: &[],
+    strings: &["\"", "'"],
+    comments_start_words: false,
+};
+
+const C_LIKE: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    strings: &["`", "\"", "'"],
+    comments_start_words: false,
+};
+
+fn syntax(language: Language) -> Option<Syntax> {
+    match language {
+        Language::Ruby => Some(Syntax {
+            block_comments: &[("\n=begin", "\n=end")],
+            strings: &["`", "\"", "'"],
+            ..HASH
+        }),
+        Language::Python => Some(Syntax {
+            strings: &["\"\"\"", "'''", "\"", "'"],
+            ..HASH
+        }),
+        Language::Elixir => Some(Syntax {
+            strings: &["\"\"\"", "\""],
+            ..HASH
+        }),
+        Language::Sh => Some(Syntax {
+            comments_start_words: true,
+            ..HASH
+        }),
+        Language::JavaScript | Language::TypeScript => Some(C_LIKE),
+        // Single quotes start lifetimes as often as character literals
+        Language::Rust => Some(Syntax {
+            strings: &["\""],
+            ..C_LIKE
+        }),
+        Language::SCSS => Some(Syntax {
+            strings: &["\"", "'"],
+            ..C_LIKE
+        }),
+        Language::CSS => Some(Syntax {
+            line_comments: &[],
+            strings: &["\"", "'"],
+            ..C_LIKE
+        }),
+        Language::Elm => Some(Syntax {
+            line_comments: &["--"],
+            block_comments: &[("{-", "-}")],
+            strings: &["\"\"\"", "\""],
+            comments_start_words: false,
+        }),
+        // Attribute values (such as class names) reference tokens, so only comments are skipped
+        Language::HTML | Language::XML | Language::SVG => Some(Syntax {
+            line_comments: &[],
+            block_comments: &[("<!--", "-->")],
+            strings: &[],
+            comments_start_words: false,
+        }),
+        Language::JSON | Language::Markdown => None,
+    }
+}
+
+/// The comments and string literals within a file, found by a lightweight lexer for its
+/// language
+///
+/// Files in languages without a lexer (or no known language) are treated as code throughout.
+pub struct Regions(Vec<(usize, usize, Region)>);
+
+impl Regions {
+    /// Find comments and string literals in `contents`, read as `language`
+    pub fn lex(language: Option<Language>, contents: &str) -> Self {
+        match language.and_then(syntax) {
+            Some(syntax) => Regions(spans(&syntax, contents)),
+            None => Regions(vec![]),
+        }
+    }
+
+    /// The region the byte at `offset` falls in
+    pub fn at(&self, offset: usize) -> Region {
+        let index = self.0.partition_point(|(_, end, _)| *end <= offset);
+        match self.0.get(index) {
+            Some((start, _, region)) if *start <= offset => *region,
+            _ => Region::Code,
+        }
+    }
+}
+
+// Spans of comments and strings, in order; delimiters are ASCII, so comparing bytes never
+// splits a character
+fn spans(syntax: &Syntax, contents: &str) -> Vec<(usize, usize, Region)> {
+    let bytes = contents.as_bytes();
+    let starts = |at: usize, delimiter: &str| bytes[at..].starts_with(delimiter.as_bytes());
+    let find = |from: usize, delimiter: &str| {
+        contents[from..]
+            .find(delimiter)
+            .map(|found| from + found + delimiter.len())
+            .unwrap_or(bytes.len())
+    };
+    let mut spans = vec![];
+    let mut at = 0;
+
+    while at < bytes.len() {
+        let word_start = at == 0 || bytes[at - 1].is_ascii_whitespace();
+        if (word_start || !syntax.comments_start_words)
+            && syntax.line_comments.iter().any(|c| starts(at, c))
+        {
+            let end = contents[at..]
+                .find('\n')
+                .map_or(bytes.len(), |found| at + found);
+            spans.push((at, end, Region::Comment));
+            at = end;
+        } else if let Some((open, close)) = syntax
+            .block_comments
+            .iter()
+            .find(|(open, _)| starts(at, open))
+        {
+            let end = find(at + open.len(), close);
+            spans.push((at, end, Region::Comment));
+            at = end;
+        } else if let Some(quote) = syntax.strings.iter().find(|q| starts(at, q)) {
+            let end = string_end(bytes, at + quote.len(), quote);
+            spans.push((at, end, Region::String));
+            at = end;
+        } else {
+            at += 1;
+        }
+    }
+
+    spans
+}
+
+fn string_end(bytes: &[u8], from: usize, quote: &str) -> usize {
+    let single_line = quote == "\"" || quote == "'";
+    let mut at = from;
+
+    while at < bytes.len() {
+        if bytes[at] == b'\\' {
+            at += 2;
+        } else if bytes[at..].starts_with(quote.as_bytes()) {
+            return at + quote.len();
+        } else if single_line && bytes[at] == b'\n' {
+            return at;
+        } else {
+            at += 1;
+        }
+    }
+
+    bytes.len()
+}
+
+type Counts = HashMap<usize, HashMap<PathBuf, usize>>;
+type Mentioned = HashMap<usize, HashMap<PathBuf, Mentions>>;
+
+/// Search every file, telling occurrences in code, comments, and string literals apart
+///
+/// Counts for each file are the occurrences outside comments, along with any mentions.
+pub(crate) fn scan(config: &TokenSearchConfig, ac: &AhoCorasick) -> (Counts, Mentioned) {
+    let scanned = TokenSearchResults::scan_with(config, ac, &config.files, |path, contents| {
+        counts(
+            ac,
+            config.language_mappings.detect(path, contents),
+            contents,
+        )
+    });
+    let mut counts = Counts::new();
+    let mut mentioned = Mentioned::new();
+
+    for (index, by_path) in scanned {
+        for (path, (count, mentions)) in by_path {
+            if count > 0 {
+                counts.entry(index).or_default().insert(path.clone(), count);
+            }
+            if mentions != Mentions::default() {
+                mentioned.entry(index).or_default().insert(path, mentions);
+            }
+        }
+    }
+
+    (counts, mentioned)
+}
+
+fn counts(
+    ac: &AhoCorasick,
+    language: Option<Language>,
+    contents: &str,
+) -> Vec<(usize, (usize, Mentions))> {
+    let regions = Regions::lex(language, contents);
+    let mut counts: HashMap<usize, (usize, Mentions)> = HashMap::new();
+
+    for found in TokenSearchResults::word_bounded_matches(ac, contents) {
+        let (count, mentions) = counts.entry(found.pattern()).or_default();
+        match regions.at(found.start()) {
+            Region::Code => *count += 1,
+            Region::Comment => mentions.comments += 1,
+            Region::String => {
+                *count += 1;
+                mentions.strings += 1;
+            }
+        }
+    }
+
+    counts.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regions(language: Language, contents: &str, token: &str) -> Vec<Region> {
+        let regions = Regions::lex(Some(language), contents);
+        contents
+            .match_indices(token)
+            .map(|(offset, _)| regions.at(offset))
+            .collect()
+    }
+
+    #[test]
+    fn lexes_comments_and_strings() {
+        use Region::*;
+
+        assert_eq!(
+            regions(
+                Language::Ruby,
+                "# full_name is memoized\nputs \"#{full_name}\" # full_name\nfull_name\n",
+                "full_name"
+            ),
+            vec![Comment, String, Comment, Code]
+        );
+        assert_eq!(
+            regions(
+                Language::Ruby,
+                "x\n=begin\nfull_name\n=end\nfull_name",
+                "full_name"
+            ),
+            vec![Comment, Code]
+        );
+        assert_eq!(
+            regions(
+                Language::JavaScript,
+                "/* renderView */ const a = 'it\\'s renderView'; // renderView\nrenderView()",
+                "renderView"
+            ),
+            vec![Comment, String, Comment, Code]
+        );
+        assert_eq!(
+            regions(
+                Language::Rust,
+                "fn f<'a>(x: &'a str) { full_name(x) } // full_name",
+                "full_name"
+            ),
+            vec![Code, Comment]
+        );
+        assert_eq!(
+            regions(Language::Sh, "echo ${#full_name}\n# full_name", "full_name"),
+            vec![Code, Comment]
+        );
+        assert_eq!(
+            regions(
+                Language::Python,
+                "\"\"\"\nfull_name\n\"\"\"\nfull_name",
+                "full_name"
+            ),
+            vec![String, Code]
+        );
+        assert_eq!(
+            regions(Language::JSON, "{\"full_name\": 1}", "full_name"),
+            vec![Code]
+        );
+    }
+}
@@ -1,8 +1,15 @@
+use codebase_files::paths::{self, PathLookup};
+use codebase_files::{text, DiskFileSystem};
 use itertools::Itertools;
-use read_ctags::{CtagItem, Language, ReadCtagsError, TagsReader};
+use read_ctags::{
+    CscopeReader, CtagItem, FallbackTags, GlobalTags, Language, LanguageMappings, ReadCtagsError,
+    TagsFile, TagsReader,
+};
 use serde::Serialize;
-use std::collections::HashSet;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// A token based on a set of `CtagItem`s
 #[derive(Clone, Serialize)]
@@ -12,7 +19,7 @@ pub struct Token {
     /// The set of `CtagItem`s that compose the token
     pub definitions: HashSet<CtagItem>,
     /// The paths where a token is defined
-    pub defined_paths: HashSet<PathBuf>,
+    pub defined_paths: HashSet<Arc<Path>>,
 }
 
 impl Token {
@@ -30,9 +37,31 @@ impl Token {
         }
     }
 
+    /// This token with definitions in files `mappings` map to a language read as that language
+    pub fn with_language_mappings(self, mappings: &LanguageMappings) -> Token {
+        let definitions = self
+            .definitions
+            .into_iter()
+            .map(|definition| match mappings.get(&definition.file_path) {
+                Some(language) => definition.with_language(Some(language)),
+                None => definition,
+            })
+            .collect();
+
+        Token {
+            definitions,
+            ..self
+        }
+    }
+
     /// Load tokens after reading tags
     pub fn all() -> Result<(PathBuf, Vec<Token>), ReadCtagsError> {
-        TagsReader::default().load().map(|tags_file| {
+        Self::from_reader(&TagsReader::default())
+    }
+
+    /// Load tokens from the first tags file `reader` finds
+    pub fn from_reader(reader: &TagsReader) -> Result<(PathBuf, Vec<Token>), ReadCtagsError> {
+        reader.load().map(|tags_file| {
             (
                 tags_file.path,
                 Self::build_tokens_from_outcome(tags_file.tags),
@@ -40,9 +69,105 @@ impl Token {
         })
     }
 
-    /// Provide the first path in the list of defined paths
-    pub fn first_path(&self) -> &PathBuf {
-        self.defined_paths.iter().nth(0).unwrap()
+    /// Load tokens from a specific tags file
+    pub fn from_tags_path(path: PathBuf) -> Result<Vec<Token>, ReadCtagsError> {
+        let contents =
+            text::read(&path).map_err(|e| ReadCtagsError::NoCtagsFile(vec![path.clone()], e))?;
+        let tags_file = CtagItem::parse(path, &contents)?.detect_languages(&DiskFileSystem);
+
+        Ok(Self::build_tokens_from_outcome(tags_file.tags))
+    }
+
+    /// Load tokens from a tags file in another directory, resolving relative definition paths
+    /// against the tags file's directory, where ctags is conventionally run
+    pub fn from_nested_tags_path(path: PathBuf) -> Result<Vec<Token>, ReadCtagsError> {
+        let contents =
+            text::read(&path).map_err(|e| ReadCtagsError::NoCtagsFile(vec![path.clone()], e))?;
+        let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let tags_file = CtagItem::parse(path, &contents)?;
+        let tags_file = TagsFile {
+            tags: tags_file
+                .tags
+                .into_iter()
+                .map(|tag| CtagItem {
+                    file_path: tag.absolute_path(&directory).into(),
+                    ..tag
+                })
+                .collect(),
+            ..tags_file
+        };
+
+        Ok(Self::build_tokens_from_outcome(
+            tags_file.detect_languages(&DiskFileSystem).tags,
+        ))
+    }
+
+    /// Load tokens from a cscope database, for projects that don't keep a tags file
+    pub fn from_cscope() -> Result<(PathBuf, Vec<Token>), ReadCtagsError> {
+        CscopeReader::default().load().map(|tags_file| {
+            (
+                tags_file.path,
+                Self::build_tokens_from_outcome(tags_file.tags),
+            )
+        })
+    }
+
+    /// Load tokens from a GNU GLOBAL GTAGS database, for projects indexed with `gtags`
+    pub fn from_gtags() -> Result<(PathBuf, Vec<Token>), ReadCtagsError> {
+        GlobalTags::default().load().map(|tags_file| {
+            (
+                tags_file.path,
+                Self::build_tokens_from_outcome(tags_file.tags),
+            )
+        })
+    }
+
+    /// Load tokens by extracting definitions from the provided files with regular expressions
+    ///
+    /// This is a degraded alternative to `Token::all` for when no tags file is available.
+    pub fn fallback(files: &[PathBuf]) -> Vec<Token> {
+        Self::build_tokens_from_outcome(FallbackTags::default().extract(files).tags)
+    }
+
+    /// Load the translation keys defined in locale files among the provided files, such as Rails'
+    /// `config/locales/en.yml`, named by their dotted path (e.g. `users.show.title`)
+    pub fn translation_keys(files: &[PathBuf]) -> Vec<Token> {
+        Self::build_tokens_from_outcome(read_ctags::translation_keys(files))
+    }
+
+    /// Provide the first of the defined paths, in path order
+    pub fn first_path(&self) -> &Path {
+        self.defined_paths.iter().min().unwrap()
+    }
+
+    /// Definitions ordered by path, then line, then name, so they're reported in the same order
+    /// between runs
+    pub fn ordered_definitions(&self) -> Vec<&CtagItem> {
+        let mut definitions: Vec<&CtagItem> = self.definitions.iter().collect();
+        definitions.sort_by(|a, b| a.cmp_location(b));
+        definitions
+    }
+
+    /// The first definition, in the order of `ordered_definitions`
+    pub fn first_definition(&self) -> Option<&CtagItem> {
+        self.definitions.iter().min_by(|a, b| a.cmp_location(b))
+    }
+
+    /// How many places in each file the token is defined
+    ///
+    /// Definitions at the same address (such as a tag and its qualified sibling, or the same
+    /// definition read from several tags files) are one site, written once in the file.
+    pub fn definition_sites(&self) -> HashMap<&Path, usize> {
+        let mut sites: HashMap<&Path, usize> = HashMap::new();
+        for (path, _) in self
+            .definitions
+            .iter()
+            .map(|d| (&*d.file_path, d.address.as_str()))
+            .unique()
+        {
+            *sites.entry(path).or_default() += 1;
+        }
+        sites
     }
 
     /// All languages based on matched `CtagItem`s
@@ -53,14 +178,43 @@ impl Token {
     /// Do all `CtagItem`s meet a particular constraint?
     pub fn only_ctag<F>(&self, check: F) -> bool
     where
-        F: FnOnce(&CtagItem) -> bool + Copy,
+        F: Fn(&CtagItem) -> bool,
     {
-        self.definitions.iter().all(|ct| check(ct))
+        self.definitions.iter().all(check)
     }
 
-    fn build_tokens_from_outcome(outcome: HashSet<CtagItem>) -> Vec<Token> {
-        outcome
+    /// This token, with definition paths referring to files in `lookup` by the path found in the
+    /// codebase (such as paths differing only in case on case-insensitive filesystems)
+    pub fn resolve_paths(self, lookup: &PathLookup) -> Self {
+        if self
+            .defined_paths
+            .iter()
+            .all(|path| lookup.resolve(path) == **path)
+        {
+            return self;
+        }
+
+        let definitions = self
+            .definitions
             .into_iter()
+            .map(|definition| CtagItem {
+                file_path: lookup.resolve(&definition.file_path).into(),
+                ..definition
+            })
+            .collect();
+        Token::new(self.token, definitions)
+    }
+
+    /// Group tags into tokens by name, as written
+    ///
+    /// Tags differing only by leading `#` or `.` (as written for RSpec examples of instance and
+    /// class methods) belong to the same token. Qualified tags (written with `--extras=+q`) are
+    /// dropped when the tag they qualify is also present, so each definition is reported once.
+    pub fn from_tags(tags: HashSet<CtagItem>) -> Vec<Token> {
+        let qualifying = Self::qualifying_tags(&tags);
+
+        tags.into_iter()
+            .filter(|ct| !qualifying.contains(ct))
             .sorted_by_key(|ct| Self::strip_prepended_punctuation(&ct.name))
             .group_by(|ct| Self::strip_prepended_punctuation(&ct.name))
             .into_iter()
@@ -68,10 +222,53 @@ impl Token {
             .collect()
     }
 
+    // Qualified tags duplicating an unqualified tag for the same definition
+    fn qualifying_tags(tags: &HashSet<CtagItem>) -> HashSet<CtagItem> {
+        let unqualified: HashSet<(&str, &Path, &str)> = tags
+            .iter()
+            .filter(|ct| !ct.is_qualified())
+            .map(|ct| (ct.name.as_str(), &*ct.file_path, ct.address.as_str()))
+            .collect();
+
+        tags.iter()
+            .filter(|ct| {
+                ct.is_qualified()
+                    && unqualified.contains(&(
+                        ct.unqualified_name(),
+                        &*ct.file_path,
+                        ct.address.as_str(),
+                    ))
+            })
+            .cloned()
+            .collect()
+    }
+
+    // Absolute definition paths (from tags generated with absolute paths) are made relative to
+    // the current directory, like the files searched
+    fn build_tokens_from_outcome(outcome: HashSet<CtagItem>) -> Vec<Token> {
+        let cwd = env::current_dir().ok();
+
+        Self::from_tags(
+            outcome
+                .into_iter()
+                .map(|tag| {
+                    match cwd
+                        .as_deref()
+                        .and_then(|cwd| paths::relative_to(&tag.file_path, cwd))
+                    {
+                        Some(file_path) => CtagItem {
+                            file_path: file_path.into(),
+                            ..tag
+                        },
+                        None => tag,
+                    }
+                })
+                .collect(),
+        )
+    }
+
     fn strip_prepended_punctuation(input: &str) -> String {
-        input
-            .trim_start_matches(|c| c == '#' || c == '.')
-            .to_string()
+        input.trim_start_matches(['#', '.']).to_string()
     }
 }
 
@@ -85,7 +282,8 @@ mod tests {
     fn building_tokens_collapses_ctags() {
         let instance_method_spec = CtagItem {
             name: String::from("#name"),
-            file_path: PathBuf::from("spec/models/person_spec.rb"),
+            file_path: PathBuf::from("spec/models/person_spec.rb").into(),
+            raw_path: "spec/models/person_spec.rb".into(),
             address: String::from("1"),
             language: Some(Language::Ruby),
             tags: BTreeMap::new(),
@@ -94,20 +292,107 @@ mod tests {
 
         let instance_method = CtagItem {
             name: String::from("name"),
-            file_path: PathBuf::from("app/models/person.rb"),
+            file_path: PathBuf::from("app/models/person.rb").into(),
+            raw_path: "app/models/person.rb".into(),
             address: String::from("1"),
             language: Some(Language::Ruby),
             tags: BTreeMap::new(),
             kind: TokenKind::Class,
         };
         let tokens = Token::build_tokens_from_outcome(
-            vec![instance_method_spec, instance_method]
+            [instance_method_spec, instance_method]
                 .iter()
                 .cloned()
                 .collect(),
         );
 
         assert_eq!(tokens.len(), 1);
-        assert_eq!(tokens.iter().nth(0).unwrap().token, "name");
+        assert_eq!(tokens.first().unwrap().token, "name");
+    }
+
+    #[test]
+    fn reads_definitions_in_mapped_files_as_their_language() {
+        let definition = |path: &str| CtagItem {
+            name: String::from("render_person"),
+            file_path: PathBuf::from(path).into(),
+            raw_path: path.into(),
+            address: String::from("1"),
+            language: Language::from_path(path),
+            tags: BTreeMap::new(),
+            kind: TokenKind::from_ctag(Language::from_path(path), 'f'),
+        };
+        let token = Token::new(
+            String::from("render_person"),
+            ["app/views/show.json.jbuilder", "app/person.js"]
+                .iter()
+                .map(|path| definition(path))
+                .collect(),
+        );
+        let mappings = LanguageMappings::new(vec![(String::from("*.jbuilder"), Language::Ruby)]);
+        let token = token.with_language_mappings(&mappings);
+
+        assert_eq!(
+            token
+                .definitions
+                .iter()
+                .map(|d| (d.language, d.kind.clone()))
+                .sorted_by_key(|(language, _)| language.map(|l| l.to_string()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(Language::JavaScript), TokenKind::Function),
+                (Some(Language::Ruby), TokenKind::Method),
+            ]
+        );
+    }
+
+    #[test]
+    fn building_tokens_aggregates_definitions_across_files() {
+        let reopened = |path: &str| CtagItem {
+            name: String::from("Person"),
+            file_path: PathBuf::from(path).into(),
+            raw_path: path.into(),
+            address: String::from("1"),
+            language: Some(Language::Ruby),
+            tags: BTreeMap::new(),
+            kind: TokenKind::Class,
+        };
+        let tokens = Token::from_tags(
+            ["lib/person/name.rb", "app/person.rb", "lib/person/age.rb"]
+                .iter()
+                .map(|path| reopened(path))
+                .collect(),
+        );
+
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].defined_paths.len(), 3);
+        assert_eq!(tokens[0].first_path(), Path::new("app/person.rb"));
+    }
+
+    #[test]
+    fn building_tokens_drops_qualified_duplicates() {
+        let full_name = CtagItem {
+            name: String::from("full_name"),
+            file_path: PathBuf::from("app/models/person.rb").into(),
+            raw_path: "app/models/person.rb".into(),
+            address: String::from("12"),
+            language: Some(Language::Ruby),
+            tags: std::iter::once(("class".into(), "Person".into())).collect(),
+            kind: TokenKind::Method,
+        };
+        let qualified = CtagItem {
+            name: String::from("Person.full_name"),
+            ..full_name.clone()
+        };
+        let unmatched = CtagItem {
+            name: String::from("Person.first_name"),
+            address: String::from("16"),
+            ..full_name.clone()
+        };
+        let tokens = Token::from_tags(vec![full_name, qualified, unmatched].into_iter().collect());
+
+        assert_eq!(
+            tokens.iter().map(|t| t.token.as_str()).collect::<Vec<_>>(),
+            vec!["Person.first_name", "full_name"]
+        );
     }
 }
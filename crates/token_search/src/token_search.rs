@@ -1,19 +1,49 @@
+use super::aliases::Aliases;
+use super::cancellation::CancellationToken;
+use super::import_graph::ImportGraph;
+use super::occurrence_cache::{OccurrenceCache, Occurrences};
+use super::scan_cache::ScanCache;
+use super::scan_timings::ScanTimings;
+use super::scope_attribution::{self, ScopeAttribution};
+use super::shards;
+use super::syntax::{self, Mentions};
 use super::token::Token;
-use aho_corasick::{AhoCorasickBuilder, MatchKind};
-use codebase_files::CodebaseFiles;
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use codebase_files::paths::PathLookup;
+use codebase_files::text::is_identifier_char;
+use codebase_files::writes::Writes;
+use codebase_files::{CodebaseFiles, DiskFileSystem, FileSystem, Symlinks};
 use indicatif::ParallelProgressIterator;
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use itertools::Itertools;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use read_ctags::{Language, TokenKind};
+use read_ctags::{Language, LanguageMappings, TokenKind};
 use serde::ser::SerializeMap;
 use serde::{Serialize, Serializer};
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
-use std::fs;
 use std::io;
 use std::iter::FromIterator;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Files larger than this (5 MiB) aren't searched by default
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+thread_local! {
+    static FILE_IN_PROGRESS: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// The file currently being searched on this thread, if any
+///
+/// This is intended for diagnostics, such as identifying which file was being read when a panic
+/// occurred.
+pub fn file_in_progress() -> Option<PathBuf> {
+    FILE_IN_PROGRESS.with(|current| current.borrow().clone())
+}
 
 /// A TokenSearchConfig is necessary to construct the list of tokens and files to search against
 /// when generating results.
@@ -31,6 +61,78 @@ pub struct TokenSearchConfig {
     pub display_progress: bool,
     /// Restrict languages searched (based on file extension)
     pub language_restriction: LanguageRestriction,
+    /// File name patterns mapped to languages ahead of their extensions, when telling files'
+    /// languages apart
+    pub language_mappings: LanguageMappings,
+    /// Where to persist occurrence counts between runs
+    ///
+    /// When set, counts are reused from the previous run and adjusted from `git diff` hunks,
+    /// rescanning only files whose changes can't be applied incrementally.
+    pub occurrence_cache: Option<PathBuf>,
+    /// Where to persist per-file occurrence counts between runs
+    ///
+    /// When set, files whose contents are unchanged since the previous run aren't rescanned.
+    /// This is ignored when `occurrence_cache` is set.
+    pub scan_cache: Option<PathBuf>,
+    /// Only count occurrences in files that plausibly import the module defining a token
+    ///
+    /// This applies to tokens defined in languages with explicit imports (Rust, Go, Python, and
+    /// JavaScript/TypeScript); see `ImportGraph::plausibly_imports`.
+    pub scope_by_imports: bool,
+    /// Credit occurrences of aliases to the names they alias
+    ///
+    /// Aliases are read from Ruby's `alias` and `alias_method`, `as` renames in JavaScript and
+    /// TypeScript imports and exports, and `use ... as` in Rust; see `Aliases`.
+    pub link_aliases: bool,
+    /// Attribute occurrences of tokens defined in several scopes to the scope each refers to
+    ///
+    /// Attributions are read from each occurrence's receiver, and recorded in each result's
+    /// `scopes`; see `ScopeAttribution`.
+    pub attribute_scopes: bool,
+    /// Contents searched in place of files on disk, such as the staged versions of files with
+    /// unstaged changes
+    ///
+    /// When set, `occurrence_cache` is ignored, since its updates come from the working tree.
+    pub content_overrides: HashMap<PathBuf, String>,
+    /// References read from an index (such as GNU GLOBAL's GRTAGS), counted in place of
+    /// searching files
+    ///
+    /// Each definition counts as an occurrence alongside its references, as it would when
+    /// searching; references from files outside `files` are ignored.
+    pub references: Option<Occurrences>,
+    /// Files larger than this many bytes aren't searched, or files of any size when `None`
+    ///
+    /// Large files are usually vendored or generated blobs, which slow searching without
+    /// referencing the project's own tokens.
+    pub max_file_size: Option<u64>,
+    /// Roughly how many bytes searching may hold at once, or no limit when `None`
+    ///
+    /// When set, tokens are searched in shards, reading every file once per shard, and counts
+    /// are spilled to disk while later shards are searched. This is ignored when
+    /// `occurrence_cache`, `scan_cache`, or `references` are set.
+    pub memory_limit: Option<u64>,
+    /// Tell occurrences in comments and string literals apart from code, with a lightweight
+    /// lexer for each file's language
+    ///
+    /// Occurrences in comments aren't counted, and those in comments and string literals are
+    /// recorded in each result's `mentions`. Every file is searched, so this takes precedence
+    /// over `occurrence_cache`, `scan_cache`, and `memory_limit`; it's ignored when `references`
+    /// are set.
+    pub syntax_aware: bool,
+    /// Where to total the time spent reading and searching each file by its directory, to find
+    /// where a slow search spends its time
+    pub scan_timings: Option<Arc<ScanTimings>>,
+    /// Aborts searching (and analyzing the results) when cancelled, leaving results partial
+    ///
+    /// Files not yet searched when it's cancelled are skipped, and caches aren't written from
+    /// a search it interrupted.
+    pub cancellation: CancellationToken,
+    /// Where files are read from, which is the disk unless files are held elsewhere (such as in
+    /// memory, in tests)
+    pub file_system: Arc<dyn FileSystem>,
+    /// Whether caches and counts spilled by `memory_limit` may be written, which they're not in
+    /// read-only mode
+    pub writes: Writes,
 }
 
 /// LanguageRestriction allows for filtering out what's searched
@@ -43,32 +145,50 @@ pub enum LanguageRestriction {
     Except(HashSet<Language>),
 }
 
+impl LanguageRestriction {
+    /// Should the file at `path`, in the language `mappings` give it, be searched?
+    ///
+    /// Templates (such as `.erb` or `.ejs` views) are searched along with the language they
+    /// embed. Files in languages that aren't recognized are always searched, since they may
+    /// reference tokens from any language.
+    pub fn includes_file(&self, path: &Path, mappings: &LanguageMappings) -> bool {
+        let language = mappings
+            .from_path(path)
+            .or_else(|| Language::embedded_in(path));
+        match (self, language) {
+            (LanguageRestriction::NoRestriction, _) | (_, None) => true,
+            (LanguageRestriction::Only(languages), Some(language)) => languages.contains(&language),
+            (LanguageRestriction::Except(languages), Some(language)) => {
+                !languages.contains(&language)
+            }
+        }
+    }
+}
+
 impl std::fmt::Display for LanguageRestriction {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // Names are sorted, since sets iterate in a different order on every run
+        let names = |languages: &HashSet<Language>| {
+            languages.iter().map(|l| l.to_string()).sorted().join(", ")
+        };
+
         match self {
             LanguageRestriction::NoRestriction => write!(f, "all file types"),
-            LanguageRestriction::Only(languages) => write!(
-                f,
-                "{}",
-                format!(
-                    "only {}",
-                    languages.iter().map(|l| l.to_string()).join(", ")
-                )
-            ),
-            LanguageRestriction::Except(languages) => write!(
-                f,
-                "{}",
-                format!(
-                    "except {}",
-                    languages.iter().map(|l| l.to_string()).join(", ")
-                )
-            ),
+            LanguageRestriction::Only(languages) => write!(f, "only {}", names(languages)),
+            LanguageRestriction::Except(languages) => write!(f, "except {}", names(languages)),
         }
     }
 }
 
 impl Default for TokenSearchConfig {
     fn default() -> Self {
+        Self::for_files(CodebaseFiles::all().paths)
+    }
+}
+
+impl TokenSearchConfig {
+    /// The default configuration, searching `files` rather than every file in the codebase
+    pub fn for_files(files: Vec<PathBuf>) -> Self {
         TokenSearchConfig {
             filter_tokens: |t| {
                 !t.token.contains(" ")
@@ -76,24 +196,56 @@ impl Default for TokenSearchConfig {
                     && !t.only_ctag(|ct| ct.kind == TokenKind::RSpecDescribe)
             },
             tokens: vec![],
-            files: CodebaseFiles::all().paths,
+            files,
             display_progress: true,
             language_restriction: LanguageRestriction::Except(HashSet::from_iter(
-                vec![Language::JSON, Language::Markdown].iter().cloned(),
+                [Language::JSON, Language::Markdown].iter().cloned(),
             )),
+            language_mappings: LanguageMappings::default(),
+            occurrence_cache: None,
+            scan_cache: None,
+            scope_by_imports: false,
+            link_aliases: false,
+            attribute_scopes: false,
+            content_overrides: HashMap::new(),
+            references: None,
+            max_file_size: Some(DEFAULT_MAX_FILE_SIZE),
+            memory_limit: None,
+            syntax_aware: false,
+            scan_timings: None,
+            cancellation: CancellationToken::default(),
+            file_system: Arc::new(DiskFileSystem),
+            writes: Writes::Enabled,
         }
     }
-}
 
-impl TokenSearchConfig {
+    /// Walk every file in the codebase, following symlinks or not as `symlinks` says, displaying
+    /// how many have been found so far
+    pub fn walk_files(display_progress: bool, symlinks: Symlinks) -> Vec<PathBuf> {
+        let spinner = if display_progress {
+            let spinner = ProgressBar::new_spinner();
+            spinner.set_style(
+                ProgressStyle::default_spinner().template("{msg:12} {spinner} {pos} files"),
+            );
+            spinner.set_message("📂 Walking...");
+            spinner
+        } else {
+            ProgressBar::hidden()
+        };
+
+        let files = CodebaseFiles::all_reporting(symlinks, || spinner.inc(1)).paths;
+        spinner.finish_and_clear();
+        files
+    }
+
     fn progress_bar(prefix: &str, size: usize) -> ProgressBar {
         let pb = ProgressBar::new(size.try_into().unwrap());
-        pb.set_message(prefix);
         pb.set_style(
             ProgressStyle::default_bar()
                 .template("{msg:12} [{bar:40.cyan/blue}] {pos:>7}/{len:7}({eta})")
                 .progress_chars("#>-"),
         );
+        pb.set_message(prefix);
         pb
     }
 
@@ -108,6 +260,46 @@ impl TokenSearchConfig {
         }
     }
 
+    // Files scanned, along with how many distinct tokens have matched so far
+    fn scan_progress_bar(&self, size: usize) -> ProgressBar {
+        if !self.display_progress {
+            return ProgressBar::hidden();
+        }
+
+        // Hidden until configured, since each change redraws the bar
+        let pb =
+            ProgressBar::with_draw_target(size.try_into().unwrap(), ProgressDrawTarget::hidden());
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("{prefix:12} [{bar:40.cyan/blue}] {pos}/{len} files, {msg} ({eta})")
+                .progress_chars("#>-"),
+        );
+        pb.set_message("0 tokens matched");
+        pb.set_prefix("🤔 Working...");
+        pb.set_draw_target(ProgressDrawTarget::stderr());
+        pb
+    }
+
+    // Logs why tokens aren't searched for
+    fn included(&self, token: &Token) -> bool {
+        if !self.filter_token(token) {
+            tracing::debug!(
+                "Skipping token {}: too short, containing spaces, or an RSpec describe",
+                token.token
+            );
+            false
+        } else if !self.filter_language(token) {
+            tracing::debug!(
+                "Skipping token {}: its language is {}",
+                token.token,
+                self.language_restriction
+            );
+            false
+        } else {
+            true
+        }
+    }
+
     fn filter_token(&self, token: &Token) -> bool {
         (self.filter_tokens)(token)
     }
@@ -143,42 +335,229 @@ impl TokenSearchResults {
         &self.0
     }
 
+    /// Count each definition of `tokens` as an occurrence, for tokens whose name isn't written
+    /// where they're defined
+    ///
+    /// Translation keys, for example, are nested in locale files rather than written out in full,
+    /// so without this a key nothing references would have no occurrences, and no result.
+    pub fn credit_definitions(&mut self, tokens: &[Token]) {
+        let mut indices: HashMap<String, usize> = self
+            .0
+            .iter()
+            .enumerate()
+            .map(|(index, result)| (result.token.token.clone(), index))
+            .collect();
+
+        for token in tokens {
+            let index = *indices.entry(token.token.clone()).or_insert_with(|| {
+                self.0.push(TokenSearchResult {
+                    token: token.clone(),
+                    occurrences: HashMap::new(),
+                    mentions: HashMap::new(),
+                    scopes: None,
+                });
+                self.0.len() - 1
+            });
+
+            for path in token.defined_paths.iter() {
+                self.0[index]
+                    .occurrences
+                    .entry(path.to_path_buf())
+                    .or_insert(1);
+            }
+        }
+    }
+
+    /// Generate results based on provided search config, without blocking the async executor
+    ///
+    /// Files are searched on tokio's blocking thread pool, so this must be awaited within a tokio
+    /// runtime. The config is shared so it can be used again once results are generated, such as
+    /// to analyze them.
+    #[cfg(feature = "async")]
+    pub async fn generate_async(config: std::sync::Arc<TokenSearchConfig>) -> Self {
+        tokio::task::spawn_blocking(move || Self::generate_with_config(&config))
+            .await
+            .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+    }
+
     /// Generate results based on provided search config
     pub fn generate_with_config(config: &TokenSearchConfig) -> Self {
+        let lookup = PathLookup::new(&config.files);
         let filtered_results: Vec<Token> = config
             .tokens
             .clone()
             .into_iter()
-            .filter(|t| config.filter_token(t) && config.filter_language(t))
+            .filter(|t| config.included(t))
+            .map(|t| t.resolve_paths(&lookup))
             .collect();
+        tracing::info!(
+            "Searching {} tokens across {} files",
+            filtered_results.len(),
+            config.files.len()
+        );
 
-        let tokens: Vec<_> = filtered_results.iter().map(|r| &r.token).collect();
-        let ac = AhoCorasickBuilder::new()
-            .match_kind(MatchKind::LeftmostLongest)
-            .build(tokens);
+        let aliases = if config.link_aliases {
+            Aliases::build(
+                &config.files,
+                config.file_system.as_ref(),
+                &config.language_mappings,
+            )
+        } else {
+            Aliases::default()
+        };
+        let mut tokens: Vec<_> = filtered_results.iter().map(|r| &r.token).collect();
+        // Aliases which aren't tokens themselves are searched for too, to credit their
+        // occurrences to the names they alias
+        let known: HashSet<&String> = tokens.iter().copied().collect();
+        tokens.extend(aliases.names().filter(|name| !known.contains(name)));
+        let ac = || {
+            AhoCorasickBuilder::new()
+                .match_kind(MatchKind::LeftmostLongest)
+                .build(&tokens)
+        };
+
+        let mut mentions = HashMap::new();
+        let mut res = match (&config.occurrence_cache, &config.scan_cache) {
+            _ if config.references.is_some() => {
+                Self::referenced(config, &filtered_results, &tokens)
+            }
+            _ if config.syntax_aware => {
+                let (counts, mentioned) = syntax::scan(config, &ac());
+                mentions = mentioned;
+                counts
+            }
+            (Some(path), _) if config.content_overrides.is_empty() => {
+                Self::incremental_counts(config, &ac(), &tokens, path)
+            }
+            (_, Some(path)) => {
+                let cache = ScanCache::read(path, &tokens);
+                let res = Self::scan(config, &ac(), &config.files, Some(&cache));
+                if !config.cancellation.interrupted() {
+                    cache.write(path, config.writes).ok();
+                }
+                res
+            }
+            (_, None) => match config.memory_limit {
+                Some(limit) => shards::scan(config, &tokens, limit).unwrap_or_else(|e| {
+                    tracing::warn!(
+                        "Unable to read counts spilled to disk, searching again: {}",
+                        e
+                    );
+                    Self::scan(config, &ac(), &config.files, None)
+                }),
+                None => Self::scan(config, &ac(), &config.files, None),
+            },
+        };
+
+        if config.scope_by_imports {
+            let graph = ImportGraph::build(&config.files);
+
+            for (idx, occurrences) in res.iter_mut() {
+                let token = match filtered_results.get(*idx) {
+                    Some(token) => token,
+                    None => continue,
+                };
+                occurrences.retain(|path, _| {
+                    token.defined_paths.is_empty()
+                        || token.defined_paths.iter().any(|definition| {
+                            graph.plausibly_imports(path, definition, &token.token)
+                        })
+                });
+            }
+        }
+
+        Self::link_aliases(&mut res, &tokens, &aliases);
+
+        let mut final_results: Vec<TokenSearchResult> = res
+            .into_iter()
+            .filter(|(idx, _)| *idx < filtered_results.len())
+            .map(|(idx, occurrences)| TokenSearchResult {
+                token: filtered_results[idx].clone(),
+                occurrences,
+                mentions: mentions.remove(&idx).unwrap_or_default(),
+                scopes: None,
+            })
+            .collect();
+
+        if config.attribute_scopes {
+            scope_attribution::attribute(config, &mut final_results);
+        }
+
+        std::thread::spawn(move || drop(filtered_results));
 
-        let res = config
-            .files
+        TokenSearchResults(final_results)
+    }
+
+    pub(crate) fn scan(
+        config: &TokenSearchConfig,
+        ac: &AhoCorasick,
+        files: &[PathBuf],
+        cache: Option<&ScanCache>,
+    ) -> HashMap<usize, HashMap<PathBuf, usize>> {
+        Self::scan_with(config, ac, files, |_, contents| match cache {
+            Some(cache) => cache.counts(contents, |c| Self::pattern_counts(ac, c)),
+            None => Self::pattern_counts(ac, contents),
+        })
+    }
+
+    // Search each file in parallel, counting matches of the automaton's patterns in each with
+    // `count`
+    pub(crate) fn scan_with<T, F>(
+        config: &TokenSearchConfig,
+        ac: &AhoCorasick,
+        files: &[PathBuf],
+        count: F,
+    ) -> HashMap<usize, HashMap<PathBuf, T>>
+    where
+        T: Send,
+        F: Fn(&Path, &str) -> Vec<(usize, T)> + Sync,
+    {
+        let progress = config.scan_progress_bar(files.len());
+        let matched: Vec<AtomicBool> = (0..ac.pattern_count())
+            .map(|_| AtomicBool::new(false))
+            .collect();
+        let matched_count = AtomicUsize::new(0);
+
+        files
             .par_iter()
-            .progress_with(config.toggleable_progress_bar(&"🤔 Working...", config.files.len()))
+            .progress_with(progress.clone())
             .fold(HashMap::new, |mut results, f| {
-                if let Ok(contents) = Self::read_file(&f) {
-                    for (key, res) in ac
-                        .find_iter(&contents)
-                        .map(|v| v.pattern())
-                        .into_iter()
-                        .sorted_by_key(|&v| v)
-                        .group_by(|&v| v)
-                        .into_iter()
-                        .map(|(idx, res)| (idx, res.count()))
-                        .collect::<Vec<(usize, usize)>>()
-                    {
+                if config.cancellation.skip() {
+                    return results;
+                }
+                FILE_IN_PROGRESS.with(|current| *current.borrow_mut() = Some(f.clone()));
+                let started = Instant::now();
+
+                let contents = match config.content_overrides.get(f) {
+                    Some(contents) => Ok(contents.clone()),
+                    None => Self::read_file(config, f),
+                };
+
+                if let Ok(contents) = contents {
+                    let counts = count(f, &contents);
+
+                    let newly_matched = counts
+                        .iter()
+                        .filter(|(key, _)| !matched[*key].swap(true, Ordering::Relaxed))
+                        .count();
+                    if newly_matched > 0 {
+                        let total = matched_count.fetch_add(newly_matched, Ordering::Relaxed)
+                            + newly_matched;
+                        progress.set_message(&format!("{} tokens matched", total));
+                    }
+
+                    for (key, res) in counts {
                         let file_with_occurrences = results.entry(key).or_insert(HashMap::new());
 
                         file_with_occurrences.insert(f.clone(), res);
                     }
+
+                    if let Some(timings) = &config.scan_timings {
+                        timings.record(f, contents.len(), started.elapsed());
+                    }
                 }
 
+                FILE_IN_PROGRESS.with(|current| *current.borrow_mut() = None);
                 results
             })
             .reduce(HashMap::new, |m1, m2| {
@@ -187,26 +566,200 @@ impl TokenSearchResults {
                     res.extend(v);
                     acc
                 })
-            });
+            })
+    }
 
-        let final_results = res
-            .into_iter()
-            .map(|(idx, occurrences)| TokenSearchResult {
-                token: filtered_results[idx].clone(),
-                occurrences,
+    // The declaration of an alias isn't a use of it, so it's left out of the occurrences credited
+    fn link_aliases(
+        res: &mut HashMap<usize, HashMap<PathBuf, usize>>,
+        tokens: &[&String],
+        aliases: &Aliases,
+    ) {
+        let indexes: HashMap<&str, usize> = tokens
+            .iter()
+            .enumerate()
+            .map(|(index, token)| (token.as_str(), index))
+            .collect();
+
+        for (alias, original, declared_in) in aliases.links() {
+            let (from, to) = match (indexes.get(alias), indexes.get(original)) {
+                (Some(from), Some(to)) if from != to => (*from, *to),
+                _ => continue,
+            };
+            let credited: Vec<(PathBuf, usize)> = res
+                .get(&from)
+                .into_iter()
+                .flatten()
+                .map(|(path, count)| {
+                    let declarations = usize::from(path == declared_in);
+                    (path.clone(), count.saturating_sub(declarations))
+                })
+                .filter(|(_, count)| *count > 0)
+                .collect();
+            if credited.is_empty() {
+                continue;
+            }
+
+            tracing::debug!(
+                "Crediting occurrences of {} to {}, which it aliases",
+                alias,
+                original
+            );
+            let occurrences = res.entry(to).or_default();
+            for (path, count) in credited {
+                *occurrences.entry(path).or_default() += count;
+            }
+        }
+    }
+
+    fn incremental_counts(
+        config: &TokenSearchConfig,
+        ac: &AhoCorasick,
+        tokens: &[&String],
+        cache_path: &Path,
+    ) -> HashMap<usize, HashMap<PathBuf, usize>> {
+        let refreshed = OccurrenceCache::read(cache_path).and_then(|cache| {
+            cache.refresh(tokens, &config.files, |contents| {
+                Self::pattern_counts(ac, contents)
+            })
+        });
+
+        let (mut occurrences, rescanned) = match refreshed {
+            Some(cached) => {
+                let rescanned = Self::scan(config, ac, &cached.rescan, None);
+                (cached.occurrences, rescanned)
+            }
+            None => {
+                let empty = tokens.iter().map(|t| (t.to_string(), HashMap::new()));
+                (empty.collect(), Self::scan(config, ac, &config.files, None))
+            }
+        };
+
+        for (index, by_path) in rescanned {
+            occurrences
+                .entry(tokens[index].to_string())
+                .or_default()
+                .extend(by_path);
+        }
+
+        if !config.cancellation.interrupted() {
+            if let Some(cache) = OccurrenceCache::capture(&config.files, occurrences.clone()) {
+                cache.write(cache_path, config.writes).ok();
+            }
+        }
+
+        Self::indexed(tokens, occurrences)
+    }
+
+    fn referenced(
+        config: &TokenSearchConfig,
+        searched: &[Token],
+        tokens: &[&String],
+    ) -> HashMap<usize, HashMap<PathBuf, usize>> {
+        let files: HashSet<&PathBuf> = config.files.iter().collect();
+        let references = config.references.as_ref();
+        let occurrences = searched
+            .iter()
+            .map(|token| {
+                let mut by_path: HashMap<PathBuf, usize> = references
+                    .and_then(|references| references.get(&token.token))
+                    .into_iter()
+                    .flatten()
+                    .filter(|(path, _)| files.contains(path))
+                    .map(|(path, count)| (path.clone(), *count))
+                    .collect();
+                for definition in &token.definitions {
+                    *by_path
+                        .entry(definition.file_path.to_path_buf())
+                        .or_default() += 1;
+                }
+                (token.token.clone(), by_path)
             })
             .collect();
 
-        std::thread::spawn(move || drop(ac));
-        std::thread::spawn(move || drop(filtered_results));
+        Self::indexed(tokens, occurrences)
+    }
 
-        TokenSearchResults(final_results)
+    fn indexed(
+        tokens: &[&String],
+        mut occurrences: Occurrences,
+    ) -> HashMap<usize, HashMap<PathBuf, usize>> {
+        tokens
+            .iter()
+            .enumerate()
+            .filter_map(|(index, token)| {
+                occurrences
+                    .remove(*token)
+                    .filter(|by_path| !by_path.is_empty())
+                    .map(|by_path| (index, by_path))
+            })
+            .collect()
+    }
+
+    fn pattern_counts(ac: &AhoCorasick, contents: &str) -> Vec<(usize, usize)> {
+        Self::word_bounded_matches(ac, contents)
+            .map(|v| v.pattern())
+            .sorted_by_key(|&v| v)
+            .group_by(|&v| v)
+            .into_iter()
+            .map(|(idx, res)| (idx, res.count()))
+            .collect()
+    }
+
+    pub(crate) fn word_bounded_matches<'a>(
+        ac: &'a AhoCorasick,
+        contents: &'a str,
+    ) -> impl Iterator<Item = aho_corasick::Match> + 'a {
+        ac.find_iter(contents)
+            .filter(move |m| Self::word_bounded(contents, m.start(), m.end()))
     }
 
-    fn read_file(filename: &PathBuf) -> Result<String, io::Error> {
-        let contents = fs::read_to_string(filename)?;
+    /// Does a match at `start..end` avoid running into neighboring identifier characters?
+    ///
+    /// Boundaries are only enforced at matched edges that are themselves identifier characters,
+    /// so tokens such as `$z-index` or `valid?` still match when adjacent to punctuation.
+    pub(crate) fn word_bounded(contents: &str, start: usize, end: usize) -> bool {
+        let matched = &contents[start..end];
+        let bounded_start = !matched.chars().next().is_some_and(is_identifier_char)
+            || !contents[..start]
+                .chars()
+                .next_back()
+                .is_some_and(is_identifier_char);
+        let bounded_end = !matched.chars().next_back().is_some_and(is_identifier_char)
+            || !contents[end..]
+                .chars()
+                .next()
+                .is_some_and(is_identifier_char);
 
-        Ok(contents)
+        bounded_start && bounded_end
+    }
+
+    // Binary and oversized files are logged with `-v`, since they're usually vendored blobs
+    // worth excluding, and other unreadable files with `-vv`
+    pub(crate) fn read_file(
+        config: &TokenSearchConfig,
+        filename: &Path,
+    ) -> Result<String, io::Error> {
+        let file_system = config.file_system.as_ref();
+        if let Some(limit) = config.max_file_size {
+            let size = file_system.size(filename).unwrap_or_default();
+            if size > limit {
+                tracing::info!(
+                    "Skipping file {}: {} bytes, over the {}-byte limit",
+                    filename.display(),
+                    size,
+                    limit
+                );
+                return Err(io::Error::other("file too large"));
+            }
+        }
+
+        file_system.read(filename).inspect_err(|e| match e.kind() {
+            io::ErrorKind::InvalidData => {
+                tracing::info!("Skipping file {}: {}", filename.display(), e)
+            }
+            _ => tracing::debug!("Skipping file {}: {}", filename.display(), e),
+        })
     }
 }
 
@@ -230,12 +783,23 @@ pub struct TokenSearchResult {
     pub token: Token,
     /// A HashMap of paths and occurrence counts
     pub occurrences: HashMap<PathBuf, usize>,
+    /// Occurrences in comments and string literals, by path, when searching with `syntax_aware`
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub mentions: HashMap<PathBuf, Mentions>,
+    /// Occurrences attributed to each scope defining the token, when searching with
+    /// `attribute_scopes` and the token is defined in several scopes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<ScopeAttribution>,
 }
 
 impl TokenSearchResult {
     /// The paths where a token is defined
     pub fn defined_paths(&self) -> HashSet<PathBuf> {
-        self.token.defined_paths.clone()
+        self.token
+            .defined_paths
+            .iter()
+            .map(|path| path.to_path_buf())
+            .collect()
     }
 
     /// The paths where a token occurs that are not also where the token is defined
@@ -246,7 +810,397 @@ impl TokenSearchResult {
             .collect()
     }
 
+    /// Occurrences away from the token's definitions, by path
+    ///
+    /// Each definition site accounts for one occurrence in its file, so the definition itself
+    /// isn't counted as a use of the token.
+    pub fn references(&self) -> HashMap<PathBuf, usize> {
+        let sites = self.token.definition_sites();
+
+        self.occurrences
+            .iter()
+            .map(|(path, count)| {
+                let own = sites.get(path.as_path()).copied().unwrap_or(0);
+                (path.clone(), count.saturating_sub(own))
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+
     fn all_occurred_paths(&self) -> HashSet<PathBuf> {
         self.occurrences.keys().cloned().collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn automaton(tokens: &[&str]) -> AhoCorasick {
+        AhoCorasickBuilder::new()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(tokens)
+    }
+
+    #[test]
+    fn counts_only_word_bounded_matches() {
+        let ac = automaton(&["name", "Person"]);
+
+        assert_eq!(
+            TokenSearchResults::pattern_counts(&ac, "Person.new.name\nrename(person_name)\n"),
+            vec![(0, 1), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn bounds_matches_by_identifier_characters_of_any_script() {
+        let ac = automaton(&["cafe", "名前"]);
+
+        assert_eq!(
+            TokenSearchResults::pattern_counts(&ac, "cafe\u{301} = cafe\n名前です(名前)\n"),
+            vec![(0, 1), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn allows_punctuated_token_edges() {
+        let ac = automaton(&["valid?", "$z-index"]);
+
+        assert_eq!(
+            TokenSearchResults::pattern_counts(&ac, "if valid? then\n.a{z:$z-index;}"),
+            vec![(0, 1), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn skips_oversized_files() {
+        let file_system = codebase_files::MemoryFileSystem::default();
+        file_system.insert("app/person.rb", "def full_name\nend\n");
+        file_system.insert("vendor/blob.js", "full_name;".repeat(10));
+        let config = TokenSearchConfig {
+            max_file_size: Some(64),
+            file_system: Arc::new(file_system),
+            ..TokenSearchConfig::for_files(vec![])
+        };
+
+        assert!(TokenSearchResults::read_file(&config, Path::new("app/person.rb")).is_ok());
+        assert!(TokenSearchResults::read_file(&config, Path::new("vendor/blob.js")).is_err());
+    }
+
+    #[test]
+    fn displays_language_restrictions_in_sorted_order() {
+        let languages = [
+            Language::Ruby,
+            Language::JSON,
+            Language::Markdown,
+            Language::Elixir,
+        ];
+        let except = LanguageRestriction::Except(languages.iter().cloned().collect());
+        let only = LanguageRestriction::Only(languages.iter().cloned().collect());
+
+        assert_eq!(except.to_string(), "except Elixir, JSON, Markdown, Ruby");
+        assert_eq!(only.to_string(), "only Elixir, JSON, Markdown, Ruby");
+        assert_eq!(
+            LanguageRestriction::NoRestriction.to_string(),
+            "all file types"
+        );
+    }
+
+    #[test]
+    fn restricts_files_by_language() {
+        let only = LanguageRestriction::Only([Language::Ruby].iter().cloned().collect());
+        let except = LanguageRestriction::Except([Language::JSON].iter().cloned().collect());
+        let mappings = LanguageMappings::new(vec![(String::from("*.jbuilder"), Language::Ruby)]);
+
+        assert!(only.includes_file(Path::new("app/person.rb"), &mappings));
+        assert!(!only.includes_file(Path::new("app/person.js"), &mappings));
+        assert!(only.includes_file(Path::new("app/views/person.html.erb"), &mappings));
+        assert!(!only.includes_file(Path::new("views/person.ejs"), &mappings));
+        assert!(only.includes_file(Path::new("app/views/person.html.mustache"), &mappings));
+        assert!(!except.includes_file(Path::new("package.json"), &mappings));
+        assert!(except.includes_file(Path::new("app/person.rb"), &mappings));
+        assert!(only.includes_file(Path::new("app/views/show.json.jbuilder"), &mappings));
+
+        let except = LanguageRestriction::Except([Language::Ruby].iter().cloned().collect());
+        assert!(!except.includes_file(Path::new("app/views/show.json.jbuilder"), &mappings));
+        assert!(except.includes_file(
+            Path::new("app/views/show.json.jbuilder"),
+            &LanguageMappings::default()
+        ));
+    }
+
+    #[test]
+    fn searches_files_in_memory() {
+        let file_system = codebase_files::MemoryFileSystem::default();
+        file_system.insert("app/person.rb", "def full_name\nend\n");
+        file_system.insert("app/report.rb", "person.full_name\n");
+
+        let definitions =
+            read_ctags::CtagItem::parse(PathBuf::from("tags"), "full_name\tapp/person.rb\t1;\"\tf")
+                .map(|file| file.tags)
+                .unwrap_or_default();
+        let config = TokenSearchConfig {
+            tokens: vec![Token::new(String::from("full_name"), definitions)],
+            display_progress: false,
+            file_system: Arc::new(file_system),
+            ..TokenSearchConfig::for_files(vec![
+                PathBuf::from("app/person.rb"),
+                PathBuf::from("app/report.rb"),
+            ])
+        };
+
+        let results = TokenSearchResults::generate_with_config(&config);
+
+        assert_eq!(results.value()[0].occurrences.len(), 2);
+    }
+
+    #[test]
+    fn credits_definitions_of_tokens_not_written_where_defined() {
+        let file_system = codebase_files::MemoryFileSystem::default();
+        file_system.insert("config/locales/en.yml", "en:\n  users:\n    title: Users\n");
+        file_system.insert("app/views/users.html.erb", "<%= t('users.title') %>\n");
+
+        let key = |name: &str| {
+            let definitions = read_ctags::CtagItem::parse(
+                PathBuf::from("tags"),
+                &format!("{}\tconfig/locales/en.yml\t3;\"\tk", name),
+            )
+            .map(|file| file.tags)
+            .unwrap_or_default();
+            Token::new(String::from(name), definitions)
+        };
+        let tokens = vec![key("users.title"), key("users.bio")];
+        let config = TokenSearchConfig {
+            tokens: tokens.clone(),
+            display_progress: false,
+            file_system: Arc::new(file_system),
+            ..TokenSearchConfig::for_files(vec![
+                PathBuf::from("config/locales/en.yml"),
+                PathBuf::from("app/views/users.html.erb"),
+            ])
+        };
+
+        let mut results = TokenSearchResults::generate_with_config(&config);
+        results.credit_definitions(&tokens);
+        let occurrences = |name: &str| {
+            results
+                .value()
+                .iter()
+                .find(|r| r.token.token == name)
+                .map(|r| r.occurrences.values().sum::<usize>())
+        };
+
+        assert_eq!(occurrences("users.title"), Some(2));
+        assert_eq!(occurrences("users.bio"), Some(1));
+    }
+
+    #[test]
+    fn counts_occurrences_within_template_tags() {
+        let file_system = codebase_files::MemoryFileSystem::default();
+        file_system.insert("app/helpers/people_helper.rb", "def full_name\nend\n");
+        file_system.insert(
+            "app/views/people/show.html.erb",
+            "<h1><%=full_name%></h1>\n",
+        );
+        file_system.insert("app/views/people/index.html.haml", "%h1= full_name\n");
+        file_system.insert("app/views/people/edit.html.slim", "h1 #{full_name}\n");
+        file_system.insert("views/people.ejs", "<h1><%- full_name %></h1>\n");
+        let files: Vec<PathBuf> = [
+            "app/helpers/people_helper.rb",
+            "app/views/people/show.html.erb",
+            "app/views/people/index.html.haml",
+            "app/views/people/edit.html.slim",
+            "views/people.ejs",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+        let definitions = read_ctags::CtagItem::parse(
+            PathBuf::from("tags"),
+            "full_name\tapp/helpers/people_helper.rb\t1;\"\tf",
+        )
+        .map(|file| file.tags)
+        .unwrap_or_default();
+        let config = TokenSearchConfig {
+            tokens: vec![Token::new(String::from("full_name"), definitions)],
+            display_progress: false,
+            file_system: Arc::new(file_system),
+            ..TokenSearchConfig::for_files(files)
+        };
+
+        let results = TokenSearchResults::generate_with_config(&config);
+
+        assert_eq!(results.value()[0].occurrences.len(), 5);
+    }
+
+    #[test]
+    fn credits_occurrences_of_aliases() {
+        let file_system = codebase_files::MemoryFileSystem::default();
+        file_system.insert(
+            "app/person.rb",
+            "def full_name\nend\nalias_method :name, :full_name\n",
+        );
+        file_system.insert("app/report.rb", "person.name\nperson.name\n");
+        let file_system: Arc<dyn FileSystem> = Arc::new(file_system);
+
+        let definitions =
+            read_ctags::CtagItem::parse(PathBuf::from("tags"), "full_name\tapp/person.rb\t1;\"\tf")
+                .map(|file| file.tags)
+                .unwrap_or_default();
+        let config = |link_aliases| TokenSearchConfig {
+            tokens: vec![Token::new(String::from("full_name"), definitions.clone())],
+            display_progress: false,
+            link_aliases,
+            file_system: file_system.clone(),
+            ..TokenSearchConfig::for_files(vec![
+                PathBuf::from("app/person.rb"),
+                PathBuf::from("app/report.rb"),
+            ])
+        };
+
+        let linked = TokenSearchResults::generate_with_config(&config(true));
+        let unlinked = TokenSearchResults::generate_with_config(&config(false));
+
+        assert_eq!(linked.value().len(), 1);
+        assert_eq!(
+            linked.value()[0]
+                .occurrences
+                .get(Path::new("app/report.rb")),
+            Some(&2)
+        );
+        assert_eq!(
+            linked.value()[0]
+                .occurrences
+                .get(Path::new("app/person.rb")),
+            Some(&2)
+        );
+        assert_eq!(unlinked.value()[0].occurrences.len(), 1);
+    }
+
+    #[test]
+    fn skips_occurrences_in_comments_when_syntax_aware() {
+        let file_system = codebase_files::MemoryFileSystem::default();
+        file_system.insert("app/person.rb", "def full_name\nend\n");
+        file_system.insert(
+            "app/report.rb",
+            "# TODO: call full_name\nputs \"full_name\"\n",
+        );
+        let file_system: Arc<dyn FileSystem> = Arc::new(file_system);
+
+        let definitions =
+            read_ctags::CtagItem::parse(PathBuf::from("tags"), "full_name\tapp/person.rb\t1;\"\tf")
+                .map(|file| file.tags)
+                .unwrap_or_default();
+        let config = |syntax_aware| TokenSearchConfig {
+            tokens: vec![Token::new(String::from("full_name"), definitions.clone())],
+            display_progress: false,
+            syntax_aware,
+            file_system: file_system.clone(),
+            ..TokenSearchConfig::for_files(vec![
+                PathBuf::from("app/person.rb"),
+                PathBuf::from("app/report.rb"),
+            ])
+        };
+
+        let aware = TokenSearchResults::generate_with_config(&config(true));
+        let plain = TokenSearchResults::generate_with_config(&config(false));
+
+        let report = Path::new("app/report.rb");
+        assert_eq!(aware.value()[0].occurrences.get(report), Some(&1));
+        assert_eq!(
+            aware.value()[0].mentions.get(report),
+            Some(&Mentions {
+                comments: 1,
+                strings: 1
+            })
+        );
+        assert_eq!(plain.value()[0].occurrences.get(report), Some(&2));
+        assert!(plain.value()[0].mentions.is_empty());
+    }
+
+    #[test]
+    fn matches_occurrences_in_paths_with_spaces() {
+        let dir = std::env::temp_dir().join("unused scan with spaces");
+        let path = dir.join("my model.rb");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, "def full_name\nend\n").unwrap();
+
+        let tags = format!("full_name\t{}\t/^def full_name$/;\"\tf", path.display());
+        let definitions = read_ctags::CtagItem::parse(PathBuf::from("tags"), &tags)
+            .map(|file| file.tags)
+            .unwrap_or_default();
+        let config = TokenSearchConfig {
+            tokens: vec![Token::new(String::from("full_name"), definitions)],
+            files: vec![path.clone()],
+            display_progress: false,
+            ..TokenSearchConfig::default()
+        };
+
+        let results = TokenSearchResults::generate_with_config(&config);
+        std::fs::remove_dir_all(&dir).ok();
+
+        let result = &results.value()[0];
+        assert_eq!(result.occurrences.get(&path), Some(&1));
+        assert!(result.occurred_paths().is_empty());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn searches_asynchronously() {
+        let dir = std::env::temp_dir().join("unused_scan_asynchronously");
+        let path = dir.join("person.rb");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, "def full_name\nend\n\nfull_name\n").unwrap();
+
+        let tags = format!("full_name\t{}\t1;\"\tf", path.display());
+        let definitions = read_ctags::CtagItem::parse(PathBuf::from("tags"), &tags)
+            .map(|file| file.tags)
+            .unwrap_or_default();
+        let config = std::sync::Arc::new(TokenSearchConfig {
+            tokens: vec![Token::new(String::from("full_name"), definitions)],
+            files: vec![path.clone()],
+            display_progress: false,
+            ..TokenSearchConfig::default()
+        });
+
+        let results = TokenSearchResults::generate_async(config.clone()).await;
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(results.value()[0].occurrences.get(&path), Some(&2));
+        assert_eq!(config.files, vec![path]);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn searches_concurrently_on_a_single_threaded_runtime() {
+        let dir = std::env::temp_dir().join("unused_scan_concurrently");
+        let person = dir.join("person.rb");
+        let order = dir.join("order.rb");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&person, "def full_name\nend\n").unwrap();
+        std::fs::write(&order, "full_name\nfull_name\n").unwrap();
+
+        let tags = format!("full_name\t{}\t1;\"\tf", person.display());
+        let definitions = read_ctags::CtagItem::parse(PathBuf::from("tags"), &tags)
+            .map(|file| file.tags)
+            .unwrap_or_default();
+        let config = |files: Vec<PathBuf>| {
+            std::sync::Arc::new(TokenSearchConfig {
+                tokens: vec![Token::new(String::from("full_name"), definitions.clone())],
+                files,
+                display_progress: false,
+                ..TokenSearchConfig::default()
+            })
+        };
+
+        let (alone, both) = tokio::join!(
+            TokenSearchResults::generate_async(config(vec![person.clone()])),
+            TokenSearchResults::generate_async(config(vec![person.clone(), order.clone()])),
+        );
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(alone.value()[0].occurrences.len(), 1);
+        assert_eq!(both.value()[0].occurrences.get(&order), Some(&2));
+    }
+}
@@ -0,0 +1,122 @@
+use super::token_search::TokenSearchResult;
+use read_ctags::{Interner, Symbol};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// UsageIndex provides lookups of token occurrences by token and by file
+///
+/// The index is immutable once built; handles are cheap to clone and safe to share across
+/// threads, making it a building block for custom analyses (e.g. checking that tokens defined in
+/// one layer of an application aren't used from another).
+#[derive(Clone)]
+pub struct UsageIndex {
+    interner: Interner,
+    by_token: Arc<HashMap<Symbol, BTreeMap<PathBuf, usize>>>,
+    by_path: Arc<HashMap<Symbol, BTreeMap<String, usize>>>,
+}
+
+impl UsageIndex {
+    /// Build an index from search results
+    pub fn new(results: &[TokenSearchResult]) -> Self {
+        Self::with_interner(results, Interner::default())
+    }
+
+    /// Build an index from search results, interning tokens and paths with `interner`
+    ///
+    /// Sharing the interner used by a `read_ctags::TagsIndex` lets symbols from both indexes be
+    /// compared directly.
+    pub fn with_interner(results: &[TokenSearchResult], interner: Interner) -> Self {
+        let mut by_token: HashMap<Symbol, BTreeMap<PathBuf, usize>> = HashMap::new();
+        let mut by_path: HashMap<Symbol, BTreeMap<String, usize>> = HashMap::new();
+
+        for result in results {
+            let token = interner.intern(&result.token.token);
+
+            for (path, count) in result.occurrences.iter() {
+                by_token
+                    .entry(token)
+                    .or_default()
+                    .insert(path.to_path_buf(), *count);
+                by_path
+                    .entry(interner.intern(&path.to_string_lossy()))
+                    .or_default()
+                    .insert(result.token.token.to_string(), *count);
+            }
+        }
+
+        UsageIndex {
+            interner,
+            by_token: Arc::new(by_token),
+            by_path: Arc::new(by_path),
+        }
+    }
+
+    /// The interner used for tokens and paths
+    pub fn interner(&self) -> &Interner {
+        &self.interner
+    }
+
+    /// Occurrence counts of a token, keyed by path
+    pub fn occurrences_of(&self, token: &str) -> Option<&BTreeMap<PathBuf, usize>> {
+        self.interner
+            .get(token)
+            .and_then(|symbol| self.by_token.get(&symbol))
+    }
+
+    /// Occurrence counts of tokens found within a file, keyed by token
+    pub fn tokens_in(&self, path: &Path) -> Option<&BTreeMap<String, usize>> {
+        self.interner
+            .get(&path.to_string_lossy())
+            .and_then(|symbol| self.by_path.get(&symbol))
+    }
+
+    /// Total occurrences of a token across all files
+    pub fn total(&self, token: &str) -> usize {
+        self.occurrences_of(token)
+            .map(|occurrences| occurrences.values().sum())
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Token;
+    use std::collections::HashSet;
+
+    #[test]
+    fn looks_up_occurrences_by_token_and_path() {
+        let result = |token: &str, occurrences: &[(&str, usize)]| TokenSearchResult {
+            token: Token::new(token.to_string(), HashSet::new()),
+            occurrences: occurrences
+                .iter()
+                .map(|(path, count)| (PathBuf::from(path), *count))
+                .collect(),
+            mentions: HashMap::new(),
+            scopes: None,
+        };
+        let index = UsageIndex::new(&[
+            result("name", &[("app/person.rb", 2), ("app/view.rb", 1)]),
+            result("Person", &[("app/person.rb", 1)]),
+        ]);
+        let shared = index.clone();
+
+        let total = std::thread::spawn(move || shared.total("name"))
+            .join()
+            .unwrap();
+
+        assert_eq!(total, 3);
+        assert_eq!(index.total("missing"), 0);
+        assert_eq!(
+            index.tokens_in(Path::new("app/person.rb")).map(|t| t.len()),
+            Some(2)
+        );
+        assert_eq!(
+            index
+                .occurrences_of("Person")
+                .map(|o| o.keys().cloned().collect::<Vec<_>>()),
+            Some(vec![PathBuf::from("app/person.rb")])
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use super::analysis_report::Likelihood;
+use token_search::CancellationToken;
+
+/// How a project is analyzed, built from the defaults the `unused` command uses
+///
+/// ```
+/// use unused_core::{AnalysisOptions, Likelihood};
+///
+/// let options = AnalysisOptions::default()
+///     .with_likelihoods(vec![Likelihood::High, Likelihood::Medium])
+///     .with_syntax_aware(true);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnalysisOptions {
+    pub(crate) likelihoods: Vec<Likelihood>,
+    pub(crate) syntax_aware: bool,
+    pub(crate) scope_by_imports: bool,
+    pub(crate) attribute_scopes: bool,
+    pub(crate) cancellation: CancellationToken,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        AnalysisOptions {
+            likelihoods: vec![Likelihood::High],
+            syntax_aware: false,
+            scope_by_imports: false,
+            attribute_scopes: false,
+            cancellation: CancellationToken::default(),
+        }
+    }
+}
+
+impl AnalysisOptions {
+    /// Report tokens with these likelihoods of being unused, rather than only `High`
+    pub fn with_likelihoods(mut self, likelihoods: Vec<Likelihood>) -> Self {
+        self.likelihoods = likelihoods;
+        self
+    }
+
+    /// Don't count occurrences in comments, as with `--syntax-aware`
+    pub fn with_syntax_aware(mut self, syntax_aware: bool) -> Self {
+        self.syntax_aware = syntax_aware;
+        self
+    }
+
+    /// Only count occurrences in files that plausibly import the defining module, as with
+    /// `--scope-by-imports`
+    pub fn with_scope_by_imports(mut self, scope_by_imports: bool) -> Self {
+        self.scope_by_imports = scope_by_imports;
+        self
+    }
+
+    /// Attribute occurrences of names defined in several scopes to the scope each refers to, as
+    /// with `--attribute-scopes`
+    pub fn with_attribute_scopes(mut self, attribute_scopes: bool) -> Self {
+        self.attribute_scopes = attribute_scopes;
+        self
+    }
+
+    /// Abort the analysis when `cancellation` is cancelled or its deadline passes, reporting
+    /// what was found so far as incomplete
+    ///
+    /// Each analysis needs its own token, since a cancelled token stays cancelled.
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+}
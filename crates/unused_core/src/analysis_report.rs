@@ -0,0 +1,120 @@
+use codebase_files::DiskFileSystem;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use token_analysis::{TokenUsage, UsageLikelihoodStatus};
+
+/// Findings from analyzing a project, ordered by token
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AnalysisReport {
+    /// Tokens likely to be unused, with the likelihoods asked for
+    pub findings: Vec<Finding>,
+    /// Tokens that would be reported but for an `unused:ignore` comment at their definition
+    pub suppressed: usize,
+    /// Tokens analyzed, including those not reported
+    pub tokens: usize,
+    /// Files searched for occurrences
+    pub files: usize,
+    /// Was the analysis cancelled before it finished?
+    ///
+    /// Incomplete reports leave out tokens not yet analyzed, and may report tokens whose
+    /// occurrences weren't all found.
+    pub incomplete: bool,
+}
+
+/// A token likely to be unused
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Finding {
+    /// Name of the token
+    pub token: String,
+    /// Where the token is defined, ordered by path and line
+    pub definitions: Vec<Definition>,
+    /// How likely the token is unused
+    pub likelihood: Likelihood,
+    /// How likely the token is unused, from 0 to 100
+    pub score: u8,
+    /// Why the token was reported
+    pub reason: String,
+    /// ID of the rule that classified the token, such as `UN001`
+    pub rule_id: String,
+    /// Number of occurrences found across the project, including definitions
+    pub occurrences: usize,
+}
+
+/// Where a token is defined
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Definition {
+    /// Path of the file, relative to the project's root
+    pub path: PathBuf,
+    /// Line of the definition, when it can be resolved
+    pub line: Option<usize>,
+    /// Kind of definition, such as `Class` or `Method`
+    pub kind: String,
+}
+
+/// How likely a token is unused
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Likelihood {
+    /// Scored 70 to 100, such as tokens occurring only at their definition
+    High,
+    /// Scored 40 to 69, such as tokens only used in tests
+    Medium,
+    /// Scored 0 to 39, such as tokens with wide usage
+    Low,
+}
+
+impl Display for Likelihood {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", UsageLikelihoodStatus::from(*self))
+    }
+}
+
+impl From<Likelihood> for UsageLikelihoodStatus {
+    fn from(likelihood: Likelihood) -> Self {
+        match likelihood {
+            Likelihood::High => UsageLikelihoodStatus::High,
+            Likelihood::Medium => UsageLikelihoodStatus::Medium,
+            Likelihood::Low => UsageLikelihoodStatus::Low,
+        }
+    }
+}
+
+impl From<&UsageLikelihoodStatus> for Likelihood {
+    fn from(status: &UsageLikelihoodStatus) -> Self {
+        match status {
+            UsageLikelihoodStatus::High => Likelihood::High,
+            UsageLikelihoodStatus::Medium => Likelihood::Medium,
+            UsageLikelihoodStatus::Low => Likelihood::Low,
+        }
+    }
+}
+
+impl Finding {
+    pub(crate) fn new(usage: &TokenUsage, root: &Path) -> Self {
+        let likelihood = &usage.usage_likelihood;
+        let token = &usage.result.token;
+
+        Finding {
+            token: token.token.clone(),
+            definitions: token
+                .ordered_definitions()
+                .into_iter()
+                .map(|d| Definition {
+                    path: d
+                        .file_path
+                        .strip_prefix(root)
+                        .unwrap_or(&d.file_path)
+                        .to_path_buf(),
+                    line: d.line(&DiskFileSystem),
+                    kind: format!("{:?}", d.kind),
+                })
+                .collect(),
+            likelihood: Likelihood::from(&likelihood.status),
+            score: likelihood.score,
+            reason: likelihood.reason.clone(),
+            rule_id: likelihood.rule.id().to_string(),
+            occurrences: usage.result.occurrences.values().sum(),
+        }
+    }
+}
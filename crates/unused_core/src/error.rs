@@ -0,0 +1,12 @@
+use thiserror::Error;
+
+/// Why a project couldn't be discovered
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The project's tags file is missing or can't be parsed
+    #[error("Unable to read tags: {0}")]
+    Tags(String),
+    /// The project's `.unused.yml` or `unused.toml` can't be read or parsed
+    #[error("Unable to load project settings: {0}")]
+    Settings(String),
+}
@@ -0,0 +1,36 @@
+#![deny(missing_docs)]
+
+//! `unused_core` is the supported entry point for analyzing projects with unused from Rust, such
+//! as in editor plugins and scripts.
+//!
+//! It wires reading tags, searching files, and classifying tokens together, and reports findings
+//! in its own types, so programs embedding unused don't depend on the crates doing that work,
+//! whose APIs change between releases:
+//!
+//! ```no_run
+//! use unused_core::{AnalysisOptions, Likelihood, Project};
+//!
+//! let project = Project::discover("path/to/project")?;
+//! let report = project.analyze(&AnalysisOptions::default().with_likelihoods(vec![
+//!     Likelihood::High,
+//!     Likelihood::Medium,
+//! ]));
+//!
+//! for finding in report.findings {
+//!     println!("{} ({}): {}", finding.token, finding.likelihood, finding.reason);
+//! }
+//! # Ok::<(), unused_core::Error>(())
+//! ```
+//!
+//! Long analyses can be aborted from another thread, or at a deadline, with a
+//! `CancellationToken`; the report is then flagged `incomplete`.
+mod analysis_options;
+mod analysis_report;
+mod error;
+mod project;
+
+pub use self::analysis_options::AnalysisOptions;
+pub use self::analysis_report::{AnalysisReport, Definition, Finding, Likelihood};
+pub use self::error::Error;
+pub use self::project::Project;
+pub use token_search::CancellationToken;
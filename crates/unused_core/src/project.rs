@@ -0,0 +1,194 @@
+use super::analysis_options::AnalysisOptions;
+use super::analysis_report::{AnalysisReport, Finding};
+use super::error::Error;
+use codebase_files::CodebaseFiles;
+use project_configuration::{ProjectConfiguration, ProjectSettings};
+use read_ctags::{CtagItem, TagsReader};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use token_analysis::{AnalysisFilter, TokenUsageResults, UsageLikelihoodStatus};
+use token_search::{InlineSuppressions, Token, TokenSearchConfig, TokenSearchResults};
+
+/// A project to analyze: its tags, and the settings checked into it
+#[derive(Clone, Debug)]
+pub struct Project {
+    root: PathBuf,
+    tags_path: PathBuf,
+    tags: HashSet<CtagItem>,
+    settings: ProjectSettings,
+}
+
+impl Project {
+    /// Read the project at `root`: its tags file, found where the `unused` command looks for it
+    /// (`.git/tags`, `tags`, or `tmp/tags`), and its `.unused.yml` or `unused.toml`, if any
+    pub fn discover<P: AsRef<Path>>(root: P) -> Result<Self, Error> {
+        let root = root.as_ref().to_path_buf();
+        let tags_file = TagsReader::for_root(&root)
+            .load()
+            .map_err(|e| Error::Tags(e.to_string()))?;
+        let settings = ProjectSettings::load(&root).map_err(Error::Settings)?;
+
+        Ok(Project {
+            root,
+            tags_path: tags_file.path,
+            tags: tags_file.tags,
+            settings,
+        })
+    }
+
+    /// The project's root, as passed to `discover`
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The tags file definitions were read from
+    pub fn tags_path(&self) -> &Path {
+        &self.tags_path
+    }
+
+    /// Number of definitions read from the tags file
+    pub fn definitions(&self) -> usize {
+        self.tags.len()
+    }
+
+    /// Search the project's files for each token defined in its tags, reporting those likely to
+    /// be unused
+    ///
+    /// Files are found the way the `unused` command finds them, honoring `.gitignore`. Tokens
+    /// ignored, allowed, or denied in the project's settings, or annotated with `unused:ignore`
+    /// comments, are treated as the command treats them. When the options' cancellation token is
+    /// cancelled, the analysis stops early and its report is flagged `incomplete`.
+    pub fn analyze(&self, options: &AnalysisOptions) -> AnalysisReport {
+        let definitions = self
+            .tags
+            .iter()
+            .map(|tag| CtagItem {
+                file_path: tag.absolute_path(&self.root).into(),
+                ..tag.clone()
+            })
+            .collect();
+        let files: Vec<PathBuf> = CodebaseFiles::from_root(&self.root)
+            .paths
+            .into_iter()
+            .map(|path| self.root.join(path))
+            .collect();
+        let files_count = files.len();
+        let config = TokenSearchConfig {
+            tokens: Token::from_tags(definitions),
+            display_progress: false,
+            syntax_aware: options.syntax_aware,
+            scope_by_imports: options.scope_by_imports,
+            attribute_scopes: options.attribute_scopes,
+            cancellation: options.cancellation.clone(),
+            ..TokenSearchConfig::for_files(files)
+        };
+        let search_results = TokenSearchResults::generate_with_config(&config);
+        let suppressions = InlineSuppressions::scan(&config, &search_results);
+        let results = TokenUsageResults::calculate(
+            &config,
+            search_results,
+            &ProjectConfiguration::default(),
+            &HashSet::new(),
+            &self.settings.token_policy,
+            &[],
+        );
+
+        let mut filter = AnalysisFilter::default();
+        filter.usage_likelihood_filter = options
+            .likelihoods
+            .iter()
+            .map(|likelihood| UsageLikelihoodStatus::from(*likelihood))
+            .collect();
+        filter.add_ignored(self.settings.ignore.assertions());
+        filter.set_inline_suppressions(suppressions);
+
+        AnalysisReport {
+            findings: results
+                .filter(&filter)
+                .into_iter()
+                .map(|usage| Finding::new(usage, &self.root))
+                .collect(),
+            suppressed: results
+                .value()
+                .iter()
+                .filter(|usage| filter.suppression(usage).is_some())
+                .count(),
+            tokens: results.len(),
+            files: files_count,
+            incomplete: config.cancellation.interrupted(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CancellationToken, Definition, Likelihood};
+    use std::env;
+    use std::fs;
+    use std::time::Duration;
+
+    #[test]
+    fn discovers_and_analyzes_projects() {
+        let root = env::temp_dir().join("unused_core_analyzes");
+        fs::remove_dir_all(&root).ok();
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(
+            root.join("app/person.rb"),
+            "class Person\n  def full_name\n  end\n\n  def legacy_name\n  end\n\n  def nickname # unused:ignore\n  end\nend\n\nPerson.new\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join(".git/tags"),
+            "Person\tapp/person.rb\t/^class Person$/;\"\tc\nfull_name\tapp/person.rb\t/^  def full_name$/;\"\tf\tclass:Person\nlegacy_name\tapp/person.rb\t/^  def legacy_name$/;\"\tf\tclass:Person\nnickname\tapp/person.rb\t8;\"\tf\tclass:Person\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join(".unused.yml"),
+            "ignore:\n  tokens:\n    - legacy_name\n",
+        )
+        .unwrap();
+
+        let project = Project::discover(&root).unwrap();
+        let report = project.analyze(&AnalysisOptions::default());
+        let all = project.analyze(
+            &AnalysisOptions::default().with_likelihoods(vec![Likelihood::High, Likelihood::Low]),
+        );
+        let cancelled = project.analyze(
+            &AnalysisOptions::default()
+                .with_cancellation(CancellationToken::with_timeout(Duration::ZERO)),
+        );
+        fs::remove_dir_all(&root).ok();
+
+        assert_eq!(project.definitions(), 4);
+        assert_eq!(report.tokens, 4);
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.suppressed, 1);
+        assert!(!report.incomplete);
+        assert!(cancelled.incomplete);
+        assert!(cancelled.findings.is_empty());
+        assert_eq!(report.findings[0].token, "full_name");
+        assert_eq!(report.findings[0].likelihood, Likelihood::High);
+        assert_eq!(report.findings[0].rule_id, "UN001");
+        assert_eq!(
+            report.findings[0].definitions,
+            vec![Definition {
+                path: PathBuf::from("app/person.rb"),
+                line: Some(2),
+                kind: String::from("Method"),
+            }]
+        );
+        assert_eq!(
+            all.findings
+                .iter()
+                .map(|f| f.token.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Person", "full_name"]
+        );
+        assert!(matches!(
+            Project::discover(root.join("missing")),
+            Err(Error::Tags(_))
+        ));
+    }
+}
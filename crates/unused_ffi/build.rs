@@ -0,0 +1,16 @@
+use std::env;
+use std::path::PathBuf;
+
+// Regenerate the C header from the exported functions and types, so it can't fall out of date
+fn main() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+
+    match cbindgen::generate(&crate_dir) {
+        Ok(bindings) => {
+            bindings.write_to_file(crate_dir.join("include/unused.h"));
+        }
+        Err(e) => println!("cargo:warning=Unable to generate include/unused.h: {}", e),
+    }
+}
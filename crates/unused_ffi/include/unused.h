@@ -0,0 +1,107 @@
+#ifndef UNUSED_H
+#define UNUSED_H
+
+/* Generated by cbindgen from crates/unused_ffi; do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Findings from `unused_analyze`, iterated with `unused_analysis_count` and
+// `unused_analysis_get`
+typedef struct UnusedAnalysis UnusedAnalysis;
+
+// Tags parsed with `unused_parse_tags`
+typedef struct UnusedTags UnusedTags;
+
+// A token likely to be unused
+typedef struct UnusedFinding {
+  // Name of the token
+  const char *token;
+  // Path of the token's first definition, relative to the analyzed root
+  const char *path;
+  // Line of the token's first definition, or 0 when it can't be resolved
+  size_t line;
+  // How likely the token is unused: `high`, `medium`, or `low`
+  const char *likelihood;
+  // How likely the token is unused, from 0 to 100
+  uint8_t score;
+  // Why the token was reported
+  const char *reason;
+  // ID of the rule that classified the token, such as `UN001`
+  const char *rule_id;
+} UnusedFinding;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Parse the contents of a tags file (tab-separated or JSON)
+//
+// Returns NULL when `contents` can't be parsed. Free the result with `unused_tags_free`.
+//
+// # Safety
+//
+// `contents` must be a valid, NUL-terminated string.
+struct UnusedTags *unused_parse_tags(const char *contents);
+
+// Number of tags parsed
+//
+// # Safety
+//
+// `tags` must be NULL or returned by `unused_parse_tags` and not yet freed.
+size_t unused_tags_count(const struct UnusedTags *tags);
+
+// Free tags returned by `unused_parse_tags`
+//
+// # Safety
+//
+// `tags` must be NULL or returned by `unused_parse_tags` and not yet freed.
+void unused_tags_free(struct UnusedTags *tags);
+
+// Search the files under `root` for each token in `tags`, reporting those likely to be unused
+//
+// Paths in `tags` are relative to `root`, as when ctags is run there. Files are found the way
+// the `unused` command finds them, honoring `.gitignore`, and analyzed with the default
+// configuration. Free the result with `unused_analysis_free`.
+//
+// # Safety
+//
+// `tags` must be returned by `unused_parse_tags` and not yet freed, and `root` must be a valid,
+// NUL-terminated string.
+struct UnusedAnalysis *unused_analyze(const struct UnusedTags *tags, const char *root);
+
+// Number of findings
+//
+// # Safety
+//
+// `analysis` must be NULL or returned by `unused_analyze` and not yet freed.
+size_t unused_analysis_count(const struct UnusedAnalysis *analysis);
+
+// The finding at `index`, ordered by token, or NULL past the last finding
+//
+// # Safety
+//
+// `analysis` must be NULL or returned by `unused_analyze` and not yet freed.
+const struct UnusedFinding *unused_analysis_get(const struct UnusedAnalysis *analysis,
+                                                size_t index);
+
+// Free findings returned by `unused_analyze`, including their strings
+//
+// # Safety
+//
+// `analysis` must be NULL or returned by `unused_analyze` and not yet freed.
+void unused_analysis_free(struct UnusedAnalysis *analysis);
+
+// Why the last call on this thread returned NULL, or NULL if none has failed
+//
+// The string is valid until the next failing call on this thread.
+const char *unused_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* UNUSED_H */
@@ -0,0 +1,295 @@
+//! C bindings for embedding unused, such as in editor plugins written in C or C++
+//!
+//! The header is generated at `include/unused.h`. Strings passed in are NUL-terminated UTF-8;
+//! strings handed out belong to the object they came from and stay valid until it's freed.
+//! Functions returning a pointer return NULL on failure, with the reason available from
+//! `unused_last_error`.
+
+use codebase_files::DiskFileSystem;
+use read_ctags::CtagItem;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use token_analysis::{AnalysisFilter, TokenUsage, TokenUsageResults};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Tags parsed with `unused_parse_tags`
+pub struct UnusedTags {
+    tags: HashSet<CtagItem>,
+}
+
+/// Findings from `unused_analyze`, iterated with `unused_analysis_count` and
+/// `unused_analysis_get`
+pub struct UnusedAnalysis {
+    findings: Vec<UnusedFinding>,
+    // Owns the strings each finding points to
+    strings: Vec<CString>,
+}
+
+/// A token likely to be unused
+#[repr(C)]
+pub struct UnusedFinding {
+    /// Name of the token
+    pub token: *const c_char,
+    /// Path of the token's first definition, relative to the analyzed root
+    pub path: *const c_char,
+    /// Line of the token's first definition, or 0 when it can't be resolved
+    pub line: usize,
+    /// How likely the token is unused: `high`, `medium`, or `low`
+    pub likelihood: *const c_char,
+    /// How likely the token is unused, from 0 to 100
+    pub score: u8,
+    /// Why the token was reported
+    pub reason: *const c_char,
+    /// ID of the rule that classified the token, such as `UN001`
+    pub rule_id: *const c_char,
+}
+
+/// Parse the contents of a tags file (tab-separated or JSON)
+///
+/// Returns NULL when `contents` can't be parsed. Free the result with `unused_tags_free`.
+///
+/// # Safety
+///
+/// `contents` must be a valid, NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn unused_parse_tags(contents: *const c_char) -> *mut UnusedTags {
+    let parsed = string(contents).and_then(|contents| {
+        CtagItem::parse(PathBuf::from("tags"), &contents).map_err(|e| e.to_string())
+    });
+
+    match parsed {
+        Ok(tags_file) => Box::into_raw(Box::new(UnusedTags {
+            tags: tags_file.tags,
+        })),
+        Err(e) => fail(e),
+    }
+}
+
+/// Number of tags parsed
+///
+/// # Safety
+///
+/// `tags` must be NULL or returned by `unused_parse_tags` and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn unused_tags_count(tags: *const UnusedTags) -> usize {
+    tags.as_ref().map_or(0, |tags| tags.tags.len())
+}
+
+/// Free tags returned by `unused_parse_tags`
+///
+/// # Safety
+///
+/// `tags` must be NULL or returned by `unused_parse_tags` and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn unused_tags_free(tags: *mut UnusedTags) {
+    if !tags.is_null() {
+        drop(Box::from_raw(tags));
+    }
+}
+
+/// Search the files under `root` for each token in `tags`, reporting those likely to be unused
+///
+/// Paths in `tags` are relative to `root`, as when ctags is run there. Files are found the way
+/// the `unused` command finds them, honoring `.gitignore`, and analyzed with the default
+/// configuration. Free the result with `unused_analysis_free`.
+///
+/// # Safety
+///
+/// `tags` must be returned by `unused_parse_tags` and not yet freed, and `root` must be a valid,
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn unused_analyze(
+    tags: *const UnusedTags,
+    root: *const c_char,
+) -> *mut UnusedAnalysis {
+    let tags = match tags.as_ref() {
+        Some(tags) => tags,
+        None => return fail(String::from("No tags provided")),
+    };
+
+    match string(root) {
+        Ok(root) => Box::into_raw(Box::new(analyze(&tags.tags, Path::new(&root)))),
+        Err(e) => fail(e),
+    }
+}
+
+/// Number of findings
+///
+/// # Safety
+///
+/// `analysis` must be NULL or returned by `unused_analyze` and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn unused_analysis_count(analysis: *const UnusedAnalysis) -> usize {
+    analysis
+        .as_ref()
+        .map_or(0, |analysis| analysis.findings.len())
+}
+
+/// The finding at `index`, ordered by token, or NULL past the last finding
+///
+/// # Safety
+///
+/// `analysis` must be NULL or returned by `unused_analyze` and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn unused_analysis_get(
+    analysis: *const UnusedAnalysis,
+    index: usize,
+) -> *const UnusedFinding {
+    analysis
+        .as_ref()
+        .and_then(|analysis| analysis.findings.get(index))
+        .map_or(ptr::null(), |finding| finding as *const UnusedFinding)
+}
+
+/// Free findings returned by `unused_analyze`, including their strings
+///
+/// # Safety
+///
+/// `analysis` must be NULL or returned by `unused_analyze` and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn unused_analysis_free(analysis: *mut UnusedAnalysis) {
+    if !analysis.is_null() {
+        drop(Box::from_raw(analysis));
+    }
+}
+
+/// Why the last call on this thread returned NULL, or NULL if none has failed
+///
+/// The string is valid until the next failing call on this thread.
+#[no_mangle]
+pub extern "C" fn unused_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| {
+        error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |error| error.as_ptr())
+    })
+}
+
+fn analyze(tags: &HashSet<CtagItem>, root: &Path) -> UnusedAnalysis {
+    let usages = TokenUsageResults::for_root(tags, root);
+
+    let mut analysis = UnusedAnalysis {
+        findings: vec![],
+        strings: vec![],
+    };
+    for usage in usages.filter(&AnalysisFilter::default()) {
+        let finding = finding(usage, root, &mut analysis.strings);
+        analysis.findings.push(finding);
+    }
+    analysis
+}
+
+fn finding(usage: &TokenUsage, root: &Path, strings: &mut Vec<CString>) -> UnusedFinding {
+    let likelihood = &usage.usage_likelihood;
+    let definition = usage
+        .result
+        .token
+        .definitions
+        .iter()
+        .min_by_key(|d| (&d.file_path, d.line(&DiskFileSystem)));
+    let path = definition
+        .map(|d| d.file_path.strip_prefix(root).unwrap_or(&d.file_path))
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut owned = |value: &str| {
+        let value = CString::new(value.replace('\0', "")).unwrap_or_default();
+        let pointer = value.as_ptr();
+        strings.push(value);
+        pointer
+    };
+
+    UnusedFinding {
+        token: owned(&usage.result.token.token),
+        path: owned(&path),
+        line: definition
+            .and_then(|d| d.line(&DiskFileSystem))
+            .unwrap_or(0),
+        likelihood: owned(&likelihood.status.to_string()),
+        score: likelihood.score,
+        reason: owned(&likelihood.reason),
+        rule_id: owned(likelihood.rule.id()),
+    }
+}
+
+unsafe fn string(value: *const c_char) -> Result<String, String> {
+    if value.is_null() {
+        return Err(String::from("Expected a string, got NULL"));
+    }
+
+    CStr::from_ptr(value)
+        .to_str()
+        .map(String::from)
+        .map_err(|e| format!("Expected UTF-8: {}", e))
+}
+
+fn fail<T>(error: String) -> *mut T {
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(error).ok());
+    ptr::null_mut()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    unsafe fn text(value: *const c_char) -> String {
+        CStr::from_ptr(value).to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn analyzes_tags_through_the_c_api() {
+        let root = env::temp_dir().join("unused_ffi_analyzes");
+        fs::remove_dir_all(&root).ok();
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::write(
+            root.join("app/person.rb"),
+            "class Person\n  def full_name\n  end\nend\n\nPerson.new\n",
+        )
+        .unwrap();
+        let tags = CString::new(
+            "Person\tapp/person.rb\t/^class Person$/;\"\tc\nfull_name\tapp/person.rb\t/^  def full_name$/;\"\tf\n",
+        )
+        .unwrap();
+        let root_path = CString::new(root.to_string_lossy().to_string()).unwrap();
+
+        unsafe {
+            let parsed = unused_parse_tags(tags.as_ptr());
+            let analysis = unused_analyze(parsed, root_path.as_ptr());
+            let finding = &*unused_analysis_get(analysis, 0);
+
+            assert_eq!(unused_tags_count(parsed), 2);
+            assert_eq!(unused_analysis_count(analysis), 1);
+            assert_eq!(text(finding.token), "full_name");
+            assert_eq!(text(finding.path), "app/person.rb");
+            assert_eq!(finding.line, 2);
+            assert_eq!(text(finding.likelihood), "high");
+            assert_eq!(text(finding.rule_id), "UN001");
+            assert!(unused_analysis_get(analysis, 1).is_null());
+
+            unused_analysis_free(analysis);
+            unused_tags_free(parsed);
+        }
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn reports_why_calls_failed() {
+        let invalid = CString::new("{\"_type\": \"tag\"}\n").unwrap();
+
+        unsafe {
+            assert!(unused_parse_tags(invalid.as_ptr()).is_null());
+            assert_eq!(text(unused_last_error()), "Invalid JSON tag on line 1");
+            assert!(unused_analyze(ptr::null(), ptr::null()).is_null());
+            assert_eq!(text(unused_last_error()), "No tags provided");
+        }
+    }
+}
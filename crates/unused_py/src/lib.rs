@@ -0,0 +1,235 @@
+//! Python bindings for scripting triage and reporting with unused, such as from notebooks
+//!
+//! Built with maturin (`maturin develop` from this directory) as the `unused` module:
+//!
+//! ```python
+//! import unused
+//!
+//! tags = unused.parse_tags("tags")
+//! findings = unused.analyze(".")
+//! ```
+
+use codebase_files::{text, DiskFileSystem};
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use read_ctags::{CtagItem as Tag, TagsReader};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use token_analysis::{AnalysisFilter, TokenUsage, TokenUsageResults};
+
+/// A definition read from a tags file
+#[pyclass(module = "unused", frozen, get_all)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CtagItem {
+    /// Name of the tag
+    pub name: String,
+    /// Path of the file defining the tag, as written in the tags file
+    pub path: String,
+    /// Tag address: a line number or a search pattern
+    pub address: String,
+    /// Line of the definition, when the address resolves to one
+    pub line: Option<usize>,
+    /// Kind of definition, such as `Class` or `Method`
+    pub kind: String,
+    /// Language, based on the tags file or the file's extension
+    pub language: Option<String>,
+    /// Remaining fields from the tags file, such as `class` or `signature`
+    pub fields: BTreeMap<String, String>,
+}
+
+#[pymethods]
+impl CtagItem {
+    fn __repr__(&self) -> String {
+        format!(
+            "CtagItem(name={:?}, path={:?}, kind={:?})",
+            self.name, self.path, self.kind
+        )
+    }
+}
+
+impl CtagItem {
+    fn new(tag: &Tag, root: &Path) -> Self {
+        let located = Tag {
+            file_path: tag.absolute_path(root).into(),
+            ..tag.clone()
+        };
+
+        CtagItem {
+            name: tag.name.clone(),
+            path: tag.raw_path.to_string(),
+            address: tag.address.clone(),
+            line: located.line(&DiskFileSystem),
+            kind: format!("{:?}", tag.kind),
+            language: tag.language.map(|l| l.to_string()),
+            fields: tag
+                .tags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// A token likely to be unused
+#[pyclass(module = "unused", frozen, get_all)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding {
+    /// Name of the token
+    pub token: String,
+    /// Path of the token's first definition, relative to the analyzed project
+    pub path: String,
+    /// Line of the token's first definition, when it can be resolved
+    pub line: Option<usize>,
+    /// Kinds of the token's definitions, such as `Class` or `Method`
+    pub kinds: Vec<String>,
+    /// How likely the token is unused: `high`, `medium`, or `low`
+    pub likelihood: String,
+    /// How likely the token is unused, from 0 to 100
+    pub score: u8,
+    /// Why the token was reported
+    pub reason: String,
+    /// ID of the rule that classified the token, such as `UN001`
+    pub rule_id: String,
+    /// Number of occurrences found across the project, including definitions
+    pub occurrences: usize,
+}
+
+#[pymethods]
+impl Finding {
+    fn __repr__(&self) -> String {
+        format!(
+            "Finding(token={:?}, path={:?}, likelihood={:?})",
+            self.token, self.path, self.likelihood
+        )
+    }
+}
+
+impl Finding {
+    fn new(usage: &TokenUsage, root: &Path) -> Self {
+        let likelihood = &usage.usage_likelihood;
+        let token = &usage.result.token;
+        let definition = token
+            .definitions
+            .iter()
+            .min_by_key(|d| (&d.file_path, d.line(&DiskFileSystem)));
+        let mut kinds: Vec<_> = token
+            .definitions
+            .iter()
+            .map(|d| format!("{:?}", d.kind))
+            .collect();
+        kinds.sort();
+        kinds.dedup();
+
+        Finding {
+            token: token.token.clone(),
+            path: definition
+                .map(|d| d.file_path.strip_prefix(root).unwrap_or(&d.file_path))
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            line: definition.and_then(|d| d.line(&DiskFileSystem)),
+            kinds,
+            likelihood: likelihood.status.to_string(),
+            score: likelihood.score,
+            reason: likelihood.reason.clone(),
+            rule_id: likelihood.rule.id().to_string(),
+            occurrences: usage.result.occurrences.values().sum(),
+        }
+    }
+}
+
+/// Parse the tags file at `path` (tab-separated or JSON), ordered by path and line
+///
+/// Paths in the tags file are relative to `root`, the current directory unless given, which is
+/// where ctags ran; it's used to resolve the line of tags addressed by search patterns.
+#[pyfunction]
+#[pyo3(signature = (path, root = None))]
+fn parse_tags(path: PathBuf, root: Option<PathBuf>) -> PyResult<Vec<CtagItem>> {
+    let root = root.unwrap_or_else(|| PathBuf::from("."));
+    let contents = text::read(&path).map_err(|e| PyOSError::new_err(e.to_string()))?;
+    let tags_file =
+        Tag::parse(path, &contents).map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+    let mut items: Vec<CtagItem> = tags_file
+        .tags
+        .iter()
+        .map(|tag| CtagItem::new(tag, &root))
+        .collect();
+    items.sort_by(|a, b| (&a.path, a.line, &a.name).cmp(&(&b.path, b.line, &b.name)));
+
+    Ok(items)
+}
+
+/// Analyze the project at `project`, returning the tokens likely to be unused, ordered by token
+///
+/// The project's tags file is found where the `unused` command looks for it (`.git/tags`,
+/// `tags`, or `tmp/tags`), and the project is analyzed with the default configuration.
+#[pyfunction]
+fn analyze(py: Python, project: PathBuf) -> PyResult<Vec<Finding>> {
+    py.allow_threads(|| {
+        let tags_file = TagsReader::for_root(&project)
+            .load()
+            .map_err(|e| PyOSError::new_err(e.to_string()))?;
+
+        Ok(findings(&tags_file.tags, &project))
+    })
+}
+
+fn findings(tags: &HashSet<Tag>, root: &Path) -> Vec<Finding> {
+    TokenUsageResults::for_root(tags, root)
+        .filter(&AnalysisFilter::default())
+        .iter()
+        .map(|usage| Finding::new(usage, root))
+        .collect()
+}
+
+#[pymodule]
+fn unused(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<CtagItem>()?;
+    m.add_class::<Finding>()?;
+    m.add_function(wrap_pyfunction!(parse_tags, m)?)?;
+    m.add_function(wrap_pyfunction!(analyze, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn parses_and_analyzes_projects() {
+        let root = env::temp_dir().join("unused_py_analyzes");
+        fs::remove_dir_all(&root).ok();
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(
+            root.join("app/person.rb"),
+            "class Person\n  def full_name\n  end\nend\n\nPerson.new\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join(".git/tags"),
+            "Person\tapp/person.rb\t/^class Person$/;\"\tc\nfull_name\tapp/person.rb\t/^  def full_name$/;\"\tf\tclass:Person\n",
+        )
+        .unwrap();
+
+        let tags = parse_tags(root.join(".git/tags"), Some(root.clone())).unwrap();
+        let tags_file = TagsReader::for_root(&root).load().unwrap();
+        let findings = findings(&tags_file.tags, &root);
+        fs::remove_dir_all(&root).ok();
+
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].name, "Person");
+        assert_eq!(tags[0].kind, "Class");
+        assert_eq!(tags[0].line, Some(1));
+        assert_eq!(tags[1].fields.get("class"), Some(&String::from("Person")));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].token, "full_name");
+        assert_eq!(findings[0].path, "app/person.rb");
+        assert_eq!(findings[0].line, Some(2));
+        assert_eq!(findings[0].kinds, vec!["Method"]);
+        assert_eq!(findings[0].likelihood, "high");
+        assert_eq!(findings[0].rule_id, "UN001");
+    }
+}
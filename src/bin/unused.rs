@@ -1,8 +1,6 @@
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
-use cli;
-
 fn main() {
-    cli::run();
+    std::process::exit(cli::run().code());
 }